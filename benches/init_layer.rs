@@ -0,0 +1,134 @@
+//! Compares initializing an entire layer tile-by-tile using `TilePlacer::add_to_layer` against
+//! the bulk `TilePlacer::init_layer_from_grid` fast path
+
+use bevy::asset::AssetPlugin;
+use bevy::ecs::system::SystemState;
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy_ecs_tilemap::prelude::*;
+use bevy_tileset_map::prelude::*;
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+
+/// The ID of the generated tileset
+const TILESET_ID: TilesetId = 1;
+
+/// Create an app containing a single tileset made up of one standard tile
+fn setup_app() -> App {
+	let mut app = App::new();
+	app.add_plugins(MinimalPlugins)
+		.add_plugin(AssetPlugin)
+		.add_asset::<Image>()
+		.add_asset::<TextureAtlas>()
+		.add_asset::<Mesh>()
+		.add_plugin(TilesetPlugin::default())
+		.add_plugin(TilesetMapPlugin::default());
+
+	let image = Image::new_fill(
+		Extent3d {
+			width: 16,
+			height: 16,
+			depth_or_array_layers: 1,
+		},
+		TextureDimension::D2,
+		&[255, 255, 255, 255],
+		TextureFormat::Rgba8UnormSrgb,
+	);
+	let handle = app.world.resource_mut::<Assets<Image>>().add(image);
+
+	let mut builder = TilesetBuilder::default();
+	let tileset = app
+		.world
+		.resource_scope(|world, mut images: Mut<Assets<Image>>| {
+			builder
+				.add_tile(TileHandle::new_standard("Tile", handle), 0, &*images)
+				.unwrap();
+			builder
+				.build("Bench Tileset", TILESET_ID, &mut *images)
+				.unwrap()
+				.into_asset(&mut world.resource_mut::<Assets<TextureAtlas>>())
+		});
+	app.world.resource_mut::<Assets<Tileset>>().add(tileset);
+
+	// Let the tileset be registered
+	app.update();
+	app
+}
+
+/// Create the settings of a square layer with the given size (in tiles)
+fn layer_settings(size: u32) -> LayerSettings {
+	LayerSettings::new(
+		MapSize(size / 32, size / 32),
+		ChunkSize(32, 32),
+		TileSize(16.0, 16.0),
+		TextureSize(16.0, 16.0),
+	)
+}
+
+fn init_layer(c: &mut Criterion) {
+	let tile_id = TileId::new(0, TILESET_ID);
+
+	let mut group = c.benchmark_group("init_layer");
+	group.sample_size(10);
+
+	for size in [256u32, 1024] {
+		let grid = vec![vec![Some(tile_id); size as usize]; size as usize];
+
+		group.bench_with_input(BenchmarkId::new("add_to_layer", size), &grid, |b, grid| {
+			b.iter_batched(
+				setup_app,
+				|mut app| {
+					let mut state = SystemState::<(Commands, TilePlacer)>::new(&mut app.world);
+					let (mut commands, mut placer) = state.get_mut(&mut app.world);
+					let (mut builder, _) = LayerBuilder::<TileBundle>::new(
+						&mut commands,
+						layer_settings(size),
+						0u16,
+						0u16,
+					);
+					for (row, tiles) in grid.iter().enumerate() {
+						let y = size - 1 - row as u32;
+						for (x, tile_id) in tiles.iter().enumerate() {
+							if let Some(tile_id) = tile_id {
+								placer
+									.add_to_layer(*tile_id, TilePos(x as u32, y), &mut builder)
+									.unwrap();
+							}
+						}
+					}
+					state.apply(&mut app.world);
+					app
+				},
+				BatchSize::LargeInput,
+			)
+		});
+
+		group.bench_with_input(
+			BenchmarkId::new("init_layer_from_grid", size),
+			&grid,
+			|b, grid| {
+				b.iter_batched(
+					setup_app,
+					|mut app| {
+						let mut state = SystemState::<(Commands, TilePlacer)>::new(&mut app.world);
+						let (mut commands, mut placer) = state.get_mut(&mut app.world);
+						let (mut builder, _) = LayerBuilder::<TileBundle>::new(
+							&mut commands,
+							layer_settings(size),
+							0u16,
+							0u16,
+						);
+						placer.init_layer_from_grid(grid, &mut builder).unwrap();
+						state.apply(&mut app.world);
+						app
+					},
+					BatchSize::LargeInput,
+				)
+			},
+		);
+	}
+
+	group.finish();
+}
+
+criterion_group!(benches, init_layer);
+criterion_main!(benches);
@@ -30,7 +30,7 @@ fn main() {
 		// Adds the `bevy_tileset` plugin
 		.add_plugin(TilesetPlugin::default())
 		// Adds this crate's plugn
-		.add_plugin(TilesetMapPlugin)
+		.add_plugin(TilesetMapPlugin::default())
 		// /== Required === //
 		// === Exmaple-Specific === //
 		.init_resource::<MyTileset>()
@@ -216,7 +216,7 @@ fn on_keypress(
 	} else if keys.just_pressed(KeyCode::Key3) {
 		build_mode.active_layer = 2u16;
 	} else if keys.just_pressed(KeyCode::Comma) {
-		saved.map = serializer.save_maps();
+		saved.map = Some(serializer.save_maps().0);
 		println!(
 			"{}",
 			serde_json::to_string(&saved.map.as_ref().unwrap()).unwrap()
@@ -28,7 +28,7 @@ fn main() {
 		// Adds the `bevy_tileset` plugin
 		.add_plugin(TilesetPlugin::default())
 		// Adds this crate's plugn
-		.add_plugin(TilesetMapPlugin)
+		.add_plugin(TilesetMapPlugin::default())
 		// /== Required === //
 		// === Exmaple-Specific === //
 		.add_state(MapState::LoadingTileset)
@@ -0,0 +1,181 @@
+//! Per-layer configuration of which chunks are considered "active"
+//!
+//! Crate subsystems (such as auto tiling) only process tiles within active chunks. A chunk is
+//! active if it is visible to any 2D camera or lies within the layer's configured margin (given in
+//! chunks) around the visible area. This allows gameplay-critical layers to simulate further out
+//! than decorative ones.
+//!
+//! By default, layers have no margin configured and are therefore always entirely active.
+//! Work on inactive tiles is deferred until their chunk becomes active.
+
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_ecs_tilemap::TilePos;
+
+use crate::coord::TileCoord;
+use crate::registry::TilesetMapRegistry;
+
+/// A resource configuring how many chunks beyond the visible area remain active, per layer
+///
+/// # Examples
+///
+/// ```
+/// # use bevy_tileset_map::prelude::ActivityConfig;
+/// let mut config = ActivityConfig::default();
+/// // Only keep one chunk around the screen active for all layers...
+/// config.set_default_margin(Some(1));
+/// // ...except for the gameplay layer
+/// config.set_margin(0, 1, Some(4));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ActivityConfig {
+	default_margin: Option<u32>,
+	layers: HashMap<(u16, u16), Option<u32>>,
+}
+
+/// A resource containing the active chunk area of every layer with a configured margin
+///
+/// This resource is added and kept up to date automatically by the
+/// [`TilesetMapPlugin`](crate::prelude::TilesetMapPlugin).
+#[derive(Debug, Default)]
+pub struct ActiveChunks {
+	layers: HashMap<(u16, u16), ActiveLayer>,
+}
+
+/// The active area of a single layer
+#[derive(Debug, Clone, Default)]
+pub struct ActiveLayer {
+	chunk_size: UVec2,
+	/// The active chunk rectangles (one per camera), given as inclusive min/max chunk positions
+	rects: Vec<(IVec2, IVec2)>,
+}
+
+impl ActivityConfig {
+	/// The margin used by layers without their own margin
+	///
+	/// A margin of `None` means the entire layer is always active.
+	pub fn default_margin(&self) -> Option<u32> {
+		self.default_margin
+	}
+
+	/// Set the margin used by layers without their own margin
+	pub fn set_default_margin(&mut self, margin: Option<u32>) {
+		self.default_margin = margin;
+	}
+
+	/// Set the margin (in chunks) of the given layer
+	///
+	/// A margin of `None` means the entire layer is always active.
+	pub fn set_margin(&mut self, map_id: u16, layer_id: u16, margin: Option<u32>) {
+		self.layers.insert((map_id, layer_id), margin);
+	}
+
+	/// Remove the margin of the given layer, reverting it to the default margin
+	pub fn clear_margin(&mut self, map_id: u16, layer_id: u16) {
+		self.layers.remove(&(map_id, layer_id));
+	}
+
+	/// Get the margin (in chunks) of the given layer
+	pub fn margin(&self, map_id: u16, layer_id: u16) -> Option<u32> {
+		self.layers
+			.get(&(map_id, layer_id))
+			.copied()
+			.unwrap_or(self.default_margin)
+	}
+}
+
+impl ActiveChunks {
+	/// Returns true if the tile at the given coordinate lies within an active chunk
+	pub fn is_active(&self, coord: &TileCoord) -> bool {
+		self.is_tile_active(coord.map_id, coord.layer_id, coord.pos)
+	}
+
+	/// Returns true if the tile at the given position lies within an active chunk of the given layer
+	pub fn is_tile_active(&self, map_id: u16, layer_id: u16, pos: TilePos) -> bool {
+		self.layers
+			.get(&(map_id, layer_id))
+			.map(|layer| layer.is_tile_active(pos))
+			.unwrap_or(true)
+	}
+
+	/// Get the active area of the given layer
+	///
+	/// Returns `None` if the entire layer is active.
+	pub fn layer(&self, map_id: u16, layer_id: u16) -> Option<&ActiveLayer> {
+		self.layers.get(&(map_id, layer_id))
+	}
+}
+
+impl ActiveLayer {
+	/// Returns true if the given chunk position is active
+	pub fn is_chunk_active(&self, chunk: IVec2) -> bool {
+		self.rects.iter().any(|(min, max)| {
+			chunk.x >= min.x && chunk.y >= min.y && chunk.x <= max.x && chunk.y <= max.y
+		})
+	}
+
+	/// Returns true if the chunk containing the given tile position is active
+	pub fn is_tile_active(&self, pos: TilePos) -> bool {
+		let chunk = IVec2::new(
+			(pos.0 / self.chunk_size.x.max(1)) as i32,
+			(pos.1 / self.chunk_size.y.max(1)) as i32,
+		);
+		self.is_chunk_active(chunk)
+	}
+
+	/// The active chunk rectangles, given as inclusive min/max chunk positions
+	pub fn rects(&self) -> &[(IVec2, IVec2)] {
+		&self.rects
+	}
+}
+
+/// __\[SYSTEM\]__ Recomputes the [`ActiveChunks`] of every registered layer with a configured margin
+pub(crate) fn update_active_chunks(
+	config: Res<ActivityConfig>,
+	registry: Res<TilesetMapRegistry>,
+	mut active: ResMut<ActiveChunks>,
+	cameras: Query<(&GlobalTransform, &OrthographicProjection), With<Camera>>,
+	transforms: Query<&GlobalTransform>,
+) {
+	active.layers.clear();
+	if config.default_margin.is_none() && config.layers.values().all(Option::is_none) {
+		return;
+	}
+
+	for (map_id, layer_id, info) in registry.layers() {
+		let margin = if let Some(margin) = config.margin(map_id, layer_id) {
+			margin as i32
+		} else {
+			continue;
+		};
+
+		let origin = transforms
+			.get(info.entity)
+			.map(|transform| transform.translation.truncate())
+			.unwrap_or_default();
+		let chunk_size = UVec2::new(info.settings.chunk_size.0, info.settings.chunk_size.1);
+		let chunk_extent = info.settings.grid_size * chunk_size.as_vec2();
+		if chunk_extent.x <= 0.0 || chunk_extent.y <= 0.0 {
+			continue;
+		}
+
+		let rects = cameras
+			.iter()
+			.map(|(transform, projection)| {
+				let center = transform.translation.truncate();
+				let scale = projection.scale * transform.scale.truncate();
+				let min = center + Vec2::new(projection.left, projection.bottom) * scale;
+				let max = center + Vec2::new(projection.right, projection.top) * scale;
+
+				let margin = IVec2::splat(margin);
+				let min_chunk = ((min - origin) / chunk_extent).floor().as_ivec2() - margin;
+				let max_chunk = ((max - origin) / chunk_extent).floor().as_ivec2() + margin;
+				(min_chunk, max_chunk)
+			})
+			.collect();
+
+		active
+			.layers
+			.insert((map_id, layer_id), ActiveLayer { chunk_size, rects });
+	}
+}
@@ -0,0 +1,229 @@
+//! Sampling the tiles around a position to drive audio ambience
+//!
+//! Tiles are classified by the [`AMBIENCE_METADATA_KEY`] key of their [`TileMetadata`]
+//! (e.g. `"cave"`, `"water"`, or `"open"`). Every entity with an [`AmbienceListener`] has the
+//! tiles within its radius sampled each frame, with the result stored in the [`AmbienceSamples`]
+//! resource. Whenever the dominant ambience around a listener changes, an [`AmbienceChanged`]
+//! event is sent.
+
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_ecs_tilemap::{TileParent, TilePos};
+use bevy_tileset::prelude::TilesetParent;
+
+use crate::coord::TileCoord;
+use crate::metadata::TileMetadata;
+use crate::registry::TilesetMapRegistry;
+
+/// The metadata key used to define a tile's ambience
+pub const AMBIENCE_METADATA_KEY: &str = "ambience";
+
+/// A component marking an entity (such as the player or camera) whose surroundings should be sampled
+#[derive(Component, Debug, Copy, Clone, PartialEq)]
+pub struct AmbienceListener {
+	/// The map to sample
+	pub map_id: u16,
+	/// The radius (in tiles) to sample around the listener
+	pub radius: u32,
+}
+
+/// The classification of the tiles around an [`AmbienceListener`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AmbienceSample {
+	/// The number of tiles sampled for each ambience
+	pub counts: HashMap<String, usize>,
+	/// The total number of tiles sampled (including those without an ambience)
+	pub total: usize,
+}
+
+/// A resource containing the latest [`AmbienceSample`] of every [`AmbienceListener`]
+///
+/// This resource is added and kept up to date automatically by the
+/// [`TilesetMapPlugin`](crate::prelude::TilesetMapPlugin).
+#[derive(Debug, Default)]
+pub struct AmbienceSamples {
+	samples: HashMap<Entity, AmbienceSample>,
+}
+
+/// An event sent whenever the dominant ambience around an [`AmbienceListener`] changes
+#[derive(Debug, Clone, PartialEq)]
+pub struct AmbienceChanged {
+	/// The listener entity
+	pub listener: Entity,
+	/// The previous dominant ambience
+	pub previous: Option<String>,
+	/// The new dominant ambience
+	pub current: Option<String>,
+}
+
+/// Tracks the ambience of every placed tile
+#[derive(Debug, Default)]
+pub(crate) struct AmbienceIndex {
+	tiles: HashMap<TileCoord, (Entity, Option<String>)>,
+	coords: HashMap<Entity, TileCoord>,
+}
+
+impl AmbienceSample {
+	/// The ratio (from `0.0` to `1.0`) of sampled tiles with the given ambience
+	pub fn ratio(&self, ambience: &str) -> f32 {
+		if self.total == 0 {
+			return 0.0;
+		}
+
+		self.counts.get(ambience).copied().unwrap_or_default() as f32 / self.total as f32
+	}
+
+	/// The most common ambience among the sampled tiles (if any)
+	pub fn dominant(&self) -> Option<&str> {
+		self.counts
+			.iter()
+			.max_by(|(name_a, count_a), (name_b, count_b)| {
+				// Break ties by name so the result is deterministic
+				count_a.cmp(count_b).then_with(|| name_b.cmp(name_a))
+			})
+			.map(|(name, _)| name.as_str())
+	}
+}
+
+impl AmbienceSamples {
+	/// Get the latest sample of the given listener
+	pub fn get(&self, listener: Entity) -> Option<&AmbienceSample> {
+		self.samples.get(&listener)
+	}
+
+	/// Iterate over the latest samples of all listeners
+	pub fn iter(&self) -> impl Iterator<Item = (Entity, &AmbienceSample)> {
+		self.samples
+			.iter()
+			.map(|(entity, sample)| (*entity, sample))
+	}
+}
+
+impl AmbienceIndex {
+	fn insert(&mut self, entity: Entity, coord: TileCoord, ambience: Option<String>) {
+		if let Some(old_coord) = self.coords.insert(entity, coord) {
+			if old_coord != coord {
+				self.remove_at(entity, &old_coord);
+			}
+		}
+		self.tiles.insert(coord, (entity, ambience));
+	}
+
+	fn remove(&mut self, entity: Entity) {
+		if let Some(coord) = self.coords.remove(&entity) {
+			self.remove_at(entity, &coord);
+		}
+	}
+
+	/// Removes the tile at the given coordinate only if it still belongs to the given entity
+	fn remove_at(&mut self, entity: Entity, coord: &TileCoord) {
+		if matches!(self.tiles.get(coord), Some((owner, _)) if *owner == entity) {
+			self.tiles.remove(coord);
+		}
+	}
+}
+
+/// __\[SYSTEM\]__ Keeps the [`AmbienceIndex`] in sync with all placed tiles
+pub(crate) fn update_ambience_index(
+	mut index: ResMut<AmbienceIndex>,
+	changed: Query<
+		(Entity, &TilePos, &TileParent, Option<&TileMetadata>),
+		(
+			With<TilesetParent>,
+			Or<(Changed<TilesetParent>, Changed<TileMetadata>)>,
+		),
+	>,
+	removed: RemovedComponents<TilesetParent>,
+) {
+	for entity in removed.iter() {
+		index.remove(entity);
+	}
+
+	for (entity, pos, parent, metadata) in changed.iter() {
+		let ambience = metadata
+			.and_then(|metadata| metadata.get_str(AMBIENCE_METADATA_KEY))
+			.map(str::to_string);
+		let coord = TileCoord {
+			pos: *pos,
+			map_id: parent.map_id,
+			layer_id: parent.layer_id,
+		};
+		index.insert(entity, coord, ambience);
+	}
+}
+
+/// __\[SYSTEM\]__ Samples the surroundings of every [`AmbienceListener`]
+pub(crate) fn sample_ambience(
+	index: Res<AmbienceIndex>,
+	registry: Res<TilesetMapRegistry>,
+	mut samples: ResMut<AmbienceSamples>,
+	mut events: EventWriter<AmbienceChanged>,
+	listeners: Query<(Entity, &AmbienceListener, &GlobalTransform)>,
+	transforms: Query<&GlobalTransform>,
+) {
+	let mut next = HashMap::default();
+	for (entity, listener, listener_transform) in listeners.iter() {
+		let position = listener_transform.translation.truncate();
+		let radius = listener.radius as i64;
+		let mut sample = AmbienceSample::default();
+
+		let map = if let Some(map) = registry.map(listener.map_id) {
+			map
+		} else {
+			next.insert(entity, sample);
+			continue;
+		};
+
+		for (layer_id, layer) in map.layers() {
+			let grid_size = layer.settings.grid_size;
+			if grid_size.x <= 0.0 || grid_size.y <= 0.0 {
+				continue;
+			}
+
+			let origin = transforms
+				.get(layer.entity)
+				.map(|transform| transform.translation.truncate())
+				.unwrap_or_default();
+			let center = ((position - origin) / grid_size).floor();
+			let (center_x, center_y) = (center.x as i64, center.y as i64);
+
+			for y in center_y - radius..=center_y + radius {
+				for x in center_x - radius..=center_x + radius {
+					let (dx, dy) = (x - center_x, y - center_y);
+					if x < 0 || y < 0 || dx * dx + dy * dy > radius * radius {
+						continue;
+					}
+
+					let coord = TileCoord {
+						pos: TilePos(x as u32, y as u32),
+						map_id: listener.map_id,
+						layer_id,
+					};
+					if let Some((_, ambience)) = index.tiles.get(&coord) {
+						sample.total += 1;
+						if let Some(ambience) = ambience {
+							*sample.counts.entry(ambience.clone()).or_default() += 1;
+						}
+					}
+				}
+			}
+		}
+
+		let previous = samples
+			.samples
+			.get(&entity)
+			.and_then(|sample| sample.dominant().map(str::to_string));
+		let current = sample.dominant().map(str::to_string);
+		if previous != current {
+			events.send(AmbienceChanged {
+				listener: entity,
+				previous,
+				current,
+			});
+		}
+
+		next.insert(entity, sample);
+	}
+
+	samples.samples = next;
+}
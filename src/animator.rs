@@ -0,0 +1,342 @@
+//! One-shot tile animations, such as crumbling blocks or opening doors
+//!
+//! Unlike the looping animations defined by a tileset (which are played on the GPU), one-shot
+//! animations are stepped on the CPU, so they can end with an event and leave the tile in a new
+//! state.
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::{GPUAnimated, MapId, MapQuery, Tile, TileParent, TilePos};
+use bevy_tileset::prelude::TileId;
+
+use crate::placement::TilePlacer;
+
+/// What happens to a tile once its [`OneShotAnimation`] has finished
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum AnimationEnd {
+	/// Restore the tile to how it looked before the animation (including any looping animation)
+	Restore,
+	/// Keep displaying the last frame of the animation
+	Hold,
+	/// Replace the tile with the given tile
+	Replace(TileId),
+	/// Remove the tile
+	Remove,
+}
+
+/// An animation that plays once on a placed tile
+///
+/// # Examples
+///
+/// ```
+/// # use bevy_tileset_map::prelude::OneShotAnimation;
+/// // Crumble over 4 frames, then disappear
+/// let crumble = OneShotAnimation::new(12..16, 0.1).then_remove();
+/// assert_eq!(&[12, 13, 14, 15], crumble.frames());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct OneShotAnimation {
+	frames: Vec<usize>,
+	frame_duration: f32,
+	end: AnimationEnd,
+}
+
+/// A component added to tiles that are currently playing a [`OneShotAnimation`]
+#[derive(Component, Debug, Clone)]
+pub struct PlayingAnimation {
+	animation: OneShotAnimation,
+	elapsed: f32,
+	/// The texture index of the tile before the animation started
+	original_index: u16,
+	/// The looping animation of the tile before the animation started
+	original_animation: Option<GPUAnimated>,
+}
+
+/// An event sent when a [`OneShotAnimation`] finishes playing
+///
+/// Sent before the tile is replaced or removed (if requested by its [`AnimationEnd`]), so the
+/// entity may no longer exist by the time this event is read. Animations that are stopped early
+/// with [`TileAnimator::stop`] do not send this event.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TileAnimationFinishedEvent {
+	/// The animated tile entity
+	pub entity: Entity,
+	/// The position of the tile
+	pub pos: TilePos,
+	/// The map containing the tile
+	pub map_id: u16,
+	/// The layer containing the tile
+	pub layer_id: u16,
+	/// What happened to the tile once the animation finished
+	pub end: AnimationEnd,
+}
+
+/// A helper system param used to play [`OneShotAnimation`]s on tiles
+///
+/// # Examples
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_ecs_tilemap::TilePos;
+/// # use bevy_tileset_map::prelude::{OneShotAnimation, TileAnimator, TileId};
+/// struct Rubble(TileId);
+///
+/// fn break_block(mut animator: TileAnimator, rubble: Res<Rubble>) {
+///   let animation = OneShotAnimation::new(vec![4, 5, 6], 0.15).then_replace(rubble.0);
+///   animator.play_at(TilePos(3, 2), 0u16, 0u16, animation).unwrap();
+/// }
+/// ```
+#[derive(SystemParam)]
+pub struct TileAnimator<'w, 's> {
+	commands: Commands<'w, 's>,
+	map_query: MapQuery<'w, 's>,
+	tiles: Query<
+		'w,
+		's,
+		(
+			&'static Tile,
+			Option<&'static GPUAnimated>,
+			Option<&'static PlayingAnimation>,
+		),
+	>,
+}
+
+impl OneShotAnimation {
+	/// Create an animation displaying the given texture indices in order, each for the given
+	/// number of seconds
+	///
+	/// Once finished, the tile is restored to how it looked before.
+	pub fn new<I: IntoIterator<Item = usize>>(frames: I, frame_duration: f32) -> Self {
+		Self {
+			frames: frames.into_iter().collect(),
+			frame_duration: frame_duration.max(0.0),
+			end: AnimationEnd::Restore,
+		}
+	}
+
+	/// Set what happens to the tile once the animation has finished (builder-style)
+	pub fn with_end(mut self, end: AnimationEnd) -> Self {
+		self.end = end;
+		self
+	}
+
+	/// Keep displaying the last frame once the animation has finished (builder-style)
+	pub fn then_hold(self) -> Self {
+		self.with_end(AnimationEnd::Hold)
+	}
+
+	/// Replace the tile with the given tile once the animation has finished (builder-style)
+	pub fn then_replace<Id: Into<TileId>>(self, tile_id: Id) -> Self {
+		self.with_end(AnimationEnd::Replace(tile_id.into()))
+	}
+
+	/// Remove the tile once the animation has finished (builder-style)
+	pub fn then_remove(self) -> Self {
+		self.with_end(AnimationEnd::Remove)
+	}
+
+	/// The texture indices displayed by this animation, in order
+	pub fn frames(&self) -> &[usize] {
+		&self.frames
+	}
+
+	/// The number of seconds each frame is displayed for
+	pub fn frame_duration(&self) -> f32 {
+		self.frame_duration
+	}
+
+	/// What happens to the tile once the animation has finished
+	pub fn end(&self) -> AnimationEnd {
+		self.end
+	}
+
+	/// The total length of this animation in seconds
+	pub fn duration(&self) -> f32 {
+		self.frames.len() as f32 * self.frame_duration
+	}
+
+	/// The texture index displayed after the given number of seconds, or `None` if finished
+	fn frame_at(&self, elapsed: f32) -> Option<usize> {
+		if self.frame_duration == 0.0 {
+			return None;
+		}
+		self.frames
+			.get((elapsed / self.frame_duration) as usize)
+			.copied()
+	}
+}
+
+impl PlayingAnimation {
+	/// The animation being played
+	pub fn animation(&self) -> &OneShotAnimation {
+		&self.animation
+	}
+
+	/// The number of seconds since the animation started
+	pub fn elapsed(&self) -> f32 {
+		self.elapsed
+	}
+
+	/// The fraction of the animation that has been played, between `0.0` and `1.0`
+	pub fn progress(&self) -> f32 {
+		let duration = self.animation.duration();
+		if duration == 0.0 {
+			1.0
+		} else {
+			(self.elapsed / duration).min(1.0)
+		}
+	}
+}
+
+impl<'w, 's> TileAnimator<'w, 's> {
+	/// Play the given animation on the tile at the given coordinate
+	///
+	/// Any one-shot animation already playing on the tile is replaced.
+	///
+	/// Returns the tile entity, or `None` if there is no tile at the given coordinate.
+	pub fn play_at<Pos: Into<TilePos>, MId: MapId>(
+		&mut self,
+		pos: Pos,
+		map_id: MId,
+		layer_id: u16,
+		animation: OneShotAnimation,
+	) -> Option<Entity> {
+		let entity = self
+			.map_query
+			.get_tile_entity(pos.into(), map_id, layer_id)
+			.ok()?;
+		self.play(entity, animation).then_some(entity)
+	}
+
+	/// Play the given animation on the given tile entity
+	///
+	/// Any one-shot animation already playing on the tile is replaced.
+	///
+	/// Returns false if the entity is not a tile.
+	pub fn play(&mut self, entity: Entity, animation: OneShotAnimation) -> bool {
+		let (tile, looping, playing) = match self.tiles.get(entity) {
+			Ok(tile) => tile,
+			Err(_) => return false,
+		};
+
+		// Keep the original state of an interrupted animation
+		let (original_index, original_animation) = match playing {
+			Some(playing) => (playing.original_index, playing.original_animation),
+			None => (tile.texture_index, looping.copied()),
+		};
+
+		self.commands
+			.entity(entity)
+			.remove::<GPUAnimated>()
+			.insert(PlayingAnimation {
+				animation,
+				elapsed: 0.0,
+				original_index,
+				original_animation,
+			});
+		true
+	}
+
+	/// Stop the one-shot animation playing on the given tile entity, restoring the tile
+	///
+	/// No [`TileAnimationFinishedEvent`] is sent. Returns false if no animation was playing.
+	pub fn stop(&mut self, entity: Entity) -> bool {
+		let (tile, playing) = match self.tiles.get(entity) {
+			Ok((tile, _, Some(playing))) => (*tile, playing),
+			_ => return false,
+		};
+
+		let mut cmds = self.commands.entity(entity);
+		cmds.remove::<PlayingAnimation>().insert(Tile {
+			texture_index: playing.original_index,
+			..tile
+		});
+		if let Some(original) = playing.original_animation {
+			cmds.insert(original);
+		}
+		true
+	}
+
+	/// Returns true if a one-shot animation is playing on the given tile entity
+	pub fn is_playing(&self, entity: Entity) -> bool {
+		matches!(self.tiles.get(entity), Ok((_, _, Some(_))))
+	}
+
+	/// Get the one-shot animation playing on the given tile entity (if any)
+	pub fn get_playing(&self, entity: Entity) -> Option<&PlayingAnimation> {
+		self.tiles.get(entity).ok()?.2
+	}
+}
+
+/// __\[SYSTEM\]__ Steps all playing [`OneShotAnimation`]s and sends a
+/// [`TileAnimationFinishedEvent`] for each one that finishes
+pub(crate) fn update_tile_animations(
+	mut commands: Commands,
+	time: Res<Time>,
+	mut map_query: MapQuery,
+	mut tiles: Query<(
+		Entity,
+		&TilePos,
+		&TileParent,
+		&mut Tile,
+		&mut PlayingAnimation,
+	)>,
+	mut finished: EventWriter<TileAnimationFinishedEvent>,
+) {
+	let delta = time.delta_seconds();
+	for (entity, pos, parent, mut tile, mut playing) in tiles.iter_mut() {
+		playing.elapsed += delta;
+
+		match playing.animation.frame_at(playing.elapsed) {
+			Some(frame) => {
+				if tile.texture_index as usize != frame {
+					tile.texture_index = frame as u16;
+					map_query.notify_chunk_for_tile(*pos, parent.map_id, parent.layer_id);
+				}
+			},
+			None => {
+				let end = playing.animation.end;
+				let mut cmds = commands.entity(entity);
+				cmds.remove::<PlayingAnimation>();
+				if end == AnimationEnd::Restore {
+					tile.texture_index = playing.original_index;
+					if let Some(original) = playing.original_animation {
+						cmds.insert(original);
+					}
+					map_query.notify_chunk_for_tile(*pos, parent.map_id, parent.layer_id);
+				}
+
+				finished.send(TileAnimationFinishedEvent {
+					entity,
+					pos: *pos,
+					map_id: parent.map_id,
+					layer_id: parent.layer_id,
+					end,
+				});
+			},
+		}
+	}
+}
+
+/// __\[SYSTEM\]__ Replaces or removes tiles whose [`OneShotAnimation`] has finished
+pub(crate) fn finish_tile_animations(
+	mut finished: EventReader<TileAnimationFinishedEvent>,
+	mut placer: TilePlacer,
+) {
+	for event in finished.iter() {
+		let result = match event.end {
+			AnimationEnd::Replace(tile_id) => placer
+				.place(tile_id, event.pos, event.map_id, event.layer_id)
+				.map(|_| ()),
+			AnimationEnd::Remove => placer.remove(event.pos, event.map_id, event.layer_id),
+			AnimationEnd::Restore | AnimationEnd::Hold => Ok(()),
+		};
+
+		if let Err(err) = result {
+			warn!(
+				"Could not finish the animation of tile {:?} in layer {} of map {}: {}",
+				event.pos, event.layer_id, event.map_id, err
+			);
+		}
+	}
+}
@@ -0,0 +1,186 @@
+//! A debug overlay visualizing how Auto tiles were resolved
+
+use bevy::prelude::*;
+use bevy_ecs_tilemap::{MapQuery, Tile};
+use bevy_tileset::auto::AutoTileId;
+use bevy_tileset::prelude::Tilesets;
+
+use crate::activity::ActiveChunks;
+use crate::auto::symmetry::neighbor_bits;
+use crate::auto::systems::RemoveAutoTileEvent;
+use crate::auto::validation::{evaluate_auto_tiles, AutoTileEvaluation, AutoTileQuery};
+
+/// The z-offset of the overlay relative to the layer
+const OVERLAY_Z_OFFSET: f32 = 0.5;
+
+/// The width of an outline, relative to the size of a tile
+const OUTLINE_WIDTH: f32 = 0.06;
+
+/// A resource configuring the Auto tile debug overlay
+///
+/// While enabled, every Auto tile in an active chunk is outlined in a color indicating whether
+/// its texture matches its neighborhood. If a [`font`](Self::font) is given, each tile is also
+/// labeled with the bitmask of its matching neighbors (north, north-east, east, south-east,
+/// south, south-west, west, then north-west, starting from the rightmost bit) and the texture
+/// index it displays.
+///
+/// This resource is added automatically by the [`TilesetMapPlugin`](crate::prelude::TilesetMapPlugin).
+///
+/// # Examples
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_tileset_map::prelude::AutoTileDebugOverlay;
+/// fn toggle_overlay(keys: Res<Input<KeyCode>>, mut overlay: ResMut<AutoTileDebugOverlay>) {
+///   if keys.just_pressed(KeyCode::F3) {
+///     overlay.enabled = !overlay.enabled;
+///   }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct AutoTileDebugOverlay {
+	/// Whether the overlay is shown
+	pub enabled: bool,
+	/// The font used to label the tiles (no labels are shown without one)
+	pub font: Option<Handle<Font>>,
+	/// The font size of the labels
+	pub font_size: f32,
+	/// The outline color of tiles whose texture matches their neighborhood
+	pub matched_color: Color,
+	/// The outline color of tiles whose texture doesn't match their neighborhood
+	pub mismatched_color: Color,
+	/// The outline color of tiles whose group doesn't define a texture for their neighborhood
+	pub missing_color: Color,
+}
+
+impl Default for AutoTileDebugOverlay {
+	fn default() -> Self {
+		Self {
+			enabled: false,
+			font: None,
+			font_size: 8.0,
+			matched_color: Color::rgba(0.2, 0.9, 0.3, 0.8),
+			mismatched_color: Color::rgba(0.95, 0.2, 0.2, 0.9),
+			missing_color: Color::rgba(0.95, 0.8, 0.2, 0.9),
+		}
+	}
+}
+
+impl AutoTileDebugOverlay {
+	/// The outline color of the given tile
+	fn color_of(&self, evaluation: &AutoTileEvaluation) -> Color {
+		if evaluation.is_match || evaluation.is_flipped {
+			self.matched_color
+		} else if evaluation.has_texture {
+			self.mismatched_color
+		} else {
+			self.missing_color
+		}
+	}
+}
+
+/// __\[SYSTEM\]__ Redraws the Auto tile debug overlay whenever it or any Auto tile changes
+pub(crate) fn update_auto_tile_overlay(
+	mut commands: Commands,
+	overlay: Res<AutoTileDebugOverlay>,
+	mut drawn: Local<Vec<Entity>>,
+	changed: Query<(), (With<AutoTileId>, Changed<Tile>)>,
+	mut removed: EventReader<RemoveAutoTileEvent>,
+	auto_tiles: AutoTileQuery,
+	tilesets: Tilesets,
+	active: Res<ActiveChunks>,
+	mut map_query: MapQuery,
+) {
+	let any_removed = removed.iter().count() > 0;
+	let is_outdated = overlay.is_changed() || any_removed || !changed.is_empty();
+	if !is_outdated || (!overlay.enabled && drawn.is_empty()) {
+		return;
+	}
+
+	for entity in drawn.drain(..) {
+		commands.entity(entity).despawn_recursive();
+	}
+
+	if !overlay.enabled {
+		return;
+	}
+
+	for evaluation in evaluate_auto_tiles(&auto_tiles, &tilesets, &active) {
+		let coord = evaluation.coord;
+		let (layer_entity, grid_size) = if let Some((layer_entity, layer)) =
+			map_query.get_layer(coord.map_id, coord.layer_id)
+		{
+			(layer_entity, layer.settings.grid_size)
+		} else {
+			continue;
+		};
+
+		let color = overlay.color_of(&evaluation);
+		let width = grid_size * OUTLINE_WIDTH;
+		let edges = [
+			(
+				Vec2::new(0.0, (grid_size.y - width.y) / 2.0),
+				Vec2::new(grid_size.x, width.y),
+			),
+			(
+				Vec2::new(0.0, (width.y - grid_size.y) / 2.0),
+				Vec2::new(grid_size.x, width.y),
+			),
+			(
+				Vec2::new((grid_size.x - width.x) / 2.0, 0.0),
+				Vec2::new(width.x, grid_size.y),
+			),
+			(
+				Vec2::new((width.x - grid_size.x) / 2.0, 0.0),
+				Vec2::new(width.x, grid_size.y),
+			),
+		];
+
+		let translation = coord.to_vec2(grid_size).extend(OVERLAY_Z_OFFSET);
+		let entity = commands
+			.spawn_bundle(TransformBundle::from_transform(
+				Transform::from_translation(translation),
+			))
+			.with_children(|parent| {
+				for (offset, size) in edges {
+					parent.spawn_bundle(SpriteBundle {
+						sprite: Sprite {
+							color,
+							custom_size: Some(size),
+							..Default::default()
+						},
+						transform: Transform::from_translation(offset.extend(0.0)),
+						..Default::default()
+					});
+				}
+
+				if let Some(font) = &overlay.font {
+					let label = format!(
+						"{:08b}\n{}",
+						neighbor_bits(&evaluation.rule),
+						evaluation.texture_index
+					);
+					parent.spawn_bundle(Text2dBundle {
+						text: Text::with_section(
+							label,
+							TextStyle {
+								font: font.clone(),
+								font_size: overlay.font_size,
+								color,
+							},
+							TextAlignment {
+								vertical: VerticalAlign::Center,
+								horizontal: HorizontalAlign::Center,
+							},
+						),
+						transform: Transform::from_xyz(0.0, 0.0, 0.01),
+						..Default::default()
+					});
+				}
+			})
+			.id();
+
+		commands.entity(layer_entity).add_child(entity);
+		drawn.push(entity);
+	}
+}
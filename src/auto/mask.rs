@@ -0,0 +1,73 @@
+//! Controlling how tiles are counted as neighbors by the auto tiler
+
+use bevy::prelude::Component;
+use bevy_tileset::auto::AutoTileId;
+use bevy_tileset::prelude::Tileset;
+
+use crate::metadata::TileMetadataRegistry;
+
+/// The metadata key used to declare how a tile group is treated by neighboring Auto tiles
+///
+/// The value should be one of the following strings:
+///
+/// * `"transparent"` - The tile is never counted as a neighbor, not even by tiles of its own group
+/// * `"masking"` - The tile is counted as a matching neighbor by every Auto tile in its layer
+///
+/// This allows decorations placed on the same layer as some terrain to keep the terrain's edges
+/// intact. Unlike other tiles, `"masking"` tiles don't need to be Auto tiles themselves.
+pub const AUTO_MASK_METADATA_KEY: &str = "auto_mask";
+
+/// How a tile group is treated by neighboring Auto tiles
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum AutoTileMask {
+	/// The tile is never counted as a neighbor
+	Transparent,
+	/// The tile is counted as a matching neighbor by every Auto tile
+	///
+	/// Masking Auto tiles count every neighboring Auto tile as matching as well.
+	Masking,
+}
+
+/// Marks a tile whose group declares an [`AutoTileMask`]
+///
+/// This also allows masking tiles that aren't Auto tiles to be found by the auto tiler.
+#[derive(Component, Debug, Copy, Clone)]
+pub struct AutoTileMasked {
+	pub mask: AutoTileMask,
+	/// The ID of the tile's group (even if it's not an Auto tile)
+	pub auto_id: AutoTileId,
+}
+
+impl AutoTileMask {
+	/// Parse the mask from its metadata value
+	pub fn from_name(name: &str) -> Option<Self> {
+		match name {
+			"transparent" => Some(Self::Transparent),
+			"masking" => Some(Self::Masking),
+			_ => None,
+		}
+	}
+
+	/// Get the mask declared for the given tile (if any)
+	pub fn from_registry(
+		registry: &TileMetadataRegistry,
+		tileset: &Tileset,
+		tile_name: &str,
+	) -> Option<Self> {
+		registry
+			.get(tileset.name(), tile_name)?
+			.get_str(AUTO_MASK_METADATA_KEY)
+			.and_then(Self::from_name)
+	}
+
+	/// Returns true if two tiles with the given masks should be counted as neighbors
+	///
+	/// Returns `None` if neither mask decides this (i.e. it depends on their groups).
+	pub(crate) fn matches(a: Option<Self>, b: Option<Self>) -> Option<bool> {
+		match (a, b) {
+			(Some(Self::Transparent), _) | (_, Some(Self::Transparent)) => Some(false),
+			(Some(Self::Masking), _) | (_, Some(Self::Masking)) => Some(true),
+			_ => None,
+		}
+	}
+}
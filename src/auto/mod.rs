@@ -1,5 +1,22 @@
+#[cfg(all(feature = "auto-tile-debug", not(feature = "headless")))]
+mod debug;
+mod mask;
+mod preview;
+mod symmetry;
 mod systems;
 mod traits;
+mod validation;
+mod variants;
 
+#[cfg(all(feature = "auto-tile-debug", not(feature = "headless")))]
+pub(crate) use debug::update_auto_tile_overlay;
+#[cfg(all(feature = "auto-tile-debug", not(feature = "headless")))]
+pub use debug::AutoTileDebugOverlay;
+pub(crate) use mask::AutoTileMasked;
+pub use mask::{AutoTileMask, AUTO_MASK_METADATA_KEY};
+pub(crate) use preview::{AutoPreviewTile, AutoTilePreviews};
+pub(crate) use symmetry::AutoTileFlipped;
+pub use symmetry::{AutoTileSymmetry, AUTO_SYMMETRY_METADATA_KEY};
 pub use systems::RemoveAutoTileEvent;
 pub(crate) use systems::{on_change_auto_tile, on_remove_auto_tile};
+pub(crate) use validation::{find_auto_tile_mismatches, AutoTileQuery};
@@ -0,0 +1,216 @@
+use crate::auto::mask::AutoTileMask;
+use crate::auto::symmetry::{AutoTileFlipped, TileFlip};
+use crate::auto::systems::{is_user_flipped, resolve_request, ResolveInput};
+use crate::auto::traits::{
+	layer_lattice, AutoCoord, AutoNeighborQuery, TileInfo, TileQuery, TilemapCache,
+};
+use crate::combined::CombinedTilesets;
+use crate::config::TilesetMapConfig;
+use crate::coord::TileCoord;
+use crate::metadata::TileMetadataRegistry;
+use crate::placement::{PreviewOverlay, TilePlacementOptions};
+use bevy::ecs::system::SystemParam;
+use bevy::math::IVec2;
+use bevy::prelude::{Color, Entity, Query};
+use bevy::utils::HashMap;
+use bevy_ecs_tilemap::{MapQuery, Tile};
+use bevy_tileset::auto::{AutoTile, AutoTileId, AutoTilemap, AutoTiler};
+use bevy_tileset::prelude::Tilesets;
+use std::cell::RefCell;
+
+/// A previewed tile, as seen by the auto tiler
+pub(crate) struct AutoPreviewTile {
+	pub coord: TileCoord,
+	/// The ID of the tile's group, if it is an Auto tile or declares an [`AutoTileMask`]
+	pub auto_id: Option<AutoTileId>,
+	pub mask: Option<AutoTileMask>,
+	/// Whether the tile itself is auto tiled (rather than only being masked)
+	pub is_auto: bool,
+	/// The index the tile would be placed with, as displayed in its layer
+	pub texture_index: usize,
+	/// The color the preview is tinted with
+	pub color: Color,
+}
+
+/// The queries used to auto tile previews without placing them
+///
+/// This is nominally public since it appears in the state of the
+/// [`TilePreviewer`](crate::prelude::TilePreviewer) param, but it is not exported.
+#[derive(SystemParam)]
+pub struct AutoTilePreviews<'w, 's> {
+	neighbors: AutoNeighborQuery<'w, 's>,
+	tiles: Query<'w, 's, (&'static Tile, Option<&'static AutoTileFlipped>)>,
+}
+
+impl<'w, 's> AutoTilePreviews<'w, 's> {
+	/// Compute how the given previews (and their neighbors) would be displayed once placed
+	///
+	/// Only previews and neighbors whose texture would differ from a plain placement are
+	/// returned. Nothing is written to the map.
+	pub(crate) fn resolve(
+		&self,
+		previews: &[AutoPreviewTile],
+		map_query: &mut MapQuery,
+		tilesets: &Tilesets,
+		metadata: &TileMetadataRegistry,
+		combined: &CombinedTilesets,
+		config: &TilesetMapConfig,
+	) -> Vec<PreviewOverlay> {
+		// Previews have no entity of their own: they are identified by their coordinate
+		let placeholder = Entity::from_raw(u32::MAX);
+
+		let mut overrides = HashMap::default();
+		let mut replaced = Vec::new();
+		let mut added = Vec::new();
+		for preview in previews {
+			let TileCoord {
+				pos,
+				map_id,
+				layer_id,
+			} = preview.coord;
+			let lattice = layer_lattice(map_query, map_id, layer_id);
+
+			// The tile beneath the preview no longer counts as a neighbor
+			if let Ok(entity) = map_query.get_tile_entity(pos, map_id, layer_id) {
+				replaced.extend(self.neighbors.find_tile(entity, lattice));
+			}
+
+			let info = preview.auto_id.map(|auto_tile| TileInfo {
+				coord: preview.coord,
+				entity: placeholder,
+				auto_tile,
+				mask: preview.mask,
+				lattice,
+			});
+			overrides.insert(preview.coord, (info, preview));
+			added.extend(info);
+		}
+
+		let map_query = RefCell::new(&mut *map_query);
+		let mut tilemap = PreviewTilemap {
+			cache: TilemapCache {
+				tiles_query: &self.neighbors,
+				map_query: &map_query,
+			},
+			overrides: &overrides,
+		};
+		let mut tiler = AutoTiler::new(&mut tilemap);
+
+		// Each coordinate is only requested once, so the replaced tiles have to update their
+		// neighbors before the previews are requested in their place
+		for tile in replaced {
+			tiler.add_tile(tile, false);
+		}
+		for tile in added {
+			tiler.add_tile(tile, true);
+		}
+
+		let requests = tiler.finish();
+
+		requests
+			.iter()
+			.filter_map(|request| {
+				let coord = request.tile.coord;
+				let variant_seed = config.get(coord.map_id).variant_seed;
+
+				// --- Snapshot --- //
+				let (input, color, fallback_flip, is_preview) = match overrides.get(&coord) {
+					Some((_, preview)) if preview.is_auto => {
+						let input = ResolveInput {
+							entity: placeholder,
+							pos: coord.pos,
+							map_id: coord.map_id,
+							layer_id: coord.layer_id,
+							auto_tile: request.tile.auto_tile,
+							rule: request.rule,
+							texture_index: preview.texture_index,
+							auto_flip: None,
+							user_flipped: false,
+							variant_seed,
+						};
+						(input, preview.color, TileFlip::default(), true)
+					},
+					// Masked tiles only affect their neighbors
+					Some(..) => return None,
+					None => {
+						let entity = request.tile.entity;
+						let (.., auto_tile, _) = self.neighbors.get(entity).ok()?;
+						let (tile, flipped) = self.tiles.get(entity).ok()?;
+						let input = ResolveInput {
+							entity,
+							pos: coord.pos,
+							map_id: coord.map_id,
+							layer_id: coord.layer_id,
+							auto_tile: *auto_tile?,
+							rule: request.rule,
+							texture_index: tile.texture_index as usize,
+							auto_flip: flipped.map(|_| TileFlip::of(tile)),
+							user_flipped: is_user_flipped(tile, flipped.is_some()),
+							variant_seed,
+						};
+						// Flips set by the auto tiler are reset, while the user's are kept
+						let flip = if flipped.is_some() {
+							TileFlip::default()
+						} else {
+							TileFlip::of(tile)
+						};
+						(input, tile.color, flip, false)
+					},
+				};
+
+				// --- Resolve --- //
+				let tileset_id = input.auto_tile.tileset_id;
+				let offset = combined.offset(&tileset_id, coord.map_id, coord.layer_id);
+				let (index, flip) = match resolve_request(&input, tilesets, metadata, combined) {
+					Some(resolution) => (
+						*resolution.index.base_index(),
+						resolution.flip.unwrap_or(fallback_flip),
+					),
+					// Neighbors that wouldn't change are already displayed correctly
+					None if !is_preview => return None,
+					None => (input.texture_index, fallback_flip),
+				};
+
+				// Overlays use the tileset's own atlas, even in a combined layer
+				Some(PreviewOverlay {
+					coord,
+					tileset_id,
+					index: index.saturating_sub(offset),
+					options: TilePlacementOptions {
+						color,
+						flip_x: flip.x,
+						flip_y: flip.y,
+						flip_d: flip.d,
+					},
+				})
+			})
+			.collect()
+	}
+}
+
+/// A tilemap that displays the previewed tiles in place of the tiles beneath them
+struct PreviewTilemap<'a, 'w, 's> {
+	cache: TilemapCache<'a, 'w, 's>,
+	/// The auto tile info of each previewed coordinate (if the preview affects its neighbors)
+	overrides: &'a HashMap<TileCoord, (Option<TileInfo>, &'a AutoPreviewTile)>,
+}
+
+impl<'a, 'w, 's> AutoTilemap for PreviewTilemap<'a, 'w, 's> {
+	type Tile = TileInfo;
+
+	fn make_coords(&self, pos: IVec2, template: &AutoCoord) -> AutoCoord {
+		self.cache.make_coords(pos, template)
+	}
+
+	fn get_tile_at(&self, coords: &<Self::Tile as AutoTile>::Coords) -> Option<Self::Tile> {
+		match self.overrides.get(&coords.coord) {
+			Some((info, _)) => *info,
+			None => self.cache.get_tile_at(coords),
+		}
+	}
+
+	fn len(&self) -> usize {
+		// Only used to reserve space, and previews rarely touch more than their neighbors
+		self.overrides.len()
+	}
+}
@@ -0,0 +1,254 @@
+//! Synthesizing missing auto tile pieces by flipping/rotating existing ones
+
+use bevy::prelude::Component;
+use bevy_ecs_tilemap::Tile;
+use bevy_tileset::prelude::AutoTileRule;
+use bevy_tileset::prelude::{TileIndex, Tileset};
+
+use crate::auto::variants::AutoVariantSelector;
+use crate::metadata::TileMetadataRegistry;
+
+/// The metadata key used to declare which transformations an auto tile group supports
+///
+/// The value should be one of the following strings:
+///
+/// * `"flip-x"` - Pieces may be mirrored horizontally
+/// * `"flip-y"` - Pieces may be mirrored vertically
+/// * `"flip"` - Pieces may be mirrored horizontally and/or vertically
+/// * `"rotate"` - Pieces may be rotated in 90° increments
+/// * `"all"` - Pieces may be mirrored and rotated
+pub const AUTO_SYMMETRY_METADATA_KEY: &str = "auto_symmetry";
+
+/// The transformations an auto tile group supports for synthesizing missing pieces
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct AutoTileSymmetry {
+	pub flip_x: bool,
+	pub flip_y: bool,
+	pub rotate: bool,
+}
+
+/// Marks an auto tile whose flip flags were set by the auto tiler (rather than by the user)
+///
+/// Only flags set by the auto tiler are ever reset by it, so user-defined flips survive
+/// neighbor updates.
+///
+/// This is nominally public since it appears in the state of the
+/// [`TilePreviewer`](crate::prelude::TilePreviewer) param, but it is not exported.
+#[derive(Component, Debug, Copy, Clone, Default)]
+pub struct AutoTileFlipped;
+
+/// A combination of `bevy_ecs_tilemap` flip flags
+///
+/// Following the Tiled convention, the diagonal flip is applied first,
+/// followed by the horizontal flip, then the vertical flip.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub(crate) struct TileFlip {
+	pub x: bool,
+	pub y: bool,
+	pub d: bool,
+}
+
+impl AutoTileSymmetry {
+	/// Parse the symmetry from its metadata value
+	pub fn from_name(name: &str) -> Option<Self> {
+		let (flip_x, flip_y, rotate) = match name {
+			"flip-x" => (true, false, false),
+			"flip-y" => (false, true, false),
+			"flip" => (true, true, false),
+			"rotate" => (false, false, true),
+			"all" => (true, true, true),
+			_ => return None,
+		};
+		Some(Self {
+			flip_x,
+			flip_y,
+			rotate,
+		})
+	}
+
+	/// Get the symmetry declared for the given tile (if any)
+	pub fn from_registry(
+		registry: &TileMetadataRegistry,
+		tileset: &Tileset,
+		tile_name: &str,
+	) -> Option<Self> {
+		registry
+			.get(tileset.name(), tile_name)?
+			.get_str(AUTO_SYMMETRY_METADATA_KEY)
+			.and_then(Self::from_name)
+	}
+
+	/// All non-identity transformations allowed by this symmetry, simplest first
+	pub(crate) fn transforms(&self) -> Vec<TileFlip> {
+		let mut transforms = Vec::with_capacity(7);
+		let mut push = |x, y, d| {
+			let flip = TileFlip { x, y, d };
+			if !transforms.contains(&flip) {
+				transforms.push(flip);
+			}
+		};
+
+		if self.flip_x {
+			push(true, false, false);
+		}
+		if self.flip_y {
+			push(false, true, false);
+		}
+		if self.rotate {
+			// Rotations by 90°, 180°, and 270°
+			push(true, false, true);
+			push(true, true, false);
+			push(false, true, true);
+		}
+		if self.flip_x && self.flip_y {
+			push(true, true, false);
+		}
+		if self.rotate && (self.flip_x || self.flip_y) {
+			// Reflections across the diagonals
+			push(false, false, true);
+			push(true, true, true);
+		}
+		transforms
+	}
+}
+
+impl TileFlip {
+	/// Get the flip flags currently applied to the given tile
+	pub fn of(tile: &Tile) -> Self {
+		Self {
+			x: tile.flip_x,
+			y: tile.flip_y,
+			d: tile.flip_d,
+		}
+	}
+
+	/// Apply these flip flags to the given tile
+	pub fn apply(&self, tile: &mut Tile) {
+		tile.flip_x = self.x;
+		tile.flip_y = self.y;
+		tile.flip_d = self.d;
+	}
+
+	/// Transform a neighbor direction (with the y-axis pointing up) the same way the texture is transformed
+	fn transform(&self, (mut x, mut y): (i32, i32)) -> (i32, i32) {
+		if self.d {
+			// Transposing the texture reflects it across its anti-diagonal in world space
+			let (old_x, old_y) = (x, y);
+			x = -old_y;
+			y = -old_x;
+		}
+		if self.x {
+			x = -x;
+		}
+		if self.y {
+			y = -y;
+		}
+		(x, y)
+	}
+
+	/// Get the rule that, once drawn with these flip flags, satisfies the given rule
+	pub fn source_rule(&self, rule: &AutoTileRule) -> AutoTileRule {
+		let mut source = *rule;
+		for dir in DIRECTIONS {
+			set_neighbor(&mut source, dir, get_neighbor(rule, self.transform(dir)));
+		}
+		source
+	}
+}
+
+/// The eight neighbor directions
+const DIRECTIONS: [(i32, i32); 8] = [
+	(0, 1),
+	(1, 1),
+	(1, 0),
+	(1, -1),
+	(0, -1),
+	(-1, -1),
+	(-1, 0),
+	(-1, 1),
+];
+
+/// Build the rule describing a tile's actual neighborhood
+pub(crate) fn rule_from_neighbors<F: Fn((i32, i32)) -> bool>(has_neighbor: F) -> AutoTileRule {
+	let mut rule = AutoTileRule::default();
+	for dir in DIRECTIONS {
+		set_neighbor(&mut rule, dir, Some(has_neighbor(dir)));
+	}
+	rule
+}
+
+/// Pack the neighbors required by the given rule into a bitmask
+///
+/// Starting from the least significant bit, the bits represent the north, north-east, east,
+/// south-east, south, south-west, west, and north-west neighbors.
+#[cfg(all(feature = "auto-tile-debug", not(feature = "headless")))]
+pub(crate) fn neighbor_bits(rule: &AutoTileRule) -> u8 {
+	DIRECTIONS
+		.iter()
+		.enumerate()
+		.filter(|(_, dir)| get_neighbor(rule, **dir) == Some(true))
+		.fold(0, |bits, (index, _)| bits | (1 << index))
+}
+
+fn get_neighbor(rule: &AutoTileRule, dir: (i32, i32)) -> Option<bool> {
+	match dir {
+		(0, 1) => rule.north,
+		(1, 1) => rule.north_east,
+		(1, 0) => rule.east,
+		(1, -1) => rule.south_east,
+		(0, -1) => rule.south,
+		(-1, -1) => rule.south_west,
+		(-1, 0) => rule.west,
+		(-1, 1) => rule.north_west,
+		_ => None,
+	}
+}
+
+fn set_neighbor(rule: &mut AutoTileRule, dir: (i32, i32), value: Option<bool>) {
+	match dir {
+		(0, 1) => rule.north = value,
+		(1, 1) => rule.north_east = value,
+		(1, 0) => rule.east = value,
+		(1, -1) => rule.south_east = value,
+		(0, -1) => rule.south = value,
+		(-1, -1) => rule.south_west = value,
+		(-1, 0) => rule.west = value,
+		(-1, 1) => rule.north_west = value,
+		_ => {},
+	}
+}
+
+/// The result of resolving an auto tile rule using the group's symmetry
+pub(crate) enum SymmetricMatch {
+	/// The tile already displays a valid (transformed) piece for the rule
+	Unchanged,
+	/// The rule can be satisfied by drawing the given index with the given flip flags
+	Found(TileIndex, TileFlip),
+}
+
+/// Attempts to satisfy the given rule by transforming one of the group's existing pieces
+///
+/// The `auto_flip` should be the tile's current flip flags, if they were set by the auto tiler.
+/// Transformed pieces are selected among the variants of their source rule.
+pub(crate) fn resolve_symmetric(
+	tileset: &Tileset,
+	tile_name: &str,
+	texture_index: usize,
+	auto_flip: Option<TileFlip>,
+	rule: &AutoTileRule,
+	symmetry: &AutoTileSymmetry,
+	selector: &AutoVariantSelector,
+) -> Option<SymmetricMatch> {
+	for flip in symmetry.transforms() {
+		let source = flip.source_rule(rule);
+		if auto_flip == Some(flip) && tileset.is_auto_variant(tile_name, &texture_index, &source) {
+			return Some(SymmetricMatch::Unchanged);
+		}
+
+		if let Some(index) = selector.select(tileset, tile_name, source) {
+			return Some(SymmetricMatch::Found(index, flip));
+		}
+	}
+
+	None
+}
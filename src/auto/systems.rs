@@ -1,11 +1,26 @@
-use crate::auto::traits::{TileInfo, TilemapCache};
-use bevy::prelude::{Changed, Commands, Entity, EventReader, Query, With};
+use crate::activity::ActiveChunks;
+use crate::auto::mask::{AutoTileMask, AutoTileMasked};
+use crate::auto::symmetry::{
+	resolve_symmetric, AutoTileFlipped, AutoTileSymmetry, SymmetricMatch, TileFlip,
+};
+use crate::auto::traits::{layer_lattice, AutoNeighborQuery, TileInfo, TilemapCache};
+use crate::auto::variants::AutoVariantSelector;
+use crate::combined::{offset_index, CombinedTilesets};
+use crate::config::TilesetMapConfig;
+use crate::metadata::TileMetadataRegistry;
+use crate::placement::dephase_speed;
+use bevy::prelude::{Changed, Commands, Entity, EventReader, Local, Mut, Or, Query, Res, With};
+use bevy::tasks::{ComputeTaskPool, ParallelSlice, TaskPool};
+use bevy::utils::HashSet;
 use bevy_ecs_tilemap::{GPUAnimated, MapQuery, Tile, TileParent, TilePos};
 use bevy_tileset::auto::{AutoTileId, AutoTileRequest, AutoTiler};
+use bevy_tileset::prelude::AutoTileRule;
 use bevy_tileset::prelude::{TileIndex, Tilesets};
 use std::cell::RefCell;
 
 /// An event used to notify the system of a removed/replaced auto tile
+///
+/// This is also sent for tiles with an [`AutoTileMask`], using the ID of their group.
 pub struct RemoveAutoTileEvent {
 	pub entity: Entity,
 	pub pos: TilePos,
@@ -16,14 +31,26 @@ pub struct RemoveAutoTileEvent {
 /// __\[SYSTEM\]__ Handles the creation/modification of an auto tile
 ///
 /// This system chooses the appropriate texture based on its surrounding neighbors,
-/// and updates any neighbors of the same type in a similar manner
+/// and updates any neighbors of the same type in a similar manner. Placing a tile with an
+/// [`AutoTileMask`] updates its neighbors as well.
+///
+/// Tiles outside the [`ActiveChunks`] are deferred until their chunk becomes active.
 pub(crate) fn on_change_auto_tile(
 	mut commands: Commands,
 	changed_tiles: Query<
-		(Entity, &TilePos, &TileParent, &AutoTileId),
-		(Changed<AutoTileId>, With<Tile>),
+		(
+			Entity,
+			&TilePos,
+			&TileParent,
+			Option<&AutoTileId>,
+			Option<&AutoTileMasked>,
+		),
+		(
+			With<Tile>,
+			Or<(Changed<AutoTileId>, Changed<AutoTileMasked>)>,
+		),
 	>,
-	all_tiles: Query<(Entity, &TilePos, &TileParent, &AutoTileId), With<Tile>>,
+	all_tiles: AutoNeighborQuery,
 	mut working_tiles: Query<(
 		Entity,
 		&TilePos,
@@ -31,24 +58,63 @@ pub(crate) fn on_change_auto_tile(
 		&TileParent,
 		&AutoTileId,
 		Option<&mut GPUAnimated>,
+		Option<&AutoTileFlipped>,
 	)>,
 	tilesets: Tilesets,
-	map_query: MapQuery,
+	metadata: Res<TileMetadataRegistry>,
+	combined: Res<CombinedTilesets>,
+	config: Res<TilesetMapConfig>,
+	active: Res<ActiveChunks>,
+	pool: Res<ComputeTaskPool>,
+	mut deferred: Local<HashSet<Entity>>,
+	mut map_query: MapQuery,
 ) {
+	// Split the changes into active and deferred tiles
+	let is_active = |pos: &TilePos, parent: &TileParent| {
+		active.is_tile_active(parent.map_id, parent.layer_id, *pos)
+	};
+	let mut pending = Vec::new();
+	for (entity, pos, parent, auto_tile, masked) in changed_tiles.iter() {
+		if is_active(pos, parent) {
+			deferred.remove(&entity);
+			pending.push((entity, pos, parent, auto_tile, masked));
+		} else {
+			deferred.insert(entity);
+		}
+	}
+
+	// Resume any deferred tiles that have since become active (or forget despawned ones)
+	deferred.retain(|entity| match all_tiles.get(*entity) {
+		Ok((entity, pos, parent, auto_tile, masked)) if is_active(pos, parent) => {
+			pending.push((entity, pos, parent, auto_tile, masked));
+			false
+		},
+		Ok(..) => true,
+		Err(..) => false,
+	});
+
 	// Ensure a change happened
-	if changed_tiles.iter().count() < 1 {
+	if pending.is_empty() {
 		return;
 	}
 
-	let mut map_query_cell = RefCell::new(map_query);
+	let pending = pending
+		.into_iter()
+		.filter_map(|(entity, pos, parent, auto_tile, masked)| {
+			let lattice = layer_lattice(&mut map_query, parent.map_id, parent.layer_id);
+			TileInfo::from_components(entity, pos, parent, auto_tile, masked, lattice)
+		})
+		.collect::<Vec<_>>();
+
+	let map_query_cell = RefCell::new(&mut map_query);
 	let mut cache = TilemapCache {
 		tiles_query: &all_tiles,
 		map_query: &map_query_cell,
 	};
 	let mut tiler = AutoTiler::new(&mut cache);
 
-	for (entity, pos, parent, auto_tile) in changed_tiles.iter() {
-		tiler.add_tile(TileInfo::new(entity, pos, parent, auto_tile), true);
+	for tile in pending {
+		tiler.add_tile(tile, true);
 	}
 
 	let requests = tiler.finish();
@@ -56,9 +122,13 @@ pub(crate) fn on_change_auto_tile(
 	apply_requests(
 		&requests,
 		&tilesets,
+		&metadata,
+		&combined,
+		&config,
 		&mut working_tiles,
 		&mut commands,
-		map_query_cell.get_mut(),
+		&mut map_query,
+		&pool,
 	);
 }
 
@@ -70,7 +140,7 @@ pub(crate) fn on_change_auto_tile(
 pub(crate) fn on_remove_auto_tile(
 	mut event: EventReader<RemoveAutoTileEvent>,
 	// All tiles (used for the tilemap cache)
-	all_tiles: Query<(Entity, &TilePos, &TileParent, &AutoTileId), With<Tile>>,
+	all_tiles: AutoNeighborQuery,
 	mut working_tiles: Query<(
 		Entity,
 		&TilePos,
@@ -78,26 +148,44 @@ pub(crate) fn on_remove_auto_tile(
 		&TileParent,
 		&AutoTileId,
 		Option<&mut GPUAnimated>,
+		Option<&AutoTileFlipped>,
 	)>,
 	tilesets: Tilesets,
-	map_query: MapQuery,
+	metadata: Res<TileMetadataRegistry>,
+	combined: Res<CombinedTilesets>,
+	config: Res<TilesetMapConfig>,
+	pool: Res<ComputeTaskPool>,
+	mut map_query: MapQuery,
 	mut commands: Commands,
 ) {
-	let mut map_query_cell = RefCell::new(map_query);
+	let removed = event
+		.iter()
+		.map(|evt| {
+			let RemoveAutoTileEvent {
+				entity,
+				pos,
+				parent,
+				auto_id,
+			} = evt;
+			let lattice = layer_lattice(&mut map_query, parent.map_id, parent.layer_id);
+			// The tile is gone, so its mask has to be looked up from its group
+			let mask = tilesets.get_by_id(&auto_id.tileset_id).and_then(|tileset| {
+				let tile_name = tileset.get_tile_name(&auto_id.group_id)?;
+				AutoTileMask::from_registry(&metadata, tileset, tile_name)
+			});
+			TileInfo::new(*entity, pos, parent, auto_id, mask, lattice)
+		})
+		.collect::<Vec<_>>();
+
+	let map_query_cell = RefCell::new(&mut map_query);
 	let mut cache = TilemapCache {
 		tiles_query: &all_tiles,
 		map_query: &map_query_cell,
 	};
 	let mut tiler = AutoTiler::new(&mut cache);
 
-	for ref evt in event.iter() {
-		let RemoveAutoTileEvent {
-			entity,
-			pos,
-			parent,
-			auto_id,
-		} = evt;
-		tiler.add_tile(TileInfo::new(*entity, pos, parent, auto_id), true);
+	for tile in removed {
+		tiler.add_tile(tile, true);
 	}
 
 	let requests = tiler.finish();
@@ -105,16 +193,63 @@ pub(crate) fn on_remove_auto_tile(
 	apply_requests(
 		&requests,
 		&tilesets,
+		&metadata,
+		&combined,
+		&config,
 		&mut working_tiles,
 		&mut commands,
-		map_query_cell.get_mut(),
+		&mut map_query,
+		&pool,
 	);
 }
 
+/// Requests beyond this count are resolved in parallel
+///
+/// Below it, the overhead of spawning tasks outweighs the work saved.
+const PARALLEL_THRESHOLD: usize = 256;
+
+/// The number of requests resolved by each parallel task
+const PARALLEL_BATCH_SIZE: usize = 64;
+
+/// A read-only snapshot of everything needed to resolve a single request
+pub(super) struct ResolveInput {
+	pub entity: Entity,
+	pub pos: TilePos,
+	pub map_id: u16,
+	pub layer_id: u16,
+	pub auto_tile: AutoTileId,
+	pub rule: AutoTileRule,
+	pub texture_index: usize,
+	/// The tile's current flip flags, if they were set by the auto tiler
+	pub auto_flip: Option<TileFlip>,
+	/// Whether the tile was flipped by the user (rather than by the auto tiler)
+	pub user_flipped: bool,
+	/// The seed used to select among the rule's variants
+	pub variant_seed: Option<u64>,
+}
+
+/// The new texture of a tile, resolved from its request
+pub(super) struct Resolution {
+	pub entity: Entity,
+	pub pos: TilePos,
+	pub map_id: u16,
+	pub layer_id: u16,
+	/// The index to display, already offset and dephased
+	pub index: TileIndex,
+	/// The flip flags needed to display the index, if it is a transformed piece
+	pub flip: Option<TileFlip>,
+}
+
 /// Applies the given rule requests
+///
+/// Requests only depend on the (unchanging) state of their own tile, so their new textures are
+/// resolved in parallel for large edits (such as loading a map) before being written serially.
 fn apply_requests(
 	requests: &[AutoTileRequest<TileInfo>],
 	tilesets: &Tilesets,
+	metadata: &TileMetadataRegistry,
+	combined: &CombinedTilesets,
+	config: &TilesetMapConfig,
 	query: &mut Query<(
 		Entity,
 		&TilePos,
@@ -122,59 +257,357 @@ fn apply_requests(
 		&TileParent,
 		&AutoTileId,
 		Option<&mut GPUAnimated>,
+		Option<&AutoTileFlipped>,
 	)>,
 	commands: &mut Commands,
 	map_query: &mut MapQuery,
+	pool: &TaskPool,
 ) {
-	for request in requests.iter() {
-		let rule = request.rule;
-		let TileInfo { entity, .. } = request.tile;
-		if let Ok((.., pos, ref mut tile, parent, auto_tile, ref mut anim)) = query.get_mut(entity)
+	// --- Snapshot --- //
+	let inputs = requests
+		.iter()
+		.filter_map(|request| {
+			let (entity, pos, tile, parent, auto_tile, _, flipped) =
+				query.get(request.tile.entity).ok()?;
+			Some(ResolveInput {
+				entity,
+				pos: *pos,
+				map_id: parent.map_id,
+				layer_id: parent.layer_id,
+				auto_tile: *auto_tile,
+				rule: request.rule,
+				texture_index: tile.texture_index as usize,
+				auto_flip: flipped.map(|_| TileFlip::of(tile)),
+				user_flipped: is_user_flipped(tile, flipped.is_some()),
+				variant_seed: config.get(parent.map_id).variant_seed,
+			})
+		})
+		.collect::<Vec<_>>();
+
+	// --- Resolve --- //
+	let resolve = |input: &ResolveInput| resolve_request(input, tilesets, metadata, combined);
+	let resolutions = if inputs.len() < PARALLEL_THRESHOLD {
+		inputs.iter().filter_map(resolve).collect::<Vec<_>>()
+	} else {
+		inputs
+			.par_chunk_map(pool, PARALLEL_BATCH_SIZE, |batch| {
+				batch.iter().filter_map(resolve).collect::<Vec<_>>()
+			})
+			.into_iter()
+			.flatten()
+			.collect()
+	};
+
+	// --- Apply --- //
+	// Only the texture index (and any flips previously set by the auto tiler) are rewritten: the
+	// tile's color, visibility, and user-defined flips are preserved
+	for resolution in resolutions {
+		if let Ok((.., ref mut tile, _, _, ref mut anim, flipped)) =
+			query.get_mut(resolution.entity)
 		{
-			if let Some(tileset) = tilesets.get_by_id(&auto_tile.tileset_id) {
-				if let Some(tile_name) = tileset.get_tile_name(&auto_tile.group_id) {
-					// --- Check If Variant --- //
-					let texture_index = tile.texture_index as usize;
-					if tileset.is_auto_variant(tile_name, &texture_index, &rule) {
-						// The request index is just a variant of the correct state -> skip it
-						continue;
-					}
-
-					// --- Apply Rule --- //
-					if let Some(index) = tileset.get_auto_index(tile_name, rule) {
-						match index {
-							TileIndex::Standard(idx) => {
-								tile.texture_index = idx as u16;
-
-								// Remove animated if it exists
-								if anim.is_some() {
-									commands.entity(entity).remove::<GPUAnimated>();
-								}
-							},
-							TileIndex::Animated(start, end, speed) => {
-								// Even though this texture index isn't seen (due to `GPUAnimated`), we still need to set this
-								// so that the system can maintain the same variant across state changes
-								tile.texture_index = start as u16;
-
-								if let Some(anim) = anim {
-									anim.start = start as u32;
-									anim.end = end as u32;
-									anim.speed = speed;
-								} else {
-									commands.entity(entity).insert(GPUAnimated::new(
-										start as u32,
-										end as u32,
-										speed,
-									));
-								}
-							},
-						}
-
-						// --- Notify Chunk --- //
-						map_query.notify_chunk_for_tile(*pos, parent.map_id, parent.layer_id);
-					}
-				}
+			apply_resolution(&resolution, tile, anim, flipped.is_some(), commands);
+
+			// --- Notify Chunk --- //
+			map_query.notify_chunk_for_tile(resolution.pos, resolution.map_id, resolution.layer_id);
+		}
+	}
+}
+
+/// Resolves the new texture of a single request
+///
+/// Returns `None` if the tile should be left as-is.
+pub(super) fn resolve_request(
+	input: &ResolveInput,
+	tilesets: &Tilesets,
+	metadata: &TileMetadataRegistry,
+	combined: &CombinedTilesets,
+) -> Option<Resolution> {
+	let tileset = tilesets.get_by_id(&input.auto_tile.tileset_id)?;
+	let tile_name = tileset.get_tile_name(&input.auto_tile.group_id)?;
+	let rule = input.rule;
+	// Indices of combined layers are shifted into the combined texture
+	let offset = combined.offset(tileset.id(), input.map_id, input.layer_id);
+
+	// --- Check If Variant --- //
+	let texture_index = input.texture_index.saturating_sub(offset);
+	let selector = AutoVariantSelector {
+		pos: input.pos,
+		seed: input.variant_seed,
+	};
+	if input.auto_flip.is_none() && selector.is_selected(tileset, tile_name, rule, texture_index) {
+		// The request index is already the selected variant of the correct state -> skip it
+		return None;
+	}
+
+	// --- Resolve Rule --- //
+	let (index, flip) = if let Some(index) = selector.select(tileset, tile_name, rule) {
+		(index, None)
+	} else if input.user_flipped {
+		// Synthesizing the missing piece would overwrite the user's flips
+		return None;
+	} else {
+		// Synthesize the missing piece by transforming an existing one
+		let symmetry = AutoTileSymmetry::from_registry(metadata, tileset, tile_name)?;
+		match resolve_symmetric(
+			tileset,
+			tile_name,
+			texture_index,
+			input.auto_flip,
+			&rule,
+			&symmetry,
+			&selector,
+		)? {
+			SymmetricMatch::Found(index, flip) => (index, Some(flip)),
+			SymmetricMatch::Unchanged => return None,
+		}
+	};
+
+	let index = match offset_index(index, offset) {
+		TileIndex::Animated(start, end, speed) => {
+			let tile_metadata = metadata.get(tileset.name(), tile_name);
+			TileIndex::Animated(start, end, dephase_speed(speed, input.pos, tile_metadata))
+		},
+		index => index,
+	};
+
+	Some(Resolution {
+		entity: input.entity,
+		pos: input.pos,
+		map_id: input.map_id,
+		layer_id: input.layer_id,
+		index,
+		flip,
+	})
+}
+
+/// Returns true if the given tile has flip flags that weren't set by the auto tiler
+pub(super) fn is_user_flipped(tile: &Tile, is_auto_flipped: bool) -> bool {
+	!is_auto_flipped && TileFlip::of(tile) != TileFlip::default()
+}
+
+/// Writes the given resolution to its tile
+///
+/// Only the texture index (and any flips set by the auto tiler) are rewritten.
+fn apply_resolution(
+	resolution: &Resolution,
+	tile: &mut Tile,
+	anim: &mut Option<Mut<GPUAnimated>>,
+	is_auto_flipped: bool,
+	commands: &mut Commands,
+) {
+	let entity = resolution.entity;
+	match resolution.flip {
+		Some(flip) => {
+			flip.apply(tile);
+			if !is_auto_flipped {
+				commands.entity(entity).insert(AutoTileFlipped);
 			}
+		},
+		None if is_auto_flipped => {
+			TileFlip::default().apply(tile);
+			commands.entity(entity).remove::<AutoTileFlipped>();
+		},
+		None => {},
+	}
+	apply_index(entity, resolution.index, tile, anim, commands);
+}
+
+/// Sets the given tile's texture index (and animation), leaving all other fields untouched
+fn apply_index(
+	entity: Entity,
+	index: TileIndex,
+	tile: &mut Tile,
+	anim: &mut Option<Mut<GPUAnimated>>,
+	commands: &mut Commands,
+) {
+	match index {
+		TileIndex::Standard(idx) => {
+			tile.texture_index = idx as u16;
+
+			// Remove animated if it exists
+			if anim.is_some() {
+				commands.entity(entity).remove::<GPUAnimated>();
+			}
+		},
+		TileIndex::Animated(start, end, speed) => {
+			// Even though this texture index isn't seen (due to `GPUAnimated`), we still need to set this
+			// so that the system can maintain the same variant across state changes
+			tile.texture_index = start as u16;
+
+			if let Some(anim) = anim {
+				anim.start = start as u32;
+				anim.end = end as u32;
+				anim.speed = speed;
+			} else {
+				// Headless builds have nothing to animate and simply display the first frame
+				#[cfg(not(feature = "headless"))]
+				commands
+					.entity(entity)
+					.insert(GPUAnimated::new(start as u32, end as u32, speed));
+				#[cfg(feature = "headless")]
+				let _ = (end, speed);
+			}
+		},
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use bevy::ecs::system::CommandQueue;
+	use bevy::prelude::{Color, World};
+
+	/// A tinted, hidden tile flipped by the user
+	fn tinted_tile() -> Tile {
+		Tile {
+			texture_index: 0,
+			color: Color::RED,
+			visible: false,
+			flip_x: true,
+			..Default::default()
+		}
+	}
+
+	fn resolution(entity: Entity, index: usize, flip: Option<TileFlip>) -> Resolution {
+		Resolution {
+			entity,
+			pos: TilePos(1, 1),
+			map_id: 0,
+			layer_id: 0,
+			index: TileIndex::Standard(index),
+			flip,
+		}
+	}
+
+	/// Apply the given resolution to a spawned tile, returning the updated tile and whether it
+	/// ends up marked as flipped by the auto tiler
+	fn apply(mut tile: Tile, is_auto_flipped: bool, flip: Option<TileFlip>) -> (Tile, bool) {
+		let mut world = World::new();
+		let entity = world.spawn().id();
+		if is_auto_flipped {
+			world.entity_mut(entity).insert(AutoTileFlipped);
 		}
+
+		let mut queue = CommandQueue::default();
+		let mut commands = Commands::new(&mut queue, &world);
+		apply_resolution(
+			&resolution(entity, 5, flip),
+			&mut tile,
+			&mut None,
+			is_auto_flipped,
+			&mut commands,
+		);
+		queue.apply(&mut world);
+
+		(tile, world.get::<AutoTileFlipped>(entity).is_some())
+	}
+
+	#[test]
+	fn tinted_tile_survives_neighbor_update() {
+		let (tile, is_auto_flipped) = apply(tinted_tile(), false, None);
+		assert_eq!(tile.texture_index, 5);
+		assert_eq!(tile.color, Color::RED);
+		assert!(!tile.visible);
+		assert!(tile.flip_x);
+		assert!(!is_auto_flipped);
+	}
+
+	#[test]
+	fn tinted_tile_survives_synthesized_piece() {
+		let tile = Tile {
+			flip_x: false,
+			..tinted_tile()
+		};
+		let flip = TileFlip {
+			x: false,
+			y: true,
+			d: false,
+		};
+		let (tile, is_auto_flipped) = apply(tile, false, Some(flip));
+		assert_eq!(tile.texture_index, 5);
+		assert_eq!(tile.color, Color::RED);
+		assert!(!tile.visible);
+		assert_eq!(TileFlip::of(&tile), flip);
+		assert!(is_auto_flipped);
+	}
+
+	#[test]
+	fn tinted_tile_survives_leaving_synthesized_piece() {
+		let (tile, is_auto_flipped) = apply(tinted_tile(), true, None);
+		assert_eq!(tile.texture_index, 5);
+		assert_eq!(tile.color, Color::RED);
+		assert!(!tile.visible);
+		assert_eq!(TileFlip::of(&tile), TileFlip::default());
+		assert!(!is_auto_flipped);
+	}
+
+	#[test]
+	fn user_flips_are_detected() {
+		assert!(is_user_flipped(&tinted_tile(), false));
+		assert!(!is_user_flipped(&tinted_tile(), true));
+		assert!(!is_user_flipped(&Tile::default(), false));
+	}
+
+	#[cfg(not(feature = "headless"))]
+	#[test]
+	fn tinted_tile_survives_auto_tiling_systems() {
+		use crate::placement::TilePlacementOptions;
+		use crate::testing::{self, TILESET_ID};
+		use bevy::ecs::system::SystemState;
+		use bevy::prelude::App;
+		use bevy_tileset::prelude::{
+			AutoTileHandle, AutoTileRule, SimpleTileHandle, TileHandle, TileId, Tilesets,
+			VariantTileHandle,
+		};
+
+		let mut app = testing::app();
+		let auto_tile = |app: &mut App, color, rule| AutoTileHandle {
+			rule,
+			variants: vec![VariantTileHandle {
+				weight: 1.0,
+				tile: SimpleTileHandle::Standard(testing::texture(app, color)),
+			}],
+		};
+		let east = AutoTileRule {
+			east: Some(true),
+			..Default::default()
+		};
+		let handles = vec![
+			auto_tile(&mut app, [255, 0, 0, 255], east),
+			auto_tile(&mut app, [0, 0, 255, 255], AutoTileRule::default()),
+		];
+		testing::add_tileset(&mut app, vec![TileHandle::new_auto("Wall", handles)]);
+		testing::spawn_map(&mut app);
+
+		let wall = TileId::new(0, TILESET_ID);
+		let options = TilePlacementOptions {
+			color: Color::RED,
+			flip_x: true,
+			..Default::default()
+		};
+		testing::with_placer(&mut app, |placer| {
+			placer
+				.place_with(wall, TilePos(1, 1), 0u16, 0, options)
+				.unwrap();
+		});
+		app.update();
+		let placed = testing::get_tile(&mut app, TilePos(1, 1)).unwrap();
+
+		// Placing a neighbor lets the auto tiling systems update the tinted tile
+		testing::place(&mut app, wall, TilePos(2, 1));
+		app.update();
+
+		let mut state = SystemState::<Tilesets>::new(&mut app.world);
+		let tilesets = state.get_mut(&mut app.world);
+		let tileset = tilesets.get_by_id(&TILESET_ID).unwrap();
+		let east = *tileset.get_auto_index("Wall", east).unwrap().base_index();
+
+		let tile = testing::get_tile(&mut app, TilePos(1, 1)).unwrap();
+		assert_ne!(tile.texture_index, placed.texture_index);
+		assert_eq!(tile.texture_index as usize, east);
+		assert_eq!(tile.color, Color::RED);
+		assert!(tile.flip_x);
+		assert!(!tile.flip_y);
+		assert!(!tile.flip_d);
 	}
 }
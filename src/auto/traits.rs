@@ -1,24 +1,39 @@
-use crate::coord::TileCoord;
+use crate::auto::mask::{AutoTileMask, AutoTileMasked};
+use crate::coord::{Lattice, TileCoord};
 use bevy::math::IVec2;
-use bevy::prelude::{Entity, Query, With};
+use bevy::prelude::{Entity, Or, Query, With};
 use bevy_ecs_tilemap::{MapQuery, Tile, TileParent, TilePos};
 use bevy_tileset::auto::{AutoTile, AutoTileId};
+use bevy_tileset::tileset::coords::TileCoords;
 use std::cell::RefCell;
 
-/// A trait over `Query<'w, 's, (Entity, &TilePos, &TileParent, &AutoTile), With<Tile>>` to prevent errors with
+/// The query used to find every tile considered by the auto tiler
+///
+/// This includes Auto tiles as well as any other tile with an [`AutoTileMask`].
+pub(crate) type AutoNeighborQuery<'w, 's> = Query<
+	'w,
+	's,
+	(
+		Entity,
+		&'static TilePos,
+		&'static TileParent,
+		Option<&'static AutoTileId>,
+		Option<&'static AutoTileMasked>,
+	),
+	(With<Tile>, Or<(With<AutoTileId>, With<AutoTileMasked>)>),
+>;
+
+/// A trait over [`AutoNeighborQuery`] to prevent errors with
 /// "explicit lifetime required in the type of `query`"
 pub(super) trait TileQuery {
-	fn find_tile(&self, entity: Entity) -> Option<TileInfo>;
+	fn find_tile(&self, entity: Entity, lattice: Lattice) -> Option<TileInfo>;
 	fn count(&self) -> usize;
 }
 
-impl<'w, 's> TileQuery for Query<'w, 's, (Entity, &TilePos, &TileParent, &AutoTileId), With<Tile>> {
-	fn find_tile(&self, entity: Entity) -> Option<TileInfo> {
-		if let Ok((entity, pos, parent, auto_tile)) = self.get(entity) {
-			Some(TileInfo::new(entity, pos, parent, auto_tile))
-		} else {
-			None
-		}
+impl<'w, 's> TileQuery for AutoNeighborQuery<'w, 's> {
+	fn find_tile(&self, entity: Entity, lattice: Lattice) -> Option<TileInfo> {
+		let (entity, pos, parent, auto_tile, masked) = self.get(entity).ok()?;
+		TileInfo::from_components(entity, pos, parent, auto_tile, masked, lattice)
 	}
 
 	fn count(&self) -> usize {
@@ -32,32 +47,87 @@ pub(super) struct TileInfo {
 	pub coord: TileCoord,
 	pub entity: Entity,
 	pub auto_tile: bevy_tileset::auto::AutoTileId,
+	/// How the tile is treated by its neighbors
+	pub mask: Option<AutoTileMask>,
+	/// The lattice of the tile's layer, used to find its neighbors
+	pub lattice: Lattice,
+}
+
+/// The coordinates of an auto tile within the lattice of its layer
+///
+/// This allows the (square) neighbor rules of auto tiles to be applied to hex and isometric maps.
+#[derive(Copy, Clone, Debug, Default, Hash, Eq, PartialEq)]
+pub(super) struct AutoCoord {
+	pub coord: TileCoord,
+	pub lattice: Lattice,
+}
+
+impl TileCoords for AutoCoord {
+	fn pos(&self) -> IVec2 {
+		self.lattice.project(self.coord.pos)
+	}
+}
+
+/// Get the [`Lattice`] of the given layer
+pub(super) fn layer_lattice(map_query: &mut MapQuery, map_id: u16, layer_id: u16) -> Lattice {
+	map_query
+		.get_layer(map_id, layer_id)
+		.map(|(_, layer)| Lattice::from(layer.settings.mesh_type))
+		.unwrap_or_default()
 }
 
 pub(super) struct TilemapCache<'a, 'w, 's> {
 	pub tiles_query: &'a dyn TileQuery,
-	pub map_query: &'a RefCell<MapQuery<'w, 's>>,
+	pub map_query: &'a RefCell<&'a mut MapQuery<'w, 's>>,
 }
 
 impl TileInfo {
-	pub fn new(entity: Entity, pos: &TilePos, parent: &TileParent, auto_tile: &AutoTileId) -> Self {
+	pub fn new(
+		entity: Entity,
+		pos: &TilePos,
+		parent: &TileParent,
+		auto_tile: &AutoTileId,
+		mask: Option<AutoTileMask>,
+		lattice: Lattice,
+	) -> Self {
 		Self {
 			entity,
 			auto_tile: *auto_tile,
+			mask,
 			coord: TileCoord {
 				pos: *pos,
 				map_id: parent.map_id,
 				layer_id: parent.layer_id,
 			},
+			lattice,
 		}
 	}
+
+	/// Create the info of a tile found using the [`AutoNeighborQuery`]
+	///
+	/// Returns `None` if the tile is neither an Auto tile nor masked.
+	pub fn from_components(
+		entity: Entity,
+		pos: &TilePos,
+		parent: &TileParent,
+		auto_tile: Option<&AutoTileId>,
+		masked: Option<&AutoTileMasked>,
+		lattice: Lattice,
+	) -> Option<Self> {
+		let auto_tile = auto_tile.or_else(|| masked.map(|masked| &masked.auto_id))?;
+		let mask = masked.map(|masked| masked.mask);
+		Some(Self::new(entity, pos, parent, auto_tile, mask, lattice))
+	}
 }
 
 impl bevy_tileset::auto::AutoTile for TileInfo {
-	type Coords = TileCoord;
+	type Coords = AutoCoord;
 
 	fn coords(&self) -> Self::Coords {
-		self.coord
+		AutoCoord {
+			coord: self.coord,
+			lattice: self.lattice,
+		}
 	}
 
 	fn auto_id(&self) -> bevy_tileset::auto::AutoTileId {
@@ -67,7 +137,8 @@ impl bevy_tileset::auto::AutoTile for TileInfo {
 	fn can_match(&self, other: &Self) -> bool {
 		self.coord.map_id == other.coord.map_id
 			&& self.coord.layer_id == other.coord.layer_id
-			&& self.auto_tile == other.auto_tile
+			&& AutoTileMask::matches(self.mask, other.mask)
+				.unwrap_or(self.auto_tile == other.auto_tile)
 	}
 }
 
@@ -79,20 +150,29 @@ impl<'a, 'w, 's> bevy_tileset::auto::AutoTilemap for TilemapCache<'a, 'w, 's> {
 		pos: IVec2,
 		template: &<Self::Tile as AutoTile>::Coords,
 	) -> <Self::Tile as AutoTile>::Coords {
-		TileCoord {
-			pos: pos.as_uvec2().into(),
-			map_id: template.map_id,
-			layer_id: template.layer_id,
+		// Positions outside the map are pushed out of bounds so that no tile is found there
+		let pos = template
+			.lattice
+			.unproject(pos)
+			.unwrap_or(TilePos(u32::MAX, u32::MAX));
+		AutoCoord {
+			coord: TileCoord {
+				pos,
+				map_id: template.coord.map_id,
+				layer_id: template.coord.layer_id,
+			},
+			lattice: template.lattice,
 		}
 	}
 
 	fn get_tile_at(&self, coords: &<Self::Tile as AutoTile>::Coords) -> Option<Self::Tile> {
+		let AutoCoord { coord, lattice } = coords;
 		let entity =
 			self.map_query
 				.borrow_mut()
-				.get_tile_entity(coords.pos, coords.map_id, coords.layer_id);
+				.get_tile_entity(coord.pos, coord.map_id, coord.layer_id);
 		if let Ok(entity) = entity {
-			self.tiles_query.find_tile(entity)
+			self.tiles_query.find_tile(entity, *lattice)
 		} else {
 			None
 		}
@@ -0,0 +1,147 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_ecs_tilemap::prelude::*;
+use bevy_tileset::auto::AutoTileId;
+use bevy_tileset::prelude::AutoTileRule;
+use bevy_tileset::prelude::Tilesets;
+
+use crate::activity::ActiveChunks;
+use crate::auto::mask::{AutoTileMask, AutoTileMasked};
+use crate::auto::symmetry::{rule_from_neighbors, AutoTileFlipped};
+use crate::coord::TileCoord;
+
+/// The query used to validate Auto tiles
+///
+/// This includes masked tiles, since they are counted as neighbors as well.
+pub(crate) type AutoTileQuery<'w, 's> = Query<
+	'w,
+	's,
+	(
+		Entity,
+		&'static TilePos,
+		&'static TileParent,
+		&'static Tile,
+		Option<&'static AutoTileId>,
+		Option<&'static AutoTileFlipped>,
+		Option<&'static AutoTileMasked>,
+	),
+	Or<(With<AutoTileId>, With<AutoTileMasked>)>,
+>;
+
+/// The state of an Auto tile, as derived from its neighborhood
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct AutoTileEvaluation {
+	pub entity: Entity,
+	pub coord: TileCoord,
+	pub tile_name: String,
+	pub texture_index: u16,
+	/// The rule describing the tile's actual neighborhood
+	pub rule: AutoTileRule,
+	/// Whether the group defines a texture for the rule
+	pub has_texture: bool,
+	/// Whether the tile displays a texture matching the rule
+	pub is_match: bool,
+	/// Whether the tile displays a symmetric piece (which is validated by the auto tiler itself)
+	pub is_flipped: bool,
+}
+
+/// An Auto tile displaying a texture that doesn't match its neighborhood
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct AutoTileMismatch {
+	pub entity: Entity,
+	pub coord: TileCoord,
+	pub tile_name: String,
+	pub texture_index: u16,
+}
+
+/// Evaluate all Auto tiles in active chunks against their neighborhood
+///
+/// Tiles in inactive chunks are deferred by the auto tiler, so they're skipped.
+pub(crate) fn evaluate_auto_tiles(
+	auto_tiles: &AutoTileQuery,
+	tilesets: &Tilesets,
+	active: &ActiveChunks,
+) -> Vec<AutoTileEvaluation> {
+	let lookup = auto_tiles
+		.iter()
+		.map(|(_, pos, parent, _, auto_id, _, masked)| {
+			(
+				TileCoord::new(*pos, parent.map_id, parent.layer_id),
+				(auto_id.copied(), masked.map(|masked| masked.mask)),
+			)
+		})
+		.collect::<HashMap<_, _>>();
+
+	let mut evaluations = Vec::new();
+	for (entity, pos, parent, tile, auto_id, flipped, masked) in auto_tiles.iter() {
+		let auto_id = if let Some(auto_id) = auto_id {
+			auto_id
+		} else {
+			continue;
+		};
+		if !active.is_tile_active(parent.map_id, parent.layer_id, *pos) {
+			continue;
+		}
+
+		let tileset = if let Some(tileset) = tilesets.get_by_id(&auto_id.tileset_id) {
+			tileset
+		} else {
+			continue;
+		};
+		let tile_name = if let Some(tile_name) = tileset.get_tile_name(&auto_id.group_id) {
+			tile_name
+		} else {
+			continue;
+		};
+
+		let coord = TileCoord::new(*pos, parent.map_id, parent.layer_id);
+		let mask = masked.map(|masked| masked.mask);
+		let rule = rule_from_neighbors(|(dx, dy)| {
+			let neighbor = coord
+				.offset(IVec2::new(dx, dy))
+				.and_then(|neighbor| lookup.get(&neighbor));
+			match neighbor {
+				Some((neighbor_id, neighbor_mask)) => AutoTileMask::matches(mask, *neighbor_mask)
+					.unwrap_or(neighbor_id.as_ref() == Some(auto_id)),
+				None => false,
+			}
+		});
+
+		let texture_index = tile.texture_index as usize;
+		evaluations.push(AutoTileEvaluation {
+			entity,
+			coord,
+			tile_name: tile_name.to_string(),
+			texture_index: tile.texture_index,
+			rule,
+			has_texture: tileset.get_auto_index(tile_name, rule).is_some(),
+			is_match: tileset.is_auto_variant(tile_name, &texture_index, &rule),
+			is_flipped: flipped.is_some(),
+		});
+	}
+	evaluations
+}
+
+/// Find all Auto tiles whose texture doesn't match their neighborhood
+///
+/// Symmetric pieces are validated by the auto tiler itself and tiles in inactive chunks are
+/// deferred, so neither is reported.
+pub(crate) fn find_auto_tile_mismatches(
+	auto_tiles: &AutoTileQuery,
+	tilesets: &Tilesets,
+	active: &ActiveChunks,
+) -> Vec<AutoTileMismatch> {
+	evaluate_auto_tiles(auto_tiles, tilesets, active)
+		.into_iter()
+		// Only report tiles for which a matching texture actually exists
+		.filter(|evaluation| {
+			!evaluation.is_flipped && evaluation.has_texture && !evaluation.is_match
+		})
+		.map(|evaluation| AutoTileMismatch {
+			entity: evaluation.entity,
+			coord: evaluation.coord,
+			tile_name: evaluation.tile_name,
+			texture_index: evaluation.texture_index,
+		})
+		.collect()
+}
@@ -0,0 +1,121 @@
+//! Selecting among the variants of an Auto tile's rule
+
+use bevy_ecs_tilemap::TilePos;
+use bevy_tileset::prelude::AutoTileRule;
+use bevy_tileset::prelude::{SimpleTileType, TileId, TileIndex, TileType, Tileset};
+
+use crate::placement::{roll, weighted_pick};
+
+/// Selects the texture of an Auto tile for a given rule
+///
+/// If the rule defines several variants, one of them is selected according to their weights:
+///
+/// * With a seed (i.e. the map's [`variant_seed`](crate::prelude::MapSettings::variant_seed)),
+///   the variant is derived from the position and the seed
+/// * Otherwise, the variant is derived from the position and the rule
+///
+/// Either way, the selection only depends on the position and the rule, so neighbor updates that
+/// lead back to the same rule restore the same variant, even after the tile has been replaced.
+pub(crate) struct AutoVariantSelector {
+	pub pos: TilePos,
+	pub seed: Option<u64>,
+}
+
+impl AutoVariantSelector {
+	/// Get the index of the given Auto tile for the given rule
+	pub fn select(
+		&self,
+		tileset: &Tileset,
+		tile_name: &str,
+		rule: AutoTileRule,
+	) -> Option<TileIndex> {
+		let index = tileset.get_auto_index(tile_name, rule)?;
+		Some(
+			self.select_variant(tileset, tile_name, &index)
+				.unwrap_or(index),
+		)
+	}
+
+	/// Returns true if the given texture index is the one selected for the given rule
+	///
+	/// Any variant of the rule counts if its variants can't be determined.
+	pub fn is_selected(
+		&self,
+		tileset: &Tileset,
+		tile_name: &str,
+		rule: AutoTileRule,
+		texture_index: usize,
+	) -> bool {
+		if !tileset.is_auto_variant(tile_name, &texture_index, &rule) {
+			return false;
+		}
+
+		tileset
+			.get_auto_index(tile_name, rule)
+			.and_then(|index| self.select_variant(tileset, tile_name, &index))
+			.is_none_or(|selected| *selected.base_index() == texture_index)
+	}
+
+	/// Select among the variants of the rule the given index belongs to
+	///
+	/// Returns `None` if the rule doesn't have multiple variants.
+	fn select_variant(
+		&self,
+		tileset: &Tileset,
+		tile_name: &str,
+		index: &TileIndex,
+	) -> Option<TileIndex> {
+		let variants =
+			rule_variants(tileset, tile_name, index).filter(|variants| variants.len() > 1)?;
+
+		// The first variant identifies the rule within its tileset
+		let seed = self
+			.seed
+			.unwrap_or_else(|| *variants[0].0.base_index() as u64);
+		let choices = variants
+			.iter()
+			.enumerate()
+			.map(|(choice, (_, weight))| (choice, *weight))
+			.collect::<Vec<_>>();
+		let choice = weighted_pick(&choices, roll(self.pos, seed))?;
+		variants.get(choice).map(|(index, _)| *index)
+	}
+}
+
+/// Get the variants (along with their weights) of the rule the given index belongs to
+fn rule_variants(
+	tileset: &Tileset,
+	tile_name: &str,
+	index: &TileIndex,
+) -> Option<Vec<(TileIndex, f32)>> {
+	let group_id = tileset.get_tile_group_id(tile_name)?;
+	let (_, data) = tileset.select_tile_by_id(TileId::new(*group_id, *tileset.id()))?;
+	let autos = match data.tile() {
+		TileType::Auto(autos) => autos,
+		_ => return None,
+	};
+
+	autos
+		.iter()
+		.map(|auto| {
+			auto.variants()
+				.iter()
+				.map(|variant| (to_index(variant.tile()), variant.weight()))
+				.collect::<Vec<_>>()
+		})
+		.find(|variants| {
+			variants
+				.iter()
+				.any(|(variant, _)| variant.base_index() == index.base_index())
+		})
+}
+
+/// Get the index displaying the given tile
+fn to_index(tile: &SimpleTileType) -> TileIndex {
+	match tile {
+		SimpleTileType::Standard(index) => TileIndex::Standard(*index),
+		SimpleTileType::Animated(anim) => {
+			TileIndex::Animated(anim.start(), anim.end(), anim.speed())
+		},
+	}
+}
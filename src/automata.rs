@@ -0,0 +1,310 @@
+//! Cellular automata over the tiles of a layer
+//!
+//! Each step computes the next state of every tile in a region from the current state of its
+//! neighborhood. All tiles are computed from the same (buffered) state before any change is
+//! applied, so the order in which tiles are visited doesn't matter.
+
+use bevy::ecs::system::SystemParam;
+use bevy::math::const_ivec2;
+use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::{MapId, TilePos};
+use bevy_tileset::prelude::TileId;
+
+use crate::coord::TileCoord;
+use crate::placement::{TilePlacementError, TilePlacer};
+use crate::registry::TilesetMapRegistry;
+
+/// The offsets of the 8-neighborhood, clockwise starting from the top-left
+const OFFSETS_8: [IVec2; 8] = [
+	const_ivec2!([-1, 1]),
+	const_ivec2!([0, 1]),
+	const_ivec2!([1, 1]),
+	const_ivec2!([1, 0]),
+	const_ivec2!([1, -1]),
+	const_ivec2!([0, -1]),
+	const_ivec2!([-1, -1]),
+	const_ivec2!([-1, 0]),
+];
+
+/// The state of a tile and its immediate neighbors, as seen by an automata rule
+///
+/// Neighbors outside the layer are treated as empty.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AutomataNeighborhood {
+	/// The position of the tile
+	pub pos: TilePos,
+	/// The tiles surrounding (and including) the tile, row by row from the bottom
+	tiles: [Option<TileId>; 9],
+}
+
+/// The outcome of running a [`TileAutomata`] step
+#[derive(Debug, Default)]
+pub struct AutomataReport {
+	/// The number of tiles that were placed (or replaced)
+	pub placed: usize,
+	/// The number of tiles that were removed
+	pub removed: usize,
+	/// The tiles that could not be changed, along with the reason why
+	pub failed: Vec<(TileCoord, TilePlacementError)>,
+}
+
+/// A helper system param used to run cellular automata over a layer
+///
+/// Rules receive the [`AutomataNeighborhood`] of each tile and return its next state, where
+/// `None` means the tile should be empty. Changes are applied through the [`TilePlacer`] in a
+/// single batch.
+///
+/// # Examples
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_ecs_tilemap::TilePos;
+/// # use bevy_tileset_map::prelude::{TileAutomata, TileId};
+/// struct Wall(TileId);
+///
+/// // Smooth randomly placed walls into caves
+/// fn smooth_caves(mut automata: TileAutomata, wall: Res<Wall>) {
+///   let wall = wall.0;
+///   automata.run_layer(0u16, 0u16, 5, |neighborhood| {
+///     let walls = neighborhood.count8(|tile| tile.is_some());
+///     match walls {
+///       0..=3 => None,
+///       4 => neighborhood.center(),
+///       _ => Some(wall),
+///     }
+///   });
+/// }
+/// ```
+#[derive(SystemParam)]
+pub struct TileAutomata<'w, 's> {
+	placer: TilePlacer<'w, 's>,
+	registry: Res<'w, TilesetMapRegistry>,
+}
+
+impl AutomataNeighborhood {
+	/// The current state of the tile itself
+	pub fn center(&self) -> Option<TileId> {
+		self.tiles[4]
+	}
+
+	/// The current state of the tile at the given offset
+	///
+	/// Only the immediate neighbors (offsets within `-1..=1` on both axes) are available.
+	pub fn get(&self, offset: IVec2) -> Option<TileId> {
+		if offset.abs().max_element() > 1 {
+			return None;
+		}
+		self.tiles[((offset.y + 1) * 3 + offset.x + 1) as usize]
+	}
+
+	/// The tiles sharing an edge with the tile: top, right, bottom, then left
+	pub fn neighbors4(&self) -> impl Iterator<Item = Option<TileId>> + '_ {
+		OFFSETS_8
+			.iter()
+			.skip(1)
+			.step_by(2)
+			.map(|offset| self.get(*offset))
+	}
+
+	/// The tiles surrounding the tile, clockwise starting from the top-left
+	pub fn neighbors8(&self) -> impl Iterator<Item = Option<TileId>> + '_ {
+		OFFSETS_8.iter().map(|offset| self.get(*offset))
+	}
+
+	/// The number of edge-sharing neighbors matching the given predicate
+	pub fn count4<F: FnMut(&Option<TileId>) -> bool>(&self, predicate: F) -> usize {
+		self.neighbors4().filter(predicate).count()
+	}
+
+	/// The number of surrounding neighbors matching the given predicate
+	pub fn count8<F: FnMut(&Option<TileId>) -> bool>(&self, predicate: F) -> usize {
+		self.neighbors8().filter(predicate).count()
+	}
+}
+
+impl<'w, 's> TileAutomata<'w, 's> {
+	/// Run a single step of the given rule over every tile in the given layer
+	pub fn step_layer<MId: MapId, F>(
+		&mut self,
+		map_id: MId,
+		layer_id: u16,
+		rule: F,
+	) -> AutomataReport
+	where
+		F: FnMut(&AutomataNeighborhood) -> Option<TileId>,
+	{
+		self.run_layer(map_id, layer_id, 1, rule)
+	}
+
+	/// Run the given number of steps of the given rule over every tile in the given layer
+	pub fn run_layer<MId: MapId, F>(
+		&mut self,
+		map_id: MId,
+		layer_id: u16,
+		steps: usize,
+		rule: F,
+	) -> AutomataReport
+	where
+		F: FnMut(&AutomataNeighborhood) -> Option<TileId>,
+	{
+		let map_id = map_id.into();
+		match self.layer_size(map_id, layer_id) {
+			Some(size) => self.run_region(
+				TilePos(0, 0),
+				TilePos(size.x - 1, size.y - 1),
+				map_id,
+				layer_id,
+				steps,
+				rule,
+			),
+			None => AutomataReport::default(),
+		}
+	}
+
+	/// Run a single step of the given rule over every tile in the given inclusive region
+	pub fn step_region<MId: MapId, F>(
+		&mut self,
+		min: TilePos,
+		max: TilePos,
+		map_id: MId,
+		layer_id: u16,
+		rule: F,
+	) -> AutomataReport
+	where
+		F: FnMut(&AutomataNeighborhood) -> Option<TileId>,
+	{
+		self.run_region(min, max, map_id, layer_id, 1, rule)
+	}
+
+	/// Run the given number of steps of the given rule over every tile in the given inclusive region
+	///
+	/// All steps are computed before any change is applied, since placed tiles can't be read back
+	/// until the end of the frame. Tiles surrounding the region are visible to the rule but keep
+	/// their current state for every step.
+	///
+	/// Regions extending past the layer are clamped to it.
+	pub fn run_region<MId: MapId, F>(
+		&mut self,
+		min: TilePos,
+		max: TilePos,
+		map_id: MId,
+		layer_id: u16,
+		steps: usize,
+		mut rule: F,
+	) -> AutomataReport
+	where
+		F: FnMut(&AutomataNeighborhood) -> Option<TileId>,
+	{
+		let map_id = map_id.into();
+		let mut report = AutomataReport::default();
+		let size = match self.layer_size(map_id, layer_id) {
+			Some(size) => size,
+			None => return report,
+		};
+
+		let (min, max) = (
+			UVec2::new(min.0.min(max.0), min.1.min(max.1)),
+			UVec2::new(min.0.max(max.0), min.1.max(max.1)).min(size - UVec2::ONE),
+		);
+		if min.x > max.x || min.y > max.y {
+			return report;
+		}
+
+		// The buffer covers the region plus a margin of one tile (where it lies within the layer)
+		let lower = UVec2::new(min.x.saturating_sub(1), min.y.saturating_sub(1));
+		let upper = (max + UVec2::ONE).min(size - UVec2::ONE);
+		let width = (upper.x - lower.x + 1) as usize;
+		let height = (upper.y - lower.y + 1) as usize;
+		let index = |x: u32, y: u32| (y - lower.y) as usize * width + (x - lower.x) as usize;
+
+		let mut current = Vec::with_capacity(width * height);
+		for y in lower.y..=upper.y {
+			for x in lower.x..=upper.x {
+				current.push(self.placer.get_tile_id_at(TilePos(x, y), map_id, layer_id));
+			}
+		}
+		let original = current.clone();
+		let mut next = current.clone();
+
+		for _ in 0..steps {
+			for y in min.y..=max.y {
+				for x in min.x..=max.x {
+					let mut tiles = [None; 9];
+					for (i, tile) in tiles.iter_mut().enumerate() {
+						let nx = x as i64 + (i % 3) as i64 - 1;
+						let ny = y as i64 + (i / 3) as i64 - 1;
+						if nx >= lower.x as i64
+							&& ny >= lower.y as i64
+							&& nx <= upper.x as i64
+							&& ny <= upper.y as i64
+						{
+							*tile = current[index(nx as u32, ny as u32)];
+						}
+					}
+
+					let neighborhood = AutomataNeighborhood {
+						pos: TilePos(x, y),
+						tiles,
+					};
+					next[index(x, y)] = rule(&neighborhood);
+				}
+			}
+			std::mem::swap(&mut current, &mut next);
+		}
+
+		// Apply only the tiles that actually changed
+		self.placer.begin_batch();
+		for y in min.y..=max.y {
+			for x in min.x..=max.x {
+				let (before, after) = (original[index(x, y)], current[index(x, y)]);
+				let coord = TileCoord::new(TilePos(x, y), map_id, layer_id);
+				let result = match (before, after) {
+					(Some(before), Some(after)) if before.eq_tile_group(&after) => continue,
+					(None, None) => continue,
+					(_, Some(after)) => self
+						.placer
+						.place(after, coord.pos, map_id, layer_id)
+						.map(|_| report.placed += 1),
+					(Some(_), None) => self
+						.placer
+						.remove(coord.pos, map_id, layer_id)
+						.map(|_| report.removed += 1),
+				};
+
+				if let Err(err) = result {
+					report.failed.push((coord, err));
+				}
+			}
+		}
+		self.placer.end_batch();
+
+		report
+	}
+
+	/// Get the [`TilePlacer`] used to apply changes
+	pub fn placer(&mut self) -> &mut TilePlacer<'w, 's> {
+		&mut self.placer
+	}
+
+	/// The size of the given layer (in tiles)
+	fn layer_size(&self, map_id: u16, layer_id: u16) -> Option<UVec2> {
+		let settings = &self.registry.layer(map_id, layer_id)?.settings;
+		let size = UVec2::new(
+			settings.map_size.0 * settings.chunk_size.0,
+			settings.map_size.1 * settings.chunk_size.1,
+		);
+		(size.x > 0 && size.y > 0).then_some(size)
+	}
+}
+
+impl AutomataReport {
+	/// Returns true if every change was applied successfully
+	pub fn is_ok(&self) -> bool {
+		self.failed.is_empty()
+	}
+
+	/// The total number of tiles that changed
+	pub fn changed(&self) -> usize {
+		self.placed + self.removed
+	}
+}
@@ -0,0 +1,152 @@
+//! Spreading large edits across several frames
+//!
+//! Edits queued in the [`PlacementBudget`] are applied at most
+//! [`tiles_per_frame`](PlacementBudget::tiles_per_frame) at a time, in the order they were queued.
+//! This keeps frame times stable when gameplay triggers huge edits (such as explosions or
+//! terraforming). Once every edit queued under a tag has been applied, a
+//! [`PlacementBatchCompleted`] event is sent.
+
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+use crate::locking::TileEdit;
+use crate::placement::TilePlacer;
+
+/// An event sent once every edit queued under a tag in the [`PlacementBudget`] has been applied
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PlacementBatchCompleted {
+	/// The tag the edits were queued under
+	pub tag: u64,
+	/// The number of edits that were applied successfully
+	pub applied: usize,
+	/// The number of edits that failed
+	pub failed: usize,
+}
+
+/// The progress of the edits queued under a single tag
+#[derive(Debug, Copy, Clone, Default)]
+struct BatchProgress {
+	remaining: usize,
+	applied: usize,
+	failed: usize,
+}
+
+/// A resource queuing edits so they are spread across frames
+///
+/// This resource is added automatically by the [`TilesetMapPlugin`](crate::prelude::TilesetMapPlugin).
+///
+/// # Examples
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_ecs_tilemap::TilePos;
+/// # use bevy_tileset_map::prelude::{PlacementBudget, TileEdit};
+/// fn explode(mut budget: ResMut<PlacementBudget>) {
+///   budget.tiles_per_frame = Some(500);
+///
+///   let edits = (0..64u32).flat_map(|x| (0..64u32).map(move |y| TileEdit::Remove {
+///     pos: TilePos(x, y),
+///     map_id: 0,
+///     layer_id: 0,
+///   }));
+///   budget.queue(1, edits);
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct PlacementBudget {
+	/// The maximum number of edits applied per frame
+	///
+	/// If `None`, all queued edits are applied in the next frame.
+	pub tiles_per_frame: Option<usize>,
+	queue: VecDeque<(u64, TileEdit)>,
+	batches: HashMap<u64, BatchProgress>,
+}
+
+impl PlacementBudget {
+	/// Queue the given edits under the given tag
+	///
+	/// Edits queued under a tag that is still pending are added to its batch, so only a single
+	/// [`PlacementBatchCompleted`] event is sent once all of them have been applied.
+	pub fn queue<I: IntoIterator<Item = TileEdit>>(&mut self, tag: u64, edits: I) {
+		let len = self.queue.len();
+		self.queue.extend(edits.into_iter().map(|edit| (tag, edit)));
+		let added = self.queue.len() - len;
+		if added > 0 {
+			self.batches.entry(tag).or_default().remaining += added;
+		}
+	}
+
+	/// Cancel all edits still queued under the given tag, returning how many were cancelled
+	///
+	/// No [`PlacementBatchCompleted`] event is sent for a cancelled batch.
+	pub fn cancel(&mut self, tag: u64) -> usize {
+		let len = self.queue.len();
+		self.queue.retain(|(queued, _)| *queued != tag);
+		self.batches.remove(&tag);
+		len - self.queue.len()
+	}
+
+	/// The number of edits still queued under the given tag
+	pub fn remaining(&self, tag: u64) -> usize {
+		self.batches
+			.get(&tag)
+			.map(|progress| progress.remaining)
+			.unwrap_or_default()
+	}
+
+	/// The total number of queued edits
+	pub fn len(&self) -> usize {
+		self.queue.len()
+	}
+
+	/// Returns true if no edits are queued
+	pub fn is_empty(&self) -> bool {
+		self.queue.is_empty()
+	}
+}
+
+/// __\[SYSTEM\]__ Applies the edits queued in the [`PlacementBudget`] that fit within this frame
+pub(crate) fn apply_budgeted_placements(
+	mut budget: ResMut<PlacementBudget>,
+	mut events: EventWriter<PlacementBatchCompleted>,
+	mut placer: TilePlacer,
+) {
+	if budget.is_empty() {
+		return;
+	}
+
+	let count = budget
+		.tiles_per_frame
+		.unwrap_or(usize::MAX)
+		.min(budget.queue.len());
+
+	placer.begin_batch();
+	for (tag, edit) in budget.queue.drain(..count).collect::<Vec<_>>() {
+		let result = edit.apply(&mut placer);
+		let progress = if let Some(progress) = budget.batches.get_mut(&tag) {
+			progress
+		} else {
+			continue;
+		};
+
+		if result.is_ok() {
+			progress.applied += 1;
+		} else {
+			progress.failed += 1;
+		}
+		progress.remaining -= 1;
+
+		if progress.remaining == 0 {
+			let progress = *progress;
+			budget.batches.remove(&tag);
+			events.send(PlacementBatchCompleted {
+				tag,
+				applied: progress.applied,
+				failed: progress.failed,
+			});
+		}
+	}
+	placer.end_batch();
+}
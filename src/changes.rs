@@ -0,0 +1,174 @@
+//! A compact, per-frame diff of the tiles that have changed
+//!
+//! Rather than scanning every tile (or setting up their own change detection), save systems,
+//! networking, and minimaps can read the [`TilemapChanges`] resource to find out exactly which
+//! coordinates were touched.
+
+use bevy::ecs::query::ChangeTrackers;
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+use bevy_ecs_tilemap::{Tile, TileParent, TilePos};
+use bevy_tileset::prelude::TilesetParent;
+
+use crate::coord::TileCoord;
+
+/// The kind of change made to a tile
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum TileChange {
+	/// A tile was placed where there was none
+	Added,
+	/// A tile was removed
+	Removed,
+	/// An existing tile was changed (or replaced)
+	Modified,
+}
+
+/// A resource containing the coordinates of all tiles that changed during the previous frame
+///
+/// The changes are collected at the very end of every frame (in [`CoreStage::Last`]), replacing
+/// those of the frame before. Each coordinate is only listed under a single [`TileChange`].
+///
+/// This resource is added and kept up to date automatically by the
+/// [`TilesetMapPlugin`](crate::prelude::TilesetMapPlugin).
+///
+/// # Examples
+///
+/// ```
+/// # use bevy::prelude::Res;
+/// # use bevy_tileset_map::prelude::{TileChange, TilemapChanges};
+/// fn update_minimap(changes: Res<TilemapChanges>) {
+///   for (coord, change) in changes.iter() {
+///     if change == TileChange::Removed {
+///       // ...
+///     }
+///   }
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct TilemapChanges {
+	added: HashSet<TileCoord>,
+	removed: HashSet<TileCoord>,
+	modified: HashSet<TileCoord>,
+}
+
+impl TilemapChanges {
+	/// The coordinates of all tiles that were added
+	pub fn added(&self) -> impl Iterator<Item = &TileCoord> {
+		self.added.iter()
+	}
+
+	/// The coordinates of all tiles that were removed
+	pub fn removed(&self) -> impl Iterator<Item = &TileCoord> {
+		self.removed.iter()
+	}
+
+	/// The coordinates of all tiles that were modified
+	pub fn modified(&self) -> impl Iterator<Item = &TileCoord> {
+		self.modified.iter()
+	}
+
+	/// Iterate over every changed coordinate along with its change
+	pub fn iter(&self) -> impl Iterator<Item = (&TileCoord, TileChange)> {
+		self.added
+			.iter()
+			.map(|coord| (coord, TileChange::Added))
+			.chain(
+				self.removed
+					.iter()
+					.map(|coord| (coord, TileChange::Removed)),
+			)
+			.chain(
+				self.modified
+					.iter()
+					.map(|coord| (coord, TileChange::Modified)),
+			)
+	}
+
+	/// Get the change made to the tile at the given coordinate (if any)
+	pub fn get(&self, coord: &TileCoord) -> Option<TileChange> {
+		if self.added.contains(coord) {
+			Some(TileChange::Added)
+		} else if self.removed.contains(coord) {
+			Some(TileChange::Removed)
+		} else if self.modified.contains(coord) {
+			Some(TileChange::Modified)
+		} else {
+			None
+		}
+	}
+
+	/// Returns true if the tile at the given coordinate changed
+	pub fn contains(&self, coord: &TileCoord) -> bool {
+		self.get(coord).is_some()
+	}
+
+	/// The total number of changed coordinates
+	pub fn len(&self) -> usize {
+		self.added.len() + self.removed.len() + self.modified.len()
+	}
+
+	/// Returns true if no tiles changed
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	fn clear(&mut self) {
+		self.added.clear();
+		self.removed.clear();
+		self.modified.clear();
+	}
+
+	fn record(&mut self, coord: TileCoord, change: TileChange) {
+		match change {
+			TileChange::Added if self.removed.remove(&coord) => {
+				self.modified.insert(coord);
+			},
+			TileChange::Added => {
+				self.added.insert(coord);
+			},
+			TileChange::Removed if self.added.remove(&coord) => {},
+			TileChange::Removed => {
+				self.modified.remove(&coord);
+				self.removed.insert(coord);
+			},
+			TileChange::Modified if self.added.contains(&coord) => {},
+			TileChange::Modified => {
+				self.modified.insert(coord);
+			},
+		}
+	}
+}
+
+/// __\[SYSTEM\]__ Replaces the contents of [`TilemapChanges`] with the changes from this frame
+pub(crate) fn track_tilemap_changes(
+	mut changes: ResMut<TilemapChanges>,
+	query: Query<
+		(Entity, &TilePos, &TileParent, ChangeTrackers<Tile>),
+		Or<(Changed<Tile>, Changed<TilesetParent>)>,
+	>,
+	removed: RemovedComponents<Tile>,
+	mut known: Local<HashMap<Entity, TileCoord>>,
+) {
+	changes.clear();
+
+	for entity in removed.iter() {
+		if let Some(coord) = known.remove(&entity) {
+			changes.record(coord, TileChange::Removed);
+		}
+	}
+
+	for (entity, pos, parent, tracker) in query.iter() {
+		let coord = TileCoord {
+			pos: *pos,
+			map_id: parent.map_id,
+			layer_id: parent.layer_id,
+		};
+
+		if tracker.is_added() {
+			known.insert(entity, coord);
+			changes.record(coord, TileChange::Added);
+		} else {
+			changes.record(coord, TileChange::Modified);
+		}
+	}
+}
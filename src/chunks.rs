@@ -0,0 +1,134 @@
+//! Crate-level events for the chunk lifecycle
+//!
+//! These allow streaming, persistence, and minimap systems to react to chunks being spawned or
+//! despawned without depending on the internals of `bevy_ecs_tilemap`.
+
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_ecs_tilemap::prelude::{Chunk, LocalTilePos, TileParent};
+use bevy_tileset::prelude::TilesetId;
+
+use crate::registry::TilesetMapRegistry;
+
+/// An event sent when a chunk is spawned
+///
+/// The chunks of an empty layer that hasn't been registered in the
+/// [`TilesetMapRegistry`] are only reported once their first tile has been placed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChunkSpawnedEvent {
+	/// The chunk entity
+	pub entity: Entity,
+	/// The map containing the chunk
+	pub map_id: u16,
+	/// The layer containing the chunk
+	pub layer_id: u16,
+	/// The position of the chunk (in chunks)
+	pub position: UVec2,
+	/// The tilesets that have been placed in the chunk's layer
+	pub tilesets: Vec<TilesetId>,
+}
+
+/// An event sent when a chunk is despawned
+///
+/// Contains the same data as the [`ChunkSpawnedEvent`] sent for this chunk, except that its
+/// tilesets reflect the layer at the time of despawning.
+///
+/// By the time this event is read, the chunk's tiles are already gone. To persist the state of
+/// chunks belonging to an infinite map, listen for
+/// [`RegionUnloadingEvent`](crate::prelude::RegionUnloadingEvent)s instead.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChunkDespawnedEvent {
+	/// The (now despawned) chunk entity
+	pub entity: Entity,
+	/// The map that contained the chunk
+	pub map_id: u16,
+	/// The layer that contained the chunk
+	pub layer_id: u16,
+	/// The position of the chunk (in chunks)
+	pub position: UVec2,
+	/// The tilesets that had been placed in the chunk's layer
+	pub tilesets: Vec<TilesetId>,
+}
+
+/// __\[SYSTEM\]__ Sends [`ChunkSpawnedEvent`]s and [`ChunkDespawnedEvent`]s
+pub(crate) fn send_chunk_events(
+	added: Query<Entity, Added<Chunk>>,
+	chunks: Query<&Chunk>,
+	tiles: Query<&TileParent>,
+	removed: RemovedComponents<Chunk>,
+	registry: Res<TilesetMapRegistry>,
+	mut spawned: EventWriter<ChunkSpawnedEvent>,
+	mut despawned: EventWriter<ChunkDespawnedEvent>,
+	mut known: Local<HashMap<Entity, (u16, u16, UVec2)>>,
+	mut pending: Local<Vec<Entity>>,
+) {
+	let tilesets = |map_id: u16, layer_id: u16| -> Vec<TilesetId> {
+		registry
+			.layer(map_id, layer_id)
+			.map(|layer| layer.tilesets.iter().copied().collect())
+			.unwrap_or_default()
+	};
+
+	for entity in removed.iter() {
+		pending.retain(|pending| *pending != entity);
+		if let Some((map_id, layer_id, position)) = known.remove(&entity) {
+			despawned.send(ChunkDespawnedEvent {
+				entity,
+				map_id,
+				layer_id,
+				position,
+				tilesets: tilesets(map_id, layer_id),
+			});
+		}
+	}
+
+	pending.extend(added.iter());
+	pending.retain(|entity| {
+		let chunk = if let Ok(chunk) = chunks.get(*entity) {
+			chunk
+		} else {
+			return false;
+		};
+		let (map_id, layer_id) = if let Some(ids) = find_layer(chunk, &registry, &tiles) {
+			ids
+		} else {
+			// Try again once the layer is known
+			return true;
+		};
+
+		let position = UVec2::new(chunk.position.0, chunk.position.1);
+		known.insert(*entity, (map_id, layer_id, position));
+		spawned.send(ChunkSpawnedEvent {
+			entity: *entity,
+			map_id,
+			layer_id,
+			position,
+			tilesets: tilesets(map_id, layer_id),
+		});
+		false
+	});
+}
+
+/// Find the map and layer IDs of the given chunk
+///
+/// Chunks only store the entity of their layer, so the IDs are taken from the registry or, if the
+/// layer isn't registered yet, from any tile within the chunk.
+fn find_layer(
+	chunk: &Chunk,
+	registry: &TilesetMapRegistry,
+	tiles: &Query<&TileParent>,
+) -> Option<(u16, u16)> {
+	if let Some((map_id, layer_id, _)) = registry
+		.layers()
+		.find(|(.., layer)| layer.entity == chunk.map_entity)
+	{
+		return Some((map_id, layer_id));
+	}
+
+	let size = chunk.settings.chunk_size;
+	(0..size.1)
+		.flat_map(|y| (0..size.0).map(move |x| LocalTilePos(x, y)))
+		.find_map(|pos| chunk.get_tile_entity(pos))
+		.and_then(|entity| tiles.get(entity).ok())
+		.map(|parent| (parent.map_id, parent.layer_id))
+}
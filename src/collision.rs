@@ -0,0 +1,261 @@
+//! Per-tile collision geometry
+//!
+//! The collision shape of a tile is determined by the [`TileCollisionRegistry`] (keyed by tileset
+//! name and tile name) or, if the tile isn't registered there, by the [`COLLISION_METADATA_KEY`]
+//! key of its [`TileMetadata`](crate::prelude::TileMetadata). The metadata value may either be
+//! `true` (for a [`TileCollision::Full`] shape) or one of the following strings:
+//!
+//! * `"full"`
+//! * `"half-top"`, `"half-bottom"`, `"half-left"`, `"half-right"`
+//! * `"slope-top-left"`, `"slope-top-right"`, `"slope-bottom-left"`, `"slope-bottom-right"`
+//!
+//! The [`TilePlacer`](crate::prelude::TilePlacer) inserts the resolved shape as a [`TileCollision`]
+//! component, and the [`CollisionMap`] resource keeps track of every tile with collision so that
+//! "is this cell solid?" can be answered without a physics engine.
+
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_ecs_tilemap::{TileParent, TilePos};
+use bevy_tileset::prelude::{TileId, Tileset};
+
+use crate::coord::TileCoord;
+use crate::metadata::{MetadataValue, TileMetadata};
+
+/// The metadata key used to define a tile's collision shape
+pub const COLLISION_METADATA_KEY: &str = "collision";
+
+/// The collision shape of a tile
+///
+/// All coordinates are normalized to the tile, with `(0, 0)` being its bottom-left corner
+/// and `(1, 1)` being its top-right corner.
+#[derive(Component, Debug, Clone, PartialEq)]
+#[cfg_attr(
+	feature = "serialization",
+	derive(serde::Serialize, serde::Deserialize)
+)]
+pub enum TileCollision {
+	/// The entire tile is solid
+	Full,
+	/// Half of the tile is solid
+	Half(TileSide),
+	/// A right triangle occupying the given corner of the tile
+	Slope(TileCorner),
+	/// A custom convex polygon
+	Polygon(Vec<[f32; 2]>),
+}
+
+/// A side of a tile
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+	feature = "serialization",
+	derive(serde::Serialize, serde::Deserialize)
+)]
+pub enum TileSide {
+	Top,
+	Bottom,
+	Left,
+	Right,
+}
+
+/// A corner of a tile
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+	feature = "serialization",
+	derive(serde::Serialize, serde::Deserialize)
+)]
+pub enum TileCorner {
+	TopLeft,
+	TopRight,
+	BottomLeft,
+	BottomRight,
+}
+
+/// A resource containing the [`TileCollision`] for each tile, keyed by tileset name and tile name
+///
+/// This resource is added automatically by the [`TilesetMapPlugin`](crate::prelude::TilesetMapPlugin).
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(
+	feature = "serialization",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(transparent)
+)]
+pub struct TileCollisionRegistry {
+	tilesets: HashMap<String, HashMap<String, TileCollision>>,
+}
+
+/// A resource mapping the coordinate of every tile with a [`TileCollision`] to its shape
+///
+/// This resource is added and kept up to date automatically by the
+/// [`TilesetMapPlugin`](crate::prelude::TilesetMapPlugin).
+#[derive(Debug, Default)]
+pub struct CollisionMap {
+	shapes: HashMap<TileCoord, (Entity, TileCollision)>,
+	coords: HashMap<Entity, TileCoord>,
+}
+
+impl TileCollision {
+	/// Returns true if this shape covers the entire tile
+	pub fn is_full(&self) -> bool {
+		matches!(self, Self::Full)
+	}
+
+	/// The outline of this shape as a counter-clockwise polygon, normalized to the tile
+	pub fn points(&self) -> Vec<[f32; 2]> {
+		match self {
+			Self::Full => vec![[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]],
+			Self::Half(TileSide::Top) => vec![[0.0, 0.5], [1.0, 0.5], [1.0, 1.0], [0.0, 1.0]],
+			Self::Half(TileSide::Bottom) => vec![[0.0, 0.0], [1.0, 0.0], [1.0, 0.5], [0.0, 0.5]],
+			Self::Half(TileSide::Left) => vec![[0.0, 0.0], [0.5, 0.0], [0.5, 1.0], [0.0, 1.0]],
+			Self::Half(TileSide::Right) => vec![[0.5, 0.0], [1.0, 0.0], [1.0, 1.0], [0.5, 1.0]],
+			Self::Slope(TileCorner::TopLeft) => vec![[0.0, 0.0], [1.0, 1.0], [0.0, 1.0]],
+			Self::Slope(TileCorner::TopRight) => vec![[1.0, 0.0], [1.0, 1.0], [0.0, 1.0]],
+			Self::Slope(TileCorner::BottomLeft) => vec![[0.0, 0.0], [1.0, 0.0], [0.0, 1.0]],
+			Self::Slope(TileCorner::BottomRight) => vec![[0.0, 0.0], [1.0, 0.0], [1.0, 1.0]],
+			Self::Polygon(points) => points.clone(),
+		}
+	}
+
+	/// Parse a collision shape from a metadata value
+	pub fn from_metadata(value: &MetadataValue) -> Option<Self> {
+		match value {
+			MetadataValue::Bool(true) => Some(Self::Full),
+			MetadataValue::String(name) => match name.as_str() {
+				"full" => Some(Self::Full),
+				"half-top" => Some(Self::Half(TileSide::Top)),
+				"half-bottom" => Some(Self::Half(TileSide::Bottom)),
+				"half-left" => Some(Self::Half(TileSide::Left)),
+				"half-right" => Some(Self::Half(TileSide::Right)),
+				"slope-top-left" => Some(Self::Slope(TileCorner::TopLeft)),
+				"slope-top-right" => Some(Self::Slope(TileCorner::TopRight)),
+				"slope-bottom-left" => Some(Self::Slope(TileCorner::BottomLeft)),
+				"slope-bottom-right" => Some(Self::Slope(TileCorner::BottomRight)),
+				_ => None,
+			},
+			_ => None,
+		}
+	}
+}
+
+impl TileCollisionRegistry {
+	/// Register the collision shape for the given tile, returning the shape it replaced (if any)
+	pub fn insert<S: Into<String>, T: Into<String>>(
+		&mut self,
+		tileset_name: S,
+		tile_name: T,
+		collision: TileCollision,
+	) -> Option<TileCollision> {
+		self.tilesets
+			.entry(tileset_name.into())
+			.or_insert_with(HashMap::default)
+			.insert(tile_name.into(), collision)
+	}
+
+	/// Remove the collision shape for the given tile
+	pub fn remove(&mut self, tileset_name: &str, tile_name: &str) -> Option<TileCollision> {
+		self.tilesets.get_mut(tileset_name)?.remove(tile_name)
+	}
+
+	/// Get the collision shape for the given tile
+	pub fn get(&self, tileset_name: &str, tile_name: &str) -> Option<&TileCollision> {
+		self.tilesets.get(tileset_name)?.get(tile_name)
+	}
+
+	/// Resolve the collision shape for the tile with the given ID
+	///
+	/// This checks the registry first, then falls back to the given metadata.
+	pub fn resolve(
+		&self,
+		tileset: &Tileset,
+		tile_id: &TileId,
+		metadata: Option<&TileMetadata>,
+	) -> Option<TileCollision> {
+		let registered = tileset
+			.get_tile_name(&tile_id.group_id)
+			.and_then(|tile_name| self.get(tileset.name(), tile_name));
+		if let Some(collision) = registered {
+			return Some(collision.clone());
+		}
+
+		metadata
+			.and_then(|metadata| metadata.get(COLLISION_METADATA_KEY))
+			.and_then(TileCollision::from_metadata)
+	}
+}
+
+impl CollisionMap {
+	/// Get the collision shape of the tile at the given coordinate
+	pub fn get(&self, coord: &TileCoord) -> Option<&TileCollision> {
+		self.shapes.get(coord).map(|(_, collision)| collision)
+	}
+
+	/// Returns true if the tile at the given coordinate has any collision
+	pub fn is_solid(&self, coord: &TileCoord) -> bool {
+		self.shapes.contains_key(coord)
+	}
+
+	/// Returns true if the tile at the given coordinate is entirely solid
+	pub fn is_full(&self, coord: &TileCoord) -> bool {
+		self.get(coord)
+			.map(TileCollision::is_full)
+			.unwrap_or_default()
+	}
+
+	/// Iterate over all tiles with collision
+	pub fn iter(&self) -> impl Iterator<Item = (&TileCoord, &TileCollision)> {
+		self.shapes
+			.iter()
+			.map(|(coord, (_, collision))| (coord, collision))
+	}
+
+	/// The number of tiles with collision
+	pub fn len(&self) -> usize {
+		self.shapes.len()
+	}
+
+	/// Returns true if no tiles have collision
+	pub fn is_empty(&self) -> bool {
+		self.shapes.is_empty()
+	}
+
+	fn insert(&mut self, entity: Entity, coord: TileCoord, collision: TileCollision) {
+		if let Some(old_coord) = self.coords.insert(entity, coord) {
+			if old_coord != coord {
+				self.remove_at(entity, &old_coord);
+			}
+		}
+		self.shapes.insert(coord, (entity, collision));
+	}
+
+	fn remove(&mut self, entity: Entity) {
+		if let Some(coord) = self.coords.remove(&entity) {
+			self.remove_at(entity, &coord);
+		}
+	}
+
+	/// Removes the shape at the given coordinate only if it still belongs to the given entity
+	fn remove_at(&mut self, entity: Entity, coord: &TileCoord) {
+		if matches!(self.shapes.get(coord), Some((owner, _)) if *owner == entity) {
+			self.shapes.remove(coord);
+		}
+	}
+}
+
+/// __\[SYSTEM\]__ Keeps the [`CollisionMap`] in sync with all tiles' [`TileCollision`]
+pub(crate) fn update_collision_map(
+	mut collision_map: ResMut<CollisionMap>,
+	changed: Query<(Entity, &TilePos, &TileParent, &TileCollision), Changed<TileCollision>>,
+	removed: RemovedComponents<TileCollision>,
+) {
+	for entity in removed.iter() {
+		collision_map.remove(entity);
+	}
+
+	for (entity, pos, parent, collision) in changed.iter() {
+		let coord = TileCoord {
+			pos: *pos,
+			map_id: parent.map_id,
+			layer_id: parent.layer_id,
+		};
+		collision_map.insert(entity, coord, collision.clone());
+	}
+}
@@ -0,0 +1,381 @@
+//! Combining several tilesets into a single texture so they can share a layer
+//!
+//! A layer renders all of its tiles using a single texture, which normally limits it to the tiles
+//! of a single tileset. A [`CombinedTileset`] packs the textures of several tilesets into one
+//! atlas at runtime. Layers created with
+//! [`TileLayerManager::create_combined_layer`](crate::prelude::TileLayerManager::create_combined_layer)
+//! use this atlas, and the [`TilePlacer`](crate::prelude::TilePlacer) translates the texture
+//! indices of every placed tile into it.
+//!
+//! Placement, auto tiling, the [`TileQuerier`](crate::prelude::TileQuerier), and serialization
+//! account for combined layers. Other tools that work with raw texture indices (such as damage
+//! stages or one-shot animation frames) expect indices into the combined atlas, which can be
+//! obtained using [`CombinedTileset::to_combined`].
+//!
+//! Building the atlas requires rendering, so the `TilesetCombiner` is not available with the
+//! `headless` feature.
+
+#[cfg(not(feature = "headless"))]
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+#[cfg(not(feature = "headless"))]
+use bevy::render::render_resource::{Extent3d, TextureDimension};
+#[cfg(not(feature = "headless"))]
+use bevy::sprite::Rect;
+use bevy::utils::HashMap;
+#[cfg(not(feature = "headless"))]
+use bevy_tileset::prelude::Tilesets;
+use bevy_tileset::prelude::{TileId, TileIndex, Tileset, TilesetId};
+#[cfg(not(feature = "headless"))]
+use thiserror::Error;
+
+/// Errors related to combining tilesets
+#[cfg(not(feature = "headless"))]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum CombineTilesetsError {
+	/// No tilesets were given
+	#[error("No tilesets were given to combine")]
+	Empty,
+	/// The tileset does not exist or is invalid
+	///
+	/// Contains the ID of the tileset in question
+	#[error("Invalid tileset {0:?}")]
+	InvalidTileset(TilesetId),
+	/// The texture of the tileset has not been loaded (yet)
+	///
+	/// Contains the ID of the tileset in question
+	#[error("The texture of tileset {0:?} is not loaded")]
+	TextureNotLoaded(TilesetId),
+	/// The tileset's tile size differs from that of the first tileset
+	///
+	/// Contains the ID of the tileset in question
+	#[error("Tileset {0:?} has a different tile size than the other tilesets")]
+	IncompatibleTileSize(TilesetId),
+	/// The tileset's texture format differs from that of the first tileset or is compressed
+	///
+	/// Contains the ID of the tileset in question
+	#[error("Tileset {0:?} has an incompatible texture format")]
+	IncompatibleFormat(TilesetId),
+}
+
+/// The texture indices of a single tileset within a [`CombinedTileset`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct CombinedRange {
+	tileset_id: TilesetId,
+	offset: usize,
+	len: usize,
+}
+
+/// A texture atlas containing the tiles of several tilesets
+///
+/// The texture indices of each tileset are shifted by a fixed offset, in the order the tilesets
+/// were combined.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CombinedTileset {
+	texture: Handle<Image>,
+	tile_size: Vec2,
+	size: Vec2,
+	ranges: Vec<CombinedRange>,
+}
+
+/// A resource mapping layers to the [`CombinedTileset`] they were created with
+///
+/// This resource is added automatically by the [`TilesetMapPlugin`](crate::prelude::TilesetMapPlugin).
+#[derive(Debug, Default)]
+pub struct CombinedTilesets {
+	layers: HashMap<(u16, u16), CombinedTileset>,
+}
+
+/// A helper system param used to combine several tilesets into a [`CombinedTileset`]
+///
+/// All tilesets must share the same tile size and texture format, and their textures must be
+/// loaded (with their data still available on the CPU).
+///
+/// # Examples
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_ecs_tilemap::prelude::{ChunkSize, MapSize};
+/// # use bevy_tileset_map::prelude::{TileLayerManager, TilesetCombiner, TilesetId};
+/// struct MyTilesets(TilesetId, TilesetId);
+///
+/// fn setup(mut combiner: TilesetCombiner, mut layers: TileLayerManager, tilesets: Res<MyTilesets>) {
+///   let combined = combiner.combine(&[tilesets.0, tilesets.1]).unwrap();
+///   let map = layers.create_map(0u16);
+///   layers.create_combined_layer(combined, map, 0u16, 0u16, MapSize(4, 4), ChunkSize(32, 32));
+///   // Tiles from either tileset can now be placed in layer 0
+/// }
+/// ```
+#[cfg(not(feature = "headless"))]
+#[derive(SystemParam)]
+pub struct TilesetCombiner<'w, 's> {
+	tilesets: Tilesets<'w, 's>,
+	images: ResMut<'w, Assets<Image>>,
+	atlases: Res<'w, Assets<TextureAtlas>>,
+}
+
+impl CombinedTileset {
+	/// The combined texture
+	pub fn texture(&self) -> &Handle<Image> {
+		&self.texture
+	}
+
+	/// The size of each tile (in pixels)
+	pub fn tile_size(&self) -> Vec2 {
+		self.tile_size
+	}
+
+	/// The size of the combined texture (in pixels)
+	pub fn size(&self) -> Vec2 {
+		self.size
+	}
+
+	/// The IDs of the combined tilesets, in order
+	pub fn tilesets(&self) -> impl Iterator<Item = &TilesetId> {
+		self.ranges.iter().map(|range| &range.tileset_id)
+	}
+
+	/// Returns true if the given tileset is part of this combined tileset
+	pub fn contains(&self, tileset_id: &TilesetId) -> bool {
+		self.range(tileset_id).is_some()
+	}
+
+	/// The offset applied to the texture indices of the given tileset
+	pub fn offset(&self, tileset_id: &TilesetId) -> Option<usize> {
+		self.range(tileset_id).map(|range| range.offset)
+	}
+
+	/// Convert a texture index of the given tileset into an index of the combined texture
+	pub fn to_combined(&self, tileset_id: &TilesetId, index: usize) -> Option<usize> {
+		let range = self.range(tileset_id)?;
+		(index < range.len).then(|| range.offset + index)
+	}
+
+	/// Convert an index of the combined texture back into its tileset and texture index
+	pub fn to_local(&self, index: usize) -> Option<(TilesetId, usize)> {
+		self.ranges
+			.iter()
+			.find(|range| (range.offset..range.offset + range.len).contains(&index))
+			.map(|range| (range.tileset_id, index - range.offset))
+	}
+
+	fn range(&self, tileset_id: &TilesetId) -> Option<&CombinedRange> {
+		self.ranges
+			.iter()
+			.find(|range| &range.tileset_id == tileset_id)
+	}
+}
+
+impl CombinedTilesets {
+	/// Get the combined tileset of the given layer (if any)
+	pub fn get(&self, map_id: u16, layer_id: u16) -> Option<&CombinedTileset> {
+		self.layers.get(&(map_id, layer_id))
+	}
+
+	/// Set the combined tileset used by the given layer, returning its previous one (if any)
+	///
+	/// This is done automatically by
+	/// [`TileLayerManager::create_combined_layer`](crate::prelude::TileLayerManager::create_combined_layer).
+	/// The layer's texture must be the combined texture.
+	pub fn insert(
+		&mut self,
+		map_id: u16,
+		layer_id: u16,
+		combined: CombinedTileset,
+	) -> Option<CombinedTileset> {
+		self.layers.insert((map_id, layer_id), combined)
+	}
+
+	/// Stop treating the given layer as combined, returning its combined tileset (if any)
+	pub fn remove(&mut self, map_id: u16, layer_id: u16) -> Option<CombinedTileset> {
+		self.layers.remove(&(map_id, layer_id))
+	}
+
+	/// Stop treating any layer of the given map as combined
+	pub fn remove_map(&mut self, map_id: u16) {
+		self.layers.retain(|(id, _), _| *id != map_id);
+	}
+
+	/// The offset applied to the texture indices of the given tileset in the given layer
+	///
+	/// This is zero for layers that aren't combined.
+	pub fn offset(&self, tileset_id: &TilesetId, map_id: u16, layer_id: u16) -> usize {
+		self.get(map_id, layer_id)
+			.and_then(|combined| combined.offset(tileset_id))
+			.unwrap_or_default()
+	}
+
+	/// Convert the texture index of a tile in the given layer into an index of its own tileset
+	pub fn local_index(
+		&self,
+		tileset_id: &TilesetId,
+		map_id: u16,
+		layer_id: u16,
+		index: usize,
+	) -> usize {
+		index.saturating_sub(self.offset(tileset_id, map_id, layer_id))
+	}
+
+	/// Get the ID of a tile of the given tileset from its texture index in the given layer
+	///
+	/// Tools reading tile IDs back out of the world should use this rather than looking up
+	/// the raw texture index, which is shifted in combined layers.
+	pub fn get_tile_id(
+		&self,
+		tileset: &Tileset,
+		map_id: u16,
+		layer_id: u16,
+		index: usize,
+	) -> Option<TileId> {
+		let index = self.local_index(tileset.id(), map_id, layer_id, index);
+		tileset.get_tile_id(&index).copied()
+	}
+}
+
+#[cfg(not(feature = "headless"))]
+impl<'w, 's> TilesetCombiner<'w, 's> {
+	/// Combine the given tilesets into a single texture
+	///
+	/// The texture is added to `Assets<Image>`. Tiles are packed into a roughly square grid.
+	pub fn combine(
+		&mut self,
+		tileset_ids: &[TilesetId],
+	) -> Result<CombinedTileset, CombineTilesetsError> {
+		let first = tileset_ids.first().ok_or(CombineTilesetsError::Empty)?;
+		let tile_size = self
+			.tilesets
+			.get_by_id(first)
+			.ok_or(CombineTilesetsError::InvalidTileset(*first))?
+			.tile_size();
+
+		let mut sources = Vec::with_capacity(tileset_ids.len());
+		let mut ranges = Vec::with_capacity(tileset_ids.len());
+		let mut descriptor = None;
+		let mut total = 0;
+		for tileset_id in tileset_ids {
+			let tileset = self
+				.tilesets
+				.get_by_id(tileset_id)
+				.ok_or(CombineTilesetsError::InvalidTileset(*tileset_id))?;
+			if tileset.tile_size() != tile_size {
+				return Err(CombineTilesetsError::IncompatibleTileSize(*tileset_id));
+			}
+
+			let image = self
+				.images
+				.get(tileset.texture())
+				.ok_or(CombineTilesetsError::TextureNotLoaded(*tileset_id))?;
+			let format = image.texture_descriptor.format;
+			let expected = descriptor.get_or_insert_with(|| image.texture_descriptor.clone());
+			if expected.format != format || format.describe().block_dimensions != (1, 1) {
+				return Err(CombineTilesetsError::IncompatibleFormat(*tileset_id));
+			}
+
+			let rects = self
+				.atlases
+				.get(tileset.atlas())
+				.ok_or(CombineTilesetsError::TextureNotLoaded(*tileset_id))?
+				.textures
+				.clone();
+			ranges.push(CombinedRange {
+				tileset_id: *tileset_id,
+				offset: total,
+				len: rects.len(),
+			});
+			total += rects.len();
+			sources.push((tileset.texture().clone(), rects));
+		}
+		let descriptor = descriptor.ok_or(CombineTilesetsError::Empty)?;
+
+		let pixel_size = descriptor.format.describe().block_size as usize;
+		let (tile_width, tile_height) = (tile_size.x as usize, tile_size.y as usize);
+		let columns = ((total as f32).sqrt().ceil() as usize).max(1);
+		let rows = total.div_ceil(columns).max(1);
+		let (width, height) = (columns * tile_width, rows * tile_height);
+
+		let mut data = vec![0; width * height * pixel_size];
+		let mut index = 0;
+		for (texture, rects) in sources {
+			let image = self
+				.images
+				.get(&texture)
+				.ok_or(CombineTilesetsError::TextureNotLoaded(ranges[0].tileset_id))?;
+
+			for rect in rects {
+				let dest = (
+					(index % columns) * tile_width,
+					(index / columns) * tile_height,
+				);
+				copy_rect(image, rect, &mut data, width, dest);
+				index += 1;
+			}
+		}
+
+		let mut image = Image::new(
+			Extent3d {
+				width: width as u32,
+				height: height as u32,
+				depth_or_array_layers: 1,
+			},
+			TextureDimension::D2,
+			data,
+			descriptor.format,
+		);
+		// Keep the usages (such as `COPY_SRC`) the tilemap renderer may rely on
+		image.texture_descriptor.usage = descriptor.usage;
+
+		Ok(CombinedTileset {
+			texture: self.images.add(image),
+			tile_size,
+			size: Vec2::new(width as f32, height as f32),
+			ranges,
+		})
+	}
+}
+
+/// Copy the pixels within the given rectangle of an image into a buffer of the same format
+///
+/// # Arguments
+///
+/// * `source`: The (uncompressed) image to copy from
+/// * `rect`: The area of the image to copy (in pixels)
+/// * `dest`: The pixel data to copy into
+/// * `dest_width`: The width of the destination (in pixels)
+/// * `dest_pos`: The position of the copied area within the destination (in pixels)
+///
+#[cfg(not(feature = "headless"))]
+pub(crate) fn copy_rect(
+	source: &Image,
+	rect: Rect,
+	dest: &mut [u8],
+	dest_width: usize,
+	dest_pos: (usize, usize),
+) {
+	let pixel_size = source.texture_descriptor.format.describe().block_size as usize;
+	let source_width = source.texture_descriptor.size.width as usize;
+	let (sx, sy) = (rect.min.x as usize, rect.min.y as usize);
+	let width = (rect.max.x - rect.min.x) as usize;
+	let height = (rect.max.y - rect.min.y) as usize;
+	let row_len = width * pixel_size;
+
+	for row in 0..height {
+		let src = ((sy + row) * source_width + sx) * pixel_size;
+		let dst = ((dest_pos.1 + row) * dest_width + dest_pos.0) * pixel_size;
+		if let (Some(src), Some(dst)) = (
+			source.data.get(src..src + row_len),
+			dest.get_mut(dst..dst + row_len),
+		) {
+			dst.copy_from_slice(src);
+		}
+	}
+}
+
+/// Shift the texture indices of the given tile index by the given offset
+pub(crate) fn offset_index(index: TileIndex, offset: usize) -> TileIndex {
+	match index {
+		TileIndex::Standard(index) => TileIndex::Standard(index + offset),
+		TileIndex::Animated(start, end, speed) => {
+			TileIndex::Animated(start + offset, end + offset, speed)
+		},
+	}
+}
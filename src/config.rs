@@ -0,0 +1,93 @@
+//! Per-map settings for the behavior of the [`TilePlacer`](crate::prelude::TilePlacer)
+
+use bevy::utils::HashMap;
+
+/// The settings of a single map
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct MapSettings {
+	/// Whether [`TilePlacer::place`](crate::prelude::TilePlacer::place) replaces existing tiles
+	///
+	/// If `false`, placing onto an occupied coordinate fails with
+	/// [`TilePlacementError::TileAlreadyExists`](crate::prelude::TilePlacementError::TileAlreadyExists)
+	/// just like [`TilePlacer::try_place`](crate::prelude::TilePlacer::try_place). Methods that
+	/// explicitly replace tiles (such as `replace` and `toggle`) are not affected.
+	pub overwrite: bool,
+	/// Whether Auto tiles are auto tiled
+	///
+	/// If `false`, Auto tiles are placed as their default texture and never updated.
+	pub auto_tile: bool,
+	/// The layer used by [`TilePlacer::place_default`](crate::prelude::TilePlacer::place_default)
+	pub default_layer: u16,
+	/// The seed used to select Variant tiles
+	///
+	/// If set, the variant placed at a given position is always the same for this seed. Otherwise,
+	/// variants are selected randomly.
+	///
+	/// This also applies to the variants of each Auto tile rule. Without a seed, the variant of
+	/// a rule is derived from the position and the rule itself, so it's stable across neighbor
+	/// updates either way.
+	pub variant_seed: Option<u64>,
+}
+
+impl Default for MapSettings {
+	fn default() -> Self {
+		Self {
+			overwrite: true,
+			auto_tile: true,
+			default_layer: 0,
+			variant_seed: None,
+		}
+	}
+}
+
+/// A resource containing the [`MapSettings`] of each map
+///
+/// Maps without their own settings use the [`defaults`](Self::defaults).
+///
+/// This resource is added automatically by the [`TilesetMapPlugin`](crate::prelude::TilesetMapPlugin).
+///
+/// # Examples
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_tileset_map::prelude::{MapSettings, TilesetMapConfig};
+/// fn setup(mut config: ResMut<TilesetMapConfig>) {
+///   // Never overwrite tiles in the world map
+///   config.insert(0, MapSettings {
+///     overwrite: false,
+///     ..Default::default()
+///   });
+///
+///   // Make variants reproducible everywhere else
+///   config.defaults.variant_seed = Some(1234);
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TilesetMapConfig {
+	/// The settings used by maps without their own settings
+	pub defaults: MapSettings,
+	maps: HashMap<u16, MapSettings>,
+}
+
+impl TilesetMapConfig {
+	/// Get the settings of the given map
+	pub fn get(&self, map_id: u16) -> &MapSettings {
+		self.maps.get(&map_id).unwrap_or(&self.defaults)
+	}
+
+	/// Get the settings of the given map for editing, starting from the defaults if needed
+	pub fn get_mut(&mut self, map_id: u16) -> &mut MapSettings {
+		let defaults = self.defaults;
+		self.maps.entry(map_id).or_insert(defaults)
+	}
+
+	/// Set the settings of the given map
+	pub fn insert(&mut self, map_id: u16, settings: MapSettings) -> Option<MapSettings> {
+		self.maps.insert(map_id, settings)
+	}
+
+	/// Remove the settings of the given map, making it use the defaults again
+	pub fn remove(&mut self, map_id: u16) -> Option<MapSettings> {
+		self.maps.remove(&map_id)
+	}
+}
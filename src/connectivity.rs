@@ -0,0 +1,303 @@
+//! Detecting connected regions of tiles, such as rooms, lakes, or forests
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+use bevy_ecs_tilemap::prelude::{MapId, TilePos};
+use bevy_tileset::prelude::TileId;
+
+use crate::changes::TilemapChanges;
+use crate::coord::TileCoord;
+use crate::metadata::TileMetadata;
+use crate::querier::TileQuerier;
+use crate::registry::TilesetMapRegistry;
+
+/// Which neighbors of a tile are considered connected to it
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Connectivity {
+	/// Tiles are connected to the tiles sharing an edge with them
+	Four,
+	/// Tiles are connected to the tiles sharing an edge or corner with them
+	Eight,
+}
+
+/// A set of connected tiles within a single layer
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConnectedRegion {
+	tiles: HashSet<TilePos>,
+	min: TilePos,
+	max: TilePos,
+}
+
+/// A resource caching the regions found by [`ConnectedRegions::find_cached`]
+///
+/// Cached regions are discarded as soon as any tile in their layer changes. This resource is added
+/// and kept up to date automatically by the [`TilesetMapPlugin`](crate::prelude::TilesetMapPlugin).
+#[derive(Debug, Default)]
+pub struct ConnectedRegionCache {
+	entries: HashMap<(String, u16, u16), Vec<ConnectedRegion>>,
+}
+
+/// A helper system param used to find connected regions of tiles matching a predicate
+///
+/// # Examples
+///
+/// ```
+/// # use bevy_tileset_map::prelude::{Connectivity, ConnectedRegions};
+/// fn find_lakes(mut regions: ConnectedRegions) {
+///   let lakes = regions.find_cached("lakes", 0u16, 0u16, Connectivity::Four, |_, metadata| {
+///     metadata.and_then(|metadata| metadata.get_bool("water")).unwrap_or_default()
+///   });
+///
+///   for lake in lakes {
+///     println!("Found a lake of {} tiles", lake.len());
+///   }
+/// }
+/// ```
+#[derive(SystemParam)]
+pub struct ConnectedRegions<'w, 's> {
+	querier: TileQuerier<'w, 's>,
+	registry: Res<'w, TilesetMapRegistry>,
+	metadata: Query<'w, 's, &'static TileMetadata>,
+	cache: ResMut<'w, ConnectedRegionCache>,
+}
+
+impl ConnectedRegion {
+	/// Returns true if the given position belongs to this region
+	pub fn contains(&self, pos: &TilePos) -> bool {
+		self.tiles.contains(pos)
+	}
+
+	/// Iterate over all positions in this region
+	pub fn iter(&self) -> impl Iterator<Item = &TilePos> {
+		self.tiles.iter()
+	}
+
+	/// The positions of all tiles in this region
+	pub fn tiles(&self) -> &HashSet<TilePos> {
+		&self.tiles
+	}
+
+	/// The inclusive bounding rectangle of this region, given as its minimum and maximum corner
+	pub fn bounds(&self) -> (TilePos, TilePos) {
+		(self.min, self.max)
+	}
+
+	/// The number of tiles in this region
+	pub fn len(&self) -> usize {
+		self.tiles.len()
+	}
+
+	/// Returns true if this region contains no tiles
+	pub fn is_empty(&self) -> bool {
+		self.tiles.is_empty()
+	}
+}
+
+impl ConnectedRegionCache {
+	/// Discard all cached regions
+	pub fn clear(&mut self) {
+		self.entries.clear();
+	}
+
+	/// Discard all cached regions of the given layer
+	pub fn invalidate(&mut self, map_id: u16, layer_id: u16) {
+		self.entries
+			.retain(|(_, map, layer), _| *map != map_id || *layer != layer_id);
+	}
+}
+
+impl<'w, 's> ConnectedRegions<'w, 's> {
+	/// Find all connected regions of tiles in the given layer for which the predicate returns true
+	///
+	/// The predicate is given the ID and metadata of each tile. Regions are ordered by their
+	/// minimum corner, bottom to top and left to right.
+	pub fn find<MId: MapId, F>(
+		&self,
+		map_id: MId,
+		layer_id: u16,
+		connectivity: Connectivity,
+		mut predicate: F,
+	) -> Vec<ConnectedRegion>
+	where
+		F: FnMut(&TileId, Option<&TileMetadata>) -> bool,
+	{
+		let map_id = map_id.into();
+		let size = if let Some(layer) = self.registry.layer(map_id, layer_id) {
+			let settings = &layer.settings;
+			UVec2::new(
+				settings.map_size.0 * settings.chunk_size.0,
+				settings.map_size.1 * settings.chunk_size.1,
+			)
+		} else {
+			return Vec::new();
+		};
+		if size.x == 0 || size.y == 0 {
+			return Vec::new();
+		}
+
+		let positions = self
+			.querier
+			.iter_region(
+				TilePos(0, 0),
+				TilePos(size.x - 1, size.y - 1),
+				map_id,
+				layer_id,
+			)
+			.filter_map(|(coord, entity, id)| {
+				let metadata = self.metadata.get(entity).ok();
+				id.filter(|id| predicate(id, metadata)).map(|_| coord.pos)
+			})
+			.collect::<HashSet<_>>();
+
+		group_regions(positions, map_id, layer_id, connectivity)
+	}
+
+	/// Find all connected regions like [`find`](Self::find), caching the result under the given key
+	///
+	/// The cached result is returned until any tile in the layer changes, so the predicate should
+	/// always be the same for the same key.
+	pub fn find_cached<MId: MapId, F>(
+		&mut self,
+		key: &str,
+		map_id: MId,
+		layer_id: u16,
+		connectivity: Connectivity,
+		predicate: F,
+	) -> &[ConnectedRegion]
+	where
+		F: FnMut(&TileId, Option<&TileMetadata>) -> bool,
+	{
+		let map_id = map_id.into();
+		let cache_key = (key.to_string(), map_id, layer_id);
+		if !self.cache.entries.contains_key(&cache_key) {
+			let regions = self.find(map_id, layer_id, connectivity, predicate);
+			self.cache.entries.insert(cache_key.clone(), regions);
+		}
+
+		&self.cache.entries[&cache_key]
+	}
+
+	/// Get the cache used by [`find_cached`](Self::find_cached)
+	pub fn cache(&mut self) -> &mut ConnectedRegionCache {
+		&mut self.cache
+	}
+}
+
+/// Group the given positions into connected regions, ordered by their minimum corner
+fn group_regions(
+	mut remaining: HashSet<TilePos>,
+	map_id: u16,
+	layer_id: u16,
+	connectivity: Connectivity,
+) -> Vec<ConnectedRegion> {
+	let mut regions = Vec::new();
+	while let Some(start) = remaining.iter().next().copied() {
+		remaining.remove(&start);
+		let mut region = ConnectedRegion {
+			tiles: HashSet::default(),
+			min: start,
+			max: start,
+		};
+
+		let mut stack = vec![start];
+		while let Some(pos) = stack.pop() {
+			region.min = TilePos(region.min.0.min(pos.0), region.min.1.min(pos.1));
+			region.max = TilePos(region.max.0.max(pos.0), region.max.1.max(pos.1));
+			region.tiles.insert(pos);
+
+			let coord = TileCoord::new(pos, map_id, layer_id);
+			let neighbors: Vec<TileCoord> = match connectivity {
+				Connectivity::Four => coord.neighbors4().collect(),
+				Connectivity::Eight => coord.neighbors8().collect(),
+			};
+			for neighbor in neighbors {
+				if remaining.remove(&neighbor.pos) {
+					stack.push(neighbor.pos);
+				}
+			}
+		}
+
+		regions.push(region);
+	}
+
+	regions.sort_by_key(|region| (region.min.1, region.min.0));
+	regions
+}
+
+/// __\[SYSTEM\]__ Discards the cached regions of every layer with changed tiles
+pub(crate) fn invalidate_connected_regions(
+	changes: Res<TilemapChanges>,
+	mut cache: ResMut<ConnectedRegionCache>,
+) {
+	if changes.is_empty() || cache.entries.is_empty() {
+		return;
+	}
+
+	let layers = changes
+		.iter()
+		.map(|(coord, _)| (coord.map_id, coord.layer_id))
+		.collect::<HashSet<_>>();
+	for (map_id, layer_id) in layers {
+		cache.invalidate(map_id, layer_id);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Collect the positions of `#` cells from rows of cells, from the top row down
+	fn positions(rows: &[&str]) -> HashSet<TilePos> {
+		let height = rows.len() as u32;
+		rows.iter()
+			.enumerate()
+			.flat_map(|(row, cells)| {
+				let y = height - 1 - row as u32;
+				cells
+					.chars()
+					.enumerate()
+					.filter(|(_, cell)| *cell == '#')
+					.map(move |(x, _)| TilePos(x as u32, y))
+			})
+			.collect()
+	}
+
+	#[test]
+	fn diagonals_only_connect_with_eight_connectivity() {
+		let tiles = positions(&[
+			"#..#", //
+			".#..", //
+			"..#.", //
+		]);
+
+		let four = group_regions(tiles.clone(), 0, 0, Connectivity::Four);
+		assert_eq!(four.len(), 4);
+		assert!(four.iter().all(|region| region.len() == 1));
+
+		let eight = group_regions(tiles, 0, 0, Connectivity::Eight);
+		assert_eq!(eight.len(), 2);
+		assert_eq!(eight[0].len(), 3);
+		assert_eq!(eight[0].bounds(), (TilePos(0, 0), TilePos(2, 2)));
+		assert_eq!(eight[1].bounds(), (TilePos(3, 2), TilePos(3, 2)));
+	}
+
+	#[test]
+	fn edges_connect_with_either_connectivity() {
+		let tiles = positions(&[
+			"##.", //
+			".##", //
+			"...", //
+			"#..", //
+		]);
+
+		for connectivity in [Connectivity::Four, Connectivity::Eight] {
+			let regions = group_regions(tiles.clone(), 0, 0, connectivity);
+			assert_eq!(regions.len(), 2);
+			// Regions are ordered by their minimum corner, bottom to top
+			assert_eq!(regions[0].bounds(), (TilePos(0, 0), TilePos(0, 0)));
+			assert_eq!(regions[1].len(), 4);
+			assert!(regions[1].contains(&TilePos(2, 2)));
+		}
+	}
+}
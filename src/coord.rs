@@ -1,9 +1,14 @@
-use bevy::math::{IVec2, UVec2};
-use bevy_ecs_tilemap::TilePos;
+use bevy::math::{const_ivec2, IVec2, UVec2, Vec2, Vec3};
+use bevy::reflect::Reflect;
+use bevy::transform::components::GlobalTransform;
+use bevy_ecs_tilemap::{HexType, IsoType, TilePos, TilemapMeshType};
 use bevy_tileset::tileset::coords::TileCoords;
 
 /// The coordinates of a tile, including the `map_id` and `layer_id`
-#[derive(Copy, Clone, Debug, Default, Hash, Eq, PartialEq)]
+///
+/// This is reflected as an opaque value since [`TilePos`] does not implement `Reflect`.
+#[derive(Reflect, Copy, Clone, Debug, Default, Hash, Eq, PartialEq)]
+#[reflect_value(PartialEq, Hash)]
 #[cfg_attr(
 	feature = "serialization",
 	derive(serde::Serialize, serde::Deserialize)
@@ -15,6 +20,264 @@ pub struct TileCoord {
 	pub layer_id: u16,
 }
 
+/// The offsets of the four orthogonal neighbors of a tile
+const NEIGHBORS_4: [IVec2; 4] = [
+	const_ivec2!([0, 1]),
+	const_ivec2!([1, 0]),
+	const_ivec2!([0, -1]),
+	const_ivec2!([-1, 0]),
+];
+
+/// The offsets of all eight neighbors of a tile
+const NEIGHBORS_8: [IVec2; 8] = [
+	const_ivec2!([0, 1]),
+	const_ivec2!([1, 1]),
+	const_ivec2!([1, 0]),
+	const_ivec2!([1, -1]),
+	const_ivec2!([0, -1]),
+	const_ivec2!([-1, -1]),
+	const_ivec2!([-1, 0]),
+	const_ivec2!([-1, 1]),
+];
+
+impl TileCoord {
+	/// Create a new coordinate
+	pub fn new<Pos: Into<TilePos>>(pos: Pos, map_id: u16, layer_id: u16) -> Self {
+		Self {
+			pos: pos.into(),
+			map_id,
+			layer_id,
+		}
+	}
+
+	/// Create a coordinate from a signed position
+	///
+	/// Returns `None` if either axis is negative.
+	pub fn from_ivec2(pos: IVec2, map_id: u16, layer_id: u16) -> Option<Self> {
+		Some(Self::new(to_tile_pos(pos)?, map_id, layer_id))
+	}
+
+	/// Create a coordinate from a position local to the layer
+	///
+	/// # Arguments
+	///
+	/// * `local`: The position relative to the layer's origin
+	/// * `grid_size`: The size of each grid cell (in pixels)
+	/// * `map_id`: The tile map
+	/// * `layer_id`: The layer within the tile map
+	///
+	/// Returns `None` if the position lies below or to the left of the layer.
+	pub fn from_vec2(local: Vec2, grid_size: Vec2, map_id: u16, layer_id: u16) -> Option<Self> {
+		Self::from_ivec2((local / grid_size).floor().as_ivec2(), map_id, layer_id)
+	}
+
+	/// Create a coordinate from a world position
+	///
+	/// # Arguments
+	///
+	/// * `world`: The world position
+	/// * `layer_transform`: The transform of the layer entity
+	/// * `grid_size`: The size of each grid cell (in pixels)
+	/// * `map_id`: The tile map
+	/// * `layer_id`: The layer within the tile map
+	///
+	/// Returns `None` if the position lies below or to the left of the layer.
+	pub fn from_world(
+		world: Vec3,
+		layer_transform: &GlobalTransform,
+		grid_size: Vec2,
+		map_id: u16,
+		layer_id: u16,
+	) -> Option<Self> {
+		let local = layer_transform
+			.compute_matrix()
+			.inverse()
+			.transform_point3(world);
+		Self::from_vec2(local.truncate(), grid_size, map_id, layer_id)
+	}
+
+	/// The position of this tile as a signed vector
+	pub fn as_ivec2(&self) -> IVec2 {
+		IVec2::new(self.pos.0 as i32, self.pos.1 as i32)
+	}
+
+	/// The center of this tile, relative to the layer's origin
+	pub fn to_vec2(self, grid_size: Vec2) -> Vec2 {
+		(self.as_ivec2().as_vec2() + Vec2::splat(0.5)) * grid_size
+	}
+
+	/// The center of this tile in world space
+	pub fn to_world(self, layer_transform: &GlobalTransform, grid_size: Vec2) -> Vec3 {
+		layer_transform
+			.compute_matrix()
+			.transform_point3(self.to_vec2(grid_size).extend(0.0))
+	}
+
+	/// Offset this coordinate by the given amount, keeping its map and layer
+	///
+	/// Returns `None` if the resulting position would be negative.
+	pub fn offset(&self, offset: IVec2) -> Option<Self> {
+		Self::from_ivec2(self.as_ivec2() + offset, self.map_id, self.layer_id)
+	}
+
+	/// Returns true if this coordinate lies within a layer of the given size (in tiles)
+	pub fn in_bounds(&self, size: UVec2) -> bool {
+		self.pos.0 < size.x && self.pos.1 < size.y
+	}
+
+	/// Iterate over the (up to) four orthogonal neighbors of this tile
+	///
+	/// Neighbors with a negative position are skipped.
+	pub fn neighbors4(&self) -> impl Iterator<Item = TileCoord> + '_ {
+		NEIGHBORS_4
+			.iter()
+			.filter_map(move |offset| self.offset(*offset))
+	}
+
+	/// Iterate over the tiles sharing an edge with this tile on a map with the given mesh type
+	///
+	/// Square and isometric maps have four such neighbors while hexagonal maps have six. For
+	/// offset layouts (such as [`HexType::RowOdd`] or [`IsoType::Staggered`]), the affected rows
+	/// (or columns) are assumed to be shifted by half a tile in the positive direction.
+	///
+	/// Neighbors with a negative position are skipped.
+	pub fn neighbors(&self, mesh_type: TilemapMeshType) -> impl Iterator<Item = TileCoord> + '_ {
+		let lattice = Lattice::from(mesh_type);
+		let center = lattice.project(self.pos);
+		lattice.offsets().iter().filter_map(move |offset| {
+			let pos = lattice.unproject(center + *offset)?;
+			Some(Self::new(pos, self.map_id, self.layer_id))
+		})
+	}
+
+	/// Iterate over the (up to) eight orthogonal and diagonal neighbors of this tile
+	///
+	/// Neighbors with a negative position are skipped.
+	pub fn neighbors8(&self) -> impl Iterator<Item = TileCoord> + '_ {
+		NEIGHBORS_8
+			.iter()
+			.filter_map(move |offset| self.offset(*offset))
+	}
+}
+
+/// The offsets of the six neighbors of a tile in axial hex coordinates
+const NEIGHBORS_HEX: [IVec2; 6] = [
+	const_ivec2!([1, 0]),
+	const_ivec2!([1, -1]),
+	const_ivec2!([0, -1]),
+	const_ivec2!([-1, 0]),
+	const_ivec2!([-1, 1]),
+	const_ivec2!([0, 1]),
+];
+
+/// A coordinate system in which the edge-sharing neighbors of every tile lie at fixed offsets
+///
+/// Offset layouts shift every other row (or column), so the offsets to their neighbors depend on
+/// the parity of the tile's position. Converting them into an equivalent lattice (axial
+/// coordinates for hex maps and diamond coordinates for staggered isometric maps) restores fixed
+/// offsets, which is what neighbor lookups and auto tiling rely on.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub(crate) enum Lattice {
+	/// Square and diamond isometric maps, which need no conversion
+	#[default]
+	Square,
+	/// Hex maps already using axial coordinates
+	HexAxial,
+	/// Hex maps with every odd (or even) row shifted
+	HexRows { odd: bool },
+	/// Hex maps with every odd (or even) column shifted
+	HexColumns { odd: bool },
+	/// Staggered isometric maps with every odd row shifted
+	IsoStaggered,
+}
+
+impl From<TilemapMeshType> for Lattice {
+	fn from(mesh_type: TilemapMeshType) -> Self {
+		match mesh_type {
+			TilemapMeshType::Square
+			| TilemapMeshType::Isometric(IsoType::Diamond)
+			| TilemapMeshType::Isometric(IsoType::Diamond3d) => Self::Square,
+			TilemapMeshType::Isometric(IsoType::Staggered) => Self::IsoStaggered,
+			TilemapMeshType::Hexagon(HexType::Row) | TilemapMeshType::Hexagon(HexType::Column) => {
+				Self::HexAxial
+			},
+			TilemapMeshType::Hexagon(HexType::RowOdd) => Self::HexRows { odd: true },
+			TilemapMeshType::Hexagon(HexType::RowEven) => Self::HexRows { odd: false },
+			TilemapMeshType::Hexagon(HexType::ColumnOdd) => Self::HexColumns { odd: true },
+			TilemapMeshType::Hexagon(HexType::ColumnEven) => Self::HexColumns { odd: false },
+		}
+	}
+}
+
+impl Lattice {
+	/// The offsets of all edge-sharing neighbors within this lattice
+	pub fn offsets(self) -> &'static [IVec2] {
+		match self {
+			Self::Square | Self::IsoStaggered => &NEIGHBORS_4,
+			Self::HexAxial | Self::HexRows { .. } | Self::HexColumns { .. } => &NEIGHBORS_HEX,
+		}
+	}
+
+	/// Convert a tile position into this lattice
+	pub fn project(self, pos: TilePos) -> IVec2 {
+		let (x, y) = (pos.0 as i32, pos.1 as i32);
+		match self {
+			Self::Square | Self::HexAxial => IVec2::new(x, y),
+			Self::HexRows { odd } => IVec2::new(x - shift(y, odd), y),
+			Self::HexColumns { odd } => IVec2::new(x, y - shift(x, odd)),
+			Self::IsoStaggered => IVec2::new(x + (y + 1).div_euclid(2), x - y.div_euclid(2)),
+		}
+	}
+
+	/// Convert a position within this lattice back into a tile position
+	///
+	/// Returns `None` if the resulting position would be negative.
+	pub fn unproject(self, pos: IVec2) -> Option<TilePos> {
+		let pos = match self {
+			Self::Square | Self::HexAxial => pos,
+			Self::HexRows { odd } => {
+				if pos.y < 0 {
+					return None;
+				}
+				IVec2::new(pos.x + shift(pos.y, odd), pos.y)
+			},
+			Self::HexColumns { odd } => {
+				if pos.x < 0 {
+					return None;
+				}
+				IVec2::new(pos.x, pos.y + shift(pos.x, odd))
+			},
+			Self::IsoStaggered => {
+				let y = pos.x - pos.y;
+				if y < 0 {
+					return None;
+				}
+				IVec2::new(pos.y + y.div_euclid(2), y)
+			},
+		};
+		to_tile_pos(pos)
+	}
+}
+
+/// The number of half-tile shifts accumulated by the given row (or column) of an offset layout
+fn shift(index: i32, odd: bool) -> i32 {
+	let parity = index & 1;
+	if odd {
+		(index - parity) / 2
+	} else {
+		(index + parity) / 2
+	}
+}
+
+/// Convert a signed position to a [`TilePos`], returning `None` if either axis is negative
+pub(crate) fn to_tile_pos(pos: IVec2) -> Option<TilePos> {
+	if pos.x < 0 || pos.y < 0 {
+		None
+	} else {
+		Some(TilePos(pos.x as u32, pos.y as u32))
+	}
+}
+
 impl TileCoords for TileCoord {
 	fn pos(&self) -> IVec2 {
 		let pos: UVec2 = self.pos.into();
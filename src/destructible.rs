@@ -0,0 +1,336 @@
+//! Tile hit points, damage stages, and destruction
+//!
+//! A tile is destructible if its [`TileMetadata`] defines the [`HEALTH_METADATA_KEY`] key. As it
+//! takes damage through [`TileDamage`], it cycles through the textures listed under the
+//! [`DAMAGE_STAGES_METADATA_KEY`] key. Once its health reaches zero, the tile is removed and a
+//! [`TileDestroyedEvent`] is sent, containing the drops listed under the [`DROPS_METADATA_KEY`] key.
+//!
+//! ```ron
+//! {
+//!   "My Tileset": {
+//!     "Stone": { "health": 30, "damage_stages": "12, 13, 14", "drops": "stone, flint" },
+//!   }
+//! }
+//! ```
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::{GPUAnimated, MapId, Tile, TileParent, TilePos};
+use bevy_tileset::prelude::{TileId, TilesetParent, Tilesets};
+
+use crate::combined::CombinedTilesets;
+use crate::metadata::TileMetadata;
+use crate::placement::{TilePlacementError, TilePlacer};
+
+/// The metadata key used to define a tile's hit points
+///
+/// The value should be a positive integer. Tiles without it cannot be damaged.
+pub const HEALTH_METADATA_KEY: &str = "health";
+
+/// The metadata key used to define the textures displayed as a tile takes damage
+///
+/// The value should be a comma-separated list of texture indices, ordered from least to most
+/// damaged. The stages are spread evenly over the tile's health.
+pub const DAMAGE_STAGES_METADATA_KEY: &str = "damage_stages";
+
+/// The metadata key used to define what a tile drops once destroyed
+///
+/// The value should be a comma-separated list of names, which are passed along in the
+/// [`TileDestroyedEvent`].
+pub const DROPS_METADATA_KEY: &str = "drops";
+
+/// A component storing the hit points of a damaged tile
+///
+/// This is inserted the first time a tile takes damage.
+#[derive(Component, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TileHealth {
+	current: u32,
+	max: u32,
+	/// The texture index of the undamaged tile
+	base_index: u16,
+}
+
+/// The outcome of damaging (or healing) a tile
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DamageOutcome {
+	/// The tile does not define any hit points (or the entity is not a tile)
+	Indestructible,
+	/// The tile's health changed but it is still standing
+	Damaged(TileHealth),
+	/// The tile's health reached zero and it was removed
+	Destroyed,
+}
+
+/// An event sent when a tile is destroyed by [`TileDamage`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct TileDestroyedEvent {
+	/// The (now despawned) tile entity
+	pub entity: Entity,
+	/// The ID of the destroyed tile (if known)
+	pub tile_id: Option<TileId>,
+	/// The position of the tile
+	pub pos: TilePos,
+	/// The map that contained the tile
+	pub map_id: u16,
+	/// The layer that contained the tile
+	pub layer_id: u16,
+	/// The drops listed under the [`DROPS_METADATA_KEY`] key of the tile's metadata
+	pub drops: Vec<String>,
+}
+
+/// A helper system param used to damage, heal, and destroy tiles
+///
+/// Damage is applied using commands, so the health of a tile only updates once they have been
+/// applied. Multiple hits on the same tile within a single frame should be summed up first.
+///
+/// Damage stages are static textures, so animated tiles stop animating once damaged.
+///
+/// # Examples
+///
+/// ```
+/// # use bevy_ecs_tilemap::TilePos;
+/// # use bevy_tileset_map::prelude::{DamageOutcome, TileDamage};
+/// fn mine(mut damage: TileDamage) {
+///   match damage.damage_at(TilePos(4, 2), 0u16, 0u16, 10) {
+///     Ok(DamageOutcome::Destroyed) => println!("Mined!"),
+///     Ok(DamageOutcome::Damaged(health)) => println!("{} hit points left", health.current()),
+///     Ok(DamageOutcome::Indestructible) => println!("Can't mine this"),
+///     Err(err) => println!("Nothing to mine: {}", err),
+///   }
+/// }
+/// ```
+#[derive(SystemParam)]
+pub struct TileDamage<'w, 's> {
+	placer: TilePlacer<'w, 's>,
+	tiles: Query<
+		'w,
+		's,
+		(
+			&'static Tile,
+			&'static TilePos,
+			&'static TileParent,
+			Option<&'static TileMetadata>,
+			Option<&'static TileHealth>,
+			Option<&'static TilesetParent>,
+		),
+	>,
+	tilesets: Tilesets<'w, 's>,
+	combined: Res<'w, CombinedTilesets>,
+	destroyed: EventWriter<'w, 's, TileDestroyedEvent>,
+}
+
+impl TileHealth {
+	/// The remaining hit points
+	pub fn current(&self) -> u32 {
+		self.current
+	}
+
+	/// The hit points of the undamaged tile
+	pub fn max(&self) -> u32 {
+		self.max
+	}
+
+	/// The remaining hit points as a fraction of the maximum, between `0.0` and `1.0`
+	pub fn fraction(&self) -> f32 {
+		if self.max == 0 {
+			0.0
+		} else {
+			self.current as f32 / self.max as f32
+		}
+	}
+
+	/// The index into the damage stages to display, or `None` for the undamaged texture
+	fn stage(&self, stage_count: usize) -> Option<usize> {
+		if stage_count == 0 || self.max == 0 {
+			return None;
+		}
+
+		let lost = (self.max - self.current) as usize;
+		let stage = lost * (stage_count + 1) / self.max as usize;
+		stage.min(stage_count).checked_sub(1)
+	}
+}
+
+impl<'w, 's> TileDamage<'w, 's> {
+	/// Damage the tile at the given coordinate
+	///
+	/// # Errors
+	///
+	/// Returns an error if there is no tile at the given coordinate or if its layer is read-only.
+	///
+	pub fn damage_at<Pos: Into<TilePos>, MId: MapId>(
+		&mut self,
+		pos: Pos,
+		map_id: MId,
+		layer_id: u16,
+		amount: u32,
+	) -> Result<DamageOutcome, TilePlacementError> {
+		let entity = self
+			.placer
+			.try_get_tile_entity(pos.into(), map_id, layer_id)?;
+		self.damage(entity, amount)
+	}
+
+	/// Damage the given tile entity, removing it if its health reaches zero
+	///
+	/// # Errors
+	///
+	/// Returns an error if the tile's layer is read-only.
+	///
+	pub fn damage(
+		&mut self,
+		entity: Entity,
+		amount: u32,
+	) -> Result<DamageOutcome, TilePlacementError> {
+		self.apply(entity, |health| health.saturating_sub(amount))
+	}
+
+	/// Heal the tile at the given coordinate, up to its maximum health
+	///
+	/// # Errors
+	///
+	/// Returns an error if there is no tile at the given coordinate or if its layer is read-only.
+	///
+	pub fn heal_at<Pos: Into<TilePos>, MId: MapId>(
+		&mut self,
+		pos: Pos,
+		map_id: MId,
+		layer_id: u16,
+		amount: u32,
+	) -> Result<DamageOutcome, TilePlacementError> {
+		let entity = self
+			.placer
+			.try_get_tile_entity(pos.into(), map_id, layer_id)?;
+		self.heal(entity, amount)
+	}
+
+	/// Heal the given tile entity, up to its maximum health
+	///
+	/// # Errors
+	///
+	/// Returns an error if the tile's layer is read-only.
+	///
+	pub fn heal(
+		&mut self,
+		entity: Entity,
+		amount: u32,
+	) -> Result<DamageOutcome, TilePlacementError> {
+		self.apply(entity, |health| health.saturating_add(amount))
+	}
+
+	/// Get the health of the given tile entity
+	///
+	/// Returns `None` if the tile is indestructible.
+	pub fn get_health(&self, entity: Entity) -> Option<TileHealth> {
+		let (tile, .., metadata, health, _) = self.tiles.get(entity).ok()?;
+		health
+			.copied()
+			.or_else(|| Self::initial_health(tile, metadata))
+	}
+
+	/// Get the [`TilePlacer`] used to remove destroyed tiles
+	pub fn placer(&mut self) -> &mut TilePlacer<'w, 's> {
+		&mut self.placer
+	}
+
+	/// Update the health of the given tile entity using the given function
+	fn apply<F: FnOnce(u32) -> u32>(
+		&mut self,
+		entity: Entity,
+		update: F,
+	) -> Result<DamageOutcome, TilePlacementError> {
+		let (tile, pos, parent, metadata, health, tileset) = match self.tiles.get(entity) {
+			Ok(result) => result,
+			Err(_) => return Ok(DamageOutcome::Indestructible),
+		};
+		let mut health = match health.copied() {
+			Some(health) => health,
+			None => match Self::initial_health(tile, metadata) {
+				Some(health) => health,
+				None => return Ok(DamageOutcome::Indestructible),
+			},
+		};
+		let (pos, map_id, layer_id) = (*pos, parent.map_id, parent.layer_id);
+		self.placer.check_writable(map_id, layer_id)?;
+
+		health.current = update(health.current).min(health.max);
+
+		if health.current == 0 {
+			let drops = metadata
+				.and_then(|metadata| metadata.get_str(DROPS_METADATA_KEY))
+				.map(|drops| {
+					drops
+						.split(',')
+						.map(str::trim)
+						.filter(|drop| !drop.is_empty())
+						.map(String::from)
+						.collect()
+				})
+				.unwrap_or_default();
+			let tile_id = tileset.and_then(|tileset| {
+				self.combined.get_tile_id(
+					self.tilesets.get_by_id(&tileset.0)?,
+					map_id,
+					layer_id,
+					health.base_index as usize,
+				)
+			});
+
+			self.placer.remove(pos, map_id, layer_id)?;
+			self.destroyed.send(TileDestroyedEvent {
+				entity,
+				tile_id,
+				pos,
+				map_id,
+				layer_id,
+				drops,
+			});
+			return Ok(DamageOutcome::Destroyed);
+		}
+
+		let stages = metadata
+			.and_then(|metadata| metadata.get_str(DAMAGE_STAGES_METADATA_KEY))
+			.map(|stages| {
+				stages
+					.split(',')
+					.filter_map(|index| index.trim().parse::<u16>().ok())
+					.collect::<Vec<_>>()
+			})
+			.unwrap_or_default();
+		let texture_index = match health.stage(stages.len()) {
+			Some(stage) => stages[stage],
+			None => health.base_index,
+		};
+
+		let tile = *tile;
+		let mut cmds = self.placer.commands().entity(entity);
+		cmds.insert(health);
+		if tile.texture_index != texture_index {
+			if texture_index != health.base_index {
+				// Damage stages are static textures
+				cmds.remove::<GPUAnimated>();
+			}
+			cmds.insert(Tile {
+				texture_index,
+				..tile
+			});
+			self.placer.notify_chunk(pos, map_id, layer_id);
+		}
+
+		Ok(DamageOutcome::Damaged(health))
+	}
+
+	/// Get the health of an undamaged tile from its metadata (if any)
+	fn initial_health(tile: &Tile, metadata: Option<&TileMetadata>) -> Option<TileHealth> {
+		let max = metadata?.get_int(HEALTH_METADATA_KEY)?;
+		if max <= 0 {
+			return None;
+		}
+
+		Some(TileHealth {
+			current: max as u32,
+			max: max as u32,
+			base_index: tile.texture_index,
+		})
+	}
+}
@@ -0,0 +1,232 @@
+//! Tile usage statistics and validation reports
+//!
+//! The [`TilesetMapDiagnosticsPlugin`] scans every placed tile at the end of each frame and
+//! stores the results, per map, in the [`TilesetMapDiagnostics`] resource. The totals (along with
+//! those of every map) are also reported through `bevy::diagnostic`, so they show up alongside any
+//! other diagnostics (e.g. when using the `LogDiagnosticsPlugin`).
+//!
+//! Since every tile is visited each frame, this is only intended for development builds.
+
+use bevy::diagnostic::{Diagnostic, DiagnosticId, Diagnostics};
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_ecs_tilemap::{Tile, TileParent};
+use bevy_tileset::prelude::TileId;
+
+use crate::plugin::TilesetMapLabel;
+use crate::querier::TileQuerier;
+
+/// The number of measurements kept by each diagnostic
+const MAX_HISTORY_LENGTH: usize = 20;
+
+/// The base of the IDs of the per-map diagnostics
+const MAP_DIAGNOSTICS_BASE: u128 = 0x7a3c_0d51_92e4_4b8f_a6d2_0000_0000_0000;
+
+/// Plugin for reporting tile usage statistics and inconsistencies
+///
+/// # Examples
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy::diagnostic::LogDiagnosticsPlugin;
+/// # use bevy_tileset_map::prelude::{TilesetMapDiagnosticsPlugin, TilesetMapPlugins};
+/// App::new()
+///   .add_plugins(DefaultPlugins)
+///   .add_plugins(TilesetMapPlugins::default())
+///   .add_plugin(TilesetMapDiagnosticsPlugin)
+///   .add_plugin(LogDiagnosticsPlugin::default())
+///   .run();
+/// ```
+#[derive(Default)]
+pub struct TilesetMapDiagnosticsPlugin;
+
+/// The diagnostics of a single map
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MapDiagnostics {
+	/// The number of placed tiles of each [`TileId`]
+	pub tile_counts: HashMap<TileId, usize>,
+	/// The total number of placed tiles
+	pub total: usize,
+	/// The number of tiles that could not be resolved to a tile of their tileset
+	///
+	/// This includes tiles without a tileset, tiles of tilesets that aren't loaded, and tiles
+	/// whose texture index doesn't belong to any tile of their tileset.
+	pub unknown: usize,
+	/// The number of Auto tiles whose texture doesn't match their neighbors
+	///
+	/// Always zero without the `auto-tile` feature.
+	pub auto_tile_mismatches: usize,
+}
+
+/// A resource containing the latest [`MapDiagnostics`] of every map
+///
+/// This resource is added and kept up to date automatically by the
+/// [`TilesetMapDiagnosticsPlugin`].
+#[derive(Debug, Default)]
+pub struct TilesetMapDiagnostics {
+	maps: HashMap<u16, MapDiagnostics>,
+}
+
+impl TilesetMapDiagnosticsPlugin {
+	/// The total number of placed tiles
+	pub const TILE_COUNT: DiagnosticId =
+		DiagnosticId::from_u128(0x7a3c_0d51_92e4_4b8f_a6d2_1c70_5e08_f301);
+	/// The total number of tiles that could not be resolved to a tile of their tileset
+	pub const UNKNOWN_TILES: DiagnosticId =
+		DiagnosticId::from_u128(0x7a3c_0d51_92e4_4b8f_a6d2_1c70_5e08_f302);
+	/// The total number of Auto tiles whose texture doesn't match their neighbors
+	pub const AUTO_TILE_MISMATCHES: DiagnosticId =
+		DiagnosticId::from_u128(0x7a3c_0d51_92e4_4b8f_a6d2_1c70_5e08_f303);
+
+	/// Get the ID of the tile count diagnostic of the given map
+	pub fn map_tile_count(map_id: u16) -> DiagnosticId {
+		map_diagnostic_id(map_id, 0)
+	}
+
+	/// Get the ID of the unknown tiles diagnostic of the given map
+	pub fn map_unknown_tiles(map_id: u16) -> DiagnosticId {
+		map_diagnostic_id(map_id, 1)
+	}
+
+	/// Get the ID of the Auto tile mismatches diagnostic of the given map
+	pub fn map_auto_tile_mismatches(map_id: u16) -> DiagnosticId {
+		map_diagnostic_id(map_id, 2)
+	}
+}
+
+impl Plugin for TilesetMapDiagnosticsPlugin {
+	fn build(&self, app: &mut App) {
+		app.init_resource::<TilesetMapDiagnostics>()
+			.add_startup_system(setup_diagnostics)
+			.add_system_to_stage(
+				CoreStage::Last,
+				update_diagnostics.label(TilesetMapLabel::UpdateDiagnostics),
+			);
+	}
+}
+
+impl TilesetMapDiagnostics {
+	/// Get the diagnostics of the given map
+	pub fn get(&self, map_id: u16) -> Option<&MapDiagnostics> {
+		self.maps.get(&map_id)
+	}
+
+	/// Iterate over the diagnostics of every map
+	pub fn iter(&self) -> impl Iterator<Item = (u16, &MapDiagnostics)> {
+		self.maps
+			.iter()
+			.map(|(map_id, diagnostics)| (*map_id, diagnostics))
+	}
+
+	/// The diagnostics of all maps combined
+	pub fn total(&self) -> MapDiagnostics {
+		let mut total = MapDiagnostics::default();
+		for diagnostics in self.maps.values() {
+			for (tile_id, count) in &diagnostics.tile_counts {
+				*total.tile_counts.entry(*tile_id).or_default() += count;
+			}
+			total.total += diagnostics.total;
+			total.unknown += diagnostics.unknown;
+			total.auto_tile_mismatches += diagnostics.auto_tile_mismatches;
+		}
+		total
+	}
+}
+
+fn map_diagnostic_id(map_id: u16, kind: u128) -> DiagnosticId {
+	DiagnosticId::from_u128(MAP_DIAGNOSTICS_BASE | ((map_id as u128) << 8) | kind)
+}
+
+/// __\[SYSTEM\]__ Registers the total diagnostics
+fn setup_diagnostics(mut diagnostics: ResMut<Diagnostics>) {
+	diagnostics.add(Diagnostic::new(
+		TilesetMapDiagnosticsPlugin::TILE_COUNT,
+		"tileset_map/tiles",
+		MAX_HISTORY_LENGTH,
+	));
+	diagnostics.add(Diagnostic::new(
+		TilesetMapDiagnosticsPlugin::UNKNOWN_TILES,
+		"tileset_map/unknown_tiles",
+		MAX_HISTORY_LENGTH,
+	));
+	diagnostics.add(Diagnostic::new(
+		TilesetMapDiagnosticsPlugin::AUTO_TILE_MISMATCHES,
+		"tileset_map/auto_tile_mismatches",
+		MAX_HISTORY_LENGTH,
+	));
+}
+
+/// __\[SYSTEM\]__ Collects the diagnostics of every map
+fn update_diagnostics(
+	mut report: ResMut<TilesetMapDiagnostics>,
+	mut diagnostics: ResMut<Diagnostics>,
+	querier: TileQuerier,
+	tiles: Query<(Entity, &TileParent), With<Tile>>,
+	#[cfg(feature = "auto-tile")] auto_tiles: crate::auto::AutoTileQuery,
+	#[cfg(feature = "auto-tile")] tilesets: bevy_tileset::prelude::Tilesets,
+	#[cfg(feature = "auto-tile")] active: Res<crate::activity::ActiveChunks>,
+) {
+	let mut maps = HashMap::<u16, MapDiagnostics>::default();
+	for (entity, parent) in tiles.iter() {
+		let map = maps.entry(parent.map_id).or_default();
+		map.total += 1;
+		match querier.get_tile_id(entity) {
+			Some(tile_id) => *map.tile_counts.entry(tile_id).or_default() += 1,
+			None => map.unknown += 1,
+		}
+	}
+
+	#[cfg(feature = "auto-tile")]
+	for mismatch in crate::auto::find_auto_tile_mismatches(&auto_tiles, &tilesets, &active) {
+		maps.entry(mismatch.coord.map_id)
+			.or_default()
+			.auto_tile_mismatches += 1;
+	}
+
+	// Maps that no longer have any tiles are reported as empty rather than dropped
+	for map_id in report.maps.keys() {
+		maps.entry(*map_id).or_default();
+	}
+	report.maps = maps;
+
+	let total = report.total();
+	diagnostics.add_measurement(TilesetMapDiagnosticsPlugin::TILE_COUNT, total.total as f64);
+	diagnostics.add_measurement(
+		TilesetMapDiagnosticsPlugin::UNKNOWN_TILES,
+		total.unknown as f64,
+	);
+	diagnostics.add_measurement(
+		TilesetMapDiagnosticsPlugin::AUTO_TILE_MISMATCHES,
+		total.auto_tile_mismatches as f64,
+	);
+
+	for (map_id, map) in report.iter() {
+		let measurements = [
+			(
+				TilesetMapDiagnosticsPlugin::map_tile_count(map_id),
+				"tiles",
+				map.total,
+			),
+			(
+				TilesetMapDiagnosticsPlugin::map_unknown_tiles(map_id),
+				"unknown_tiles",
+				map.unknown,
+			),
+			(
+				TilesetMapDiagnosticsPlugin::map_auto_tile_mismatches(map_id),
+				"auto_tile_mismatches",
+				map.auto_tile_mismatches,
+			),
+		];
+		for (id, name, value) in measurements {
+			if diagnostics.get(id).is_none() {
+				diagnostics.add(Diagnostic::new(
+					id,
+					format!("tileset_map/map_{}/{}", map_id, name),
+					MAX_HISTORY_LENGTH,
+				));
+			}
+			diagnostics.add_measurement(id, value as f64);
+		}
+	}
+}
@@ -0,0 +1,253 @@
+//! Ready-made egui panels for building an in-game map editor
+//!
+//! The [`TileEditorPlugin`] draws a side panel containing a layer list, a brush selector, and
+//! a palette of every loaded tileset. The panels read and write the [`TileBrush`] and
+//! [`EditorLayer`] resources, so painting only requires applying the brush wherever the user
+//! clicks:
+//!
+//! ```
+//! # use bevy::prelude::*;
+//! # use bevy_ecs_tilemap::TilePos;
+//! # use bevy_tileset_map::prelude::{EditorLayer, TileBrush, TilePlacer};
+//! fn paint(mut placer: TilePlacer, brush: Res<TileBrush>, layer: Res<EditorLayer>) {
+//! #   let clicked_pos = TilePos(0, 0);
+//!   // ...
+//!   placer
+//!     .apply_brush(&brush, clicked_pos, layer.map_id, layer.layer_id)
+//!     .ok();
+//! }
+//! ```
+//!
+//! The individual widgets ([`tile_palette`], [`brush_selector`], and [`layer_list`]) can also be
+//! used within custom egui windows.
+
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_egui::{egui, EguiContext};
+use bevy_tileset::prelude::{TileId, Tileset};
+
+use crate::placement::{BrushShape, BrushTiles, TileBrush};
+use crate::registry::TilesetMapRegistry;
+
+/// The size (in points) of each tile within a [`tile_palette`]
+const PALETTE_TILE_SIZE: f32 = 32.0;
+
+/// The largest brush size selectable using the [`brush_selector`]
+const MAX_BRUSH_SIZE: u32 = 16;
+
+/// A resource containing the layer edited using the editor panels
+///
+/// This resource is added automatically by the [`TileEditorPlugin`].
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct EditorLayer {
+	/// The map containing the layer
+	pub map_id: u16,
+	/// The layer being edited
+	pub layer_id: u16,
+}
+
+/// Plugin drawing the editor panels on every frame
+///
+/// This requires the `EguiPlugin` from `bevy_egui` to be added as well. A [`TileBrush`] resource
+/// painting the first tile of the first tileset is inserted unless one already exists.
+#[derive(Default)]
+pub struct TileEditorPlugin;
+
+impl Plugin for TileEditorPlugin {
+	fn build(&self, app: &mut App) {
+		if !app.world.contains_resource::<TileBrush>() {
+			app.insert_resource(TileBrush::single(TileId::new(0, 0)));
+		}
+
+		app.init_resource::<EditorLayer>()
+			.add_system(draw_editor_panels);
+	}
+}
+
+/// Show a grid of the tiles in the given tileset, selecting the clicked tile for the given brush
+///
+/// Each tile group is shown once, using its first texture. Selecting a tile keeps the shape of
+/// the brush but replaces its tiles.
+///
+/// Returns true if a tile was selected.
+///
+/// # Arguments
+///
+/// * `ui`: The egui UI to draw in
+/// * `tileset`: The tileset to show
+/// * `atlas`: The texture atlas of the tileset
+/// * `texture`: The egui texture of the tileset (see `EguiContext::add_image`)
+/// * `brush`: The brush to update
+///
+pub fn tile_palette(
+	ui: &mut egui::Ui,
+	tileset: &Tileset,
+	atlas: &TextureAtlas,
+	texture: egui::TextureId,
+	brush: &mut TileBrush,
+) -> bool {
+	let size = tileset.size();
+	let rects = &atlas.textures;
+	let mut shown = Vec::new();
+	let mut selected = false;
+
+	ui.horizontal_wrapped(|ui| {
+		for (index, rect) in rects.iter().enumerate() {
+			let tile_id = match tileset.get_tile_id(&index) {
+				Some(tile_id) if !shown.contains(&tile_id.group_id) => *tile_id,
+				_ => continue,
+			};
+			shown.push(tile_id.group_id);
+
+			let tile_id = TileId::new(tile_id.group_id, tile_id.tileset_id);
+			let uv = egui::Rect::from_min_max(
+				egui::pos2(rect.min.x / size.x, rect.min.y / size.y),
+				egui::pos2(rect.max.x / size.x, rect.max.y / size.y),
+			);
+			let is_selected = matches!(
+				brush.tiles,
+				BrushTiles::Single(current) if current.eq_tile_group(&tile_id)
+			);
+
+			let button = egui::ImageButton::new(texture, [PALETTE_TILE_SIZE; 2])
+				.uv(uv)
+				.selected(is_selected);
+			let mut response = ui.add(button);
+			if let Some(name) = tileset.get_tile_name(&tile_id.group_id) {
+				response = response.on_hover_text(name);
+			}
+
+			if response.clicked() {
+				brush.tiles = BrushTiles::Single(tile_id);
+				selected = true;
+			}
+		}
+	});
+
+	selected
+}
+
+/// Show controls for choosing the shape and size of the given brush
+///
+/// Returns true if the brush was changed.
+pub fn brush_selector(ui: &mut egui::Ui, brush: &mut TileBrush) -> bool {
+	let before = brush.shape;
+	let mut size = match brush.shape {
+		BrushShape::Point => 1,
+		BrushShape::Square(radius) | BrushShape::Circle(radius) => radius.max(1),
+	};
+
+	ui.horizontal(|ui| {
+		if ui
+			.selectable_label(matches!(brush.shape, BrushShape::Point), "Point")
+			.clicked()
+		{
+			brush.shape = BrushShape::Point;
+		}
+		if ui
+			.selectable_label(matches!(brush.shape, BrushShape::Square(..)), "Square")
+			.clicked()
+		{
+			brush.shape = BrushShape::Square(size);
+		}
+		if ui
+			.selectable_label(matches!(brush.shape, BrushShape::Circle(..)), "Circle")
+			.clicked()
+		{
+			brush.shape = BrushShape::Circle(size);
+		}
+	});
+
+	if brush.shape != BrushShape::Point {
+		ui.add(egui::Slider::new(&mut size, 1..=MAX_BRUSH_SIZE).text("Size"));
+		brush.shape = match brush.shape {
+			BrushShape::Square(..) => BrushShape::Square(size),
+			BrushShape::Circle(..) => BrushShape::Circle(size),
+			BrushShape::Point => BrushShape::Point,
+		};
+	}
+
+	if let BrushTiles::Stamp(..) = brush.tiles {
+		ui.label("Stamps always cover their own pattern");
+	}
+
+	brush.shape != before
+}
+
+/// Show every registered layer, selecting the clicked one
+///
+/// Returns true if a layer was selected.
+pub fn layer_list(
+	ui: &mut egui::Ui,
+	registry: &TilesetMapRegistry,
+	selection: &mut EditorLayer,
+) -> bool {
+	let mut layers = registry
+		.layers()
+		.map(|(map_id, layer_id, _)| EditorLayer { map_id, layer_id })
+		.collect::<Vec<_>>();
+	layers.sort_by_key(|layer| (layer.map_id, layer.layer_id));
+
+	if layers.is_empty() {
+		ui.label("No layers");
+		return false;
+	}
+
+	let mut selected = false;
+	for layer in layers {
+		let text = format!("Map {} / Layer {}", layer.map_id, layer.layer_id);
+		if ui.selectable_label(*selection == layer, text).clicked() {
+			*selection = layer;
+			selected = true;
+		}
+	}
+	selected
+}
+
+/// __\[SYSTEM\]__ Draws the editor side panel
+fn draw_editor_panels(
+	mut egui_context: ResMut<EguiContext>,
+	tilesets: Res<Assets<Tileset>>,
+	atlases: Res<Assets<TextureAtlas>>,
+	registry: Res<TilesetMapRegistry>,
+	mut brush: ResMut<TileBrush>,
+	mut layer: ResMut<EditorLayer>,
+	mut textures: Local<HashMap<Handle<Image>, egui::TextureId>>,
+) {
+	let mut tilesets = tilesets
+		.iter()
+		.map(|(_, tileset)| tileset)
+		.collect::<Vec<_>>();
+	tilesets.sort_by_key(|tileset| *tileset.id());
+
+	// Textures must be registered with egui before the context is borrowed for drawing
+	let palettes = tilesets
+		.into_iter()
+		.filter_map(|tileset| {
+			let atlas = atlases.get(tileset.atlas())?;
+			let handle = tileset.texture();
+			let texture = *textures
+				.entry(handle.clone_weak())
+				.or_insert_with(|| egui_context.add_image(handle.clone_weak()));
+			Some((tileset, atlas, texture))
+		})
+		.collect::<Vec<_>>();
+
+	egui::SidePanel::left("tile_editor").show(egui_context.ctx_mut(), |ui| {
+		ui.heading("Layers");
+		layer_list(ui, &registry, &mut layer);
+
+		ui.separator();
+		ui.heading("Brush");
+		brush_selector(ui, &mut brush);
+
+		ui.separator();
+		ui.heading("Tiles");
+		egui::ScrollArea::vertical().show(ui, |ui| {
+			for (tileset, atlas, texture) in palettes {
+				ui.label(tileset.name());
+				tile_palette(ui, tileset, atlas, texture, &mut brush);
+			}
+		});
+	});
+}
@@ -0,0 +1,181 @@
+//! Per-tile elevation offsets for pseudo-3D terrain
+//!
+//! A tile's elevation is a vertical offset (in pixels) stored in the [`TileElevation`] component.
+//! It can be set explicitly with [`TilePlacer::set_elevation`](crate::prelude::TilePlacer::set_elevation)
+//! or derived from the tile's metadata using the [`ELEVATION_METADATA_KEY`] key.
+//!
+//! Since `bevy_ecs_tilemap` meshes all tiles of a chunk together, with the `elevation` feature enabled
+//! elevated tiles are hidden from their chunk and instead drawn as sprites offset by their elevation.
+//! Note that these sprites display the tile's base texture, so animated tiles will not animate while
+//! elevated.
+
+use bevy::prelude::{Component, Reflect, ReflectComponent};
+
+/// The metadata key used to derive a tile's default elevation
+///
+/// The value should be a number, given in pixels.
+pub const ELEVATION_METADATA_KEY: &str = "elevation";
+
+/// The vertical offset (in pixels) applied when rendering a tile
+#[derive(Component, Reflect, Debug, Copy, Clone, Default, PartialEq)]
+#[reflect(Component)]
+#[cfg_attr(
+	feature = "serialization",
+	derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct TileElevation(pub f32);
+
+/// The visibility of an elevated tile
+///
+/// Elevated tiles are always hidden from their chunk (since they're drawn as sprites instead), so
+/// their actual visibility is stored here rather than in their `Tile`.
+#[derive(Component, Debug, Copy, Clone, PartialEq)]
+pub struct ElevatedVisibility {
+	pub visible: bool,
+}
+
+#[cfg(all(feature = "elevation", not(feature = "headless")))]
+pub(crate) use render::*;
+
+#[cfg(all(feature = "elevation", not(feature = "headless")))]
+mod render {
+	use bevy::prelude::*;
+	use bevy::sprite::TextureAtlasSprite;
+	use bevy::utils::HashMap;
+	use bevy_ecs_tilemap::{MapQuery, Tile, TileParent, TilePos};
+	use bevy_tileset::prelude::{TilesetParent, Tilesets};
+
+	use super::{ElevatedVisibility, TileElevation};
+	use crate::combined::CombinedTilesets;
+
+	/// The z-offset of an elevated tile's sprite relative to its layer
+	const ELEVATION_Z_OFFSET: f32 = 0.1;
+
+	/// Tracks the sprites used to draw elevated tiles
+	#[derive(Default)]
+	pub(crate) struct ElevatedTiles {
+		/// Maps tile entities to their sprite entities
+		sprites: HashMap<Entity, Entity>,
+	}
+
+	/// __\[SYSTEM\]__ Spawns, updates, and despawns the sprites used to draw elevated tiles
+	pub(crate) fn sync_elevated_tiles(
+		mut commands: Commands,
+		mut elevated: ResMut<ElevatedTiles>,
+		tilesets: Tilesets,
+		combined: Res<CombinedTilesets>,
+		mut map_query: MapQuery,
+		mut tiles: Query<
+			(
+				Entity,
+				&TilePos,
+				&TileParent,
+				&mut Tile,
+				&TilesetParent,
+				&TileElevation,
+				Option<&ElevatedVisibility>,
+			),
+			Or<(
+				Changed<TileElevation>,
+				Changed<Tile>,
+				Changed<ElevatedVisibility>,
+			)>,
+		>,
+		mut restored: Query<
+			(
+				&TilePos,
+				&TileParent,
+				&mut Tile,
+				Option<&ElevatedVisibility>,
+			),
+			Without<TileElevation>,
+		>,
+		mut sprites: Query<(&mut Transform, &mut TextureAtlasSprite, &mut Visibility)>,
+		removed: RemovedComponents<TileElevation>,
+	) {
+		// === Removed Elevations === //
+		for entity in removed.iter() {
+			if let Some(sprite) = elevated.sprites.remove(&entity) {
+				commands.entity(sprite).despawn();
+			}
+
+			// Restore the tile's visibility in its chunk (if it still exists)
+			if let Ok((pos, parent, mut tile, Some(visibility))) = restored.get_mut(entity) {
+				tile.visible = visibility.visible;
+				commands.entity(entity).remove::<ElevatedVisibility>();
+				map_query.notify_chunk_for_tile(*pos, parent.map_id, parent.layer_id);
+			}
+		}
+
+		// === Added/Changed Elevations === //
+		for (entity, pos, parent, mut tile, tileset_parent, elevation, visibility) in
+			tiles.iter_mut()
+		{
+			let tileset = if let Some(tileset) = tilesets.get_by_id(&tileset_parent.0) {
+				tileset
+			} else {
+				continue;
+			};
+
+			let (layer_entity, tile_size) = if let Some((layer_entity, layer)) =
+				map_query.get_layer(parent.map_id, parent.layer_id)
+			{
+				(layer_entity, layer.settings.tile_size)
+			} else {
+				continue;
+			};
+
+			let translation = Vec3::new(
+				(pos.0 as f32 + 0.5) * tile_size.0,
+				(pos.1 as f32 + 0.5) * tile_size.1 + elevation.0,
+				ELEVATION_Z_OFFSET,
+			);
+			// Anything showing the tile in its chunk means it should be visible
+			let visible = tile.visible || visibility.is_some_and(|visibility| visibility.visible);
+			if visibility.map(|visibility| visibility.visible) != Some(visible) {
+				commands
+					.entity(entity)
+					.insert(ElevatedVisibility { visible });
+			}
+
+			// The sprite uses the tileset's own atlas, even in a combined layer
+			let offset = combined.offset(tileset.id(), parent.map_id, parent.layer_id);
+			let sprite = TextureAtlasSprite {
+				index: (tile.texture_index as usize).saturating_sub(offset),
+				color: tile.color,
+				flip_x: tile.flip_x,
+				flip_y: tile.flip_y,
+				..Default::default()
+			};
+
+			let existing = elevated.sprites.get(&entity).copied();
+			if let Some((mut transform, mut atlas_sprite, mut sprite_visibility)) =
+				existing.and_then(|sprite_entity| sprites.get_mut(sprite_entity).ok())
+			{
+				transform.translation = translation;
+				*atlas_sprite = sprite;
+				sprite_visibility.is_visible = visible;
+			} else {
+				let sprite_entity = commands
+					.spawn_bundle(SpriteSheetBundle {
+						sprite,
+						texture_atlas: tileset.atlas().clone(),
+						transform: Transform::from_translation(translation),
+						visibility: Visibility {
+							is_visible: visible,
+						},
+						..Default::default()
+					})
+					.id();
+				commands.entity(layer_entity).add_child(sprite_entity);
+				elevated.sprites.insert(entity, sprite_entity);
+			}
+
+			// Hide the tile in its chunk so it isn't drawn twice
+			if tile.visible {
+				tile.visible = false;
+				map_query.notify_chunk_for_tile(*pos, parent.map_id, parent.layer_id);
+			}
+		}
+	}
+}
@@ -0,0 +1,65 @@
+//! A general-purpose per-tile bitfield
+
+use bevy::prelude::{Component, Reflect, ReflectComponent};
+
+/// A compact set of user-defined flags attached to a tile
+///
+/// The meaning of each bit is entirely up to you. Flags are set using the
+/// [`TilePlacer`](crate::prelude::TilePlacer) and are included in serialization. Since this is
+/// a regular component, tiles can be found by their flags using a standard `Query<&TileFlags>`.
+///
+/// # Examples
+///
+/// ```
+/// # use bevy_tileset_map::prelude::TileFlags;
+/// const BURNING: u32 = 1 << 0;
+/// const WET: u32 = 1 << 1;
+///
+/// let mut flags = TileFlags::default();
+/// flags.insert(BURNING);
+/// assert!(flags.contains(BURNING));
+/// assert!(!flags.contains(BURNING | WET));
+///
+/// flags.remove(BURNING);
+/// assert!(flags.is_empty());
+/// ```
+#[derive(Component, Reflect, Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+#[reflect(Component)]
+#[cfg_attr(
+	feature = "serialization",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(transparent)
+)]
+pub struct TileFlags(pub u32);
+
+impl TileFlags {
+	/// Returns true if all of the given bits are set
+	pub fn contains(&self, bits: u32) -> bool {
+		self.0 & bits == bits
+	}
+
+	/// Returns true if any of the given bits are set
+	pub fn intersects(&self, bits: u32) -> bool {
+		self.0 & bits != 0
+	}
+
+	/// Set the given bits
+	pub fn insert(&mut self, bits: u32) {
+		self.0 |= bits;
+	}
+
+	/// Clear the given bits
+	pub fn remove(&mut self, bits: u32) {
+		self.0 &= !bits;
+	}
+
+	/// Toggle the given bits
+	pub fn toggle(&mut self, bits: u32) {
+		self.0 ^= bits;
+	}
+
+	/// Returns true if no bits are set
+	pub fn is_empty(&self) -> bool {
+		self.0 == 0
+	}
+}
@@ -0,0 +1,250 @@
+//! Fog of war drawn onto a dedicated overlay layer
+//!
+//! The [`FogOfWarPlugin`] tracks the explored/visible state of every tile within an area and keeps
+//! an overlay layer in sync with it: unexplored tiles are covered with the configured fog tile,
+//! explored tiles that aren't currently visible are covered with the (optional) explored tile, and
+//! visible tiles are left uncovered.
+//!
+//! The overlay layer itself needs to be created by you, just like any other layer.
+
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use bevy_ecs_tilemap::TilePos;
+use bevy_tileset::prelude::TileId;
+
+use crate::placement::TilePlacer;
+use crate::plugin::{TilesetMapLabel, TilesetMapStage};
+
+/// Plugin for setting up fog of war
+///
+/// # Examples
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy::math::UVec2;
+/// # use bevy_tileset_map::prelude::{FogOfWarConfig, FogOfWarPlugin, TileId, TilesetMapPlugins};
+/// # let fog_tile = TileId::new(0, 0);
+/// App::new()
+///   .add_plugins(TilesetMapPlugins::default())
+///   .insert_resource(FogOfWarConfig {
+///     map_id: 0,
+///     layer_id: 5,
+///     size: UVec2::new(64, 64),
+///     unexplored: fog_tile,
+///     explored: None,
+///   })
+///   .add_plugin(FogOfWarPlugin);
+/// ```
+#[derive(Default)]
+pub struct FogOfWarPlugin;
+
+/// A resource configuring the fog of war overlay
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct FogOfWarConfig {
+	/// The map containing the overlay layer
+	pub map_id: u16,
+	/// The overlay layer the fog is drawn on
+	pub layer_id: u16,
+	/// The size (in tiles) of the area covered by fog
+	pub size: UVec2,
+	/// The tile covering unexplored tiles
+	pub unexplored: TileId,
+	/// The tile covering explored tiles that aren't currently visible
+	///
+	/// If `None`, explored tiles are left uncovered.
+	pub explored: Option<TileId>,
+}
+
+/// The fog state of a single tile
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum FogState {
+	/// The tile has never been revealed
+	Unexplored,
+	/// The tile has been revealed before but isn't currently visible
+	Explored,
+	/// The tile is currently visible
+	Visible,
+}
+
+/// A resource containing the explored/visible state of every tile covered by fog
+///
+/// Only the explored tiles are serialized: visibility is expected to be recomputed every frame.
+#[derive(Debug, Clone)]
+#[cfg_attr(
+	feature = "serialization",
+	derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct FogOfWar {
+	explored: HashSet<(u32, u32)>,
+	#[cfg_attr(feature = "serialization", serde(skip))]
+	visible: HashSet<(u32, u32)>,
+	/// The tiles whose overlay needs to be redrawn
+	#[cfg_attr(feature = "serialization", serde(skip))]
+	dirty: HashSet<(u32, u32)>,
+	/// Whether the entire overlay needs to be redrawn
+	#[cfg_attr(feature = "serialization", serde(skip, default = "redraw_all"))]
+	redraw_all: bool,
+}
+
+impl Plugin for FogOfWarPlugin {
+	fn build(&self, app: &mut App) {
+		app.init_resource::<FogOfWar>().add_system_to_stage(
+			TilesetMapStage,
+			update_fog_of_war.label(TilesetMapLabel::UpdateFogOfWar),
+		);
+	}
+}
+
+impl Default for FogOfWar {
+	fn default() -> Self {
+		Self {
+			explored: HashSet::default(),
+			visible: HashSet::default(),
+			dirty: HashSet::default(),
+			redraw_all: redraw_all(),
+		}
+	}
+}
+
+fn redraw_all() -> bool {
+	true
+}
+
+impl FogOfWar {
+	/// Get the fog state of the given tile
+	pub fn state(&self, pos: TilePos) -> FogState {
+		let cell = (pos.0, pos.1);
+		if self.visible.contains(&cell) {
+			FogState::Visible
+		} else if self.explored.contains(&cell) {
+			FogState::Explored
+		} else {
+			FogState::Unexplored
+		}
+	}
+
+	/// Returns true if the given tile has ever been revealed
+	pub fn is_explored(&self, pos: TilePos) -> bool {
+		self.explored.contains(&(pos.0, pos.1))
+	}
+
+	/// Returns true if the given tile is currently visible
+	pub fn is_visible(&self, pos: TilePos) -> bool {
+		self.visible.contains(&(pos.0, pos.1))
+	}
+
+	/// Reveal a single tile, making it visible and explored
+	pub fn reveal(&mut self, pos: TilePos) {
+		let cell = (pos.0, pos.1);
+		self.explored.insert(cell);
+		if self.visible.insert(cell) {
+			self.dirty.insert(cell);
+		}
+	}
+
+	/// Reveal all tiles within the given radius (in tiles) of the given center
+	pub fn reveal_circle(&mut self, center: TilePos, radius: u32) {
+		let radius = radius as i64;
+		let (center_x, center_y) = (center.0 as i64, center.1 as i64);
+		for y in (center_y - radius).max(0)..=center_y + radius {
+			for x in (center_x - radius).max(0)..=center_x + radius {
+				let (dx, dy) = (x - center_x, y - center_y);
+				if dx * dx + dy * dy <= radius * radius {
+					self.reveal(TilePos(x as u32, y as u32));
+				}
+			}
+		}
+	}
+
+	/// Reveal all tiles within the given rectangle (inclusive)
+	pub fn reveal_rect(&mut self, from: TilePos, to: TilePos) {
+		for y in from.1.min(to.1)..=from.1.max(to.1) {
+			for x in from.0.min(to.0)..=from.0.max(to.0) {
+				self.reveal(TilePos(x, y));
+			}
+		}
+	}
+
+	/// Mark all currently visible tiles as merely explored
+	///
+	/// This is useful for dynamic vision: clear the visible tiles at the start of every frame and
+	/// reveal the tiles around each viewer again.
+	pub fn clear_visible(&mut self) {
+		self.dirty.extend(self.visible.drain());
+	}
+
+	/// Forget all explored and visible tiles, covering everything in fog again
+	pub fn reset(&mut self) {
+		self.explored.clear();
+		self.visible.clear();
+		self.dirty.clear();
+		self.redraw_all = true;
+	}
+
+	/// Redraw the entire overlay
+	///
+	/// This happens automatically when the [`FogOfWarConfig`] changes.
+	pub fn redraw(&mut self) {
+		self.redraw_all = true;
+	}
+
+	/// The tile that should cover the given tile (if any)
+	fn cover(&self, pos: TilePos, config: &FogOfWarConfig) -> Option<TileId> {
+		match self.state(pos) {
+			FogState::Unexplored => Some(config.unexplored),
+			FogState::Explored => config.explored,
+			FogState::Visible => None,
+		}
+	}
+}
+
+/// __\[SYSTEM\]__ Keeps the fog overlay layer in sync with the [`FogOfWar`]
+fn update_fog_of_war(
+	config: Option<Res<FogOfWarConfig>>,
+	mut fog: ResMut<FogOfWar>,
+	mut placer: TilePlacer,
+) {
+	let config = if let Some(config) = config {
+		config
+	} else {
+		return;
+	};
+
+	if config.is_changed() {
+		fog.redraw_all = true;
+	}
+
+	if !fog.redraw_all && fog.dirty.is_empty() {
+		return;
+	}
+
+	if !placer.has_layer(config.map_id, config.layer_id) {
+		// Wait for the overlay layer to be created
+		return;
+	}
+
+	let cells: Vec<(u32, u32)> = if fog.redraw_all {
+		(0..config.size.y)
+			.flat_map(|y| (0..config.size.x).map(move |x| (x, y)))
+			.collect()
+	} else {
+		fog.dirty.iter().copied().collect()
+	};
+	fog.redraw_all = false;
+	fog.dirty.clear();
+
+	for (x, y) in cells {
+		if x >= config.size.x || y >= config.size.y {
+			continue;
+		}
+
+		let pos = TilePos(x, y);
+		// Failures here just mean the tile is already covered correctly (or already gone)
+		let _ = match fog.cover(pos, &config) {
+			Some(tile_id) => placer
+				.replace(tile_id, pos, config.map_id, config.layer_id)
+				.map(|_| ()),
+			None => placer.remove(pos, config.map_id, config.layer_id),
+		};
+	}
+}
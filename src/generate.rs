@@ -0,0 +1,179 @@
+//! Tools for procedurally generating tiles
+//!
+//! Generation is expressed as a closure that produces an optional [`TileId`] for every position
+//! in a region, which is then applied in a single batch using [`TilePlacer::fill_with`]. The
+//! adapters in this module turn noise functions into such closures.
+
+use bevy_ecs_tilemap::prelude::{MapId, TilePos};
+use bevy_tileset::prelude::TileId;
+
+use crate::placement::{AutoTileAccess, TilePlacementError, TilePlacer};
+
+impl<'w, 's, A: AutoTileAccess> TilePlacer<'w, 's, A> {
+	/// Fill the given rectangle (inclusive) using a tile-producing function
+	///
+	/// The function is called once for every position in the rectangle. Positions for which it
+	/// returns `None` are left untouched. All affected chunks are notified once the entire
+	/// region has been filled, rather than once per tile.
+	///
+	/// Returns the number of tiles that were placed.
+	///
+	/// # Arguments
+	///
+	/// * `generator`: The function producing the tile for each position
+	/// * `from`: A corner of the rectangle
+	/// * `to`: The opposite corner of the rectangle
+	/// * `map_id`: The tile map
+	/// * `layer_id`: The layer within the tile map
+	///
+	/// # Examples
+	///
+	/// ```
+	/// # use bevy_ecs_tilemap::TilePos;
+	/// # use bevy_tileset_map::prelude::{noise_threshold, TileId, TilePlacer, ValueNoise};
+	/// fn generate(mut placer: TilePlacer) {
+	///   let noise = ValueNoise::new(1234);
+	///   let rocks = noise_threshold(move |x, y| noise.get(x, y), 0.1, 0.6, TileId::new(0, 0));
+	///   placer.fill_with(rocks, TilePos(0, 0), TilePos(63, 63), 0u16, 0u16).unwrap();
+	/// }
+	/// ```
+	pub fn fill_with<F: FnMut(TilePos) -> Option<TileId>, Pos: Into<TilePos>, MId: MapId>(
+		&mut self,
+		mut generator: F,
+		from: Pos,
+		to: Pos,
+		map_id: MId,
+		layer_id: u16,
+	) -> Result<usize, TilePlacementError> {
+		let (from, to) = (from.into(), to.into());
+		self.begin_batch();
+
+		let mut count = 0;
+		let mut result = Ok(());
+		'fill: for y in from.1.min(to.1)..=from.1.max(to.1) {
+			for x in from.0.min(to.0)..=from.0.max(to.0) {
+				let pos = TilePos(x, y);
+				if let Some(tile_id) = generator(pos) {
+					if let Err(err) = self.place(tile_id, pos, map_id, layer_id) {
+						result = Err(err);
+						break 'fill;
+					}
+					count += 1;
+				}
+			}
+		}
+
+		self.end_batch();
+		result.map(|_| count)
+	}
+}
+
+/// Create a generator that places the given tile wherever the noise exceeds a threshold
+///
+/// # Arguments
+///
+/// * `noise`: A noise function sampled at `(x, y)`
+/// * `scale`: The factor applied to tile coordinates before sampling
+/// * `threshold`: The value the noise must exceed in order to place the tile
+/// * `tile_id`: The tile to place
+///
+pub fn noise_threshold<N: Fn(f64, f64) -> f64>(
+	noise: N,
+	scale: f64,
+	threshold: f64,
+	tile_id: TileId,
+) -> impl FnMut(TilePos) -> Option<TileId> {
+	move |pos| {
+		let value = noise(pos.0 as f64 * scale, pos.1 as f64 * scale);
+		(value > threshold).then_some(tile_id)
+	}
+}
+
+/// Create a generator that selects a tile based on which band the noise falls within
+///
+/// Each band is given as its (exclusive) upper bound along with the tile to place, and should be
+/// sorted in ascending order. Values above the last band produce no tile. A band can map to
+/// `None` to leave that range empty.
+///
+/// # Arguments
+///
+/// * `noise`: A noise function sampled at `(x, y)`
+/// * `scale`: The factor applied to tile coordinates before sampling
+/// * `bands`: The upper bound and tile of each band
+///
+/// # Examples
+///
+/// ```
+/// # use bevy_ecs_tilemap::TilePos;
+/// # use bevy_tileset_map::prelude::{noise_bands, TileId, ValueNoise};
+/// let noise = ValueNoise::new(0);
+/// let mut terrain = noise_bands(
+///   move |x, y| noise.get(x, y),
+///   0.05,
+///   vec![
+///     (0.3, Some(TileId::new(0, 0))), // Water
+///     (0.4, Some(TileId::new(1, 0))), // Sand
+///     (1.0, Some(TileId::new(2, 0))), // Grass
+///   ],
+/// );
+/// assert!(terrain(TilePos(0, 0)).is_some());
+/// ```
+pub fn noise_bands<N: Fn(f64, f64) -> f64>(
+	noise: N,
+	scale: f64,
+	bands: Vec<(f64, Option<TileId>)>,
+) -> impl FnMut(TilePos) -> Option<TileId> {
+	move |pos| {
+		let value = noise(pos.0 as f64 * scale, pos.1 as f64 * scale);
+		bands
+			.iter()
+			.find(|(upper, _)| value < *upper)
+			.and_then(|(_, tile_id)| *tile_id)
+	}
+}
+
+/// A simple, seeded 2D value noise in the range `[0, 1)`
+///
+/// This is mainly meant for quick prototyping. Any other noise function can be used with the
+/// adapters in this module, such as those provided by the `noise` crate.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ValueNoise {
+	seed: u32,
+}
+
+impl ValueNoise {
+	/// Create a new noise function with the given seed
+	pub fn new(seed: u32) -> Self {
+		Self { seed }
+	}
+
+	/// Sample the noise at the given point
+	pub fn get(&self, x: f64, y: f64) -> f64 {
+		let (x0, y0) = (x.floor(), y.floor());
+		let (tx, ty) = (smoothstep(x - x0), smoothstep(y - y0));
+		let (ix, iy) = (x0 as i64, y0 as i64);
+
+		let top = lerp(self.lattice(ix, iy), self.lattice(ix + 1, iy), tx);
+		let bottom = lerp(self.lattice(ix, iy + 1), self.lattice(ix + 1, iy + 1), tx);
+		lerp(top, bottom, ty)
+	}
+
+	/// A pseudo-random value for the given lattice point
+	fn lattice(&self, x: i64, y: i64) -> f64 {
+		let mut hash = (x as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+			^ (y as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F)
+			^ (self.seed as u64).wrapping_mul(0x1656_67B1_9E37_79F9);
+		hash ^= hash >> 33;
+		hash = hash.wrapping_mul(0xFF51_AFD7_ED55_8CCD);
+		hash ^= hash >> 33;
+		(hash >> 11) as f64 / (1u64 << 53) as f64
+	}
+}
+
+fn smoothstep(t: f64) -> f64 {
+	t * t * (3.0 - 2.0 * t)
+}
+
+fn lerp(a: f64, b: f64, t: f64) -> f64 {
+	a + (b - a) * t
+}
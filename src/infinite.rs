@@ -0,0 +1,766 @@
+//! Chunk-based infinite maps that are streamed in and out around tracked entities
+//!
+//! An infinite map is split into fixed-size regions, each of which is backed by its own
+//! `bevy_ecs_tilemap` map (with its own map ID) positioned at the region's offset. The
+//! [`InfiniteMapPlugin`] spawns the regions around every entity marked with [`InfiniteMapTracker`]
+//! and despawns them once all trackers have moved far enough away.
+//!
+//! Tiles are addressed using global coordinates, which the [`InfiniteMap`] translates to the map
+//! ID and position of the region containing them. The [`InfinitePlacer`] does this automatically.
+//!
+//! The contents of a region can be generated (or loaded) using the
+//! [`generator`](InfiniteMapConfig::with_generator) callback or by listening for
+//! [`RegionLoadedEvent`]s. Regions that are no longer needed are unloaded according to the
+//! configured [`RetentionPolicy`]. A [`RegionUnloadingEvent`] is sent one frame before a region is
+//! despawned, giving systems a chance to save its tiles, followed by a [`RegionDespawnedEvent`]
+//! once it is gone.
+//!
+//! Sparse worlds don't need to keep every region around them loaded: placing a tile using
+//! [`InfinitePlacer::place_or_spawn`] spawns its region on demand. Regions whose tiles have all
+//! been removed can be despawned periodically by setting [`InfiniteMapConfig::collect_empty`].
+
+use std::fmt::{Debug, Formatter};
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+use bevy_ecs_tilemap::{ChunkSize, MapSize, TileParent, TilePos};
+use bevy_tileset::prelude::{TileId, TilesetId, Tilesets};
+
+use crate::layers::TileLayerManager;
+use crate::placement::{PlacedTile, TilePlacementError, TilePlacementResult, TilePlacer};
+use crate::plugin::{TilesetMapLabel, TilesetMapStage};
+
+/// A callback used to generate the contents of a newly spawned region
+pub type RegionGenerator = Arc<dyn Fn(&mut TilePlacer, &InfiniteRegion) + Send + Sync>;
+
+/// Plugin for setting up infinite maps
+///
+/// # Examples
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_tileset_map::prelude::{InfiniteMapConfig, InfiniteMapPlugin, TileId, TilesetMapPlugins};
+/// # let grass = TileId::new(0, 0);
+/// App::new()
+///   .add_plugins(TilesetMapPlugins::default())
+///   .insert_resource(
+///     InfiniteMapConfig {
+///       layers: vec![(0, grass.tileset_id)],
+///       ..Default::default()
+///     }
+///     .with_generator(move |placer, region| {
+///       for pos in region.positions() {
+///         placer.place(grass, pos, region.map_id, 0).ok();
+///       }
+///     }),
+///   )
+///   .add_plugin(InfiniteMapPlugin);
+/// ```
+#[derive(Default)]
+pub struct InfiniteMapPlugin;
+
+/// A resource configuring the [`InfiniteMapPlugin`]
+#[derive(Clone)]
+pub struct InfiniteMapConfig {
+	/// The map IDs that may be assigned to regions
+	///
+	/// These should not be used by any other map. The number of IDs limits how many regions can
+	/// be loaded at once.
+	pub map_ids: Range<u16>,
+	/// The size of each region (in chunks)
+	pub region_size: MapSize,
+	/// The size of each chunk (in tiles)
+	pub chunk_size: ChunkSize,
+	/// The layers created in every region, given as `(layer_id, tileset_id)`
+	///
+	/// All tilesets should share the same tile size, since the tile size of the first one is
+	/// used to position the regions.
+	pub layers: Vec<(u16, TilesetId)>,
+	/// How many regions around each tracker are loaded
+	///
+	/// Regions within this radius are never unloaded, regardless of the retention policy.
+	pub load_radius: u32,
+	/// Which regions outside the load radius are kept loaded
+	pub retention: RetentionPolicy,
+	/// The callback used to generate the contents of a newly spawned region
+	pub generator: Option<RegionGenerator>,
+	/// How often loaded regions without any tiles are despawned
+	///
+	/// Regions within the load radius of a tracker are never collected, since they would be
+	/// spawned (and generated) again right away. If `None`, empty regions are only unloaded
+	/// according to the retention policy.
+	pub collect_empty: Option<Duration>,
+}
+
+/// A policy deciding which regions outside the load radius of every tracker are kept loaded
+///
+/// Keeping regions loaded prevents them from being reloaded repeatedly when a tracker moves back
+/// and forth along a border, at the cost of memory.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum RetentionPolicy {
+	/// Keep all regions within the given radius (in regions) of any tracker
+	///
+	/// Radii smaller than the [`load_radius`](InfiniteMapConfig::load_radius) behave like the
+	/// load radius itself.
+	Distance(u32),
+	/// Keep at most the given number of regions, unloading those farthest from any tracker first
+	KeepCount(usize),
+	/// Keep at most the given number of regions, unloading the least recently visited ones first
+	///
+	/// A region is visited on every frame it lies within the load radius of any tracker.
+	LeastRecentlyUsed(usize),
+}
+
+/// A marker component for entities (such as cameras) that keep the regions around them loaded
+#[derive(Component, Reflect, Debug, Copy, Clone, Default)]
+#[reflect(Component)]
+pub struct InfiniteMapTracker;
+
+/// The lifecycle state of a region
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum RegionState {
+	/// The region's map and layers are being spawned
+	Spawning,
+	/// The region has been generated and is ready for use
+	Loaded,
+	/// The region will be despawned at the start of the next frame
+	Unloading,
+}
+
+/// A single region of an infinite map
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct InfiniteRegion {
+	/// The position of the region (in regions)
+	pub position: IVec2,
+	/// The ID of the map backing this region
+	pub map_id: u16,
+	/// The entity of the map backing this region
+	pub map_entity: Entity,
+	/// The size of the region (in tiles)
+	pub size: UVec2,
+	/// The current state of the region
+	pub state: RegionState,
+	/// The last frame (as counted by the [`InfiniteMap`]) this region was visited by a tracker
+	pub last_visited: u64,
+}
+
+/// A resource containing the regions of the infinite map
+///
+/// This resource is added and kept up to date automatically by the [`InfiniteMapPlugin`].
+#[derive(Debug, Default)]
+pub struct InfiniteMap {
+	regions: HashMap<IVec2, InfiniteRegion>,
+	map_ids: HashMap<u16, IVec2>,
+	region_size: UVec2,
+	tile_size: Vec2,
+	frame: u64,
+	/// The regions to spawn regardless of the trackers
+	requested: HashSet<IVec2>,
+	/// The tiles to place once their region has been generated
+	pending: HashMap<IVec2, Vec<PendingTile>>,
+}
+
+/// A tile waiting for its region to be spawned
+#[derive(Debug, Copy, Clone)]
+struct PendingTile {
+	tile_id: TileId,
+	pos: TilePos,
+	layer_id: u16,
+}
+
+/// An event sent once a region has been spawned and generated
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RegionLoadedEvent {
+	/// The loaded region
+	pub region: InfiniteRegion,
+}
+
+/// An event sent one frame before a region is despawned
+///
+/// The region's tiles can still be accessed (and saved) until the end of the frame.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RegionUnloadingEvent {
+	/// The region being unloaded
+	pub region: InfiniteRegion,
+}
+
+/// An event sent when a loaded region is unloaded because it no longer contains any tiles
+///
+/// This is sent alongside the [`RegionUnloadingEvent`], allowing persistence systems to delete
+/// the region's saved state instead of saving it.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RegionCollectedEvent {
+	/// The collected region
+	pub region: InfiniteRegion,
+}
+
+/// An event sent once a region (along with its map, layers, and tiles) has been despawned
+///
+/// Its map ID may be reused by another region from now on.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RegionDespawnedEvent {
+	/// The despawned region
+	pub region: InfiniteRegion,
+}
+
+impl Plugin for InfiniteMapPlugin {
+	fn build(&self, app: &mut App) {
+		app.init_resource::<InfiniteMapConfig>()
+			.init_resource::<InfiniteMap>()
+			.add_event::<RegionLoadedEvent>()
+			.add_event::<RegionUnloadingEvent>()
+			.add_event::<RegionCollectedEvent>()
+			.add_event::<RegionDespawnedEvent>()
+			.add_system_to_stage(
+				TilesetMapStage,
+				update_infinite_regions.label(TilesetMapLabel::UpdateInfiniteMap),
+			)
+			.add_system_to_stage(
+				TilesetMapStage,
+				generate_infinite_regions
+					.label(TilesetMapLabel::GenerateRegions)
+					.after(TilesetMapLabel::UpdateInfiniteMap),
+			)
+			.add_system_to_stage(
+				CoreStage::Last,
+				collect_empty_regions.label(TilesetMapLabel::CollectEmptyRegions),
+			);
+	}
+}
+
+impl Default for InfiniteMapConfig {
+	fn default() -> Self {
+		Self {
+			map_ids: 1024..2048,
+			region_size: MapSize(2, 2),
+			chunk_size: ChunkSize(32, 32),
+			layers: Vec::new(),
+			load_radius: 1,
+			retention: RetentionPolicy::default(),
+			generator: None,
+			collect_empty: None,
+		}
+	}
+}
+
+impl Default for RetentionPolicy {
+	fn default() -> Self {
+		Self::Distance(2)
+	}
+}
+
+impl Debug for InfiniteMapConfig {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("InfiniteMapConfig")
+			.field("map_ids", &self.map_ids)
+			.field("region_size", &self.region_size)
+			.field("chunk_size", &self.chunk_size)
+			.field("layers", &self.layers)
+			.field("load_radius", &self.load_radius)
+			.field("retention", &self.retention)
+			.field("generator", &self.generator.is_some())
+			.field("collect_empty", &self.collect_empty)
+			.finish()
+	}
+}
+
+impl InfiniteMapConfig {
+	/// Set the callback used to generate the contents of a newly spawned region
+	pub fn with_generator<F>(mut self, generator: F) -> Self
+	where
+		F: Fn(&mut TilePlacer, &InfiniteRegion) + Send + Sync + 'static,
+	{
+		self.generator = Some(Arc::new(generator));
+		self
+	}
+
+	/// The size of each region (in tiles)
+	pub fn region_tiles(&self) -> UVec2 {
+		UVec2::new(
+			self.region_size.0 * self.chunk_size.0,
+			self.region_size.1 * self.chunk_size.1,
+		)
+	}
+}
+
+impl InfiniteRegion {
+	/// The global coordinate of this region's minimum corner
+	pub fn origin(&self) -> IVec2 {
+		self.position * self.size.as_ivec2()
+	}
+
+	/// Iterate over every local tile position within this region
+	pub fn positions(&self) -> impl Iterator<Item = TilePos> {
+		let size = self.size;
+		(0..size.y).flat_map(move |y| (0..size.x).map(move |x| TilePos(x, y)))
+	}
+
+	/// Convert a local tile position within this region to a global coordinate
+	pub fn to_global(&self, pos: TilePos) -> IVec2 {
+		self.origin() + IVec2::new(pos.0 as i32, pos.1 as i32)
+	}
+}
+
+impl InfiniteMap {
+	/// Get the region at the given position (in regions)
+	pub fn region(&self, position: IVec2) -> Option<&InfiniteRegion> {
+		self.regions.get(&position)
+	}
+
+	/// Get the region backed by the given map
+	pub fn region_by_map(&self, map_id: u16) -> Option<&InfiniteRegion> {
+		self.region(*self.map_ids.get(&map_id)?)
+	}
+
+	/// Iterate over all regions, regardless of their state
+	pub fn regions(&self) -> impl Iterator<Item = &InfiniteRegion> {
+		self.regions.values()
+	}
+
+	/// Returns true if the region at the given position (in regions) is loaded
+	pub fn is_loaded(&self, position: IVec2) -> bool {
+		self.region(position)
+			.is_some_and(|region| region.state == RegionState::Loaded)
+	}
+
+	/// The position (in regions) of the region containing the given global coordinate
+	pub fn region_of(&self, global: IVec2) -> IVec2 {
+		let size = self.region_size.max(UVec2::ONE).as_ivec2();
+		IVec2::new(global.x.div_euclid(size.x), global.y.div_euclid(size.y))
+	}
+
+	/// The global coordinate of the tile at the given world position
+	pub fn world_to_global(&self, world: Vec2) -> IVec2 {
+		(world / self.tile_size.max(Vec2::ONE)).floor().as_ivec2()
+	}
+
+	/// Translate the given global coordinate to the map ID and local position of its region
+	///
+	/// Returns `None` if the region has not been spawned.
+	pub fn locate(&self, global: IVec2) -> Option<(u16, TilePos)> {
+		let region = self.region(self.region_of(global))?;
+		let local = global - region.origin();
+		Some((region.map_id, TilePos(local.x as u32, local.y as u32)))
+	}
+
+	/// Translate the given map ID and local position back to a global coordinate
+	///
+	/// Returns `None` if the map does not back any region.
+	pub fn to_global(&self, map_id: u16, pos: TilePos) -> Option<IVec2> {
+		Some(self.region_by_map(map_id)?.to_global(pos))
+	}
+
+	/// The map ID and local position of the given global coordinate, as long as its region exists
+	fn locate_or_err(&self, global: IVec2) -> Result<(u16, TilePos), TilePlacementError> {
+		self.locate(global)
+			.ok_or_else(|| TilePlacementError::UnloadedRegion(self.region_of(global)))
+	}
+}
+
+/// A helper system param used to place tiles in an infinite map using global coordinates
+///
+/// # Examples
+///
+/// ```
+/// # use bevy::math::IVec2;
+/// # use bevy_tileset_map::prelude::{InfinitePlacer, TileId};
+/// fn place_far_away(mut placer: InfinitePlacer) {
+/// #   let tile_id = TileId::new(0, 0);
+///   placer.place(tile_id, IVec2::new(-5000, 12000), 0).ok();
+/// }
+/// ```
+#[derive(bevy::ecs::system::SystemParam)]
+pub struct InfinitePlacer<'w, 's> {
+	placer: TilePlacer<'w, 's>,
+	map: ResMut<'w, InfiniteMap>,
+	config: Res<'w, InfiniteMapConfig>,
+}
+
+impl<'w, 's> InfinitePlacer<'w, 's> {
+	/// Place a tile at the given global coordinate
+	///
+	/// Returns [`TilePlacementError::UnloadedRegion`] if the containing region has not been spawned.
+	pub fn place<Id: Into<TileId>>(
+		&mut self,
+		tile_id: Id,
+		global: IVec2,
+		layer_id: u16,
+	) -> TilePlacementResult {
+		let (map_id, pos) = self.map.locate_or_err(global)?;
+		self.placer.place(tile_id, pos, map_id, layer_id)
+	}
+
+	/// Place a tile at the given global coordinate, replacing any existing tile
+	pub fn replace<Id: Into<TileId>>(
+		&mut self,
+		tile_id: Id,
+		global: IVec2,
+		layer_id: u16,
+	) -> TilePlacementResult {
+		let (map_id, pos) = self.map.locate_or_err(global)?;
+		self.placer.replace(tile_id, pos, map_id, layer_id)
+	}
+
+	/// Place a tile at the given global coordinate, spawning its region if needed
+	///
+	/// If the containing region is not loaded, it is (re)spawned on the next update of the
+	/// [`InfiniteMapPlugin`] and the tile is placed right after the region has been generated. In
+	/// that case, `Ok(None)` is returned.
+	///
+	/// Regions spawned this way are subject to the configured [`RetentionPolicy`] once they are
+	/// loaded, so they may be unloaded again if no tracker is nearby.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// # use bevy::math::IVec2;
+	/// # use bevy_tileset_map::prelude::{InfinitePlacer, TileId};
+	/// fn place_landmark(mut placer: InfinitePlacer) {
+	/// #   let tile_id = TileId::new(0, 0);
+	///   // Works even if nobody is near the landmark
+	///   placer.place_or_spawn(tile_id, IVec2::new(-5000, 12000), 0).ok();
+	/// }
+	/// ```
+	pub fn place_or_spawn<Id: Into<TileId>>(
+		&mut self,
+		tile_id: Id,
+		global: IVec2,
+		layer_id: u16,
+	) -> Result<Option<PlacedTile>, TilePlacementError> {
+		// The region size is only known to the map once the tilesets have loaded
+		let size = self.config.region_tiles().max(UVec2::ONE).as_ivec2();
+		let position = IVec2::new(global.x.div_euclid(size.x), global.y.div_euclid(size.y));
+		let is_spawned = self
+			.map
+			.region(position)
+			.is_some_and(|region| region.state == RegionState::Loaded);
+		if is_spawned {
+			return self.place(tile_id, global, layer_id).map(Some);
+		}
+
+		let local = global - position * size;
+		self.map
+			.pending
+			.entry(position)
+			.or_default()
+			.push(PendingTile {
+				tile_id: tile_id.into(),
+				pos: TilePos(local.x as u32, local.y as u32),
+				layer_id,
+			});
+		self.map.requested.insert(position);
+		Ok(None)
+	}
+
+	/// Remove the tile at the given global coordinate
+	pub fn remove(&mut self, global: IVec2, layer_id: u16) -> Result<(), TilePlacementError> {
+		let (map_id, pos) = self.map.locate_or_err(global)?;
+		self.placer.remove(pos, map_id, layer_id)
+	}
+
+	/// Get the infinite map
+	pub fn map(&self) -> &InfiniteMap {
+		&self.map
+	}
+
+	/// Get the placer used to place tiles by map ID
+	pub fn placer(&mut self) -> &mut TilePlacer<'w, 's> {
+		&mut self.placer
+	}
+}
+
+/// __\[SYSTEM\]__ Spawns the regions around every tracker and unloads those no longer retained
+fn update_infinite_regions(
+	config: Res<InfiniteMapConfig>,
+	mut map: ResMut<InfiniteMap>,
+	mut layers: TileLayerManager,
+	tilesets: Tilesets,
+	trackers: Query<&GlobalTransform, With<InfiniteMapTracker>>,
+	mut unloading: EventWriter<RegionUnloadingEvent>,
+	mut despawned: EventWriter<RegionDespawnedEvent>,
+) {
+	map.frame += 1;
+	let frame = map.frame;
+
+	// === Despawn Unloaded Regions === //
+	let unloaded = map
+		.regions
+		.values()
+		.filter(|region| region.state == RegionState::Unloading)
+		.copied()
+		.collect::<Vec<_>>();
+	for region in unloaded {
+		layers.despawn_map(region.map_id);
+		map.regions.remove(&region.position);
+		map.map_ids.remove(&region.map_id);
+		despawned.send(RegionDespawnedEvent { region });
+	}
+
+	let tile_size = if let Some(tileset) = config
+		.layers
+		.first()
+		.and_then(|(_, tileset_id)| tilesets.get_by_id(tileset_id))
+	{
+		tileset.tile_size()
+	} else {
+		// Wait for the tilesets to load
+		return;
+	};
+	map.tile_size = tile_size;
+	map.region_size = config.region_tiles();
+
+	let region_extent = map.region_size.as_vec2() * tile_size;
+	let centers = trackers
+		.iter()
+		.map(|transform| {
+			(transform.translation.truncate() / region_extent)
+				.floor()
+				.as_ivec2()
+		})
+		.collect::<Vec<_>>();
+	// The distance (in regions) to the nearest tracker
+	let distance = |position: IVec2| {
+		centers
+			.iter()
+			.map(|center| (position - *center).abs().max_element() as u32)
+			.min()
+			.unwrap_or(u32::MAX)
+	};
+
+	// === Spawn Nearby Regions === //
+	let radius = config.load_radius as i32;
+	'spawn: for center in &centers {
+		for y in -radius..=radius {
+			for x in -radius..=radius {
+				let position = *center + IVec2::new(x, y);
+				if map.regions.contains_key(&position) {
+					continue;
+				}
+
+				if !spawn_region(&config, &mut map, &mut layers, position, region_extent) {
+					break 'spawn;
+				}
+			}
+		}
+	}
+
+	// === Spawn Requested Regions === //
+	let requested = std::mem::take(&mut map.requested);
+	for position in requested {
+		if !map.regions.contains_key(&position)
+			&& !spawn_region(&config, &mut map, &mut layers, position, region_extent)
+		{
+			// The region will never be spawned, so neither will its tiles
+			map.pending.remove(&position);
+		}
+	}
+
+	// === Unload Regions === //
+	let mut candidates = Vec::new();
+	let mut retained = 0usize;
+	for region in map.regions.values_mut() {
+		if region.state == RegionState::Unloading {
+			continue;
+		}
+
+		retained += 1;
+		let region_distance = distance(region.position);
+		if region_distance <= config.load_radius {
+			region.last_visited = frame;
+		} else if region.state == RegionState::Loaded {
+			// Spawning regions may still have pending tiles
+			candidates.push((region.position, region_distance, region.last_visited));
+		}
+	}
+
+	let unloaded = match config.retention {
+		RetentionPolicy::Distance(radius) => candidates
+			.into_iter()
+			.filter(|(_, distance, _)| *distance > radius)
+			.map(|(position, ..)| position)
+			.collect::<Vec<_>>(),
+		RetentionPolicy::KeepCount(count) => {
+			candidates.sort_by_key(|(_, distance, _)| std::cmp::Reverse(*distance));
+			let excess = retained.saturating_sub(count);
+			candidates
+				.into_iter()
+				.take(excess)
+				.map(|(position, ..)| position)
+				.collect()
+		},
+		RetentionPolicy::LeastRecentlyUsed(count) => {
+			candidates.sort_by_key(|(_, _, last_visited)| *last_visited);
+			let excess = retained.saturating_sub(count);
+			candidates
+				.into_iter()
+				.take(excess)
+				.map(|(position, ..)| position)
+				.collect()
+		},
+	};
+
+	for position in unloaded {
+		if let Some(region) = map.regions.get_mut(&position) {
+			region.state = RegionState::Unloading;
+			unloading.send(RegionUnloadingEvent { region: *region });
+		}
+	}
+}
+
+/// Spawns the map and layers of the region at the given position (in regions)
+///
+/// Returns false if all map IDs are in use.
+fn spawn_region(
+	config: &InfiniteMapConfig,
+	map: &mut InfiniteMap,
+	layers: &mut TileLayerManager,
+	position: IVec2,
+	region_extent: Vec2,
+) -> bool {
+	let map_id = if let Some(map_id) = config
+		.map_ids
+		.clone()
+		.find(|map_id| !map.map_ids.contains_key(map_id))
+	{
+		map_id
+	} else {
+		warn!(
+			"Could not load region {:?}: all infinite map IDs are in use",
+			position
+		);
+		return false;
+	};
+
+	let translation = (position.as_vec2() * region_extent).extend(0.0);
+	let map_entity =
+		layers.create_map_with_transform(map_id, Transform::from_translation(translation));
+	for (layer_id, tileset_id) in &config.layers {
+		if let Err(err) = layers.create_layer(
+			tileset_id,
+			map_entity,
+			map_id,
+			*layer_id,
+			config.region_size,
+			config.chunk_size,
+		) {
+			warn!(
+				"Could not create layer {} of region {:?}: {}",
+				layer_id, position, err
+			);
+		}
+	}
+
+	map.map_ids.insert(map_id, position);
+	map.regions.insert(
+		position,
+		InfiniteRegion {
+			position,
+			map_id,
+			map_entity,
+			size: map.region_size,
+			state: RegionState::Spawning,
+			last_visited: map.frame,
+		},
+	);
+	true
+}
+
+/// __\[SYSTEM\]__ Generates the contents of all regions whose layers have finished spawning
+fn generate_infinite_regions(
+	config: Res<InfiniteMapConfig>,
+	mut map: ResMut<InfiniteMap>,
+	mut placer: TilePlacer,
+	mut loaded: EventWriter<RegionLoadedEvent>,
+) {
+	let spawning = map
+		.regions
+		.values()
+		.filter(|region| region.state == RegionState::Spawning)
+		.copied()
+		.collect::<Vec<_>>();
+
+	for mut region in spawning {
+		let is_ready = config
+			.layers
+			.iter()
+			.all(|(layer_id, _)| placer.has_layer(region.map_id, *layer_id));
+		if !is_ready {
+			continue;
+		}
+
+		placer.begin_batch();
+		if let Some(generator) = &config.generator {
+			generator(&mut placer, &region);
+		}
+		for tile in map.pending.remove(&region.position).into_iter().flatten() {
+			if let Err(err) = placer.place(tile.tile_id, tile.pos, region.map_id, tile.layer_id) {
+				warn!(
+					"Could not place pending tile in region {:?}: {}",
+					region.position, err
+				);
+			}
+		}
+		placer.end_batch();
+
+		region.state = RegionState::Loaded;
+		map.regions.insert(region.position, region);
+		loaded.send(RegionLoadedEvent { region });
+	}
+}
+
+/// __\[SYSTEM\]__ Unloads the loaded regions outside the load radius that no longer contain tiles
+///
+/// This runs in [`CoreStage::Last`] so that tiles placed during the frame have been spawned.
+fn collect_empty_regions(
+	config: Res<InfiniteMapConfig>,
+	mut map: ResMut<InfiniteMap>,
+	tiles: Query<&TileParent>,
+	time: Res<Time>,
+	mut timer: Local<Option<Timer>>,
+	mut unloading: EventWriter<RegionUnloadingEvent>,
+	mut collected: EventWriter<RegionCollectedEvent>,
+) {
+	let is_due = match (config.collect_empty, timer.as_mut()) {
+		(Some(interval), Some(timer)) if timer.duration() == interval => {
+			timer.tick(time.delta()).just_finished()
+		},
+		(Some(interval), _) => {
+			*timer = Some(Timer::new(interval, true));
+			false
+		},
+		(None, _) => {
+			*timer = None;
+			false
+		},
+	};
+	if !is_due {
+		return;
+	}
+
+	// Regions visited this frame lie within the load radius of a tracker
+	let frame = map.frame;
+	let mut empty = map
+		.regions
+		.values()
+		.filter(|region| region.state == RegionState::Loaded && region.last_visited < frame)
+		.map(|region| region.map_id)
+		.collect::<HashSet<_>>();
+	for parent in tiles.iter() {
+		empty.remove(&parent.map_id);
+		if empty.is_empty() {
+			return;
+		}
+	}
+
+	for map_id in empty {
+		let position = map.map_ids[&map_id];
+		if let Some(region) = map.regions.get_mut(&position) {
+			region.state = RegionState::Unloading;
+			unloading.send(RegionUnloadingEvent { region: *region });
+			collected.send(RegionCollectedEvent { region: *region });
+		}
+	}
+}
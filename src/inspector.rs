@@ -0,0 +1,134 @@
+//! Integration with `bevy-inspector-egui`
+//!
+//! The [`TileInspectorPlugin`] adds a [`TileInfo`] component to every tile, describing the tile
+//! in terms of its tileset rather than its raw texture index. When a tile entity is selected in
+//! the world inspector, this component is shown as a read-only panel.
+//!
+//! The remaining components of this crate (such as [`TileFlags`](crate::prelude::TileFlags) and
+//! [`TileOwner`](crate::prelude::TileOwner)) are registered for reflection by the
+//! [`TilesetMapPlugin`](crate::prelude::TilesetMapPlugin), so the world inspector can show (and
+//! edit) them as well.
+
+use bevy::prelude::*;
+use bevy_ecs_tilemap::Tile;
+use bevy_inspector_egui::{egui, Context, Inspectable, RegisterInspectable};
+use bevy_tileset::prelude::{TileId, TilesetParent, Tilesets};
+
+use crate::plugin::TilesetMapLabel;
+use crate::querier::TileQuerier;
+
+/// Plugin keeping the [`TileInfo`] of every tile up to date and registering it with the
+/// inspector
+///
+/// This requires the `WorldInspectorPlugin` from `bevy-inspector-egui` to be added as well.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_inspector_egui::WorldInspectorPlugin;
+/// # use bevy_tileset_map::prelude::{TileInspectorPlugin, TilesetMapPlugins};
+/// App::new()
+///   .add_plugins(DefaultPlugins)
+///   .add_plugins(TilesetMapPlugins::default())
+///   .add_plugin(WorldInspectorPlugin::new())
+///   .add_plugin(TileInspectorPlugin)
+///   .run();
+/// ```
+#[derive(Default)]
+pub struct TileInspectorPlugin;
+
+/// A component describing a tile, shown when the tile is selected in the inspector
+///
+/// This component is added and kept up to date automatically by the [`TileInspectorPlugin`].
+/// Changing it has no effect on the tile.
+#[derive(Component, Debug, Clone, Default, PartialEq)]
+pub struct TileInfo {
+	/// The ID of the tile (if it belongs to a loaded tileset)
+	pub tile_id: Option<TileId>,
+	/// The name of the tile's tileset
+	pub tileset_name: Option<String>,
+	/// The name of the tile
+	pub tile_name: Option<String>,
+	/// Whether the tile is an Auto tile
+	///
+	/// Always false without the `auto-tile` feature.
+	pub is_auto: bool,
+	/// The variant of the tile (if it's a Variant tile)
+	///
+	/// Always `None` without the `variants` feature.
+	pub variant: Option<usize>,
+}
+
+impl Plugin for TileInspectorPlugin {
+	fn build(&self, app: &mut App) {
+		app.register_inspectable::<TileInfo>().add_system_to_stage(
+			CoreStage::Last,
+			update_tile_info.label(TilesetMapLabel::UpdateTileInfo),
+		);
+	}
+}
+
+impl Inspectable for TileInfo {
+	type Attributes = ();
+
+	fn ui(&mut self, ui: &mut egui::Ui, _: Self::Attributes, _: &mut Context) -> bool {
+		let tile_id = self
+			.tile_id
+			.map(|tile_id| format!("group {}, tileset {}", tile_id.group_id, tile_id.tileset_id));
+		let variant = self.variant.map(|variant| variant.to_string());
+
+		egui::Grid::new("tile_info").show(ui, |ui| {
+			info_row(ui, "Tile ID", tile_id.as_deref());
+			info_row(ui, "Tileset", self.tileset_name.as_deref());
+			info_row(ui, "Tile", self.tile_name.as_deref());
+			info_row(ui, "Auto", Some(if self.is_auto { "yes" } else { "no" }));
+			info_row(ui, "Variant", variant.as_deref());
+		});
+
+		false
+	}
+}
+
+/// Show a single labeled row within the [`TileInfo`] grid
+fn info_row(ui: &mut egui::Ui, label: &str, value: Option<&str>) {
+	ui.label(label);
+	ui.label(value.unwrap_or("-"));
+	ui.end_row();
+}
+
+/// __\[SYSTEM\]__ Updates the [`TileInfo`] of every changed tile
+fn update_tile_info(
+	mut commands: Commands,
+	querier: TileQuerier,
+	tilesets: Tilesets,
+	tiles: Query<Entity, (With<Tile>, Or<(Changed<Tile>, Changed<TilesetParent>)>)>,
+	#[cfg(feature = "auto-tile")] auto_tiles: Query<(), With<bevy_tileset::auto::AutoTileId>>,
+) {
+	for entity in tiles.iter() {
+		let tile_id = querier.get_tile_id(entity);
+		let tileset = tile_id.and_then(|tile_id| tilesets.get_by_id(&tile_id.tileset_id));
+
+		#[cfg(feature = "auto-tile")]
+		let is_auto = auto_tiles.contains(entity);
+		#[cfg(not(feature = "auto-tile"))]
+		let is_auto = false;
+
+		#[cfg(feature = "variants")]
+		let variant = tile_id.and_then(|tile_id| tile_id.variant_index);
+		#[cfg(not(feature = "variants"))]
+		let variant = None;
+
+		commands.entity(entity).insert(TileInfo {
+			tile_id,
+			tileset_name: tileset.map(|tileset| tileset.name().to_string()),
+			tile_name: tile_id.zip(tileset).and_then(|(tile_id, tileset)| {
+				tileset
+					.get_tile_name(&tile_id.group_id)
+					.map(|name| name.to_string())
+			}),
+			is_auto,
+			variant,
+		});
+	}
+}
@@ -0,0 +1,134 @@
+use std::collections::BTreeMap;
+
+use bevy_ecs_tilemap::TilePos;
+use bevy_tileset::prelude::TileId;
+
+use crate::ir::{LayerIr, TileExtras, TileExtrasEntry, TileRun, TilemapIr};
+
+/// A builder for a [`LayerIr`]
+///
+/// Tiles may be added in any order. If a tile is added to an already occupied position,
+/// it replaces the previous one.
+#[derive(Debug, Clone, Default)]
+pub struct LayerIrBuilder {
+	/// The tiles keyed by `(y, x)` so that they iterate in row-major order
+	tiles: BTreeMap<(u32, u32), (TileId, TileExtras)>,
+}
+
+/// A builder for a [`TilemapIr`]
+///
+/// # Examples
+///
+/// ```
+/// # use bevy_ecs_tilemap::TilePos;
+/// # use bevy_tileset_map::prelude::{TileId, TilemapIr};
+/// let mut builder = TilemapIr::builder();
+/// builder
+///   .add_tile(0, 0, TilePos(0, 0), TileId::new(0, 0))
+///   .add_tile(0, 0, TilePos(1, 0), TileId::new(0, 0));
+/// let ir = builder.build();
+///
+/// assert_eq!(1, ir.layer(0, 0).unwrap().runs().len());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TilemapIrBuilder {
+	layers: BTreeMap<(u16, u16), LayerIrBuilder>,
+}
+
+impl LayerIrBuilder {
+	/// Add a tile to the layer
+	pub fn add_tile(&mut self, pos: TilePos, id: TileId) -> &mut Self {
+		self.add_tile_with_extras(pos, id, TileExtras::default())
+	}
+
+	/// Add a tile with the given extras to the layer
+	pub fn add_tile_with_extras(
+		&mut self,
+		pos: TilePos,
+		id: TileId,
+		extras: TileExtras,
+	) -> &mut Self {
+		let TilePos(x, y) = pos;
+		self.tiles.insert((y, x), (id, extras));
+		self
+	}
+
+	/// Remove the tile at the given position
+	pub fn remove_tile(&mut self, pos: TilePos) -> Option<TileId> {
+		let TilePos(x, y) = pos;
+		self.tiles.remove(&(y, x)).map(|(id, _)| id)
+	}
+
+	/// The number of tiles added so far
+	pub fn len(&self) -> usize {
+		self.tiles.len()
+	}
+
+	/// Returns true if no tiles have been added
+	pub fn is_empty(&self) -> bool {
+		self.tiles.is_empty()
+	}
+
+	/// Build the layer, compressing the tiles into runs
+	pub fn build(self) -> LayerIr {
+		let mut runs: Vec<TileRun> = Vec::new();
+		let mut extras = Vec::new();
+
+		for ((y, x), (id, tile_extras)) in self.tiles {
+			match runs.last_mut() {
+				Some(run)
+					if run.y == y && run.x.checked_add(run.len) == Some(x) && run.id == id =>
+				{
+					run.len += 1;
+				},
+				_ => runs.push(TileRun { x, y, len: 1, id }),
+			}
+
+			if !tile_extras.is_empty() {
+				extras.push(TileExtrasEntry {
+					x,
+					y,
+					extras: tile_extras,
+				});
+			}
+		}
+
+		LayerIr { runs, extras }
+	}
+}
+
+impl TilemapIrBuilder {
+	/// Add a tile to the given map layer
+	pub fn add_tile(&mut self, map_id: u16, layer_id: u16, pos: TilePos, id: TileId) -> &mut Self {
+		self.layer_mut(map_id, layer_id).add_tile(pos, id);
+		self
+	}
+
+	/// Add a tile with the given extras to the given map layer
+	pub fn add_tile_with_extras(
+		&mut self,
+		map_id: u16,
+		layer_id: u16,
+		pos: TilePos,
+		id: TileId,
+		extras: TileExtras,
+	) -> &mut Self {
+		self.layer_mut(map_id, layer_id)
+			.add_tile_with_extras(pos, id, extras);
+		self
+	}
+
+	/// Get the builder for the given map layer, creating it if it doesn't exist yet
+	pub fn layer_mut(&mut self, map_id: u16, layer_id: u16) -> &mut LayerIrBuilder {
+		self.layers.entry((map_id, layer_id)).or_default()
+	}
+
+	/// Build the IR
+	pub fn build(self) -> TilemapIr {
+		let mut ir = TilemapIr::default();
+		for ((map_id, layer_id), layer) in self.layers {
+			ir.insert_layer(map_id, layer_id, layer.build());
+		}
+		ir
+	}
+}
@@ -0,0 +1,129 @@
+use std::collections::btree_map;
+
+use bevy_ecs_tilemap::TilePos;
+use bevy_tileset::prelude::TileId;
+
+use crate::coord::TileCoord;
+use crate::ir::{LayerIr, MapIr, TileExtras, TilemapIr};
+
+/// A single tile yielded by iterating over a [`LayerIr`]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct IrTile<'a> {
+	pub pos: TilePos,
+	pub id: TileId,
+	pub extras: Option<&'a TileExtras>,
+}
+
+impl<'a> IrTile<'a> {
+	/// The ID of this tile with its extras (such as the selected variant) applied
+	pub fn resolved_id(&self) -> TileId {
+		match self.extras {
+			Some(extras) => extras.apply_to(self.id),
+			None => self.id,
+		}
+	}
+}
+
+/// An iterator over all tiles in a [`LayerIr`], in row-major order
+pub struct LayerIrIter<'a> {
+	layer: &'a LayerIr,
+	run: usize,
+	offset: u32,
+	extras: usize,
+}
+
+impl<'a> LayerIrIter<'a> {
+	pub(crate) fn new(layer: &'a LayerIr) -> Self {
+		Self {
+			layer,
+			run: 0,
+			offset: 0,
+			extras: 0,
+		}
+	}
+}
+
+impl<'a> Iterator for LayerIrIter<'a> {
+	type Item = IrTile<'a>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let run = self.layer.runs.get(self.run)?;
+		let pos = TilePos(run.x + self.offset, run.y);
+
+		self.offset += 1;
+		if self.offset >= run.len {
+			self.run += 1;
+			self.offset = 0;
+		}
+
+		// Extras are sorted in the same order as the runs, so we can just walk them alongside
+		let mut extras = None;
+		while let Some(entry) = self.layer.extras.get(self.extras) {
+			let entry_key = (entry.y, entry.x);
+			let key = (pos.1, pos.0);
+			if entry_key < key {
+				self.extras += 1;
+			} else {
+				if entry_key == key {
+					extras = Some(&entry.extras);
+					self.extras += 1;
+				}
+				break;
+			}
+		}
+
+		Some(IrTile {
+			pos,
+			id: run.id,
+			extras,
+		})
+	}
+}
+
+/// An iterator over all tiles in a [`TilemapIr`], yielding their full [`TileCoord`]
+pub struct TilemapIrIter<'a> {
+	maps: btree_map::Iter<'a, u16, MapIr>,
+	layers: Option<(u16, btree_map::Iter<'a, u16, LayerIr>)>,
+	tiles: Option<(u16, u16, LayerIrIter<'a>)>,
+}
+
+impl<'a> TilemapIrIter<'a> {
+	pub(crate) fn new(ir: &'a TilemapIr) -> Self {
+		Self {
+			maps: ir.maps.iter(),
+			layers: None,
+			tiles: None,
+		}
+	}
+}
+
+impl<'a> Iterator for TilemapIrIter<'a> {
+	type Item = (TileCoord, IrTile<'a>);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		loop {
+			if let Some((map_id, layer_id, ref mut tiles)) = self.tiles {
+				if let Some(tile) = tiles.next() {
+					let coord = TileCoord {
+						pos: tile.pos,
+						map_id,
+						layer_id,
+					};
+					return Some((coord, tile));
+				}
+				self.tiles = None;
+			}
+
+			if let Some((map_id, ref mut layers)) = self.layers {
+				if let Some((layer_id, layer)) = layers.next() {
+					self.tiles = Some((map_id, *layer_id, layer.iter()));
+					continue;
+				}
+				self.layers = None;
+			}
+
+			let (map_id, map) = self.maps.next()?;
+			self.layers = Some((*map_id, map.layers.iter()));
+		}
+	}
+}
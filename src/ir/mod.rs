@@ -0,0 +1,482 @@
+//! An intermediate, ECS-independent representation of tilemaps
+//!
+//! The [`TilemapIr`] is the shared structure used by the serializer, and can be used by anything
+//! else that needs to produce or consume whole tilemaps (importers, exporters, generators, network
+//! code, etc.) without touching the ECS directly.
+//!
+//! Tiles are stored per map and per layer as horizontal runs of identical [`TileId`]s, alongside
+//! any per-tile [`TileExtras`].
+
+use std::collections::BTreeMap;
+
+use bevy_ecs_tilemap::TilePos;
+use bevy_tileset::prelude::TileId;
+use thiserror::Error;
+
+use crate::flags::TileFlags;
+use crate::ownership::TileOwner;
+
+mod builder;
+mod iter;
+mod world;
+
+pub use builder::*;
+pub use iter::*;
+pub use world::*;
+
+/// An intermediate representation of one or more tilemaps
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(
+	feature = "serialization",
+	derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct TilemapIr {
+	maps: BTreeMap<u16, MapIr>,
+}
+
+/// An intermediate representation of a single tilemap
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(
+	feature = "serialization",
+	derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct MapIr {
+	layers: BTreeMap<u16, LayerIr>,
+}
+
+/// An intermediate representation of a single tilemap layer
+///
+/// The tiles are stored as runs sorted in row-major order (by `y`, then by `x`). Runs never
+/// overlap, which is checked when deserializing a layer or creating it using [`LayerIr::from_runs`].
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(
+	feature = "serialization",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(try_from = "LayerIrData")
+)]
+pub struct LayerIr {
+	runs: Vec<TileRun>,
+	#[cfg_attr(
+		feature = "serialization",
+		serde(default, skip_serializing_if = "Vec::is_empty")
+	)]
+	extras: Vec<TileExtrasEntry>,
+}
+
+/// The unchecked contents of a [`LayerIr`], validated when deserializing
+#[cfg(feature = "serialization")]
+#[derive(serde::Deserialize)]
+struct LayerIrData {
+	runs: Vec<TileRun>,
+	#[serde(default)]
+	extras: Vec<TileExtrasEntry>,
+}
+
+#[cfg(feature = "serialization")]
+impl TryFrom<LayerIrData> for LayerIr {
+	type Error = LayerIrError;
+
+	fn try_from(data: LayerIrData) -> Result<Self, Self::Error> {
+		Self::from_runs(data.runs, data.extras)
+	}
+}
+
+/// An error describing why a set of runs can't make up a [`LayerIr`]
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum LayerIrError {
+	/// A run doesn't contain any tiles
+	#[error("The run at {pos:?} is empty")]
+	EmptyRun { pos: TilePos },
+	/// A run extends past the largest possible tile position
+	#[error("The run at {pos:?} with a length of {len} extends past the maximum position")]
+	RunOverflow { pos: TilePos, len: u32 },
+	/// A run starts before the end of the previous one (or isn't in row-major order)
+	#[error("The run at {pos:?} is out of order or overlaps the previous run")]
+	UnsortedRuns { pos: TilePos },
+	/// An extras entry has the same position as (or comes before) the previous one
+	#[error("The extras at {pos:?} are out of order or duplicated")]
+	UnsortedExtras { pos: TilePos },
+}
+
+/// A horizontal run of identical tiles, starting at `(x, y)` and spanning `len` tiles along the x-axis
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(
+	feature = "serialization",
+	derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct TileRun {
+	pub x: u32,
+	pub y: u32,
+	pub len: u32,
+	pub id: TileId,
+}
+
+/// Additional per-tile data that isn't captured by a tile's [`TileId`]
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(
+	feature = "serialization",
+	derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct TileExtras {
+	/// The index of the variant that was selected for this tile (if it is a Variant tile)
+	#[cfg(feature = "variants")]
+	#[cfg_attr(
+		feature = "serialization",
+		serde(default, skip_serializing_if = "Option::is_none")
+	)]
+	pub variant: Option<usize>,
+	/// The elevation of this tile (in pixels), if any
+	#[cfg_attr(
+		feature = "serialization",
+		serde(default, skip_serializing_if = "Option::is_none")
+	)]
+	pub elevation: Option<f32>,
+	/// The flags of this tile, if any
+	#[cfg_attr(
+		feature = "serialization",
+		serde(default, skip_serializing_if = "Option::is_none")
+	)]
+	pub flags: Option<TileFlags>,
+	/// The owner of this tile, if any
+	#[cfg_attr(
+		feature = "serialization",
+		serde(default, skip_serializing_if = "Option::is_none")
+	)]
+	pub owner: Option<TileOwner>,
+	/// The number of seconds until this tile's timed transition happens, if any
+	#[cfg_attr(
+		feature = "serialization",
+		serde(default, skip_serializing_if = "Option::is_none")
+	)]
+	pub transition: Option<f32>,
+}
+
+/// The [`TileExtras`] for the tile at `(x, y)`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+	feature = "serialization",
+	derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct TileExtrasEntry {
+	pub x: u32,
+	pub y: u32,
+	pub extras: TileExtras,
+}
+
+impl TilemapIr {
+	/// Create an empty IR
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Create a builder for an IR
+	pub fn builder() -> TilemapIrBuilder {
+		TilemapIrBuilder::default()
+	}
+
+	/// Get the map with the given ID
+	pub fn map(&self, map_id: u16) -> Option<&MapIr> {
+		self.maps.get(&map_id)
+	}
+
+	/// Get the layer with the given IDs
+	pub fn layer(&self, map_id: u16, layer_id: u16) -> Option<&LayerIr> {
+		self.map(map_id).and_then(|map| map.layer(layer_id))
+	}
+
+	/// Insert a map, returning the map it replaced (if any)
+	pub fn insert_map(&mut self, map_id: u16, map: MapIr) -> Option<MapIr> {
+		self.maps.insert(map_id, map)
+	}
+
+	/// Insert a layer, returning the layer it replaced (if any)
+	pub fn insert_layer(&mut self, map_id: u16, layer_id: u16, layer: LayerIr) -> Option<LayerIr> {
+		self.maps
+			.entry(map_id)
+			.or_default()
+			.insert_layer(layer_id, layer)
+	}
+
+	/// Remove the map with the given ID
+	pub fn remove_map(&mut self, map_id: u16) -> Option<MapIr> {
+		self.maps.remove(&map_id)
+	}
+
+	/// Iterate over all maps in ascending order of their IDs
+	pub fn maps(&self) -> impl Iterator<Item = (u16, &MapIr)> {
+		self.maps.iter().map(|(id, map)| (*id, map))
+	}
+
+	/// Iterate over every tile in every layer of every map
+	pub fn iter(&self) -> TilemapIrIter<'_> {
+		TilemapIrIter::new(self)
+	}
+
+	/// The total number of tiles contained in this IR
+	pub fn len(&self) -> usize {
+		self.maps.values().map(MapIr::len).sum()
+	}
+
+	/// Returns true if this IR contains no tiles
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+}
+
+impl MapIr {
+	/// Get the layer with the given ID
+	pub fn layer(&self, layer_id: u16) -> Option<&LayerIr> {
+		self.layers.get(&layer_id)
+	}
+
+	/// Insert a layer, returning the layer it replaced (if any)
+	pub fn insert_layer(&mut self, layer_id: u16, layer: LayerIr) -> Option<LayerIr> {
+		self.layers.insert(layer_id, layer)
+	}
+
+	/// Remove the layer with the given ID
+	pub fn remove_layer(&mut self, layer_id: u16) -> Option<LayerIr> {
+		self.layers.remove(&layer_id)
+	}
+
+	/// Iterate over all layers in ascending order of their IDs
+	pub fn layers(&self) -> impl Iterator<Item = (u16, &LayerIr)> {
+		self.layers.iter().map(|(id, layer)| (*id, layer))
+	}
+
+	/// The total number of tiles contained in this map
+	pub fn len(&self) -> usize {
+		self.layers.values().map(LayerIr::len).sum()
+	}
+
+	/// Returns true if this map contains no tiles
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+}
+
+impl LayerIr {
+	/// Create a builder for a layer
+	pub fn builder() -> LayerIrBuilder {
+		LayerIrBuilder::default()
+	}
+
+	/// Create a layer from the given runs and extras, both sorted in row-major order
+	///
+	/// Unlike the [builder](Self::builder), this keeps the runs exactly as given (without merging
+	/// adjacent runs).
+	///
+	/// # Errors
+	///
+	/// Returns a [`LayerIrError`] if a run is empty or extends past the maximum position, if the
+	/// runs are unsorted or overlap, or if the extras are unsorted or duplicated.
+	///
+	pub fn from_runs(
+		runs: Vec<TileRun>,
+		extras: Vec<TileExtrasEntry>,
+	) -> Result<Self, LayerIrError> {
+		// The position right after the previous run, or `None` if it ended at the end of its row
+		let mut next: Option<(u32, Option<u32>)> = None;
+		for run in &runs {
+			let end = match run.last_x() {
+				Some(end) => end,
+				None if run.len == 0 => return Err(LayerIrError::EmptyRun { pos: run.start() }),
+				None => {
+					return Err(LayerIrError::RunOverflow {
+						pos: run.start(),
+						len: run.len,
+					})
+				},
+			};
+			let is_sorted = match next {
+				Some((y, Some(x))) => (run.y, run.x) >= (y, x),
+				Some((y, None)) => run.y > y,
+				None => true,
+			};
+			if !is_sorted {
+				return Err(LayerIrError::UnsortedRuns { pos: run.start() });
+			}
+			next = Some((run.y, end.checked_add(1)));
+		}
+
+		for pair in extras.windows(2) {
+			if (pair[1].y, pair[1].x) <= (pair[0].y, pair[0].x) {
+				return Err(LayerIrError::UnsortedExtras {
+					pos: TilePos(pair[1].x, pair[1].y),
+				});
+			}
+		}
+
+		Ok(Self { runs, extras })
+	}
+
+	/// The runs making up this layer
+	pub fn runs(&self) -> &[TileRun] {
+		&self.runs
+	}
+
+	/// Iterate over every tile in this layer in row-major order
+	pub fn iter(&self) -> LayerIrIter<'_> {
+		LayerIrIter::new(self)
+	}
+
+	/// Get the ID of the tile at the given position
+	pub fn get(&self, pos: TilePos) -> Option<TileId> {
+		let TilePos(x, y) = pos;
+		// Find the last run starting at or before this position
+		let index = self
+			.runs
+			.partition_point(|run| (run.y, run.x) <= (y, x))
+			.checked_sub(1)?;
+		let run = &self.runs[index];
+		// The run starts at or before `x`, so this can't underflow
+		if run.y == y && x - run.x < run.len {
+			Some(run.id)
+		} else {
+			None
+		}
+	}
+
+	/// Get the extras for the tile at the given position
+	pub fn extras(&self, pos: TilePos) -> Option<&TileExtras> {
+		let TilePos(x, y) = pos;
+		self.extras
+			.binary_search_by(|entry| (entry.y, entry.x).cmp(&(y, x)))
+			.ok()
+			.map(|index| &self.extras[index].extras)
+	}
+
+	/// The total number of tiles contained in this layer
+	pub fn len(&self) -> usize {
+		self.runs.iter().map(|run| run.len as usize).sum()
+	}
+
+	/// Returns true if this layer contains no tiles
+	pub fn is_empty(&self) -> bool {
+		self.runs.is_empty()
+	}
+}
+
+impl TileRun {
+	/// The position of the first tile in this run
+	pub fn start(&self) -> TilePos {
+		TilePos(self.x, self.y)
+	}
+
+	/// The x-coordinate of the last tile in this run
+	///
+	/// Returns `None` if the run is empty or extends past the maximum position.
+	pub fn last_x(&self) -> Option<u32> {
+		self.x.checked_add(self.len.checked_sub(1)?)
+	}
+
+	/// Iterate over the positions covered by this run
+	///
+	/// Positions past the maximum position are skipped.
+	pub fn positions(&self) -> impl Iterator<Item = TilePos> {
+		let (x, y) = (self.x, self.y);
+		(0..self.len).map_while(move |offset| Some(TilePos(x.checked_add(offset)?, y)))
+	}
+}
+
+impl TileExtras {
+	/// Returns true if these extras contain no data worth storing
+	pub fn is_empty(&self) -> bool {
+		*self == Self::default()
+	}
+
+	/// Apply these extras to the given [`TileId`]
+	pub fn apply_to(&self, id: TileId) -> TileId {
+		#[allow(unused_mut)]
+		let mut id = id;
+		#[cfg(feature = "variants")]
+		if self.variant.is_some() {
+			id.variant_index = self.variant;
+		}
+		id
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use bevy_ecs_tilemap::TilePos;
+	use bevy_tileset::prelude::TileId;
+
+	use crate::ir::{LayerIr, LayerIrError, TileRun};
+
+	fn run(x: u32, y: u32, len: u32) -> TileRun {
+		TileRun {
+			x,
+			y,
+			len,
+			id: TileId::new(0, 0),
+		}
+	}
+
+	#[test]
+	fn runs_at_the_maximum_position() {
+		let layer = LayerIr::from_runs(vec![run(u32::MAX - 1, 0, 2)], Vec::new()).unwrap();
+		assert!(layer.get(TilePos(u32::MAX, 0)).is_some());
+		assert!(layer.get(TilePos(u32::MAX - 2, 0)).is_none());
+		assert_eq!(layer.runs()[0].positions().count(), 2);
+		assert_eq!(layer.iter().count(), 2);
+
+		assert_eq!(
+			LayerIr::from_runs(vec![run(u32::MAX, 0, 2)], Vec::new()),
+			Err(LayerIrError::RunOverflow {
+				pos: TilePos(u32::MAX, 0),
+				len: 2
+			})
+		);
+	}
+
+	#[test]
+	fn invalid_runs_are_rejected() {
+		// Overlapping
+		assert_eq!(
+			LayerIr::from_runs(vec![run(0, 0, 3), run(2, 0, 1)], Vec::new()),
+			Err(LayerIrError::UnsortedRuns { pos: TilePos(2, 0) })
+		);
+		// Unsorted
+		assert_eq!(
+			LayerIr::from_runs(vec![run(0, 1, 1), run(0, 0, 1)], Vec::new()),
+			Err(LayerIrError::UnsortedRuns { pos: TilePos(0, 0) })
+		);
+		// After a run ending at the end of its row
+		assert!(LayerIr::from_runs(vec![run(u32::MAX, 0, 1), run(0, 0, 1)], Vec::new()).is_err());
+		// Empty
+		assert_eq!(
+			LayerIr::from_runs(vec![run(0, 0, 0)], Vec::new()),
+			Err(LayerIrError::EmptyRun { pos: TilePos(0, 0) })
+		);
+
+		// Adjacent runs and runs on the next row are fine
+		assert!(LayerIr::from_runs(
+			vec![
+				run(0, 0, 2),
+				run(2, 0, 1),
+				run(u32::MAX, 0, 1),
+				run(0, 1, 1)
+			],
+			Vec::new()
+		)
+		.is_ok());
+	}
+
+	#[cfg(feature = "serialization")]
+	#[test]
+	fn invalid_runs_fail_to_deserialize() {
+		let layer = LayerIr::from_runs(vec![run(0, 0, 3)], Vec::new()).unwrap();
+		let mut json = serde_json::to_value(&layer).unwrap();
+		assert_eq!(
+			serde_json::from_value::<LayerIr>(json.clone()).unwrap(),
+			layer
+		);
+
+		json["runs"]
+			.as_array_mut()
+			.unwrap()
+			.push(serde_json::to_value(run(1, 0, 1)).unwrap());
+		assert!(serde_json::from_value::<LayerIr>(json).is_err());
+	}
+}
@@ -0,0 +1,373 @@
+//! Applying a [`TilemapIr`] to the world and extracting one back out of it
+
+use bevy::math::UVec2;
+use bevy::prelude::Query;
+use bevy_ecs_tilemap::{Tile, TileParent, TilePos};
+use bevy_tileset::prelude::{TilesetParent, Tilesets};
+
+use crate::combined::CombinedTilesets;
+use crate::coord::TileCoord;
+use crate::elevation::TileElevation;
+use crate::flags::TileFlags;
+use crate::ir::{TileExtras, TilemapIr};
+use crate::ownership::TileOwner;
+use crate::placement::{PlacedTile, TilePlacementError, TilePlacer};
+use crate::transitions::TileTransition;
+
+/// The query used to extract tiles into a [`TilemapIr`]
+pub type TileExtractQuery<'w, 's> = Query<
+	'w,
+	's,
+	(
+		&'static Tile,
+		&'static TileParent,
+		&'static TilePos,
+		&'static TilesetParent,
+		Option<&'static TileElevation>,
+		Option<&'static TileFlags>,
+		Option<&'static TileOwner>,
+		Option<&'static TileTransition>,
+	),
+>;
+
+/// Which tiles should be extracted into a [`TilemapIr`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum IrScope {
+	/// All tiles in all maps
+	#[default]
+	All,
+	/// All tiles in the given map
+	Map(u16),
+	/// All tiles in the given layer of the given map
+	Layer { map_id: u16, layer_id: u16 },
+}
+
+/// How each tile of a [`TilemapIr`] should be placed when spawned
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum IrSpawnMode {
+	/// Uses [`TilePlacer::place`], overwriting any existing tiles
+	#[default]
+	Place,
+	/// Uses [`TilePlacer::try_place`], skipping occupied coordinates
+	TryPlace,
+	/// Uses [`TilePlacer::replace`], skipping coordinates already occupied by a matching tile
+	Replace,
+}
+
+/// Options for spawning a [`TilemapIr`]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct IrSpawnOptions {
+	/// How each tile should be placed
+	pub mode: IrSpawnMode,
+	/// An offset applied to the position of every tile
+	pub offset: UVec2,
+}
+
+/// The outcome of spawning a [`TilemapIr`]
+#[derive(Debug, Default)]
+pub struct IrSpawnReport {
+	/// The number of tiles that were successfully placed
+	pub placed: usize,
+	/// The tiles that could not be placed, along with the reason why
+	pub failed: Vec<(TileCoord, TilePlacementError)>,
+}
+
+impl IrScope {
+	/// Returns true if the given tile parent falls within this scope
+	pub fn contains(&self, parent: &TileParent) -> bool {
+		match self {
+			Self::All => true,
+			Self::Map(map_id) => parent.map_id == *map_id,
+			Self::Layer { map_id, layer_id } => {
+				parent.map_id == *map_id && parent.layer_id == *layer_id
+			},
+		}
+	}
+}
+
+impl Default for IrSpawnOptions {
+	fn default() -> Self {
+		Self {
+			mode: IrSpawnMode::default(),
+			offset: UVec2::ZERO,
+		}
+	}
+}
+
+impl IrSpawnReport {
+	/// Returns true if every tile was placed successfully
+	pub fn is_ok(&self) -> bool {
+		self.failed.is_empty()
+	}
+}
+
+impl TilemapIr {
+	/// Extract the tiles within the given scope from the world
+	///
+	/// Only tiles that belong to a registered tileset are extracted.
+	///
+	/// # Arguments
+	///
+	/// * `scope`: Which tiles to extract
+	/// * `tiles`: A query over all tiles
+	/// * `tilesets`: The registered tilesets
+	/// * `combined`: The combined tilesets of the current layers
+	///
+	pub fn extract(
+		scope: IrScope,
+		tiles: &TileExtractQuery,
+		tilesets: &Tilesets,
+		combined: &CombinedTilesets,
+	) -> Self {
+		let mut builder = TilemapIr::builder();
+		for (tile, parent, pos, tileset, elevation, flags, owner, transition) in tiles.iter() {
+			if !scope.contains(parent) {
+				continue;
+			}
+
+			let tile_id = tilesets.get_by_id(&tileset.0).and_then(|tileset| {
+				combined.get_tile_id(
+					tileset,
+					parent.map_id,
+					parent.layer_id,
+					tile.texture_index as usize,
+				)
+			});
+			if let Some(tile_id) = tile_id {
+				let extras = TileExtras {
+					#[cfg(feature = "variants")]
+					variant: tile_id.variant_index,
+					elevation: elevation.map(|elevation| elevation.0),
+					flags: flags.copied(),
+					owner: owner.copied(),
+					transition: transition.and_then(|transition| transition.remaining),
+				};
+				builder.add_tile_with_extras(parent.map_id, parent.layer_id, *pos, tile_id, extras);
+			}
+		}
+		builder.build()
+	}
+
+	/// Spawn all tiles of this IR into the world using the given [`TilePlacer`]
+	///
+	/// This is the central path for applying an IR: Auto Tiles, animations, metadata, and placement
+	/// hooks are all handled by the placer. Chunks are only remeshed once every tile has been
+	/// placed.
+	///
+	/// Tiles whose offset position would overflow are reported as
+	/// [`TilePlacementError::OutOfBounds`].
+	///
+	/// # Arguments
+	///
+	/// * `placer`: The tile placer
+	/// * `options`: How the tiles should be spawned
+	///
+	pub fn spawn(&self, placer: &mut TilePlacer, options: &IrSpawnOptions) -> IrSpawnReport {
+		let mut report = IrSpawnReport::default();
+		placer.begin_batch();
+		for (coord, tile) in self.iter() {
+			let id = tile.resolved_id();
+			let pos = match (
+				coord.pos.0.checked_add(options.offset.x),
+				coord.pos.1.checked_add(options.offset.y),
+			) {
+				(Some(x), Some(y)) => TilePos(x, y),
+				_ => {
+					let pos = TilePos(
+						coord.pos.0.saturating_add(options.offset.x),
+						coord.pos.1.saturating_add(options.offset.y),
+					);
+					let map_size = placer
+						.map_bounds(coord.map_id, coord.layer_id)
+						.unwrap_or_default();
+					report.failed.push((
+						TileCoord { pos, ..coord },
+						TilePlacementError::OutOfBounds { pos, map_size },
+					));
+					continue;
+				},
+			};
+
+			let result = match options.mode {
+				IrSpawnMode::Place => placer.place(id, pos, coord.map_id, coord.layer_id),
+				IrSpawnMode::TryPlace => placer.try_place(id, pos, coord.map_id, coord.layer_id),
+				IrSpawnMode::Replace => placer.replace(id, pos, coord.map_id, coord.layer_id),
+			};
+
+			match result {
+				Ok(placed) => {
+					if let (PlacedTile::Added { new_tile, .. }, Some(extras)) =
+						(placed, tile.extras)
+					{
+						if let Some(elevation) = extras.elevation {
+							placer.set_tile_elevation(new_tile.0, elevation);
+						}
+						if let Some(flags) = extras.flags {
+							placer.set_tile_flags(new_tile.0, flags);
+						}
+						if let Some(owner) = extras.owner {
+							placer.set_tile_owner(new_tile.0, Some(owner));
+						}
+						if let Some(remaining) = extras.transition {
+							placer.restore_transition(new_tile.0, &new_tile.1, remaining);
+						}
+					}
+					report.placed += 1;
+				},
+				Err(err) => report.failed.push((TileCoord { pos, ..coord }, err)),
+			}
+		}
+		placer.end_batch();
+		report
+	}
+}
+
+#[cfg(all(test, not(feature = "headless")))]
+mod tests {
+	use bevy::asset::AssetPlugin;
+	use bevy::ecs::system::SystemState;
+	use bevy::prelude::*;
+	use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+	use bevy_ecs_tilemap::{Tile, TileParent, TilePos};
+	use bevy_tileset::prelude::{TileId, TilesetId, TilesetParent, TilesetPlugin, Tilesets};
+
+	use crate::combined::{CombinedTilesets, TilesetCombiner};
+	use crate::coord::TileCoord;
+	use crate::ir::{IrScope, TileExtractQuery, TilemapIr};
+	use crate::runtime::TilesetFactory;
+	use crate::snapshot::{TileSnapshotQuery, TilemapGeneration, TilemapReadSnapshot};
+
+	const GRASS: TilesetId = 1;
+	const DIRT: TilesetId = 2;
+
+	fn texture(color: [u8; 4]) -> Image {
+		Image::new_fill(
+			Extent3d {
+				width: 1,
+				height: 1,
+				depth_or_array_layers: 1,
+			},
+			TextureDimension::D2,
+			&color,
+			TextureFormat::Rgba8UnormSrgb,
+		)
+	}
+
+	/// Create an app with two tilesets sharing a combined layer (map 0, layer 0)
+	fn combined_app() -> App {
+		let mut app = App::new();
+		app.add_plugins(MinimalPlugins)
+			.add_plugin(AssetPlugin)
+			.add_asset::<Image>()
+			.add_asset::<TextureAtlas>()
+			.add_plugin(TilesetPlugin::default())
+			.init_resource::<CombinedTilesets>()
+			.init_resource::<TilemapGeneration>();
+
+		let mut state = SystemState::<TilesetFactory>::new(&mut app.world);
+		let mut factory = state.get_mut(&mut app.world);
+		factory
+			.create_from_textures(
+				"Grass",
+				GRASS,
+				[
+					("Grass", texture([0, 255, 0, 255])),
+					("Flowers", texture([255, 0, 255, 255])),
+				],
+			)
+			.unwrap();
+		factory
+			.create_from_textures(
+				"Dirt",
+				DIRT,
+				[
+					("Dirt", texture([128, 64, 0, 255])),
+					("Mud", texture([64, 32, 0, 255])),
+				],
+			)
+			.unwrap();
+
+		// Let the tilesets get registered
+		app.update();
+		app.update();
+
+		let mut state = SystemState::<TilesetCombiner>::new(&mut app.world);
+		let combined = state
+			.get_mut(&mut app.world)
+			.combine(&[GRASS, DIRT])
+			.unwrap();
+		app.world
+			.resource_mut::<CombinedTilesets>()
+			.insert(0, 0, combined);
+
+		app
+	}
+
+	/// Spawn a tile of the given tileset in the combined layer, returning its combined index
+	fn spawn_tile(app: &mut App, tileset_id: TilesetId, index: usize, pos: TilePos) -> usize {
+		let index = app
+			.world
+			.resource::<CombinedTilesets>()
+			.get(0, 0)
+			.and_then(|combined| combined.to_combined(&tileset_id, index))
+			.unwrap();
+		let chunk = app.world.spawn().id();
+		app.world
+			.spawn()
+			.insert(Tile {
+				texture_index: index as u16,
+				..Default::default()
+			})
+			.insert(TileParent {
+				chunk,
+				layer_id: 0,
+				map_id: 0,
+			})
+			.insert(pos)
+			.insert(TilesetParent(tileset_id));
+		index
+	}
+
+	#[test]
+	fn combined_layer_round_trips() {
+		let mut app = combined_app();
+		let expected = [
+			(TilePos(0, 0), TileId::new(1, GRASS)),
+			(TilePos(1, 0), TileId::new(1, DIRT)),
+		];
+		let indices = expected.map(|(pos, tile_id)| {
+			spawn_tile(&mut app, tile_id.tileset_id, tile_id.group_id as usize, pos)
+		});
+
+		// --- Extract --- //
+		let mut state =
+			SystemState::<(TileExtractQuery, Tilesets, Res<CombinedTilesets>)>::new(&mut app.world);
+		let (tiles, tilesets, combined) = state.get_mut(&mut app.world);
+		let ir = TilemapIr::extract(IrScope::All, &tiles, &tilesets, &combined);
+		let layer = ir.layer(0, 0).unwrap();
+		for ((pos, tile_id), index) in expected.into_iter().zip(indices) {
+			let id = layer.get(pos).unwrap();
+			assert!(id.eq_tile_group(&tile_id));
+
+			// --- Spawn --- //
+			// The placer shifts the (local) index of each spawned tile by its offset in the layer
+			let offset = combined.offset(&id.tileset_id, 0, 0);
+			assert_eq!(offset + id.group_id as usize, index);
+		}
+
+		// --- Snapshot --- //
+		let mut state = SystemState::<(
+			TileSnapshotQuery,
+			Tilesets,
+			Res<CombinedTilesets>,
+			Res<TilemapGeneration>,
+		)>::new(&mut app.world);
+		let (tiles, tilesets, combined, generation) = state.get_mut(&mut app.world);
+		let snapshot =
+			TilemapReadSnapshot::capture(IrScope::All, &tiles, &tilesets, &combined, &generation);
+		for (pos, tile_id) in expected {
+			let tile = snapshot.get(&TileCoord::new(pos, 0u16, 0)).unwrap();
+			assert!(tile.id.eq_tile_group(&tile_id));
+		}
+	}
+}
@@ -0,0 +1,210 @@
+//! Pseudo-3D maps where each layer represents a level of elevation
+//!
+//! Maps configured in the [`IsoElevationConfig`] treat a range of their layers as stacked height
+//! levels. Each of these layers is offset upwards by its height and depth-sorted above the levels
+//! beneath it, so tiles can simply be placed at a given level using the [`IsoPlacer`].
+//!
+//! While mainly intended for isometric maps, this works for any mesh type.
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_ecs_tilemap::prelude::{MapId, TilePos};
+use bevy_tileset::prelude::TileId;
+
+use crate::placement::{TilePlacementError, TilePlacementResult, TilePlacer};
+use crate::registry::TilesetMapRegistry;
+
+/// The elevation settings of a single map
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct IsoElevation {
+	/// The layer representing the ground level
+	pub base_layer: u16,
+	/// The number of levels above the ground level
+	pub levels: u16,
+	/// The vertical offset (in pixels) between two consecutive levels
+	pub level_height: f32,
+	/// The z-offset between two consecutive levels, used to draw higher levels on top
+	pub level_depth: f32,
+}
+
+/// A resource configuring which maps use their layers as elevation levels
+///
+/// The transforms of all layers belonging to a configured map's levels are managed by the
+/// [`TilesetMapPlugin`](crate::prelude::TilesetMapPlugin) and should not be changed manually.
+///
+/// # Examples
+///
+/// ```
+/// # use bevy_tileset_map::prelude::{IsoElevation, IsoElevationConfig};
+/// let mut config = IsoElevationConfig::default();
+/// // Layers 1 through 5 of map 0 represent increasingly high terrain
+/// config.insert(0, IsoElevation {
+///   base_layer: 1,
+///   levels: 4,
+///   level_height: 16.0,
+///   level_depth: 1.0,
+/// });
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct IsoElevationConfig {
+	maps: HashMap<u16, IsoElevation>,
+}
+
+impl IsoElevation {
+	/// The layer representing the given level (if any)
+	pub fn layer(&self, level: u16) -> Option<u16> {
+		if level > self.levels {
+			return None;
+		}
+		self.base_layer.checked_add(level)
+	}
+
+	/// The level represented by the given layer (if any)
+	pub fn level(&self, layer_id: u16) -> Option<u16> {
+		let level = layer_id.checked_sub(self.base_layer)?;
+		(level <= self.levels).then_some(level)
+	}
+
+	/// The translation applied to the layer representing the given level
+	pub fn offset(&self, level: u16) -> Vec3 {
+		let level = level as f32;
+		Vec3::new(0.0, level * self.level_height, level * self.level_depth)
+	}
+}
+
+impl IsoElevationConfig {
+	/// Get the elevation settings of the given map
+	pub fn get(&self, map_id: u16) -> Option<&IsoElevation> {
+		self.maps.get(&map_id)
+	}
+
+	/// Configure the given map to use its layers as elevation levels
+	pub fn insert(&mut self, map_id: u16, elevation: IsoElevation) -> Option<IsoElevation> {
+		self.maps.insert(map_id, elevation)
+	}
+
+	/// Stop using the layers of the given map as elevation levels
+	///
+	/// The transforms of its layers are left as they are.
+	pub fn remove(&mut self, map_id: u16) -> Option<IsoElevation> {
+		self.maps.remove(&map_id)
+	}
+
+	/// The layer representing the given level of the given map (if any)
+	pub fn layer(&self, map_id: u16, level: u16) -> Option<u16> {
+		self.get(map_id)?.layer(level)
+	}
+
+	/// The level represented by the given layer of the given map (if any)
+	pub fn level(&self, map_id: u16, layer_id: u16) -> Option<u16> {
+		self.get(map_id)?.level(layer_id)
+	}
+}
+
+/// A helper system param used to place tiles at a given level of elevation
+///
+/// # Examples
+///
+/// ```
+/// # use bevy_ecs_tilemap::TilePos;
+/// # use bevy_tileset_map::prelude::{IsoPlacer, TileId};
+/// fn raise_terrain(mut placer: IsoPlacer) {
+/// #   let tile_id = TileId::new(0, 0);
+///   let level = placer.top_level(TilePos(4, 2), 0u16).map_or(0, |level| level + 1);
+///   placer.place_at(tile_id, TilePos(4, 2), 0u16, level).ok();
+/// }
+/// ```
+#[derive(SystemParam)]
+pub struct IsoPlacer<'w, 's> {
+	placer: TilePlacer<'w, 's>,
+	config: Res<'w, IsoElevationConfig>,
+}
+
+impl<'w, 's> IsoPlacer<'w, 's> {
+	/// Place a tile at the given level
+	///
+	/// # Arguments
+	///
+	/// * `tile_id`: The full ID of the tile to place
+	/// * `pos`: The tile position
+	/// * `map_id`: The tile map
+	/// * `level`: The level of elevation, where `0` is the ground level
+	///
+	pub fn place_at<Id: Into<TileId>, Pos: Into<TilePos>, MId: MapId>(
+		&mut self,
+		tile_id: Id,
+		pos: Pos,
+		map_id: MId,
+		level: u16,
+	) -> TilePlacementResult {
+		let layer_id = self.layer(map_id.into(), level)?;
+		self.placer.place(tile_id, pos.into(), map_id, layer_id)
+	}
+
+	/// Remove the tile at the given level
+	pub fn remove_at<Pos: Into<TilePos>, MId: MapId>(
+		&mut self,
+		pos: Pos,
+		map_id: MId,
+		level: u16,
+	) -> Result<(), TilePlacementError> {
+		let layer_id = self.layer(map_id.into(), level)?;
+		self.placer.remove(pos, map_id, layer_id)
+	}
+
+	/// The highest level containing a tile at the given position (if any)
+	pub fn top_level<Pos: Into<TilePos>, MId: MapId>(
+		&mut self,
+		pos: Pos,
+		map_id: MId,
+	) -> Option<u16> {
+		let pos = pos.into();
+		let elevation = *self.config.get(map_id.into())?;
+		(0..=elevation.levels).rev().find(|level| {
+			elevation.layer(*level).is_some_and(|layer_id| {
+				self.placer.get_tile_entity(pos, map_id, layer_id).is_some()
+			})
+		})
+	}
+
+	/// Get the placer used to place tiles regardless of their level
+	pub fn placer(&mut self) -> &mut TilePlacer<'w, 's> {
+		&mut self.placer
+	}
+
+	fn layer(&self, map_id: u16, level: u16) -> Result<u16, TilePlacementError> {
+		self.config
+			.layer(map_id, level)
+			.ok_or(TilePlacementError::InvalidLevel { map_id, level })
+	}
+}
+
+/// __\[SYSTEM\]__ Offsets and depth-sorts the layers of all maps configured in [`IsoElevationConfig`]
+pub(crate) fn update_iso_layers(
+	config: Res<IsoElevationConfig>,
+	registry: Res<TilesetMapRegistry>,
+	mut transforms: Query<&mut Transform>,
+) {
+	if !config.is_changed() && !registry.is_changed() {
+		return;
+	}
+
+	for (map_id, layer_id, info) in registry.layers() {
+		let offset = if let Some(elevation) = config.get(map_id) {
+			if let Some(level) = elevation.level(layer_id) {
+				elevation.offset(level)
+			} else {
+				continue;
+			}
+		} else {
+			continue;
+		};
+
+		if let Ok(mut transform) = transforms.get_mut(info.entity) {
+			if transform.translation != offset {
+				transform.translation = offset;
+			}
+		}
+	}
+}
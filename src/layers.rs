@@ -0,0 +1,320 @@
+//! Tools for creating and managing entire layers
+
+use bevy::ecs::system::{Command, CommandQueue, SystemParam};
+use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::*;
+use bevy_tileset::prelude::*;
+use thiserror::Error;
+
+use crate::combined::{CombinedTileset, CombinedTilesets};
+use crate::elevation::ElevatedVisibility;
+use crate::objects::TileObjects;
+use crate::parallax::LayerParallax;
+
+/// Errors related to the management of layers
+#[derive(Error, Debug)]
+pub enum TileLayerError {
+	/// The tileset does not exist or is invalid
+	///
+	/// Contains the ID of the tileset in question
+	#[error("Invalid tileset {0:?}")]
+	InvalidTileset(TilesetId),
+	/// The layer does not exist
+	#[error("Layer {layer_id} does not exist in map {map_id}")]
+	InvalidLayer { map_id: u16, layer_id: u16 },
+	/// The tilesets differ in tile size or texture size and cannot be swapped
+	#[error("Tileset {from:?} cannot be swapped for incompatible tileset {to:?}")]
+	IncompatibleTileset { from: TilesetId, to: TilesetId },
+}
+
+/// A helper system param used to create, clear, hide/show, and despawn entire layers
+///
+/// # Examples
+///
+/// ```
+/// # use bevy_ecs_tilemap::prelude::{ChunkSize, MapSize};
+/// # use bevy_tileset_map::prelude::{TileLayerManager, TilesetId};
+/// struct MyTileset(TilesetId);
+///
+/// fn setup(mut layers: TileLayerManager, tileset: bevy::prelude::Res<MyTileset>) {
+///   let map = layers.create_map(0u16);
+///   layers.create_layer(
+///     &tileset.0,
+///     map,
+///     0u16,
+///     0u16,
+///     MapSize(4, 4),
+///     ChunkSize(32, 32),
+///   ).unwrap();
+/// }
+/// ```
+#[derive(SystemParam)]
+pub struct TileLayerManager<'w, 's> {
+	commands: Commands<'w, 's>,
+	map_query: MapQuery<'w, 's>,
+	tilesets: Tilesets<'w, 's>,
+	tiles: Query<
+		'w,
+		's,
+		(
+			Entity,
+			&'static Tile,
+			&'static TilePos,
+			&'static TileParent,
+			Option<&'static ElevatedVisibility>,
+		),
+	>,
+	combined: ResMut<'w, CombinedTilesets>,
+	objects: ResMut<'w, TileObjects>,
+}
+
+impl<'w, 's> TileLayerManager<'w, 's> {
+	/// Spawn a new, empty map with the given ID
+	///
+	/// Returns the map entity.
+	pub fn create_map<MId: MapId>(&mut self, map_id: MId) -> Entity {
+		self.create_map_with_transform(map_id, Transform::default())
+	}
+
+	/// Spawn a new, empty map with the given ID at the given transform
+	///
+	/// Returns the map entity.
+	pub fn create_map_with_transform<MId: MapId>(
+		&mut self,
+		map_id: MId,
+		transform: Transform,
+	) -> Entity {
+		let map_entity = self.commands.spawn().id();
+		let map = Map::new(map_id, map_entity);
+		self.commands
+			.entity(map_entity)
+			.insert(map)
+			.insert(transform)
+			.insert(GlobalTransform::from(transform));
+		map_entity
+	}
+
+	/// Create a new, empty layer sized and configured to match the given tileset
+	///
+	/// Returns the layer entity.
+	///
+	/// # Arguments
+	///
+	/// * `tileset_id`: The tileset whose texture and tile size should be used
+	/// * `map_entity`: The entity of the map to add the layer to
+	/// * `map_id`: The ID of the map to add the layer to
+	/// * `layer_id`: The ID of the new layer
+	/// * `map_size`: The size of the layer (in chunks)
+	/// * `chunk_size`: The size of each chunk (in tiles)
+	///
+	pub fn create_layer<MId: MapId>(
+		&mut self,
+		tileset_id: &TilesetId,
+		map_entity: Entity,
+		map_id: MId,
+		layer_id: u16,
+		map_size: MapSize,
+		chunk_size: ChunkSize,
+	) -> Result<Entity, TileLayerError> {
+		let tileset = self
+			.tilesets
+			.get_by_id(tileset_id)
+			.ok_or(TileLayerError::InvalidTileset(*tileset_id))?;
+
+		let texture_size = tileset.size();
+		let settings = LayerSettings::new(
+			map_size,
+			chunk_size,
+			tileset.tile_size().into(),
+			TextureSize(texture_size.x, texture_size.y),
+		);
+
+		let (layer_builder, _) =
+			LayerBuilder::<TileBundle>::new(&mut self.commands, settings, map_id, layer_id);
+		let layer_entity = self.map_query.build_layer(
+			&mut self.commands,
+			layer_builder,
+			tileset.texture().clone(),
+		);
+		self.commands.add(AddLayer {
+			map_entity,
+			layer_id,
+			layer_entity,
+		});
+
+		Ok(layer_entity)
+	}
+
+	/// Create a new, empty layer using the texture of the given [`CombinedTileset`]
+	///
+	/// Tiles from any of the combined tilesets can be placed in this layer. Returns the layer entity.
+	///
+	/// # Arguments
+	///
+	/// * `combined`: The combined tileset whose texture and tile size should be used
+	/// * `map_entity`: The entity of the map to add the layer to
+	/// * `map_id`: The ID of the map to add the layer to
+	/// * `layer_id`: The ID of the new layer
+	/// * `map_size`: The size of the layer (in chunks)
+	/// * `chunk_size`: The size of each chunk (in tiles)
+	///
+	pub fn create_combined_layer<MId: MapId>(
+		&mut self,
+		combined: CombinedTileset,
+		map_entity: Entity,
+		map_id: MId,
+		layer_id: u16,
+		map_size: MapSize,
+		chunk_size: ChunkSize,
+	) -> Entity {
+		let map_id = map_id.into();
+		let texture_size = combined.size();
+		let settings = LayerSettings::new(
+			map_size,
+			chunk_size,
+			combined.tile_size().into(),
+			TextureSize(texture_size.x, texture_size.y),
+		);
+
+		let (layer_builder, _) =
+			LayerBuilder::<TileBundle>::new(&mut self.commands, settings, map_id, layer_id);
+		let layer_entity = self.map_query.build_layer(
+			&mut self.commands,
+			layer_builder,
+			combined.texture().clone(),
+		);
+		self.commands.add(AddLayer {
+			map_entity,
+			layer_id,
+			layer_entity,
+		});
+		self.combined.insert(map_id, layer_id, combined);
+
+		layer_entity
+	}
+
+	/// Set the z-offset and parallax factor of the given layer
+	///
+	/// This can be called right after [`create_layer`](Self::create_layer).
+	pub fn set_layer_parallax(&mut self, layer_entity: Entity, parallax: LayerParallax) {
+		self.commands.entity(layer_entity).insert(parallax);
+	}
+
+	/// Remove the parallax of the given layer, leaving it at its current position
+	pub fn clear_layer_parallax(&mut self, layer_entity: Entity) {
+		self.commands.entity(layer_entity).remove::<LayerParallax>();
+	}
+
+	/// Despawn all tiles in the given layer, keeping the layer itself
+	pub fn clear_layer<MId: MapId>(&mut self, map_id: MId, layer_id: u16) {
+		self.map_query
+			.despawn_layer_tiles(&mut self.commands, map_id, layer_id);
+	}
+
+	/// Show or hide every tile in the given layer without despawning them
+	///
+	/// Returns the number of tiles whose visibility was changed.
+	pub fn set_layer_visible<MId: MapId>(
+		&mut self,
+		map_id: MId,
+		layer_id: u16,
+		visible: bool,
+	) -> usize {
+		let map_id = map_id.into();
+		let mut count = 0;
+		for (entity, tile, pos, parent, elevated) in self.tiles.iter() {
+			if parent.map_id != map_id || parent.layer_id != layer_id {
+				continue;
+			}
+
+			// Elevated tiles are always hidden from their chunk
+			if let Some(elevated) = elevated {
+				if elevated.visible != visible {
+					self.commands
+						.entity(entity)
+						.insert(ElevatedVisibility { visible });
+					count += 1;
+				}
+				continue;
+			}
+
+			if tile.visible == visible {
+				continue;
+			}
+
+			self.commands
+				.entity(entity)
+				.insert(Tile { visible, ..*tile });
+			self.map_query.notify_chunk_for_tile(*pos, map_id, layer_id);
+			count += 1;
+		}
+		count
+	}
+
+	/// Hide every tile in the given layer
+	pub fn hide_layer<MId: MapId>(&mut self, map_id: MId, layer_id: u16) -> usize {
+		self.set_layer_visible(map_id, layer_id, false)
+	}
+
+	/// Show every tile in the given layer
+	pub fn show_layer<MId: MapId>(&mut self, map_id: MId, layer_id: u16) -> usize {
+		self.set_layer_visible(map_id, layer_id, true)
+	}
+
+	/// Despawn the given layer entirely, including all of its tiles and objects
+	pub fn despawn_layer<MId: MapId>(
+		&mut self,
+		map_id: MId,
+		layer_id: u16,
+	) -> Result<(), TileLayerError> {
+		let map_id = map_id.into();
+		if self.map_query.get_layer(map_id, layer_id).is_none() {
+			return Err(TileLayerError::InvalidLayer { map_id, layer_id });
+		}
+
+		// Objects are children of the layer, so they're despawned first
+		for entity in self.objects.take_layer(map_id, layer_id) {
+			self.commands.entity(entity).despawn_recursive();
+		}
+		self.map_query
+			.despawn_layer(&mut self.commands, map_id, layer_id);
+		self.combined.remove(map_id, layer_id);
+		Ok(())
+	}
+
+	/// Despawn the given map entirely, including all of its layers, tiles, and objects
+	pub fn despawn_map<MId: MapId>(&mut self, map_id: MId) {
+		let map_id = map_id.into();
+		for entity in self.objects.take_map(map_id) {
+			self.commands.entity(entity).despawn_recursive();
+		}
+		self.map_query.despawn(&mut self.commands, map_id);
+		self.combined.remove_map(map_id);
+	}
+}
+
+/// A command that adds a layer to an existing [`Map`]
+///
+/// This is needed since the [`Map`] component can't be accessed alongside a [`MapQuery`].
+struct AddLayer {
+	map_entity: Entity,
+	layer_id: u16,
+	layer_entity: Entity,
+}
+
+impl Command for AddLayer {
+	fn write(self, world: &mut World) {
+		let mut map = if let Some(map) = world.entity_mut(self.map_entity).remove::<Map>() {
+			map
+		} else {
+			return;
+		};
+
+		let mut queue = CommandQueue::default();
+		let mut commands = Commands::new(&mut queue, world);
+		map.add_layer(&mut commands, self.layer_id, self.layer_entity);
+
+		world.entity_mut(self.map_entity).insert(map);
+		queue.apply(world);
+	}
+}
@@ -11,7 +11,7 @@
 //!
 //! Add the plugins to your Bevy app:
 //!
-//! ```
+//! ```no_run
 //! use bevy::prelude::*;
 //! use bevy_tileset_map::prelude::{TilesetPlugin, TilesetMapPlugin};
 //! use bevy_ecs_tilemap::prelude::TilemapPlugin;
@@ -24,7 +24,23 @@
 //!     // bevy_tileset
 //!     .add_plugin(TilesetPlugin::default())
 //!     // bevy_tileset_map
-//!     .add_plugin(TilesetMapPlugin)
+//!     .add_plugin(TilesetMapPlugin::default())
+//!     // ...
+//!     .run();
+//! }
+//! ```
+//!
+//! Or add them all at once, in the correct order, using the [`TilesetMapPlugins`](prelude::TilesetMapPlugins)
+//! group:
+//!
+//! ```no_run
+//! use bevy::prelude::*;
+//! use bevy_tileset_map::prelude::TilesetMapPlugins;
+//!
+//! fn main() {
+//!   App::new()
+//!     // ...
+//!     .add_plugins(TilesetMapPlugins::default())
 //!     // ...
 //!     .run();
 //! }
@@ -33,8 +49,8 @@
 //! And add a system to place tiles:
 //!
 //! ```
-//! use bevy_tileset_map::prelude::Tileplacer;
-//! fn place_tile(mut placer: Tileplacer, /* ... */) {
+//! use bevy_tileset_map::prelude::TilePlacer;
+//! fn place_tile(mut placer: TilePlacer, /* ... */) {
 //! #   let tile_id = bevy_tileset_map::prelude::TileId::new(0, 0);
 //! #   let tile_pos = bevy_ecs_tilemap::TilePos(0, 0);
 //! #   let map_id = 0u16;
@@ -54,27 +70,216 @@
 //! * __`default`__ - No features automatically enabled
 //! * __`variants`__ - Enables usage of Variant tiles
 //! * __`auto-tile`__ - Enables usage of Auto tiles
+//! * __`auto-tile-debug`__ - Enables an overlay visualizing how Auto tiles were resolved
 //! * __`serialization`__ - Enables tilemap serialization
+//! * __`elevation`__ - Enables rendering of per-tile elevation offsets
+//! * __`physics-rapier`__ - Enables collider generation for solid tiles using `bevy_rapier2d`
+//! * __`nav`__ - Enables walkability grids and A* pathfinding
+//! * __`ambience`__ - Enables sampling of tile ambience around listeners
+//! * __`strict`__ - Enables runtime invariant checks for catching integration bugs during development
+//! * __`headless`__ - Allows running without the `TilemapPlugin` or any rendering (such as on a dedicated server).
+//!   This removes the `TilesetCombiner`, `TilesetFactory`, tile previews, elevation sprites, and the Auto tile debug overlay
+//! * __`replication`__ - Enables serializable tile deltas for replicating tilemaps over the network
+//! * __`destructible`__ - Enables tile hit points, damage stages, and destruction
+//! * __`wfc`__ - Enables procedural generation using Wave Function Collapse
+//! * __`editor-egui`__ - Enables ready-made egui panels for building an in-game map editor
+//! * __`inspector`__ - Enables tile information panels for the world inspector of `bevy-inspector-egui`
+//! * __`scripting`__ - Enables a command queue for editing and querying tiles from scripts
 //!
 
+// Systems commonly take many (and deeply nested) params
+#![allow(clippy::too_many_arguments, clippy::type_complexity)]
+
 pub use bevy_tileset as tileset;
 
+mod activity;
+#[cfg(feature = "ambience")]
+mod ambience;
+mod animator;
 #[cfg(feature = "auto-tile")]
 pub(crate) mod auto;
+mod automata;
+mod budget;
+mod changes;
+mod chunks;
+mod collision;
+mod combined;
+mod config;
+mod connectivity;
 mod coord;
+#[cfg(feature = "destructible")]
+mod destructible;
+mod diagnostics;
+#[cfg(feature = "editor-egui")]
+mod editor;
+mod elevation;
+mod flags;
+mod fog;
+mod generate;
+mod infinite;
+#[cfg(feature = "inspector")]
+mod inspector;
+mod ir;
+mod iso;
+mod layers;
+mod limiter;
+mod locking;
+mod markers;
+mod metadata;
+#[cfg(feature = "nav")]
+mod nav;
+mod objects;
+mod ownership;
+mod parallax;
+#[cfg(feature = "physics-rapier")]
+mod physics;
+mod placed;
 mod placement;
 mod plugin;
+#[cfg(not(feature = "headless"))]
+mod preview;
+mod querier;
+mod raycast;
+mod registry;
+mod reload;
+mod remesh;
+#[cfg(feature = "replication")]
+mod replication;
+#[cfg(not(feature = "headless"))]
+mod runtime;
+mod scene;
+#[cfg(feature = "scripting")]
+mod scripting;
+mod selection;
 #[cfg(feature = "serialization")]
 mod serialization;
+mod snapshot;
+#[cfg(feature = "strict")]
+mod strict;
+mod substitution;
+mod swap;
+mod template;
+#[cfg(all(test, not(feature = "headless")))]
+mod testing;
+mod transitions;
+#[cfg(feature = "wfc")]
+mod wfc;
 
 pub mod prelude {
 	pub use bevy_tileset::prelude::*;
 
+	pub use super::activity::{ActiveChunks, ActiveLayer, ActivityConfig};
+	#[cfg(feature = "ambience")]
+	pub use super::ambience::{
+		AmbienceChanged, AmbienceListener, AmbienceSample, AmbienceSamples, AMBIENCE_METADATA_KEY,
+	};
+	pub use super::animator::{
+		AnimationEnd, OneShotAnimation, PlayingAnimation, TileAnimationFinishedEvent, TileAnimator,
+	};
+	#[cfg(all(feature = "auto-tile-debug", not(feature = "headless")))]
+	pub use super::auto::AutoTileDebugOverlay;
 	#[cfg(feature = "auto-tile")]
-	pub use super::auto::RemoveAutoTileEvent;
+	pub use super::auto::{
+		AutoTileMask, AutoTileSymmetry, RemoveAutoTileEvent, AUTO_MASK_METADATA_KEY,
+		AUTO_SYMMETRY_METADATA_KEY,
+	};
+	pub use super::automata::{AutomataNeighborhood, AutomataReport, TileAutomata};
+	pub use super::budget::{PlacementBatchCompleted, PlacementBudget};
+	pub use super::changes::{TileChange, TilemapChanges};
+	pub use super::chunks::{ChunkDespawnedEvent, ChunkSpawnedEvent};
+	pub use super::collision::*;
+	#[cfg(not(feature = "headless"))]
+	pub use super::combined::{CombineTilesetsError, TilesetCombiner};
+	pub use super::combined::{CombinedTileset, CombinedTilesets};
+	pub use super::config::{MapSettings, TilesetMapConfig};
+	pub use super::connectivity::{
+		ConnectedRegion, ConnectedRegionCache, ConnectedRegions, Connectivity,
+	};
 	pub use super::coord::TileCoord;
+	#[cfg(feature = "destructible")]
+	pub use super::destructible::{
+		DamageOutcome, TileDamage, TileDestroyedEvent, TileHealth, DAMAGE_STAGES_METADATA_KEY,
+		DROPS_METADATA_KEY, HEALTH_METADATA_KEY,
+	};
+	pub use super::diagnostics::{
+		MapDiagnostics, TilesetMapDiagnostics, TilesetMapDiagnosticsPlugin,
+	};
+	#[cfg(feature = "editor-egui")]
+	pub use super::editor::{
+		brush_selector, layer_list, tile_palette, EditorLayer, TileEditorPlugin,
+	};
+	pub use super::elevation::{TileElevation, ELEVATION_METADATA_KEY};
+	pub use super::flags::TileFlags;
+	pub use super::fog::{FogOfWar, FogOfWarConfig, FogOfWarPlugin, FogState};
+	pub use super::generate::{noise_bands, noise_threshold, ValueNoise};
+	pub use super::infinite::{
+		InfiniteMap, InfiniteMapConfig, InfiniteMapPlugin, InfiniteMapTracker, InfinitePlacer,
+		InfiniteRegion, RegionCollectedEvent, RegionDespawnedEvent, RegionGenerator,
+		RegionLoadedEvent, RegionState, RegionUnloadingEvent, RetentionPolicy,
+	};
+	#[cfg(feature = "inspector")]
+	pub use super::inspector::{TileInfo, TileInspectorPlugin};
+	pub use super::ir::*;
+	pub use super::iso::{IsoElevation, IsoElevationConfig, IsoPlacer};
+	pub use super::layers::{TileLayerError, TileLayerManager};
+	pub use super::limiter::{
+		PlacementLimitError, PlacementLimiter, PlacementLimits, RateLimit, COST_METADATA_KEY,
+	};
+	pub use super::locking::{
+		ConflictResolution, LockRelease, MapLocks, TileEdit, TileEditConflict, TileEditEvent,
+		TileEditRequest, TileEditStatus,
+	};
+	pub use super::markers::{MarkerPlaced, MarkerSpawnerFn, TileMarkers, MARKER_METADATA_KEY};
+	pub use super::metadata::*;
+	#[cfg(feature = "nav")]
+	pub use super::nav::{NavGrid, NavLayer, NAV_METADATA_KEY};
+	pub use super::objects::{
+		ObjectLayer, ObjectPlacementError, ObjectPlacer, TileObject, TileObjectSpawnerFn,
+		TileObjectSpawners, TileObjects,
+	};
+	pub use super::ownership::TileOwner;
+	pub use super::parallax::LayerParallax;
+	#[cfg(feature = "physics-rapier")]
+	pub use super::physics::TileChunkCollider;
+	pub use super::placed::PlacedTiles;
 	pub use super::placement::*;
-	pub use super::plugin::{TilesetMapLabel, TilesetMapPlugin, TilesetMapStage};
+	pub use super::plugin::{
+		ChunkUpdateStage, TilesetMapLabel, TilesetMapPlugin, TilesetMapPluginConfig,
+		TilesetMapPlugins, TilesetMapStage,
+	};
+	#[cfg(not(feature = "headless"))]
+	pub use super::preview::{TilePreviewCache, TilePreviews};
+	pub use super::querier::TileQuerier;
+	pub use super::raycast::{TileHit, TilemapRaycast};
+	pub use super::registry::{LayerInfo, MapInfo, TilesetMapRegistry};
+	pub use super::reload::TilesetReloadedEvent;
+	pub use super::remesh::{ChunkFlushMode, ChunkNotifications};
+	#[cfg(feature = "replication")]
+	pub use super::replication::{
+		ReplicationState, TileDelta, TileDeltaError, TileDeltaKind, TileReplicator,
+	};
+	#[cfg(not(feature = "headless"))]
+	pub use super::runtime::{RuntimeTilesetError, TilesetFactory};
+	pub use super::scene::{SceneLayer, SceneTile};
+	#[cfg(feature = "scripting")]
+	pub use super::scripting::{ScriptTileApi, TileScriptingPlugin};
+	pub use super::selection::{TileHighlight, TileHighlightConfig, TileSelection};
 	#[cfg(feature = "serialization")]
 	pub use super::serialization::*;
+	pub use super::snapshot::{
+		SnapshotTile, TileSnapshotQuery, TilemapGeneration, TilemapReadSnapshot,
+	};
+	#[cfg(feature = "strict")]
+	pub use super::strict::{StrictModeConfig, StrictViolation};
+	pub use super::substitution::{SubstitutionTable, TileSubstituter, TileSubstitutions};
+	pub use super::swap::TilesetSwapper;
+	pub use super::template::{
+		MapTemplate, MapTemplateError, MapTemplateLoader, TemplateStamp, TemplateTile,
+	};
+	pub use super::transitions::{
+		TileTransition, TileTransitionedEvent, TriggerTileTransitions,
+		TRANSITION_AFTER_METADATA_KEY, TRANSITION_METADATA_KEY, TRANSITION_ON_METADATA_KEY,
+	};
+	#[cfg(feature = "wfc")]
+	pub use super::wfc::{WfcError, WfcGenerator, WfcModel, WfcOutput};
 }
@@ -0,0 +1,287 @@
+//! Rate limits and resource costs for player placements
+//!
+//! The [`PlacementLimiter`] wraps the owner-aware methods of the [`TilePlacer`] with per-owner
+//! rate limits and resource costs, as configured in the [`PlacementLimits`] resource. This keeps
+//! game-economy enforcement next to the authoritative placement code.
+//!
+//! The cost of a tile is taken from the cost table of the [`PlacementLimits`] or, if it isn't
+//! listed there, from the [`COST_METADATA_KEY`] key in its [`TileMetadata`](crate::prelude::TileMetadata).
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_ecs_tilemap::prelude::{MapId, TilePos};
+use bevy_tileset::prelude::{TileId, Tilesets};
+use thiserror::Error;
+
+use crate::metadata::TileMetadataRegistry;
+use crate::ownership::TileOwner;
+use crate::placement::{PlacedTile, TilePlacementError, TilePlacer};
+
+/// The metadata key used to define the cost of placing a tile
+pub const COST_METADATA_KEY: &str = "cost";
+
+/// Errors related to limited placements
+#[derive(Error, Debug)]
+pub enum PlacementLimitError {
+	/// The owner placed too many tiles recently
+	#[error("{owner:?} is placing tiles too quickly (retry in {retry_in:?})")]
+	RateLimited {
+		owner: TileOwner,
+		/// The time until the owner may place again
+		retry_in: Duration,
+	},
+	/// The owner can't afford the tile
+	#[error("{owner:?} cannot afford {tile_id:?} (costs {cost}, has {balance})")]
+	InsufficientBalance {
+		owner: TileOwner,
+		tile_id: TileId,
+		cost: u64,
+		balance: u64,
+	},
+	/// The placement itself failed
+	#[error(transparent)]
+	Placement(#[from] TilePlacementError),
+}
+
+/// A limit on the number of placements per time window
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct RateLimit {
+	/// The maximum number of placements within the window
+	pub placements: usize,
+	/// The length of the window
+	pub per: Duration,
+}
+
+/// A resource configuring the rate limits and costs enforced by the [`PlacementLimiter`]
+///
+/// Owners start out with a balance of zero, so tiles that cost anything can only be placed once
+/// the owner has been given a balance (see [`deposit`](Self::deposit)).
+///
+/// This resource is added automatically by the [`TilesetMapPlugin`](crate::prelude::TilesetMapPlugin).
+///
+/// # Examples
+///
+/// ```
+/// # use std::time::Duration;
+/// # use bevy::prelude::*;
+/// # use bevy_tileset_map::prelude::{PlacementLimits, RateLimit, TileId, TileOwner};
+/// fn setup(mut limits: ResMut<PlacementLimits>) {
+///   limits.rate_limit = Some(RateLimit {
+///     placements: 10,
+///     per: Duration::from_secs(1),
+///   });
+///   limits.set_cost(TileId::new(0, 0), 5);
+///   limits.deposit(TileOwner(1), 100);
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct PlacementLimits {
+	/// The rate limit applied to each owner, if any
+	pub rate_limit: Option<RateLimit>,
+	/// The cost of tiles without a listed or metadata-defined cost
+	pub default_cost: u64,
+	costs: HashMap<TileId, u64>,
+	balances: HashMap<TileOwner, u64>,
+	history: HashMap<TileOwner, VecDeque<f64>>,
+}
+
+/// A helper system param used to place tiles on behalf of owners, respecting the
+/// [`PlacementLimits`]
+///
+/// # Examples
+///
+/// ```
+/// # use bevy_ecs_tilemap::TilePos;
+/// # use bevy_tileset_map::prelude::{PlacementLimitError, PlacementLimiter, TileId, TileOwner};
+/// fn build(mut limiter: PlacementLimiter) {
+///   let player = TileOwner(1);
+///   match limiter.place(TileId::new(0, 0), TilePos(3, 4), 0u16, 0u16, player) {
+///     Ok(_) => {},
+///     Err(PlacementLimitError::InsufficientBalance { .. }) => println!("Not enough gold!"),
+///     Err(err) => println!("Could not build: {}", err),
+///   }
+/// }
+/// ```
+#[derive(SystemParam)]
+pub struct PlacementLimiter<'w, 's> {
+	placer: TilePlacer<'w, 's>,
+	limits: ResMut<'w, PlacementLimits>,
+	tilesets: Tilesets<'w, 's>,
+	metadata: Res<'w, TileMetadataRegistry>,
+	time: Res<'w, Time>,
+}
+
+impl PlacementLimits {
+	/// Set the cost of the given tile, overriding its metadata
+	pub fn set_cost<Id: Into<TileId>>(&mut self, tile_id: Id, cost: u64) -> Option<u64> {
+		self.costs.insert(tile_id.into(), cost)
+	}
+
+	/// Remove the cost of the given tile from the cost table
+	pub fn remove_cost<Id: Into<TileId>>(&mut self, tile_id: Id) -> Option<u64> {
+		self.costs.remove(&tile_id.into())
+	}
+
+	/// The balance of the given owner
+	pub fn balance(&self, owner: TileOwner) -> u64 {
+		self.balances.get(&owner).copied().unwrap_or_default()
+	}
+
+	/// Set the balance of the given owner
+	pub fn set_balance(&mut self, owner: TileOwner, balance: u64) {
+		self.balances.insert(owner, balance);
+	}
+
+	/// Add the given amount to the balance of the given owner
+	pub fn deposit(&mut self, owner: TileOwner, amount: u64) {
+		let balance = self.balances.entry(owner).or_default();
+		*balance = balance.saturating_add(amount);
+	}
+
+	/// Forget the balance and placement history of the given owner
+	pub fn remove_owner(&mut self, owner: TileOwner) {
+		self.balances.remove(&owner);
+		self.history.remove(&owner);
+	}
+
+	/// Make sure the given owner may place another tile at the given time (in seconds)
+	fn check_rate(&mut self, owner: TileOwner, now: f64) -> Result<(), PlacementLimitError> {
+		let limit = if let Some(limit) = self.rate_limit {
+			limit
+		} else {
+			return Ok(());
+		};
+
+		let window = limit.per.as_secs_f64();
+		let history = self.history.entry(owner).or_default();
+		while matches!(history.front(), Some(time) if now - time >= window) {
+			history.pop_front();
+		}
+
+		match history.front() {
+			Some(oldest) if history.len() >= limit.placements => {
+				Err(PlacementLimitError::RateLimited {
+					owner,
+					retry_in: Duration::from_secs_f64((window - (now - oldest)).max(0.0)),
+				})
+			},
+			None if limit.placements == 0 => Err(PlacementLimitError::RateLimited {
+				owner,
+				retry_in: limit.per,
+			}),
+			_ => Ok(()),
+		}
+	}
+}
+
+impl<'w, 's> PlacementLimiter<'w, 's> {
+	/// Place a tile owned by the given owner, if allowed by the limits
+	///
+	/// This uses [`TilePlacer::place_owned`]. The owner is only charged (and the placement only
+	/// counts towards the rate limit) if the tile was placed successfully.
+	///
+	/// # Arguments
+	///
+	/// * `tile_id`: The full ID of the tile to place
+	/// * `pos`: The tile position
+	/// * `map_id`: The tile map
+	/// * `layer_id`: The layer within the tile map
+	/// * `owner`: The owner placing the tile
+	///
+	pub fn place<Id: Into<TileId>, Pos: Into<TilePos>, MId: MapId>(
+		&mut self,
+		tile_id: Id,
+		pos: Pos,
+		map_id: MId,
+		layer_id: u16,
+		owner: TileOwner,
+	) -> Result<PlacedTile, PlacementLimitError> {
+		let tile_id = tile_id.into();
+		let cost = self.check(tile_id, owner)?;
+		let placed = self
+			.placer
+			.place_owned(tile_id, pos, map_id, layer_id, owner)?;
+		self.charge(owner, cost);
+		Ok(placed)
+	}
+
+	/// Place a tile owned by the given owner only if the coordinate is not already occupied, the
+	/// placement is allowed by all rules, and it is allowed by the limits
+	///
+	/// This uses [`TilePlacer::try_place_owned`]. See [`place`](Self::place) for details.
+	pub fn try_place<Id: Into<TileId>, Pos: Into<TilePos>, MId: MapId>(
+		&mut self,
+		tile_id: Id,
+		pos: Pos,
+		map_id: MId,
+		layer_id: u16,
+		owner: TileOwner,
+	) -> Result<PlacedTile, PlacementLimitError> {
+		let tile_id = tile_id.into();
+		let cost = self.check(tile_id, owner)?;
+		let placed = self
+			.placer
+			.try_place_owned(tile_id, pos, map_id, layer_id, owner)?;
+		self.charge(owner, cost);
+		Ok(placed)
+	}
+
+	/// The cost of placing the given tile
+	pub fn cost_of<Id: Into<TileId>>(&self, tile_id: Id) -> u64 {
+		let tile_id = tile_id.into();
+		if let Some(cost) = self.limits.costs.get(&tile_id) {
+			return *cost;
+		}
+
+		self.tilesets
+			.get_by_id(&tile_id.tileset_id)
+			.and_then(|tileset| self.metadata.get_by_id(tileset, &tile_id))
+			.and_then(|metadata| metadata.get_int(COST_METADATA_KEY))
+			.map(|cost| cost.max(0) as u64)
+			.unwrap_or(self.limits.default_cost)
+	}
+
+	/// The limits enforced by this limiter
+	pub fn limits(&mut self) -> &mut PlacementLimits {
+		&mut self.limits
+	}
+
+	/// The underlying placer, for edits that shouldn't be limited
+	pub fn placer(&mut self) -> &mut TilePlacer<'w, 's> {
+		&mut self.placer
+	}
+
+	/// Make sure the given owner may place the given tile, returning its cost
+	fn check(&mut self, tile_id: TileId, owner: TileOwner) -> Result<u64, PlacementLimitError> {
+		let now = self.time.seconds_since_startup();
+		self.limits.check_rate(owner, now)?;
+
+		let cost = self.cost_of(tile_id);
+		let balance = self.limits.balance(owner);
+		if cost > balance {
+			return Err(PlacementLimitError::InsufficientBalance {
+				owner,
+				tile_id,
+				cost,
+				balance,
+			});
+		}
+		Ok(cost)
+	}
+
+	/// Charge the given owner for a successful placement
+	fn charge(&mut self, owner: TileOwner, cost: u64) {
+		let now = self.time.seconds_since_startup();
+		if self.limits.rate_limit.is_some() {
+			self.limits.history.entry(owner).or_default().push_back(now);
+		}
+		if cost > 0 {
+			let balance = self.limits.balances.entry(owner).or_default();
+			*balance = balance.saturating_sub(cost);
+		}
+	}
+}
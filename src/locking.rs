@@ -0,0 +1,511 @@
+//! Locking maps for exclusive (e.g. scripted) edits
+//!
+//! While a map is locked in the [`MapLocks`] resource, all [`TileEditRequest`] events targeting it
+//! are queued rather than applied. Once the lock is released, the queued edits are either applied
+//! or rejected. Every request is answered with [`TileEditEvent`]s so the requester knows what
+//! happened to it.
+//!
+//! Lock holders (such as a cutscene timeline) should keep editing the map directly using the
+//! [`TilePlacer`], which is never blocked.
+//!
+//! When several requests sent during the same frame target the same [`TileCoord`], only one of
+//! them is applied. The winner is chosen according to the [`ConflictResolution`] resource, using
+//! the tags, priorities, and contents of the requests rather than the (nondeterministic) order in
+//! which the requesting systems ran. The dropped requests are listed in a [`TileEditConflict`]
+//! event. Edits made directly through the [`TilePlacer`] are never part of a conflict.
+
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+use bevy_ecs_tilemap::TilePos;
+use bevy_tileset::prelude::{TileGroupId, TileId, TilesetId};
+
+use crate::coord::TileCoord;
+use crate::placement::{TilePlacementError, TilePlacer};
+
+/// A single tile edit
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum TileEdit {
+	/// Place a tile using [`TilePlacer::place`]
+	Place {
+		tile_id: TileId,
+		pos: TilePos,
+		map_id: u16,
+		layer_id: u16,
+	},
+	/// Remove a tile using [`TilePlacer::remove`]
+	Remove {
+		pos: TilePos,
+		map_id: u16,
+		layer_id: u16,
+	},
+	/// Toggle a tile using [`TilePlacer::toggle`]
+	Toggle {
+		tile_id: TileId,
+		pos: TilePos,
+		map_id: u16,
+		layer_id: u16,
+	},
+}
+
+/// An event used to request a tile edit that respects [`MapLocks`]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TileEditRequest {
+	/// An arbitrary value used to identify this request in the resulting [`TileEditEvent`]s
+	///
+	/// This is also used to order conflicting requests (see [`ConflictResolution`]), so each
+	/// requester should use its own tags.
+	pub tag: u64,
+	/// The requested edit
+	pub edit: TileEdit,
+	/// The priority of this request when using [`ConflictResolution::Priority`]
+	pub priority: i32,
+}
+
+/// A resource determining which request is applied when several requests sent during the same
+/// frame target the same [`TileCoord`]
+///
+/// Conflicting requests are ordered by their tag, so the outcome never depends on the order in
+/// which the requesting systems ran. Requests sharing a tag are further ordered by their edit:
+/// removals come first, followed by placements and toggles ordered by their [`TileId`]. Only
+/// requests that are identical in every respect are left in the order they were sent in, which
+/// doesn't affect the outcome.
+///
+/// Only [`TileEditRequest`]s are resolved. Edits made directly through the [`TilePlacer`] (such
+/// as by lock holders) are applied immediately, in the order the editing systems run, and never
+/// conflict with requests.
+///
+/// This resource is added automatically by the [`TilesetMapPlugin`](crate::prelude::TilesetMapPlugin).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum ConflictResolution {
+	/// Apply the request with the highest tag
+	#[default]
+	HighestTag,
+	/// Apply the request with the lowest tag
+	LowestTag,
+	/// Apply the request with the highest priority, falling back to
+	/// [`HighestTag`](Self::HighestTag) for requests of equal priority
+	Priority,
+}
+
+/// An event listing the requests dropped in favor of another request targeting the same
+/// [`TileCoord`]
+///
+/// The dropped requests are also answered with a [`TileEditStatus::Dropped`] event.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TileEditConflict {
+	/// The contested coordinate
+	pub coord: TileCoord,
+	/// The request that was applied (or queued)
+	pub kept: TileEditRequest,
+	/// The requests that were dropped
+	pub dropped: Vec<TileEditRequest>,
+}
+
+/// An event notifying requesters of what happened to their [`TileEditRequest`]
+#[derive(Debug)]
+pub struct TileEditEvent {
+	/// The tag of the request
+	pub tag: u64,
+	/// The requested edit
+	pub edit: TileEdit,
+	/// What happened to the request
+	pub status: TileEditStatus,
+}
+
+/// The status of a [`TileEditRequest`]
+#[derive(Debug)]
+pub enum TileEditStatus {
+	/// The map is locked and the edit was queued
+	Queued,
+	/// The edit was applied
+	Applied,
+	/// The edit was applied but failed
+	Failed(TilePlacementError),
+	/// The edit was rejected when the lock was released
+	Rejected,
+	/// The edit was dropped in favor of another edit targeting the same coordinate
+	Dropped,
+}
+
+/// What should happen to the queued edits of a map once its lock is released
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LockRelease {
+	/// Apply all queued edits in the order they were requested
+	Apply,
+	/// Discard all queued edits
+	Reject,
+}
+
+/// A resource tracking which maps are locked, along with the edits queued for them
+///
+/// This resource is added automatically by the [`TilesetMapPlugin`](crate::prelude::TilesetMapPlugin).
+#[derive(Debug, Default)]
+pub struct MapLocks {
+	locked: HashSet<u16>,
+	queued: Vec<TileEditRequest>,
+	released: Vec<(u16, LockRelease)>,
+}
+
+impl TileEdit {
+	/// The map this edit targets
+	pub fn map_id(&self) -> u16 {
+		self.coord().map_id
+	}
+
+	/// The coordinate this edit targets
+	pub fn coord(&self) -> TileCoord {
+		match *self {
+			Self::Place {
+				pos,
+				map_id,
+				layer_id,
+				..
+			}
+			| Self::Remove {
+				pos,
+				map_id,
+				layer_id,
+			}
+			| Self::Toggle {
+				pos,
+				map_id,
+				layer_id,
+				..
+			} => TileCoord::new(pos, map_id, layer_id),
+		}
+	}
+
+	/// A key ordering edits of the same coordinate by their contents
+	///
+	/// Removals come first, followed by placements and toggles ordered by their tile.
+	fn sort_key(&self) -> (u8, Option<TileSortKey>) {
+		match self {
+			Self::Remove { .. } => (0, None),
+			Self::Place { tile_id, .. } => (1, Some(TileSortKey::of(tile_id))),
+			Self::Toggle { tile_id, .. } => (2, Some(TileSortKey::of(tile_id))),
+		}
+	}
+
+	/// Apply this edit using the given placer
+	pub fn apply(&self, placer: &mut TilePlacer) -> Result<(), TilePlacementError> {
+		match *self {
+			Self::Place {
+				tile_id,
+				pos,
+				map_id,
+				layer_id,
+			} => placer.place(tile_id, pos, map_id, layer_id).map(|_| ()),
+			Self::Remove {
+				pos,
+				map_id,
+				layer_id,
+			} => placer.remove(pos, map_id, layer_id),
+			Self::Toggle {
+				tile_id,
+				pos,
+				map_id,
+				layer_id,
+			} => placer.toggle(tile_id, pos, map_id, layer_id).map(|_| ()),
+		}
+	}
+}
+
+/// The fields of a [`TileId`] in the order used to sort [`TileEdit`]s
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct TileSortKey {
+	tileset_id: TilesetId,
+	group_id: TileGroupId,
+	variant_index: Option<usize>,
+	auto_index: Option<usize>,
+}
+
+impl TileSortKey {
+	fn of(tile_id: &TileId) -> Self {
+		Self {
+			tileset_id: tile_id.tileset_id,
+			group_id: tile_id.group_id,
+			#[cfg(feature = "variants")]
+			variant_index: tile_id.variant_index,
+			#[cfg(not(feature = "variants"))]
+			variant_index: None,
+			#[cfg(feature = "auto-tile")]
+			auto_index: tile_id.auto_index,
+			#[cfg(not(feature = "auto-tile"))]
+			auto_index: None,
+		}
+	}
+}
+
+impl TileEditRequest {
+	/// Create a request with the default priority
+	pub fn new(tag: u64, edit: TileEdit) -> Self {
+		Self {
+			tag,
+			edit,
+			priority: 0,
+		}
+	}
+
+	/// Set the priority of this request
+	pub fn with_priority(mut self, priority: i32) -> Self {
+		self.priority = priority;
+		self
+	}
+}
+
+impl ConflictResolution {
+	/// Split the given requests into those to apply and the conflicts between the rest
+	///
+	/// The kept requests and the conflicts are both returned in a deterministic order.
+	fn resolve(
+		&self,
+		requests: Vec<TileEditRequest>,
+	) -> (Vec<TileEditRequest>, Vec<TileEditConflict>) {
+		let mut contested = HashMap::<TileCoord, Vec<TileEditRequest>>::default();
+		for request in requests {
+			contested
+				.entry(request.edit.coord())
+				.or_default()
+				.push(request);
+		}
+
+		let mut kept = Vec::with_capacity(contested.len());
+		let mut conflicts = Vec::new();
+		for (coord, mut requests) in contested {
+			requests.sort_by_key(|request| (request.tag, request.edit.sort_key()));
+			let index = match self {
+				Self::HighestTag => requests.len() - 1,
+				Self::LowestTag => 0,
+				Self::Priority => requests
+					.iter()
+					.enumerate()
+					// Returns the last of several maxima, matching `HighestTag`
+					.max_by_key(|(_, request)| request.priority)
+					.map(|(index, _)| index)
+					.unwrap_or_default(),
+			};
+
+			let winner = requests.remove(index);
+			kept.push(winner);
+			if !requests.is_empty() {
+				conflicts.push(TileEditConflict {
+					coord,
+					kept: winner,
+					dropped: requests,
+				});
+			}
+		}
+
+		kept.sort_by_key(|request| (request.tag, coord_key(&request.edit.coord())));
+		conflicts.sort_by_key(|conflict| coord_key(&conflict.coord));
+		(kept, conflicts)
+	}
+}
+
+impl MapLocks {
+	/// Lock the given map, queuing all requested edits until it is unlocked
+	pub fn lock(&mut self, map_id: u16) {
+		self.locked.insert(map_id);
+	}
+
+	/// Unlock the given map, handling its queued edits as specified
+	///
+	/// The queued edits are handled the next time the lock system runs.
+	pub fn unlock(&mut self, map_id: u16, release: LockRelease) {
+		if self.locked.remove(&map_id) {
+			self.released.push((map_id, release));
+		}
+	}
+
+	/// Returns true if the given map is locked
+	pub fn is_locked(&self, map_id: u16) -> bool {
+		self.locked.contains(&map_id)
+	}
+
+	/// Iterate over all edits currently queued for the given map
+	pub fn queued(&self, map_id: u16) -> impl Iterator<Item = &TileEditRequest> {
+		self.queued
+			.iter()
+			.filter(move |request| request.edit.map_id() == map_id)
+	}
+}
+
+/// __\[SYSTEM\]__ Applies or queues all [`TileEditRequest`]s and handles released locks
+pub(crate) fn apply_tile_edit_requests(
+	mut locks: ResMut<MapLocks>,
+	resolution: Res<ConflictResolution>,
+	mut requests: EventReader<TileEditRequest>,
+	mut events: EventWriter<TileEditEvent>,
+	mut conflicts: EventWriter<TileEditConflict>,
+	mut placer: TilePlacer,
+) {
+	// === Handle Released Locks === //
+	let released = std::mem::take(&mut locks.released);
+	for (map_id, release) in released {
+		if locks.is_locked(map_id) {
+			// The map was locked again before its queued edits could be handled
+			continue;
+		}
+
+		let (edits, remaining) = std::mem::take(&mut locks.queued)
+			.into_iter()
+			.partition::<Vec<_>, _>(|request| request.edit.map_id() == map_id);
+		locks.queued = remaining;
+
+		for request in edits {
+			let status = match release {
+				LockRelease::Apply => to_status(request.edit.apply(&mut placer)),
+				LockRelease::Reject => TileEditStatus::Rejected,
+			};
+			events.send(TileEditEvent {
+				tag: request.tag,
+				edit: request.edit,
+				status,
+			});
+		}
+	}
+
+	// === Resolve Conflicts === //
+	let (requests, conflicted) = resolution.resolve(requests.iter().copied().collect());
+	for conflict in conflicted {
+		for request in &conflict.dropped {
+			events.send(TileEditEvent {
+				tag: request.tag,
+				edit: request.edit,
+				status: TileEditStatus::Dropped,
+			});
+		}
+		conflicts.send(conflict);
+	}
+
+	// === Handle New Requests === //
+	for request in requests {
+		let status = if locks.is_locked(request.edit.map_id()) {
+			locks.queued.push(request);
+			TileEditStatus::Queued
+		} else {
+			to_status(request.edit.apply(&mut placer))
+		};
+		events.send(TileEditEvent {
+			tag: request.tag,
+			edit: request.edit,
+			status,
+		});
+	}
+}
+
+/// A key ordering coordinates by map, layer, then position
+fn coord_key(coord: &TileCoord) -> (u16, u16, u32, u32) {
+	(coord.map_id, coord.layer_id, coord.pos.0, coord.pos.1)
+}
+
+fn to_status(result: Result<(), TilePlacementError>) -> TileEditStatus {
+	match result {
+		Ok(..) => TileEditStatus::Applied,
+		Err(err) => TileEditStatus::Failed(err),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn place(tag: u64, group_id: TileGroupId, pos: TilePos) -> TileEditRequest {
+		TileEditRequest::new(
+			tag,
+			TileEdit::Place {
+				tile_id: TileId::new(group_id, 0),
+				pos,
+				map_id: 0,
+				layer_id: 0,
+			},
+		)
+	}
+
+	fn remove(tag: u64, pos: TilePos) -> TileEditRequest {
+		TileEditRequest::new(
+			tag,
+			TileEdit::Remove {
+				pos,
+				map_id: 0,
+				layer_id: 0,
+			},
+		)
+	}
+
+	/// Every ordering of the given requests
+	fn permutations(requests: &[TileEditRequest]) -> Vec<Vec<TileEditRequest>> {
+		if requests.len() <= 1 {
+			return vec![requests.to_vec()];
+		}
+		(0..requests.len())
+			.flat_map(|index| {
+				let mut rest = requests.to_vec();
+				let first = rest.remove(index);
+				permutations(&rest).into_iter().map(move |mut order| {
+					order.insert(0, first);
+					order
+				})
+			})
+			.collect()
+	}
+
+	#[test]
+	fn ties_are_broken_by_edit_contents() {
+		let pos = TilePos(1, 1);
+		let requests = [place(7, 2, pos), remove(7, pos), place(7, 1, pos)];
+		for order in permutations(&requests) {
+			let (kept, conflicts) = ConflictResolution::HighestTag.resolve(order.clone());
+			assert_eq!(kept, vec![place(7, 2, pos)]);
+			assert_eq!(conflicts[0].dropped, vec![remove(7, pos), place(7, 1, pos)]);
+
+			let (kept, _) = ConflictResolution::LowestTag.resolve(order);
+			assert_eq!(kept, vec![remove(7, pos)]);
+		}
+	}
+
+	#[test]
+	fn equal_priorities_fall_back_to_the_highest_tag() {
+		let pos = TilePos(1, 1);
+		let requests = [
+			place(1, 0, pos).with_priority(5),
+			place(3, 0, pos),
+			place(2, 1, pos).with_priority(5),
+		];
+		for order in permutations(&requests) {
+			let (kept, _) = ConflictResolution::Priority.resolve(order);
+			assert_eq!(kept, vec![place(2, 1, pos).with_priority(5)]);
+		}
+	}
+
+	#[test]
+	fn results_do_not_depend_on_the_request_order() {
+		let requests = [
+			place(2, 0, TilePos(0, 0)),
+			place(1, 0, TilePos(0, 0)),
+			place(1, 0, TilePos(3, 0)),
+			remove(1, TilePos(3, 0)),
+			place(1, 0, TilePos(2, 0)),
+		];
+		let expected = ConflictResolution::HighestTag.resolve(requests.to_vec());
+		assert_eq!(
+			expected.0,
+			vec![
+				place(1, 0, TilePos(2, 0)),
+				place(1, 0, TilePos(3, 0)),
+				place(2, 0, TilePos(0, 0)),
+			]
+		);
+		assert_eq!(
+			expected
+				.1
+				.iter()
+				.map(|conflict| conflict.coord.pos)
+				.collect::<Vec<_>>(),
+			vec![TilePos(0, 0), TilePos(3, 0)]
+		);
+		for order in permutations(&requests) {
+			assert_eq!(ConflictResolution::HighestTag.resolve(order), expected);
+		}
+	}
+}
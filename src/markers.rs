@@ -0,0 +1,145 @@
+//! Logic markers authored as tiles
+//!
+//! Tiles with the [`MARKER_METADATA_KEY`] key in their [`TileMetadata`] (e.g.
+//! `"marker": "PlayerStart"`) mark a location rather than display something. Whenever such a tile
+//! is placed, it is hidden and a [`MarkerPlaced`] event is sent, and any spawners registered for
+//! the marker in the [`TileMarkers`] resource are run.
+//!
+//! Since markers are regular tiles, they are serialized along with the rest of the tilemap and
+//! fire again when it is loaded.
+
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_ecs_tilemap::{Tile, TileParent, TilePos};
+
+use crate::coord::TileCoord;
+use crate::elevation::ElevatedVisibility;
+use crate::metadata::TileMetadata;
+
+/// The metadata key used to turn a tile into a marker with the given name
+pub const MARKER_METADATA_KEY: &str = "marker";
+
+/// The signature of a marker spawner
+pub type MarkerSpawnerFn = dyn Fn(&mut Commands, &MarkerPlaced) + Send + Sync + 'static;
+
+/// An event sent whenever a marker tile is placed
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarkerPlaced {
+	/// The name of the marker
+	pub marker: String,
+	/// The coordinate of the marker tile
+	pub coord: TileCoord,
+	/// The marker tile entity
+	pub entity: Entity,
+}
+
+/// A resource configuring how marker tiles are handled
+///
+/// This resource is added automatically by the [`TilesetMapPlugin`](crate::prelude::TilesetMapPlugin).
+///
+/// # Examples
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_tileset_map::prelude::TileMarkers;
+/// #[derive(Component)]
+/// struct Player;
+///
+/// fn setup(mut markers: ResMut<TileMarkers>) {
+///   markers.on("PlayerStart", |commands, placed| {
+///     commands.spawn().insert(Player);
+///     println!("Player spawned at {:?}", placed.coord);
+///   });
+/// }
+/// ```
+pub struct TileMarkers {
+	/// Whether marker tiles are hidden when placed
+	///
+	/// Editors may want to disable this so markers can be seen (and removed) while authoring.
+	pub hide: bool,
+	spawners: HashMap<String, Vec<Box<MarkerSpawnerFn>>>,
+}
+
+impl Default for TileMarkers {
+	fn default() -> Self {
+		Self {
+			hide: true,
+			spawners: HashMap::default(),
+		}
+	}
+}
+
+impl TileMarkers {
+	/// Register a spawner that runs whenever a marker with the given name is placed
+	pub fn on<S, F>(&mut self, marker: S, spawner: F) -> &mut Self
+	where
+		S: Into<String>,
+		F: Fn(&mut Commands, &MarkerPlaced) + Send + Sync + 'static,
+	{
+		self.spawners
+			.entry(marker.into())
+			.or_insert_with(Vec::new)
+			.push(Box::new(spawner));
+		self
+	}
+
+	/// Remove all spawners registered for the given marker
+	pub fn clear(&mut self, marker: &str) {
+		self.spawners.remove(marker);
+	}
+
+	/// Returns true if a spawner is registered for the given marker
+	pub fn contains(&self, marker: &str) -> bool {
+		self.spawners.contains_key(marker)
+	}
+
+	/// Run all spawners of the given marker
+	fn run(&self, commands: &mut Commands, placed: &MarkerPlaced) {
+		for spawner in self.spawners.get(&placed.marker).into_iter().flatten() {
+			spawner(commands, placed);
+		}
+	}
+}
+
+/// __\[SYSTEM\]__ Hides newly placed marker tiles and sends their [`MarkerPlaced`] events
+pub(crate) fn handle_placed_markers(
+	mut commands: Commands,
+	markers: Res<TileMarkers>,
+	mut events: EventWriter<MarkerPlaced>,
+	mut tiles: Query<
+		(
+			Entity,
+			&mut Tile,
+			&TilePos,
+			&TileParent,
+			&TileMetadata,
+			Option<&mut ElevatedVisibility>,
+		),
+		Added<TileMetadata>,
+	>,
+) {
+	for (entity, mut tile, pos, parent, metadata, elevated) in tiles.iter_mut() {
+		let marker = if let Some(marker) = metadata.get_str(MARKER_METADATA_KEY) {
+			marker
+		} else {
+			continue;
+		};
+
+		// Tiles are placed before the chunks they were placed in get remeshed, so there's no need
+		// to notify the chunk again
+		if markers.hide && tile.visible {
+			tile.visible = false;
+		}
+		if let Some(mut elevated) = elevated.filter(|elevated| markers.hide && elevated.visible) {
+			elevated.visible = false;
+		}
+
+		let placed = MarkerPlaced {
+			marker: marker.to_string(),
+			coord: TileCoord::new(*pos, parent.map_id, parent.layer_id),
+			entity,
+		};
+		markers.run(&mut commands, &placed);
+		events.send(placed);
+	}
+}
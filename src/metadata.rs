@@ -0,0 +1,269 @@
+//! Arbitrary key-value metadata attached to tiles
+//!
+//! Metadata is registered per tile (by tileset name and tile name) in the [`TileMetadataRegistry`]
+//! resource. Whenever the [`TilePlacer`](crate::prelude::TilePlacer) places a tile with registered
+//! metadata, a copy of it is inserted onto the tile entity as a [`TileMetadata`] component.
+
+use bevy::prelude::Component;
+use bevy::utils::HashMap;
+use bevy_tileset::prelude::{TileId, Tileset};
+
+/// A single metadata value
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+	feature = "serialization",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(untagged)
+)]
+pub enum MetadataValue {
+	Bool(bool),
+	Int(i64),
+	Float(f64),
+	String(String),
+}
+
+/// A collection of key-value metadata for a single tile
+///
+/// # Examples
+///
+/// ```
+/// # use bevy_tileset_map::prelude::TileMetadata;
+/// let metadata = TileMetadata::new()
+///   .with("walkable", false)
+///   .with("damage", 5);
+///
+/// assert_eq!(Some(false), metadata.get_bool("walkable"));
+/// assert_eq!(Some(5), metadata.get_int("damage"));
+/// ```
+#[derive(Component, Debug, Clone, Default, PartialEq)]
+#[cfg_attr(
+	feature = "serialization",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(transparent)
+)]
+pub struct TileMetadata {
+	values: HashMap<String, MetadataValue>,
+}
+
+/// A resource containing the [`TileMetadata`] for each tile, keyed by tileset name and tile name
+///
+/// This resource is added automatically by the [`TilesetMapPlugin`](crate::prelude::TilesetMapPlugin).
+/// With the `serialization` feature enabled, it can also be deserialized from a file that lives
+/// alongside the tileset definition:
+///
+/// ```ron
+/// {
+///   "My Tileset": {
+///     "Wall": { "walkable": false },
+///     "Lava": { "walkable": true, "damage": 5 },
+///   }
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(
+	feature = "serialization",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(transparent)
+)]
+pub struct TileMetadataRegistry {
+	tilesets: HashMap<String, HashMap<String, TileMetadata>>,
+}
+
+impl MetadataValue {
+	/// Get this value as a `bool`
+	pub fn as_bool(&self) -> Option<bool> {
+		match self {
+			Self::Bool(value) => Some(*value),
+			_ => None,
+		}
+	}
+
+	/// Get this value as an `i64`
+	pub fn as_int(&self) -> Option<i64> {
+		match self {
+			Self::Int(value) => Some(*value),
+			_ => None,
+		}
+	}
+
+	/// Get this value as an `f64`
+	///
+	/// Integers are converted to floats.
+	pub fn as_float(&self) -> Option<f64> {
+		match self {
+			Self::Float(value) => Some(*value),
+			Self::Int(value) => Some(*value as f64),
+			_ => None,
+		}
+	}
+
+	/// Get this value as a string slice
+	pub fn as_str(&self) -> Option<&str> {
+		match self {
+			Self::String(value) => Some(value.as_str()),
+			_ => None,
+		}
+	}
+}
+
+impl From<bool> for MetadataValue {
+	fn from(value: bool) -> Self {
+		Self::Bool(value)
+	}
+}
+
+impl From<i32> for MetadataValue {
+	fn from(value: i32) -> Self {
+		Self::Int(value as i64)
+	}
+}
+
+impl From<i64> for MetadataValue {
+	fn from(value: i64) -> Self {
+		Self::Int(value)
+	}
+}
+
+impl From<f32> for MetadataValue {
+	fn from(value: f32) -> Self {
+		Self::Float(value as f64)
+	}
+}
+
+impl From<f64> for MetadataValue {
+	fn from(value: f64) -> Self {
+		Self::Float(value)
+	}
+}
+
+impl From<&str> for MetadataValue {
+	fn from(value: &str) -> Self {
+		Self::String(value.to_string())
+	}
+}
+
+impl From<String> for MetadataValue {
+	fn from(value: String) -> Self {
+		Self::String(value)
+	}
+}
+
+impl TileMetadata {
+	/// Create empty metadata
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Add a value to this metadata (builder-style)
+	pub fn with<K: Into<String>, V: Into<MetadataValue>>(mut self, key: K, value: V) -> Self {
+		self.insert(key, value);
+		self
+	}
+
+	/// Insert a value, returning the value it replaced (if any)
+	pub fn insert<K: Into<String>, V: Into<MetadataValue>>(
+		&mut self,
+		key: K,
+		value: V,
+	) -> Option<MetadataValue> {
+		self.values.insert(key.into(), value.into())
+	}
+
+	/// Remove the value with the given key
+	pub fn remove(&mut self, key: &str) -> Option<MetadataValue> {
+		self.values.remove(key)
+	}
+
+	/// Get the value with the given key
+	pub fn get(&self, key: &str) -> Option<&MetadataValue> {
+		self.values.get(key)
+	}
+
+	/// Get the value with the given key as a `bool`
+	pub fn get_bool(&self, key: &str) -> Option<bool> {
+		self.get(key).and_then(MetadataValue::as_bool)
+	}
+
+	/// Get the value with the given key as an `i64`
+	pub fn get_int(&self, key: &str) -> Option<i64> {
+		self.get(key).and_then(MetadataValue::as_int)
+	}
+
+	/// Get the value with the given key as an `f64`
+	pub fn get_float(&self, key: &str) -> Option<f64> {
+		self.get(key).and_then(MetadataValue::as_float)
+	}
+
+	/// Get the value with the given key as a string slice
+	pub fn get_str(&self, key: &str) -> Option<&str> {
+		self.get(key).and_then(MetadataValue::as_str)
+	}
+
+	/// Returns true if a value exists for the given key
+	pub fn contains(&self, key: &str) -> bool {
+		self.values.contains_key(key)
+	}
+
+	/// Iterate over all key-value pairs
+	pub fn iter(&self) -> impl Iterator<Item = (&str, &MetadataValue)> {
+		self.values.iter().map(|(key, value)| (key.as_str(), value))
+	}
+
+	/// The number of values
+	pub fn len(&self) -> usize {
+		self.values.len()
+	}
+
+	/// Returns true if there are no values
+	pub fn is_empty(&self) -> bool {
+		self.values.is_empty()
+	}
+}
+
+impl TileMetadataRegistry {
+	/// Register metadata for the given tile, returning the metadata it replaced (if any)
+	///
+	/// # Arguments
+	///
+	/// * `tileset_name`: The name of the tileset containing the tile
+	/// * `tile_name`: The name of the tile
+	/// * `metadata`: The metadata to register
+	///
+	pub fn insert<S: Into<String>, T: Into<String>>(
+		&mut self,
+		tileset_name: S,
+		tile_name: T,
+		metadata: TileMetadata,
+	) -> Option<TileMetadata> {
+		self.tilesets
+			.entry(tileset_name.into())
+			.or_insert_with(HashMap::default)
+			.insert(tile_name.into(), metadata)
+	}
+
+	/// Remove the metadata for the given tile
+	pub fn remove(&mut self, tileset_name: &str, tile_name: &str) -> Option<TileMetadata> {
+		self.tilesets.get_mut(tileset_name)?.remove(tile_name)
+	}
+
+	/// Get the metadata for the given tile
+	pub fn get(&self, tileset_name: &str, tile_name: &str) -> Option<&TileMetadata> {
+		self.tilesets.get(tileset_name)?.get(tile_name)
+	}
+
+	/// Get the metadata for the tile with the given ID in the given tileset
+	pub fn get_by_id(&self, tileset: &Tileset, tile_id: &TileId) -> Option<&TileMetadata> {
+		let tile_name = tileset.get_tile_name(&tile_id.group_id)?;
+		self.get(tileset.name(), tile_name)
+	}
+
+	/// Merge all metadata from `other` into this registry, overwriting any existing entries
+	pub fn extend(&mut self, other: TileMetadataRegistry) {
+		for (tileset_name, tiles) in other.tilesets {
+			self.tilesets
+				.entry(tileset_name)
+				.or_insert_with(HashMap::default)
+				.extend(tiles);
+		}
+	}
+}
@@ -0,0 +1,315 @@
+//! Walkability grids and pathfinding
+//!
+//! Every tile placed by the [`TilePlacer`](crate::prelude::TilePlacer) contributes a cell to the
+//! [`NavGrid`] of its map and layer. A cell is walkable unless the tile's
+//! [`TileMetadata`] sets the [`NAV_METADATA_KEY`] key to `false`. Cells without a tile are
+//! never walkable.
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+use bevy_ecs_tilemap::{TileParent, TilePos};
+use bevy_tileset::prelude::TilesetParent;
+
+use crate::coord::TileCoord;
+use crate::metadata::TileMetadata;
+
+/// The metadata key used to define whether a tile is walkable
+pub const NAV_METADATA_KEY: &str = "walkable";
+
+/// A resource containing the walkability grid of every map layer
+///
+/// This resource is added and kept up to date automatically by the
+/// [`TilesetMapPlugin`](crate::prelude::TilesetMapPlugin).
+#[derive(Debug, Default)]
+pub struct NavGrid {
+	layers: HashMap<(u16, u16), NavLayer>,
+	coords: HashMap<Entity, TileCoord>,
+	owners: HashMap<TileCoord, Entity>,
+}
+
+/// The walkability grid of a single map layer
+#[derive(Debug, Default, Clone)]
+pub struct NavLayer {
+	walkable: HashSet<(u32, u32)>,
+}
+
+impl NavGrid {
+	/// Get the walkability grid of the given layer
+	pub fn layer(&self, map_id: u16, layer_id: u16) -> Option<&NavLayer> {
+		self.layers.get(&(map_id, layer_id))
+	}
+
+	/// Returns true if the given coordinate is walkable
+	pub fn is_walkable(&self, coord: &TileCoord) -> bool {
+		self.layer(coord.map_id, coord.layer_id)
+			.map(|layer| layer.is_walkable(coord.pos))
+			.unwrap_or_default()
+	}
+
+	/// Find the shortest path between two tiles of the given layer
+	///
+	/// See [`NavLayer::find_path`] for details.
+	pub fn find_path(
+		&self,
+		map_id: u16,
+		layer_id: u16,
+		from: TilePos,
+		to: TilePos,
+	) -> Option<Vec<TilePos>> {
+		self.layer(map_id, layer_id)?.find_path(from, to)
+	}
+
+	fn insert(&mut self, entity: Entity, coord: TileCoord, walkable: bool) {
+		if let Some(old_coord) = self.coords.insert(entity, coord) {
+			if old_coord != coord {
+				self.remove_at(entity, &old_coord);
+			}
+		}
+
+		self.owners.insert(coord, entity);
+		let layer = self
+			.layers
+			.entry((coord.map_id, coord.layer_id))
+			.or_default();
+		if walkable {
+			layer.walkable.insert((coord.pos.0, coord.pos.1));
+		} else {
+			layer.walkable.remove(&(coord.pos.0, coord.pos.1));
+		}
+	}
+
+	fn remove(&mut self, entity: Entity) {
+		if let Some(coord) = self.coords.remove(&entity) {
+			self.remove_at(entity, &coord);
+		}
+	}
+
+	/// Clears the cell at the given coordinate only if it still belongs to the given entity
+	fn remove_at(&mut self, entity: Entity, coord: &TileCoord) {
+		if self.owners.get(coord) != Some(&entity) {
+			return;
+		}
+
+		self.owners.remove(coord);
+		if let Some(layer) = self.layers.get_mut(&(coord.map_id, coord.layer_id)) {
+			layer.walkable.remove(&(coord.pos.0, coord.pos.1));
+		}
+	}
+}
+
+impl NavLayer {
+	/// Returns true if the given position is walkable
+	pub fn is_walkable(&self, pos: TilePos) -> bool {
+		self.walkable.contains(&(pos.0, pos.1))
+	}
+
+	/// The number of walkable cells in this layer
+	pub fn len(&self) -> usize {
+		self.walkable.len()
+	}
+
+	/// Returns true if this layer contains no walkable cells
+	pub fn is_empty(&self) -> bool {
+		self.walkable.is_empty()
+	}
+
+	/// Iterate over the walkable neighbors of the given position (north, east, south, west)
+	pub fn neighbors(&self, pos: TilePos) -> impl Iterator<Item = TilePos> + '_ {
+		let TilePos(x, y) = pos;
+		[
+			Some((x, y + 1)),
+			Some((x + 1, y)),
+			y.checked_sub(1).map(|y| (x, y)),
+			x.checked_sub(1).map(|x| (x, y)),
+		]
+		.into_iter()
+		.flatten()
+		.filter(|cell| self.walkable.contains(cell))
+		.map(|(x, y)| TilePos(x, y))
+	}
+
+	/// Find the shortest path between two tiles using A*
+	///
+	/// Movement is restricted to the four cardinal directions and every step costs the same.
+	///
+	/// Returns the path from `from` to `to` (both inclusive), or `None` if either tile isn't
+	/// walkable or no path exists.
+	pub fn find_path(&self, from: TilePos, to: TilePos) -> Option<Vec<TilePos>> {
+		if !self.is_walkable(from) || !self.is_walkable(to) {
+			return None;
+		}
+
+		let mut open = BinaryHeap::new();
+		let mut came_from: HashMap<(u32, u32), (u32, u32)> = HashMap::default();
+		let mut costs: HashMap<(u32, u32), u32> = HashMap::default();
+
+		costs.insert((from.0, from.1), 0);
+		open.push(OpenNode {
+			pos: from,
+			cost: 0,
+			estimate: manhattan(from, to),
+		});
+
+		while let Some(OpenNode { pos, cost, .. }) = open.pop() {
+			if pos == to {
+				return Some(reconstruct_path(&came_from, to));
+			}
+
+			if cost > costs[&(pos.0, pos.1)] {
+				// A cheaper route to this node has already been processed
+				continue;
+			}
+
+			for next in self.neighbors(pos) {
+				let next_cost = cost + 1;
+				let is_better = costs
+					.get(&(next.0, next.1))
+					.map(|&known| next_cost < known)
+					.unwrap_or(true);
+				if is_better {
+					costs.insert((next.0, next.1), next_cost);
+					came_from.insert((next.0, next.1), (pos.0, pos.1));
+					open.push(OpenNode {
+						pos: next,
+						cost: next_cost,
+						estimate: next_cost + manhattan(next, to),
+					});
+				}
+			}
+		}
+
+		None
+	}
+}
+
+/// A node in the A* open set, ordered so that the lowest estimate is popped first
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct OpenNode {
+	pos: TilePos,
+	cost: u32,
+	estimate: u32,
+}
+
+impl Ord for OpenNode {
+	fn cmp(&self, other: &Self) -> Ordering {
+		other
+			.estimate
+			.cmp(&self.estimate)
+			.then_with(|| self.cost.cmp(&other.cost))
+	}
+}
+
+impl PartialOrd for OpenNode {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+fn manhattan(a: TilePos, b: TilePos) -> u32 {
+	a.0.abs_diff(b.0) + a.1.abs_diff(b.1)
+}
+
+fn reconstruct_path(came_from: &HashMap<(u32, u32), (u32, u32)>, to: TilePos) -> Vec<TilePos> {
+	let mut path = vec![to];
+	let mut current = (to.0, to.1);
+	while let Some(&prev) = came_from.get(&current) {
+		path.push(TilePos(prev.0, prev.1));
+		current = prev;
+	}
+	path.reverse();
+	path
+}
+
+/// __\[SYSTEM\]__ Keeps the [`NavGrid`] in sync with all placed tiles
+pub(crate) fn update_nav_grid(
+	mut nav_grid: ResMut<NavGrid>,
+	changed: Query<
+		(Entity, &TilePos, &TileParent, Option<&TileMetadata>),
+		(
+			With<TilesetParent>,
+			Or<(Changed<TilesetParent>, Changed<TileMetadata>)>,
+		),
+	>,
+	removed: RemovedComponents<TilesetParent>,
+) {
+	for entity in removed.iter() {
+		nav_grid.remove(entity);
+	}
+
+	for (entity, pos, parent, metadata) in changed.iter() {
+		let walkable = metadata
+			.and_then(|metadata| metadata.get_bool(NAV_METADATA_KEY))
+			.unwrap_or(true);
+		let coord = TileCoord {
+			pos: *pos,
+			map_id: parent.map_id,
+			layer_id: parent.layer_id,
+		};
+		nav_grid.insert(entity, coord, walkable);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Create a layer from rows of cells (`.` is walkable, `#` is not), from the top row down
+	fn layer(rows: &[&str]) -> NavLayer {
+		let height = rows.len() as u32;
+		let walkable = rows
+			.iter()
+			.enumerate()
+			.flat_map(|(row, cells)| {
+				let y = height - 1 - row as u32;
+				cells
+					.chars()
+					.enumerate()
+					.filter(|(_, cell)| *cell == '.')
+					.map(move |(x, _)| (x as u32, y))
+			})
+			.collect();
+		NavLayer { walkable }
+	}
+
+	#[test]
+	fn path_goes_around_walls() {
+		let layer = layer(&[
+			"...", //
+			".#.", //
+			".#.", //
+		]);
+		let path = layer.find_path(TilePos(0, 0), TilePos(2, 0)).unwrap();
+		assert_eq!(path.first(), Some(&TilePos(0, 0)));
+		assert_eq!(path.last(), Some(&TilePos(2, 0)));
+		assert_eq!(path.len(), 7);
+		assert!(path.iter().all(|pos| layer.is_walkable(*pos)));
+		assert!(path.windows(2).all(|step| manhattan(step[0], step[1]) == 1));
+	}
+
+	#[test]
+	fn blocked_endpoints_have_no_path() {
+		let layer = layer(&[
+			"..#", //
+		]);
+		assert_eq!(layer.find_path(TilePos(0, 0), TilePos(2, 0)), None);
+		assert_eq!(layer.find_path(TilePos(2, 0), TilePos(0, 0)), None);
+		// Cells outside the layer are never walkable
+		assert_eq!(layer.find_path(TilePos(0, 0), TilePos(9, 9)), None);
+	}
+
+	#[test]
+	fn unreachable_goal_has_no_path() {
+		let layer = layer(&[
+			"..#..", //
+			"..#..", //
+		]);
+		assert_eq!(layer.find_path(TilePos(0, 0), TilePos(4, 1)), None);
+		assert_eq!(
+			layer.find_path(TilePos(0, 0), TilePos(0, 0)),
+			Some(vec![TilePos(0, 0)])
+		);
+	}
+}
@@ -0,0 +1,456 @@
+//! Regular entities anchored to tile coordinates
+//!
+//! Many "tiles" in games (chests, doors, spawn points) are really entities with their own
+//! components and sprites that just happen to sit on the tile grid. The [`ObjectPlacer`] spawns
+//! such objects snapped to the center of a tile and records them in the [`TileObjects`] index, so
+//! they can be looked up by coordinate just like tiles. Each layer's objects form an
+//! [`ObjectLayer`], which is independent from the tiles of that layer: an object and a tile can
+//! share the same coordinate.
+//!
+//! Objects are identified by a string kind. The components of each kind are inserted by the
+//! spawners registered in the [`TileObjectSpawners`] resource, which also allows objects to be
+//! spawned again when a tilemap is loaded.
+
+use bevy::ecs::system::{Command, EntityCommands, SystemParam};
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_ecs_tilemap::prelude::{MapId, TilePos};
+use thiserror::Error;
+
+use crate::coord::TileCoord;
+use crate::registry::TilesetMapRegistry;
+
+/// The signature of an object spawner
+pub type TileObjectSpawnerFn =
+	dyn Fn(&mut EntityCommands<'_, '_, '_>, &TileCoord) + Send + Sync + 'static;
+
+/// Errors related to the placement of objects
+#[derive(Error, Debug)]
+pub enum ObjectPlacementError {
+	/// An object already exists at the given coordinate
+	#[error("Object {existing:?} already exists at {coord:?}")]
+	Occupied {
+		/// The occupied coordinate
+		coord: TileCoord,
+		/// The existing object entity
+		existing: Entity,
+	},
+	/// No object exists at the given coordinate
+	#[error("No object exists at {0:?}")]
+	NotFound(TileCoord),
+	/// The layer does not exist (or has not been registered yet)
+	#[error("Layer {layer_id} does not exist in map {map_id}")]
+	InvalidLayer { map_id: u16, layer_id: u16 },
+}
+
+/// A component marking an entity placed using the [`ObjectPlacer`]
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+pub struct TileObject {
+	kind: String,
+	coord: TileCoord,
+}
+
+impl TileObject {
+	/// The kind of this object
+	pub fn kind(&self) -> &str {
+		&self.kind
+	}
+
+	/// The coordinate this object is anchored to
+	pub fn coord(&self) -> TileCoord {
+		self.coord
+	}
+}
+
+/// The objects placed within a single layer
+#[derive(Debug, Clone, Default)]
+pub struct ObjectLayer {
+	objects: HashMap<TilePos, (Entity, String)>,
+}
+
+impl ObjectLayer {
+	/// Get the object at the given position
+	pub fn get(&self, pos: TilePos) -> Option<Entity> {
+		self.objects.get(&pos).map(|(entity, _)| *entity)
+	}
+
+	/// Get the kind of the object at the given position
+	pub fn kind(&self, pos: TilePos) -> Option<&str> {
+		self.objects.get(&pos).map(|(_, kind)| kind.as_str())
+	}
+
+	/// Iterate over the position, entity, and kind of every object in this layer
+	pub fn iter(&self) -> impl Iterator<Item = (TilePos, Entity, &str)> {
+		self.objects
+			.iter()
+			.map(|(pos, (entity, kind))| (*pos, *entity, kind.as_str()))
+	}
+
+	/// The number of objects in this layer
+	pub fn len(&self) -> usize {
+		self.objects.len()
+	}
+
+	/// Returns true if this layer contains no objects
+	pub fn is_empty(&self) -> bool {
+		self.objects.is_empty()
+	}
+}
+
+/// A resource indexing every object by its coordinate
+///
+/// Unlike the [`PlacedTiles`](crate::prelude::PlacedTiles) index, this is updated right away by
+/// the [`ObjectPlacer`]. Objects despawned by other means are removed from the index at the end
+/// of the frame.
+///
+/// This resource is added automatically by the [`TilesetMapPlugin`](crate::prelude::TilesetMapPlugin).
+#[derive(Debug, Default)]
+pub struct TileObjects {
+	layers: HashMap<(u16, u16), ObjectLayer>,
+	coords: HashMap<Entity, TileCoord>,
+}
+
+impl TileObjects {
+	/// Get the object at the given coordinate
+	pub fn get(&self, coord: &TileCoord) -> Option<Entity> {
+		self.layer(coord.map_id, coord.layer_id)?.get(coord.pos)
+	}
+
+	/// Get the coordinate of the given object
+	pub fn get_coord(&self, entity: Entity) -> Option<TileCoord> {
+		self.coords.get(&entity).copied()
+	}
+
+	/// Get the objects of the given layer
+	pub fn layer(&self, map_id: u16, layer_id: u16) -> Option<&ObjectLayer> {
+		self.layers.get(&(map_id, layer_id))
+	}
+
+	/// Iterate over the coordinate, entity, and kind of every object
+	pub fn iter(&self) -> impl Iterator<Item = (TileCoord, Entity, &str)> {
+		self.layers.iter().flat_map(|((map_id, layer_id), layer)| {
+			layer
+				.iter()
+				.map(|(pos, entity, kind)| (TileCoord::new(pos, *map_id, *layer_id), entity, kind))
+		})
+	}
+
+	/// The number of objects
+	pub fn len(&self) -> usize {
+		self.coords.len()
+	}
+
+	/// Returns true if there are no objects
+	pub fn is_empty(&self) -> bool {
+		self.coords.is_empty()
+	}
+
+	fn insert(&mut self, coord: TileCoord, entity: Entity, kind: String) {
+		self.layers
+			.entry((coord.map_id, coord.layer_id))
+			.or_default()
+			.objects
+			.insert(coord.pos, (entity, kind));
+		self.coords.insert(entity, coord);
+	}
+
+	fn remove(&mut self, coord: &TileCoord) -> Option<(Entity, String)> {
+		let key = (coord.map_id, coord.layer_id);
+		let layer = self.layers.get_mut(&key)?;
+		let removed = layer.objects.remove(&coord.pos)?;
+		if layer.is_empty() {
+			self.layers.remove(&key);
+		}
+		self.coords.remove(&removed.0);
+		Some(removed)
+	}
+
+	/// Remove every object of the given layer from the index, returning their entities
+	pub(crate) fn take_layer(&mut self, map_id: u16, layer_id: u16) -> Vec<Entity> {
+		let layer = self.layers.remove(&(map_id, layer_id)).unwrap_or_default();
+		let entities = layer
+			.objects
+			.into_iter()
+			.map(|(_, (entity, _))| entity)
+			.collect::<Vec<_>>();
+		for entity in &entities {
+			self.coords.remove(entity);
+		}
+		entities
+	}
+
+	/// Remove every object of the given map from the index, returning their entities
+	pub(crate) fn take_map(&mut self, map_id: u16) -> Vec<Entity> {
+		let layer_ids = self
+			.layers
+			.keys()
+			.filter(|(map, _)| *map == map_id)
+			.map(|(_, layer_id)| *layer_id)
+			.collect::<Vec<_>>();
+		layer_ids
+			.into_iter()
+			.flat_map(|layer_id| self.take_layer(map_id, layer_id))
+			.collect()
+	}
+}
+
+/// A resource containing the spawners that insert the components of each object kind
+///
+/// This resource is added automatically by the [`TilesetMapPlugin`](crate::prelude::TilesetMapPlugin).
+///
+/// # Examples
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_tileset_map::prelude::TileObjectSpawners;
+/// #[derive(Component, Clone)]
+/// struct Chest;
+///
+/// fn setup(mut spawners: ResMut<TileObjectSpawners>) {
+///   spawners.insert_on("chest", (Chest,));
+///   spawners.on("spawn_point", |cmds, coord| {
+///     println!("Spawn point {:?} placed at {:?}", cmds.id(), coord);
+///   });
+/// }
+/// ```
+#[derive(Default)]
+pub struct TileObjectSpawners {
+	spawners: HashMap<String, Vec<Box<TileObjectSpawnerFn>>>,
+}
+
+impl TileObjectSpawners {
+	/// Register a spawner that runs for every object of the given kind
+	pub fn on<K, F>(&mut self, kind: K, spawner: F) -> &mut Self
+	where
+		K: Into<String>,
+		F: Fn(&mut EntityCommands<'_, '_, '_>, &TileCoord) + Send + Sync + 'static,
+	{
+		self.spawners
+			.entry(kind.into())
+			.or_insert_with(Vec::new)
+			.push(Box::new(spawner));
+		self
+	}
+
+	/// Insert a copy of the given bundle on every object of the given kind
+	pub fn insert_on<K: Into<String>, B: Bundle + Clone>(
+		&mut self,
+		kind: K,
+		bundle: B,
+	) -> &mut Self {
+		self.on(kind, move |cmds, _| {
+			cmds.insert_bundle(bundle.clone());
+		})
+	}
+
+	/// Remove all spawners registered for the given kind
+	pub fn clear(&mut self, kind: &str) {
+		self.spawners.remove(kind);
+	}
+
+	/// Returns true if a spawner is registered for the given kind
+	pub fn contains(&self, kind: &str) -> bool {
+		self.spawners.contains_key(kind)
+	}
+
+	/// Run all spawners of the given kind
+	fn run(&self, kind: &str, cmds: &mut EntityCommands, coord: &TileCoord) {
+		for spawner in self.spawners.get(kind).into_iter().flatten() {
+			spawner(cmds, coord);
+		}
+	}
+}
+
+/// A helper system param used to place and remove objects
+///
+/// Objects are spawned as children of their layer entity, centered on their tile. Their
+/// z-coordinate is left as set by their spawners (or zero).
+///
+/// # Examples
+///
+/// ```
+/// # use bevy_ecs_tilemap::TilePos;
+/// # use bevy_tileset_map::prelude::ObjectPlacer;
+/// fn place_chest(mut objects: ObjectPlacer) {
+///   objects.place("chest", TilePos(4, 2), 0u16, 1u16).unwrap();
+///
+///   // Removing returns the object so it can be placed again (e.g. to undo the removal)
+///   if let Some(removed) = objects.remove(TilePos(4, 2), 0u16, 1u16) {
+///     objects.place(removed.kind(), removed.coord().pos, 0u16, 1u16).unwrap();
+///   }
+/// }
+/// ```
+#[derive(SystemParam)]
+pub struct ObjectPlacer<'w, 's> {
+	commands: Commands<'w, 's>,
+	objects: ResMut<'w, TileObjects>,
+	spawners: Res<'w, TileObjectSpawners>,
+	registry: Res<'w, TilesetMapRegistry>,
+}
+
+impl<'w, 's> ObjectPlacer<'w, 's> {
+	/// Spawn an object of the given kind at the given coordinate
+	///
+	/// The layer must be registered in the [`TilesetMapRegistry`], which happens during the frame
+	/// after it was created.
+	///
+	/// # Arguments
+	///
+	/// * `kind`: The kind of object, selecting the [`TileObjectSpawners`] to run
+	/// * `pos`: The tile position
+	/// * `map_id`: The tile map
+	/// * `layer_id`: The layer within the tile map
+	///
+	pub fn place<K: Into<String>, Pos: Into<TilePos>, MId: MapId>(
+		&mut self,
+		kind: K,
+		pos: Pos,
+		map_id: MId,
+		layer_id: u16,
+	) -> Result<Entity, ObjectPlacementError> {
+		let map_id = map_id.into();
+		let coord = TileCoord::new(pos, map_id, layer_id);
+		if let Some(existing) = self.objects.get(&coord) {
+			return Err(ObjectPlacementError::Occupied { coord, existing });
+		}
+
+		let (layer_entity, grid_size) = self
+			.registry
+			.layer(map_id, layer_id)
+			.map(|info| (info.entity, info.settings.grid_size))
+			.ok_or(ObjectPlacementError::InvalidLayer { map_id, layer_id })?;
+
+		let kind = kind.into();
+		let mut cmds = self.commands.spawn();
+		cmds.insert(TileObject {
+			kind: kind.clone(),
+			coord,
+		});
+		self.spawners.run(&kind, &mut cmds, &coord);
+		let entity = cmds.id();
+
+		self.commands.add(SnapObject {
+			entity,
+			translation: coord.to_vec2(grid_size),
+		});
+		self.commands.entity(layer_entity).add_child(entity);
+		self.objects.insert(coord, entity, kind);
+		Ok(entity)
+	}
+
+	/// Despawn the object at the given coordinate
+	///
+	/// Returns the removed object, which can be used to place it again.
+	pub fn remove<Pos: Into<TilePos>, MId: MapId>(
+		&mut self,
+		pos: Pos,
+		map_id: MId,
+		layer_id: u16,
+	) -> Option<TileObject> {
+		let coord = TileCoord::new(pos, map_id.into(), layer_id);
+		let (entity, kind) = self.objects.remove(&coord)?;
+		self.commands.entity(entity).despawn_recursive();
+		Some(TileObject { kind, coord })
+	}
+
+	/// Move the object at the given position to another position in the same layer
+	///
+	/// The object keeps its entity and components.
+	pub fn move_object<Pos: Into<TilePos>, MId: MapId>(
+		&mut self,
+		from: Pos,
+		to: Pos,
+		map_id: MId,
+		layer_id: u16,
+	) -> Result<Entity, ObjectPlacementError> {
+		let map_id = map_id.into();
+		let from = TileCoord::new(from, map_id, layer_id);
+		let to = TileCoord::new(to, map_id, layer_id);
+		let entity = self
+			.objects
+			.get(&from)
+			.ok_or(ObjectPlacementError::NotFound(from))?;
+		if from == to {
+			return Ok(entity);
+		}
+		if let Some(existing) = self.objects.get(&to) {
+			return Err(ObjectPlacementError::Occupied {
+				coord: to,
+				existing,
+			});
+		}
+		let grid_size = self
+			.registry
+			.layer(map_id, layer_id)
+			.map(|info| info.settings.grid_size)
+			.ok_or(ObjectPlacementError::InvalidLayer { map_id, layer_id })?;
+
+		let (entity, kind) = self.objects.remove(&from).unwrap();
+		self.commands.entity(entity).insert(TileObject {
+			kind: kind.clone(),
+			coord: to,
+		});
+		self.commands.add(SnapObject {
+			entity,
+			translation: to.to_vec2(grid_size),
+		});
+		self.objects.insert(to, entity, kind);
+		Ok(entity)
+	}
+
+	/// Get the object at the given coordinate
+	pub fn get<Pos: Into<TilePos>, MId: MapId>(
+		&self,
+		pos: Pos,
+		map_id: MId,
+		layer_id: u16,
+	) -> Option<Entity> {
+		self.objects
+			.get(&TileCoord::new(pos, map_id.into(), layer_id))
+	}
+
+	/// Get the index of all objects
+	pub fn objects(&self) -> &TileObjects {
+		&self.objects
+	}
+}
+
+/// A command that moves an object to the center of its tile, keeping its z-coordinate
+struct SnapObject {
+	entity: Entity,
+	translation: Vec2,
+}
+
+impl Command for SnapObject {
+	fn write(self, world: &mut World) {
+		let mut entity = if let Some(entity) = world.get_entity_mut(self.entity) {
+			entity
+		} else {
+			return;
+		};
+
+		if let Some(mut transform) = entity.get_mut::<Transform>() {
+			transform.translation = self.translation.extend(transform.translation.z);
+		} else {
+			let transform = Transform::from_translation(self.translation.extend(0.0));
+			entity
+				.insert(transform)
+				.insert(GlobalTransform::from(transform));
+		}
+	}
+}
+
+/// __\[SYSTEM\]__ Removes despawned objects from the [`TileObjects`] index
+pub(crate) fn update_tile_objects(
+	mut objects: ResMut<TileObjects>,
+	removed: RemovedComponents<TileObject>,
+) {
+	for entity in removed.iter() {
+		if let Some(coord) = objects.get_coord(entity) {
+			// The index may already point to a newer object at the same coordinate
+			if objects.get(&coord) == Some(entity) {
+				objects.remove(&coord);
+			}
+		}
+	}
+}
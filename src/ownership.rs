@@ -0,0 +1,40 @@
+//! Per-tile ownership for multiplayer building games
+//!
+//! Tiles placed using [`TilePlacer::place_owned`](crate::prelude::TilePlacer::place_owned) are
+//! given a [`TileOwner`], such as the ID of the player or team that built them. While a
+//! [`TilePlacer`](crate::prelude::TilePlacer) [acts as](crate::prelude::TilePlacer::act_as) an
+//! owner (as done by its ownership-aware methods), tiles owned by someone else can't be edited,
+//! and [`PlacementRules`](crate::prelude::PlacementRules) can inspect the owners of both the placed
+//! and the existing tile.
+//!
+//! Owners are included in serialization and indexed by the
+//! [`PlacedTiles`](crate::prelude::PlacedTiles) resource.
+
+use bevy::prelude::{Component, Reflect, ReflectComponent};
+
+/// The owner (such as a player or team ID) of a tile
+///
+/// Since this is a regular component, owned tiles can also be found using a standard
+/// `Query<&TileOwner>`.
+///
+/// # Examples
+///
+/// ```
+/// # use bevy_ecs_tilemap::TilePos;
+/// # use bevy_tileset_map::prelude::{TileId, TileOwner, TilePlacer};
+/// fn build_wall(mut placer: TilePlacer) {
+///   let player = TileOwner(42);
+///   placer.place_owned(TileId::new(0, 0), TilePos(3, 4), 0u16, 0u16, player).unwrap();
+///
+///   // Another player can't remove it
+///   assert!(placer.remove_owned(TilePos(3, 4), 0u16, 0u16, TileOwner(7)).is_err());
+/// }
+/// ```
+#[derive(Component, Reflect, Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+#[reflect(Component)]
+#[cfg_attr(
+	feature = "serialization",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(transparent)
+)]
+pub struct TileOwner(pub u64);
@@ -0,0 +1,79 @@
+//! Per-layer z-ordering and parallax scrolling
+
+use bevy::prelude::*;
+
+/// A component controlling the z-order and parallax of a layer
+///
+/// Layer entities with this component are repositioned every frame relative to the first 2D camera.
+/// Use [`TileLayerManager::set_layer_parallax`](crate::prelude::TileLayerManager::set_layer_parallax)
+/// to add it to a layer.
+///
+/// Since this takes full control over the layer's translation, it should not be combined with
+/// other features that move layers, such as isometric elevation.
+///
+/// # Examples
+///
+/// ```
+/// # use bevy::math::Vec2;
+/// # use bevy_tileset_map::prelude::LayerParallax;
+/// // A distant background that scrolls at a quarter of the camera's speed
+/// let background = LayerParallax {
+///   z_offset: -10.0,
+///   factor: Vec2::splat(0.25),
+/// };
+/// ```
+#[derive(Component, Reflect, Debug, Copy, Clone, PartialEq)]
+#[reflect(Component)]
+pub struct LayerParallax {
+	/// The z-offset of the layer, used to draw it in front of or behind other layers
+	pub z_offset: f32,
+	/// How fast the layer scrolls compared to the camera, per axis
+	///
+	/// A factor of `1.0` scrolls like any other layer, `0.0` stays fixed on screen, values in
+	/// between appear further away, and values above `1.0` appear closer (such as for foreground
+	/// layers).
+	pub factor: Vec2,
+}
+
+impl Default for LayerParallax {
+	fn default() -> Self {
+		Self {
+			z_offset: 0.0,
+			factor: Vec2::ONE,
+		}
+	}
+}
+
+impl LayerParallax {
+	/// Create a parallax with the given z-offset that scrolls like any other layer
+	pub fn from_z(z_offset: f32) -> Self {
+		Self {
+			z_offset,
+			..Default::default()
+		}
+	}
+
+	/// The translation of the layer when the camera is at the given position
+	pub fn translation(&self, camera: Vec2) -> Vec3 {
+		(camera * (Vec2::ONE - self.factor)).extend(self.z_offset)
+	}
+}
+
+/// __\[SYSTEM\]__ Moves every layer with a [`LayerParallax`] relative to the first 2D camera
+pub(crate) fn update_layer_parallax(
+	cameras: Query<&GlobalTransform, (With<Camera>, With<OrthographicProjection>)>,
+	mut layers: Query<(&LayerParallax, &mut Transform), Without<Camera>>,
+) {
+	let camera = cameras
+		.iter()
+		.next()
+		.map(|transform| transform.translation.truncate())
+		.unwrap_or_default();
+
+	for (parallax, mut transform) in layers.iter_mut() {
+		let translation = parallax.translation(camera);
+		if transform.translation != translation {
+			transform.translation = translation;
+		}
+	}
+}
@@ -0,0 +1,205 @@
+//! Physics collider generation using `bevy_rapier2d`
+//!
+//! Colliders are generated for every tile with a [`TileCollision`]. Whenever the solid tiles of a
+//! chunk change, the colliders for that chunk are regenerated, greedily merging adjacent
+//! [`TileCollision::Full`] tiles into as few cuboids as possible. All other shapes receive their own
+//! convex collider.
+//!
+//! Note that you'll still need to add the `bevy_rapier2d` physics plugin to your app yourself.
+
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+use bevy_ecs_tilemap::{MapQuery, TileParent, TilePos};
+use bevy_rapier2d::prelude::{Collider, RigidBody};
+
+use crate::collision::TileCollision;
+
+/// A component added to every collider entity generated for a chunk
+#[derive(Component, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TileChunkCollider {
+	/// The chunk this collider was generated for
+	pub chunk: Entity,
+}
+
+/// A rectangle of tiles, given in tile coordinates
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) struct TileRect {
+	pub x: u32,
+	pub y: u32,
+	pub width: u32,
+	pub height: u32,
+}
+
+/// Tracks the generated colliders
+#[derive(Default)]
+pub(crate) struct TileColliders {
+	/// The collider entities generated for each chunk
+	chunks: HashMap<Entity, Vec<Entity>>,
+	/// Maps each solid tile entity to its chunk
+	///
+	/// This is needed so we know which chunk to regenerate once a tile has been despawned.
+	solid_tiles: HashMap<Entity, Entity>,
+}
+
+/// __\[SYSTEM\]__ Regenerates the colliders of all chunks whose solid tiles have changed
+pub(crate) fn update_tile_colliders(
+	mut commands: Commands,
+	mut colliders: ResMut<TileColliders>,
+	mut map_query: MapQuery,
+	changed: Query<&TileParent, Changed<TileCollision>>,
+	tiles: Query<(Entity, &TilePos, &TileParent, &TileCollision)>,
+	removed: RemovedComponents<TileCollision>,
+) {
+	// === Collect Dirty Chunks === //
+	let mut dirty: HashSet<Entity> = changed.iter().map(|parent| parent.chunk).collect();
+	for entity in removed.iter() {
+		if let Some(chunk) = colliders.solid_tiles.remove(&entity) {
+			dirty.insert(chunk);
+		}
+	}
+
+	if dirty.is_empty() {
+		return;
+	}
+
+	// === Collect Solid Tiles === //
+	let mut solid: HashMap<Entity, ChunkShapes> = HashMap::default();
+	colliders
+		.solid_tiles
+		.retain(|_, chunk| !dirty.contains(chunk));
+	for (entity, pos, parent, collision) in tiles.iter() {
+		if !dirty.contains(&parent.chunk) {
+			continue;
+		}
+
+		colliders.solid_tiles.insert(entity, parent.chunk);
+		let shapes = solid.entry(parent.chunk).or_insert_with(|| ChunkShapes {
+			map_id: parent.map_id,
+			layer_id: parent.layer_id,
+			full: Vec::new(),
+			partial: Vec::new(),
+		});
+		if collision.is_full() {
+			shapes.full.push((pos.0, pos.1));
+		} else {
+			shapes.partial.push(((pos.0, pos.1), collision.points()));
+		}
+	}
+
+	// === Regenerate Colliders === //
+	for chunk in dirty {
+		if let Some(old) = colliders.chunks.remove(&chunk) {
+			for collider in old {
+				commands.entity(collider).despawn();
+			}
+		}
+
+		let shapes = if let Some(shapes) = solid.remove(&chunk) {
+			shapes
+		} else {
+			continue;
+		};
+
+		let (layer_entity, tile_size) = if let Some((layer_entity, layer)) =
+			map_query.get_layer(shapes.map_id, shapes.layer_id)
+		{
+			(layer_entity, layer.settings.tile_size)
+		} else {
+			continue;
+		};
+		let tile_size = Vec2::new(tile_size.0, tile_size.1);
+
+		let mut spawned = Vec::new();
+
+		// Merge all full tiles into cuboids
+		for rect in merge_cells(&shapes.full) {
+			let half_size = Vec2::new(rect.width as f32, rect.height as f32) * tile_size / 2.0;
+			let center = Vec2::new(rect.x as f32, rect.y as f32) * tile_size + half_size;
+			let collider = Collider::cuboid(half_size.x, half_size.y);
+			spawned.push(spawn_collider(&mut commands, collider, center, chunk));
+		}
+
+		// Give every other shape its own convex collider
+		for ((x, y), points) in shapes.partial {
+			let points: Vec<Vec2> = points
+				.iter()
+				.map(|[px, py]| Vec2::new(*px, *py) * tile_size)
+				.collect();
+			if let Some(collider) = Collider::convex_hull(&points) {
+				let origin = Vec2::new(x as f32, y as f32) * tile_size;
+				spawned.push(spawn_collider(&mut commands, collider, origin, chunk));
+			}
+		}
+
+		commands.entity(layer_entity).push_children(&spawned);
+		colliders.chunks.insert(chunk, spawned);
+	}
+}
+
+/// The collision shapes found within a single chunk
+struct ChunkShapes {
+	map_id: u16,
+	layer_id: u16,
+	/// The positions of all [`TileCollision::Full`] tiles
+	full: Vec<(u32, u32)>,
+	/// The positions and normalized outlines of all other tiles
+	partial: Vec<((u32, u32), Vec<[f32; 2]>)>,
+}
+
+/// Spawns a fixed collider at the given layer-local position
+fn spawn_collider(
+	commands: &mut Commands,
+	collider: Collider,
+	translation: Vec2,
+	chunk: Entity,
+) -> Entity {
+	commands
+		.spawn()
+		.insert(collider)
+		.insert(RigidBody::Fixed)
+		.insert(Transform::from_translation(translation.extend(0.0)))
+		.insert(GlobalTransform::default())
+		.insert(TileChunkCollider { chunk })
+		.id()
+}
+
+/// Greedily merges the given cells into as few rectangles as possible
+///
+/// Each rectangle is grown along the x-axis first, then along the y-axis for as long as
+/// every cell in the next row is also available.
+pub(crate) fn merge_cells(cells: &[(u32, u32)]) -> Vec<TileRect> {
+	let mut remaining: HashSet<(u32, u32)> = cells.iter().copied().collect();
+	let mut sorted: Vec<(u32, u32)> = cells.to_vec();
+	sorted.sort_unstable_by_key(|&(x, y)| (y, x));
+
+	let mut rects = Vec::new();
+	for (x, y) in sorted {
+		if !remaining.contains(&(x, y)) {
+			continue;
+		}
+
+		let mut width = 1;
+		while remaining.contains(&(x + width, y)) {
+			width += 1;
+		}
+
+		let mut height = 1;
+		while (x..x + width).all(|cell_x| remaining.contains(&(cell_x, y + height))) {
+			height += 1;
+		}
+
+		for cell_y in y..y + height {
+			for cell_x in x..x + width {
+				remaining.remove(&(cell_x, cell_y));
+			}
+		}
+
+		rects.push(TileRect {
+			x,
+			y,
+			width,
+			height,
+		});
+	}
+	rects
+}
@@ -0,0 +1,189 @@
+//! A reverse index of every placed tile
+//!
+//! Looking up what tile is at a given coordinate normally requires the [`MapQuery`] along with
+//! queries for the tile's components. The [`PlacedTiles`] resource keeps this information in a
+//! single map so it can be read synchronously from anywhere.
+//!
+//! [`MapQuery`]: bevy_ecs_tilemap::MapQuery
+
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_ecs_tilemap::{Tile, TileParent, TilePos};
+use bevy_tileset::prelude::{TileId, TilesetParent, Tilesets};
+
+use crate::combined::CombinedTilesets;
+use crate::coord::TileCoord;
+use crate::ownership::TileOwner;
+
+/// A resource mapping the coordinate of every placed tile to its entity and [`TileId`]
+///
+/// The [`TileOwner`] of every owned tile is indexed as well.
+///
+/// The index is updated at the very end of every frame (in [`CoreStage::Last`]), so tiles placed
+/// during a frame can be looked up starting from the next one. Tiles whose tileset is not loaded
+/// are not included.
+///
+/// This resource is added and kept up to date automatically by the
+/// [`TilesetMapPlugin`](crate::prelude::TilesetMapPlugin).
+///
+/// # Examples
+///
+/// ```
+/// # use bevy::prelude::Res;
+/// # use bevy_ecs_tilemap::TilePos;
+/// # use bevy_tileset_map::prelude::{PlacedTiles, TileCoord};
+/// fn inspect(placed: Res<PlacedTiles>) {
+///   let coord = TileCoord::new(TilePos(3, 4), 0u16, 0u16);
+///   if let Some(tile_id) = placed.get_tile_id(&coord) {
+///     // ...
+///   }
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct PlacedTiles {
+	tiles: HashMap<TileCoord, (Entity, TileId)>,
+	coords: HashMap<Entity, TileCoord>,
+	owners: HashMap<Entity, TileOwner>,
+}
+
+impl PlacedTiles {
+	/// Get the entity and ID of the tile at the given coordinate
+	pub fn get(&self, coord: &TileCoord) -> Option<(Entity, TileId)> {
+		self.tiles.get(coord).copied()
+	}
+
+	/// Get the entity of the tile at the given coordinate
+	pub fn get_entity(&self, coord: &TileCoord) -> Option<Entity> {
+		self.get(coord).map(|(entity, _)| entity)
+	}
+
+	/// Get the ID of the tile at the given coordinate
+	pub fn get_tile_id(&self, coord: &TileCoord) -> Option<TileId> {
+		self.get(coord).map(|(_, tile_id)| tile_id)
+	}
+
+	/// Get the coordinate of the given tile entity
+	pub fn get_coord(&self, entity: Entity) -> Option<TileCoord> {
+		self.coords.get(&entity).copied()
+	}
+
+	/// Get the owner of the tile at the given coordinate (if it has one)
+	pub fn get_owner(&self, coord: &TileCoord) -> Option<TileOwner> {
+		let (entity, _) = self.get(coord)?;
+		self.owners.get(&entity).copied()
+	}
+
+	/// Iterate over every placed tile owned by the given owner
+	pub fn iter_owned(
+		&self,
+		owner: TileOwner,
+	) -> impl Iterator<Item = (&TileCoord, Entity, TileId)> {
+		self.iter()
+			.filter(move |(_, entity, _)| self.owners.get(entity) == Some(&owner))
+	}
+
+	/// Returns true if a tile is placed at the given coordinate
+	pub fn contains(&self, coord: &TileCoord) -> bool {
+		self.tiles.contains_key(coord)
+	}
+
+	/// Iterate over every placed tile
+	pub fn iter(&self) -> impl Iterator<Item = (&TileCoord, Entity, TileId)> {
+		self.tiles
+			.iter()
+			.map(|(coord, (entity, tile_id))| (coord, *entity, *tile_id))
+	}
+
+	/// Iterate over every placed tile in the given layer
+	pub fn iter_layer(
+		&self,
+		map_id: u16,
+		layer_id: u16,
+	) -> impl Iterator<Item = (&TileCoord, Entity, TileId)> {
+		self.iter()
+			.filter(move |(coord, ..)| coord.map_id == map_id && coord.layer_id == layer_id)
+	}
+
+	/// The number of placed tiles
+	pub fn len(&self) -> usize {
+		self.tiles.len()
+	}
+
+	/// Returns true if no tiles are placed
+	pub fn is_empty(&self) -> bool {
+		self.tiles.is_empty()
+	}
+
+	fn insert(&mut self, entity: Entity, coord: TileCoord, tile_id: TileId) {
+		if let Some(old_coord) = self.coords.insert(entity, coord) {
+			if old_coord != coord {
+				self.remove_at(entity, &old_coord);
+			}
+		}
+		self.tiles.insert(coord, (entity, tile_id));
+	}
+
+	fn remove(&mut self, entity: Entity) {
+		self.owners.remove(&entity);
+		if let Some(coord) = self.coords.remove(&entity) {
+			self.remove_at(entity, &coord);
+		}
+	}
+
+	/// Removes the tile at the given coordinate only if it still belongs to the given entity
+	fn remove_at(&mut self, entity: Entity, coord: &TileCoord) {
+		if matches!(self.tiles.get(coord), Some((owner, _)) if *owner == entity) {
+			self.tiles.remove(coord);
+		}
+	}
+}
+
+/// __\[SYSTEM\]__ Keeps the [`PlacedTiles`] index in sync with all placed tiles
+pub(crate) fn update_placed_tiles(
+	mut placed: ResMut<PlacedTiles>,
+	changed: Query<
+		(Entity, &TilePos, &TileParent, &Tile, &TilesetParent),
+		Or<(Changed<Tile>, Changed<TilesetParent>)>,
+	>,
+	owners: Query<(Entity, &TileOwner), (With<Tile>, Changed<TileOwner>)>,
+	removed_tiles: RemovedComponents<Tile>,
+	removed_parents: RemovedComponents<TilesetParent>,
+	removed_owners: RemovedComponents<TileOwner>,
+	tilesets: Tilesets,
+	combined: Res<CombinedTilesets>,
+) {
+	for entity in removed_tiles.iter().chain(removed_parents.iter()) {
+		placed.remove(entity);
+	}
+	for entity in removed_owners.iter() {
+		placed.owners.remove(&entity);
+	}
+	for (entity, owner) in owners.iter() {
+		placed.owners.insert(entity, *owner);
+	}
+
+	for (entity, pos, parent, tile, tileset_parent) in changed.iter() {
+		let index = combined.local_index(
+			&tileset_parent.0,
+			parent.map_id,
+			parent.layer_id,
+			tile.texture_index as usize,
+		);
+		let tile_id = tilesets
+			.get_by_id(&tileset_parent.0)
+			.and_then(|tileset| tileset.get_tile_id(&index))
+			.copied();
+
+		match tile_id {
+			Some(tile_id) => {
+				let coord = TileCoord {
+					pos: *pos,
+					map_id: parent.map_id,
+					layer_id: parent.layer_id,
+				};
+				placed.insert(entity, coord, tile_id);
+			},
+			None => placed.remove(entity),
+		}
+	}
+}
@@ -0,0 +1,181 @@
+//! Controlling the animation of placed animated tiles
+//!
+//! Animated tiles are driven by a clock shared by the whole tilemap, so all tiles of the same
+//! animated [`TileId`](bevy_tileset::prelude::TileId) flicker in phase by default. To make them
+//! drift apart instead, give the tile a value for the [`ANIMATION_DEPHASE_METADATA_KEY`] key in
+//! its [`TileMetadata`].
+
+use bevy::prelude::{Component, Entity};
+use bevy_ecs_tilemap::prelude::{GPUAnimated, MapId, TilePos};
+
+#[cfg(any(not(feature = "headless"), feature = "auto-tile"))]
+use crate::metadata::TileMetadata;
+use crate::placement::{AutoTileAccess, TilePlacementError, TilePlacer};
+
+/// The metadata key used to de-phase the animations of a tile
+///
+/// The value should be a number between `0.0` and `1.0`, giving the largest fraction by which the
+/// speed of each placed tile may differ from the speed defined by its tileset. The actual speed
+/// is derived from the tile's position, so the same tile always animates the same way. A value of
+/// `0.0` (or no value at all) keeps every tile synchronized.
+///
+/// Since the animation clock starts at the same time for every tile, de-phased tiles start out
+/// in phase and then gradually drift apart.
+pub const ANIMATION_DEPHASE_METADATA_KEY: &str = "animation_dephase";
+
+/// The playback state of an animated tile
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum AnimationState {
+	/// The animation is playing at the given speed
+	Playing {
+		/// The playback speed, using the same units as the tileset
+		speed: f32,
+	},
+	/// The animation is stopped on the given frame
+	Paused {
+		/// The displayed frame, relative to the first frame of the animation
+		frame: usize,
+	},
+}
+
+/// A component storing the animation of a paused tile, so it can be resumed later
+///
+/// This is nominally public since it appears in the state of the [`TilePlacer`] param, but it
+/// is not exported.
+#[derive(Component, Debug, Copy, Clone, PartialEq)]
+pub struct PausedAnimation {
+	start: u32,
+	end: u32,
+	speed: f32,
+}
+
+impl<'w, 's, A: AutoTileAccess> TilePlacer<'w, 's, A> {
+	/// Get the animation state of the given tile entity
+	///
+	/// Returns `None` if the tile is not animated.
+	pub fn get_animation_state(&self, entity: Entity) -> Option<AnimationState> {
+		let current = self.query.get(entity).ok()?.1?;
+		match self.get_paused(entity) {
+			Some(paused) => Some(AnimationState::Paused {
+				frame: current.start.saturating_sub(paused.start) as usize,
+			}),
+			None => Some(AnimationState::Playing {
+				speed: current.speed,
+			}),
+		}
+	}
+
+	/// Set the animation state of the tile at the given coordinate
+	///
+	/// # Arguments
+	///
+	/// * `pos`: The tile position
+	/// * `map_id`: The tile map
+	/// * `layer_id`: The layer within the tile map
+	/// * `state`: The new animation state
+	///
+	/// # Errors
+	///
+	/// Returns [`TilePlacementError::NotAnimated`] if the tile is not animated (which is always
+	/// the case with the `headless` feature enabled).
+	///
+	pub fn set_animation_state<Pos: Into<TilePos>, MId: MapId>(
+		&mut self,
+		pos: Pos,
+		map_id: MId,
+		layer_id: u16,
+		state: AnimationState,
+	) -> Result<(), TilePlacementError> {
+		self.check_writable(map_id, layer_id)?;
+		let entity = self
+			.map_query
+			.get_tile_entity(pos.into(), map_id, layer_id)
+			.map_err(TilePlacementError::MapError)?;
+		self.set_tile_animation_state(entity, state)
+	}
+
+	/// Set the animation state of the given tile entity
+	///
+	/// Pausing stores the tile's animation so that it can later be resumed at any speed. Paused
+	/// frames are clamped to the length of the animation.
+	pub fn set_tile_animation_state(
+		&mut self,
+		entity: Entity,
+		state: AnimationState,
+	) -> Result<(), TilePlacementError> {
+		let (start, end, speed) = self
+			.get_animation(entity)
+			.ok_or(TilePlacementError::NotAnimated(entity))?;
+
+		let mut cmds = self.commands.entity(entity);
+		match state {
+			AnimationState::Playing { speed: new_speed } => {
+				cmds.remove::<PausedAnimation>()
+					.insert(GPUAnimated::new(start, end, new_speed));
+			},
+			AnimationState::Paused { frame } => {
+				let last = end.saturating_sub(start).saturating_sub(1);
+				let frame = start + (frame as u32).min(last);
+				cmds.insert(PausedAnimation { start, end, speed })
+					.insert(GPUAnimated::new(frame, frame + 1, 0.0));
+			},
+		}
+		Ok(())
+	}
+
+	/// Resume the animation of the given tile entity at the speed defined by its tileset
+	pub fn resume_animation(&mut self, entity: Entity) -> Result<(), TilePlacementError> {
+		let (.., speed) = self
+			.get_animation(entity)
+			.ok_or(TilePlacementError::NotAnimated(entity))?;
+		self.set_tile_animation_state(entity, AnimationState::Playing { speed })
+	}
+
+	/// Pause the animation of the given tile entity on the given frame
+	///
+	/// The frame is relative to the first frame of the animation.
+	pub fn pause_animation(
+		&mut self,
+		entity: Entity,
+		frame: usize,
+	) -> Result<(), TilePlacementError> {
+		self.set_tile_animation_state(entity, AnimationState::Paused { frame })
+	}
+
+	/// Get the stored animation of the given tile entity (if paused)
+	fn get_paused(&self, entity: Entity) -> Option<&PausedAnimation> {
+		self.animations
+			.get(entity)
+			.ok()
+			.and_then(|(_, paused, _)| paused)
+	}
+
+	/// Get the full animation of the given tile entity as `(start, end, speed)`, even if paused
+	fn get_animation(&self, entity: Entity) -> Option<(u32, u32, f32)> {
+		if let Some(paused) = self.get_paused(entity) {
+			return Some((paused.start, paused.end, paused.speed));
+		}
+
+		let current = self.query.get(entity).ok()?.1?;
+		Some((current.start, current.end, current.speed))
+	}
+}
+
+/// Adjust the given animation speed for a tile at the given position, according to its
+/// [`ANIMATION_DEPHASE_METADATA_KEY`] metadata
+#[cfg(any(not(feature = "headless"), feature = "auto-tile"))]
+pub(crate) fn dephase_speed(speed: f32, pos: TilePos, metadata: Option<&TileMetadata>) -> f32 {
+	let variance = metadata
+		.and_then(|metadata| metadata.get_float(ANIMATION_DEPHASE_METADATA_KEY))
+		.unwrap_or_default()
+		.clamp(0.0, 1.0) as f32;
+	if variance == 0.0 {
+		return speed;
+	}
+
+	// Hash the position into a value in `[-1.0, 1.0)`
+	let hash = (pos.0.wrapping_mul(0x9E37_79B1) ^ pos.1.wrapping_mul(0x85EB_CA77))
+		.wrapping_mul(0xC2B2_AE3D);
+	let offset = (hash >> 8) as f32 / (1u32 << 23) as f32 - 1.0;
+	speed * (1.0 + variance * offset)
+}
@@ -0,0 +1,206 @@
+use bevy_ecs_tilemap::prelude::{MapId, TilePos};
+use bevy_tileset::prelude::TileId;
+
+use crate::placement::groups::{roll, weighted_pick};
+use crate::placement::{AutoTileAccess, TilePlacementError, TilePlacer};
+
+/// The tiles painted by a [`TileBrush`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum BrushTiles {
+	/// Always paint the same tile
+	Single(TileId),
+	/// Paint a tile chosen uniformly from the set
+	Random(Vec<TileId>),
+	/// Paint a tile chosen from the set according to its weight
+	Weighted(Vec<(TileId, f32)>),
+	/// Paint a fixed pattern of tiles
+	///
+	/// Rows are given top to bottom (i.e. `stamp[0][0]` is the top-left tile) and the brush
+	/// position marks the bottom-left corner of the stamp. Empty entries are left untouched.
+	Stamp(Vec<Vec<Option<TileId>>>),
+}
+
+/// The area covered by a [`TileBrush`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BrushShape {
+	/// A single tile
+	Point,
+	/// A square extending the given number of tiles from the brush position in each direction
+	Square(u32),
+	/// A circle with the given radius (in tiles) centered on the brush position
+	Circle(u32),
+}
+
+/// A reusable tile selection and shape, applied using [`TilePlacer::apply_brush`]
+///
+/// Random selections are deterministic: the same brush always paints the same tile at a given
+/// position. Use [`with_seed`](Self::with_seed) to vary the result.
+///
+/// # Examples
+///
+/// ```
+/// # use bevy_ecs_tilemap::TilePos;
+/// # use bevy_tileset_map::prelude::{BrushShape, TileBrush, TileId, TilePlacer};
+/// fn paint(mut placer: TilePlacer) {
+///   let brush = TileBrush::weighted(vec![
+///     (TileId::new(0, 0), 3.0),
+///     (TileId::new(1, 0), 1.0),
+///   ])
+///   .with_shape(BrushShape::Circle(3));
+///
+///   placer.apply_brush(&brush, TilePos(10, 10), 0u16, 0u16).unwrap();
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct TileBrush {
+	/// The tiles painted by this brush
+	pub tiles: BrushTiles,
+	/// The area covered by this brush
+	///
+	/// This is ignored by [`BrushTiles::Stamp`], which always covers its own pattern.
+	pub shape: BrushShape,
+	/// The seed used for random selections
+	pub seed: u64,
+}
+
+impl TileBrush {
+	/// Create a point brush that paints the given tile
+	pub fn single<Id: Into<TileId>>(tile_id: Id) -> Self {
+		Self::new(BrushTiles::Single(tile_id.into()))
+	}
+
+	/// Create a point brush that paints a random tile from the given set
+	pub fn random(tile_ids: Vec<TileId>) -> Self {
+		Self::new(BrushTiles::Random(tile_ids))
+	}
+
+	/// Create a point brush that paints a random tile from the given weighted set
+	pub fn weighted(tile_ids: Vec<(TileId, f32)>) -> Self {
+		Self::new(BrushTiles::Weighted(tile_ids))
+	}
+
+	/// Create a brush that paints the given pattern
+	pub fn stamp(stamp: Vec<Vec<Option<TileId>>>) -> Self {
+		Self::new(BrushTiles::Stamp(stamp))
+	}
+
+	/// Create a point brush that paints the given tiles
+	pub fn new(tiles: BrushTiles) -> Self {
+		Self {
+			tiles,
+			shape: BrushShape::Point,
+			seed: 0,
+		}
+	}
+
+	/// Set the shape of this brush
+	pub fn with_shape(mut self, shape: BrushShape) -> Self {
+		self.shape = shape;
+		self
+	}
+
+	/// Set the seed used for random selections
+	pub fn with_seed(mut self, seed: u64) -> Self {
+		self.seed = seed;
+		self
+	}
+
+	/// Get every position this brush paints (along with its tile) when applied at the given position
+	///
+	/// Positions that would fall below zero are skipped.
+	pub fn tiles_at<Pos: Into<TilePos>>(&self, pos: Pos) -> Vec<(TilePos, TileId)> {
+		let pos = pos.into();
+
+		if let BrushTiles::Stamp(stamp) = &self.tiles {
+			let height = stamp.len() as u32;
+			return stamp
+				.iter()
+				.enumerate()
+				.flat_map(|(row, tiles)| {
+					let y = pos.1 + (height - 1 - row as u32);
+					tiles.iter().enumerate().filter_map(move |(x, tile_id)| {
+						tile_id.map(|tile_id| (TilePos(pos.0 + x as u32, y), tile_id))
+					})
+				})
+				.collect();
+		}
+
+		let radius = match self.shape {
+			BrushShape::Point => 0,
+			BrushShape::Square(radius) | BrushShape::Circle(radius) => radius as i64,
+		};
+		let (cx, cy) = (pos.0 as i64, pos.1 as i64);
+
+		let mut tiles = Vec::new();
+		for y in (cy - radius).max(0)..=cy + radius {
+			for x in (cx - radius).max(0)..=cx + radius {
+				if let BrushShape::Circle(..) = self.shape {
+					let (dx, dy) = (x - cx, y - cy);
+					if dx * dx + dy * dy > radius * radius {
+						continue;
+					}
+				}
+
+				let pos = TilePos(x as u32, y as u32);
+				if let Some(tile_id) = self.select(pos) {
+					tiles.push((pos, tile_id));
+				}
+			}
+		}
+		tiles
+	}
+
+	/// Select the tile to paint at the given position
+	fn select(&self, pos: TilePos) -> Option<TileId> {
+		match &self.tiles {
+			BrushTiles::Single(tile_id) => Some(*tile_id),
+			BrushTiles::Random(tile_ids) => {
+				if tile_ids.is_empty() {
+					return None;
+				}
+				let index = (roll(pos, self.seed) * tile_ids.len() as f64) as usize;
+				tile_ids.get(index.min(tile_ids.len() - 1)).copied()
+			},
+			BrushTiles::Weighted(tile_ids) => weighted_pick(tile_ids, roll(pos, self.seed)),
+			BrushTiles::Stamp(..) => None,
+		}
+	}
+}
+
+impl<'w, 's, A: AutoTileAccess> TilePlacer<'w, 's, A> {
+	/// Paint the given brush at the given position
+	///
+	/// All affected chunks are notified once the entire brush has been applied.
+	///
+	/// Returns the number of tiles that were placed.
+	///
+	/// # Arguments
+	///
+	/// * `brush`: The brush to apply
+	/// * `pos`: The brush position
+	/// * `map_id`: The tile map
+	/// * `layer_id`: The layer within the tile map
+	///
+	pub fn apply_brush<Pos: Into<TilePos>, MId: MapId>(
+		&mut self,
+		brush: &TileBrush,
+		pos: Pos,
+		map_id: MId,
+		layer_id: u16,
+	) -> Result<usize, TilePlacementError> {
+		self.begin_batch();
+
+		let mut count = 0;
+		let mut result = Ok(());
+		for (pos, tile_id) in brush.tiles_at(pos) {
+			if let Err(err) = self.place(tile_id, pos, map_id, layer_id) {
+				result = Err(err);
+				break;
+			}
+			count += 1;
+		}
+
+		self.end_batch();
+		result.map(|_| count)
+	}
+}
@@ -0,0 +1,403 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_ecs_tilemap::prelude::*;
+use bevy_tileset::prelude::*;
+
+use crate::placement::{AutoTileAccess, TilePlacementError, TilePlacer};
+use crate::template::parse_grid;
+
+/// A builder used to spawn an entire map (its [`Map`], layers, and tiles) in one go
+///
+/// Grids are given as rows of tiles, where the first row is the _top_ of the map
+/// (i.e. `grid[0][0]` is the top-left tile). Each layer is configured to match the tileset of
+/// its first tile, so all tiles within a layer must belong to tilesets sharing its texture.
+///
+/// # Examples
+///
+/// ```
+/// # use bevy_tileset_map::prelude::{TileId, TilePlacer, TilesetMapBuilder};
+/// fn spawn_map(mut placer: TilePlacer) {
+///   let grass = Some(TileId::new(0, 0));
+///   let water = Some(TileId::new(1, 0));
+///
+///   let built = TilesetMapBuilder::from_grid(vec![
+///     vec![grass, grass, grass],
+///     vec![grass, water, grass],
+///     vec![grass, grass, grass],
+///   ])
+///   .build(&mut placer)
+///   .unwrap();
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct TilesetMapBuilder {
+	map_id: u16,
+	chunk_size: ChunkSize,
+	layers: BTreeMap<u16, Vec<Vec<Option<TileId>>>>,
+}
+
+/// The entities spawned by a [`TilesetMapBuilder`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuiltMap {
+	/// The map entity
+	pub map_entity: Entity,
+	/// The ID and entity of every spawned layer
+	pub layers: Vec<(u16, Entity)>,
+}
+
+impl TilesetMapBuilder {
+	/// Create a builder for map `0` whose layer `0` is made up of the given grid
+	pub fn from_grid(grid: Vec<Vec<Option<TileId>>>) -> Self {
+		Self::new().with_layer(0, grid)
+	}
+
+	/// Create a builder for map `0` whose layer `0` is generated using the given function
+	///
+	/// The function is called once for every position within the given size.
+	pub fn from_fn<F: FnMut(TilePos) -> Option<TileId>>(
+		width: u32,
+		height: u32,
+		generator: F,
+	) -> Self {
+		Self::new().with_layer_fn(0, width, height, generator)
+	}
+
+	/// Create a builder for map `0` whose layer `0` is drawn using the given characters
+	///
+	/// See [`Self::with_ascii_layer`] for details.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// # use bevy_tileset_map::prelude::{TileId, TilePlacer, TilesetMapBuilder};
+	/// fn spawn_room(mut placer: TilePlacer) {
+	///   let wall = TileId::new(0, 0);
+	///   let floor = TileId::new(1, 0);
+	///
+	///   let built = TilesetMapBuilder::from_ascii(
+	///     "
+	///     #####
+	///     #...#
+	///     #...#
+	///     #####
+	///     ",
+	///     &[('#', wall), ('.', floor)],
+	///   )
+	///   .build(&mut placer)
+	///   .unwrap();
+	/// }
+	/// ```
+	pub fn from_ascii(ascii: &str, legend: &[(char, TileId)]) -> Self {
+		Self::new().with_ascii_layer(0, ascii, legend)
+	}
+
+	/// Create an empty builder for map `0`
+	pub fn new() -> Self {
+		Self {
+			map_id: 0,
+			chunk_size: ChunkSize(32, 32),
+			layers: BTreeMap::new(),
+		}
+	}
+
+	/// Set the ID of the map
+	pub fn with_map_id(mut self, map_id: u16) -> Self {
+		self.map_id = map_id;
+		self
+	}
+
+	/// Set the size (in tiles) of each chunk
+	///
+	/// Defaults to `32x32`.
+	pub fn with_chunk_size(mut self, chunk_size: ChunkSize) -> Self {
+		self.chunk_size = chunk_size;
+		self
+	}
+
+	/// Add (or replace) a layer made up of the given grid
+	pub fn with_layer(mut self, layer_id: u16, grid: Vec<Vec<Option<TileId>>>) -> Self {
+		self.layers.insert(layer_id, grid);
+		self
+	}
+
+	/// Add (or replace) a layer drawn using the given characters
+	///
+	/// Each line of the string is a row of the layer, with the first line at the _top_. Every
+	/// character is mapped to a tile using the given legend, while characters without an entry
+	/// (such as spaces) are left empty.
+	///
+	/// Blank lines at the start and end are ignored and the indentation shared by all lines is
+	/// removed, so the grid can be written inline and indented along with the surrounding code.
+	pub fn with_ascii_layer(self, layer_id: u16, ascii: &str, legend: &[(char, TileId)]) -> Self {
+		let lines = ascii
+			.lines()
+			.skip_while(|line| line.trim().is_empty())
+			.collect::<Vec<_>>();
+		let indent = lines
+			.iter()
+			.filter(|line| !line.trim().is_empty())
+			.map(|line| line.len() - line.trim_start().len())
+			.min()
+			.unwrap_or_default();
+		let (width, height, cells) = parse_grid(
+			lines
+				.iter()
+				.map(|line| line.get(indent..).unwrap_or_default()),
+		);
+
+		let grid = (0..height)
+			.rev()
+			.map(|y| {
+				(0..width)
+					.map(|x| {
+						let c = cells[(y * width + x) as usize]?;
+						legend
+							.iter()
+							.find(|(key, _)| *key == c)
+							.map(|(_, tile_id)| *tile_id)
+					})
+					.collect()
+			})
+			.collect();
+		self.with_layer(layer_id, grid)
+	}
+
+	/// Add (or replace) a layer generated using the given function
+	///
+	/// The function is called once for every position within the given size.
+	pub fn with_layer_fn<F: FnMut(TilePos) -> Option<TileId>>(
+		mut self,
+		layer_id: u16,
+		width: u32,
+		height: u32,
+		mut generator: F,
+	) -> Self {
+		let grid = (0..height)
+			.rev()
+			.map(|y| (0..width).map(|x| generator(TilePos(x, y))).collect())
+			.collect();
+		self.layers.insert(layer_id, grid);
+		self
+	}
+
+	/// Spawn the map using the given [`TilePlacer`]
+	///
+	/// Layers without any tiles are skipped.
+	pub fn build(self, placer: &mut TilePlacer) -> Result<BuiltMap, TilePlacementError> {
+		let (map_entity, mut map) = placer.spawn_map(self.map_id);
+		let mut layers = Vec::with_capacity(self.layers.len());
+
+		for (layer_id, grid) in self.layers {
+			let first = grid.iter().flatten().flatten().next();
+			let first = if let Some(first) = first {
+				*first
+			} else {
+				continue;
+			};
+
+			let height = grid.len() as u32;
+			let width = grid.iter().map(Vec::len).max().unwrap_or_default() as u32;
+
+			let tileset = placer.get_tileset(&first)?;
+			let texture = tileset.texture().clone();
+			let texture_size = tileset.size();
+			let map_size = MapSize(
+				width.div_ceil(self.chunk_size.0),
+				height.div_ceil(self.chunk_size.1),
+			);
+			let settings = LayerSettings::new(
+				map_size,
+				self.chunk_size,
+				tileset.tile_size().into(),
+				TextureSize(texture_size.x, texture_size.y),
+			);
+
+			let mut tileset_ids = grid
+				.iter()
+				.flatten()
+				.flatten()
+				.map(|tile_id| tile_id.tileset_id)
+				.collect::<Vec<_>>();
+			tileset_ids.sort_unstable();
+			tileset_ids.dedup();
+			for tileset_id in tileset_ids {
+				let tileset = placer
+					.tilesets
+					.get_by_id(&tileset_id)
+					.ok_or(TilePlacementError::InvalidTileset(tileset_id))?;
+				if tileset_id != first.tileset_id && tileset.texture() != &texture {
+					return Err(TilePlacementError::TilesetMismatch {
+						tileset_id,
+						map_id: self.map_id,
+						layer_id,
+					});
+				}
+			}
+
+			let mut layer_builder = placer.create_layer_builder(settings, self.map_id, layer_id);
+			placer.init_layer_from_grid(&grid, &mut layer_builder)?;
+
+			let layer_entity = placer.finish_layer(&mut map, layer_id, layer_builder, texture);
+			layers.push((layer_id, layer_entity));
+		}
+
+		placer.finish_map(map_entity, map);
+
+		Ok(BuiltMap { map_entity, layers })
+	}
+}
+
+impl<'w, 's, A: AutoTileAccess> TilePlacer<'w, 's, A> {
+	/// Adds an entire grid of tiles to the given `LayerBuilder`
+	///
+	/// This is a faster alternative to calling [`add_to_layer`](Self::add_to_layer) for every
+	/// tile of a large layer. Each distinct tile is only looked up once, and tiles that need no
+	/// components besides their [`TilesetParent`] are written directly into the builder, with all
+	/// of their components inserted in a single command. Other tiles (such as animated tiles,
+	/// Auto tiles, or tiles with metadata) fall back to `add_to_layer`.
+	///
+	/// Grids are given as rows of tiles, where the first row is the _top_ of the layer.
+	///
+	/// Returns the number of added tiles.
+	///
+	/// # Arguments
+	///
+	/// * `grid`: The rows of tiles to add
+	/// * `layer_builder`: The layer builder
+	///
+	pub fn init_layer_from_grid(
+		&mut self,
+		grid: &[Vec<Option<TileId>>],
+		layer_builder: &mut LayerBuilder<TileBundle>,
+	) -> Result<usize, TilePlacementError> {
+		let height = grid.len() as u32;
+		let mut resolved: HashMap<TileId, Option<(usize, TilesetId)>> = HashMap::default();
+		let mut parents = Vec::new();
+		let mut count = 0;
+
+		for (row, tiles) in grid.iter().enumerate() {
+			let y = height - 1 - row as u32;
+			for (x, tile_id) in tiles.iter().enumerate() {
+				let tile_id = if let Some(tile_id) = tile_id {
+					*tile_id
+				} else {
+					continue;
+				};
+				let pos = TilePos(x as u32, y);
+
+				let plain = match resolved.get(&tile_id) {
+					Some(plain) => *plain,
+					None => {
+						let plain = self.resolve_plain_tile(&tile_id)?;
+						resolved.insert(tile_id, plain);
+						plain
+					},
+				};
+
+				if let Some((index, tileset_id)) = plain {
+					let tile = Tile {
+						texture_index: index as u16,
+						..Default::default()
+					};
+					layer_builder
+						.set_tile(pos, tile.into())
+						.map_err(TilePlacementError::MapError)?;
+					let entity = layer_builder
+						.get_tile_entity(&mut self.commands, pos)
+						.map_err(TilePlacementError::MapError)?;
+					parents.push((entity, (TilesetParent(tileset_id),)));
+				} else {
+					self.add_to_layer(tile_id, pos, layer_builder)?;
+				}
+				count += 1;
+			}
+		}
+
+		self.commands.insert_or_spawn_batch(parents);
+		Ok(count)
+	}
+
+	/// Get the texture index and tileset of the given tile if it needs no components other than
+	/// its [`TilesetParent`]
+	///
+	/// Returns `None` for tiles that need to be added using [`Self::add_to_layer`].
+	fn resolve_plain_tile(
+		&self,
+		tile_id: &TileId,
+	) -> Result<Option<(usize, TilesetId)>, TilePlacementError> {
+		// Variant and Auto tiles may resolve differently for every tile
+		let index = match self.get_tile_data(tile_id)?.tile() {
+			TileType::Standard(index) => *index,
+			_ => return Ok(None),
+		};
+		if !self.resources.hooks.is_empty() || self.get_transition(tile_id).is_some() {
+			return Ok(None);
+		}
+
+		let tileset = self.get_tileset(tile_id)?;
+		let metadata = self.resources.metadata.get_by_id(tileset, tile_id);
+		if metadata.is_some()
+			|| self
+				.resources
+				.collision
+				.resolve(tileset, tile_id, metadata)
+				.is_some()
+		{
+			return Ok(None);
+		}
+
+		Ok(Some((index, *tileset.id())))
+	}
+
+	/// Spawn the entity for a new map
+	///
+	/// The returned [`Map`] should be passed to [`Self::finish_map`] once all layers have been added.
+	pub(crate) fn spawn_map(&mut self, map_id: u16) -> (Entity, Map) {
+		let map_entity = self.commands.spawn().id();
+		(map_entity, Map::new(map_id, map_entity))
+	}
+
+	/// Insert the given map into its entity
+	pub(crate) fn finish_map(&mut self, map_entity: Entity, map: Map) {
+		self.commands
+			.entity(map_entity)
+			.insert(map)
+			.insert(Transform::default())
+			.insert(GlobalTransform::default());
+	}
+
+	/// Create a builder for a new layer, to be filled using [`Self::add_to_layer`]
+	pub(crate) fn create_layer_builder(
+		&mut self,
+		settings: LayerSettings,
+		map_id: u16,
+		layer_id: u16,
+	) -> LayerBuilder<TileBundle> {
+		let (layer_builder, _) =
+			LayerBuilder::<TileBundle>::new(&mut self.commands, settings, map_id, layer_id);
+		layer_builder
+	}
+
+	/// Build the given layer and add it to the given map
+	pub(crate) fn finish_layer(
+		&mut self,
+		map: &mut Map,
+		layer_id: u16,
+		layer_builder: LayerBuilder<TileBundle>,
+		texture: Handle<Image>,
+	) -> Entity {
+		let layer_entity = self
+			.map_query
+			.build_layer(&mut self.commands, layer_builder, texture);
+		map.add_layer(&mut self.commands, layer_id, layer_entity);
+		layer_entity
+	}
+}
+
+impl Default for TilesetMapBuilder {
+	fn default() -> Self {
+		Self::new()
+	}
+}
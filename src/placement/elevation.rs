@@ -0,0 +1,46 @@
+use bevy::prelude::Entity;
+use bevy_ecs_tilemap::prelude::{MapId, TilePos};
+
+use crate::elevation::TileElevation;
+use crate::placement::{AutoTileAccess, TilePlacementError, TilePlacer};
+
+impl<'w, 's, A: AutoTileAccess> TilePlacer<'w, 's, A> {
+	/// Set the elevation (in pixels) of the tile at the given coordinate
+	///
+	/// An elevation of `0.0` removes the tile's [`TileElevation`] entirely.
+	///
+	/// # Arguments
+	///
+	/// * `pos`: The tile position
+	/// * `map_id`: The tile map
+	/// * `layer_id`: The layer within the tile map
+	/// * `elevation`: The vertical offset in pixels
+	///
+	pub fn set_elevation<Pos: Into<TilePos>, MId: MapId>(
+		&mut self,
+		pos: Pos,
+		map_id: MId,
+		layer_id: u16,
+		elevation: f32,
+	) -> Result<(), TilePlacementError> {
+		self.check_writable(map_id, layer_id)?;
+		let entity = self
+			.map_query
+			.get_tile_entity(pos.into(), map_id, layer_id)
+			.map_err(TilePlacementError::MapError)?;
+		self.set_tile_elevation(entity, elevation);
+		Ok(())
+	}
+
+	/// Set the elevation (in pixels) of the given tile entity
+	///
+	/// An elevation of `0.0` removes the tile's [`TileElevation`] entirely.
+	pub fn set_tile_elevation(&mut self, entity: Entity, elevation: f32) {
+		let mut cmds = self.commands.entity(entity);
+		if elevation == 0.0 {
+			cmds.remove::<TileElevation>();
+		} else {
+			cmds.insert(TileElevation(elevation));
+		}
+	}
+}
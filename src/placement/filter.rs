@@ -0,0 +1,141 @@
+use bevy_ecs_tilemap::prelude::{MapId, TilePos};
+use bevy_tileset::prelude::{TileId, Tileset, TilesetId};
+
+use crate::placement::{AutoTileAccess, TilePlacementError, TilePlacer};
+
+/// A filter used to select placed tiles, such as with [`TilePlacer::remove_matching`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum TileFilter {
+	/// Matches the exact tile (including its variant, if any)
+	Id(TileId),
+	/// Matches any tile in the same group as the given tile, regardless of variant
+	Group(TileId),
+	/// Matches any tile with the given name, from any tileset
+	Name(String),
+	/// Matches any tile from the given tileset
+	Tileset(TilesetId),
+	/// Matches if any of the contained filters match
+	Any(Vec<TileFilter>),
+	/// Matches if none of the contained filters match
+	None(Vec<TileFilter>),
+}
+
+impl TileFilter {
+	/// Returns true if the given tile from the given tileset matches this filter
+	pub fn matches(&self, tile_id: &TileId, tileset: &Tileset) -> bool {
+		match self {
+			Self::Id(id) => id == tile_id,
+			Self::Group(id) => id.eq_tile_group(tile_id),
+			Self::Name(name) => tileset
+				.get_tile_name(&tile_id.group_id)
+				.map(|tile_name| tile_name == name)
+				.unwrap_or_default(),
+			Self::Tileset(tileset_id) => &tile_id.tileset_id == tileset_id,
+			Self::Any(filters) => filters
+				.iter()
+				.any(|filter| filter.matches(tile_id, tileset)),
+			Self::None(filters) => !filters
+				.iter()
+				.any(|filter| filter.matches(tile_id, tileset)),
+		}
+	}
+}
+
+impl<'w, 's, A: AutoTileAccess> TilePlacer<'w, 's, A> {
+	/// Remove the tile at the given coordinate only if it matches the given filter
+	///
+	/// Returns true if the tile was removed.
+	///
+	/// # Arguments
+	///
+	/// * `pos`: The tile position
+	/// * `map_id`: The tile map
+	/// * `layer_id`: The layer within the tile map
+	/// * `filter`: The filter the tile must match
+	///
+	pub fn remove_matching<Pos: Into<TilePos>, MId: MapId>(
+		&mut self,
+		pos: Pos,
+		map_id: MId,
+		layer_id: u16,
+		filter: &TileFilter,
+	) -> Result<bool, TilePlacementError> {
+		let pos = pos.into();
+		self.map_query
+			.get_tile_entity(pos, map_id, layer_id)
+			.map_err(TilePlacementError::MapError)?;
+		if !self.matches_filter(pos, map_id, layer_id, filter) {
+			return Ok(false);
+		}
+
+		self.remove(pos, map_id, layer_id)?;
+		Ok(true)
+	}
+
+	/// Remove all tiles within the given rectangle (inclusive) that match the given filter
+	///
+	/// Empty coordinates are skipped. All affected chunks are notified once the entire region has
+	/// been handled. Returns the number of removed tiles.
+	///
+	/// # Arguments
+	///
+	/// * `from`: A corner of the rectangle
+	/// * `to`: The opposite corner of the rectangle
+	/// * `map_id`: The tile map
+	/// * `layer_id`: The layer within the tile map
+	/// * `filter`: The filter the tiles must match
+	///
+	/// # Examples
+	///
+	/// ```
+	/// # use bevy_ecs_tilemap::TilePos;
+	/// # use bevy_tileset_map::prelude::{TileFilter, TilePlacer};
+	/// fn drain_lake(mut placer: TilePlacer) {
+	///   let water = TileFilter::Name(String::from("Water"));
+	///   placer.remove_matching_region(TilePos(0, 0), TilePos(15, 15), 0u16, 0u16, &water);
+	/// }
+	/// ```
+	pub fn remove_matching_region<Pos: Into<TilePos>, MId: MapId>(
+		&mut self,
+		from: Pos,
+		to: Pos,
+		map_id: MId,
+		layer_id: u16,
+		filter: &TileFilter,
+	) -> usize {
+		let (from, to) = (from.into(), to.into());
+		self.begin_batch();
+
+		let mut count = 0;
+		for y in from.1.min(to.1)..=from.1.max(to.1) {
+			for x in from.0.min(to.0)..=from.0.max(to.0) {
+				if let Ok(true) = self.remove_matching(TilePos(x, y), map_id, layer_id, filter) {
+					count += 1;
+				}
+			}
+		}
+
+		self.end_batch();
+		count
+	}
+
+	/// Returns true if the tile at the given position matches the given filter
+	fn matches_filter<MId: MapId>(
+		&mut self,
+		pos: TilePos,
+		map_id: MId,
+		layer_id: u16,
+		filter: &TileFilter,
+	) -> bool {
+		let tile_id = if let Some(tile_id) = self.get_tile_id_at(pos, map_id, layer_id) {
+			tile_id
+		} else {
+			return false;
+		};
+
+		self.tilesets
+			.get_by_id(&tile_id.tileset_id)
+			.map(|tileset| filter.matches(&tile_id, tileset))
+			.unwrap_or_default()
+	}
+}
@@ -0,0 +1,111 @@
+use bevy::prelude::Entity;
+use bevy_ecs_tilemap::prelude::{MapId, TilePos};
+
+use crate::flags::TileFlags;
+use crate::placement::{AutoTileAccess, TilePlacementError, TilePlacer};
+
+impl<'w, 's, A: AutoTileAccess> TilePlacer<'w, 's, A> {
+	/// Get the [`TileFlags`] of the tile at the given coordinate
+	///
+	/// Tiles without any flags return an empty set of flags.
+	///
+	/// # Arguments
+	///
+	/// * `pos`: The tile position
+	/// * `map_id`: The tile map
+	/// * `layer_id`: The layer within the tile map
+	///
+	pub fn get_flags<Pos: Into<TilePos>, MId: MapId>(
+		&mut self,
+		pos: Pos,
+		map_id: MId,
+		layer_id: u16,
+	) -> Result<TileFlags, TilePlacementError> {
+		let entity = self
+			.map_query
+			.get_tile_entity(pos.into(), map_id, layer_id)
+			.map_err(TilePlacementError::MapError)?;
+		Ok(self.flags.get(entity).copied().unwrap_or_default())
+	}
+
+	/// Replace the [`TileFlags`] of the tile at the given coordinate
+	///
+	/// # Arguments
+	///
+	/// * `pos`: The tile position
+	/// * `map_id`: The tile map
+	/// * `layer_id`: The layer within the tile map
+	/// * `flags`: The new flags
+	///
+	pub fn set_flags<Pos: Into<TilePos>, MId: MapId>(
+		&mut self,
+		pos: Pos,
+		map_id: MId,
+		layer_id: u16,
+		flags: TileFlags,
+	) -> Result<(), TilePlacementError> {
+		self.check_writable(map_id, layer_id)?;
+		let entity = self
+			.map_query
+			.get_tile_entity(pos.into(), map_id, layer_id)
+			.map_err(TilePlacementError::MapError)?;
+		self.set_tile_flags(entity, flags);
+		Ok(())
+	}
+
+	/// Set the given bits in the [`TileFlags`] of the tile at the given coordinate
+	///
+	/// # Arguments
+	///
+	/// * `pos`: The tile position
+	/// * `map_id`: The tile map
+	/// * `layer_id`: The layer within the tile map
+	/// * `bits`: The bits to set
+	///
+	pub fn insert_flags<Pos: Into<TilePos>, MId: MapId>(
+		&mut self,
+		pos: Pos,
+		map_id: MId,
+		layer_id: u16,
+		bits: u32,
+	) -> Result<(), TilePlacementError> {
+		let pos = pos.into();
+		let mut flags = self.get_flags(pos, map_id, layer_id)?;
+		flags.insert(bits);
+		self.set_flags(pos, map_id, layer_id, flags)
+	}
+
+	/// Clear the given bits in the [`TileFlags`] of the tile at the given coordinate
+	///
+	/// # Arguments
+	///
+	/// * `pos`: The tile position
+	/// * `map_id`: The tile map
+	/// * `layer_id`: The layer within the tile map
+	/// * `bits`: The bits to clear
+	///
+	pub fn remove_flags<Pos: Into<TilePos>, MId: MapId>(
+		&mut self,
+		pos: Pos,
+		map_id: MId,
+		layer_id: u16,
+		bits: u32,
+	) -> Result<(), TilePlacementError> {
+		let pos = pos.into();
+		let mut flags = self.get_flags(pos, map_id, layer_id)?;
+		flags.remove(bits);
+		self.set_flags(pos, map_id, layer_id, flags)
+	}
+
+	/// Replace the [`TileFlags`] of the given tile entity
+	///
+	/// Empty flags remove the tile's [`TileFlags`] entirely.
+	pub fn set_tile_flags(&mut self, entity: Entity, flags: TileFlags) {
+		let mut cmds = self.commands.entity(entity);
+		if flags.is_empty() {
+			cmds.remove::<TileFlags>();
+		} else {
+			cmds.insert(flags);
+		}
+	}
+}
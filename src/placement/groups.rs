@@ -0,0 +1,135 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+use bevy::utils::HashMap;
+use bevy_ecs_tilemap::prelude::{MapId, TilePos};
+use bevy_tileset::prelude::TileId;
+
+use crate::placement::{AutoTileAccess, TilePlacementError, TilePlacementResult, TilePlacer};
+
+/// A resource containing named, weighted groups of tiles
+///
+/// Unlike variants, a tile placed from a group keeps its own identity (i.e. its own [`TileId`]).
+///
+/// This resource is added automatically by the [`TilesetMapPlugin`](crate::prelude::TilesetMapPlugin).
+///
+/// # Examples
+///
+/// ```
+/// # use bevy::prelude::ResMut;
+/// # use bevy_tileset_map::prelude::{TileId, WeightedTileGroups};
+/// fn setup(mut groups: ResMut<WeightedTileGroups>) {
+///   groups.register("meadow", vec![
+///     (TileId::new(0, 0), 9.0), // Grass
+///     (TileId::new(1, 0), 1.0), // Flowers
+///   ]);
+/// }
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct WeightedTileGroups {
+	groups: HashMap<String, Vec<(TileId, f32)>>,
+}
+
+impl WeightedTileGroups {
+	/// Register a group of tiles and their weights, returning the group it replaced (if any)
+	pub fn register<S: Into<String>>(
+		&mut self,
+		name: S,
+		tiles: Vec<(TileId, f32)>,
+	) -> Option<Vec<(TileId, f32)>> {
+		self.groups.insert(name.into(), tiles)
+	}
+
+	/// Unregister the group with the given name
+	pub fn unregister(&mut self, name: &str) -> Option<Vec<(TileId, f32)>> {
+		self.groups.remove(name)
+	}
+
+	/// Get the tiles and weights of the group with the given name
+	pub fn get(&self, name: &str) -> Option<&[(TileId, f32)]> {
+		self.groups.get(name).map(Vec::as_slice)
+	}
+
+	/// Select a tile from the given group using a value in the range `[0, 1)`
+	pub fn select(&self, name: &str, roll: f64) -> Option<TileId> {
+		weighted_pick(self.get(name)?, roll)
+	}
+}
+
+impl<'w, 's, A: AutoTileAccess> TilePlacer<'w, 's, A> {
+	/// Place a random tile from the given [`WeightedTileGroups`] group
+	///
+	/// If a seed is given, the same tile is always chosen for a given seed and position.
+	///
+	/// # Arguments
+	///
+	/// * `group`: The name of the group
+	/// * `pos`: The tile position
+	/// * `map_id`: The tile map
+	/// * `layer_id`: The layer within the tile map
+	/// * `seed`: An optional seed used to make the selection deterministic
+	///
+	/// # Errors
+	///
+	/// Returns [`TilePlacementError::InvalidGroup`] if the group doesn't exist or contains no
+	/// tiles with a positive weight.
+	///
+	pub fn place_random_from_group<Pos: Into<TilePos>, MId: MapId>(
+		&mut self,
+		group: &str,
+		pos: Pos,
+		map_id: MId,
+		layer_id: u16,
+		seed: Option<u64>,
+	) -> TilePlacementResult {
+		let pos = pos.into();
+		let roll = match seed {
+			Some(seed) => roll(pos, seed),
+			None => roll(pos, RandomState::new().build_hasher().finish()),
+		};
+
+		let tile_id = self
+			.resources
+			.groups
+			.select(group, roll)
+			.ok_or_else(|| TilePlacementError::InvalidGroup(group.to_string()))?;
+		self.place(tile_id, pos, map_id, layer_id)
+	}
+}
+
+/// Select a tile from the given weighted set using a value in the range `[0, 1)`
+///
+/// Tiles with a non-positive weight are never selected.
+pub(crate) fn weighted_pick<T: Copy>(tiles: &[(T, f32)], roll: f64) -> Option<T> {
+	let total: f64 = tiles.iter().map(|(_, weight)| weight.max(0.0) as f64).sum();
+	if total <= 0.0 {
+		return None;
+	}
+
+	let mut target = roll * total;
+	for (tile_id, weight) in tiles {
+		let weight = weight.max(0.0) as f64;
+		if target < weight {
+			return Some(*tile_id);
+		}
+		target -= weight;
+	}
+
+	// Guard against rounding errors
+	tiles
+		.iter()
+		.rev()
+		.find(|(_, weight)| *weight > 0.0)
+		.map(|(tile_id, _)| *tile_id)
+}
+
+/// A deterministic pseudo-random value in the range `[0, 1)` for the given position and seed
+pub(crate) fn roll(pos: TilePos, seed: u64) -> f64 {
+	let mut hash = (pos.0 as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+		^ (pos.1 as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F)
+		^ seed.wrapping_mul(0x1656_67B1_9E37_79F9);
+	hash ^= hash >> 33;
+	hash = hash.wrapping_mul(0xFF51_AFD7_ED55_8CCD);
+	hash ^= hash >> 33;
+	(hash >> 11) as f64 / (1u64 << 53) as f64
+}
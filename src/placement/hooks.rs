@@ -0,0 +1,149 @@
+//! Hooks for inserting custom components onto placed tiles
+
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::{Bundle, Entity};
+use bevy::utils::HashMap;
+use bevy_tileset::prelude::{TileGroupId, TileId, Tileset, TilesetId};
+
+/// The signature of a placement hook
+pub type TilePlacementHookFn =
+	dyn Fn(&mut EntityCommands<'_, '_, '_>, &TileHookContext<'_>) + Send + Sync + 'static;
+
+/// Information about the tile a placement hook is being run for
+pub struct TileHookContext<'a> {
+	/// The placed tile entity
+	pub entity: Entity,
+	/// The ID of the placed tile
+	pub tile_id: TileId,
+	/// The tileset the placed tile belongs to
+	pub tileset: &'a Tileset,
+}
+
+/// A resource containing hooks that are run by the [`TilePlacer`](crate::prelude::TilePlacer)
+/// on each placed tile entity
+///
+/// This allows tiles to automatically receive game-specific components (colliders, doors, spawners, etc.)
+/// without having to post-process newly added tiles.
+///
+/// Hooks are only run when a tile is placed. If a tile is replaced or updated, any components that were
+/// inserted by a previous hook are _not_ removed automatically.
+///
+/// This resource is added automatically by the [`TilesetMapPlugin`](crate::prelude::TilesetMapPlugin).
+///
+/// # Examples
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_tileset_map::prelude::TilePlacementHooks;
+/// #[derive(Component, Clone)]
+/// struct Door;
+///
+/// fn setup(mut hooks: ResMut<TilePlacementHooks>) {
+///   hooks.insert_on_name("My Tileset", "Door", (Door,));
+///   hooks.on_any(|cmds, ctx| {
+///     println!("Placed {:?} on {:?}", ctx.tile_id, cmds.id());
+///   });
+/// }
+/// ```
+#[derive(Default)]
+pub struct TilePlacementHooks {
+	by_group: HashMap<(TilesetId, TileGroupId), Vec<Box<TilePlacementHookFn>>>,
+	by_name: HashMap<String, HashMap<String, Vec<Box<TilePlacementHookFn>>>>,
+	any: Vec<Box<TilePlacementHookFn>>,
+}
+
+impl TilePlacementHooks {
+	/// Register a hook that runs for all tiles in the same tile group as the given [`TileId`]
+	pub fn on_group<F>(&mut self, tile_id: TileId, hook: F) -> &mut Self
+	where
+		F: Fn(&mut EntityCommands<'_, '_, '_>, &TileHookContext<'_>) + Send + Sync + 'static,
+	{
+		self.by_group
+			.entry((tile_id.tileset_id, tile_id.group_id))
+			.or_default()
+			.push(Box::new(hook));
+		self
+	}
+
+	/// Register a hook that runs for the tile with the given name in the given tileset
+	pub fn on_name<S, T, F>(&mut self, tileset_name: S, tile_name: T, hook: F) -> &mut Self
+	where
+		S: Into<String>,
+		T: Into<String>,
+		F: Fn(&mut EntityCommands<'_, '_, '_>, &TileHookContext<'_>) + Send + Sync + 'static,
+	{
+		self.by_name
+			.entry(tileset_name.into())
+			.or_insert_with(HashMap::default)
+			.entry(tile_name.into())
+			.or_insert_with(Vec::new)
+			.push(Box::new(hook));
+		self
+	}
+
+	/// Register a hook that runs for every placed tile
+	pub fn on_any<F>(&mut self, hook: F) -> &mut Self
+	where
+		F: Fn(&mut EntityCommands<'_, '_, '_>, &TileHookContext<'_>) + Send + Sync + 'static,
+	{
+		self.any.push(Box::new(hook));
+		self
+	}
+
+	/// Insert a copy of the given bundle on all tiles in the same tile group as the given [`TileId`]
+	pub fn insert_on_group<B: Bundle + Clone>(&mut self, tile_id: TileId, bundle: B) -> &mut Self {
+		self.on_group(tile_id, move |cmds, _| {
+			cmds.insert_bundle(bundle.clone());
+		})
+	}
+
+	/// Insert a copy of the given bundle on the tile with the given name in the given tileset
+	pub fn insert_on_name<S, T, B>(&mut self, tileset_name: S, tile_name: T, bundle: B) -> &mut Self
+	where
+		S: Into<String>,
+		T: Into<String>,
+		B: Bundle + Clone,
+	{
+		self.on_name(tileset_name, tile_name, move |cmds, _| {
+			cmds.insert_bundle(bundle.clone());
+		})
+	}
+
+	/// Remove all hooks registered for the tile group of the given [`TileId`]
+	pub fn clear_group(&mut self, tile_id: &TileId) {
+		self.by_group
+			.remove(&(tile_id.tileset_id, tile_id.group_id));
+	}
+
+	/// Remove all hooks registered for the given tile name
+	pub fn clear_name(&mut self, tileset_name: &str, tile_name: &str) {
+		if let Some(tiles) = self.by_name.get_mut(tileset_name) {
+			tiles.remove(tile_name);
+		}
+	}
+
+	/// Returns true if no hooks have been registered
+	pub fn is_empty(&self) -> bool {
+		self.any.is_empty() && self.by_group.is_empty() && self.by_name.is_empty()
+	}
+
+	/// Run all hooks matching the given context
+	pub(crate) fn run(&self, cmds: &mut EntityCommands, ctx: &TileHookContext) {
+		if self.is_empty() {
+			return;
+		}
+
+		let group_key = (ctx.tile_id.tileset_id, ctx.tile_id.group_id);
+		let by_group = self.by_group.get(&group_key).into_iter().flatten();
+		let by_name = ctx
+			.tileset
+			.get_tile_name(&ctx.tile_id.group_id)
+			.and_then(|tile_name| self.by_name.get(ctx.tileset.name())?.get(tile_name))
+			.into_iter()
+			.flatten();
+
+		for hook in self.any.iter().chain(by_group).chain(by_name) {
+			hook(cmds, ctx);
+		}
+	}
+}
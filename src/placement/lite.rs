@@ -0,0 +1,124 @@
+use bevy::ecs::query::WorldQuery;
+#[cfg(feature = "auto-tile")]
+use bevy::ecs::system::Command;
+#[cfg(feature = "auto-tile")]
+use bevy::prelude::*;
+#[cfg(feature = "auto-tile")]
+use bevy_ecs_tilemap::{Tile, TileParent, TilePos};
+#[cfg(feature = "auto-tile")]
+use bevy_tileset::auto::AutoTileId;
+
+#[cfg(feature = "auto-tile")]
+use crate::auto::{AutoTileMasked, RemoveAutoTileEvent};
+use crate::placement::TilePlacer;
+
+/// A [`TilePlacer`] that never accesses Auto tile components
+///
+/// Auto tiles placed with it are placed as their default texture and never auto tiled, and
+/// removing (or replacing) existing Auto tiles does not update their neighbors. In exchange, it
+/// skips the Auto tile lookups made on every placement and removal.
+///
+/// This only matters when the `auto-tile` feature is enabled. Otherwise, it's identical to the
+/// regular `TilePlacer`.
+///
+/// Since it doesn't query Auto tile components, systems using it can run in parallel with
+/// systems that mutate those components. Placers only read the shared placement resources, but
+/// they still can't run in parallel with each other (or with the auto tiling systems), since
+/// they all need mutable access to the tilemap through the `MapQuery`.
+///
+/// # Examples
+///
+/// ```
+/// # use bevy_ecs_tilemap::TilePos;
+/// # use bevy_tileset_map::prelude::{TileId, TilePlacerLite};
+/// fn place_decoration(mut placer: TilePlacerLite) {
+///   placer.place(TileId::new(0, 0), TilePos(0, 0), 0u16, 1u16).ok();
+/// }
+/// ```
+pub type TilePlacerLite<'w, 's> = TilePlacer<'w, 's, NoAutoTiles>;
+
+/// Determines whether a [`TilePlacer`] handles Auto tiles
+///
+/// This is implemented by [`AutoTiles`] (used by the regular `TilePlacer`) and [`NoAutoTiles`]
+/// (used by the [`TilePlacerLite`]).
+pub trait AutoTileAccess: Send + Sync + 'static {
+	/// The components queried to find Auto tiles
+	type Query: WorldQuery;
+
+	/// Whether placed Auto tiles are auto tiled
+	const ENABLED: bool;
+
+	/// Get the event needed to remove the given tile if it is an Auto tile
+	#[cfg(feature = "auto-tile")]
+	fn find(query: &Query<Self::Query, With<Tile>>, entity: Entity) -> Option<RemoveAutoTileEvent>;
+}
+
+/// Handle Auto tiles when placing and removing tiles
+#[derive(Debug, Copy, Clone, Default)]
+pub struct AutoTiles;
+
+/// Treat Auto tiles like any other tile, without accessing their components
+#[derive(Debug, Copy, Clone, Default)]
+pub struct NoAutoTiles;
+
+#[cfg(feature = "auto-tile")]
+impl AutoTileAccess for AutoTiles {
+	type Query = (
+		&'static TilePos,
+		&'static TileParent,
+		Option<&'static AutoTileId>,
+		Option<&'static AutoTileMasked>,
+	);
+
+	const ENABLED: bool = true;
+
+	fn find(query: &Query<Self::Query, With<Tile>>, entity: Entity) -> Option<RemoveAutoTileEvent> {
+		let (pos, parent, auto_id, masked) = query.get(entity).ok()?;
+		// Masked tiles affect their neighbors even if they aren't Auto tiles themselves
+		let auto_id = auto_id.or_else(|| masked.map(|masked| &masked.auto_id))?;
+		Some(RemoveAutoTileEvent {
+			entity,
+			pos: *pos,
+			parent: *parent,
+			auto_id: *auto_id,
+		})
+	}
+}
+
+#[cfg(not(feature = "auto-tile"))]
+impl AutoTileAccess for AutoTiles {
+	type Query = ();
+
+	const ENABLED: bool = false;
+}
+
+impl AutoTileAccess for NoAutoTiles {
+	type Query = ();
+
+	const ENABLED: bool = false;
+
+	#[cfg(feature = "auto-tile")]
+	fn find(_: &Query<Self::Query, With<Tile>>, _: Entity) -> Option<RemoveAutoTileEvent> {
+		None
+	}
+}
+
+/// A command sending a [`RemoveAutoTileEvent`]
+///
+/// Sending it through a command means the `TilePlacer` doesn't need to access the events. Since
+/// commands are applied at the end of the stage, removals made before the
+/// [`TilesetMapStage`](crate::prelude::TilesetMapStage) still update their neighbors within the
+/// same frame.
+#[cfg(feature = "auto-tile")]
+pub(super) struct SendRemoveAutoTile(pub RemoveAutoTileEvent);
+
+#[cfg(feature = "auto-tile")]
+impl Command for SendRemoveAutoTile {
+	fn write(self, world: &mut World) {
+		if let Some(mut events) =
+			world.get_resource_mut::<bevy::ecs::event::Events<RemoveAutoTileEvent>>()
+		{
+			events.send(self.0);
+		}
+	}
+}
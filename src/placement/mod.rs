@@ -0,0 +1,1395 @@
+//! Tools for placing and removing tiles
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::*;
+use bevy_tileset::prelude::*;
+use thiserror::Error;
+
+use crate::collision::TileCollision;
+use crate::elevation::{ElevatedVisibility, TileElevation, ELEVATION_METADATA_KEY};
+use crate::flags::TileFlags;
+use crate::metadata::TileMetadata;
+use crate::ownership::TileOwner;
+use crate::remesh::{ChunkBatch, ChunkNotifications};
+use crate::transitions::TileTransition;
+
+mod animation;
+mod brush;
+mod builder;
+mod elevation;
+mod filter;
+mod flags;
+mod groups;
+mod hooks;
+mod lite;
+mod options;
+mod ownership;
+mod policy;
+mod preview;
+mod relocate;
+mod resources;
+mod rules;
+mod shapes;
+mod structure;
+mod template;
+mod transaction;
+mod transform;
+mod transition;
+mod visibility;
+
+#[cfg(feature = "auto-tile")]
+pub(crate) use animation::dephase_speed;
+pub use animation::{AnimationState, ANIMATION_DEPHASE_METADATA_KEY};
+pub use brush::*;
+pub use builder::*;
+pub use filter::TileFilter;
+pub use groups::WeightedTileGroups;
+#[cfg(feature = "auto-tile")]
+pub(crate) use groups::{roll, weighted_pick};
+pub use hooks::*;
+pub use lite::{AutoTileAccess, AutoTiles, NoAutoTiles, TilePlacerLite};
+pub use options::*;
+pub use policy::{LayerPermissions, LayerPolicy, PolicyViolation};
+#[cfg(not(feature = "headless"))]
+pub(crate) use preview::sync_preview_overlays;
+pub use preview::{PlacementPreview, PreviewOverlay, TilePreviewer};
+pub use rules::{PlacementRuleContext, PlacementRuleFn, PlacementRules};
+pub use structure::Structure;
+pub use transaction::TileTransaction;
+pub use transform::TileTransform;
+
+/// Errors related to the placement of tiles
+#[derive(Error, Debug)]
+pub enum TilePlacementError {
+	/// A tile already exists at the given coordinate
+	///
+	/// The meaning of this changes a bit depending on the context. For example, it could
+	/// mean that an unexpected tile was found or that _any_ tile was found.
+	#[error("Attempted to place tile {new:?} but found existing tile {existing:?} (@ {pos:?})")]
+	TileAlreadyExists {
+		/// The ID of the new tile to be placed
+		new: TileId,
+		/// The ID of the existing tile
+		existing: Option<TileId>,
+		/// The desired/occupied tile coordinate
+		pos: TilePos,
+	},
+	/// The tileset does not exist or is invalid
+	///
+	/// Contains the ID of the tileset in question
+	#[error("Invalid tileset {0:?}")]
+	InvalidTileset(TilesetId),
+	/// The tile does not exist or is invalid
+	///
+	/// This can happen when a given [`TileId`] doesn't exist within a tileset
+	///
+	/// Contains the ID of the tile in question
+	#[error("Invalid tile {0:?}")]
+	InvalidTile(TileId),
+	/// No tile with the given name exists in the tileset with the given name
+	#[error("Invalid tile {tile:?} in tileset {tileset:?}")]
+	InvalidTileName { tileset: String, tile: String },
+	/// A saved tile refers to a name that is missing from the name table of its tilemap
+	///
+	/// Contains the index of the missing name
+	#[error("Invalid tile name index {0}")]
+	InvalidTileNameIndex(usize),
+	/// The weighted tile group does not exist or contains no selectable tiles
+	///
+	/// Contains the name of the group in question
+	#[error("Invalid tile group {0:?}")]
+	InvalidGroup(String),
+	/// The map is not configured for elevation or does not have the given level
+	#[error("Invalid level {level} in map {map_id}")]
+	InvalidLevel { map_id: u16, level: u16 },
+	/// The tile position lies outside the layer
+	#[error("Position {pos:?} is outside of the map (size: {map_size:?})")]
+	OutOfBounds {
+		/// The desired tile coordinate
+		pos: TilePos,
+		/// The size of the layer (in tiles)
+		map_size: UVec2,
+	},
+	/// The region of the infinite map containing the tile has not been spawned
+	///
+	/// Contains the position of the region (in regions)
+	#[error("Region {0:?} is not loaded")]
+	UnloadedRegion(IVec2),
+	/// The placement was rejected by the [`LayerPolicy`] of the layer
+	#[error("Layer {layer_id} in map {map_id} does not allow this edit: {violation:?}")]
+	PolicyViolation {
+		map_id: u16,
+		layer_id: u16,
+		violation: PolicyViolation,
+	},
+	/// The placement was rejected by a registered [`PlacementRules`] rule
+	#[error("Placing {tile:?} at {pos:?} violates rule {rule:?}")]
+	RuleViolation {
+		/// The name of the violated rule
+		rule: String,
+		/// The ID of the tile that was to be placed
+		tile: TileId,
+		/// The desired tile coordinate
+		pos: TilePos,
+	},
+	/// The tile is owned by someone else
+	#[error("Tile at {pos:?} is owned by {owner:?}")]
+	NotOwner {
+		/// The tile coordinate
+		pos: TilePos,
+		/// The owner of the tile
+		owner: TileOwner,
+	},
+	/// The tile is not animated
+	///
+	/// Contains the tile entity in question
+	#[error("Tile {0:?} is not animated")]
+	NotAnimated(Entity),
+	/// The tileset does not match the texture of the layer
+	///
+	/// All tiles in a layer are rendered using a single texture, so tiles from a tileset with a
+	/// different texture would display the wrong images. Place them in a separate layer instead.
+	#[error(
+		"Tileset {tileset_id:?} does not match the texture of layer {layer_id} in map {map_id}"
+	)]
+	TilesetMismatch {
+		/// The tileset of the tile that was to be placed
+		tileset_id: TilesetId,
+		map_id: u16,
+		layer_id: u16,
+	},
+	/// A tile lies within the footprint of a [`Structure`] that was to be placed
+	#[error(
+		"Footprint of structure is blocked by tile {existing:?} (@ {pos:?} in layer {layer_id})"
+	)]
+	FootprintBlocked {
+		/// The blocking tile (if it belongs to a loaded tileset)
+		existing: Option<TileId>,
+		/// The occupied tile coordinate
+		pos: TilePos,
+		layer_id: u16,
+	},
+	/// A template character is not mapped to any tile
+	///
+	/// Contains the character in question
+	#[error("Template character {0:?} has no legend entry")]
+	UnmappedTemplateChar(char),
+	/// A catch-all for errors generated by `bevy_ecs_tilemap`
+	///
+	/// Contains the generated error
+	#[error("Tilemap error: {0:?}")]
+	MapError(MapTileError),
+}
+
+/// An enum denoting how a tile was placed or removed
+///
+/// This allows you to respond to the results the placement, such as handling cleanup
+/// or performing a secondary action.
+///
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum PlacedTile {
+	/// A tile was added
+	Added {
+		/// The replaced tile
+		old_tile: Option<(Entity, Option<TileId>)>,
+		/// The placed tile
+		new_tile: (Entity, TileId),
+	},
+	/// A tile was removed
+	Removed {
+		/// The removed tile
+		old_tile: Option<(Entity, Option<TileId>)>,
+	},
+}
+
+/// A result type alias for tile placement
+pub type TilePlacementResult = Result<PlacedTile, TilePlacementError>;
+
+/// A helper system param used to place tiles
+///
+/// All methods automatically account for the tile's [`TileType`] and respects Auto Tiles,
+/// allowing for a much simpler user experience. Systems that never use Auto tiles can use the
+/// [`TilePlacerLite`] instead to skip looking them up.
+///
+/// Additionally, tilesets are automatically derived from the given [`TileId`]s. This works for
+/// any [`Tileset`] registered in `Assets<Tileset>`.
+///
+/// # Examples
+///
+/// ```
+/// # use bevy::prelude::Res;
+/// # use bevy_ecs_tilemap::TilePos;
+/// # use bevy_tileset_map::prelude::{TileId, TilePlacer};
+/// struct CurrentTile(TileId);
+///
+/// fn tile_placement_system(mut placer: TilePlacer, tile: Res<CurrentTile>) {
+///   placer.place(
+///     tile.0,
+///     TilePos(0, 0),
+///     0u16,
+///     0u16,
+///   ).unwrap();
+/// }
+/// ```
+#[derive(SystemParam)]
+pub struct TilePlacer<'w, 's, A: AutoTileAccess = AutoTiles> {
+	map_query: MapQuery<'w, 's>,
+	tilesets: Tilesets<'w, 's>,
+	commands: Commands<'w, 's>,
+	resources: resources::PlacementResources<'w, 's>,
+	notifications: Res<'w, ChunkNotifications>,
+	/// Chunk notifications collected by [`Self::begin_batch`]
+	batch: Local<'s, ChunkBatch>,
+	/// Query used to get the tileset of a tile
+	tileset_parents: Query<'w, 's, &'static TilesetParent>,
+	/// Query used to get the flags of a tile
+	flags: Query<'w, 's, &'static TileFlags>,
+	/// Query used to get the owner of a tile
+	owners: Query<'w, 's, &'static TileOwner>,
+	/// Query used to get the position, paused animation, and layer of a tile
+	animations: Query<
+		'w,
+		's,
+		(
+			Option<&'static TilePos>,
+			Option<&'static animation::PausedAnimation>,
+			Option<&'static TileParent>,
+		),
+	>,
+	/// Query used to get info about a tile
+	#[allow(dead_code)]
+	query: Query<
+		'w,
+		's,
+		(
+			&'static Tile,
+			Option<&'static GPUAnimated>,
+			Option<&'static ElevatedVisibility>,
+		),
+	>,
+	/// Query used to find Auto tiles and the data for their [`RemoveAutoTileEvent`]
+	///
+	/// [`RemoveAutoTileEvent`]: crate::prelude::RemoveAutoTileEvent
+	#[cfg(feature = "auto-tile")]
+	#[allow(dead_code)]
+	auto_query: Query<'w, 's, A::Query, With<Tile>>,
+	/// The owner edits are made on behalf of (see [`Self::act_as`])
+	#[system_param(ignore)]
+	acting_owner: Option<TileOwner>,
+	#[system_param(ignore)]
+	marker: std::marker::PhantomData<A>,
+}
+
+impl<'w, 's, A: AutoTileAccess> TilePlacer<'w, 's, A> {
+	/// Place a tile
+	///
+	/// This will remove and overwrite any tile beneath it, whether it matches this one or not,
+	/// unless overwriting is disabled for the map in the [`TilesetMapConfig`](crate::prelude::TilesetMapConfig).
+	/// The placement must still be allowed by all [`PlacementRules`].
+	///
+	/// # Arguments
+	///
+	/// * `tile_id`: The full ID of the tile to place
+	/// * `pos`: The tile position
+	/// * `map_id`: The tile map
+	/// * `layer_id`: The layer within the tile map
+	///
+	pub fn place<Id: Into<TileId>, Pos: Into<TilePos> + Clone, MId: MapId>(
+		&mut self,
+		tile_id: Id,
+		pos: Pos,
+		map_id: MId,
+		layer_id: u16,
+	) -> TilePlacementResult {
+		let id = tile_id.into();
+		let pos = pos.into();
+		self.check_overwrite(id, pos, map_id, layer_id)?;
+		self.check_rules(id, pos, map_id, layer_id)?;
+		self.place_unchecked(id, pos, map_id, layer_id)
+	}
+
+	/// Place a tile in the default layer of the given map
+	///
+	/// The default layer is set in the [`TilesetMapConfig`](crate::prelude::TilesetMapConfig).
+	/// Otherwise, this behaves just like [`place`](Self::place).
+	///
+	/// # Arguments
+	///
+	/// * `tile_id`: The full ID of the tile to place
+	/// * `pos`: The tile position
+	/// * `map_id`: The tile map
+	///
+	pub fn place_default<Id: Into<TileId>, Pos: Into<TilePos> + Clone, MId: MapId>(
+		&mut self,
+		tile_id: Id,
+		pos: Pos,
+		map_id: MId,
+	) -> TilePlacementResult {
+		let layer_id = self.resources.config.get(map_id.into()).default_layer;
+		self.place(tile_id, pos, map_id, layer_id)
+	}
+
+	/// Place a tile with the given options
+	///
+	/// This will remove and overwrite any tile beneath it, whether it matches this one or not,
+	/// unless overwriting is disabled for the map in the [`TilesetMapConfig`](crate::prelude::TilesetMapConfig).
+	/// The placement must still be allowed by all [`PlacementRules`].
+	///
+	/// # Arguments
+	///
+	/// * `tile_id`: The full ID of the tile to place
+	/// * `pos`: The tile position
+	/// * `map_id`: The tile map
+	/// * `layer_id`: The layer within the tile map
+	/// * `options`: The color and flip flags of the placed tile
+	///
+	pub fn place_with<Id: Into<TileId>, Pos: Into<TilePos> + Clone, MId: MapId>(
+		&mut self,
+		tile_id: Id,
+		pos: Pos,
+		map_id: MId,
+		layer_id: u16,
+		options: TilePlacementOptions,
+	) -> TilePlacementResult {
+		let id = tile_id.into();
+		let pos = pos.into();
+		self.check_overwrite(id, pos, map_id, layer_id)?;
+		self.check_rules(id, pos, map_id, layer_id)?;
+		self.place_unchecked_with(id, pos, map_id, layer_id, &options)
+	}
+
+	/// Place a tile only if the coordinate is not already occupied and the placement is allowed by
+	/// all [`PlacementRules`]
+	///
+	/// # Arguments
+	///
+	/// * `tile_id`: The full ID of the tile to place
+	/// * `pos`: The tile position
+	/// * `map_id`: The tile map
+	/// * `layer_id`: The layer within the tile map
+	///
+	/// # Errors
+	///
+	/// While this method can return any kind of error from [`TilePlacementError`], the one
+	/// to look out for is [`TilePlacementError::TileAlreadyExists`] as it denotes that the
+	/// given coordinate is already occupied and that the tile could not be placed. Similarly,
+	/// [`TilePlacementError::RuleViolation`] denotes that a rule did not allow the placement.
+	///
+	/// You can use this to respond appropriately on a failed placement.
+	///
+	pub fn try_place<Id: Into<TileId>, Pos: Into<TilePos> + Clone, MId: MapId>(
+		&mut self,
+		tile_id: Id,
+		pos: Pos,
+		map_id: MId,
+		layer_id: u16,
+	) -> TilePlacementResult {
+		let id = tile_id.into();
+		let pos = pos.into();
+
+		if let Some(existing) = self.get_existing(id, pos, map_id, layer_id) {
+			// Tile already exists -> don't place
+			return Err(TilePlacementError::TileAlreadyExists {
+				new: id,
+				existing: existing.id,
+				pos,
+			});
+		}
+
+		self.check_rules(id, pos, map_id, layer_id)?;
+		self.place_unchecked(id, pos, map_id, layer_id)
+	}
+
+	/// Check whether [`try_place`](Self::try_place) would succeed without placing anything
+	///
+	/// This runs the same checks (layer policy, tileset validity, bounds, occupancy, and
+	/// [`PlacementRules`]), making it cheap enough to gray out invalid placements every frame.
+	///
+	/// # Arguments
+	///
+	/// * `tile_id`: The full ID of the tile to check
+	/// * `pos`: The tile position
+	/// * `map_id`: The tile map
+	/// * `layer_id`: The layer within the tile map
+	///
+	/// # Errors
+	///
+	/// Returns the error `try_place` would have returned.
+	///
+	pub fn can_place<Id: Into<TileId>, Pos: Into<TilePos>, MId: MapId>(
+		&mut self,
+		tile_id: Id,
+		pos: Pos,
+		map_id: MId,
+		layer_id: u16,
+	) -> Result<(), TilePlacementError> {
+		let id = tile_id.into();
+		let pos = pos.into();
+
+		self.check_placeable(id, pos, map_id, layer_id)?;
+
+		if let Some(existing) = self.get_existing(id, pos, map_id, layer_id) {
+			return Err(TilePlacementError::TileAlreadyExists {
+				new: id,
+				existing: existing.id,
+				pos,
+			});
+		}
+
+		self.check_rules(id, pos, map_id, layer_id)
+	}
+
+	/// Get the size of the given layer (in tiles)
+	///
+	/// Valid tile positions range from `(0, 0)` up to (but excluding) the returned size.
+	///
+	/// Returns `None` if the layer does not exist.
+	///
+	/// # Arguments
+	///
+	/// * `map_id`: The tile map
+	/// * `layer_id`: The layer within the tile map
+	///
+	pub fn map_bounds<MId: MapId>(&mut self, map_id: MId, layer_id: u16) -> Option<UVec2> {
+		self.map_query
+			.get_layer(map_id, layer_id)
+			.map(|(_, layer)| {
+				UVec2::new(
+					layer.settings.map_size.0 * layer.settings.chunk_size.0,
+					layer.settings.map_size.1 * layer.settings.chunk_size.1,
+				)
+			})
+	}
+
+	/// Places a tile if the coordinate is not already occupied or if the existing tile does not
+	/// match this one
+	///
+	/// Just like with [`place`](Self::place), the existing tile is only overwritten if the map
+	/// allows it, and the placement must be allowed by all [`PlacementRules`].
+	///
+	/// # Arguments
+	///
+	/// * `tile_id`: The full ID of the tile to place
+	/// * `pos`: The tile position
+	/// * `map_id`: The tile map
+	/// * `layer_id`: The layer within the tile map
+	///
+	/// # Errors
+	///
+	/// While this method can return any kind of error from [`TilePlacementError`], the one
+	/// to look out for is [`TilePlacementError::TileAlreadyExists`] as it denotes that the
+	/// given coordinate is already occupied by a matching tile and could not be replaced.
+	///
+	pub fn replace<Id: Into<TileId>, Pos: Into<TilePos> + Clone, MId: MapId>(
+		&mut self,
+		tile_id: Id,
+		pos: Pos,
+		map_id: MId,
+		layer_id: u16,
+	) -> TilePlacementResult {
+		let id = tile_id.into();
+		let pos = pos.into();
+
+		if let Some(existing) = self.get_existing(id, pos, map_id, layer_id) {
+			// Check that the existing tile is of a different type
+			if let Some(existing_id) = existing.id {
+				if existing_id.eq_tile_group(&id) {
+					return Err(TilePlacementError::TileAlreadyExists {
+						new: id,
+						existing: existing.id,
+						pos,
+					});
+				}
+			}
+		}
+
+		self.check_overwrite(id, pos, map_id, layer_id)?;
+		self.check_rules(id, pos, map_id, layer_id)?;
+		self.place_unchecked(id, pos, map_id, layer_id)
+	}
+
+	/// Places a tile if the coordinate is not already occupied, otherwise, if the existing tile matches
+	/// this one, remove it
+	///
+	/// The placement must be allowed by all [`PlacementRules`].
+	///
+	/// # Arguments
+	///
+	/// * `tile_id`: The full ID of the tile to place
+	/// * `pos`: The tile position
+	/// * `map_id`: The tile map
+	/// * `layer_id`: The layer within the tile map
+	///
+	/// # Errors
+	///
+	/// While this method can return any kind of error from [`TilePlacementError`], the one
+	/// to look out for is [`TilePlacementError::TileAlreadyExists`] as it denotes that the
+	/// given coordinate is already occupied by a _non_-matching tile and could not be placed.
+	///
+	pub fn toggle_matching<Id: Into<TileId>, Pos: Into<TilePos> + Clone, MId: MapId>(
+		&mut self,
+		tile_id: Id,
+		pos: Pos,
+		map_id: MId,
+		layer_id: u16,
+	) -> TilePlacementResult {
+		let id = tile_id.into();
+		let pos = pos.into();
+
+		if let Some(existing) = self.get_existing(id, pos, map_id, layer_id) {
+			// Remove the existing tile if it matches
+			if let Some(existing_id) = existing.id {
+				if existing_id.eq_tile_group(&id) {
+					self.remove(pos, map_id, layer_id)?;
+					return Ok(PlacedTile::Removed {
+						old_tile: Some((existing.entity, Some(existing_id))),
+					});
+				}
+			}
+
+			// Tile exists but did not match -> don't remove
+			return Err(TilePlacementError::TileAlreadyExists {
+				new: id,
+				existing: existing.id,
+				pos,
+			});
+		}
+
+		self.check_overwrite(id, pos, map_id, layer_id)?;
+		self.check_rules(id, pos, map_id, layer_id)?;
+		self.place_unchecked(id, pos, map_id, layer_id)
+	}
+
+	/// Places a tile if the coordinate is not already occupied, otherwise removes the existing tile
+	///
+	/// The placement must be allowed by all [`PlacementRules`].
+	///
+	/// # Arguments
+	///
+	/// * `tile_id`: The full ID of the tile to place
+	/// * `pos`: The tile position
+	/// * `map_id`: The tile map
+	/// * `layer_id`: The layer within the tile map
+	///
+	pub fn toggle<Id: Into<TileId>, Pos: Into<TilePos> + Clone, MId: MapId>(
+		&mut self,
+		tile_id: Id,
+		pos: Pos,
+		map_id: MId,
+		layer_id: u16,
+	) -> TilePlacementResult {
+		let id = tile_id.into();
+		let pos = pos.into();
+
+		if let Some(existing) = self.get_existing(id, pos, map_id, layer_id) {
+			self.remove(pos, map_id, layer_id)?;
+			return Ok(PlacedTile::Removed {
+				old_tile: Some((existing.entity, existing.id)),
+			});
+		}
+
+		self.check_overwrite(id, pos, map_id, layer_id)?;
+		self.check_rules(id, pos, map_id, layer_id)?;
+		self.place_unchecked(id, pos, map_id, layer_id)
+	}
+
+	/// Removes the tile at the given coordinate
+	///
+	/// This method is preferred over handling the removal manually as it will also account for
+	/// any Auto Tiles and handle their removal accordingly.
+	///
+	/// # Arguments
+	///
+	/// * `pos`: The tile position
+	/// * `map_id`: The tile map
+	/// * `layer_id`: The layer within the tile map
+	///
+	pub fn remove<Pos: Into<TilePos>, MId: MapId>(
+		&mut self,
+		pos: Pos,
+		map_id: MId,
+		layer_id: u16,
+	) -> Result<(), TilePlacementError> {
+		self.check_writable(map_id, layer_id)?;
+		let pos = pos.into();
+		self.check_bounds(pos, map_id, layer_id)?;
+		self.check_edit(None, pos, map_id, layer_id)?;
+		self.remove_unchecked(pos, map_id, layer_id)
+	}
+
+	/// Remove the tile at the given coordinate without checking its owner
+	pub(crate) fn remove_unchecked<MId: MapId>(
+		&mut self,
+		pos: TilePos,
+		map_id: MId,
+		layer_id: u16,
+	) -> Result<(), TilePlacementError> {
+		#[cfg(feature = "auto-tile")]
+		{
+			// Get the current tile entity
+			let entity = self
+				.map_query
+				.get_tile_entity(pos, map_id, layer_id)
+				.map_err(TilePlacementError::MapError)?;
+
+			// Attempt to remove the auto tile
+			self.try_remove_auto_tile(entity);
+		}
+
+		// Despawn the tile and notify the chunk
+		self.map_query
+			.despawn_tile(&mut self.commands, pos, map_id, layer_id)
+			.map_err(TilePlacementError::MapError)?;
+		self.notify_chunk(pos, map_id, layer_id);
+		Ok(())
+	}
+
+	/// Adds a tile to the given `LayerBuilder`
+	///
+	/// This is used to initialize the tilemap layer _before_ it becomes accessible via queries
+	///
+	/// # Arguments
+	///
+	/// * `tile_id`: The full ID of the tile to place
+	/// * `pos`: The tile position
+	/// * `layer_builder`: The layer builder
+	///
+	pub fn add_to_layer<TId: Into<TileId>, Pos: Into<TilePos>>(
+		&mut self,
+		tile_id: TId,
+		pos: Pos,
+		layer_builder: &mut LayerBuilder<TileBundle>,
+	) -> TilePlacementResult {
+		let id = tile_id.into();
+		let pos = pos.into();
+		let tileset_id = self.get_tileset_id(&id)?;
+		let tile_index = self.get_tile_index(&id)?;
+
+		let entity = match tile_index {
+			TileIndex::Standard(index) => {
+				layer_builder
+					.set_tile(
+						pos,
+						Tile {
+							texture_index: index as u16,
+							..Default::default()
+						}
+						.into(),
+					)
+					.map_err(TilePlacementError::MapError)?;
+				layer_builder
+					.get_tile_entity(&mut self.commands, pos)
+					.map_err(TilePlacementError::MapError)?
+			},
+			TileIndex::Animated(start, end, speed) => {
+				layer_builder
+					.set_tile(
+						pos,
+						Tile {
+							texture_index: start as u16,
+							..Default::default()
+						}
+						.into(),
+					)
+					.map_err(TilePlacementError::MapError)?;
+				let entity = layer_builder
+					.get_tile_entity(&mut self.commands, pos)
+					.map_err(TilePlacementError::MapError)?;
+				self.insert_animation(entity, &id, pos, start, end, speed);
+				entity
+			},
+		};
+
+		self.apply_tile_components(&id, &tileset_id, entity, None);
+
+		Ok(PlacedTile::Added {
+			old_tile: None,
+			new_tile: (entity, id),
+		})
+	}
+
+	/// Updates an entity to match the given tile
+	///
+	/// This is useful for when you need to update a specific entity rather than replacing it.
+	/// If you don't care or need to maintain the same entity, you're better off using the
+	/// [`place`](Self::place) method.
+	///
+	/// The tile's existing color, flip flags, and visibility are preserved.
+	///
+	/// # Arguments
+	///
+	/// * `tile_id`: The full ID of the tile to place
+	/// * `entity`: The tile entity
+	///
+	pub fn update<TId: Into<TileId>>(
+		&mut self,
+		tile_id: TId,
+		entity: Entity,
+	) -> Result<(), TilePlacementError> {
+		let id = tile_id.into();
+		let tileset_id = self.get_tileset_id(&id)?;
+		let parent = self
+			.animations
+			.get(entity)
+			.ok()
+			.and_then(|(.., parent)| parent)
+			.map(|parent| (parent.map_id, parent.layer_id));
+		let map_id = parent.map(|(map_id, _)| map_id);
+		let tile_index = match parent {
+			Some((map_id, layer_id)) => self.get_layer_tile_index(&id, map_id, layer_id)?,
+			None => self.get_tile_index(&id)?,
+		};
+
+		// Preserve the existing color and flags
+		let base = self
+			.query
+			.get(entity)
+			.map(|(tile, ..)| *tile)
+			.unwrap_or_default();
+
+		match tile_index {
+			TileIndex::Standard(index) => {
+				self.commands
+					.entity(entity)
+					.insert(Tile {
+						texture_index: index as u16,
+						..base
+					})
+					.remove::<GPUAnimated>()
+					.remove::<animation::PausedAnimation>();
+			},
+			TileIndex::Animated(start, end, speed) => {
+				self.commands.entity(entity).insert(Tile {
+					texture_index: start as u16,
+					..base
+				});
+				let pos = self
+					.animations
+					.get(entity)
+					.ok()
+					.and_then(|(pos, ..)| pos.copied())
+					.unwrap_or_default();
+				self.insert_animation(entity, &id, pos, start, end, speed);
+			},
+		}
+
+		self.apply_tile_components(&id, &tileset_id, entity, map_id);
+
+		Ok(())
+	}
+
+	/// Place a tile displaying the given texture index, bypassing its tile type
+	///
+	/// The tile still belongs to the tileset of the given [`TileId`] and receives its metadata,
+	/// but no variant selection, animation, or auto tiling is performed. This is mainly used to
+	/// restore tiles exactly as they were saved.
+	///
+	/// The tile is still subject to the [`LayerPolicy`] and must lie within the layer.
+	///
+	/// # Arguments
+	///
+	/// * `tile_id`: The ID of the tile the index belongs to
+	/// * `index`: The texture index to display
+	/// * `pos`: The tile position
+	/// * `map_id`: The tile map
+	/// * `layer_id`: The layer within the tile map
+	///
+	pub fn place_baked<Id: Into<TileId>, Pos: Into<TilePos>, MId: MapId>(
+		&mut self,
+		tile_id: Id,
+		index: usize,
+		pos: Pos,
+		map_id: MId,
+		layer_id: u16,
+	) -> TilePlacementResult {
+		let id = tile_id.into();
+		let pos = pos.into();
+		self.check_policy(&id, map_id, layer_id)?;
+		self.check_tileset(&id, map_id, layer_id)?;
+		let tileset_id = self.get_tileset_id(&id)?;
+		self.check_bounds(pos, map_id, layer_id)?;
+		let index = index
+			+ self
+				.resources
+				.combined
+				.offset(&tileset_id, map_id.into(), layer_id);
+
+		let old_tile = if let Some(existing) = self.get_existing(id, pos, map_id, layer_id) {
+			// Remove existing
+			self.remove(pos, map_id, layer_id)?;
+			Some((existing.entity, existing.id))
+		} else {
+			None
+		};
+
+		let entity = self
+			.map_query
+			.set_tile(
+				&mut self.commands,
+				pos,
+				Tile {
+					texture_index: index as u16,
+					..Default::default()
+				},
+				map_id,
+				layer_id,
+			)
+			.map_err(TilePlacementError::MapError)?;
+
+		self.commands
+			.entity(entity)
+			.remove::<GPUAnimated>()
+			.remove::<animation::PausedAnimation>()
+			.insert(TilesetParent(tileset_id));
+		self.apply_metadata(&id, entity);
+		self.apply_hooks(&id, entity);
+
+		self.notify_chunk(pos, map_id, layer_id);
+
+		Ok(PlacedTile::Added {
+			old_tile,
+			new_tile: (entity, id),
+		})
+	}
+
+	/// The main placement method
+	///
+	/// Handles the actual placement of a tile, without checking if it should or shouldn't
+	/// be placed.
+	fn place_unchecked<Id: Into<TileId>, Pos: Into<TilePos>, MId: MapId>(
+		&mut self,
+		tile_id: Id,
+		pos: Pos,
+		map_id: MId,
+		layer_id: u16,
+	) -> TilePlacementResult {
+		self.place_unchecked_with(
+			tile_id,
+			pos,
+			map_id,
+			layer_id,
+			&TilePlacementOptions::default(),
+		)
+	}
+
+	/// Same as [`place_unchecked`](Self::place_unchecked) but with the given placement options
+	fn place_unchecked_with<Id: Into<TileId>, Pos: Into<TilePos>, MId: MapId>(
+		&mut self,
+		tile_id: Id,
+		pos: Pos,
+		map_id: MId,
+		layer_id: u16,
+		options: &TilePlacementOptions,
+	) -> TilePlacementResult {
+		let pos = pos.into();
+		let id = self.seed_variant(tile_id.into(), pos, map_id.into());
+		self.check_policy(&id, map_id, layer_id)?;
+		self.check_tileset(&id, map_id, layer_id)?;
+		let tileset_id = self.get_tileset_id(&id)?;
+		let tile_index = self.get_layer_tile_index(&id, map_id.into(), layer_id)?;
+		self.check_bounds(pos, map_id, layer_id)?;
+
+		let old_tile = if let Some(existing) = self.get_existing(id, pos, map_id, layer_id) {
+			// Remove existing
+			self.remove(pos, map_id, layer_id)?;
+			Some((existing.entity, existing.id))
+		} else {
+			None
+		};
+
+		let index = *tile_index.base_index();
+
+		// Set the tile
+		let entity = self
+			.map_query
+			.set_tile(
+				&mut self.commands,
+				pos,
+				options.to_tile(index as u16),
+				map_id,
+				layer_id,
+			)
+			.map_err(TilePlacementError::MapError)?;
+
+		// Handle index specifics
+		match tile_index {
+			TileIndex::Standard(..) => {
+				// Remove any `GPUAnimated` component
+				self.commands.entity(entity).remove::<GPUAnimated>().id()
+			},
+			TileIndex::Animated(start, end, speed) => {
+				// Add the `GPUAnimated` component
+				self.insert_animation(entity, &id, pos, start, end, speed);
+				entity
+			},
+		};
+
+		self.apply_tile_components(&id, &tileset_id, entity, Some(map_id.into()));
+		if let Some(owner) = self.acting_owner {
+			self.set_tile_owner(entity, Some(owner));
+		}
+
+		self.notify_chunk(pos, map_id, layer_id);
+
+		Ok(PlacedTile::Added {
+			old_tile,
+			new_tile: (entity, id),
+		})
+	}
+
+	/// Immediately notify every chunk with pending changes
+	///
+	/// This is only needed when the [`ChunkFlushMode`](crate::prelude::ChunkFlushMode) is not
+	/// [`Immediate`](crate::prelude::ChunkFlushMode::Immediate).
+	pub fn flush_chunks(&mut self) {
+		self.notifications.flush(&mut self.map_query);
+	}
+
+	/// Notify the chunk containing the given tile, respecting the current
+	/// [`ChunkFlushMode`](crate::prelude::ChunkFlushMode)
+	pub(crate) fn notify_chunk<MId: MapId>(&mut self, pos: TilePos, map_id: MId, layer_id: u16) {
+		let map_id = map_id.into();
+		if !self
+			.batch
+			.collect(&mut self.map_query, pos, map_id, layer_id)
+		{
+			self.notifications
+				.notify(&mut self.map_query, pos, map_id, layer_id);
+		}
+	}
+
+	/// Start collecting chunk notifications so that each chunk is only notified once
+	///
+	/// Batches only affect this placer, and must be closed with [`Self::end_batch`].
+	pub(crate) fn begin_batch(&mut self) {
+		self.batch.begin();
+	}
+
+	/// Stop collecting chunk notifications started by [`Self::begin_batch`]
+	///
+	/// The collected notifications are sent according to the current
+	/// [`ChunkFlushMode`](crate::prelude::ChunkFlushMode).
+	pub(crate) fn end_batch(&mut self) {
+		self.batch.end(&self.notifications, &mut self.map_query);
+	}
+
+	/// Get the entity of the tile at the given coordinate (if any)
+	pub(crate) fn get_tile_entity<MId: MapId>(
+		&mut self,
+		pos: TilePos,
+		map_id: MId,
+		layer_id: u16,
+	) -> Option<Entity> {
+		self.map_query.get_tile_entity(pos, map_id, layer_id).ok()
+	}
+
+	/// Get the entity of the tile at the given coordinate, failing if there is none
+	pub(crate) fn try_get_tile_entity<MId: MapId>(
+		&mut self,
+		pos: TilePos,
+		map_id: MId,
+		layer_id: u16,
+	) -> Result<Entity, TilePlacementError> {
+		self.map_query
+			.get_tile_entity(pos, map_id, layer_id)
+			.map_err(TilePlacementError::MapError)
+	}
+
+	/// Get the commands used to place tiles
+	///
+	/// Commands affecting placed tiles should be queued here so they are applied in order with
+	/// the placements themselves.
+	#[cfg(feature = "destructible")]
+	pub(crate) fn commands(&mut self) -> &mut Commands<'w, 's> {
+		&mut self.commands
+	}
+
+	/// Animate the given tile entity between the given texture indices
+	///
+	/// The speed is adjusted according to the tile's [`ANIMATION_DEPHASE_METADATA_KEY`] metadata.
+	/// Headless builds have nothing to animate, so the tile simply displays its first frame.
+	fn insert_animation(
+		&mut self,
+		entity: Entity,
+		id: &TileId,
+		pos: TilePos,
+		start: usize,
+		end: usize,
+		speed: f32,
+	) {
+		#[cfg(not(feature = "headless"))]
+		{
+			let metadata = self
+				.tilesets
+				.get_by_id(&id.tileset_id)
+				.and_then(|tileset| self.resources.metadata.get_by_id(tileset, id));
+			let speed = animation::dephase_speed(speed, pos, metadata);
+			self.commands
+				.entity(entity)
+				.remove::<animation::PausedAnimation>()
+				.insert(GPUAnimated::new(start as u32, end as u32, speed));
+		}
+		#[cfg(feature = "headless")]
+		let _ = (entity, id, pos, start, end, speed);
+	}
+
+	/// Returns true if the given layer exists
+	pub(crate) fn has_layer<MId: MapId>(&mut self, map_id: MId, layer_id: u16) -> bool {
+		self.map_query.get_layer(map_id, layer_id).is_some()
+	}
+
+	/// Inserts (or removes) all components that depend on the placed tile's ID
+	///
+	/// The map is used to look up its [`MapSettings`](crate::prelude::MapSettings), falling back
+	/// to the defaults if it is not known.
+	fn apply_tile_components(
+		&mut self,
+		id: &TileId,
+		tileset_id: &TilesetId,
+		entity: Entity,
+		map_id: Option<u16>,
+	) {
+		// Insert the reference to the tileset this tile belongs to
+		self.commands
+			.entity(entity)
+			.insert(TilesetParent(*tileset_id));
+
+		self.apply_metadata(id, entity);
+
+		#[cfg(feature = "auto-tile")]
+		self.apply_auto_tile(id, tileset_id, entity, map_id);
+		#[cfg(not(feature = "auto-tile"))]
+		let _ = map_id;
+
+		self.apply_hooks(id, entity);
+	}
+
+	/// Attempts to add/remove the registered [`TileMetadata`] and [`TileCollision`] for the given tile
+	fn apply_metadata(&mut self, id: &TileId, entity: Entity) {
+		let tileset = self.tilesets.get_by_id(&id.tileset_id);
+		let metadata = tileset.and_then(|tileset| self.resources.metadata.get_by_id(tileset, id));
+		let collision =
+			tileset.and_then(|tileset| self.resources.collision.resolve(tileset, id, metadata));
+		let metadata = metadata.cloned();
+		let transition = self.get_transition(id);
+
+		let mut cmds = self.commands.entity(entity);
+		if let Some(collision) = collision {
+			cmds.insert(collision);
+		} else {
+			cmds.remove::<TileCollision>();
+		}
+
+		let elevation = metadata
+			.as_ref()
+			.and_then(|metadata| metadata.get_float(ELEVATION_METADATA_KEY));
+		if let Some(elevation) = elevation {
+			cmds.insert(TileElevation(elevation as f32));
+		} else {
+			cmds.remove::<TileElevation>();
+		}
+
+		if let Some(metadata) = metadata {
+			cmds.insert(metadata);
+		} else {
+			cmds.remove::<TileMetadata>();
+		}
+
+		if let Some(transition) = transition {
+			cmds.insert(transition);
+		} else {
+			cmds.remove::<TileTransition>();
+		}
+	}
+
+	/// Runs all registered [`TilePlacementHooks`] for the given tile
+	fn apply_hooks(&mut self, id: &TileId, entity: Entity) {
+		if self.resources.hooks.is_empty() {
+			return;
+		}
+
+		if let Some(tileset) = self.tilesets.get_by_id(&id.tileset_id) {
+			let ctx = TileHookContext {
+				entity,
+				tile_id: *id,
+				tileset,
+			};
+			let mut cmds = self.commands.entity(entity);
+			self.resources.hooks.run(&mut cmds, &ctx);
+		}
+	}
+
+	/// Attempts to add/remove an Auto Tile for the given tile
+	#[cfg(feature = "auto-tile")]
+	fn apply_auto_tile(
+		&mut self,
+		id: &TileId,
+		tileset_id: &TilesetId,
+		entity: Entity,
+		map_id: Option<u16>,
+	) {
+		let config = &self.resources.config;
+		let enabled = map_id
+			.map_or(config.defaults, |map_id| *config.get(map_id))
+			.auto_tile;
+		let is_auto = A::ENABLED
+			&& enabled
+			&& self
+				.get_tile_data(id)
+				.ok()
+				.map(|data| data.is_auto())
+				.unwrap_or_default();
+
+		let mask = if A::ENABLED && enabled {
+			self.get_auto_mask(id)
+		} else {
+			None
+		};
+
+		let auto_id = bevy_tileset::auto::AutoTileId {
+			group_id: id.group_id,
+			tileset_id: *tileset_id,
+		};
+		let mut cmds = self.commands.entity(entity);
+		if let Some(mask) = mask {
+			cmds.insert(crate::auto::AutoTileMasked { mask, auto_id });
+		} else {
+			cmds.remove::<crate::auto::AutoTileMasked>();
+		}
+
+		if is_auto {
+			cmds.insert(auto_id);
+		} else {
+			cmds.remove::<bevy_tileset::auto::AutoTileId>();
+			self.try_remove_auto_tile(entity);
+		}
+	}
+
+	/// Get the [`AutoTileMask`](crate::prelude::AutoTileMask) declared for the given tile (if any)
+	#[cfg(feature = "auto-tile")]
+	fn get_auto_mask(&self, id: &TileId) -> Option<crate::auto::AutoTileMask> {
+		let tileset = self.tilesets.get_by_id(&id.tileset_id)?;
+		let tile_name = tileset.get_tile_name(&id.group_id)?;
+		crate::auto::AutoTileMask::from_registry(&self.resources.metadata, tileset, tile_name)
+	}
+
+	/// Attempts to handle the removal of an Auto Tile
+	///
+	/// Returns true if the tile was successfully removed
+	#[cfg(feature = "auto-tile")]
+	fn try_remove_auto_tile(&mut self, entity: Entity) -> bool {
+		if let Some(event) = A::find(&self.auto_query, entity) {
+			self.commands.add(lite::SendRemoveAutoTile(event));
+			true
+		} else {
+			false
+		}
+	}
+
+	/// Tries to get the existing tile for a given tile coordinate
+	fn get_existing<Pos: Into<TilePos>, MId: MapId>(
+		&mut self,
+		tile_id: TileId,
+		pos: Pos,
+		map_id: MId,
+		layer_id: u16,
+	) -> Option<ExistingTile> {
+		let mut tile =
+			if let Ok(entity) = self.map_query.get_tile_entity(pos.into(), map_id, layer_id) {
+				if let Ok(results) = self.query.get(entity) {
+					let tex_idx = results.0.texture_index as usize;
+					let mut existing = ExistingTile::new(entity, None, tex_idx);
+					existing.is_animated = results.1.is_some();
+					#[cfg(feature = "auto-tile")]
+					{
+						existing.is_auto = A::find(&self.auto_query, entity).is_some();
+					}
+					Some(existing)
+				} else {
+					None
+				}
+			} else {
+				None
+			};
+
+		if let Some(ref mut tile) = tile {
+			let map_id = map_id.into();
+			let local = match self.resources.combined.get(map_id, layer_id) {
+				Some(combined) => combined.to_local(tile.texture_index),
+				None => Some((tile_id.tileset_id, tile.texture_index)),
+			};
+			if let Some((tileset_id, tex_idx)) = local {
+				if let Some(tileset) = self.tilesets.get_by_id(&tileset_id) {
+					tile.id = tileset.get_tile_id(&tex_idx).cloned();
+				}
+			}
+		}
+
+		tile
+	}
+
+	/// Get the tileset belonging to the given `TileId`
+	fn get_tileset(&self, tile_id: &TileId) -> Result<&Tileset, TilePlacementError> {
+		let tileset = self
+			.tilesets
+			.get_by_id(&tile_id.tileset_id)
+			.ok_or(TilePlacementError::InvalidTileset(tile_id.tileset_id))?;
+		Ok(tileset)
+	}
+
+	/// Check that the given tile could be placed, ignoring any existing tile and [`PlacementRules`]
+	fn check_placeable<MId: MapId>(
+		&mut self,
+		tile_id: TileId,
+		pos: TilePos,
+		map_id: MId,
+		layer_id: u16,
+	) -> Result<(), TilePlacementError> {
+		self.check_policy(&tile_id, map_id, layer_id)?;
+		self.check_tileset(&tile_id, map_id, layer_id)?;
+		self.get_layer_tile_index(&tile_id, map_id.into(), layer_id)?;
+
+		if !self.has_layer(map_id, layer_id) {
+			return Err(TilePlacementError::MapError(MapTileError::NonExistent(pos)));
+		}
+		self.check_bounds(pos, map_id, layer_id)
+	}
+
+	/// Check that the given position lies within the layer
+	///
+	/// Missing layers are left for `bevy_ecs_tilemap` to report.
+	fn check_bounds<MId: MapId>(
+		&mut self,
+		pos: TilePos,
+		map_id: MId,
+		layer_id: u16,
+	) -> Result<(), TilePlacementError> {
+		match self.map_bounds(map_id, layer_id) {
+			Some(map_size) if pos.0 >= map_size.x || pos.1 >= map_size.y => {
+				Err(TilePlacementError::OutOfBounds { pos, map_size })
+			},
+			_ => Ok(()),
+		}
+	}
+
+	/// Check that the given coordinate may be overwritten according to the map's
+	/// [`MapSettings`](crate::prelude::MapSettings)
+	fn check_overwrite<MId: MapId>(
+		&mut self,
+		tile_id: TileId,
+		pos: TilePos,
+		map_id: MId,
+		layer_id: u16,
+	) -> Result<(), TilePlacementError> {
+		if self.resources.config.get(map_id.into()).overwrite {
+			return Ok(());
+		}
+
+		match self.get_existing(tile_id, pos, map_id, layer_id) {
+			Some(existing) => Err(TilePlacementError::TileAlreadyExists {
+				new: tile_id,
+				existing: existing.id,
+				pos,
+			}),
+			None => Ok(()),
+		}
+	}
+
+	/// Select the variant of the given tile using the map's variant seed (if any)
+	///
+	/// Tiles that already specify a variant (or aren't Variant tiles) are returned as-is.
+	#[cfg(feature = "variants")]
+	fn seed_variant(&self, tile_id: TileId, pos: TilePos, map_id: u16) -> TileId {
+		let seed = match self.resources.config.get(map_id).variant_seed {
+			Some(seed) if tile_id.variant_index.is_none() => seed,
+			_ => return tile_id,
+		};
+
+		let variants = match self.get_tile_data(&tile_id).map(|data| data.tile()) {
+			Ok(TileType::Variant(variants)) => variants
+				.iter()
+				.enumerate()
+				.map(|(index, variant)| {
+					let mut id = tile_id;
+					id.variant_index = Some(index);
+					(id, variant.weight())
+				})
+				.collect::<Vec<_>>(),
+			_ => return tile_id,
+		};
+
+		groups::weighted_pick(&variants, groups::roll(pos, seed)).unwrap_or(tile_id)
+	}
+
+	/// Select the variant of the given tile using the map's variant seed (if any)
+	#[cfg(not(feature = "variants"))]
+	fn seed_variant(&self, tile_id: TileId, _pos: TilePos, _map_id: u16) -> TileId {
+		tile_id
+	}
+
+	/// Get the ID of the tileset belonging to the given `TileId`
+	fn get_tileset_id(&self, tile_id: &TileId) -> Result<TilesetId, TilePlacementError> {
+		let tileset = self.get_tileset(tile_id)?;
+		Ok(*tileset.id())
+	}
+
+	/// Get the `TileIndex` matching the given `TileId`
+	fn get_tile_index(&self, tile_id: &TileId) -> Result<TileIndex, TilePlacementError> {
+		let tileset = self.get_tileset(tile_id)?;
+		let (tile_index, _) = tileset
+			.select_tile_by_id(tile_id)
+			.ok_or(TilePlacementError::InvalidTile(*tile_id))?;
+		Ok(tile_index)
+	}
+
+	/// Get the `TileIndex` matching the given `TileId` as displayed in the given layer
+	///
+	/// For layers created from a [`CombinedTileset`](crate::prelude::CombinedTileset), this points
+	/// into the combined texture.
+	fn get_layer_tile_index(
+		&self,
+		tile_id: &TileId,
+		map_id: u16,
+		layer_id: u16,
+	) -> Result<TileIndex, TilePlacementError> {
+		let tile_index = self.get_tile_index(tile_id)?;
+		let offset = self
+			.resources
+			.combined
+			.offset(&tile_id.tileset_id, map_id, layer_id);
+		Ok(crate::combined::offset_index(tile_index, offset))
+	}
+
+	/// Get the `TileData` matching the given `TileId`
+	fn get_tile_data(&self, tile_id: &TileId) -> Result<&TileData, TilePlacementError> {
+		let tileset = self.get_tileset(tile_id)?;
+		let (_, tile_data) = tileset
+			.select_tile_by_id(tile_id)
+			.ok_or(TilePlacementError::InvalidTile(*tile_id))?;
+		Ok(tile_data)
+	}
+}
+
+/// Helper struct for passing information regarding existing tiles
+struct ExistingTile {
+	entity: Entity,
+	id: Option<TileId>,
+	texture_index: usize,
+	is_animated: bool,
+	#[cfg(feature = "auto-tile")]
+	is_auto: bool,
+}
+
+impl ExistingTile {
+	fn new(entity: Entity, id: Option<TileId>, texture_index: usize) -> Self {
+		Self {
+			entity,
+			id,
+			texture_index,
+			is_animated: false,
+			#[cfg(feature = "auto-tile")]
+			is_auto: false,
+		}
+	}
+}
@@ -0,0 +1,52 @@
+use bevy::prelude::Color;
+use bevy_ecs_tilemap::Tile;
+
+/// Options for customizing the [`Tile`] created when placing a tile
+///
+/// # Examples
+///
+/// ```
+/// # use bevy::prelude::Color;
+/// # use bevy_tileset_map::prelude::TilePlacementOptions;
+/// let options = TilePlacementOptions {
+///   color: Color::RED,
+///   flip_x: true,
+///   ..Default::default()
+/// };
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TilePlacementOptions {
+	/// The color the tile's texture is tinted with
+	pub color: Color,
+	/// Whether the tile is flipped horizontally
+	pub flip_x: bool,
+	/// Whether the tile is flipped vertically
+	pub flip_y: bool,
+	/// Whether the tile is flipped diagonally (i.e. its x and y axes are swapped)
+	pub flip_d: bool,
+}
+
+impl TilePlacementOptions {
+	/// Create a [`Tile`] with the given texture index using these options
+	pub(crate) fn to_tile(self, texture_index: u16) -> Tile {
+		Tile {
+			texture_index,
+			color: self.color,
+			flip_x: self.flip_x,
+			flip_y: self.flip_y,
+			flip_d: self.flip_d,
+			..Default::default()
+		}
+	}
+}
+
+impl Default for TilePlacementOptions {
+	fn default() -> Self {
+		Self {
+			color: Color::WHITE,
+			flip_x: false,
+			flip_y: false,
+			flip_d: false,
+		}
+	}
+}
@@ -0,0 +1,264 @@
+use bevy::prelude::Entity;
+use bevy_ecs_tilemap::prelude::{MapId, TilePos};
+use bevy_tileset::prelude::TileId;
+
+use crate::ownership::TileOwner;
+use crate::placement::{AutoTileAccess, TilePlacementError, TilePlacementResult, TilePlacer};
+
+impl<'w, 's, A: AutoTileAccess> TilePlacer<'w, 's, A> {
+	/// Place a tile owned by the given owner
+	///
+	/// This works like [`place`](Self::place) while [acting as](Self::act_as) the given owner, so
+	/// a tile owned by someone else is never replaced. Unowned tiles can be replaced by anyone.
+	///
+	/// # Arguments
+	///
+	/// * `tile_id`: The full ID of the tile to place
+	/// * `pos`: The tile position
+	/// * `map_id`: The tile map
+	/// * `layer_id`: The layer within the tile map
+	/// * `owner`: The owner of the placed tile
+	///
+	/// # Errors
+	///
+	/// Returns [`TilePlacementError::NotOwner`] if the existing tile is owned by someone else.
+	///
+	pub fn place_owned<Id: Into<TileId>, Pos: Into<TilePos>, MId: MapId>(
+		&mut self,
+		tile_id: Id,
+		pos: Pos,
+		map_id: MId,
+		layer_id: u16,
+		owner: TileOwner,
+	) -> TilePlacementResult {
+		self.as_owner(owner, |placer| {
+			placer.place(tile_id, pos.into(), map_id, layer_id)
+		})
+	}
+
+	/// Place a tile owned by the given owner only if the coordinate is not already occupied and
+	/// the placement is allowed by all [`PlacementRules`](crate::prelude::PlacementRules)
+	///
+	/// This works like [`try_place`](Self::try_place) while [acting as](Self::act_as) the given
+	/// owner. The rules are given the owner through
+	/// [`PlacementRuleContext::owner`](crate::prelude::PlacementRuleContext::owner).
+	///
+	/// # Arguments
+	///
+	/// * `tile_id`: The full ID of the tile to place
+	/// * `pos`: The tile position
+	/// * `map_id`: The tile map
+	/// * `layer_id`: The layer within the tile map
+	/// * `owner`: The owner of the placed tile
+	///
+	pub fn try_place_owned<Id: Into<TileId>, Pos: Into<TilePos>, MId: MapId>(
+		&mut self,
+		tile_id: Id,
+		pos: Pos,
+		map_id: MId,
+		layer_id: u16,
+		owner: TileOwner,
+	) -> TilePlacementResult {
+		self.as_owner(owner, |placer| {
+			placer.try_place(tile_id, pos.into(), map_id, layer_id)
+		})
+	}
+
+	/// Remove the tile at the given coordinate on behalf of the given owner
+	///
+	/// This works like [`remove`](Self::remove) while [acting as](Self::act_as) the given owner.
+	/// Unowned tiles can be removed by anyone.
+	///
+	/// # Arguments
+	///
+	/// * `pos`: The tile position
+	/// * `map_id`: The tile map
+	/// * `layer_id`: The layer within the tile map
+	/// * `owner`: The owner removing the tile
+	///
+	/// # Errors
+	///
+	/// Returns [`TilePlacementError::NotOwner`] if the tile is owned by someone else.
+	///
+	pub fn remove_owned<Pos: Into<TilePos>, MId: MapId>(
+		&mut self,
+		pos: Pos,
+		map_id: MId,
+		layer_id: u16,
+		owner: TileOwner,
+	) -> Result<(), TilePlacementError> {
+		self.as_owner(owner, |placer| placer.remove(pos, map_id, layer_id))
+	}
+
+	/// Make every following edit on behalf of the given owner (or of nobody)
+	///
+	/// While acting as an owner, every edit made through this placer (including removals, fills,
+	/// and transactions) fails with [`TilePlacementError::NotOwner`] for tiles owned by someone
+	/// else, and placed tiles are given to the owner. The owner is also passed on to the
+	/// [`PlacementRules`](crate::prelude::PlacementRules).
+	///
+	/// This only lasts for the current run of the system. Returns the previous acting owner.
+	///
+	/// # Arguments
+	///
+	/// * `owner`: The owner to act as, or `None` to edit tiles regardless of their owner
+	///
+	/// # Examples
+	///
+	/// ```
+	/// # use bevy_ecs_tilemap::TilePos;
+	/// # use bevy_tileset_map::prelude::{TileOwner, TilePlacer};
+	/// fn clear_area(mut placer: TilePlacer) {
+	///   placer.act_as(Some(TileOwner(42)));
+	///   // Tiles owned by other players are left alone
+	///   for x in 0..4 {
+	///     placer.remove(TilePos(x, 0), 0u16, 0u16).ok();
+	///   }
+	/// }
+	/// ```
+	pub fn act_as(&mut self, owner: Option<TileOwner>) -> Option<TileOwner> {
+		std::mem::replace(&mut self.acting_owner, owner)
+	}
+
+	/// The owner edits are currently made on behalf of (see [`act_as`](Self::act_as))
+	pub fn acting_owner(&self) -> Option<TileOwner> {
+		self.acting_owner
+	}
+
+	/// Get the owner of the tile at the given coordinate (if it has one)
+	///
+	/// # Arguments
+	///
+	/// * `pos`: The tile position
+	/// * `map_id`: The tile map
+	/// * `layer_id`: The layer within the tile map
+	///
+	pub fn get_owner<Pos: Into<TilePos>, MId: MapId>(
+		&mut self,
+		pos: Pos,
+		map_id: MId,
+		layer_id: u16,
+	) -> Result<Option<TileOwner>, TilePlacementError> {
+		let entity = self
+			.map_query
+			.get_tile_entity(pos.into(), map_id, layer_id)
+			.map_err(TilePlacementError::MapError)?;
+		Ok(self.owners.get(entity).ok().copied())
+	}
+
+	/// Set (or clear) the owner of the given tile entity
+	pub fn set_tile_owner(&mut self, entity: Entity, owner: Option<TileOwner>) {
+		let mut cmds = self.commands.entity(entity);
+		match owner {
+			Some(owner) => cmds.insert(owner),
+			None => cmds.remove::<TileOwner>(),
+		};
+	}
+
+	/// Get the owner of the tile at the given coordinate, ignoring missing tiles
+	pub(crate) fn get_owner_at<MId: MapId>(
+		&mut self,
+		pos: TilePos,
+		map_id: MId,
+		layer_id: u16,
+	) -> Option<TileOwner> {
+		let entity = self.map_query.get_tile_entity(pos, map_id, layer_id).ok()?;
+		self.owners.get(entity).ok().copied()
+	}
+
+	/// Make sure the tile at the given coordinate (if any) may be edited by the acting owner
+	pub(crate) fn check_owner<MId: MapId>(
+		&mut self,
+		pos: TilePos,
+		map_id: MId,
+		layer_id: u16,
+	) -> Result<(), TilePlacementError> {
+		let owner = match self.acting_owner {
+			Some(owner) => owner,
+			None => return Ok(()),
+		};
+
+		match self.get_owner_at(pos, map_id, layer_id) {
+			Some(existing) if existing != owner => Err(TilePlacementError::NotOwner {
+				pos,
+				owner: existing,
+			}),
+			_ => Ok(()),
+		}
+	}
+
+	/// Perform the given edits while acting as the given owner
+	fn as_owner<R>(&mut self, owner: TileOwner, edit: impl FnOnce(&mut Self) -> R) -> R {
+		let previous = self.act_as(Some(owner));
+		let result = edit(self);
+		self.act_as(previous);
+		result
+	}
+}
+
+#[cfg(all(test, not(feature = "headless")))]
+mod tests {
+	use bevy_ecs_tilemap::TilePos;
+	use bevy_tileset::prelude::{TileHandle, TileId};
+
+	use crate::ownership::TileOwner;
+	use crate::placement::TilePlacementError;
+	use crate::testing::{self, TILESET_ID};
+
+	#[test]
+	fn every_edit_checks_the_owner() {
+		let mut app = testing::app();
+		let stone = testing::texture(&mut app, [128, 128, 128, 255]);
+		let wood = testing::texture(&mut app, [128, 64, 0, 255]);
+		testing::add_tileset(
+			&mut app,
+			vec![
+				TileHandle::new_standard("Stone", stone),
+				TileHandle::new_standard("Wood", wood),
+			],
+		);
+		testing::spawn_map(&mut app);
+
+		let stone = TileId::new(0, TILESET_ID);
+		let wood = TileId::new(1, TILESET_ID);
+		let (builder, intruder) = (TileOwner(1), TileOwner(2));
+		let pos = TilePos(1, 1);
+		testing::with_placer(&mut app, |placer| {
+			placer.place_owned(stone, pos, 0u16, 0, builder).unwrap();
+		});
+		app.update();
+
+		let is_not_owner = |result: Result<_, TilePlacementError>| matches!(result, Err(TilePlacementError::NotOwner { owner, .. }) if owner == builder);
+		testing::with_placer(&mut app, |placer| {
+			placer.act_as(Some(intruder));
+			assert!(is_not_owner(placer.place(wood, pos, 0u16, 0).map(|_| ())));
+			assert!(is_not_owner(placer.replace(wood, pos, 0u16, 0).map(|_| ())));
+			assert!(is_not_owner(placer.toggle(wood, pos, 0u16, 0).map(|_| ())));
+			assert!(is_not_owner(placer.remove(pos, 0u16, 0)));
+			assert!(is_not_owner(placer.transaction(|tx| {
+				tx.remove(pos, 0u16, 0)?;
+				Ok(())
+			})));
+			assert!(is_not_owner(placer.swap(pos, TilePos(2, 1), 0u16, 0)));
+		});
+		app.update();
+		assert!(testing::get_tile(&mut app, pos).is_some());
+
+		// Placed tiles are given to the acting owner
+		testing::with_placer(&mut app, |placer| {
+			placer.act_as(Some(intruder));
+			placer.place(wood, TilePos(2, 1), 0u16, 0).unwrap();
+		});
+		app.update();
+		assert_eq!(
+			testing::with_placer(&mut app, |placer| placer.get_owner(TilePos(2, 1), 0u16, 0))
+				.unwrap(),
+			Some(intruder)
+		);
+
+		// Without an acting owner, tiles are edited regardless of their owner
+		testing::with_placer(&mut app, |placer| placer.remove(pos, 0u16, 0).unwrap());
+		app.update();
+		assert!(testing::get_tile(&mut app, pos).is_none());
+	}
+}
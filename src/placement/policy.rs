@@ -0,0 +1,201 @@
+//! Per-layer permissions enforced by the [`TilePlacer`]
+
+use bevy::utils::HashMap;
+use bevy_ecs_tilemap::prelude::MapId;
+use bevy_tileset::prelude::{TileId, TilesetId};
+
+use crate::placement::{AutoTileAccess, TilePlacementError, TilePlacer};
+
+/// The permissions of a single layer
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct LayerPermissions {
+	/// Whether tiles in this layer may not be placed, removed, or modified
+	pub read_only: bool,
+	/// The only tileset whose tiles may be placed in this layer
+	pub tileset: Option<TilesetId>,
+	/// Whether only auto tiles may be placed in this layer
+	pub auto_tile_only: bool,
+}
+
+/// The reason a placement was rejected by the [`LayerPolicy`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum PolicyViolation {
+	/// The layer is read-only
+	ReadOnly,
+	/// The tile belongs to a tileset other than the one allowed in the layer
+	WrongTileset {
+		/// The tileset allowed in the layer
+		expected: TilesetId,
+		/// The tileset of the tile that was to be placed
+		found: TilesetId,
+	},
+	/// The tile is not an auto tile, but the layer only allows auto tiles
+	NotAutoTile,
+}
+
+/// A resource mapping layers to the [`LayerPermissions`] enforced by the [`TilePlacer`]
+///
+/// This prevents editor tools and gameplay systems from accidentally editing the wrong layer.
+/// Layers without any permissions configured may be edited freely.
+///
+/// This resource is added automatically by the [`TilesetMapPlugin`](crate::prelude::TilesetMapPlugin).
+///
+/// # Examples
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_tileset_map::prelude::{LayerPermissions, LayerPolicy};
+/// fn setup(mut policy: ResMut<LayerPolicy>) {
+///   // The background layer must never change during gameplay
+///   policy.set(0, 0, LayerPermissions {
+///     read_only: true,
+///     ..Default::default()
+///   });
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct LayerPolicy {
+	layers: HashMap<(u16, u16), LayerPermissions>,
+}
+
+impl LayerPolicy {
+	/// Get the permissions of the given layer
+	pub fn get(&self, map_id: u16, layer_id: u16) -> LayerPermissions {
+		self.layers
+			.get(&(map_id, layer_id))
+			.copied()
+			.unwrap_or_default()
+	}
+
+	/// Set the permissions of the given layer, returning its previous permissions (if any)
+	pub fn set(
+		&mut self,
+		map_id: u16,
+		layer_id: u16,
+		permissions: LayerPermissions,
+	) -> Option<LayerPermissions> {
+		self.layers.insert((map_id, layer_id), permissions)
+	}
+
+	/// Remove the permissions of the given layer, allowing it to be edited freely
+	pub fn clear(&mut self, map_id: u16, layer_id: u16) -> Option<LayerPermissions> {
+		self.layers.remove(&(map_id, layer_id))
+	}
+
+	/// Returns true if the given layer is read-only
+	pub fn is_read_only(&self, map_id: u16, layer_id: u16) -> bool {
+		self.get(map_id, layer_id).read_only
+	}
+}
+
+impl<'w, 's, A: AutoTileAccess> TilePlacer<'w, 's, A> {
+	/// Check that the given layer may be edited according to the [`LayerPolicy`]
+	pub(crate) fn check_writable<MId: MapId>(
+		&self,
+		map_id: MId,
+		layer_id: u16,
+	) -> Result<(), TilePlacementError> {
+		let map_id = map_id.into();
+		if self.resources.policy.is_read_only(map_id, layer_id) {
+			return Err(TilePlacementError::PolicyViolation {
+				map_id,
+				layer_id,
+				violation: PolicyViolation::ReadOnly,
+			});
+		}
+		Ok(())
+	}
+
+	/// Check that the given tile may be placed in the given layer according to the [`LayerPolicy`]
+	pub(crate) fn check_policy<MId: MapId>(
+		&self,
+		tile_id: &TileId,
+		map_id: MId,
+		layer_id: u16,
+	) -> Result<(), TilePlacementError> {
+		let map_id = map_id.into();
+		let permissions = self.resources.policy.get(map_id, layer_id);
+		let violation = if permissions.read_only {
+			PolicyViolation::ReadOnly
+		} else if let Some(expected) = permissions.tileset.filter(|id| *id != tile_id.tileset_id) {
+			PolicyViolation::WrongTileset {
+				expected,
+				found: tile_id.tileset_id,
+			}
+		} else if permissions.auto_tile_only && !self.is_auto_tile(tile_id) {
+			PolicyViolation::NotAutoTile
+		} else {
+			return Ok(());
+		};
+
+		Err(TilePlacementError::PolicyViolation {
+			map_id,
+			layer_id,
+			violation,
+		})
+	}
+
+	/// Check that the tileset of the given tile matches the texture of the given layer
+	///
+	/// The layer's texture is taken from the [`TilesetMapRegistry`](crate::prelude::TilesetMapRegistry).
+	/// For layers without any registered tiles yet, the texture and tile sizes the layer was
+	/// created with are compared instead.
+	pub(crate) fn check_tileset<MId: MapId>(
+		&mut self,
+		tile_id: &TileId,
+		map_id: MId,
+		layer_id: u16,
+	) -> Result<(), TilePlacementError> {
+		let map_id = map_id.into();
+		let tileset = self
+			.tilesets
+			.get_by_id(&tile_id.tileset_id)
+			.ok_or(TilePlacementError::InvalidTileset(tile_id.tileset_id))?;
+		let (layer_entity, layer) = match self.map_query.get_layer(map_id, layer_id) {
+			Some(layer) => layer,
+			None => return Ok(()),
+		};
+
+		let combined = self.resources.combined.get(map_id, layer_id);
+		let texture = self
+			.resources
+			.registry
+			.layer(map_id, layer_id)
+			.filter(|info| info.entity == layer_entity)
+			.and_then(|info| info.texture.as_ref());
+		let matches = match (combined, texture) {
+			(Some(combined), _) => combined.contains(&tile_id.tileset_id),
+			(None, Some(texture)) => texture == tileset.texture(),
+			(None, None) => {
+				let (texture_size, tile_size) = (tileset.size(), tileset.tile_size());
+				let settings = &layer.settings;
+				settings.texture_size.0 == texture_size.x
+					&& settings.texture_size.1 == texture_size.y
+					&& settings.tile_size.0 == tile_size.x
+					&& settings.tile_size.1 == tile_size.y
+			},
+		};
+
+		if matches {
+			Ok(())
+		} else {
+			Err(TilePlacementError::TilesetMismatch {
+				tileset_id: tile_id.tileset_id,
+				map_id,
+				layer_id,
+			})
+		}
+	}
+
+	#[cfg(feature = "auto-tile")]
+	fn is_auto_tile(&self, tile_id: &TileId) -> bool {
+		self.get_tile_data(tile_id)
+			.map(|data| data.is_auto())
+			.unwrap_or_default()
+	}
+
+	#[cfg(not(feature = "auto-tile"))]
+	fn is_auto_tile(&self, _tile_id: &TileId) -> bool {
+		false
+	}
+}
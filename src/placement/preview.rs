@@ -0,0 +1,502 @@
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::{MapId, TilePos};
+use bevy_tileset::prelude::{TileId, TilesetId};
+
+use crate::coord::TileCoord;
+use crate::placement::{
+	AutoTileAccess, AutoTiles, TilePlacementError, TilePlacementOptions, TilePlacer,
+};
+
+/// A resource containing the tiles previewed using the [`TilePreviewer`]
+///
+/// This resource is added automatically by the [`TilesetMapPlugin`](crate::prelude::TilesetMapPlugin).
+#[derive(Debug, Clone)]
+pub struct PlacementPreview {
+	/// The color preview tiles are tinted with
+	///
+	/// This applies to tiles previewed after it is changed.
+	pub color: Color,
+	tiles: Vec<PreviewTile>,
+	overlays: Vec<PreviewOverlay>,
+}
+
+/// A single previewed tile
+#[derive(Debug, Copy, Clone)]
+struct PreviewTile {
+	coord: TileCoord,
+	tile_id: TileId,
+}
+
+/// A tile drawn on top of the map to display a [`PlacementPreview`]
+///
+/// Besides the previewed tiles themselves, this includes the neighboring Auto tiles that would
+/// change once the preview is committed.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PreviewOverlay {
+	/// The coordinate the overlay is drawn at
+	pub coord: TileCoord,
+	/// The tileset whose atlas is drawn
+	pub tileset_id: TilesetId,
+	/// The index of the texture within the tileset's atlas
+	pub index: usize,
+	/// The color and flip flags of the overlay
+	pub options: TilePlacementOptions,
+}
+
+impl Default for PlacementPreview {
+	fn default() -> Self {
+		Self {
+			color: Color::rgba(1.0, 1.0, 1.0, 0.5),
+			tiles: Vec::new(),
+			overlays: Vec::new(),
+		}
+	}
+}
+
+impl PlacementPreview {
+	/// Returns true if the given tile is a preview
+	pub fn contains(&self, coord: &TileCoord) -> bool {
+		self.tiles.iter().any(|tile| tile.coord == *coord)
+	}
+
+	/// Get the ID of the tile previewed at the given coordinate
+	pub fn get(&self, coord: &TileCoord) -> Option<TileId> {
+		self.tiles
+			.iter()
+			.find(|tile| tile.coord == *coord)
+			.map(|tile| tile.tile_id)
+	}
+
+	/// Iterate over the coordinates of all preview tiles
+	pub fn iter(&self) -> impl Iterator<Item = &TileCoord> {
+		self.tiles.iter().map(|tile| &tile.coord)
+	}
+
+	/// Iterate over the overlays displaying the preview
+	pub fn overlays(&self) -> impl Iterator<Item = &PreviewOverlay> {
+		self.overlays.iter()
+	}
+
+	/// The number of preview tiles
+	pub fn len(&self) -> usize {
+		self.tiles.len()
+	}
+
+	/// Returns true if there is no preview
+	pub fn is_empty(&self) -> bool {
+		self.tiles.is_empty()
+	}
+}
+
+/// A helper system param used to preview tiles before placing them
+///
+/// Previews never touch the map: they are drawn as translucent overlays on top of it (tinted with
+/// the [`PlacementPreview`] color), so other systems (such as the
+/// [`CollisionMap`](crate::prelude::CollisionMap)) never see them. Auto tiles (and their
+/// neighbors) are resolved as if the preview was placed, without committing the result.
+///
+/// The wrapped [`TilePlacer`] is available through [`placer`](Self::placer).
+///
+/// # Examples
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_ecs_tilemap::TilePos;
+/// # use bevy_tileset_map::prelude::{TileId, TilePreviewer};
+/// struct Hovered(TilePos);
+///
+/// fn preview_wall(mut previewer: TilePreviewer, hovered: Res<Hovered>, mouse: Res<Input<MouseButton>>) {
+///   previewer.cancel_preview();
+///   previewer.preview(TileId::new(0, 0), hovered.0, 0u16, 0u16).ok();
+///   if mouse.just_pressed(MouseButton::Left) {
+///     previewer.commit_preview();
+///   }
+/// }
+/// ```
+#[derive(SystemParam)]
+pub struct TilePreviewer<'w, 's, A: AutoTileAccess = AutoTiles> {
+	placer: TilePlacer<'w, 's, A>,
+	preview: ResMut<'w, PlacementPreview>,
+	#[cfg(feature = "auto-tile")]
+	auto_tiles: crate::auto::AutoTilePreviews<'w, 's>,
+}
+
+impl<'w, 's, A: AutoTileAccess> TilePreviewer<'w, 's, A> {
+	/// Preview a tile
+	///
+	/// This runs the same checks as [`TilePlacer::place`], without placing anything. Previewing
+	/// over a preview replaces it. Use [`commit_preview`](Self::commit_preview) to place every
+	/// preview tile or [`cancel_preview`](Self::cancel_preview) to discard them.
+	///
+	/// # Arguments
+	///
+	/// * `tile_id`: The full ID of the tile to preview
+	/// * `pos`: The tile position
+	/// * `map_id`: The tile map
+	/// * `layer_id`: The layer within the tile map
+	///
+	/// # Errors
+	///
+	/// Returns the error `place` would have returned.
+	///
+	pub fn preview<Id: Into<TileId>, Pos: Into<TilePos>, MId: MapId>(
+		&mut self,
+		tile_id: Id,
+		pos: Pos,
+		map_id: MId,
+		layer_id: u16,
+	) -> Result<(), TilePlacementError> {
+		let pos = pos.into();
+		let tile_id = self.placer.seed_variant(tile_id.into(), pos, map_id.into());
+		self.placer.check_writable(map_id, layer_id)?;
+		self.placer
+			.check_placeable(tile_id, pos, map_id, layer_id)?;
+		self.placer
+			.check_overwrite(tile_id, pos, map_id, layer_id)?;
+		self.placer.check_rules(tile_id, pos, map_id, layer_id)?;
+
+		let coord = TileCoord::new(pos, map_id.into(), layer_id);
+		let preview = &mut self.preview;
+		preview.tiles.retain(|tile| tile.coord != coord);
+		preview.tiles.push(PreviewTile { coord, tile_id });
+		self.update_overlays();
+		Ok(())
+	}
+
+	/// Place every preview tile
+	///
+	/// Returns the number of placed tiles.
+	pub fn commit_preview(&mut self) -> usize {
+		let tiles = std::mem::take(&mut self.preview.tiles);
+		self.preview.overlays.clear();
+		self.placer.begin_batch();
+		let count = tiles
+			.iter()
+			.filter(|tile| {
+				let TileCoord {
+					pos,
+					map_id,
+					layer_id,
+				} = tile.coord;
+				self.placer
+					.place(tile.tile_id, pos, map_id, layer_id)
+					.is_ok()
+			})
+			.count();
+		self.placer.end_batch();
+		count
+	}
+
+	/// Discard every preview tile
+	///
+	/// Returns the number of discarded tiles.
+	pub fn cancel_preview(&mut self) -> usize {
+		if self.preview.is_empty() {
+			return 0;
+		}
+
+		let count = self.preview.tiles.len();
+		self.preview.tiles.clear();
+		self.preview.overlays.clear();
+		count
+	}
+
+	/// Get the [`TilePlacer`] used to commit previews
+	pub fn placer(&mut self) -> &mut TilePlacer<'w, 's, A> {
+		&mut self.placer
+	}
+
+	/// Recompute the overlays displaying the current preview
+	fn update_overlays(&mut self) {
+		let color = self.preview.color;
+		#[allow(unused_mut)]
+		let mut overlays = self
+			.preview
+			.tiles
+			.iter()
+			.filter_map(|tile| {
+				let index = self.placer.get_tile_index(&tile.tile_id).ok()?;
+				Some(PreviewOverlay {
+					coord: tile.coord,
+					tileset_id: tile.tile_id.tileset_id,
+					index: *index.base_index(),
+					options: TilePlacementOptions {
+						color,
+						..Default::default()
+					},
+				})
+			})
+			.collect::<Vec<_>>();
+
+		#[cfg(feature = "auto-tile")]
+		if A::ENABLED {
+			for resolved in self.resolve_auto_tiles(color) {
+				match overlays
+					.iter_mut()
+					.find(|overlay| overlay.coord == resolved.coord)
+				{
+					Some(overlay) => *overlay = resolved,
+					None => overlays.push(resolved),
+				}
+			}
+		}
+
+		self.preview.overlays = overlays;
+	}
+
+	/// Compute how the previewed Auto tiles (and their neighbors) would be displayed once placed
+	#[cfg(feature = "auto-tile")]
+	fn resolve_auto_tiles(&mut self, color: Color) -> Vec<PreviewOverlay> {
+		let placer = &mut self.placer;
+		let previews = self
+			.preview
+			.tiles
+			.iter()
+			.filter(|tile| placer.resources.config.get(tile.coord.map_id).auto_tile)
+			.filter_map(|tile| {
+				let TileCoord {
+					map_id, layer_id, ..
+				} = tile.coord;
+				let is_auto = placer.get_tile_data(&tile.tile_id).ok()?.is_auto();
+				let mask = placer.get_auto_mask(&tile.tile_id);
+				let auto_id =
+					(is_auto || mask.is_some()).then_some(bevy_tileset::auto::AutoTileId {
+						group_id: tile.tile_id.group_id,
+						tileset_id: tile.tile_id.tileset_id,
+					});
+				let index = placer
+					.get_layer_tile_index(&tile.tile_id, map_id, layer_id)
+					.ok()?;
+				Some(crate::auto::AutoPreviewTile {
+					coord: tile.coord,
+					auto_id,
+					mask,
+					is_auto,
+					texture_index: *index.base_index(),
+					color,
+				})
+			})
+			.collect::<Vec<_>>();
+
+		self.auto_tiles.resolve(
+			&previews,
+			&mut placer.map_query,
+			&placer.tilesets,
+			&placer.resources.metadata,
+			&placer.resources.combined,
+			&placer.resources.config,
+		)
+	}
+}
+
+#[cfg(not(feature = "headless"))]
+mod render {
+	use bevy::prelude::*;
+	use bevy::sprite::TextureAtlasSprite;
+	use bevy_ecs_tilemap::MapQuery;
+	use bevy_tileset::prelude::Tilesets;
+
+	use super::PlacementPreview;
+
+	/// The z-offset of a preview overlay relative to its layer (above any elevated tile)
+	const PREVIEW_Z_OFFSET: f32 = 0.2;
+
+	/// __\[SYSTEM\]__ Spawns the sprites drawing the [`PlacementPreview`] whenever it changes
+	pub(crate) fn sync_preview_overlays(
+		mut commands: Commands,
+		preview: Res<PlacementPreview>,
+		tilesets: Tilesets,
+		mut map_query: MapQuery,
+		mut sprites: Local<Vec<Entity>>,
+	) {
+		if !preview.is_changed() {
+			return;
+		}
+
+		for sprite in sprites.drain(..) {
+			commands.entity(sprite).despawn();
+		}
+
+		for overlay in preview.overlays() {
+			let tileset = if let Some(tileset) = tilesets.get_by_id(&overlay.tileset_id) {
+				tileset
+			} else {
+				continue;
+			};
+
+			let coord = overlay.coord;
+			let (layer_entity, tile_size) = if let Some((layer_entity, layer)) =
+				map_query.get_layer(coord.map_id, coord.layer_id)
+			{
+				(layer_entity, layer.settings.tile_size)
+			} else {
+				continue;
+			};
+
+			let translation = coord
+				.to_vec2(Vec2::new(tile_size.0, tile_size.1))
+				.extend(PREVIEW_Z_OFFSET);
+			let sprite = commands
+				.spawn_bundle(SpriteSheetBundle {
+					sprite: TextureAtlasSprite {
+						index: overlay.index,
+						color: overlay.options.color,
+						flip_x: overlay.options.flip_x,
+						flip_y: overlay.options.flip_y,
+						..Default::default()
+					},
+					texture_atlas: tileset.atlas().clone(),
+					transform: Transform::from_translation(translation),
+					..Default::default()
+				})
+				.id();
+			commands.entity(layer_entity).add_child(sprite);
+			sprites.push(sprite);
+		}
+	}
+}
+
+#[cfg(not(feature = "headless"))]
+pub(crate) use render::sync_preview_overlays;
+
+#[cfg(all(test, not(feature = "headless")))]
+mod tests {
+	use bevy::ecs::system::SystemState;
+	use bevy::prelude::*;
+	use bevy_ecs_tilemap::TilePos;
+	use bevy_tileset::prelude::{TileHandle, TileId};
+
+	use crate::placement::{PlacementPreview, TilePreviewer};
+	use crate::testing::{self, TILESET_ID};
+
+	/// Run the given closure with a [`TilePreviewer`], applying its commands afterwards
+	fn with_previewer<R>(app: &mut App, f: impl FnOnce(&mut TilePreviewer) -> R) -> R {
+		let mut state = SystemState::<TilePreviewer>::new(&mut app.world);
+		let result = f(&mut state.get_mut(&mut app.world));
+		state.apply(&mut app.world);
+		result
+	}
+
+	#[test]
+	fn preview_leaves_map_untouched() {
+		let mut app = testing::app();
+		let grass = testing::texture(&mut app, [0, 255, 0, 255]);
+		let dirt = testing::texture(&mut app, [128, 64, 0, 255]);
+		testing::add_tileset(
+			&mut app,
+			vec![
+				TileHandle::new_standard("Grass", grass),
+				TileHandle::new_standard("Dirt", dirt),
+			],
+		);
+		testing::spawn_map(&mut app);
+
+		let grass = TileId::new(0, TILESET_ID);
+		let dirt = TileId::new(1, TILESET_ID);
+		testing::place(&mut app, grass, TilePos(1, 1));
+		let placed = testing::get_tile(&mut app, TilePos(1, 1)).unwrap();
+
+		with_previewer(&mut app, |previewer| {
+			previewer.preview(dirt, TilePos(1, 1), 0u16, 0).unwrap();
+			previewer.preview(dirt, TilePos(2, 1), 0u16, 0).unwrap();
+		});
+		app.update();
+
+		// --- Preview --- //
+		let tile = testing::get_tile(&mut app, TilePos(1, 1)).unwrap();
+		assert_eq!(tile.texture_index, placed.texture_index);
+		assert_eq!(tile.color, Color::WHITE);
+		assert!(testing::get_tile(&mut app, TilePos(2, 1)).is_none());
+		assert_eq!(
+			app.world.resource::<PlacementPreview>().overlays().count(),
+			2
+		);
+
+		// --- Cancel --- //
+		assert_eq!(
+			with_previewer(&mut app, |previewer| previewer.cancel_preview()),
+			2
+		);
+		app.update();
+		let tile = testing::get_tile(&mut app, TilePos(1, 1)).unwrap();
+		assert_eq!(tile.texture_index, placed.texture_index);
+		assert!(testing::get_tile(&mut app, TilePos(2, 1)).is_none());
+
+		// --- Commit --- //
+		let committed = with_previewer(&mut app, |previewer| {
+			previewer.preview(dirt, TilePos(2, 1), 0u16, 0).unwrap();
+			previewer.commit_preview()
+		});
+		assert_eq!(committed, 1);
+		app.update();
+		let tile = testing::get_tile(&mut app, TilePos(2, 1)).unwrap();
+		assert_ne!(tile.texture_index, placed.texture_index);
+		assert_eq!(tile.color, Color::WHITE);
+		assert!(app.world.resource::<PlacementPreview>().is_empty());
+	}
+
+	#[cfg(feature = "auto-tile")]
+	#[test]
+	fn preview_resolves_auto_tiles_without_placing() {
+		use bevy_tileset::prelude::{
+			AutoTileHandle, AutoTileRule, SimpleTileHandle, Tilesets, VariantTileHandle,
+		};
+
+		fn overlay_index(app: &App, pos: TilePos) -> Option<usize> {
+			app.world
+				.resource::<PlacementPreview>()
+				.overlays()
+				.find(|overlay| overlay.coord == crate::coord::TileCoord::new(pos, 0u16, 0))
+				.map(|overlay| overlay.index)
+		}
+
+		let mut app = testing::app();
+		let auto_tile = |app: &mut App, color, rule| AutoTileHandle {
+			rule,
+			variants: vec![VariantTileHandle {
+				weight: 1.0,
+				tile: SimpleTileHandle::Standard(testing::texture(app, color)),
+			}],
+		};
+		let east = AutoTileRule {
+			east: Some(true),
+			..Default::default()
+		};
+		let west = AutoTileRule {
+			west: Some(true),
+			..Default::default()
+		};
+		let handles = vec![
+			auto_tile(&mut app, [255, 0, 0, 255], east),
+			auto_tile(&mut app, [0, 255, 0, 255], west),
+			auto_tile(&mut app, [0, 0, 255, 255], AutoTileRule::default()),
+		];
+		testing::add_tileset(&mut app, vec![TileHandle::new_auto("Wall", handles)]);
+		testing::spawn_map(&mut app);
+
+		let wall = TileId::new(0, TILESET_ID);
+		testing::place(&mut app, wall, TilePos(1, 1));
+		app.update();
+		let placed = testing::get_tile(&mut app, TilePos(1, 1)).unwrap();
+
+		with_previewer(&mut app, |previewer| {
+			previewer.preview(wall, TilePos(2, 1), 0u16, 0).unwrap();
+		});
+		app.update();
+
+		// The existing neighbor keeps its texture until the preview is committed
+		let tile = testing::get_tile(&mut app, TilePos(1, 1)).unwrap();
+		assert_eq!(tile.texture_index, placed.texture_index);
+		assert!(testing::get_tile(&mut app, TilePos(2, 1)).is_none());
+
+		let mut state = SystemState::<Tilesets>::new(&mut app.world);
+		let tilesets = state.get_mut(&mut app.world);
+		let tileset = tilesets.get_by_id(&TILESET_ID).unwrap();
+		let east = *tileset.get_auto_index("Wall", east).unwrap().base_index();
+		let west = *tileset.get_auto_index("Wall", west).unwrap().base_index();
+		assert_ne!(east, placed.texture_index as usize);
+		assert_eq!(overlay_index(&app, TilePos(1, 1)), Some(east));
+		assert_eq!(overlay_index(&app, TilePos(2, 1)), Some(west));
+	}
+}
@@ -0,0 +1,186 @@
+use bevy::ecs::system::Command;
+use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::{GPUAnimated, MapId, Tile, TilePos};
+use bevy_tileset::prelude::TilesetParent;
+
+use crate::animator::PlayingAnimation;
+use crate::collision::TileCollision;
+use crate::elevation::TileElevation;
+use crate::flags::TileFlags;
+use crate::metadata::TileMetadata;
+use crate::placement::animation::PausedAnimation;
+use crate::placement::{AutoTileAccess, TilePlacementError, TilePlacer};
+use crate::transitions::TileTransition;
+
+impl<'w, 's, A: AutoTileAccess> TilePlacer<'w, 's, A> {
+	/// Exchange the tiles at the given positions
+	///
+	/// Unlike removing and placing both tiles again, this keeps their state: the texture index,
+	/// color, flip flags, visibility, animation, flags, metadata, and any pending transition or
+	/// one-shot animation travel with each tile. Auto tiles are updated at both positions.
+	///
+	/// Components inserted by [`TilePlacementHooks`](crate::prelude::TilePlacementHooks) are
+	/// inserted again for the tile's new position, but are not removed from its old one.
+	///
+	/// If only one of the positions contains a tile, it is moved using [`move_tile`](Self::move_tile).
+	///
+	/// # Arguments
+	///
+	/// * `pos_a`: The position of the first tile
+	/// * `pos_b`: The position of the second tile
+	/// * `map_id`: The tile map
+	/// * `layer_id`: The layer within the tile map
+	///
+	pub fn swap<Pos: Into<TilePos>, MId: MapId>(
+		&mut self,
+		pos_a: Pos,
+		pos_b: Pos,
+		map_id: MId,
+		layer_id: u16,
+	) -> Result<(), TilePlacementError> {
+		self.check_writable(map_id, layer_id)?;
+		let (pos_a, pos_b) = (pos_a.into(), pos_b.into());
+		if pos_a == pos_b {
+			return Ok(());
+		}
+		self.check_edit(None, pos_a, map_id, layer_id)?;
+		self.check_edit(None, pos_b, map_id, layer_id)?;
+
+		let (a, b) = match (
+			self.get_tile_entity(pos_a, map_id, layer_id),
+			self.get_tile_entity(pos_b, map_id, layer_id),
+		) {
+			(Some(a), Some(b)) => (a, b),
+			(Some(_), None) => return self.move_tile(pos_a, pos_b, map_id, layer_id).map(|_| ()),
+			(None, Some(_)) => return self.move_tile(pos_b, pos_a, map_id, layer_id).map(|_| ()),
+			(None, None) => return Ok(()),
+		};
+		let id_a = self.get_tile_id_at(pos_a, map_id, layer_id);
+		let id_b = self.get_tile_id_at(pos_b, map_id, layer_id);
+
+		// Let the neighbors of both positions know their auto tiles are leaving
+		#[cfg(feature = "auto-tile")]
+		{
+			self.try_remove_auto_tile(a);
+			self.try_remove_auto_tile(b);
+		}
+
+		self.commands.add(SwapTileComponents { a, b });
+		if let Some(id) = id_b {
+			self.apply_hooks(&id, a);
+		}
+		if let Some(id) = id_a {
+			self.apply_hooks(&id, b);
+		}
+
+		self.notify_chunk(pos_a, map_id, layer_id);
+		self.notify_chunk(pos_b, map_id, layer_id);
+		Ok(())
+	}
+
+	/// Move the tile at the given position to another position
+	///
+	/// Any tile at the destination is removed. The moved tile keeps its state just like with
+	/// [`swap`](Self::swap), but is carried over to a new entity, which is returned.
+	///
+	/// # Arguments
+	///
+	/// * `from`: The current position of the tile
+	/// * `to`: The position to move the tile to
+	/// * `map_id`: The tile map
+	/// * `layer_id`: The layer within the tile map
+	///
+	pub fn move_tile<Pos: Into<TilePos>, MId: MapId>(
+		&mut self,
+		from: Pos,
+		to: Pos,
+		map_id: MId,
+		layer_id: u16,
+	) -> Result<Entity, TilePlacementError> {
+		self.check_writable(map_id, layer_id)?;
+		let (from, to) = (from.into(), to.into());
+		let entity = self.try_get_tile_entity(from, map_id, layer_id)?;
+		if from == to {
+			return Ok(entity);
+		}
+		self.check_edit(None, from, map_id, layer_id)?;
+		self.check_edit(None, to, map_id, layer_id)?;
+		let tile_id = self.get_tile_id_at(from, map_id, layer_id);
+		let tile = self
+			.query
+			.get(entity)
+			.map(|(tile, ..)| *tile)
+			.unwrap_or_default();
+
+		if self.get_tile_entity(to, map_id, layer_id).is_some() {
+			self.remove(to, map_id, layer_id)?;
+		}
+		let moved = self
+			.map_query
+			.set_tile(&mut self.commands, to, tile, map_id, layer_id)
+			.map_err(TilePlacementError::MapError)?;
+
+		// The old entity is despawned right after, so swapping simply moves the components over
+		self.commands.add(SwapTileComponents {
+			a: entity,
+			b: moved,
+		});
+		if let Some(id) = tile_id {
+			self.apply_hooks(&id, moved);
+		}
+		self.notify_chunk(to, map_id, layer_id);
+
+		// Removing the old tile also lets its auto tile neighbors know it left
+		self.remove(from, map_id, layer_id)?;
+
+		Ok(moved)
+	}
+}
+
+/// A command that exchanges the state of two tile entities, leaving their position untouched
+struct SwapTileComponents {
+	a: Entity,
+	b: Entity,
+}
+
+impl Command for SwapTileComponents {
+	fn write(self, world: &mut World) {
+		swap_component::<Tile>(world, self.a, self.b);
+		swap_component::<TilesetParent>(world, self.a, self.b);
+		swap_component::<GPUAnimated>(world, self.a, self.b);
+		swap_component::<PausedAnimation>(world, self.a, self.b);
+		swap_component::<PlayingAnimation>(world, self.a, self.b);
+		swap_component::<TileFlags>(world, self.a, self.b);
+		swap_component::<TileMetadata>(world, self.a, self.b);
+		swap_component::<TileCollision>(world, self.a, self.b);
+		swap_component::<TileElevation>(world, self.a, self.b);
+		swap_component::<TileTransition>(world, self.a, self.b);
+
+		#[cfg(feature = "auto-tile")]
+		{
+			swap_component::<bevy_tileset::auto::AutoTileId>(world, self.a, self.b);
+			swap_component::<crate::auto::AutoTileFlipped>(world, self.a, self.b);
+			swap_component::<crate::auto::AutoTileMasked>(world, self.a, self.b);
+		}
+
+		#[cfg(feature = "destructible")]
+		swap_component::<crate::destructible::TileHealth>(world, self.a, self.b);
+	}
+}
+
+/// Exchange the given component between two entities, moving it if only one of them has it
+fn swap_component<C: Component>(world: &mut World, a: Entity, b: Entity) {
+	let from_a = world
+		.get_entity_mut(a)
+		.and_then(|mut entity| entity.remove::<C>());
+	let from_b = world
+		.get_entity_mut(b)
+		.and_then(|mut entity| entity.remove::<C>());
+
+	if let (Some(component), Some(mut entity)) = (from_b, world.get_entity_mut(a)) {
+		entity.insert(component);
+	}
+	if let (Some(component), Some(mut entity)) = (from_a, world.get_entity_mut(b)) {
+		entity.insert(component);
+	}
+}
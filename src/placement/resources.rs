@@ -0,0 +1,31 @@
+use std::marker::PhantomData;
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+
+use crate::collision::TileCollisionRegistry;
+use crate::combined::CombinedTilesets;
+use crate::config::TilesetMapConfig;
+use crate::metadata::TileMetadataRegistry;
+use crate::placement::{LayerPolicy, PlacementRules, TilePlacementHooks, WeightedTileGroups};
+use crate::registry::TilesetMapRegistry;
+
+/// The resources configuring the [`TilePlacer`](crate::prelude::TilePlacer)
+///
+/// These are grouped into their own param to stay within the field limit of `SystemParam`. This
+/// is nominally public since it appears in the state of the `TilePlacer` param, but it is not
+/// exported.
+#[derive(SystemParam)]
+pub struct PlacementResources<'w, 's> {
+	pub(super) metadata: Res<'w, TileMetadataRegistry>,
+	pub(super) hooks: Res<'w, TilePlacementHooks>,
+	pub(super) rules: Res<'w, PlacementRules>,
+	pub(super) policy: Res<'w, LayerPolicy>,
+	pub(super) groups: Res<'w, WeightedTileGroups>,
+	pub(super) collision: Res<'w, TileCollisionRegistry>,
+	pub(super) registry: Res<'w, TilesetMapRegistry>,
+	pub(super) combined: Res<'w, CombinedTilesets>,
+	pub(super) config: Res<'w, TilesetMapConfig>,
+	#[system_param(ignore)]
+	marker: PhantomData<&'s ()>,
+}
@@ -0,0 +1,403 @@
+//! Custom rules validating tile placements
+
+use bevy::math::{const_ivec2, IVec2};
+use bevy_ecs_tilemap::prelude::{MapId, TilePos};
+use bevy_tileset::prelude::{TileId, Tileset};
+
+use crate::coord::TileCoord;
+use crate::ownership::TileOwner;
+use crate::placement::{AutoTileAccess, TilePlacementError, TilePlacer};
+
+/// The signature of a placement rule
+///
+/// Returns true if the placement is allowed.
+pub type PlacementRuleFn = dyn Fn(&PlacementRuleContext<'_>) -> bool + Send + Sync + 'static;
+
+/// Information about the placement a rule is being checked for
+pub struct PlacementRuleContext<'a> {
+	/// The ID of the tile being placed
+	pub tile_id: TileId,
+	/// The coordinate the tile is being placed at
+	pub coord: TileCoord,
+	/// The tileset the tile belongs to
+	pub tileset: &'a Tileset,
+	/// The ID of the tile currently at the coordinate (if any)
+	pub existing: Option<TileId>,
+	/// The owner of the tile being placed
+	///
+	/// This is only set while the placer [acts as](crate::prelude::TilePlacer::act_as) an owner,
+	/// such as when placing using
+	/// [`TilePlacer::try_place_owned`](crate::prelude::TilePlacer::try_place_owned).
+	pub owner: Option<TileOwner>,
+	/// The owner of the tile currently at the coordinate (if any)
+	pub existing_owner: Option<TileOwner>,
+	/// The IDs and names of the surrounding tiles (including the center), row by row from the bottom
+	neighbors: [Option<(TileId, Option<&'a str>)>; 9],
+}
+
+/// A resource containing the rules consulted whenever a [`TilePlacer`] places a tile
+///
+/// This allows placement validation (such as for building mechanics on an authoritative server)
+/// to be defined in a single place.
+///
+/// This resource is added automatically by the [`TilesetMapPlugin`](crate::prelude::TilesetMapPlugin).
+///
+/// # Examples
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_tileset_map::prelude::PlacementRules;
+/// fn setup(mut rules: ResMut<PlacementRules>) {
+///   rules.add_for_name("water-shores", "My Tileset", "Water", |ctx| {
+///     ctx.neighbors4()
+///       .flatten()
+///       .all(|(_, name)| matches!(name, Some("Sand") | Some("Water")))
+///   });
+/// }
+/// ```
+pub struct PlacementRules {
+	enabled: bool,
+	rules: Vec<PlacementRule>,
+}
+
+struct PlacementRule {
+	name: String,
+	/// The `(tileset_name, tile_name)` this rule applies to, or `None` for every tile
+	tile: Option<(String, String)>,
+	check: Box<PlacementRuleFn>,
+}
+
+/// The offsets of the 4-neighborhood, in the order returned by [`PlacementRuleContext::neighbors4`]
+const OFFSETS_4: [IVec2; 4] = [
+	const_ivec2!([0, 1]),
+	const_ivec2!([1, 0]),
+	const_ivec2!([0, -1]),
+	const_ivec2!([-1, 0]),
+];
+
+/// The offsets of the 8-neighborhood, in the order returned by [`PlacementRuleContext::neighbors8`]
+const OFFSETS_8: [IVec2; 8] = [
+	const_ivec2!([-1, 1]),
+	const_ivec2!([0, 1]),
+	const_ivec2!([1, 1]),
+	const_ivec2!([1, 0]),
+	const_ivec2!([1, -1]),
+	const_ivec2!([0, -1]),
+	const_ivec2!([-1, -1]),
+	const_ivec2!([-1, 0]),
+];
+
+fn neighbor_index(offset: IVec2) -> Option<usize> {
+	if offset.abs().max_element() > 1 {
+		return None;
+	}
+	Some(((offset.y + 1) * 3 + offset.x + 1) as usize)
+}
+
+impl<'a> PlacementRuleContext<'a> {
+	/// The name of the tile being placed
+	pub fn tile_name(&self) -> Option<&'a str> {
+		self.tileset
+			.get_tile_name(&self.tile_id.group_id)
+			.map(String::as_str)
+	}
+
+	/// The ID and name of the tile at the given offset from the placed tile (if any)
+	///
+	/// Only the immediate neighbors (offsets within `-1..=1` on both axes) are available.
+	pub fn neighbor(&self, offset: IVec2) -> Option<(TileId, Option<&'a str>)> {
+		self.neighbors[neighbor_index(offset)?]
+	}
+
+	/// The tiles sharing an edge with the placed tile: top, right, bottom, then left
+	pub fn neighbors4(&self) -> impl Iterator<Item = Option<(TileId, Option<&'a str>)>> + '_ {
+		OFFSETS_4.iter().map(|offset| self.neighbor(*offset))
+	}
+
+	/// The tiles surrounding the placed tile, clockwise starting from the top-left
+	pub fn neighbors8(&self) -> impl Iterator<Item = Option<(TileId, Option<&'a str>)>> + '_ {
+		OFFSETS_8.iter().map(|offset| self.neighbor(*offset))
+	}
+}
+
+impl Default for PlacementRules {
+	fn default() -> Self {
+		Self {
+			enabled: true,
+			rules: Vec::new(),
+		}
+	}
+}
+
+impl PlacementRules {
+	/// Register a rule that is checked for every placed tile
+	pub fn add<S, F>(&mut self, name: S, rule: F) -> &mut Self
+	where
+		S: Into<String>,
+		F: Fn(&PlacementRuleContext<'_>) -> bool + Send + Sync + 'static,
+	{
+		self.rules.push(PlacementRule {
+			name: name.into(),
+			tile: None,
+			check: Box::new(rule),
+		});
+		self
+	}
+
+	/// Register a rule that is checked for the tile with the given name in the given tileset
+	pub fn add_for_name<S, T, U, F>(
+		&mut self,
+		name: S,
+		tileset_name: T,
+		tile_name: U,
+		rule: F,
+	) -> &mut Self
+	where
+		S: Into<String>,
+		T: Into<String>,
+		U: Into<String>,
+		F: Fn(&PlacementRuleContext<'_>) -> bool + Send + Sync + 'static,
+	{
+		self.rules.push(PlacementRule {
+			name: name.into(),
+			tile: Some((tileset_name.into(), tile_name.into())),
+			check: Box::new(rule),
+		});
+		self
+	}
+
+	/// Remove all rules with the given name
+	pub fn remove(&mut self, name: &str) {
+		self.rules.retain(|rule| rule.name != name);
+	}
+
+	/// The names of all registered rules, in the order they are checked
+	pub fn names(&self) -> impl Iterator<Item = &str> {
+		self.rules.iter().map(|rule| rule.name.as_str())
+	}
+
+	/// Returns true if rules are checked
+	pub fn is_enabled(&self) -> bool {
+		self.enabled
+	}
+
+	/// Enable or disable checking rules without removing them
+	pub fn set_enabled(&mut self, enabled: bool) {
+		self.enabled = enabled;
+	}
+
+	/// Returns true if no rules have been registered
+	pub fn is_empty(&self) -> bool {
+		self.rules.is_empty()
+	}
+
+	/// Get the name of the first rule violated by the given placement (if any)
+	fn check(&self, ctx: &PlacementRuleContext) -> Option<&str> {
+		let tile_name = ctx.tile_name();
+		self.rules
+			.iter()
+			.filter(|rule| match &rule.tile {
+				Some((tileset_name, name)) => {
+					tileset_name == ctx.tileset.name() && Some(name.as_str()) == tile_name
+				},
+				None => true,
+			})
+			.find(|rule| !(rule.check)(ctx))
+			.map(|rule| rule.name.as_str())
+	}
+}
+
+impl<'w, 's, A: AutoTileAccess> TilePlacer<'w, 's, A> {
+	/// Check the given placement against the owner of the existing tile and all registered
+	/// [`PlacementRules`]
+	///
+	/// This is done automatically by every method placing tiles.
+	///
+	/// # Errors
+	///
+	/// Returns [`TilePlacementError::NotOwner`] if the existing tile is owned by someone other than
+	/// the [acting owner](Self::act_as), or [`TilePlacementError::RuleViolation`] containing the
+	/// name of the first rule that does not allow the placement.
+	///
+	pub fn check_rules<Id: Into<TileId>, Pos: Into<TilePos>, MId: MapId>(
+		&mut self,
+		tile_id: Id,
+		pos: Pos,
+		map_id: MId,
+		layer_id: u16,
+	) -> Result<(), TilePlacementError> {
+		self.check_edit(Some(tile_id.into()), pos.into(), map_id, layer_id)
+	}
+
+	/// Check that the given edit is allowed
+	///
+	/// The tile at the coordinate must not be owned by someone other than the acting owner and,
+	/// when placing a tile, the placement must be allowed by all registered [`PlacementRules`].
+	/// Every method editing tiles goes through this check, so that ownership can't be bypassed.
+	pub(crate) fn check_edit<MId: MapId>(
+		&mut self,
+		tile_id: Option<TileId>,
+		pos: TilePos,
+		map_id: MId,
+		layer_id: u16,
+	) -> Result<(), TilePlacementError> {
+		self.check_owner(pos, map_id, layer_id)?;
+		match tile_id {
+			Some(tile_id) => self.check_placement_rules(tile_id, pos, map_id, layer_id),
+			None => Ok(()),
+		}
+	}
+
+	/// Check the given placement on behalf of the acting owner against all registered
+	/// [`PlacementRules`]
+	fn check_placement_rules<MId: MapId>(
+		&mut self,
+		tile_id: TileId,
+		pos: TilePos,
+		map_id: MId,
+		layer_id: u16,
+	) -> Result<(), TilePlacementError> {
+		if !self.resources.rules.is_enabled() || self.resources.rules.is_empty() {
+			return Ok(());
+		}
+
+		let coord = TileCoord::new(pos, map_id.into(), layer_id);
+		let owner = self.acting_owner;
+
+		let existing = self.get_tile_id_at(coord.pos, map_id, layer_id);
+		let existing_owner = self.get_owner_at(coord.pos, map_id, layer_id);
+		let mut neighbor_ids = [None; 9];
+		for offset in OFFSETS_8 {
+			if let (Some(index), Some(neighbor)) = (neighbor_index(offset), coord.offset(offset)) {
+				neighbor_ids[index] = self.get_tile_id_at(neighbor.pos, map_id, layer_id);
+			}
+		}
+
+		let tileset = self.get_tileset(&tile_id)?;
+		let mut neighbors = [None; 9];
+		for (neighbor, id) in neighbors.iter_mut().zip(neighbor_ids) {
+			*neighbor = id.map(|id: TileId| {
+				let name = self
+					.tilesets
+					.get_by_id(&id.tileset_id)
+					.and_then(|tileset| tileset.get_tile_name(&id.group_id))
+					.map(String::as_str);
+				(id, name)
+			});
+		}
+
+		let ctx = PlacementRuleContext {
+			tile_id,
+			coord,
+			tileset,
+			existing,
+			owner,
+			existing_owner,
+			neighbors,
+		};
+		match self.resources.rules.check(&ctx) {
+			Some(rule) => Err(TilePlacementError::RuleViolation {
+				rule: rule.to_string(),
+				tile: tile_id,
+				pos: coord.pos,
+			}),
+			None => Ok(()),
+		}
+	}
+
+	/// Get the ID of the tile at the given position (if any)
+	pub(crate) fn get_tile_id_at<MId: MapId>(
+		&mut self,
+		pos: TilePos,
+		map_id: MId,
+		layer_id: u16,
+	) -> Option<TileId> {
+		let entity = self.map_query.get_tile_entity(pos, map_id, layer_id).ok()?;
+		let tileset_id = self.tileset_parents.get(entity).ok()?.0;
+		let texture_index = self.query.get(entity).ok()?.0.texture_index as usize;
+		let texture_index = self.resources.combined.local_index(
+			&tileset_id,
+			map_id.into(),
+			layer_id,
+			texture_index,
+		);
+		self.tilesets
+			.get_by_id(&tileset_id)?
+			.get_tile_id(&texture_index)
+			.copied()
+	}
+}
+
+#[cfg(all(test, not(feature = "headless")))]
+mod tests {
+	use bevy::ecs::system::SystemState;
+	use bevy_ecs_tilemap::TilePos;
+	use bevy_tileset::prelude::{TileHandle, TileId};
+
+	use crate::config::TilesetMapConfig;
+	use crate::placement::{PlacementRules, TilePlacementError, TilePreviewer};
+	use crate::testing::{self, TILESET_ID};
+
+	fn is_violation<T>(result: Result<T, TilePlacementError>) -> bool {
+		matches!(result, Err(TilePlacementError::RuleViolation { rule, .. }) if rule == "no-lava")
+	}
+
+	#[test]
+	fn every_placement_checks_overwrite_and_rules() {
+		let mut app = testing::app();
+		let grass = testing::texture(&mut app, [0, 255, 0, 255]);
+		let lava = testing::texture(&mut app, [255, 64, 0, 255]);
+		testing::add_tileset(
+			&mut app,
+			vec![
+				TileHandle::new_standard("Grass", grass),
+				TileHandle::new_standard("Lava", lava),
+			],
+		);
+		testing::spawn_map(&mut app);
+
+		let grass = TileId::new(0, TILESET_ID);
+		let lava = TileId::new(1, TILESET_ID);
+		testing::place(&mut app, grass, TilePos(1, 1));
+
+		// --- Rules --- //
+		app.world.resource_mut::<PlacementRules>().add_for_name(
+			"no-lava",
+			"Test Tileset",
+			"Lava",
+			|_| false,
+		);
+		let empty = TilePos(2, 1);
+		testing::with_placer(&mut app, |placer| {
+			assert!(is_violation(placer.place(lava, empty, 0u16, 0)));
+			assert!(is_violation(placer.replace(lava, TilePos(1, 1), 0u16, 0)));
+			assert!(is_violation(placer.toggle(lava, empty, 0u16, 0)));
+			assert!(is_violation(placer.toggle_matching(lava, empty, 0u16, 0)));
+		});
+		let mut state = SystemState::<TilePreviewer>::new(&mut app.world);
+		assert!(is_violation(
+			state.get_mut(&mut app.world).preview(lava, empty, 0u16, 0)
+		));
+		state.apply(&mut app.world);
+		app.update();
+		assert!(testing::get_tile(&mut app, empty).is_none());
+
+		// --- Overwrite --- //
+		app.world
+			.resource_mut::<TilesetMapConfig>()
+			.get_mut(0)
+			.overwrite = false;
+		app.world
+			.resource_mut::<PlacementRules>()
+			.set_enabled(false);
+		testing::with_placer(&mut app, |placer| {
+			assert!(matches!(
+				placer.replace(lava, TilePos(1, 1), 0u16, 0),
+				Err(TilePlacementError::TileAlreadyExists { .. })
+			));
+		});
+		app.update();
+		let tile = testing::get_tile(&mut app, TilePos(1, 1)).unwrap();
+		assert_eq!(tile.texture_index, 0);
+	}
+}
@@ -0,0 +1,171 @@
+use bevy::math::UVec2;
+use bevy_ecs_tilemap::prelude::{MapId, MapTileError, TilePos};
+use bevy_tileset::prelude::TileId;
+
+use crate::placement::{AutoTileAccess, TilePlacementError, TilePlacer};
+
+impl<'w, 's, A: AutoTileAccess> TilePlacer<'w, 's, A> {
+	/// Place a tile at every position within the given circle
+	///
+	/// See [`place_ellipse`](Self::place_ellipse) for details.
+	///
+	/// # Arguments
+	///
+	/// * `tile_id`: The full ID of the tile to place
+	/// * `center`: The center of the circle
+	/// * `radius`: The radius of the circle (in tiles)
+	/// * `map_id`: The tile map
+	/// * `layer_id`: The layer within the tile map
+	///
+	pub fn place_circle<Id: Into<TileId>, Pos: Into<TilePos>, MId: MapId>(
+		&mut self,
+		tile_id: Id,
+		center: Pos,
+		radius: u32,
+		map_id: MId,
+		layer_id: u16,
+	) -> Result<usize, TilePlacementError> {
+		self.place_ellipse(tile_id, center, UVec2::splat(radius), map_id, layer_id)
+	}
+
+	/// Remove every tile within the given circle
+	///
+	/// See [`remove_ellipse`](Self::remove_ellipse) for details.
+	///
+	/// # Arguments
+	///
+	/// * `center`: The center of the circle
+	/// * `radius`: The radius of the circle (in tiles)
+	/// * `map_id`: The tile map
+	/// * `layer_id`: The layer within the tile map
+	///
+	/// # Examples
+	///
+	/// ```
+	/// # use bevy_ecs_tilemap::TilePos;
+	/// # use bevy_tileset_map::prelude::TilePlacer;
+	/// fn explode(mut placer: TilePlacer) {
+	///   let destroyed = placer.remove_circle(TilePos(10, 10), 4, 0u16, 0u16);
+	/// }
+	/// ```
+	pub fn remove_circle<Pos: Into<TilePos>, MId: MapId>(
+		&mut self,
+		center: Pos,
+		radius: u32,
+		map_id: MId,
+		layer_id: u16,
+	) -> usize {
+		self.remove_ellipse(center, UVec2::splat(radius), map_id, layer_id)
+	}
+
+	/// Place a tile at every position within the given ellipse
+	///
+	/// Positions outside the layer are skipped and existing tiles are replaced (following the
+	/// same rules as [`place`](Self::place)). Auto tiles are updated and all affected chunks are
+	/// notified once the entire ellipse has been placed.
+	///
+	/// Returns the number of tiles that were placed, stopping at the first error.
+	///
+	/// # Arguments
+	///
+	/// * `tile_id`: The full ID of the tile to place
+	/// * `center`: The center of the ellipse
+	/// * `radii`: The horizontal and vertical radius of the ellipse (in tiles)
+	/// * `map_id`: The tile map
+	/// * `layer_id`: The layer within the tile map
+	///
+	pub fn place_ellipse<Id: Into<TileId>, Pos: Into<TilePos>, MId: MapId>(
+		&mut self,
+		tile_id: Id,
+		center: Pos,
+		radii: UVec2,
+		map_id: MId,
+		layer_id: u16,
+	) -> Result<usize, TilePlacementError> {
+		let (tile_id, center) = (tile_id.into(), center.into());
+		let bounds = self
+			.map_bounds(map_id, layer_id)
+			.ok_or(TilePlacementError::MapError(MapTileError::NonExistent(
+				center,
+			)))?;
+
+		self.begin_batch();
+
+		let mut count = 0;
+		let mut result = Ok(());
+		for pos in ellipse_positions(center, radii, bounds) {
+			if let Err(err) = self.place(tile_id, pos, map_id, layer_id) {
+				result = Err(err);
+				break;
+			}
+			count += 1;
+		}
+
+		self.end_batch();
+		result.map(|_| count)
+	}
+
+	/// Remove every tile within the given ellipse
+	///
+	/// Empty positions and positions outside the layer are skipped. Auto tiles are updated and
+	/// all affected chunks are notified once the entire ellipse has been handled.
+	///
+	/// Returns the number of removed tiles.
+	///
+	/// # Arguments
+	///
+	/// * `center`: The center of the ellipse
+	/// * `radii`: The horizontal and vertical radius of the ellipse (in tiles)
+	/// * `map_id`: The tile map
+	/// * `layer_id`: The layer within the tile map
+	///
+	pub fn remove_ellipse<Pos: Into<TilePos>, MId: MapId>(
+		&mut self,
+		center: Pos,
+		radii: UVec2,
+		map_id: MId,
+		layer_id: u16,
+	) -> usize {
+		let center = center.into();
+		let bounds = if let Some(bounds) = self.map_bounds(map_id, layer_id) {
+			bounds
+		} else {
+			return 0;
+		};
+
+		self.begin_batch();
+
+		let mut count = 0;
+		for pos in ellipse_positions(center, radii, bounds) {
+			let exists = self
+				.map_query
+				.get_tile_entity(pos, map_id, layer_id)
+				.is_ok();
+			if exists && self.remove(pos, map_id, layer_id).is_ok() {
+				count += 1;
+			}
+		}
+
+		self.end_batch();
+		count
+	}
+}
+
+/// Get every position within the given ellipse that also lies within the given bounds
+fn ellipse_positions(center: TilePos, radii: UVec2, bounds: UVec2) -> Vec<TilePos> {
+	let (cx, cy) = (center.0 as i64, center.1 as i64);
+	let (rx, ry) = (radii.x as i64, radii.y as i64);
+	let (max_x, max_y) = (bounds.x as i64 - 1, bounds.y as i64 - 1);
+
+	let mut positions = Vec::new();
+	for y in (cy - ry).max(0)..=(cy + ry).min(max_y) {
+		for x in (cx - rx).max(0)..=(cx + rx).min(max_x) {
+			// Equivalent to (dx / rx)^2 + (dy / ry)^2 <= 1, without dividing by zero
+			let (dx, dy) = (x - cx, y - cy);
+			if dx * dx * ry * ry + dy * dy * rx * rx <= rx * rx * ry * ry {
+				positions.push(TilePos(x as u32, y as u32));
+			}
+		}
+	}
+	positions
+}
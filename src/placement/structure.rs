@@ -0,0 +1,274 @@
+use bevy::prelude::Entity;
+use bevy_ecs_tilemap::prelude::MapTileError;
+use bevy_ecs_tilemap::prelude::{MapId, TilePos};
+use bevy_tileset::prelude::TileId;
+
+use crate::placement::{AutoTileAccess, PlacedTile, TilePlacementError, TilePlacer};
+
+/// A multi-layer stamp of tiles placed as a whole using [`TilePlacer::place_structure`]
+///
+/// Grids are given row by row, top to bottom (i.e. `grid[0][0]` is the top-left tile), and are
+/// aligned at their bottom-left corner. Empty entries are left untouched.
+///
+/// The footprint marks the positions the structure claims. They must be unoccupied in every layer
+/// of the structure for it to be placed. By default, the footprint consists of every position
+/// with a tile in any layer.
+///
+/// # Examples
+///
+/// ```
+/// # use bevy_ecs_tilemap::TilePos;
+/// # use bevy_tileset_map::prelude::{Structure, TileId};
+/// let (wall, door, roof) = (TileId::new(0, 0), TileId::new(1, 0), TileId::new(2, 0));
+/// let house = Structure::new()
+///   .with_layer(0, vec![
+///     vec![Some(wall), Some(wall), Some(wall)],
+///     vec![Some(wall), Some(door), Some(wall)],
+///   ])
+///   // The roof overhangs the walls without claiming any space
+///   .with_layer(1, vec![
+///     vec![Some(roof), Some(roof), Some(roof), Some(roof), Some(roof)],
+///     vec![None, None, None, None, None],
+///   ])
+///   .with_footprint(vec![
+///     vec![false, true, true, true, false],
+///     vec![false, true, true, true, false],
+///   ])
+///   // Place the house by its door
+///   .with_anchor(TilePos(1, 0));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Structure {
+	/// The tiles of each layer
+	layers: Vec<(u16, Vec<Vec<Option<TileId>>>)>,
+	/// The positions claimed by the structure
+	footprint: Option<Vec<Vec<bool>>>,
+	/// The position within the structure that is placed at the origin
+	anchor: TilePos,
+}
+
+impl Structure {
+	/// Create an empty structure
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Set the tiles placed in the given layer
+	pub fn with_layer(mut self, layer_id: u16, grid: Vec<Vec<Option<TileId>>>) -> Self {
+		self.layers.retain(|(id, ..)| *id != layer_id);
+		self.layers.push((layer_id, grid));
+		self
+	}
+
+	/// Set the positions claimed by the structure
+	pub fn with_footprint(mut self, footprint: Vec<Vec<bool>>) -> Self {
+		self.footprint = Some(footprint);
+		self
+	}
+
+	/// Set the position within the structure (relative to its bottom-left corner) that is placed
+	/// at the origin
+	pub fn with_anchor<Pos: Into<TilePos>>(mut self, anchor: Pos) -> Self {
+		self.anchor = anchor.into();
+		self
+	}
+
+	/// The position within the structure that is placed at the origin
+	pub fn anchor(&self) -> TilePos {
+		self.anchor
+	}
+
+	/// Iterate over the layers of the structure
+	pub fn layer_ids(&self) -> impl Iterator<Item = u16> + '_ {
+		self.layers.iter().map(|(layer_id, ..)| *layer_id)
+	}
+
+	/// Get every tile (along with its layer and position) when placed at the given origin
+	///
+	/// Returns `None` if any tile would fall below zero.
+	pub fn tiles_at<Pos: Into<TilePos>>(&self, origin: Pos) -> Option<Vec<(u16, TilePos, TileId)>> {
+		let origin = origin.into();
+		let mut tiles = Vec::new();
+		for (layer_id, grid) in &self.layers {
+			for (offset, tile_id) in cells(grid) {
+				if let Some(tile_id) = tile_id {
+					tiles.push((*layer_id, self.offset(origin, offset)?, *tile_id));
+				}
+			}
+		}
+		Some(tiles)
+	}
+
+	/// Get every position claimed by the structure when placed at the given origin
+	///
+	/// Returns `None` if any position would fall below zero.
+	pub fn footprint_at<Pos: Into<TilePos>>(&self, origin: Pos) -> Option<Vec<TilePos>> {
+		let origin = origin.into();
+		match &self.footprint {
+			Some(footprint) => cells(footprint)
+				.filter(|(_, is_claimed)| **is_claimed)
+				.map(|(offset, _)| self.offset(origin, offset))
+				.collect(),
+			None => {
+				let mut claimed = self
+					.layers
+					.iter()
+					.flat_map(|(_, grid)| cells(grid))
+					.filter(|(_, tile_id)| tile_id.is_some())
+					.map(|(offset, _)| offset)
+					.collect::<Vec<_>>();
+				claimed.sort_unstable();
+				claimed.dedup();
+				claimed
+					.into_iter()
+					.map(|offset| self.offset(origin, offset))
+					.collect()
+			},
+		}
+	}
+
+	/// Get the position of the given offset (relative to the bottom-left corner) when placed at
+	/// the given origin
+	fn offset(&self, origin: TilePos, (x, y): (u32, u32)) -> Option<TilePos> {
+		Some(TilePos(
+			(origin.0 + x).checked_sub(self.anchor.0)?,
+			(origin.1 + y).checked_sub(self.anchor.1)?,
+		))
+	}
+}
+
+/// Iterate over the cells of the given grid along with their offset from its bottom-left corner
+fn cells<T>(grid: &[Vec<T>]) -> impl Iterator<Item = ((u32, u32), &T)> {
+	let height = grid.len() as u32;
+	grid.iter().enumerate().flat_map(move |(row, cells)| {
+		let y = height - 1 - row as u32;
+		cells
+			.iter()
+			.enumerate()
+			.map(move |(x, cell)| ((x as u32, y), cell))
+	})
+}
+
+impl<'w, 's, A: AutoTileAccess> TilePlacer<'w, 's, A> {
+	/// Place the given structure
+	///
+	/// The structure is first validated using [`can_place_structure`](Self::can_place_structure)
+	/// and then placed within a single [`transaction`](Self::transaction), so either every tile is
+	/// placed or the map is left untouched.
+	///
+	/// Returns the entities of the placed tiles.
+	///
+	/// # Arguments
+	///
+	/// * `structure`: The structure to place
+	/// * `origin`: The position of the structure's anchor
+	/// * `map_id`: The tile map
+	///
+	/// # Examples
+	///
+	/// ```
+	/// # use bevy::prelude::*;
+	/// # use bevy_ecs_tilemap::TilePos;
+	/// # use bevy_tileset_map::prelude::{Structure, TilePlacer};
+	/// struct House(Structure);
+	///
+	/// fn place_house(mut placer: TilePlacer, house: Res<House>) {
+	///   match placer.place_structure(&house.0, TilePos(10, 10), 0u16) {
+	///     Ok(entities) => println!("Placed {} tiles", entities.len()),
+	///     Err(err) => println!("Could not place the house: {}", err),
+	///   }
+	/// }
+	/// ```
+	pub fn place_structure<Pos: Into<TilePos>, MId: MapId>(
+		&mut self,
+		structure: &Structure,
+		origin: Pos,
+		map_id: MId,
+	) -> Result<Vec<Entity>, TilePlacementError> {
+		let origin = origin.into();
+		let map_id = map_id.into();
+		self.can_place_structure(structure, origin, map_id)?;
+
+		let tiles = structure.tiles_at(origin).unwrap_or_default();
+		self.transaction(|tx| {
+			let mut entities = Vec::with_capacity(tiles.len());
+			for (layer_id, pos, tile_id) in tiles {
+				if let PlacedTile::Added {
+					new_tile: (entity, ..),
+					..
+				} = tx.place(tile_id, pos, map_id, layer_id)?
+				{
+					entities.push(entity);
+				}
+			}
+			Ok(entities)
+		})
+	}
+
+	/// Check whether [`place_structure`](Self::place_structure) would succeed without placing
+	/// anything
+	///
+	/// Every position within the footprint must lie within the layer and be unoccupied in every
+	/// layer of the structure. Every tile must pass the same checks as
+	/// [`can_place`](Self::can_place), apart from the occupancy of positions outside the footprint.
+	///
+	/// # Arguments
+	///
+	/// * `structure`: The structure to check
+	/// * `origin`: The position of the structure's anchor
+	/// * `map_id`: The tile map
+	///
+	/// # Errors
+	///
+	/// Returns [`TilePlacementError::FootprintBlocked`] if any position within the footprint is
+	/// occupied. Otherwise, returns the error `can_place` would have returned for the first
+	/// invalid tile.
+	///
+	pub fn can_place_structure<Pos: Into<TilePos>, MId: MapId>(
+		&mut self,
+		structure: &Structure,
+		origin: Pos,
+		map_id: MId,
+	) -> Result<(), TilePlacementError> {
+		let origin = origin.into();
+		let map_id = map_id.into();
+
+		let (tiles, footprint) = match (structure.tiles_at(origin), structure.footprint_at(origin))
+		{
+			(Some(tiles), Some(footprint)) => (tiles, footprint),
+			_ => {
+				let layer_id = structure.layer_ids().next().unwrap_or_default();
+				return Err(TilePlacementError::OutOfBounds {
+					pos: origin,
+					map_size: self.map_bounds(map_id, layer_id).unwrap_or_default(),
+				});
+			},
+		};
+
+		for layer_id in structure.layer_ids() {
+			if !self.has_layer(map_id, layer_id) {
+				return Err(TilePlacementError::MapError(MapTileError::NonExistent(
+					origin,
+				)));
+			}
+
+			for pos in footprint.iter().copied() {
+				self.check_bounds(pos, map_id, layer_id)?;
+				if self.get_tile_entity(pos, map_id, layer_id).is_some() {
+					return Err(TilePlacementError::FootprintBlocked {
+						existing: self.get_tile_id_at(pos, map_id, layer_id),
+						pos,
+						layer_id,
+					});
+				}
+			}
+		}
+
+		for (layer_id, pos, tile_id) in tiles {
+			self.check_placeable(tile_id, pos, map_id, layer_id)?;
+			self.check_rules(tile_id, pos, map_id, layer_id)?;
+		}
+
+		Ok(())
+	}
+}
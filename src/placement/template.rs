@@ -0,0 +1,103 @@
+use bevy_ecs_tilemap::prelude::{MapId, TilePos};
+use bevy_tileset::prelude::TileId;
+
+use crate::placement::{AutoTileAccess, TilePlacementError, TilePlacer};
+use crate::template::{MapTemplate, TemplateStamp, TemplateTile};
+
+impl<'w, 's, A: AutoTileAccess> TilePlacer<'w, 's, A> {
+	/// Stamp the given template into the given layer
+	///
+	/// All tiles are resolved before any of them are placed, so a template with unknown tiles or
+	/// unmapped characters leaves the map untouched. All affected chunks are notified once the
+	/// entire template has been placed.
+	///
+	/// Returns the number of tiles that were placed.
+	///
+	/// # Arguments
+	///
+	/// * `template`: The template to stamp
+	/// * `stamp`: How the template should be transformed and its characters mapped
+	/// * `pos`: The position of the bottom-left corner of the (transformed) template
+	/// * `map_id`: The tile map
+	/// * `layer_id`: The layer within the tile map
+	///
+	/// # Examples
+	///
+	/// ```
+	/// # use bevy::prelude::*;
+	/// # use bevy_ecs_tilemap::TilePos;
+	/// # use bevy_tileset_map::prelude::{MapTemplate, TemplateStamp, TileId, TilePlacer};
+	/// struct Room(Handle<MapTemplate>);
+	///
+	/// fn place_room(mut placer: TilePlacer, room: Res<Room>, templates: Res<Assets<MapTemplate>>) {
+	///   if let Some(template) = templates.get(&room.0) {
+	///     let stamp = TemplateStamp::new().with_legend('D', TileId::new(3, 0)).rotated(1);
+	///     placer.stamp_template(template, &stamp, TilePos(10, 10), 0u16, 0u16).unwrap();
+	///   }
+	/// }
+	/// ```
+	pub fn stamp_template<Pos: Into<TilePos>, MId: MapId>(
+		&mut self,
+		template: &MapTemplate,
+		stamp: &TemplateStamp,
+		pos: Pos,
+		map_id: MId,
+		layer_id: u16,
+	) -> Result<usize, TilePlacementError> {
+		let pos = pos.into();
+		let map_id = map_id.into();
+		let tiles = template
+			.iter_stamped(stamp)
+			.map(|(offset, tile)| {
+				let tile_id = self.resolve_template_tile(template, stamp, tile)?;
+				Ok((TilePos(pos.0 + offset.0, pos.1 + offset.1), tile_id))
+			})
+			.collect::<Result<Vec<_>, TilePlacementError>>()?;
+
+		self.begin_batch();
+
+		let mut count = 0;
+		let mut result = Ok(());
+		for (pos, tile_id) in tiles {
+			if let Err(err) = self.place(tile_id, pos, map_id, layer_id) {
+				result = Err(err);
+				break;
+			}
+			count += 1;
+		}
+
+		self.end_batch();
+		result.map(|_| count)
+	}
+
+	/// Get the ID of the given template tile
+	fn resolve_template_tile(
+		&self,
+		template: &MapTemplate,
+		stamp: &TemplateStamp,
+		tile: &TemplateTile,
+	) -> Result<TileId, TilePlacementError> {
+		match tile {
+			TemplateTile::Id(tile_id) => Ok(*tile_id),
+			TemplateTile::Named { tileset, tile } => {
+				let tileset_ref = self.tilesets.get_by_name(tileset);
+				let group_id = tileset_ref.and_then(|tileset| tileset.get_tile_group_id(tile));
+				match (tileset_ref, group_id) {
+					(Some(tileset), Some(group_id)) => Ok(TileId::new(*group_id, *tileset.id())),
+					_ => Err(TilePlacementError::InvalidTileName {
+						tileset: tileset.clone(),
+						tile: tile.clone(),
+					}),
+				}
+			},
+			TemplateTile::Char(c) => match (stamp.legend.get(c), template.get_legend(*c)) {
+				(Some(tile_id), _) => Ok(*tile_id),
+				// Legend entries never map to other characters
+				(None, Some(TemplateTile::Char(..))) | (None, None) => {
+					Err(TilePlacementError::UnmappedTemplateChar(*c))
+				},
+				(None, Some(tile)) => self.resolve_template_tile(template, stamp, tile),
+			},
+		}
+	}
+}
@@ -0,0 +1,378 @@
+use bevy_ecs_tilemap::prelude::{MapId, TilePos};
+use bevy_tileset::prelude::TileId;
+
+use crate::coord::TileCoord;
+use crate::ownership::TileOwner;
+use crate::placement::{
+	AutoTileAccess, PlacedTile, TilePlacementError, TilePlacementOptions, TilePlacementResult,
+	TilePlacer,
+};
+
+/// A tile taken by [`TilePlacer::snapshot_tile`]: its ID, options, and owner
+type TileSnapshot = (TileId, TilePlacementOptions, Option<TileOwner>);
+
+/// A set of edits made within [`TilePlacer::transaction`]
+///
+/// Every edit made through the transaction records the tile it replaced, so that all of them can
+/// be rolled back if the transaction fails.
+pub struct TileTransaction<'a, 'w, 's, A: AutoTileAccess> {
+	placer: &'a mut TilePlacer<'w, 's, A>,
+	/// The coordinates edited so far, along with the tile (and its options) found there before
+	/// the first edit
+	touched: Vec<(TileCoord, Option<TileSnapshot>)>,
+}
+
+impl<'a, 'w, 's, A: AutoTileAccess> TileTransaction<'a, 'w, 's, A> {
+	/// Place a tile
+	///
+	/// See [`TilePlacer::place`] for details.
+	///
+	/// # Arguments
+	///
+	/// * `tile_id`: The full ID of the tile to place
+	/// * `pos`: The tile position
+	/// * `map_id`: The tile map
+	/// * `layer_id`: The layer within the tile map
+	///
+	pub fn place<Id: Into<TileId>, Pos: Into<TilePos>, MId: MapId>(
+		&mut self,
+		tile_id: Id,
+		pos: Pos,
+		map_id: MId,
+		layer_id: u16,
+	) -> TilePlacementResult {
+		let tile_id = tile_id.into();
+		self.record(pos.into(), map_id, layer_id, |placer, pos| {
+			placer.place(tile_id, pos, map_id, layer_id)
+		})
+	}
+
+	/// Place a tile with the given options
+	///
+	/// See [`TilePlacer::place_with`] for details.
+	///
+	/// # Arguments
+	///
+	/// * `tile_id`: The full ID of the tile to place
+	/// * `pos`: The tile position
+	/// * `map_id`: The tile map
+	/// * `layer_id`: The layer within the tile map
+	/// * `options`: The color and flip flags of the placed tile
+	///
+	pub fn place_with<Id: Into<TileId>, Pos: Into<TilePos>, MId: MapId>(
+		&mut self,
+		tile_id: Id,
+		pos: Pos,
+		map_id: MId,
+		layer_id: u16,
+		options: TilePlacementOptions,
+	) -> TilePlacementResult {
+		let tile_id = tile_id.into();
+		self.record(pos.into(), map_id, layer_id, |placer, pos| {
+			placer.place_with(tile_id, pos, map_id, layer_id, options)
+		})
+	}
+
+	/// Place a tile only if the coordinate is not already occupied and the placement is allowed by
+	/// all [`PlacementRules`](crate::prelude::PlacementRules)
+	///
+	/// See [`TilePlacer::try_place`] for details.
+	///
+	/// # Arguments
+	///
+	/// * `tile_id`: The full ID of the tile to place
+	/// * `pos`: The tile position
+	/// * `map_id`: The tile map
+	/// * `layer_id`: The layer within the tile map
+	///
+	pub fn try_place<Id: Into<TileId>, Pos: Into<TilePos>, MId: MapId>(
+		&mut self,
+		tile_id: Id,
+		pos: Pos,
+		map_id: MId,
+		layer_id: u16,
+	) -> TilePlacementResult {
+		let tile_id = tile_id.into();
+		self.record(pos.into(), map_id, layer_id, |placer, pos| {
+			placer.try_place(tile_id, pos, map_id, layer_id)
+		})
+	}
+
+	/// Places a tile if the coordinate is not already occupied, otherwise removes the existing tile
+	///
+	/// See [`TilePlacer::toggle`] for details.
+	///
+	/// # Arguments
+	///
+	/// * `tile_id`: The full ID of the tile to place
+	/// * `pos`: The tile position
+	/// * `map_id`: The tile map
+	/// * `layer_id`: The layer within the tile map
+	///
+	pub fn toggle<Id: Into<TileId>, Pos: Into<TilePos>, MId: MapId>(
+		&mut self,
+		tile_id: Id,
+		pos: Pos,
+		map_id: MId,
+		layer_id: u16,
+	) -> TilePlacementResult {
+		let tile_id = tile_id.into();
+		self.record(pos.into(), map_id, layer_id, |placer, pos| {
+			placer.toggle(tile_id, pos, map_id, layer_id)
+		})
+	}
+
+	/// Removes the tile at the given coordinate
+	///
+	/// See [`TilePlacer::remove`] for details.
+	///
+	/// # Arguments
+	///
+	/// * `pos`: The tile position
+	/// * `map_id`: The tile map
+	/// * `layer_id`: The layer within the tile map
+	///
+	pub fn remove<Pos: Into<TilePos>, MId: MapId>(
+		&mut self,
+		pos: Pos,
+		map_id: MId,
+		layer_id: u16,
+	) -> TilePlacementResult {
+		self.record(pos.into(), map_id, layer_id, |placer, pos| {
+			let old_tile = placer
+				.get_tile_entity(pos, map_id, layer_id)
+				.map(|entity| (entity, placer.get_tile_id_at(pos, map_id, layer_id)));
+			placer.remove(pos, map_id, layer_id)?;
+			Ok(PlacedTile::Removed { old_tile })
+		})
+	}
+
+	/// The number of coordinates edited so far
+	pub fn len(&self) -> usize {
+		self.touched.len()
+	}
+
+	/// Returns true if nothing was edited so far
+	pub fn is_empty(&self) -> bool {
+		self.touched.is_empty()
+	}
+
+	/// Perform an edit, remembering the tile it replaces if it succeeds
+	fn record<MId: MapId, F>(
+		&mut self,
+		pos: TilePos,
+		map_id: MId,
+		layer_id: u16,
+		edit: F,
+	) -> TilePlacementResult
+	where
+		F: FnOnce(&mut TilePlacer<'w, 's, A>, TilePos) -> TilePlacementResult,
+	{
+		let coord = TileCoord::new(pos, map_id.into(), layer_id);
+		let is_touched = self.touched.iter().any(|(touched, _)| *touched == coord);
+		// Only the tile found before the first edit matters
+		let replaced = if is_touched {
+			None
+		} else {
+			self.placer.snapshot_tile(pos, map_id, layer_id)
+		};
+
+		let placed = edit(&mut *self.placer, pos)?;
+		if !is_touched {
+			self.touched.push((coord, replaced));
+		}
+		Ok(placed)
+	}
+
+	/// Undo every recorded edit, from last to first
+	fn rollback(self) {
+		for (coord, replaced) in self.touched.into_iter().rev() {
+			let TileCoord {
+				pos,
+				map_id,
+				layer_id,
+			} = coord;
+			self.placer.remove_unchecked(pos, map_id, layer_id).ok();
+			if let Some(replaced) = replaced {
+				self.placer.restore_tile(coord, replaced);
+			}
+		}
+	}
+}
+
+impl<'w, 's, A: AutoTileAccess> TilePlacer<'w, 's, A> {
+	/// Perform several edits atomically
+	///
+	/// If the given closure returns an error, every edit made through the [`TileTransaction`] is
+	/// rolled back, placing the replaced tiles (with their colors and flip flags) again. Errors
+	/// returned by the transaction's methods can simply be propagated using `?`. Edits made
+	/// within the closure that don't go through the transaction are not rolled back.
+	///
+	/// Chunks are only remeshed once the transaction is done, so a rolled back transaction
+	/// never becomes visible.
+	///
+	/// Restoring a tile relies on its components, so tiles placed earlier within the same frame
+	/// can't be restored.
+	///
+	/// # Arguments
+	///
+	/// * `f`: The closure performing the edits
+	///
+	/// # Examples
+	///
+	/// ```
+	/// # use bevy_ecs_tilemap::TilePos;
+	/// # use bevy_tileset_map::prelude::{TileId, TilePlacer};
+	/// fn place_house(mut placer: TilePlacer) {
+	///   let wall = TileId::new(0, 0);
+	///   let door = TileId::new(1, 0);
+	///   // Either the entire house is placed or nothing at all
+	///   let result = placer.transaction(|tx| {
+	///     for x in 0..3 {
+	///       tx.try_place(wall, TilePos(x, 1), 0u16, 0u16)?;
+	///     }
+	///     tx.try_place(wall, TilePos(0, 0), 0u16, 0u16)?;
+	///     tx.try_place(door, TilePos(1, 0), 0u16, 0u16)?;
+	///     tx.try_place(wall, TilePos(2, 0), 0u16, 0u16)?;
+	///     Ok(())
+	///   });
+	///   if let Err(err) = result {
+	///     println!("Could not place the house: {}", err);
+	///   }
+	/// }
+	/// ```
+	pub fn transaction<T, F>(&mut self, f: F) -> Result<T, TilePlacementError>
+	where
+		F: FnOnce(&mut TileTransaction<'_, 'w, 's, A>) -> Result<T, TilePlacementError>,
+	{
+		self.begin_batch();
+		let mut transaction = TileTransaction {
+			placer: self,
+			touched: Vec::new(),
+		};
+
+		let result = f(&mut transaction);
+		if result.is_err() {
+			transaction.rollback();
+		}
+
+		self.end_batch();
+		result
+	}
+
+	/// Get the tile (along with its options and owner) at the given coordinate, so it can be
+	/// restored later
+	fn snapshot_tile<MId: MapId>(
+		&mut self,
+		pos: TilePos,
+		map_id: MId,
+		layer_id: u16,
+	) -> Option<TileSnapshot> {
+		let id = self.get_tile_id_at(pos, map_id, layer_id)?;
+		let options = self
+			.get_tile_entity(pos, map_id, layer_id)
+			.and_then(|entity| self.query.get(entity).ok())
+			.map(|(tile, ..)| TilePlacementOptions {
+				color: tile.color,
+				flip_x: tile.flip_x,
+				flip_y: tile.flip_y,
+				flip_d: tile.flip_d,
+			})
+			.unwrap_or_default();
+		let owner = self.get_owner_at(pos, map_id, layer_id);
+		Some((id, options, owner))
+	}
+
+	/// Place a tile taken by [`snapshot_tile`](Self::snapshot_tile) again
+	///
+	/// The tile is placed regardless of the [`PlacementRules`](crate::prelude::PlacementRules),
+	/// since it was already there. Returns true if the tile could be placed.
+	fn restore_tile(&mut self, coord: TileCoord, (tile_id, options, owner): TileSnapshot) -> bool {
+		let TileCoord {
+			pos,
+			map_id,
+			layer_id,
+		} = coord;
+		// The tile may no longer be placeable (e.g. its tileset was removed)
+		match self.place_unchecked_with(tile_id, pos, map_id, layer_id, &options) {
+			Ok(PlacedTile::Added { new_tile, .. }) => {
+				self.set_tile_owner(new_tile.0, owner);
+				true
+			},
+			_ => false,
+		}
+	}
+}
+
+#[cfg(all(test, not(feature = "headless")))]
+mod tests {
+	use bevy::prelude::Color;
+	use bevy_ecs_tilemap::TilePos;
+	use bevy_tileset::prelude::{TileHandle, TileId};
+
+	use crate::ownership::TileOwner;
+	use crate::placement::TilePlacementOptions;
+	use crate::testing::{self, MAP_SIZE, TILESET_ID};
+
+	#[test]
+	fn rollback_restores_tiles_options_and_owners() {
+		let mut app = testing::app();
+		let stone = testing::texture(&mut app, [128, 128, 128, 255]);
+		let wood = testing::texture(&mut app, [128, 64, 0, 255]);
+		testing::add_tileset(
+			&mut app,
+			vec![
+				TileHandle::new_standard("Stone", stone),
+				TileHandle::new_standard("Wood", wood),
+			],
+		);
+		testing::spawn_map(&mut app);
+
+		let stone = TileId::new(0, TILESET_ID);
+		let wood = TileId::new(1, TILESET_ID);
+		let owner = TileOwner(1);
+		let options = TilePlacementOptions {
+			color: Color::RED,
+			flip_x: true,
+			..Default::default()
+		};
+		let (owned, plain, empty) = (TilePos(1, 1), TilePos(2, 1), TilePos(3, 1));
+		testing::with_placer(&mut app, |placer| {
+			placer.place_with(stone, owned, 0u16, 0, options).unwrap();
+			placer.place(wood, plain, 0u16, 0).unwrap();
+		});
+		app.update();
+		testing::with_placer(&mut app, |placer| {
+			let entity = placer.get_tile_entity(owned, 0u16, 0).unwrap();
+			placer.set_tile_owner(entity, Some(owner));
+		});
+		app.update();
+
+		let result = testing::with_placer(&mut app, |placer| {
+			placer.transaction(|tx| {
+				tx.place(wood, owned, 0u16, 0)?;
+				tx.remove(plain, 0u16, 0)?;
+				tx.place(stone, empty, 0u16, 0)?;
+				// Editing the same coordinate again must still restore the original tile
+				tx.place(stone, owned, 0u16, 0)?;
+				tx.place(stone, TilePos(MAP_SIZE, 0), 0u16, 0)?;
+				Ok(())
+			})
+		});
+		assert!(result.is_err());
+		app.update();
+
+		let tile = testing::get_tile(&mut app, owned).unwrap();
+		assert_eq!(
+			(tile.color, tile.flip_x, tile.flip_y),
+			(Color::RED, true, false)
+		);
+		testing::with_placer(&mut app, |placer| {
+			assert_eq!(placer.get_tile_id_at(owned, 0u16, 0), Some(stone));
+			assert_eq!(placer.get_owner(owned, 0u16, 0).unwrap(), Some(owner));
+			assert_eq!(placer.get_tile_id_at(plain, 0u16, 0), Some(wood));
+			assert_eq!(placer.get_owner(plain, 0u16, 0).unwrap(), None);
+		});
+		assert!(testing::get_tile(&mut app, empty).is_none());
+	}
+}
@@ -0,0 +1,154 @@
+use bevy_ecs_tilemap::prelude::{MapId, Tile, TilePos};
+
+use crate::placement::{AutoTileAccess, TilePlacementError, TilePlacer};
+
+/// A 2x2 matrix mapping directions of a tile's texture (with the y-axis pointing up) to the world
+type Orientation = [[i8; 2]; 2];
+
+/// A change in orientation applied to an already placed tile
+///
+/// The tile is mirrored first, then rotated. The transformation is applied on top of the tile's
+/// current orientation, so rotating a tile four times brings it back to where it started.
+///
+/// # Examples
+///
+/// ```
+/// # use bevy_tileset_map::prelude::TileTransform;
+/// // Turn a pipe piece a quarter turn clockwise
+/// let transform = TileTransform::rotation(1);
+/// assert_eq!(TileTransform { flip_x: false, flip_y: false, rotate: 1 }, transform);
+/// ```
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct TileTransform {
+	/// Whether the tile is mirrored horizontally
+	pub flip_x: bool,
+	/// Whether the tile is mirrored vertically
+	pub flip_y: bool,
+	/// The number of 90° clockwise turns applied to the tile
+	pub rotate: u8,
+}
+
+impl TileTransform {
+	/// A transformation rotating the tile by the given number of 90° clockwise turns
+	pub fn rotation(turns: u8) -> Self {
+		Self {
+			rotate: turns,
+			..Default::default()
+		}
+	}
+
+	/// A transformation mirroring the tile horizontally
+	pub fn mirror_x() -> Self {
+		Self {
+			flip_x: true,
+			..Default::default()
+		}
+	}
+
+	/// A transformation mirroring the tile vertically
+	pub fn mirror_y() -> Self {
+		Self {
+			flip_y: true,
+			..Default::default()
+		}
+	}
+
+	/// Returns true if this transformation leaves tiles unchanged
+	pub fn is_identity(&self) -> bool {
+		let mut tile = Tile::default();
+		self.apply(&mut tile);
+		!tile.flip_x && !tile.flip_y && !tile.flip_d
+	}
+
+	/// Apply this transformation to the flip flags of the given tile
+	pub(crate) fn apply(&self, tile: &mut Tile) {
+		let mut matrix = orientation(tile.flip_x, tile.flip_y, tile.flip_d);
+		if self.flip_x {
+			matrix = multiply(orientation(true, false, false), matrix);
+		}
+		if self.flip_y {
+			matrix = multiply(orientation(false, true, false), matrix);
+		}
+		for _ in 0..self.rotate % 4 {
+			// A clockwise quarter turn is a diagonal flip followed by a horizontal flip
+			matrix = multiply(orientation(true, false, true), matrix);
+		}
+
+		// Every orientation corresponds to exactly one combination of flip flags
+		for flags in 0..8 {
+			let (x, y, d) = (flags & 1 != 0, flags & 2 != 0, flags & 4 != 0);
+			if orientation(x, y, d) == matrix {
+				tile.flip_x = x;
+				tile.flip_y = y;
+				tile.flip_d = d;
+				return;
+			}
+		}
+	}
+}
+
+impl<'w, 's, A: AutoTileAccess> TilePlacer<'w, 's, A> {
+	/// Change the orientation of the tile at the given coordinate without despawning it
+	///
+	/// # Arguments
+	///
+	/// * `pos`: The tile position
+	/// * `map_id`: The tile map
+	/// * `layer_id`: The layer within the tile map
+	/// * `transform`: The transformation to apply on top of the tile's current orientation
+	///
+	pub fn transform_tile<Pos: Into<TilePos>, MId: MapId>(
+		&mut self,
+		pos: Pos,
+		map_id: MId,
+		layer_id: u16,
+		transform: TileTransform,
+	) -> Result<(), TilePlacementError> {
+		self.check_writable(map_id, layer_id)?;
+		let pos = pos.into();
+		let entity = self.try_get_tile_entity(pos, map_id, layer_id)?;
+		if transform.is_identity() {
+			return Ok(());
+		}
+
+		let mut tile = self
+			.query
+			.get(entity)
+			.map(|(tile, ..)| *tile)
+			.unwrap_or_default();
+		transform.apply(&mut tile);
+		self.commands.entity(entity).insert(tile);
+		self.notify_chunk(pos, map_id, layer_id);
+		Ok(())
+	}
+}
+
+/// The orientation described by the given `bevy_ecs_tilemap` flip flags
+///
+/// Following the Tiled convention, the diagonal flip is applied first, followed by the horizontal
+/// flip, then the vertical flip.
+fn orientation(flip_x: bool, flip_y: bool, flip_d: bool) -> Orientation {
+	// Transposing the texture reflects it across its anti-diagonal in world space
+	let mut matrix = if flip_d {
+		[[0, -1], [-1, 0]]
+	} else {
+		[[1, 0], [0, 1]]
+	};
+	if flip_x {
+		matrix[0] = [-matrix[0][0], -matrix[0][1]];
+	}
+	if flip_y {
+		matrix[1] = [-matrix[1][0], -matrix[1][1]];
+	}
+	matrix
+}
+
+fn multiply(a: Orientation, b: Orientation) -> Orientation {
+	let mut result = [[0; 2]; 2];
+	for (row, result_row) in result.iter_mut().enumerate() {
+		for (col, value) in result_row.iter_mut().enumerate() {
+			*value = a[row][0] * b[0][col] + a[row][1] * b[1][col];
+		}
+	}
+	result
+}
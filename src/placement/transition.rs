@@ -0,0 +1,60 @@
+//! Resolving the [`TileTransition`] of placed tiles
+
+use bevy::prelude::Entity;
+use bevy_tileset::prelude::TileId;
+
+use crate::placement::{AutoTileAccess, TilePlacer};
+use crate::transitions::{
+	TileTransition, TRANSITION_AFTER_METADATA_KEY, TRANSITION_METADATA_KEY,
+	TRANSITION_ON_METADATA_KEY,
+};
+
+impl<'w, 's, A: AutoTileAccess> TilePlacer<'w, 's, A> {
+	/// Get the transition defined by the metadata of the given tile (if any)
+	///
+	/// Transitions without a duration or a trigger never happen, so `None` is returned for them.
+	pub fn get_transition(&self, tile_id: &TileId) -> Option<TileTransition> {
+		let tileset = self.tilesets.get_by_id(&tile_id.tileset_id)?;
+		let metadata = self.resources.metadata.get_by_id(tileset, tile_id)?;
+		let target = tileset.get_tile_group_id(metadata.get_str(TRANSITION_METADATA_KEY)?)?;
+
+		let remaining = metadata
+			.get_float(TRANSITION_AFTER_METADATA_KEY)
+			.map(|seconds| seconds as f32);
+		let trigger = metadata
+			.get_str(TRANSITION_ON_METADATA_KEY)
+			.map(String::from);
+		if remaining.is_none() && trigger.is_none() {
+			return None;
+		}
+
+		Some(TileTransition {
+			target: TileId::new(*target, *tileset.id()),
+			remaining,
+			trigger,
+		})
+	}
+
+	/// Set the pending transition of the given tile entity, replacing the one from its metadata
+	pub fn set_tile_transition(&mut self, entity: Entity, transition: TileTransition) {
+		self.commands.entity(entity).insert(transition);
+	}
+
+	/// Cancel the pending transition of the given tile entity
+	pub fn cancel_tile_transition(&mut self, entity: Entity) {
+		self.commands.entity(entity).remove::<TileTransition>();
+	}
+
+	/// Restore the remaining time of a saved transition onto a loaded tile
+	pub(crate) fn restore_transition(&mut self, entity: Entity, tile_id: &TileId, remaining: f32) {
+		if let Some(transition) = self.get_transition(tile_id) {
+			self.set_tile_transition(
+				entity,
+				TileTransition {
+					remaining: Some(remaining),
+					..transition
+				},
+			);
+		}
+	}
+}
@@ -0,0 +1,168 @@
+use bevy::prelude::{Color, Entity};
+use bevy_ecs_tilemap::prelude::{MapId, Tile, TilePos};
+
+use crate::elevation::ElevatedVisibility;
+use crate::placement::{AutoTileAccess, TilePlacementError, TilePlacer};
+
+impl<'w, 's, A: AutoTileAccess> TilePlacer<'w, 's, A> {
+	/// Show or hide the tile at the given coordinate without despawning it
+	///
+	/// # Arguments
+	///
+	/// * `pos`: The tile position
+	/// * `map_id`: The tile map
+	/// * `layer_id`: The layer within the tile map
+	/// * `visible`: Whether the tile should be visible
+	///
+	pub fn set_visible<Pos: Into<TilePos>, MId: MapId>(
+		&mut self,
+		pos: Pos,
+		map_id: MId,
+		layer_id: u16,
+		visible: bool,
+	) -> Result<(), TilePlacementError> {
+		self.check_writable(map_id, layer_id)?;
+		let pos = pos.into();
+		let entity = self
+			.map_query
+			.get_tile_entity(pos, map_id, layer_id)
+			.map_err(TilePlacementError::MapError)?;
+		if self.set_tile_visible(entity, visible) {
+			self.notify_chunk(pos, map_id, layer_id);
+		}
+		Ok(())
+	}
+
+	/// Set the color the tile at the given coordinate is tinted with
+	///
+	/// Returns the previous color of the tile.
+	///
+	/// # Arguments
+	///
+	/// * `pos`: The tile position
+	/// * `map_id`: The tile map
+	/// * `layer_id`: The layer within the tile map
+	/// * `color`: The new color of the tile
+	///
+	pub fn set_color<Pos: Into<TilePos>, MId: MapId>(
+		&mut self,
+		pos: Pos,
+		map_id: MId,
+		layer_id: u16,
+		color: Color,
+	) -> Result<Color, TilePlacementError> {
+		self.check_writable(map_id, layer_id)?;
+		let pos = pos.into();
+		let entity = self.try_get_tile_entity(pos, map_id, layer_id)?;
+		let tile = self
+			.query
+			.get(entity)
+			.map(|(tile, ..)| *tile)
+			.unwrap_or_default();
+
+		if tile.color != color {
+			self.commands.entity(entity).insert(Tile { color, ..tile });
+			self.notify_chunk(pos, map_id, layer_id);
+		}
+		Ok(tile.color)
+	}
+
+	/// Hide all tiles within the given rectangle (inclusive)
+	///
+	/// Empty coordinates are skipped. Returns the number of tiles that were hidden.
+	///
+	/// # Arguments
+	///
+	/// * `from`: A corner of the rectangle
+	/// * `to`: The opposite corner of the rectangle
+	/// * `map_id`: The tile map
+	/// * `layer_id`: The layer within the tile map
+	///
+	pub fn hide_region<Pos: Into<TilePos>, MId: MapId>(
+		&mut self,
+		from: Pos,
+		to: Pos,
+		map_id: MId,
+		layer_id: u16,
+	) -> usize {
+		self.set_region_visible(from.into(), to.into(), map_id, layer_id, false)
+	}
+
+	/// Show all tiles within the given rectangle (inclusive)
+	///
+	/// Empty coordinates are skipped. Returns the number of tiles that were shown.
+	///
+	/// # Arguments
+	///
+	/// * `from`: A corner of the rectangle
+	/// * `to`: The opposite corner of the rectangle
+	/// * `map_id`: The tile map
+	/// * `layer_id`: The layer within the tile map
+	///
+	pub fn show_region<Pos: Into<TilePos>, MId: MapId>(
+		&mut self,
+		from: Pos,
+		to: Pos,
+		map_id: MId,
+		layer_id: u16,
+	) -> usize {
+		self.set_region_visible(from.into(), to.into(), map_id, layer_id, true)
+	}
+
+	fn set_region_visible<MId: MapId>(
+		&mut self,
+		from: TilePos,
+		to: TilePos,
+		map_id: MId,
+		layer_id: u16,
+		visible: bool,
+	) -> usize {
+		if self.check_writable(map_id, layer_id).is_err() {
+			return 0;
+		}
+
+		let mut count = 0;
+		for y in from.1.min(to.1)..=from.1.max(to.1) {
+			for x in from.0.min(to.0)..=from.0.max(to.0) {
+				let pos = TilePos(x, y);
+				if let Ok(entity) = self.map_query.get_tile_entity(pos, map_id, layer_id) {
+					if self.set_tile_visible(entity, visible) {
+						self.notify_chunk(pos, map_id, layer_id);
+						count += 1;
+					}
+				}
+			}
+		}
+		count
+	}
+
+	/// Sets the visibility of the given tile entity
+	///
+	/// Returns true if the visibility was changed.
+	fn set_tile_visible(&mut self, entity: Entity, visible: bool) -> bool {
+		let (tile, elevated) = if let Ok((tile, _, elevated)) = self.query.get(entity) {
+			(tile, elevated)
+		} else {
+			return false;
+		};
+
+		// Elevated tiles are always hidden from their chunk
+		if let Some(elevated) = elevated {
+			if elevated.visible == visible {
+				return false;
+			}
+			self.commands
+				.entity(entity)
+				.insert(ElevatedVisibility { visible });
+			return true;
+		}
+
+		if tile.visible == visible {
+			return false;
+		}
+
+		let tile = Tile { visible, ..*tile };
+		self.commands.entity(entity).insert(tile);
+		true
+	}
+}
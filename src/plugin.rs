@@ -1,6 +1,18 @@
+use bevy::app::PluginGroupBuilder;
+use bevy::ecs::schedule::ParallelSystemDescriptor;
 use bevy::prelude::*;
+#[cfg(not(feature = "headless"))]
 use bevy_ecs_tilemap::TilemapStage;
 
+/// The stage in which tiles are prepared right before their chunks are updated
+#[cfg(not(feature = "headless"))]
+const TILEMAP_STAGE: TilemapStage = TilemapStage;
+/// The stage in which tiles are prepared right before their chunks are updated
+///
+/// Headless builds don't add the `TilemapPlugin`, so there is no `TilemapStage` to use.
+#[cfg(feature = "headless")]
+const TILEMAP_STAGE: CoreStage = CoreStage::PostUpdate;
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash, StageLabel)]
 pub struct TilesetMapStage;
 
@@ -10,29 +22,501 @@ pub enum TilesetMapLabel {
 	UpdateAutoTiles,
 	/// Labels the system that handles auto tile removals
 	RemoveAutoTiles,
+	/// Labels the system that keeps the collision map up to date
+	UpdateCollisionMap,
+	/// Labels the system that regenerates tile colliders
+	UpdateColliders,
+	/// Labels the system that keeps the map registry up to date
+	UpdateMapRegistry,
+	/// Labels the system that keeps the navigation grid up to date
+	UpdateNavGrid,
+	/// Labels the system that tracks the tilemap generation
+	UpdateGeneration,
+	/// Labels the system that computes the active chunks of each layer
+	UpdateActiveChunks,
+	/// Labels the system that keeps the ambience index up to date
+	UpdateAmbienceIndex,
+	/// Labels the system that samples the ambience around each listener
+	SampleAmbience,
+	/// Labels the system that keeps the fog of war overlay up to date
+	UpdateFogOfWar,
+	/// Labels the system that applies (or queues) tile edit requests
+	ApplyEditRequests,
+	/// Labels the system that flushes pending chunk notifications
+	FlushChunks,
+	/// Labels the system that sends chunk lifecycle events
+	SendChunkEvents,
+	/// Labels the system that offsets the layers of isometric elevation maps
+	UpdateIsoLayers,
+	/// Labels the system that moves parallax layers relative to the camera
+	UpdateParallax,
+	/// Labels the system that steps one-shot tile animations
+	UpdateTileAnimations,
+	/// Labels the system that replaces or removes tiles whose one-shot animation has finished
+	FinishTileAnimations,
+	/// Labels the system that applies tile transitions
+	UpdateTileTransitions,
+	/// Labels the system that spawns and unloads the regions of the infinite map
+	UpdateInfiniteMap,
+	/// Labels the system that generates newly spawned regions of the infinite map
+	GenerateRegions,
+	/// Labels the system that unloads empty regions of the infinite map
+	CollectEmptyRegions,
+	/// Labels the system that validates runtime invariants (`strict` feature)
+	CheckInvariants,
+	/// Labels the system that collects the tiles changed during the frame
+	TrackChanges,
+	/// Labels the system that keeps the index of placed tiles up to date
+	UpdatePlacedTiles,
+	/// Labels the system that discards outdated cached connected regions
+	InvalidateConnectedRegions,
+	/// Labels the system that keeps the highlights of the tile selection up to date
+	UpdateTileHighlights,
+	/// Labels the system that removes despawned objects from the object index
+	UpdateTileObjects,
+	/// Labels the system that hides placed marker tiles and sends their events
+	HandlePlacedMarkers,
+	/// Labels the system that saves dirty chunks (`serialization` feature)
+	Autosave,
+	/// Labels the system that patches placed tiles of hot-reloaded tilesets
+	PatchReloadedTilesets,
+	/// Labels the system that collects tile usage statistics
+	UpdateDiagnostics,
+	/// Labels the system that applies the edits queued in the placement budget
+	ApplyBudgetedPlacements,
+	/// Labels the system that applies the edits queued by scripts (`scripting` feature)
+	ApplyScriptEdits,
+	/// Labels the system that copies the placed tiles for scripts (`scripting` feature)
+	SyncScriptTiles,
+	/// Labels the system that keeps the inspector info of tiles up to date (`inspector` feature)
+	UpdateTileInfo,
+	/// Labels the system that redraws the Auto tile debug overlay (`auto-tile-debug` feature)
+	UpdateAutoTileOverlay,
+}
+
+/// The stage in which tiles are prepared right before their chunks are updated
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ChunkUpdateStage {
+	/// The `TilemapStage` of `bevy_ecs_tilemap`
+	///
+	/// This requires the `TilemapPlugin` to be added before the [`TilesetMapPlugin`]. With the
+	/// `headless` feature enabled, [`CoreStage::PostUpdate`] is used instead.
+	Tilemap,
+	/// The given core stage
+	///
+	/// Use this when the `TilemapPlugin` is added after the [`TilesetMapPlugin`] (or not at all).
+	Core(CoreStage),
+}
+
+/// Configures how the [`TilesetMapPlugin`] schedules its systems
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TilesetMapPluginConfig {
+	/// Whether the plugin adds the [`TilesetMapStage`] (right before the chunk update stage)
+	///
+	/// If `false`, the stage must already have been added to the app, allowing it to be placed
+	/// anywhere before the chunk update stage.
+	pub add_stage: bool,
+	/// The stage in which tiles are prepared right before their chunks are updated
+	pub chunk_update_stage: ChunkUpdateStage,
 }
 
 /// Plugin for setting up tilesets
-#[derive(Default)]
-pub struct TilesetMapPlugin;
+///
+/// With the `headless` feature enabled, this plugin no longer requires the `TilemapPlugin` and
+/// can be used on dedicated servers without any rendering.
+///
+/// Misconfigured stages (such as adding this plugin before the `TilemapPlugin`) don't panic:
+/// instead, an error is logged and the plugin falls back to [`CoreStage::PostUpdate`].
+///
+/// # Examples
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_tileset_map::prelude::{
+/// #   ChunkUpdateStage, TilesetMapPlugin, TilesetMapPluginConfig, TilesetMapStage,
+/// # };
+/// App::new()
+///   .add_stage_after(CoreStage::Update, TilesetMapStage, SystemStage::parallel())
+///   .add_plugin(TilesetMapPlugin::new(TilesetMapPluginConfig {
+///     add_stage: false,
+///     chunk_update_stage: ChunkUpdateStage::Core(CoreStage::PostUpdate),
+///   }));
+/// ```
+#[derive(Debug, Default)]
+pub struct TilesetMapPlugin {
+	config: TilesetMapPluginConfig,
+}
+
+impl Default for TilesetMapPluginConfig {
+	fn default() -> Self {
+		Self {
+			add_stage: true,
+			chunk_update_stage: ChunkUpdateStage::Tilemap,
+		}
+	}
+}
+
+impl TilesetMapPlugin {
+	/// Create the plugin with the given config
+	pub fn new(config: TilesetMapPluginConfig) -> Self {
+		Self { config }
+	}
+
+	/// The config of this plugin
+	pub fn config(&self) -> &TilesetMapPluginConfig {
+		&self.config
+	}
+
+	/// Get the configured chunk update stage, falling back to [`CoreStage::PostUpdate`] if it
+	/// doesn't exist
+	fn chunk_update_stage(&self, app: &App) -> ChunkUpdateStage {
+		let stage = self.config.chunk_update_stage.clone();
+		if stage.exists(app) {
+			return stage;
+		}
+
+		error!(
+			"Could not find the chunk update stage {:?} of the TilesetMapPlugin, using \
+			 CoreStage::PostUpdate instead. Add the TilemapPlugin before the TilesetMapPlugin or \
+			 configure the stage using TilesetMapPlugin::new.",
+			stage
+		);
+		ChunkUpdateStage::Core(CoreStage::PostUpdate)
+	}
+}
+
+impl ChunkUpdateStage {
+	/// Returns true if this stage has been added to the app
+	fn exists(&self, app: &App) -> bool {
+		match self {
+			Self::Tilemap => app.schedule.get_stage::<SystemStage>(&TILEMAP_STAGE),
+			Self::Core(stage) => app.schedule.get_stage::<SystemStage>(stage),
+		}
+		.is_some()
+	}
+
+	/// Add the given stage right before this one
+	fn add_stage_before<S: StageLabel>(&self, app: &mut App, label: S, stage: SystemStage) {
+		match self {
+			Self::Tilemap => app.add_stage_before(TILEMAP_STAGE, label, stage),
+			Self::Core(core) => app.add_stage_before(core.clone(), label, stage),
+		};
+	}
+
+	/// Add the given system to this stage, ordered before chunks are updated
+	fn add_system(&self, app: &mut App, system: ParallelSystemDescriptor) {
+		match self {
+			Self::Tilemap => app.add_system_to_stage(TILEMAP_STAGE, before_chunk_update(system)),
+			Self::Core(core) => app.add_system_to_stage(core.clone(), system),
+		};
+	}
+}
+
+/// A group containing every plugin needed to use this crate, added in the correct order
+///
+/// This adds (in order):
+/// 1. The `TilemapPlugin` of `bevy_ecs_tilemap` (not added with the `headless` feature enabled)
+/// 2. The [`TilesetPlugin`](bevy_tileset::prelude::TilesetPlugin) of `bevy_tileset`
+/// 3. The [`TilesetMapPlugin`]
+///
+/// Any of the first two can be skipped if they're already added elsewhere.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_tileset_map::prelude::TilesetMapPlugins;
+/// App::new()
+///   .add_plugins(DefaultPlugins)
+///   .add_plugins(TilesetMapPlugins::default())
+///   .run();
+/// ```
+#[derive(Debug)]
+pub struct TilesetMapPlugins {
+	/// Whether to add the `TilemapPlugin`
+	pub tilemap: bool,
+	/// Whether to add the `TilesetPlugin`
+	pub tileset: bool,
+	/// The config of the added [`TilesetMapPlugin`]
+	pub config: TilesetMapPluginConfig,
+}
+
+impl Default for TilesetMapPlugins {
+	fn default() -> Self {
+		Self {
+			tilemap: true,
+			tileset: true,
+			config: TilesetMapPluginConfig::default(),
+		}
+	}
+}
+
+impl PluginGroup for TilesetMapPlugins {
+	fn build(&mut self, group: &mut PluginGroupBuilder) {
+		#[cfg(not(feature = "headless"))]
+		if self.tilemap {
+			group.add(bevy_ecs_tilemap::TilemapPlugin);
+		}
+		if self.tileset {
+			group.add(bevy_tileset::prelude::TilesetPlugin::default());
+		}
+		group.add(TilesetMapPlugin::new(self.config.clone()));
+	}
+}
 
 impl Plugin for TilesetMapPlugin {
 	fn build(&self, app: &mut App) {
-		app.add_stage_before(TilemapStage, TilesetMapStage, SystemStage::parallel());
+		let chunk_update_stage = self.chunk_update_stage(app);
+		let has_stage = app
+			.schedule
+			.get_stage::<SystemStage>(&TilesetMapStage)
+			.is_some();
+		if !self.config.add_stage && !has_stage {
+			error!(
+				"The TilesetMapPlugin was configured not to add the TilesetMapStage, but it does \
+				 not exist. Adding it before the chunk update stage instead."
+			);
+		}
+		if !has_stage {
+			chunk_update_stage.add_stage_before(app, TilesetMapStage, SystemStage::parallel());
+		}
 
-		#[cfg(feature = "auto-tile")]
-		app.add_event::<crate::auto::RemoveAutoTileEvent>()
-			.add_system_set_to_stage(
+		chunk_update_stage.add_system(
+			app,
+			crate::remesh::flush_chunk_notifications.label(TilesetMapLabel::FlushChunks),
+		);
+
+		app.init_resource::<crate::config::TilesetMapConfig>()
+			.init_resource::<crate::metadata::TileMetadataRegistry>()
+			.init_resource::<crate::placement::TilePlacementHooks>()
+			.init_resource::<crate::placement::PlacementRules>()
+			.init_resource::<crate::placement::LayerPolicy>()
+			.init_resource::<crate::placement::WeightedTileGroups>()
+			.init_resource::<crate::placement::PlacementPreview>()
+			.init_resource::<crate::collision::TileCollisionRegistry>()
+			.init_resource::<crate::collision::CollisionMap>()
+			.init_resource::<crate::registry::TilesetMapRegistry>()
+			.init_resource::<crate::combined::CombinedTilesets>()
+			.init_resource::<crate::snapshot::TilemapGeneration>()
+			.init_resource::<crate::activity::ActivityConfig>()
+			.init_resource::<crate::activity::ActiveChunks>()
+			.init_resource::<crate::substitution::TileSubstitutions>()
+			.init_resource::<crate::locking::MapLocks>()
+			.init_resource::<crate::locking::ConflictResolution>()
+			.init_resource::<crate::remesh::ChunkNotifications>()
+			.init_resource::<crate::changes::TilemapChanges>()
+			.init_resource::<crate::placed::PlacedTiles>()
+			.init_resource::<crate::objects::TileObjects>()
+			.init_resource::<crate::objects::TileObjectSpawners>()
+			.init_resource::<crate::markers::TileMarkers>()
+			.init_resource::<crate::selection::TileSelection>()
+			.init_resource::<crate::iso::IsoElevationConfig>()
+			.init_resource::<crate::connectivity::ConnectedRegionCache>()
+			.init_resource::<crate::reload::TilesetSnapshots>()
+			.init_resource::<crate::budget::PlacementBudget>()
+			.init_resource::<crate::limiter::PlacementLimits>()
+			.add_event::<crate::locking::TileEditRequest>()
+			.add_event::<crate::locking::TileEditEvent>()
+			.add_event::<crate::locking::TileEditConflict>()
+			.add_event::<crate::markers::MarkerPlaced>()
+			.add_event::<crate::chunks::ChunkSpawnedEvent>()
+			.add_event::<crate::chunks::ChunkDespawnedEvent>()
+			.add_event::<crate::animator::TileAnimationFinishedEvent>()
+			.add_event::<crate::transitions::TriggerTileTransitions>()
+			.add_event::<crate::transitions::TileTransitionedEvent>()
+			.add_event::<crate::reload::TilesetReloadedEvent>()
+			.add_event::<crate::budget::PlacementBatchCompleted>()
+			.add_asset::<crate::template::MapTemplate>()
+			.init_asset_loader::<crate::template::MapTemplateLoader>()
+			.register_type::<crate::coord::TileCoord>()
+			.register_type::<crate::flags::TileFlags>()
+			.register_type::<crate::ownership::TileOwner>()
+			.register_type::<crate::elevation::TileElevation>()
+			.register_type::<crate::parallax::LayerParallax>()
+			.register_type::<crate::infinite::InfiniteMapTracker>()
+			.register_type::<crate::scene::SceneTile>()
+			.register_type::<crate::scene::SceneLayer>()
+			.add_system_to_stage(
+				TilesetMapStage,
+				crate::collision::update_collision_map.label(TilesetMapLabel::UpdateCollisionMap),
+			)
+			.add_system_to_stage(
+				TilesetMapStage,
+				crate::registry::update_map_registry.label(TilesetMapLabel::UpdateMapRegistry),
+			)
+			.add_system_to_stage(
+				TilesetMapStage,
+				crate::snapshot::update_tilemap_generation.label(TilesetMapLabel::UpdateGeneration),
+			)
+			.add_system_to_stage(
 				TilesetMapStage,
-				SystemSet::new().with_system(
-					crate::auto::on_remove_auto_tile.label(TilesetMapLabel::RemoveAutoTiles),
-				),
+				crate::activity::update_active_chunks
+					.label(TilesetMapLabel::UpdateActiveChunks)
+					.after(TilesetMapLabel::UpdateMapRegistry),
 			)
 			.add_system_to_stage(
-				TilemapStage,
-				crate::auto::on_change_auto_tile
-					.label(TilesetMapLabel::UpdateAutoTiles)
-					.before(bevy_ecs_tilemap::TilemapLabel::UpdateChunkVisibility),
+				TilesetMapStage,
+				crate::chunks::send_chunk_events
+					.label(TilesetMapLabel::SendChunkEvents)
+					.after(TilesetMapLabel::UpdateMapRegistry),
+			)
+			.add_system_to_stage(
+				TilesetMapStage,
+				crate::parallax::update_layer_parallax.label(TilesetMapLabel::UpdateParallax),
+			)
+			.add_system_to_stage(
+				TilesetMapStage,
+				crate::iso::update_iso_layers
+					.label(TilesetMapLabel::UpdateIsoLayers)
+					.after(TilesetMapLabel::UpdateMapRegistry),
+			)
+			.add_system_to_stage(
+				TilesetMapStage,
+				crate::animator::update_tile_animations
+					.label(TilesetMapLabel::UpdateTileAnimations),
+			)
+			.add_system_to_stage(
+				TilesetMapStage,
+				crate::animator::finish_tile_animations
+					.label(TilesetMapLabel::FinishTileAnimations)
+					.after(TilesetMapLabel::UpdateTileAnimations),
+			)
+			.add_system_to_stage(
+				TilesetMapStage,
+				crate::transitions::update_tile_transitions
+					.label(TilesetMapLabel::UpdateTileTransitions),
+			)
+			.add_system_to_stage(
+				TilesetMapStage,
+				crate::locking::apply_tile_edit_requests.label(TilesetMapLabel::ApplyEditRequests),
+			)
+			.add_system_to_stage(
+				TilesetMapStage,
+				crate::budget::apply_budgeted_placements
+					.label(TilesetMapLabel::ApplyBudgetedPlacements),
+			)
+			.add_system_to_stage(
+				TilesetMapStage,
+				crate::markers::handle_placed_markers.label(TilesetMapLabel::HandlePlacedMarkers),
+			)
+			.add_system_to_stage(
+				TilesetMapStage,
+				crate::selection::update_tile_highlights
+					.label(TilesetMapLabel::UpdateTileHighlights),
+			)
+			.add_system_to_stage(
+				TilesetMapStage,
+				crate::reload::patch_reloaded_tilesets
+					.label(TilesetMapLabel::PatchReloadedTilesets),
+			)
+			.add_system_to_stage(
+				CoreStage::Last,
+				crate::changes::track_tilemap_changes.label(TilesetMapLabel::TrackChanges),
+			)
+			.add_system_to_stage(
+				CoreStage::Last,
+				crate::placed::update_placed_tiles.label(TilesetMapLabel::UpdatePlacedTiles),
+			)
+			.add_system_to_stage(
+				CoreStage::Last,
+				crate::objects::update_tile_objects.label(TilesetMapLabel::UpdateTileObjects),
+			)
+			.add_system_to_stage(
+				CoreStage::Last,
+				crate::connectivity::invalidate_connected_regions
+					.label(TilesetMapLabel::InvalidateConnectedRegions)
+					.after(TilesetMapLabel::TrackChanges),
+			);
+
+		#[cfg(not(feature = "headless"))]
+		app.init_resource::<crate::preview::TilePreviewCache>()
+			.add_system_to_stage(TilesetMapStage, crate::placement::sync_preview_overlays);
+
+		#[cfg(feature = "serialization")]
+		app.init_resource::<crate::serialization::TilemapFormats>()
+			.init_resource::<crate::serialization::TilemapMigrations>();
+
+		#[cfg(feature = "replication")]
+		app.init_resource::<crate::replication::ReplicationState>();
+
+		#[cfg(feature = "destructible")]
+		app.add_event::<crate::destructible::TileDestroyedEvent>();
+
+		#[cfg(feature = "physics-rapier")]
+		app.init_resource::<crate::physics::TileColliders>()
+			.add_system_to_stage(
+				TilesetMapStage,
+				crate::physics::update_tile_colliders.label(TilesetMapLabel::UpdateColliders),
+			);
+
+		#[cfg(feature = "nav")]
+		app.init_resource::<crate::nav::NavGrid>()
+			.add_system_to_stage(
+				TilesetMapStage,
+				crate::nav::update_nav_grid.label(TilesetMapLabel::UpdateNavGrid),
+			);
+
+		#[cfg(feature = "ambience")]
+		app.init_resource::<crate::ambience::AmbienceIndex>()
+			.init_resource::<crate::ambience::AmbienceSamples>()
+			.add_event::<crate::ambience::AmbienceChanged>()
+			.add_system_to_stage(
+				TilesetMapStage,
+				crate::ambience::update_ambience_index.label(TilesetMapLabel::UpdateAmbienceIndex),
+			)
+			.add_system_to_stage(
+				TilesetMapStage,
+				crate::ambience::sample_ambience
+					.label(TilesetMapLabel::SampleAmbience)
+					.after(TilesetMapLabel::UpdateAmbienceIndex)
+					.after(TilesetMapLabel::UpdateMapRegistry),
 			);
+
+		#[cfg(feature = "strict")]
+		app.init_resource::<crate::strict::StrictModeConfig>()
+			.add_system_to_stage(
+				CoreStage::Last,
+				crate::strict::check_invariants.label(TilesetMapLabel::CheckInvariants),
+			);
+
+		#[cfg(all(feature = "elevation", not(feature = "headless")))]
+		{
+			app.init_resource::<crate::elevation::ElevatedTiles>();
+			chunk_update_stage.add_system(
+				app,
+				crate::elevation::sync_elevated_tiles.after(TilesetMapLabel::UpdateAutoTiles),
+			);
+		}
+
+		#[cfg(feature = "auto-tile")]
+		{
+			app.add_event::<crate::auto::RemoveAutoTileEvent>()
+				.add_system_set_to_stage(
+					TilesetMapStage,
+					SystemSet::new().with_system(
+						crate::auto::on_remove_auto_tile.label(TilesetMapLabel::RemoveAutoTiles),
+					),
+				);
+			chunk_update_stage.add_system(
+				app,
+				crate::auto::on_change_auto_tile.label(TilesetMapLabel::UpdateAutoTiles),
+			);
+		}
+
+		#[cfg(all(feature = "auto-tile-debug", not(feature = "headless")))]
+		app.init_resource::<crate::auto::AutoTileDebugOverlay>()
+			.add_system_to_stage(
+				CoreStage::Last,
+				crate::auto::update_auto_tile_overlay.label(TilesetMapLabel::UpdateAutoTileOverlay),
+			);
+	}
+}
+
+/// Order the given system before chunks are updated for the current frame
+fn before_chunk_update(system: ParallelSystemDescriptor) -> ParallelSystemDescriptor {
+	if cfg!(feature = "headless") {
+		system
+	} else {
+		system.before(bevy_ecs_tilemap::TilemapLabel::UpdateChunkVisibility)
 	}
 }
@@ -0,0 +1,171 @@
+//! Thumbnail images of individual tiles for building palettes and other UI
+//!
+//! Tileset textures contain every tile of a tileset, which makes them awkward to display within
+//! UI toolkits that can't crop images. [`TilePreviews`] renders single tiles into their own
+//! images, caching them so each tile is only rendered once.
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension};
+use bevy::utils::HashMap;
+use bevy_tileset::prelude::{TileId, Tileset, Tilesets};
+
+use crate::combined::copy_rect;
+
+/// A resource caching the preview images rendered by [`TilePreviews`]
+///
+/// This resource is added automatically by the [`TilesetMapPlugin`](crate::prelude::TilesetMapPlugin).
+#[derive(Debug, Default)]
+pub struct TilePreviewCache {
+	images: HashMap<TileId, Handle<Image>>,
+	#[cfg(feature = "editor-egui")]
+	textures: HashMap<TileId, bevy_egui::egui::TextureId>,
+}
+
+/// A helper system param used to get a preview image of a single tile
+///
+/// The preview shows the first frame of animated tiles, the first variant of variant tiles, and
+/// the first piece of auto tiles (unless the given [`TileId`] specifies one).
+///
+/// # Examples
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_tileset_map::prelude::{TileId, TilePreviews};
+/// struct Selected(TileId);
+///
+/// fn show_selected(mut previews: TilePreviews, selected: Res<Selected>, mut images: Query<&mut UiImage>) {
+///   if let Some(preview) = previews.get(selected.0) {
+///     for mut image in images.iter_mut() {
+///       image.0 = preview.clone();
+///     }
+///   }
+/// }
+/// ```
+#[derive(SystemParam)]
+pub struct TilePreviews<'w, 's> {
+	tilesets: Tilesets<'w, 's>,
+	images: ResMut<'w, Assets<Image>>,
+	atlases: Res<'w, Assets<TextureAtlas>>,
+	cache: ResMut<'w, TilePreviewCache>,
+}
+
+impl TilePreviewCache {
+	/// Get the cached preview of the given tile (if any)
+	pub fn get(&self, tile_id: &TileId) -> Option<&Handle<Image>> {
+		self.images.get(tile_id)
+	}
+
+	/// Forget the preview of the given tile so it is rendered again when next requested
+	pub fn remove(&mut self, tile_id: &TileId) -> Option<Handle<Image>> {
+		#[cfg(feature = "editor-egui")]
+		self.textures.remove(tile_id);
+		self.images.remove(tile_id)
+	}
+
+	/// Forget every cached preview (such as after a tileset was modified)
+	pub fn clear(&mut self) {
+		#[cfg(feature = "editor-egui")]
+		self.textures.clear();
+		self.images.clear();
+	}
+
+	/// The number of cached previews
+	pub fn len(&self) -> usize {
+		self.images.len()
+	}
+
+	/// Returns true if no previews are cached
+	pub fn is_empty(&self) -> bool {
+		self.images.is_empty()
+	}
+}
+
+impl<'w, 's> TilePreviews<'w, 's> {
+	/// Get the preview image of the given tile, rendering it if needed
+	///
+	/// Returns `None` if the tile does not exist or its tileset's texture is not loaded (yet).
+	pub fn get<Id: Into<TileId>>(&mut self, tile_id: Id) -> Option<Handle<Image>> {
+		let tile_id = tile_id.into();
+		if let Some(handle) = self.cache.images.get(&tile_id) {
+			return Some(handle.clone());
+		}
+
+		let tileset = self.tilesets.get_by_id(&tile_id.tileset_id)?;
+		let index = preview_index(tileset, &tile_id)?;
+		let rect = *self.atlases.get(tileset.atlas())?.textures.get(index)?;
+		let source = self.images.get(tileset.texture())?;
+		let format = source.texture_descriptor.format;
+		if format.describe().block_dimensions != (1, 1) {
+			return None;
+		}
+
+		let (width, height) = (
+			(rect.max.x - rect.min.x) as usize,
+			(rect.max.y - rect.min.y) as usize,
+		);
+		let pixel_size = format.describe().block_size as usize;
+		let mut data = vec![0; width * height * pixel_size];
+		copy_rect(source, rect, &mut data, width, (0, 0));
+
+		let mut image = Image::new(
+			Extent3d {
+				width: width as u32,
+				height: height as u32,
+				depth_or_array_layers: 1,
+			},
+			TextureDimension::D2,
+			data,
+			format,
+		);
+		image.texture_descriptor.usage = source.texture_descriptor.usage;
+		image.sampler_descriptor = source.sampler_descriptor.clone();
+
+		let handle = self.images.add(image);
+		self.cache.images.insert(tile_id, handle.clone());
+		Some(handle)
+	}
+
+	/// Get the preview of the given tile as an egui texture, rendering it if needed
+	///
+	/// Returns `None` if the tile does not exist or its tileset's texture is not loaded (yet).
+	#[cfg(feature = "editor-egui")]
+	pub fn get_egui<Id: Into<TileId>>(
+		&mut self,
+		tile_id: Id,
+		egui_context: &mut bevy_egui::EguiContext,
+	) -> Option<bevy_egui::egui::TextureId> {
+		let tile_id = tile_id.into();
+		if let Some(texture) = self.cache.textures.get(&tile_id) {
+			return Some(*texture);
+		}
+
+		let handle = self.get(tile_id)?;
+		let texture = egui_context.add_image(handle);
+		self.cache.textures.insert(tile_id, texture);
+		Some(texture)
+	}
+
+	/// Get the cache containing all rendered previews
+	pub fn cache(&mut self) -> &mut TilePreviewCache {
+		&mut self.cache
+	}
+}
+
+/// Find the texture index shown as the preview of the given tile
+///
+/// This is the index matching the tile exactly (including its variant or auto tile piece) if
+/// there is one, or otherwise the first index belonging to its group.
+fn preview_index(tileset: &Tileset, tile_id: &TileId) -> Option<usize> {
+	let mut first = None;
+	// Every texture index of a tileset belongs to a tile
+	for (index, id) in (0..).map_while(|index| Some((index, tileset.get_tile_id(&index)?))) {
+		if id == tile_id {
+			return Some(index);
+		}
+		if first.is_none() && id.eq_tile_group(tile_id) {
+			first = Some(index);
+		}
+	}
+	first
+}
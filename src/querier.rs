@@ -0,0 +1,128 @@
+//! Efficiently reading the tiles within an area
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::{Chunk, LocalTilePos, MapId, Tile, TileParent, TilePos};
+use bevy_tileset::prelude::{TileId, TilesetParent, Tilesets};
+
+use crate::combined::CombinedTilesets;
+use crate::coord::TileCoord;
+use crate::registry::TilesetMapRegistry;
+
+/// A helper system param used to scan the tiles within an area of a layer
+///
+/// Rather than looking up the entity of every position individually, tiles are read directly from
+/// the chunks overlapping the area.
+///
+/// # Examples
+///
+/// ```
+/// # use bevy_ecs_tilemap::TilePos;
+/// # use bevy_tileset_map::prelude::TileQuerier;
+/// fn count_walls(querier: TileQuerier) {
+/// #   let wall = bevy_tileset_map::prelude::TileId::new(0, 0);
+///   let walls = querier
+///     .iter_region(TilePos(0, 0), TilePos(15, 15), 0u16, 0u16)
+///     .filter(|(.., id)| *id == Some(wall))
+///     .count();
+/// }
+/// ```
+#[derive(SystemParam)]
+pub struct TileQuerier<'w, 's> {
+	registry: Res<'w, TilesetMapRegistry>,
+	tilesets: Tilesets<'w, 's>,
+	chunks: Query<'w, 's, &'static Chunk>,
+	tiles: Query<
+		'w,
+		's,
+		(
+			&'static Tile,
+			Option<&'static TilesetParent>,
+			Option<&'static TileParent>,
+		),
+	>,
+	combined: Res<'w, CombinedTilesets>,
+}
+
+impl<'w, 's> TileQuerier<'w, 's> {
+	/// Iterate over every tile within the given rectangle (inclusive) of the given layer
+	///
+	/// Each item contains the coordinate of the tile, its entity, and its ID (if it belongs to a
+	/// loaded tileset). Tiles are yielded chunk by chunk, so their order is unspecified.
+	///
+	/// Only layers known to the [`TilesetMapRegistry`] can be queried.
+	pub fn iter_region<Pos: Into<TilePos>, MId: MapId>(
+		&self,
+		min: Pos,
+		max: Pos,
+		map_id: MId,
+		layer_id: u16,
+	) -> impl Iterator<Item = (TileCoord, Entity, Option<TileId>)> + '_ {
+		let map_id = map_id.into();
+		let (min, max) = (min.into(), max.into());
+		let (min, max) = (
+			UVec2::new(min.0.min(max.0), min.1.min(max.1)),
+			UVec2::new(min.0.max(max.0), min.1.max(max.1)),
+		);
+
+		let (layer_entity, chunk_size) = self
+			.registry
+			.layer(map_id, layer_id)
+			.map(|layer| {
+				let chunk_size = layer.settings.chunk_size;
+				(Some(layer.entity), UVec2::new(chunk_size.0, chunk_size.1))
+			})
+			.unwrap_or_default();
+
+		self.chunks
+			.iter()
+			.filter(move |chunk| {
+				// Chunks refer to their layer through their `map_entity`
+				Some(chunk.map_entity) == layer_entity && chunk_size.cmpgt(UVec2::ZERO).all()
+			})
+			.flat_map(move |chunk| {
+				// The part of the region covered by this chunk (in tiles)
+				let origin = UVec2::new(chunk.position.0, chunk.position.1) * chunk_size;
+				let from = min.max(origin);
+				let to = max.min(origin + chunk_size - UVec2::ONE);
+				let positions = if from.cmple(to).all() {
+					Some(
+						(from.y..=to.y)
+							.flat_map(move |y| (from.x..=to.x).map(move |x| UVec2::new(x, y))),
+					)
+				} else {
+					None
+				};
+
+				positions.into_iter().flatten().filter_map(move |pos| {
+					let local = pos - origin;
+					let entity = chunk.get_tile_entity(LocalTilePos(local.x, local.y))?;
+					let coord = TileCoord {
+						pos: TilePos(pos.x, pos.y),
+						map_id,
+						layer_id,
+					};
+					Some((coord, entity, self.get_tile_id(entity)))
+				})
+			})
+	}
+
+	/// Get the ID of the given tile entity (if it belongs to a loaded tileset)
+	pub fn get_tile_id(&self, entity: Entity) -> Option<TileId> {
+		let (tile, tileset, parent) = self.tiles.get(entity).ok()?;
+		let tileset_id = tileset?.0;
+		let index = match parent {
+			Some(parent) => self.combined.local_index(
+				&tileset_id,
+				parent.map_id,
+				parent.layer_id,
+				tile.texture_index as usize,
+			),
+			None => tile.texture_index as usize,
+		};
+		self.tilesets
+			.get_by_id(&tileset_id)?
+			.get_tile_id(&index)
+			.copied()
+	}
+}
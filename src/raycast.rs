@@ -0,0 +1,292 @@
+//! Raycasting and line-of-sight queries against the tiles of a layer
+//!
+//! Rays are traversed cell by cell using a DDA (digital differential analyzer), so every tile the
+//! ray passes through is visited exactly once, in order. By default, a tile blocks the ray if it
+//! has any collision according to the [`CollisionMap`].
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::MapId;
+
+use crate::collision::CollisionMap;
+use crate::coord::TileCoord;
+use crate::registry::TilesetMapRegistry;
+
+/// The result of a successful raycast
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TileHit {
+	/// The coordinate of the tile that was hit
+	pub coord: TileCoord,
+	/// The world position where the ray entered the tile
+	pub point: Vec2,
+	/// The side of the tile the ray entered through, pointing outwards
+	///
+	/// This is zero if the ray started inside the tile.
+	pub normal: IVec2,
+	/// The distance (in world units) from the start of the ray to the [`point`](Self::point)
+	pub distance: f32,
+}
+
+/// A helper system param used to cast rays against the tiles of a layer
+///
+/// Only layers known to the [`TilesetMapRegistry`] can be raycast against.
+///
+/// # Examples
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_tileset_map::prelude::TilemapRaycast;
+/// # #[derive(Component)]
+/// # struct Enemy;
+/// # #[derive(Component)]
+/// # struct Player;
+/// fn spot_player(
+///   raycast: TilemapRaycast,
+///   enemies: Query<&Transform, With<Enemy>>,
+///   player: Query<&Transform, With<Player>>,
+/// ) {
+///   let player = player.single().translation.truncate();
+///   for enemy in enemies.iter() {
+///     if raycast.line_of_sight(enemy.translation.truncate(), player, 0u16, 0u16) {
+///       // ...
+///     }
+///   }
+/// }
+/// ```
+#[derive(SystemParam)]
+pub struct TilemapRaycast<'w, 's> {
+	collision: Res<'w, CollisionMap>,
+	registry: Res<'w, TilesetMapRegistry>,
+	transforms: Query<'w, 's, &'static GlobalTransform>,
+}
+
+impl<'w, 's> TilemapRaycast<'w, 's> {
+	/// Cast a ray between the given world positions, returning the first solid tile it hits
+	///
+	/// A tile is solid if it has any collision according to the [`CollisionMap`].
+	///
+	/// # Arguments
+	///
+	/// * `from`: The world position the ray starts at
+	/// * `to`: The world position the ray ends at
+	/// * `map_id`: The tile map
+	/// * `layer_id`: The layer within the tile map
+	///
+	pub fn cast<MId: MapId>(
+		&self,
+		from: Vec2,
+		to: Vec2,
+		map_id: MId,
+		layer_id: u16,
+	) -> Option<TileHit> {
+		self.cast_with(from, to, map_id, layer_id, |coord| {
+			self.collision.is_solid(coord)
+		})
+	}
+
+	/// Cast a ray between the given world positions, returning the first tile it hits for which
+	/// the given predicate returns true
+	///
+	/// This can be used to block rays with something other than collision, such as [`TileFlags`]
+	/// stored in a lookup of your own.
+	///
+	/// [`TileFlags`]: crate::prelude::TileFlags
+	pub fn cast_with<MId: MapId, F: FnMut(&TileCoord) -> bool>(
+		&self,
+		from: Vec2,
+		to: Vec2,
+		map_id: MId,
+		layer_id: u16,
+		mut is_blocking: F,
+	) -> Option<TileHit> {
+		let map_id = map_id.into();
+		let layer = self.registry.layer(map_id, layer_id)?;
+		let grid_size = layer.settings.grid_size;
+		let matrix = self
+			.transforms
+			.get(layer.entity)
+			.map(GlobalTransform::compute_matrix)
+			.unwrap_or_default();
+		let inverse = matrix.inverse();
+
+		// Work in grid space, where each tile is exactly one unit wide
+		let start = inverse.transform_point3(from.extend(0.0)).truncate() / grid_size;
+		let end = inverse.transform_point3(to.extend(0.0)).truncate() / grid_size;
+
+		let (coord, t, normal) = traverse(start, end, |cell| {
+			TileCoord::from_ivec2(cell, map_id, layer_id)
+				.map(|coord| is_blocking(&coord))
+				.unwrap_or_default()
+		})?;
+
+		let local = (start + (end - start) * t) * grid_size;
+		let point = matrix.transform_point3(local.extend(0.0)).truncate();
+		Some(TileHit {
+			coord: TileCoord::from_ivec2(coord, map_id, layer_id)?,
+			point,
+			normal,
+			distance: from.distance(point),
+		})
+	}
+
+	/// Returns true if no solid tile lies between the given world positions
+	pub fn line_of_sight<MId: MapId>(
+		&self,
+		from: Vec2,
+		to: Vec2,
+		map_id: MId,
+		layer_id: u16,
+	) -> bool {
+		self.cast(from, to, map_id, layer_id).is_none()
+	}
+}
+
+/// Walk the grid cells between two points (in grid space) until the predicate returns true
+///
+/// Returns the matching cell, the fraction of the segment travelled before entering it, and the
+/// normal of the side it was entered through.
+fn traverse<F: FnMut(IVec2) -> bool>(
+	start: Vec2,
+	end: Vec2,
+	mut predicate: F,
+) -> Option<(IVec2, f32, IVec2)> {
+	let delta = end - start;
+	let mut cell = start.floor().as_ivec2();
+	let last = end.floor().as_ivec2();
+	let step = IVec2::new(delta.x.signum() as i32, delta.y.signum() as i32);
+
+	// The fraction of the segment needed to cross a full cell along each axis
+	let t_delta = Vec2::new(
+		if delta.x != 0.0 {
+			(1.0 / delta.x).abs()
+		} else {
+			f32::INFINITY
+		},
+		if delta.y != 0.0 {
+			(1.0 / delta.y).abs()
+		} else {
+			f32::INFINITY
+		},
+	);
+	// The fraction of the segment needed to reach the next cell boundary along each axis
+	let boundary = |start: f32, cell: i32, step: i32, delta: f32| -> f32 {
+		if delta == 0.0 {
+			f32::INFINITY
+		} else {
+			let next = cell + (step > 0) as i32;
+			(next as f32 - start) / delta
+		}
+	};
+	let mut t_max = Vec2::new(
+		boundary(start.x, cell.x, step.x, delta.x),
+		boundary(start.y, cell.y, step.y, delta.y),
+	);
+
+	let mut t = 0.0;
+	let mut normal = IVec2::ZERO;
+	loop {
+		if predicate(cell) {
+			return Some((cell, t, normal));
+		}
+		if cell == last {
+			return None;
+		}
+
+		// Never step past the last cell along an axis, otherwise a ray ending exactly on a
+		// boundary could leave through it and miss the cell containing its end point
+		let step_x = if cell.x == last.x {
+			false
+		} else if cell.y == last.y {
+			true
+		} else {
+			t_max.x < t_max.y
+		};
+		if step_x {
+			t = t_max.x.min(1.0);
+			t_max.x += t_delta.x;
+			cell.x += step.x;
+			normal = IVec2::new(-step.x, 0);
+		} else {
+			t = t_max.y.min(1.0);
+			t_max.y += t_delta.y;
+			cell.y += step.y;
+			normal = IVec2::new(0, -step.y);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Collect every cell visited between two points (in grid space), in order
+	fn cells(start: Vec2, end: Vec2) -> Vec<IVec2> {
+		let mut visited = Vec::new();
+		traverse(start, end, |cell| {
+			visited.push(cell);
+			false
+		});
+		visited
+	}
+
+	/// Traverse until the given cell, returning when and how it was entered
+	fn hit(start: Vec2, end: Vec2, target: IVec2) -> Option<(IVec2, f32, IVec2)> {
+		traverse(start, end, |cell| cell == target)
+	}
+
+	#[test]
+	fn axis_rays_ending_on_an_edge_enter_the_last_cell() {
+		let (start, end) = (Vec2::new(0.5, 0.5), Vec2::new(2.0, 0.5));
+		assert_eq!(
+			cells(start, end),
+			vec![IVec2::new(0, 0), IVec2::new(1, 0), IVec2::new(2, 0)]
+		);
+		assert_eq!(
+			hit(start, end, IVec2::new(2, 0)),
+			Some((IVec2::new(2, 0), 1.0, IVec2::new(-1, 0)))
+		);
+
+		// Moving backwards, the end point belongs to the cell the ray is leaving
+		let (start, end) = (Vec2::new(0.5, 2.5), Vec2::new(0.5, 1.0));
+		assert_eq!(cells(start, end), vec![IVec2::new(0, 2), IVec2::new(0, 1)]);
+		assert_eq!(hit(start, end, IVec2::new(0, 0)), None);
+
+		let (start, end) = (Vec2::new(2.5, 0.5), Vec2::new(1.0, 0.5));
+		assert_eq!(cells(start, end), vec![IVec2::new(2, 0), IVec2::new(1, 0)]);
+		assert_eq!(hit(start, end, IVec2::new(0, 0)), None);
+	}
+
+	#[test]
+	fn diagonal_rays_ending_on_a_corner_reach_the_last_cell() {
+		for (start, end) in [
+			(Vec2::new(0.5, 0.5), Vec2::new(2.0, 2.0)),
+			(Vec2::new(1.0, 1.0), Vec2::new(3.0, 3.0)),
+			(Vec2::new(2.5, 2.5), Vec2::new(1.0, 1.0)),
+			(Vec2::new(0.5, 2.5), Vec2::new(2.0, 1.0)),
+			(Vec2::new(2.5, 0.5), Vec2::new(1.0, 2.0)),
+		] {
+			let path = cells(start, end);
+			assert_eq!(path.first(), Some(&start.floor().as_ivec2()));
+			assert_eq!(path.last(), Some(&end.floor().as_ivec2()));
+			// Every step crosses exactly one edge, so no corner is skipped
+			assert!(path.windows(2).all(|step| {
+				let diff = (step[1] - step[0]).abs();
+				diff.x + diff.y == 1
+			}));
+		}
+	}
+
+	#[test]
+	fn hits_report_the_entry_point_and_normal() {
+		let (start, end) = (Vec2::new(0.5, 0.5), Vec2::new(0.5, 3.0));
+		assert_eq!(
+			hit(start, end, IVec2::new(0, 0)),
+			Some((IVec2::new(0, 0), 0.0, IVec2::ZERO))
+		);
+		assert_eq!(
+			hit(start, end, IVec2::new(0, 2)),
+			Some((IVec2::new(0, 2), 0.6, IVec2::new(0, -1)))
+		);
+		assert_eq!(hit(start, end, IVec2::new(0, 4)), None);
+	}
+}
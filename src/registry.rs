@@ -0,0 +1,197 @@
+//! Introspection of all maps and layers managed by this crate
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use bevy::prelude::*;
+use bevy_ecs_tilemap::{Chunk, Layer, LayerSettings, MapQuery, TileParent};
+use bevy_tileset::prelude::{TilesetId, TilesetParent};
+
+/// A resource listing every map and layer this crate has placed tiles in
+///
+/// This allows tools, savers, and debug UIs to iterate over "everything" without having to
+/// maintain their own bookkeeping. Layers are registered once a tile from a tileset is placed
+/// in them and unregistered once their layer entity is despawned.
+///
+/// This resource is added and kept up to date automatically by the
+/// [`TilesetMapPlugin`](crate::prelude::TilesetMapPlugin).
+#[derive(Debug, Default)]
+pub struct TilesetMapRegistry {
+	maps: BTreeMap<u16, MapInfo>,
+}
+
+/// Information about a single registered map
+#[derive(Debug, Default)]
+pub struct MapInfo {
+	layers: BTreeMap<u16, LayerInfo>,
+}
+
+/// Information about a single registered layer
+#[derive(Debug, Clone)]
+pub struct LayerInfo {
+	/// The layer entity
+	pub entity: Entity,
+	/// The settings the layer was created with
+	pub settings: LayerSettings,
+	/// The IDs of all tilesets that have been placed in this layer
+	pub tilesets: BTreeSet<TilesetId>,
+	/// The texture used to render this layer (if known)
+	pub texture: Option<Handle<Image>>,
+}
+
+impl TilesetMapRegistry {
+	/// Get the info of the given map
+	pub fn map(&self, map_id: u16) -> Option<&MapInfo> {
+		self.maps.get(&map_id)
+	}
+
+	/// Get the info of the given layer
+	pub fn layer(&self, map_id: u16, layer_id: u16) -> Option<&LayerInfo> {
+		self.maps.get(&map_id)?.layer(layer_id)
+	}
+
+	/// Returns true if the given map has been registered
+	pub fn contains_map(&self, map_id: u16) -> bool {
+		self.maps.contains_key(&map_id)
+	}
+
+	/// Returns true if the given layer has been registered
+	pub fn contains_layer(&self, map_id: u16, layer_id: u16) -> bool {
+		self.layer(map_id, layer_id).is_some()
+	}
+
+	/// Iterate over all registered maps, ordered by map ID
+	pub fn maps(&self) -> impl Iterator<Item = (u16, &MapInfo)> {
+		self.maps.iter().map(|(map_id, map)| (*map_id, map))
+	}
+
+	/// Iterate over all registered layers of all maps, ordered by map ID then layer ID
+	pub fn layers(&self) -> impl Iterator<Item = (u16, u16, &LayerInfo)> {
+		self.maps.iter().flat_map(|(map_id, map)| {
+			map.layers()
+				.map(move |(layer_id, layer)| (*map_id, layer_id, layer))
+		})
+	}
+
+	/// The number of registered maps
+	pub fn len(&self) -> usize {
+		self.maps.len()
+	}
+
+	/// Returns true if no maps have been registered
+	pub fn is_empty(&self) -> bool {
+		self.maps.is_empty()
+	}
+
+	fn register(
+		&mut self,
+		map_id: u16,
+		layer_id: u16,
+		entity: Entity,
+		settings: LayerSettings,
+		tileset_id: TilesetId,
+		texture: Option<Handle<Image>>,
+	) {
+		let layer = self
+			.maps
+			.entry(map_id)
+			.or_default()
+			.layers
+			.entry(layer_id)
+			.or_insert_with(|| LayerInfo {
+				entity,
+				settings,
+				tilesets: BTreeSet::new(),
+				texture: None,
+			});
+
+		if layer.entity != entity {
+			// The layer was recreated -> start over
+			layer.entity = entity;
+			layer.tilesets.clear();
+			layer.texture = None;
+		}
+		layer.settings = settings;
+		layer.tilesets.insert(tileset_id);
+		if texture.is_some() {
+			layer.texture = texture;
+		}
+	}
+
+	/// Removes all layers for which the given predicate returns false, along with any maps left empty
+	fn retain_layers<F: FnMut(&LayerInfo) -> bool>(&mut self, mut predicate: F) {
+		for map in self.maps.values_mut() {
+			map.layers.retain(|_, layer| predicate(layer));
+		}
+		self.maps.retain(|_, map| !map.layers.is_empty());
+	}
+}
+
+impl MapInfo {
+	/// Get the info of the given layer
+	pub fn layer(&self, layer_id: u16) -> Option<&LayerInfo> {
+		self.layers.get(&layer_id)
+	}
+
+	/// Iterate over all registered layers of this map, ordered by layer ID
+	pub fn layers(&self) -> impl Iterator<Item = (u16, &LayerInfo)> {
+		self.layers
+			.iter()
+			.map(|(layer_id, layer)| (*layer_id, layer))
+	}
+
+	/// The IDs of all tilesets that have been placed in any layer of this map
+	pub fn tilesets(&self) -> BTreeSet<TilesetId> {
+		self.layers
+			.values()
+			.flat_map(|layer| layer.tilesets.iter().copied())
+			.collect()
+	}
+
+	/// The number of registered layers in this map
+	pub fn len(&self) -> usize {
+		self.layers.len()
+	}
+
+	/// Returns true if no layers have been registered in this map
+	pub fn is_empty(&self) -> bool {
+		self.layers.is_empty()
+	}
+}
+
+/// __\[SYSTEM\]__ Keeps the [`TilesetMapRegistry`] in sync with the placed tiles and existing layers
+pub(crate) fn update_map_registry(
+	mut registry: ResMut<TilesetMapRegistry>,
+	mut map_query: MapQuery,
+	changed: Query<(&TileParent, &TilesetParent), Changed<TilesetParent>>,
+	layers: Query<(), With<Layer>>,
+	textures: Query<&Handle<Image>, With<Chunk>>,
+) {
+	// === Remove Despawned Layers === //
+	if !registry.is_empty() {
+		registry.retain_layers(|layer| layers.get(layer.entity).is_ok());
+	}
+
+	// === Register Touched Layers === //
+	for (parent, tileset) in changed.iter() {
+		// The texture may change without the tileset being new (such as when swapping tilesets)
+		let texture = textures.get(parent.chunk).ok();
+		let is_known = registry
+			.layer(parent.map_id, parent.layer_id)
+			.map(|layer| layer.tilesets.contains(&tileset.0) && layer.texture.as_ref() == texture)
+			.unwrap_or_default();
+		if is_known {
+			continue;
+		}
+
+		if let Some((entity, layer)) = map_query.get_layer(parent.map_id, parent.layer_id) {
+			registry.register(
+				parent.map_id,
+				parent.layer_id,
+				entity,
+				layer.settings,
+				tileset.0,
+				texture.cloned(),
+			);
+		}
+	}
+}
@@ -0,0 +1,149 @@
+//! Patching placed tiles when their tileset is hot-reloaded
+//!
+//! A placed tile only stores the texture index it was placed with. When a tileset is modified
+//! (e.g. by hot reloading its config), those indices may no longer point at the same tiles. To fix
+//! this, the tiles of every tileset are remembered by name whenever it's loaded, and any placed
+//! tiles are re-resolved by name (and variant) once it is modified.
+//!
+//! Tiles whose name no longer exists in the modified tileset are left as is. Layers that combine
+//! several tilesets (see [`CombinedTileset`](crate::prelude::CombinedTileset)) are not patched,
+//! since their combined texture has to be recreated.
+
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+use bevy_ecs_tilemap::{Tile, TileParent};
+use bevy_tileset::prelude::{TileId, Tileset, TilesetId, TilesetParent};
+
+use crate::combined::CombinedTilesets;
+use crate::placement::TilePlacer;
+
+/// An event sent after the placed tiles of a modified tileset have been patched
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TilesetReloadedEvent {
+	/// The ID of the reloaded tileset
+	pub tileset_id: TilesetId,
+	/// The number of placed tiles that were updated
+	pub updated: usize,
+}
+
+/// The tiles of a tileset, as they were when it was last loaded
+#[derive(Debug, Default)]
+struct TilesetSnapshot {
+	/// Maps the texture indices of the tileset to the ID and name of their tile
+	tiles: HashMap<usize, (TileId, String)>,
+}
+
+/// A resource remembering the tiles of every loaded tileset
+///
+/// This resource is added automatically by the [`TilesetMapPlugin`](crate::prelude::TilesetMapPlugin).
+#[derive(Debug, Default)]
+pub(crate) struct TilesetSnapshots {
+	snapshots: HashMap<Handle<Tileset>, TilesetSnapshot>,
+}
+
+impl TilesetSnapshot {
+	fn new(tileset: &Tileset) -> Self {
+		// Every texture index of a tileset belongs to a tile
+		let tiles = (0..)
+			.map_while(|index| Some((index, *tileset.get_tile_id(&index)?)))
+			.filter_map(|(index, tile_id)| {
+				let name = tileset.get_tile_name(&tile_id.group_id)?;
+				Some((index, (tile_id, name.to_string())))
+			})
+			.collect();
+		Self { tiles }
+	}
+}
+
+/// __\[SYSTEM\]__ Re-resolves the texture indices of placed tiles whose tileset was modified
+pub(crate) fn patch_reloaded_tilesets(
+	mut commands: Commands,
+	mut snapshots: ResMut<TilesetSnapshots>,
+	mut events: EventReader<AssetEvent<Tileset>>,
+	mut reloaded: EventWriter<TilesetReloadedEvent>,
+	assets: Res<Assets<Tileset>>,
+	combined: Res<CombinedTilesets>,
+	mut placer: TilePlacer,
+	tiles: Query<(Entity, &Tile, &TileParent, &TilesetParent)>,
+) {
+	for event in events.iter() {
+		let handle = match event {
+			AssetEvent::Created { handle } => {
+				if let Some(tileset) = assets.get(handle) {
+					snapshots
+						.snapshots
+						.insert(handle.clone_weak(), TilesetSnapshot::new(tileset));
+				}
+				continue;
+			},
+			AssetEvent::Removed { handle } => {
+				snapshots.snapshots.remove(handle);
+				continue;
+			},
+			AssetEvent::Modified { handle } => handle,
+		};
+
+		let tileset = if let Some(tileset) = assets.get(handle) {
+			tileset
+		} else {
+			continue;
+		};
+		let previous = snapshots
+			.snapshots
+			.insert(handle.clone_weak(), TilesetSnapshot::new(tileset));
+		let previous = if let Some(previous) = previous {
+			previous
+		} else {
+			continue;
+		};
+
+		let tileset_id = *tileset.id();
+		let mut updates = Vec::new();
+		let mut chunks = HashSet::default();
+		for (entity, tile, parent, tileset_parent) in tiles.iter() {
+			if tileset_parent.0 != tileset_id
+				|| combined.get(parent.map_id, parent.layer_id).is_some()
+			{
+				continue;
+			}
+
+			let target =
+				previous
+					.tiles
+					.get(&(tile.texture_index as usize))
+					.and_then(|(original, name)| {
+						let group_id = tileset.get_tile_group_id(name)?;
+						Some(TileId {
+							group_id: *group_id,
+							..*original
+						})
+					});
+			if let Some(target) = target {
+				updates.push((entity, target));
+				chunks.insert(parent.chunk);
+			}
+		}
+
+		let mut updated = 0;
+		for (entity, target) in updates {
+			// Fall back to the plain tile if the exact variant no longer exists
+			let success = placer.update(target, entity).is_ok()
+				|| placer
+					.update(TileId::new(target.group_id, target.tileset_id), entity)
+					.is_ok();
+			if success {
+				updated += 1;
+			}
+		}
+
+		let texture = tileset.texture().clone();
+		for chunk in chunks {
+			commands.entity(chunk).insert(texture.clone());
+		}
+
+		reloaded.send(TilesetReloadedEvent {
+			tileset_id,
+			updated,
+		});
+	}
+}
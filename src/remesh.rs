@@ -0,0 +1,225 @@
+//! Control over when chunks are notified of tile changes
+//!
+//! By default, every tile edit made through the [`TilePlacer`](crate::prelude::TilePlacer)
+//! immediately notifies its chunk, skipping chunks that have already been notified since the
+//! tilemap was last updated. When many systems edit the same chunks within a frame, it can be
+//! useful to collect these notifications and flush them all at once instead, deferring the
+//! notifications until the end of the stage (or until explicitly requested).
+
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+use bevy_ecs_tilemap::prelude::*;
+use std::sync::{Mutex, MutexGuard};
+
+/// When chunk notifications should be flushed
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum ChunkFlushMode {
+	/// Chunks are notified as soon as one of their tiles is edited
+	///
+	/// Each chunk is still only notified once until the tilemap is updated.
+	#[default]
+	Immediate,
+	/// Chunks are notified once, right before the tilemap is updated for the current frame
+	EndOfStage,
+	/// Chunks are only notified when explicitly flushed
+	///
+	/// See [`ChunkNotifications::flush_chunks`] and
+	/// [`TilePlacer::flush_chunks`](crate::prelude::TilePlacer::flush_chunks).
+	Manual,
+}
+
+/// A resource collecting chunk notifications according to its [`ChunkFlushMode`]
+///
+/// This resource is added automatically by the [`TilesetMapPlugin`](crate::prelude::TilesetMapPlugin).
+///
+/// # Examples
+///
+/// ```
+/// # use bevy::prelude::ResMut;
+/// # use bevy_tileset_map::prelude::{ChunkFlushMode, ChunkNotifications};
+/// fn setup(mut notifications: ResMut<ChunkNotifications>) {
+///   notifications.set_mode(ChunkFlushMode::EndOfStage);
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct ChunkNotifications {
+	mode: ChunkFlushMode,
+	/// Kept behind a lock so that placers only need shared access to this resource
+	state: Mutex<NotificationState>,
+}
+
+/// Identifies a chunk by `(map_id, layer_id, chunk_pos)`
+type ChunkKey = (u16, u16, (u32, u32));
+
+/// The mutable state of the [`ChunkNotifications`]
+#[derive(Debug, Default)]
+struct NotificationState {
+	/// A single tile for every pending chunk
+	pending: HashMap<ChunkKey, TilePos>,
+	/// The chunks notified in [`ChunkFlushMode::Immediate`] since the tilemap was last updated
+	notified: HashSet<ChunkKey>,
+	flush_requested: bool,
+}
+
+impl ChunkNotifications {
+	/// The current flush mode
+	pub fn mode(&self) -> ChunkFlushMode {
+		self.mode
+	}
+
+	/// Set the flush mode
+	///
+	/// Any notifications that are still pending will be flushed as usual by the new mode.
+	pub fn set_mode(&mut self, mode: ChunkFlushMode) {
+		self.mode = mode;
+	}
+
+	/// Request that all pending notifications be flushed right before the tilemap is updated
+	///
+	/// This is mainly useful in [`ChunkFlushMode::Manual`] when the [`TilePlacer`] isn't
+	/// available.
+	///
+	/// [`TilePlacer`]: crate::prelude::TilePlacer
+	pub fn flush_chunks(&mut self) {
+		self.lock().flush_requested = true;
+	}
+
+	/// The number of chunks waiting to be notified
+	pub fn pending(&self) -> usize {
+		self.lock().pending.len()
+	}
+
+	/// Notify the chunk containing the given tile, respecting the current flush mode
+	pub(crate) fn notify(
+		&self,
+		map_query: &mut MapQuery,
+		pos: TilePos,
+		map_id: u16,
+		layer_id: u16,
+	) {
+		let key = if let Some(key) = chunk_key(map_query, pos, map_id, layer_id) {
+			key
+		} else {
+			return;
+		};
+
+		let mut state = self.lock();
+		if self.mode == ChunkFlushMode::Immediate {
+			// The chunk is remeshed using all of its tiles, so one notification is enough
+			if state.notified.insert(key) {
+				map_query.notify_chunk_for_tile(pos, map_id, layer_id);
+			}
+			return;
+		}
+
+		state.pending.insert(key, pos);
+	}
+
+	/// Notify every pending chunk
+	pub(crate) fn flush(&self, map_query: &mut MapQuery) {
+		let pending = {
+			let mut state = self.lock();
+			state.flush_requested = false;
+			std::mem::take(&mut state.pending)
+		};
+		for ((map_id, layer_id, _), pos) in pending {
+			map_query.notify_chunk_for_tile(pos, map_id, layer_id);
+		}
+	}
+
+	fn lock(&self) -> MutexGuard<'_, NotificationState> {
+		// A panic while holding the lock can't leave the state half-updated
+		self.state
+			.lock()
+			.unwrap_or_else(|poisoned| poisoned.into_inner())
+	}
+}
+
+/// The chunk notifications collected by a single [`TilePlacer`] during a batch of edits
+///
+/// Batches are local to each placer, so they never change the [`ChunkFlushMode`] seen by other
+/// systems. This is nominally public since it appears in the state of the [`TilePlacer`] param,
+/// but it is not exported.
+///
+/// [`TilePlacer`]: crate::prelude::TilePlacer
+#[derive(Debug, Default)]
+pub struct ChunkBatch {
+	/// The number of batches currently open (batches may be nested)
+	depth: usize,
+	/// A single tile for every chunk edited during the batch
+	pending: HashMap<ChunkKey, TilePos>,
+}
+
+impl ChunkBatch {
+	/// Start collecting notifications
+	pub(crate) fn begin(&mut self) {
+		self.depth += 1;
+	}
+
+	/// Collect the notification for the given tile if a batch is open
+	///
+	/// Returns false if the notification has to be sent right away.
+	pub(crate) fn collect(
+		&mut self,
+		map_query: &mut MapQuery,
+		pos: TilePos,
+		map_id: u16,
+		layer_id: u16,
+	) -> bool {
+		if self.depth == 0 {
+			return false;
+		}
+
+		if let Some(key) = chunk_key(map_query, pos, map_id, layer_id) {
+			self.pending.insert(key, pos);
+		}
+		true
+	}
+
+	/// Stop collecting notifications, sending them once the outermost batch ends
+	pub(crate) fn end(&mut self, notifications: &ChunkNotifications, map_query: &mut MapQuery) {
+		self.depth = self.depth.saturating_sub(1);
+		if self.depth > 0 {
+			return;
+		}
+
+		for ((map_id, layer_id, _), pos) in self.pending.drain() {
+			notifications.notify(map_query, pos, map_id, layer_id);
+		}
+	}
+}
+
+/// Get the key of the chunk containing the given tile
+fn chunk_key(
+	map_query: &mut MapQuery,
+	pos: TilePos,
+	map_id: u16,
+	layer_id: u16,
+) -> Option<ChunkKey> {
+	let (_, layer) = map_query.get_layer(map_id, layer_id)?;
+	let chunk_size = layer.settings.chunk_size;
+	Some((
+		map_id,
+		layer_id,
+		(pos.0 / chunk_size.0, pos.1 / chunk_size.1),
+	))
+}
+
+/// __\[SYSTEM\]__ Flushes pending chunk notifications according to the current flush mode
+pub(crate) fn flush_chunk_notifications(
+	notifications: Res<ChunkNotifications>,
+	mut map_query: MapQuery,
+) {
+	let should_flush = match notifications.mode {
+		// Also catches anything left over from switching modes
+		ChunkFlushMode::Immediate | ChunkFlushMode::EndOfStage => true,
+		ChunkFlushMode::Manual => notifications.lock().flush_requested,
+	};
+
+	if should_flush {
+		notifications.flush(&mut map_query);
+	}
+
+	// Edits made after the tilemap has been updated need to notify their chunks again
+	notifications.lock().notified.clear();
+}
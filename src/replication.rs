@@ -0,0 +1,266 @@
+//! Replicating tile edits between peers
+//!
+//! On the sending side, [`TileReplicator::collect`] turns the [`TilemapChanges`] of the previous
+//! frame into a list of serializable [`TileDelta`]s. These can be sent over the network using any
+//! transport and applied on the receiving side with [`TileReplicator::apply`], which places the
+//! tiles through the [`TilePlacer`] so that auto tiles, hooks, and metadata all behave as usual.
+//!
+//! Every delta carries a tick. Deltas older than the last delta applied to (or collected for) the
+//! same coordinate are rejected, so the most recent edit always wins regardless of the order in
+//! which deltas arrive.
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+use bevy_tileset::prelude::TileId;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::changes::{TileChange, TilemapChanges};
+use crate::coord::TileCoord;
+use crate::elevation::TileElevation;
+use crate::flags::TileFlags;
+use crate::placement::{PlacedTile, TilePlacementError, TilePlacer};
+
+/// A single replicated tile edit
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct TileDelta {
+	/// The tick this edit was made on
+	///
+	/// This is typically the sender's (or server's) simulation tick and is used to discard
+	/// outdated edits.
+	pub tick: u64,
+	/// The coordinate of the edited tile
+	pub coord: TileCoord,
+	/// The edit itself
+	pub kind: TileDeltaKind,
+}
+
+/// The kind of edit contained in a [`TileDelta`]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub enum TileDeltaKind {
+	/// Place the given tile, replacing any existing tile
+	Place {
+		id: TileId,
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		flags: Option<TileFlags>,
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		elevation: Option<f32>,
+	},
+	/// Remove the tile
+	Remove,
+	/// Update the data of an existing tile without replacing it
+	Update {
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		flags: Option<TileFlags>,
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		elevation: Option<f32>,
+	},
+}
+
+/// Errors related to applying a [`TileDelta`]
+#[derive(Error, Debug)]
+pub enum TileDeltaError {
+	/// A newer delta has already been applied to (or collected for) the same coordinate
+	#[error("Delta from tick {tick} is older than tick {latest} at {coord:?}")]
+	Stale {
+		coord: TileCoord,
+		tick: u64,
+		latest: u64,
+	},
+	/// The delta could not be applied
+	#[error(transparent)]
+	Placement(#[from] TilePlacementError),
+}
+
+/// A resource tracking the sequencing state used by the [`TileReplicator`]
+///
+/// This resource is added automatically by the [`TilesetMapPlugin`](crate::prelude::TilesetMapPlugin)
+/// when the `replication` feature is enabled.
+#[derive(Debug, Default)]
+pub struct ReplicationState {
+	/// The tick of the latest delta for every coordinate
+	latest: HashMap<TileCoord, u64>,
+	/// Coordinates edited by applied deltas, which should not be sent back out
+	applied: HashSet<TileCoord>,
+}
+
+impl ReplicationState {
+	/// The tick of the latest delta applied to (or collected for) the given coordinate
+	pub fn latest_tick(&self, coord: &TileCoord) -> Option<u64> {
+		self.latest.get(coord).copied()
+	}
+
+	/// Forget all sequencing state, such as when reconnecting to a new session
+	pub fn clear(&mut self) {
+		self.latest.clear();
+		self.applied.clear();
+	}
+}
+
+/// A helper system param used to collect and apply [`TileDelta`]s
+///
+/// # Examples
+///
+/// ```
+/// # use bevy::prelude::Res;
+/// # use bevy_tileset_map::prelude::{TileDelta, TileReplicator};
+/// # fn send(deltas: Vec<TileDelta>) {}
+/// # fn receive() -> Vec<TileDelta> { Vec::new() }
+/// # struct Tick(u64);
+/// fn replicate(mut replicator: TileReplicator, tick: Res<Tick>) {
+///   send(replicator.collect(tick.0));
+///
+///   for (delta, err) in replicator.apply_all(receive()) {
+///     bevy::log::warn!("Rejected {:?}: {}", delta, err);
+///   }
+/// }
+/// ```
+#[derive(SystemParam)]
+pub struct TileReplicator<'w, 's> {
+	placer: TilePlacer<'w, 's>,
+	changes: Res<'w, TilemapChanges>,
+	state: ResMut<'w, ReplicationState>,
+	tiles: Query<'w, 's, (Option<&'static TileFlags>, Option<&'static TileElevation>)>,
+}
+
+impl<'w, 's> TileReplicator<'w, 's> {
+	/// Create deltas for all tiles that changed during the previous frame
+	///
+	/// Changes caused by applying deltas are skipped so they aren't echoed back to their sender.
+	///
+	/// # Arguments
+	///
+	/// * `tick`: The tick to stamp each delta with
+	///
+	pub fn collect(&mut self, tick: u64) -> Vec<TileDelta> {
+		let mut deltas = Vec::with_capacity(self.changes.len());
+		let coords = self
+			.changes
+			.iter()
+			.map(|(coord, change)| (*coord, change))
+			.collect::<Vec<_>>();
+
+		for (coord, change) in coords {
+			if self.state.applied.contains(&coord) {
+				continue;
+			}
+
+			let kind = match change {
+				TileChange::Removed => TileDeltaKind::Remove,
+				TileChange::Added | TileChange::Modified => {
+					if let Some(kind) = self.get_place_kind(&coord) {
+						kind
+					} else {
+						continue;
+					}
+				},
+			};
+
+			self.state.latest.insert(coord, tick);
+			deltas.push(TileDelta { tick, coord, kind });
+		}
+
+		self.state.applied.clear();
+		deltas
+	}
+
+	/// Apply a single delta
+	///
+	/// Returns [`TileDeltaError::Stale`] if a newer delta has already been applied to (or
+	/// collected for) the same coordinate.
+	pub fn apply(&mut self, delta: &TileDelta) -> Result<(), TileDeltaError> {
+		let TileDelta { tick, coord, kind } = delta;
+		if let Some(latest) = self.state.latest_tick(coord) {
+			if *tick < latest {
+				return Err(TileDeltaError::Stale {
+					coord: *coord,
+					tick: *tick,
+					latest,
+				});
+			}
+		}
+
+		match kind {
+			TileDeltaKind::Place {
+				id,
+				flags,
+				elevation,
+			} => {
+				let placed = self
+					.placer
+					.place(*id, coord.pos, coord.map_id, coord.layer_id)?;
+				if let PlacedTile::Added { new_tile, .. } = placed {
+					if let Some(flags) = flags {
+						self.placer.set_tile_flags(new_tile.0, *flags);
+					}
+					if let Some(elevation) = elevation {
+						self.placer.set_tile_elevation(new_tile.0, *elevation);
+					}
+				}
+			},
+			TileDeltaKind::Remove => {
+				self.placer
+					.remove(coord.pos, coord.map_id, coord.layer_id)?;
+			},
+			TileDeltaKind::Update { flags, elevation } => {
+				if let Some(flags) = flags {
+					self.placer
+						.set_flags(coord.pos, coord.map_id, coord.layer_id, *flags)?;
+				}
+				if let Some(elevation) = elevation {
+					self.placer.set_elevation(
+						coord.pos,
+						coord.map_id,
+						coord.layer_id,
+						*elevation,
+					)?;
+				}
+			},
+		}
+
+		self.state.latest.insert(*coord, *tick);
+		self.state.applied.insert(*coord);
+		Ok(())
+	}
+
+	/// Apply the given deltas in order of their ticks
+	///
+	/// Deltas sharing a tick are applied in the order they were given. Returns every delta that
+	/// could not be applied, along with the reason why.
+	pub fn apply_all<I: IntoIterator<Item = TileDelta>>(
+		&mut self,
+		deltas: I,
+	) -> Vec<(TileDelta, TileDeltaError)> {
+		let mut deltas = deltas.into_iter().collect::<Vec<_>>();
+		deltas.sort_by_key(|delta| delta.tick);
+
+		self.placer.begin_batch();
+		let mut failed = Vec::new();
+		for delta in deltas {
+			if let Err(err) = self.apply(&delta) {
+				failed.push((delta, err));
+			}
+		}
+		self.placer.end_batch();
+
+		failed
+	}
+
+	/// Get the [`TileDeltaKind::Place`] that recreates the tile at the given coordinate
+	fn get_place_kind(&mut self, coord: &TileCoord) -> Option<TileDeltaKind> {
+		let entity = self
+			.placer
+			.get_tile_entity(coord.pos, coord.map_id, coord.layer_id)?;
+		let (flags, elevation) = self.tiles.get(entity).ok()?;
+		let id = self
+			.placer
+			.get_tile_id_at(coord.pos, coord.map_id, coord.layer_id)?;
+
+		Some(TileDeltaKind::Place {
+			id,
+			flags: flags.copied(),
+			elevation: elevation.map(|elevation| elevation.0),
+		})
+	}
+}
@@ -0,0 +1,181 @@
+//! Creating tilesets at runtime
+//!
+//! Tilesets are normally loaded up front from their config files. The [`TilesetFactory`] instead
+//! assembles them from images that are only known at runtime (such as modded content or
+//! procedurally generated textures).
+//!
+//! Building a tileset's texture atlas requires rendering, so the `TilesetFactory` is not available
+//! with the `headless` feature.
+
+use std::marker::PhantomData;
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy_tileset::prelude::{TileHandle, Tileset, TilesetBuilder, TilesetError, TilesetId};
+use thiserror::Error;
+
+/// Errors related to creating tilesets at runtime
+#[derive(Error, Debug)]
+pub enum RuntimeTilesetError {
+	/// No tiles were given
+	#[error("No tiles were given to create the tileset from")]
+	Empty,
+	/// A tileset with the given ID already exists
+	///
+	/// Contains the ID in question
+	#[error("A tileset with ID {0:?} already exists")]
+	IdTaken(TilesetId),
+	/// The tileset could not be built
+	#[error("Could not build tileset: {0:?}")]
+	Build(TilesetError),
+	/// The texture atlas of the tileset could not be built
+	///
+	/// Contains the reason given by the atlas builder
+	#[error("Could not build the tileset's texture atlas: {0}")]
+	Atlas(String),
+}
+
+/// A helper system param used to create tilesets at runtime
+///
+/// Created tilesets are added to `Assets<Tileset>` like any loaded tileset. Once `bevy_tileset`
+/// has registered them (at the start of the next frame), their tiles can be placed using the
+/// [`TilePlacer`](crate::prelude::TilePlacer) just like any other tile.
+///
+/// Each tile is given its own group, in the order the tiles were given. So the first tile has the
+/// [`TileId`](crate::prelude::TileId) `TileId::new(0, tileset_id)`, the second
+/// `TileId::new(1, tileset_id)`, and so on.
+///
+/// # Examples
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_tileset_map::prelude::TilesetFactory;
+/// struct ModTextures(Vec<(String, Handle<Image>)>);
+///
+/// fn load_mod(mut factory: TilesetFactory, textures: Res<ModTextures>) {
+///   factory
+///     .create_from_images("My Mod", 100, textures.0.iter().cloned())
+///     .unwrap();
+/// }
+/// ```
+#[derive(SystemParam)]
+pub struct TilesetFactory<'w, 's> {
+	tilesets: ResMut<'w, Assets<Tileset>>,
+	images: ResMut<'w, Assets<Image>>,
+	atlases: ResMut<'w, Assets<TextureAtlas>>,
+	#[system_param(ignore)]
+	marker: PhantomData<&'s ()>,
+}
+
+impl<'w, 's> TilesetFactory<'w, 's> {
+	/// Create a tileset from the given tiles
+	///
+	/// The textures of all tiles must already be loaded.
+	///
+	/// # Arguments
+	///
+	/// * `name`: The name of the tileset
+	/// * `id`: The ID to assign to the tileset
+	/// * `tiles`: The tiles of the tileset, each of which is given its own group
+	///
+	pub fn create<I: IntoIterator<Item = TileHandle>>(
+		&mut self,
+		name: &str,
+		id: TilesetId,
+		tiles: I,
+	) -> Result<Handle<Tileset>, RuntimeTilesetError> {
+		if self.contains(&id) {
+			return Err(RuntimeTilesetError::IdTaken(id));
+		}
+
+		let mut builder = TilesetBuilder::default();
+		let mut count = 0;
+		for (group_id, tile) in tiles.into_iter().enumerate() {
+			builder
+				.add_tile(tile, group_id as u32, &*self.images)
+				.map_err(RuntimeTilesetError::Build)?;
+			count += 1;
+		}
+		if count == 0 {
+			return Err(RuntimeTilesetError::Empty);
+		}
+
+		let tileset = builder
+			.build(name, id, &mut *self.images)
+			.map_err(|err| RuntimeTilesetError::Atlas(err.to_string()))?
+			.into_asset(&mut self.atlases);
+		Ok(self.tilesets.add(tileset))
+	}
+
+	/// Create a tileset made up of standard tiles using the given images
+	///
+	/// # Arguments
+	///
+	/// * `name`: The name of the tileset
+	/// * `id`: The ID to assign to the tileset
+	/// * `images`: The name and (loaded) image of each tile
+	///
+	pub fn create_from_images<S, I>(
+		&mut self,
+		name: &str,
+		id: TilesetId,
+		images: I,
+	) -> Result<Handle<Tileset>, RuntimeTilesetError>
+	where
+		S: AsRef<str>,
+		I: IntoIterator<Item = (S, Handle<Image>)>,
+	{
+		let tiles = images
+			.into_iter()
+			.map(|(name, image)| TileHandle::new_standard(name.as_ref(), image))
+			.collect::<Vec<_>>();
+		self.create(name, id, tiles)
+	}
+
+	/// Create a tileset made up of standard tiles using the given generated textures
+	///
+	/// The textures are added to `Assets<Image>`.
+	///
+	/// # Arguments
+	///
+	/// * `name`: The name of the tileset
+	/// * `id`: The ID to assign to the tileset
+	/// * `textures`: The name and texture of each tile
+	///
+	pub fn create_from_textures<S, I>(
+		&mut self,
+		name: &str,
+		id: TilesetId,
+		textures: I,
+	) -> Result<Handle<Tileset>, RuntimeTilesetError>
+	where
+		S: AsRef<str>,
+		I: IntoIterator<Item = (S, Image)>,
+	{
+		let images = textures
+			.into_iter()
+			.map(|(name, texture)| (name, self.images.add(texture)))
+			.collect::<Vec<_>>();
+		self.create_from_images(name, id, images)
+	}
+
+	/// Returns true if a tileset with the given ID exists
+	///
+	/// Unlike [`Tilesets::get_by_id`](bevy_tileset::prelude::Tilesets::get_by_id), this includes
+	/// tilesets that have not been registered yet.
+	pub fn contains(&self, id: &TilesetId) -> bool {
+		self.tilesets.iter().any(|(_, tileset)| tileset.id() == id)
+	}
+
+	/// Remove the tileset with the given ID, returning it (if it existed)
+	///
+	/// Tiles of this tileset that are still placed are left as is.
+	pub fn remove(&mut self, id: &TilesetId) -> Option<Tileset> {
+		let handle = self
+			.tilesets
+			.iter()
+			.find(|(_, tileset)| tileset.id() == id)
+			.map(|(handle, _)| handle)?;
+		self.tilesets.remove(handle)
+	}
+}
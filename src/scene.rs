@@ -0,0 +1,163 @@
+//! Saving the logical tiles of a layer as part of a Bevy scene
+//!
+//! Tiles reference their tileset through `bevy_tileset` types that can't be reflected, so
+//! a tile entity on its own can't be saved in a `DynamicScene`. A [`SceneLayer`] stores the tiles
+//! of a layer using only plain, reflectable data. It can be inserted on any entity (such as the
+//! layer entity) before building a scene, and placed again once the scene has been loaded.
+
+use bevy::prelude::*;
+use bevy::reflect::FromReflect;
+use bevy_ecs_tilemap::TilePos;
+use bevy_tileset::prelude::{TileGroupId, TileId, TilesetId, Tilesets};
+
+use crate::combined::CombinedTilesets;
+use crate::coord::TileCoord;
+use crate::ir::IrSpawnReport;
+use crate::placement::TilePlacer;
+use crate::snapshot::TileSnapshotQuery;
+
+/// A single tile within a [`SceneLayer`]
+#[derive(Reflect, FromReflect, Debug, Copy, Clone, Default, PartialEq)]
+pub struct SceneTile {
+	/// The x-coordinate of the tile
+	pub x: u32,
+	/// The y-coordinate of the tile
+	pub y: u32,
+	/// The ID of the tileset the tile belongs to
+	pub tileset_id: TilesetId,
+	/// The ID of the tile group within its tileset
+	pub group_id: TileGroupId,
+	/// The texture index of the tile within its tileset
+	///
+	/// This preserves the exact variant or auto tile piece the tile was displaying.
+	pub index: u32,
+}
+
+/// A scene-compatible snapshot of the tiles of a single layer
+///
+/// # Examples
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_tileset_map::prelude::*;
+/// // Store the layer on an entity so it is included in the scene
+/// fn capture(
+///   mut commands: Commands,
+///   tiles: TileSnapshotQuery,
+///   tilesets: Tilesets,
+///   combined: Res<CombinedTilesets>,
+/// ) {
+///   let layer = SceneLayer::capture(0u16, 0u16, &tiles, &tilesets, &combined);
+///   commands.spawn().insert(layer);
+/// }
+///
+/// // Place the tiles again once the scene has been spawned
+/// fn restore(mut placer: TilePlacer, layers: Query<&SceneLayer, Added<SceneLayer>>) {
+///   for layer in layers.iter() {
+///     layer.place(&mut placer);
+///   }
+/// }
+/// ```
+#[derive(Component, Reflect, Debug, Clone, Default, PartialEq)]
+#[reflect(Component)]
+pub struct SceneLayer {
+	/// The ID of the map the layer belongs to
+	pub map_id: u16,
+	/// The ID of the layer
+	pub layer_id: u16,
+	/// The tiles of the layer, in no particular order
+	pub tiles: Vec<SceneTile>,
+}
+
+impl SceneTile {
+	/// The position of the tile
+	pub fn pos(&self) -> TilePos {
+		TilePos(self.x, self.y)
+	}
+
+	/// The ID of the tile (without any variant or auto tile information)
+	pub fn tile_id(&self) -> TileId {
+		TileId::new(self.group_id, self.tileset_id)
+	}
+}
+
+impl SceneLayer {
+	/// Capture the tiles of the given layer
+	///
+	/// Only tiles that belong to a registered tileset are captured.
+	///
+	/// # Arguments
+	///
+	/// * `map_id`: The map containing the layer
+	/// * `layer_id`: The layer to capture
+	/// * `tiles`: A query over all tiles
+	/// * `tilesets`: The registered tilesets
+	/// * `combined`: The combined tilesets, used to read the tiles of combined layers
+	///
+	pub fn capture(
+		map_id: u16,
+		layer_id: u16,
+		tiles: &TileSnapshotQuery,
+		tilesets: &Tilesets,
+		combined: &CombinedTilesets,
+	) -> Self {
+		let tiles = tiles
+			.iter()
+			.filter(|(_, parent, ..)| parent.map_id == map_id && parent.layer_id == layer_id)
+			.filter_map(|(tile, _, pos, tileset, _)| {
+				let index =
+					combined.local_index(&tileset.0, map_id, layer_id, tile.texture_index as usize);
+				let tile_id = tilesets.get_by_id(&tileset.0)?.get_tile_id(&index)?;
+				Some(SceneTile {
+					x: pos.0,
+					y: pos.1,
+					tileset_id: tile_id.tileset_id,
+					group_id: tile_id.group_id,
+					index: index as u32,
+				})
+			})
+			.collect();
+
+		Self {
+			map_id,
+			layer_id,
+			tiles,
+		}
+	}
+
+	/// Place every tile of this snapshot into its layer, which must already exist
+	///
+	/// Tiles are placed with the texture index they were captured with, replacing any existing
+	/// tiles at their positions.
+	pub fn place(&self, placer: &mut TilePlacer) -> IrSpawnReport {
+		let mut report = IrSpawnReport::default();
+		placer.begin_batch();
+		for tile in &self.tiles {
+			let result = placer.place_baked(
+				tile.tile_id(),
+				tile.index as usize,
+				tile.pos(),
+				self.map_id,
+				self.layer_id,
+			);
+			match result {
+				Ok(_) => report.placed += 1,
+				Err(err) => report
+					.failed
+					.push((TileCoord::new(tile.pos(), self.map_id, self.layer_id), err)),
+			}
+		}
+		placer.end_batch();
+		report
+	}
+
+	/// The number of tiles in this snapshot
+	pub fn len(&self) -> usize {
+		self.tiles.len()
+	}
+
+	/// Returns true if this snapshot contains no tiles
+	pub fn is_empty(&self) -> bool {
+		self.tiles.is_empty()
+	}
+}
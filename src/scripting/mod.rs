@@ -0,0 +1,190 @@
+//! A command queue for editing and querying tiles from scripts
+//!
+//! Scripts don't have direct access to the [`TilePlacer`]. Instead, edits made by scripts are
+//! queued in the [`ScriptTileApi`] and applied in the next [`TilesetMapStage`](crate::prelude::TilesetMapStage),
+//! while queries are answered from a copy of the [`PlacedTiles`] taken at the end of every frame.
+//! This means a script won't see its own edits until the following frame.
+//!
+//! The queue doesn't depend on any particular scripting language: the [`ScriptTileApi`] can be
+//! shared with any script engine, whose functions simply forward to it.
+
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_ecs_tilemap::TilePos;
+use bevy_tileset::prelude::TileId;
+
+use crate::coord::TileCoord;
+use crate::locking::TileEdit;
+use crate::placed::PlacedTiles;
+use crate::placement::TilePlacer;
+use crate::plugin::{TilesetMapLabel, TilesetMapStage};
+
+/// Plugin for applying the tile edits made by scripts
+///
+/// This plugin must be added after the [`TilesetMapPlugin`](crate::prelude::TilesetMapPlugin).
+///
+/// Scripts are bound by handing a clone of the [`ScriptTileApi`] to the script engine and
+/// registering functions that forward to it:
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_ecs_tilemap::TilePos;
+/// # use bevy_tileset_map::prelude::{ScriptTileApi, TileId, TileScriptingPlugin, TilesetMapPlugin};
+/// let mut app = App::new();
+/// # app.add_plugin(TilesetMapPlugin::default());
+/// app.add_plugin(TileScriptingPlugin);
+///
+/// let api = app.world.get_resource::<ScriptTileApi>().unwrap().clone();
+/// // Register this as `tilemap.place` (or similar) with the script engine
+/// let place = move |group_id: u32, tileset_id: u8, x: u32, y: u32| {
+///   api.place(TileId::new(group_id, tileset_id), TilePos(x, y), 0, 0);
+/// };
+/// place(0, 0, 1, 2);
+/// ```
+#[derive(Default)]
+pub struct TileScriptingPlugin;
+
+/// A resource shared with scripts in order to edit and query placed tiles
+///
+/// Clones of this resource share the same state, so a clone can be handed to the API providers
+/// while the original stays in the world.
+///
+/// This resource is added automatically by the [`TileScriptingPlugin`].
+#[derive(Debug, Clone, Default)]
+pub struct ScriptTileApi {
+	state: Arc<Mutex<ScriptTileState>>,
+}
+
+#[derive(Debug, Default)]
+struct ScriptTileState {
+	edits: Vec<TileEdit>,
+	tiles: HashMap<TileCoord, TileId>,
+}
+
+impl Plugin for TileScriptingPlugin {
+	fn build(&self, app: &mut App) {
+		app.init_resource::<ScriptTileApi>()
+			.add_system_to_stage(
+				TilesetMapStage,
+				apply_script_edits.label(TilesetMapLabel::ApplyScriptEdits),
+			)
+			.add_system_to_stage(
+				CoreStage::Last,
+				sync_script_tiles
+					.label(TilesetMapLabel::SyncScriptTiles)
+					.after(TilesetMapLabel::UpdatePlacedTiles),
+			);
+	}
+}
+
+impl ScriptTileApi {
+	/// Queue placing a tile
+	///
+	/// # Arguments
+	///
+	/// * `tile_id`: The full ID of the tile to place
+	/// * `pos`: The tile position
+	/// * `map_id`: The tile map
+	/// * `layer_id`: The layer within the tile map
+	///
+	pub fn place(&self, tile_id: TileId, pos: TilePos, map_id: u16, layer_id: u16) {
+		self.queue(TileEdit::Place {
+			tile_id,
+			pos,
+			map_id,
+			layer_id,
+		});
+	}
+
+	/// Queue removing a tile
+	///
+	/// # Arguments
+	///
+	/// * `pos`: The tile position
+	/// * `map_id`: The tile map
+	/// * `layer_id`: The layer within the tile map
+	///
+	pub fn remove(&self, pos: TilePos, map_id: u16, layer_id: u16) {
+		self.queue(TileEdit::Remove {
+			pos,
+			map_id,
+			layer_id,
+		});
+	}
+
+	/// Queue toggling a tile
+	///
+	/// # Arguments
+	///
+	/// * `tile_id`: The full ID of the tile to toggle
+	/// * `pos`: The tile position
+	/// * `map_id`: The tile map
+	/// * `layer_id`: The layer within the tile map
+	///
+	pub fn toggle(&self, tile_id: TileId, pos: TilePos, map_id: u16, layer_id: u16) {
+		self.queue(TileEdit::Toggle {
+			tile_id,
+			pos,
+			map_id,
+			layer_id,
+		});
+	}
+
+	/// Queue the given edit
+	pub fn queue(&self, edit: TileEdit) {
+		self.lock().edits.push(edit);
+	}
+
+	/// Get the ID of the tile at the given coordinate, as of the end of the previous frame
+	pub fn get(&self, coord: &TileCoord) -> Option<TileId> {
+		self.lock().tiles.get(coord).copied()
+	}
+
+	/// Returns true if a tile existed at the given coordinate at the end of the previous frame
+	pub fn exists(&self, coord: &TileCoord) -> bool {
+		self.lock().tiles.contains_key(coord)
+	}
+
+	/// The number of edits waiting to be applied
+	pub fn pending(&self) -> usize {
+		self.lock().edits.len()
+	}
+
+	fn lock(&self) -> MutexGuard<'_, ScriptTileState> {
+		// A panic while holding the lock can't leave the state half-updated
+		self.state
+			.lock()
+			.unwrap_or_else(|poisoned| poisoned.into_inner())
+	}
+}
+
+/// __\[SYSTEM\]__ Applies the edits queued by scripts
+fn apply_script_edits(api: Res<ScriptTileApi>, mut placer: TilePlacer) {
+	let edits = std::mem::take(&mut api.lock().edits);
+	if edits.is_empty() {
+		return;
+	}
+
+	placer.begin_batch();
+	for edit in edits {
+		if let Err(err) = edit.apply(&mut placer) {
+			warn!("Could not apply scripted edit {:?}: {}", edit, err);
+		}
+	}
+	placer.end_batch();
+}
+
+/// __\[SYSTEM\]__ Copies the placed tiles so they can be queried by scripts
+fn sync_script_tiles(api: Res<ScriptTileApi>, placed: Res<PlacedTiles>) {
+	if !placed.is_changed() {
+		return;
+	}
+
+	let mut state = api.lock();
+	state.tiles.clear();
+	state
+		.tiles
+		.extend(placed.iter().map(|(coord, _, tile_id)| (*coord, tile_id)));
+}
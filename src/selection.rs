@@ -0,0 +1,261 @@
+//! Highlighting selected tiles
+//!
+//! The [`TileSelection`] resource contains the coordinates that should be highlighted, such as the
+//! hovered tile, a rectangular selection, or the footprint of a brush. Whenever it changes, the
+//! highlight is redrawn according to the [`TileHighlightConfig`]: either by placing a highlight
+//! tile on an overlay layer or by tinting the selected tiles themselves.
+//!
+//! Nothing is highlighted unless a [`TileHighlightConfig`] resource exists.
+
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+use bevy_ecs_tilemap::TilePos;
+use bevy_tileset::prelude::TileId;
+
+use crate::coord::TileCoord;
+use crate::placement::{TileBrush, TilePlacer};
+
+/// How selected tiles are highlighted
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum TileHighlight {
+	/// Place the given tile on an overlay layer above each selected tile
+	///
+	/// The overlay layer needs to be created by you, just like any other layer.
+	Tile {
+		/// The tile drawn over selected tiles
+		tile_id: TileId,
+		/// The overlay layer (within the map of each selected tile) the highlight is drawn on
+		layer_id: u16,
+	},
+	/// Tint each selected tile with the given color
+	///
+	/// The original color is restored once the tile is deselected.
+	Tint(Color),
+}
+
+/// A resource configuring how the [`TileSelection`] is highlighted
+///
+/// # Examples
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_tileset_map::prelude::{TileHighlight, TileHighlightConfig};
+/// App::new().insert_resource(TileHighlightConfig {
+///   highlight: TileHighlight::Tint(Color::rgba(1.0, 1.0, 0.5, 0.8)),
+/// });
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TileHighlightConfig {
+	/// How selected tiles are highlighted
+	pub highlight: TileHighlight,
+}
+
+/// A resource containing the highlighted tiles
+///
+/// Tiles placed at a selected coordinate after it was highlighted are only highlighted once the
+/// selection changes again.
+///
+/// This resource is added automatically by the [`TilesetMapPlugin`](crate::prelude::TilesetMapPlugin).
+///
+/// # Examples
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_ecs_tilemap::TilePos;
+/// # use bevy_tileset_map::prelude::{TileBrush, TileSelection};
+/// struct Hovered(Option<TilePos>);
+///
+/// fn preview_brush(hovered: Res<Hovered>, brush: Res<TileBrush>, mut selection: ResMut<TileSelection>) {
+///   selection.clear();
+///   if let Some(pos) = hovered.0 {
+///     selection.select_brush(&brush, pos, 0, 0);
+///   }
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TileSelection {
+	tiles: HashSet<TileCoord>,
+}
+
+/// The highlights currently drawn by [`update_tile_highlights`]
+#[derive(Default)]
+pub(crate) struct DrawnHighlights {
+	/// The config the highlights were drawn with
+	config: Option<TileHighlightConfig>,
+	/// The highlighted coordinates, along with their original color when tinted
+	tiles: HashMap<TileCoord, Option<Color>>,
+}
+
+impl TileSelection {
+	/// Select the given tile
+	///
+	/// Returns true if the tile wasn't selected already.
+	pub fn select(&mut self, coord: TileCoord) -> bool {
+		self.tiles.insert(coord)
+	}
+
+	/// Deselect the given tile
+	///
+	/// Returns true if the tile was selected.
+	pub fn deselect(&mut self, coord: &TileCoord) -> bool {
+		self.tiles.remove(coord)
+	}
+
+	/// Select the given tile if it isn't selected, otherwise deselect it
+	///
+	/// Returns true if the tile is now selected.
+	pub fn toggle(&mut self, coord: TileCoord) -> bool {
+		if self.tiles.remove(&coord) {
+			false
+		} else {
+			self.tiles.insert(coord)
+		}
+	}
+
+	/// Select all tiles within the given rectangle (inclusive)
+	pub fn select_rect<Pos: Into<TilePos>>(
+		&mut self,
+		from: Pos,
+		to: Pos,
+		map_id: u16,
+		layer_id: u16,
+	) {
+		let (from, to) = (from.into(), to.into());
+		for y in from.1.min(to.1)..=from.1.max(to.1) {
+			for x in from.0.min(to.0)..=from.0.max(to.0) {
+				self.tiles
+					.insert(TileCoord::new(TilePos(x, y), map_id, layer_id));
+			}
+		}
+	}
+
+	/// Select every tile the given brush would paint when applied at the given position
+	pub fn select_brush<Pos: Into<TilePos>>(
+		&mut self,
+		brush: &TileBrush,
+		pos: Pos,
+		map_id: u16,
+		layer_id: u16,
+	) {
+		self.tiles.extend(
+			brush
+				.tiles_at(pos)
+				.into_iter()
+				.map(|(pos, _)| TileCoord::new(pos, map_id, layer_id)),
+		);
+	}
+
+	/// Replace the selection with the given tiles
+	pub fn set<I: IntoIterator<Item = TileCoord>>(&mut self, coords: I) {
+		self.tiles.clear();
+		self.tiles.extend(coords);
+	}
+
+	/// Deselect all tiles
+	pub fn clear(&mut self) {
+		self.tiles.clear();
+	}
+
+	/// Returns true if the given tile is selected
+	pub fn contains(&self, coord: &TileCoord) -> bool {
+		self.tiles.contains(coord)
+	}
+
+	/// Iterate over all selected tiles
+	pub fn iter(&self) -> impl Iterator<Item = &TileCoord> {
+		self.tiles.iter()
+	}
+
+	/// The number of selected tiles
+	pub fn len(&self) -> usize {
+		self.tiles.len()
+	}
+
+	/// Returns true if no tiles are selected
+	pub fn is_empty(&self) -> bool {
+		self.tiles.is_empty()
+	}
+}
+
+impl TileHighlight {
+	/// Highlight the given tile, returning its original color if it was tinted
+	fn draw(&self, coord: TileCoord, placer: &mut TilePlacer) -> Option<Color> {
+		match *self {
+			Self::Tile { tile_id, layer_id } => {
+				// The overlay layer may not exist yet, in which case there's nothing to draw on
+				placer
+					.replace(tile_id, coord.pos, coord.map_id, layer_id)
+					.ok();
+				None
+			},
+			Self::Tint(color) => placer
+				.set_color(coord.pos, coord.map_id, coord.layer_id, color)
+				.ok(),
+		}
+	}
+
+	/// Remove the highlight from the given tile
+	fn erase(&self, coord: TileCoord, original: Option<Color>, placer: &mut TilePlacer) {
+		// Failures here just mean the highlighted tile is already gone
+		match *self {
+			Self::Tile { layer_id, .. } => {
+				placer.remove(coord.pos, coord.map_id, layer_id).ok();
+			},
+			Self::Tint(..) => {
+				if let Some(color) = original {
+					placer
+						.set_color(coord.pos, coord.map_id, coord.layer_id, color)
+						.ok();
+				}
+			},
+		}
+	}
+}
+
+/// __\[SYSTEM\]__ Keeps the highlights in sync with the [`TileSelection`]
+pub(crate) fn update_tile_highlights(
+	config: Option<Res<TileHighlightConfig>>,
+	selection: Res<TileSelection>,
+	mut drawn: Local<DrawnHighlights>,
+	mut placer: TilePlacer,
+) {
+	let config = config.as_deref().copied();
+	if drawn.config != config {
+		// Erase everything drawn using the old config so it can be redrawn using the new one
+		if let Some(old) = drawn.config {
+			for (coord, original) in drawn.tiles.drain() {
+				old.highlight.erase(coord, original, &mut placer);
+			}
+		}
+		drawn.tiles.clear();
+		drawn.config = config;
+	} else if !selection.is_changed() {
+		return;
+	}
+
+	let config = if let Some(config) = config {
+		config
+	} else {
+		return;
+	};
+
+	placer.begin_batch();
+	let deselected = drawn
+		.tiles
+		.keys()
+		.filter(|coord| !selection.contains(coord))
+		.copied()
+		.collect::<Vec<_>>();
+	for coord in deselected {
+		let original = drawn.tiles.remove(&coord).flatten();
+		config.highlight.erase(coord, original, &mut placer);
+	}
+
+	for coord in selection.iter() {
+		if !drawn.tiles.contains_key(coord) {
+			let original = config.highlight.draw(*coord, &mut placer);
+			drawn.tiles.insert(*coord, original);
+		}
+	}
+	placer.end_batch();
+}
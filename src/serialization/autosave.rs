@@ -0,0 +1,261 @@
+//! Periodically saving the chunks of a tilemap that have changed
+//!
+//! The [`TilemapAutosavePlugin`] is opt-in and tracks which chunks have been edited since they
+//! were last saved. On every [`AutosaveConfig::interval`] (or whenever a [`RequestSave`] event is
+//! sent), each dirty chunk is encoded with the configured [`TilemapFormat`] and written to its own
+//! file on the [`IoTaskPool`], so the main thread never waits on the file system.
+//!
+//! [`TilemapFormat`]: crate::prelude::TilemapFormat
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::changes::TilemapChanges;
+use crate::ir::TilemapIr;
+use crate::plugin::TilesetMapLabel;
+use crate::serialization::{SerializableTilemap, TilemapFormats, TilemapSerializer};
+use bevy::math::UVec2;
+use bevy::prelude::*;
+use bevy::tasks::IoTaskPool;
+use bevy::utils::{HashMap, HashSet};
+
+/// Plugin for automatically saving edited chunks
+///
+/// # Examples
+///
+/// ```
+/// # use std::time::Duration;
+/// # use bevy::prelude::*;
+/// # use bevy_tileset_map::prelude::{AutosaveConfig, TilemapAutosavePlugin};
+/// App::new()
+///   .insert_resource(AutosaveConfig {
+///     interval: Some(Duration::from_secs(30)),
+///     directory: "saves/world".into(),
+///     ..Default::default()
+///   })
+///   .add_plugin(TilemapAutosavePlugin);
+/// ```
+#[derive(Default)]
+pub struct TilemapAutosavePlugin;
+
+/// A resource configuring the [`TilemapAutosavePlugin`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct AutosaveConfig {
+	/// Whether dirty chunks should be saved
+	///
+	/// Chunks are still tracked while disabled, so they will be saved once re-enabled.
+	pub enabled: bool,
+	/// How often dirty chunks are saved
+	///
+	/// If `None`, chunks are only saved when a [`RequestSave`] event is sent.
+	pub interval: Option<Duration>,
+	/// The directory each chunk file is written to
+	pub directory: PathBuf,
+	/// The name of the [`TilemapFormat`](crate::prelude::TilemapFormat) used to encode each chunk
+	///
+	/// The format must be registered in the [`TilemapFormats`] resource. Its name is also used as
+	/// the file extension.
+	pub format: String,
+}
+
+/// An event requesting that all dirty chunks be saved at the end of the current frame
+#[derive(Debug, Copy, Clone, Default)]
+pub struct RequestSave;
+
+/// A resource tracking the chunks that have changed since they were last saved
+#[derive(Debug, Default)]
+pub struct AutosaveState {
+	/// The dirty chunks, keyed by `(map_id, layer_id, chunk_pos)`
+	dirty: HashSet<(u16, u16, (u32, u32))>,
+	timer: Option<Timer>,
+}
+
+impl Plugin for TilemapAutosavePlugin {
+	fn build(&self, app: &mut App) {
+		app.init_resource::<AutosaveConfig>()
+			.init_resource::<AutosaveState>()
+			.add_event::<RequestSave>()
+			.add_system_to_stage(
+				CoreStage::Last,
+				autosave_chunks
+					.label(TilesetMapLabel::Autosave)
+					.after(TilesetMapLabel::TrackChanges),
+			);
+	}
+}
+
+impl Default for AutosaveConfig {
+	fn default() -> Self {
+		Self {
+			enabled: true,
+			interval: Some(Duration::from_secs(60)),
+			directory: PathBuf::from("autosave"),
+			format: String::from("json"),
+		}
+	}
+}
+
+impl AutosaveState {
+	/// The number of chunks waiting to be saved
+	pub fn dirty(&self) -> usize {
+		self.dirty.len()
+	}
+
+	/// Returns true if the given chunk is waiting to be saved
+	pub fn is_dirty(&self, map_id: u16, layer_id: u16, chunk_pos: UVec2) -> bool {
+		self.dirty
+			.contains(&(map_id, layer_id, (chunk_pos.x, chunk_pos.y)))
+	}
+
+	/// Mark the given chunk as needing to be saved
+	pub fn mark_dirty(&mut self, map_id: u16, layer_id: u16, chunk_pos: UVec2) {
+		self.dirty
+			.insert((map_id, layer_id, (chunk_pos.x, chunk_pos.y)));
+	}
+
+	/// Forget all dirty chunks without saving them
+	pub fn clear(&mut self) {
+		self.dirty.clear();
+	}
+}
+
+/// The path of the file the given chunk is saved to
+pub fn autosave_chunk_path(
+	config: &AutosaveConfig,
+	map_id: u16,
+	layer_id: u16,
+	chunk_pos: UVec2,
+) -> PathBuf {
+	config.directory.join(format!(
+		"map{}_layer{}_chunk{}_{}.{}",
+		map_id, layer_id, chunk_pos.x, chunk_pos.y, config.format
+	))
+}
+
+/// __\[SYSTEM\]__ Tracks edited chunks and saves them on the configured interval or on request
+fn autosave_chunks(
+	serializer: TilemapSerializer,
+	formats: Res<TilemapFormats>,
+	config: Res<AutosaveConfig>,
+	mut state: ResMut<AutosaveState>,
+	mut requests: EventReader<RequestSave>,
+	time: Res<Time>,
+	pool: Res<IoTaskPool>,
+	changes: Res<TilemapChanges>,
+) {
+	for (coord, _) in changes.iter() {
+		if let Some(layer) = serializer.registry.layer(coord.map_id, coord.layer_id) {
+			let chunk_size = layer.settings.chunk_size;
+			let chunk_pos = UVec2::new(coord.pos.0 / chunk_size.0, coord.pos.1 / chunk_size.1);
+			state.mark_dirty(coord.map_id, coord.layer_id, chunk_pos);
+		}
+	}
+
+	let mut should_save = requests.iter().count() > 0;
+	match (config.interval, state.timer.as_mut()) {
+		(Some(interval), Some(timer)) if timer.duration() == interval => {
+			should_save |= timer.tick(time.delta()).just_finished();
+		},
+		(Some(interval), _) => state.timer = Some(Timer::new(interval, true)),
+		(None, _) => state.timer = None,
+	}
+
+	if !should_save || !config.enabled || state.dirty.is_empty() {
+		return;
+	}
+
+	let format = if let Some(format) = formats.get(&config.format) {
+		format
+	} else {
+		warn!(
+			"Could not autosave: no tilemap format registered as {:?}",
+			config.format
+		);
+		return;
+	};
+
+	// Group the dirty chunks by layer so each layer only needs to be saved once
+	let mut layers: HashMap<(u16, u16), Vec<(u32, u32)>> = HashMap::default();
+	for (map_id, layer_id, chunk_pos) in state.dirty.drain() {
+		layers
+			.entry((map_id, layer_id))
+			.or_default()
+			.push(chunk_pos);
+	}
+
+	let mut files = Vec::new();
+	for ((map_id, layer_id), chunk_positions) in layers {
+		let chunk_size = if let Some(layer) = serializer.registry.layer(map_id, layer_id) {
+			layer.settings.chunk_size
+		} else {
+			// The layer no longer exists
+			continue;
+		};
+		let (tilemap, report) = serializer.save_layer(map_id, layer_id);
+		if !report.skipped.is_empty() {
+			warn!(
+				"Could not autosave {} tile(s) of layer {} in map {}",
+				report.skipped.len(),
+				layer_id,
+				map_id
+			);
+		}
+		let tiles = tilemap
+			.data
+			.get(&map_id)
+			.and_then(|layers| layers.get(&layer_id))
+			.map(Vec::as_slice)
+			.unwrap_or_default();
+
+		for (x, y) in chunk_positions {
+			let chunk_tiles = tiles
+				.iter()
+				.filter(|tile| tile.pos.0 / chunk_size.0 == x && tile.pos.1 / chunk_size.1 == y)
+				.cloned()
+				.collect::<Vec<_>>();
+
+			let mut data = HashMap::default();
+			data.entry(map_id)
+				.or_insert_with(HashMap::default)
+				.insert(layer_id, chunk_tiles);
+			let chunk = SerializableTilemap {
+				version: tilemap.version,
+				data,
+				names: Vec::new(),
+				objects: HashMap::default(),
+				manifest: Default::default(),
+			};
+
+			match format.encode(&TilemapIr::from(&chunk)) {
+				Ok(bytes) => {
+					let path = autosave_chunk_path(&config, map_id, layer_id, UVec2::new(x, y));
+					files.push((path, bytes));
+				},
+				Err(err) => warn!(
+					"Could not autosave chunk {:?} of layer {} in map {}: {}",
+					(x, y),
+					layer_id,
+					map_id,
+					err
+				),
+			}
+		}
+	}
+
+	let directory = config.directory.clone();
+	pool.spawn(async move {
+		if let Err(err) = std::fs::create_dir_all(&directory) {
+			error!(
+				"Could not create autosave directory {:?}: {}",
+				directory, err
+			);
+			return;
+		}
+		for (path, bytes) in files {
+			if let Err(err) = std::fs::write(&path, bytes) {
+				error!("Could not write autosave file {:?}: {}", path, err);
+			}
+		}
+	})
+	.detach();
+}
@@ -0,0 +1,115 @@
+//! Pluggable encoding backends for serialized tilemaps
+
+use bevy::utils::HashMap;
+use thiserror::Error;
+
+use crate::ir::TilemapIr;
+
+/// Errors related to encoding or decoding a tilemap with a [`TilemapFormat`]
+#[derive(Error, Debug)]
+pub enum TilemapFormatError {
+	/// No format has been registered under the given name
+	///
+	/// Contains the name of the format in question
+	#[error("No tilemap format registered with the name {0:?}")]
+	UnknownFormat(String),
+	/// The tilemap could not be encoded
+	///
+	/// Contains a description of the failure, as given by the format
+	#[error("Could not encode tilemap: {0}")]
+	Encode(String),
+	/// The payload could not be decoded into a tilemap
+	///
+	/// Contains a description of the failure, as given by the format
+	#[error("Could not decode tilemap: {0}")]
+	Decode(String),
+}
+
+/// A backend capable of turning a [`TilemapIr`] into bytes and back again
+///
+/// Implement this to store tilemaps in your own format (protobuf, flatbuffers, an encrypted
+/// blob, etc.) and register it in the [`TilemapFormats`] resource so it can be looked up by name.
+///
+/// # Examples
+///
+/// ```
+/// # use bevy_tileset_map::prelude::{TilemapIr, TilemapFormat, TilemapFormatError};
+/// struct JsonFormat;
+///
+/// impl TilemapFormat for JsonFormat {
+///   fn name(&self) -> &str {
+///     "json"
+///   }
+///
+///   fn encode(&self, tilemap: &TilemapIr) -> Result<Vec<u8>, TilemapFormatError> {
+///     serde_json::to_vec(tilemap).map_err(|err| TilemapFormatError::Encode(err.to_string()))
+///   }
+///
+///   fn decode(&self, bytes: &[u8]) -> Result<TilemapIr, TilemapFormatError> {
+///     serde_json::from_slice(bytes).map_err(|err| TilemapFormatError::Decode(err.to_string()))
+///   }
+/// }
+/// ```
+pub trait TilemapFormat: Send + Sync + 'static {
+	/// The name this format is registered under
+	fn name(&self) -> &str;
+
+	/// Encode the given tilemap into a byte payload
+	fn encode(&self, tilemap: &TilemapIr) -> Result<Vec<u8>, TilemapFormatError>;
+
+	/// Decode a byte payload back into a tilemap
+	fn decode(&self, bytes: &[u8]) -> Result<TilemapIr, TilemapFormatError>;
+}
+
+/// A resource containing all registered [`TilemapFormat`]s, keyed by their name
+///
+/// This resource is added automatically by the [`TilesetMapPlugin`](crate::prelude::TilesetMapPlugin).
+#[derive(Default)]
+pub struct TilemapFormats {
+	formats: HashMap<String, Box<dyn TilemapFormat>>,
+}
+
+impl TilemapFormats {
+	/// Register a format
+	///
+	/// If a format with the same name was already registered, it is replaced and returned.
+	pub fn register<F: TilemapFormat>(&mut self, format: F) -> Option<Box<dyn TilemapFormat>> {
+		let name = format.name().to_string();
+		self.formats.insert(name, Box::new(format))
+	}
+
+	/// Remove the format with the given name
+	pub fn unregister(&mut self, name: &str) -> Option<Box<dyn TilemapFormat>> {
+		self.formats.remove(name)
+	}
+
+	/// Get the format with the given name
+	pub fn get(&self, name: &str) -> Option<&dyn TilemapFormat> {
+		self.formats.get(name).map(|format| format.as_ref())
+	}
+
+	/// Returns true if a format with the given name has been registered
+	pub fn contains(&self, name: &str) -> bool {
+		self.formats.contains_key(name)
+	}
+
+	/// Iterate over the names of all registered formats
+	pub fn names(&self) -> impl Iterator<Item = &str> {
+		self.formats.keys().map(|name| name.as_str())
+	}
+
+	/// Encode the given tilemap using the format with the given name
+	pub fn encode(&self, name: &str, tilemap: &TilemapIr) -> Result<Vec<u8>, TilemapFormatError> {
+		self.get_or_err(name)?.encode(tilemap)
+	}
+
+	/// Decode the given payload using the format with the given name
+	pub fn decode(&self, name: &str, bytes: &[u8]) -> Result<TilemapIr, TilemapFormatError> {
+		self.get_or_err(name)?.decode(bytes)
+	}
+
+	fn get_or_err(&self, name: &str) -> Result<&dyn TilemapFormat, TilemapFormatError> {
+		self.get(name)
+			.ok_or_else(|| TilemapFormatError::UnknownFormat(name.to_string()))
+	}
+}
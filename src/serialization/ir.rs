@@ -0,0 +1,99 @@
+//! Conversions between the serializer and the [`TilemapIr`]
+
+use bevy::utils::HashMap;
+
+use crate::ir::{IrScope, IrSpawnOptions, IrSpawnReport, TileExtras, TilemapIr};
+use crate::serialization::{
+	SerializableTile, SerializableTilemap, TilemapSerializer, TilesetManifest,
+	TILEMAP_FORMAT_VERSION,
+};
+
+impl<'w, 's> TilemapSerializer<'w, 's> {
+	/// Save all current maps as a [`TilemapIr`]
+	pub fn save_maps_ir(&self) -> TilemapIr {
+		TilemapIr::extract(IrScope::All, &self.tiles, &self.tilesets, &self.combined)
+	}
+
+	/// Save the given map as a [`TilemapIr`]
+	pub fn save_map_ir(&self, map_id: u16) -> TilemapIr {
+		TilemapIr::extract(
+			IrScope::Map(map_id),
+			&self.tiles,
+			&self.tilesets,
+			&self.combined,
+		)
+	}
+
+	/// Save the given layer for the given map as a [`TilemapIr`]
+	pub fn save_layer_ir(&self, map_id: u16, layer_id: u16) -> TilemapIr {
+		TilemapIr::extract(
+			IrScope::Layer { map_id, layer_id },
+			&self.tiles,
+			&self.tilesets,
+			&self.combined,
+		)
+	}
+
+	/// Load the given [`TilemapIr`]
+	pub fn load_ir(&mut self, ir: &TilemapIr) -> IrSpawnReport {
+		ir.spawn(&mut self.tile_placer, &IrSpawnOptions::default())
+	}
+}
+
+impl From<&SerializableTilemap> for TilemapIr {
+	fn from(tilemap: &SerializableTilemap) -> Self {
+		let mut builder = TilemapIr::builder();
+		for (map_id, layers) in &tilemap.data {
+			for (layer_id, tiles) in layers {
+				let layer = builder.layer_mut(*map_id, *layer_id);
+				for tile in tiles {
+					let extras = TileExtras {
+						#[cfg(feature = "variants")]
+						variant: tile.variant,
+						elevation: tile.elevation,
+						flags: tile.flags,
+						owner: tile.owner,
+						transition: tile.transition,
+					};
+					layer.add_tile_with_extras(tile.pos, tile.id, extras);
+				}
+			}
+		}
+		builder.build()
+	}
+}
+
+impl From<&TilemapIr> for SerializableTilemap {
+	fn from(ir: &TilemapIr) -> Self {
+		let mut data: HashMap<u16, HashMap<u16, Vec<SerializableTile>>> = HashMap::default();
+		for (coord, tile) in ir.iter() {
+			let layer = data
+				.entry(coord.map_id)
+				.or_insert_with(HashMap::default)
+				.entry(coord.layer_id)
+				.or_insert_with(Vec::default);
+			layer.push(SerializableTile {
+				id: tile.id,
+				name: None,
+				pos: tile.pos,
+				#[cfg(feature = "variants")]
+				variant: tile.extras.and_then(|extras| extras.variant),
+				#[cfg(not(feature = "variants"))]
+				variant: None,
+				auto: false,
+				index: None,
+				elevation: tile.extras.and_then(|extras| extras.elevation),
+				flags: tile.extras.and_then(|extras| extras.flags),
+				owner: tile.extras.and_then(|extras| extras.owner),
+				transition: tile.extras.and_then(|extras| extras.transition),
+			});
+		}
+		SerializableTilemap {
+			version: TILEMAP_FORMAT_VERSION,
+			data,
+			names: Vec::new(),
+			objects: HashMap::default(),
+			manifest: TilesetManifest::default(),
+		}
+	}
+}
@@ -0,0 +1,161 @@
+//! A manifest of the tilesets used by a saved tilemap
+
+use std::path::{Component, Path};
+
+use bevy::asset::LoadState;
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use bevy_tileset::prelude::{Tileset, TilesetId};
+use serde::{Deserialize, Serialize};
+
+use crate::serialization::{SerializableTilemap, TilemapLoadReport, TilemapSerializer};
+
+/// A list of the tilesets used by a saved tilemap, along with their asset paths
+///
+/// Paths are relative to the asset folder and always use `/` as their separator, so a saved
+/// tilemap can be loaded on any platform.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct TilesetManifest {
+	entries: Vec<TilesetManifestEntry>,
+}
+
+/// A single tileset within a [`TilesetManifest`]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct TilesetManifestEntry {
+	/// The ID of the tileset
+	pub id: TilesetId,
+	/// The name of the tileset
+	pub name: String,
+	/// The asset path of the tileset
+	pub path: String,
+}
+
+/// The result of [`TilemapSerializer::load_maps_with_manifest`]
+#[derive(Debug)]
+pub enum ManifestLoad {
+	/// All tilesets were available and the tilemap was loaded
+	Loaded(TilemapLoadReport),
+	/// Some tilesets are still loading, so the tilemap was not loaded yet
+	///
+	/// Contains the handles of the loading tilesets, which should be kept alive until loading
+	/// completes.
+	Pending(Vec<Handle<Tileset>>),
+	/// Some tilesets failed to load (or have no path in the manifest)
+	///
+	/// Contains the IDs of the missing tilesets.
+	Failed(Vec<TilesetId>),
+}
+
+impl TilesetManifest {
+	/// Iterate over all entries in this manifest
+	pub fn iter(&self) -> impl Iterator<Item = &TilesetManifestEntry> {
+		self.entries.iter()
+	}
+
+	/// Get the entry for the given tileset
+	pub fn get(&self, id: &TilesetId) -> Option<&TilesetManifestEntry> {
+		self.entries.iter().find(|entry| &entry.id == id)
+	}
+
+	/// Add an entry to this manifest, replacing any existing entry for the same tileset
+	pub fn insert(&mut self, entry: TilesetManifestEntry) {
+		self.entries.retain(|existing| existing.id != entry.id);
+		self.entries.push(entry);
+	}
+
+	/// The number of tilesets in this manifest
+	pub fn len(&self) -> usize {
+		self.entries.len()
+	}
+
+	/// Returns true if this manifest contains no tilesets
+	pub fn is_empty(&self) -> bool {
+		self.entries.is_empty()
+	}
+}
+
+impl<'w, 's> TilemapSerializer<'w, 's> {
+	/// Create a manifest for the given tilesets
+	///
+	/// Tilesets that were not loaded from an asset path are skipped.
+	pub(crate) fn create_manifest(&self, ids: &HashSet<TilesetId>) -> TilesetManifest {
+		let mut manifest = TilesetManifest::default();
+		for (handle_id, tileset) in self.tileset_assets.iter() {
+			if !ids.contains(tileset.id()) {
+				continue;
+			}
+
+			if let Some(path) = self.asset_server.get_handle_path(handle_id) {
+				manifest.insert(TilesetManifestEntry {
+					id: *tileset.id(),
+					name: tileset.name().to_string(),
+					path: normalize_path(path.path()),
+				});
+			}
+		}
+		manifest
+			.entries
+			.sort_by(|a, b| a.path.cmp(&b.path).then_with(|| a.name.cmp(&b.name)));
+		manifest
+	}
+
+	/// Load the given tilemap, first loading any tilesets from its manifest that aren't loaded yet
+	///
+	/// Since assets load asynchronously, this should be called every frame until it no longer
+	/// returns [`ManifestLoad::Pending`].
+	pub fn load_maps_with_manifest(&mut self, tilemap: &SerializableTilemap) -> ManifestLoad {
+		let mut pending = Vec::new();
+		let mut failed = Vec::new();
+
+		for id in tilemap.tileset_ids() {
+			if self.tilesets.get_by_id(&id).is_some() {
+				continue;
+			}
+
+			let entry = if let Some(entry) = tilemap.manifest.get(&id) {
+				entry
+			} else {
+				failed.push(id);
+				continue;
+			};
+
+			let handle: Handle<Tileset> = self.asset_server.load(entry.path.as_str());
+			match self.asset_server.get_load_state(&handle) {
+				LoadState::Failed => failed.push(id),
+				_ => pending.push(handle),
+			}
+		}
+
+		if !failed.is_empty() {
+			ManifestLoad::Failed(failed)
+		} else if !pending.is_empty() {
+			ManifestLoad::Pending(pending)
+		} else {
+			ManifestLoad::Loaded(self.load_maps(tilemap))
+		}
+	}
+}
+
+impl SerializableTilemap {
+	/// The IDs of all tilesets used by this tilemap
+	pub fn tileset_ids(&self) -> HashSet<TilesetId> {
+		self.data
+			.values()
+			.flat_map(|layers| layers.values())
+			.flatten()
+			.map(|tile| tile.id.tileset_id)
+			.collect()
+	}
+}
+
+/// Convert a path to a string using `/` as the separator
+fn normalize_path(path: &Path) -> String {
+	path.components()
+		.filter_map(|component| match component {
+			Component::Normal(part) => Some(part.to_string_lossy()),
+			_ => None,
+		})
+		.collect::<Vec<_>>()
+		.join("/")
+}
@@ -0,0 +1,135 @@
+//! Versioning and migration of saved tilemaps
+
+use std::collections::BTreeMap;
+
+use thiserror::Error;
+
+use crate::serialization::{SerializableTilemap, SerializedWorld};
+
+/// The current version of the save format
+///
+/// Saves created before the format was versioned are treated as version `0`.
+pub const TILEMAP_FORMAT_VERSION: u32 = 1;
+
+/// Errors related to migrating saved tilemaps
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum TilemapMigrationError {
+	/// The save was created by a newer version of the format than this build supports
+	#[error("Save format version {found} is newer than the supported version {supported}")]
+	UnsupportedVersion { found: u32, supported: u32 },
+	/// No migration is registered to upgrade from the given version
+	#[error("No migration registered from save format version {0}")]
+	MissingMigration(u32),
+}
+
+type TilemapMigrationFn = Box<dyn Fn(&mut SerializableTilemap) + Send + Sync>;
+type WorldMigrationFn = Box<dyn Fn(&mut SerializedWorld) + Send + Sync>;
+
+/// A resource containing the migrations used to upgrade older saves to the current format
+///
+/// A migration registered for version `N` upgrades a save from version `N` to version `N + 1`.
+/// When migrating, every migration from the save's version up to [`TILEMAP_FORMAT_VERSION`] is
+/// applied in order. Migrations for all built-in format changes are registered by default.
+///
+/// This resource is added automatically by the [`TilesetMapPlugin`](crate::prelude::TilesetMapPlugin).
+///
+/// # Examples
+///
+/// ```
+/// # use bevy_tileset_map::prelude::{SerializableTilemap, TilemapMigrations};
+/// fn load(migrations: bevy::prelude::Res<TilemapMigrations>, mut tilemap: SerializableTilemap) {
+///   migrations.migrate_tilemap(&mut tilemap).unwrap();
+///   // Load the tilemap as usual...
+/// }
+/// ```
+pub struct TilemapMigrations {
+	tilemaps: BTreeMap<u32, TilemapMigrationFn>,
+	worlds: BTreeMap<u32, WorldMigrationFn>,
+}
+
+impl TilemapMigrations {
+	/// Create a registry without any migrations
+	pub fn empty() -> Self {
+		Self {
+			tilemaps: BTreeMap::new(),
+			worlds: BTreeMap::new(),
+		}
+	}
+
+	/// Register the migration upgrading a [`SerializableTilemap`] from the given version
+	///
+	/// Replaces any migration previously registered for that version.
+	pub fn register_tilemap<F: Fn(&mut SerializableTilemap) + Send + Sync + 'static>(
+		&mut self,
+		from_version: u32,
+		migration: F,
+	) {
+		self.tilemaps.insert(from_version, Box::new(migration));
+	}
+
+	/// Register the migration upgrading a [`SerializedWorld`] from the given version
+	///
+	/// Replaces any migration previously registered for that version.
+	pub fn register_world<F: Fn(&mut SerializedWorld) + Send + Sync + 'static>(
+		&mut self,
+		from_version: u32,
+		migration: F,
+	) {
+		self.worlds.insert(from_version, Box::new(migration));
+	}
+
+	/// Upgrade the given tilemap to the current format version
+	pub fn migrate_tilemap(
+		&self,
+		tilemap: &mut SerializableTilemap,
+	) -> Result<(), TilemapMigrationError> {
+		tilemap.version = migrate(&self.tilemaps, tilemap.version, |migration| {
+			migration(tilemap)
+		})?;
+		Ok(())
+	}
+
+	/// Upgrade the given world to the current format version
+	pub fn migrate_world(&self, world: &mut SerializedWorld) -> Result<(), TilemapMigrationError> {
+		world.version = migrate(&self.worlds, world.version, |migration| migration(world))?;
+		Ok(())
+	}
+}
+
+impl Default for TilemapMigrations {
+	fn default() -> Self {
+		let mut migrations = Self::empty();
+		// Version 0 -> 1: Added the format version itself, along with fields that all have defaults
+		migrations.register_tilemap(0, |_| {});
+		migrations.register_world(0, |_| {});
+		migrations
+	}
+}
+
+/// Apply every migration from the given version up to the current version
+///
+/// Returns the resulting version.
+fn migrate<M, F: FnMut(&M)>(
+	migrations: &BTreeMap<u32, M>,
+	version: u32,
+	mut apply: F,
+) -> Result<u32, TilemapMigrationError> {
+	if version > TILEMAP_FORMAT_VERSION {
+		return Err(TilemapMigrationError::UnsupportedVersion {
+			found: version,
+			supported: TILEMAP_FORMAT_VERSION,
+		});
+	}
+
+	// Ensure the whole chain exists before modifying anything
+	for from in version..TILEMAP_FORMAT_VERSION {
+		if !migrations.contains_key(&from) {
+			return Err(TilemapMigrationError::MissingMigration(from));
+		}
+	}
+
+	for from in version..TILEMAP_FORMAT_VERSION {
+		apply(&migrations[&from]);
+	}
+	Ok(TILEMAP_FORMAT_VERSION)
+}
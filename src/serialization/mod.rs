@@ -0,0 +1,640 @@
+//! Tools for serializing and deserializing entire tilemaps with one or more tilesets
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::{warn, AssetServer, Assets, Res};
+use bevy::utils::HashMap;
+use bevy_ecs_tilemap::TilePos;
+use serde::{Deserialize, Serialize};
+
+use crate::combined::CombinedTilesets;
+use crate::coord::TileCoord;
+use crate::flags::TileFlags;
+use crate::ir::TileExtractQuery;
+use crate::objects::{ObjectPlacementError, ObjectPlacer};
+use crate::ownership::TileOwner;
+use crate::prelude::{PlacedTile, TilePlacementError, TilePlacer};
+use crate::registry::TilesetMapRegistry;
+use bevy_tileset::prelude::{TileId, Tileset, TilesetId, Tilesets};
+use thiserror::Error;
+
+mod autosave;
+mod format;
+mod ir;
+mod manifest;
+mod migration;
+mod region;
+mod world;
+
+pub use autosave::*;
+pub use format::*;
+pub use manifest::*;
+pub use migration::*;
+pub use region::*;
+pub use world::*;
+
+/// Contains serializable tilemap data
+#[derive(Debug, Copy, Clone, Deserialize, Serialize)]
+pub struct SerializableTile {
+	pub id: TileId,
+	/// The index of the names of this tile and its tileset within the `names` table of the
+	/// containing [`SerializableTilemap`] or [`SerializedWorld`]
+	///
+	/// When present, these take precedence over the numeric [`TileId`] when loading. See
+	/// [`TilemapSerializer::name_tiles`].
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub name: Option<usize>,
+	#[serde(with = "crate::coord::TilePosRef")]
+	pub pos: TilePos,
+	/// The index of the variant that was selected for this tile (if it is a Variant tile)
+	///
+	/// This is only honored when the `variants` feature is enabled.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub variant: Option<usize>,
+	/// Whether this tile was an Auto tile
+	///
+	/// This is only honored when the `auto-tile` feature is enabled.
+	#[serde(default, skip_serializing_if = "is_false")]
+	pub auto: bool,
+	/// The texture index this tile was displaying when it was saved
+	///
+	/// This is used to load the tile as-is when the data above can't be honored.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub index: Option<usize>,
+	/// The elevation of this tile (in pixels), if any
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub elevation: Option<f32>,
+	/// The flags of this tile, if any
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub flags: Option<TileFlags>,
+	/// The owner of this tile, if any
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub owner: Option<TileOwner>,
+	/// The number of seconds until this tile's timed transition happens, if any
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub transition: Option<f32>,
+}
+
+/// Contains serializable object data
+///
+/// See [`ObjectPlacer`] for details on objects.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SerializableObject {
+	/// The kind of the object
+	pub kind: String,
+	#[serde(with = "crate::coord::TilePosRef")]
+	pub pos: TilePos,
+}
+
+/// A tile identified by name rather than by its numeric [`TileId`]
+///
+/// Unlike numeric IDs, names remain stable when tiles are added to (or reordered within) a
+/// tileset config. Each distinct name is only stored once per tilemap, and referenced by index
+/// from its [`SerializableTile`]s.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct NamedTileId {
+	/// The name of the tileset
+	pub tileset: String,
+	/// The name of the tile within the tileset
+	pub tile: String,
+}
+
+impl SerializableTile {
+	/// The [`TileId`] that should be placed when loading this tile
+	///
+	/// This includes the saved variant index (if any) so that the exact same variant is
+	/// restored, rather than a new one being randomly selected.
+	pub fn tile_id(&self) -> TileId {
+		#[allow(unused_mut)]
+		let mut id = self.id;
+		#[cfg(feature = "variants")]
+		if self.variant.is_some() {
+			id.variant_index = self.variant;
+		}
+		id
+	}
+
+	/// The reason this tile can't be loaded by its [`TileId`] in this build (if any)
+	pub fn downgrade(&self) -> Option<TileDowngrade> {
+		if cfg!(not(feature = "auto-tile")) && self.auto {
+			Some(TileDowngrade::AutoTile)
+		} else if cfg!(not(feature = "variants")) && self.variant.is_some() {
+			Some(TileDowngrade::Variant)
+		} else {
+			None
+		}
+	}
+}
+
+fn is_false(value: &bool) -> bool {
+	!*value
+}
+
+/// Why a tile was loaded using its baked texture index rather than its [`TileId`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TileDowngrade {
+	/// The tile was saved as a specific variant but the `variants` feature is disabled
+	Variant,
+	/// The tile was saved as an Auto tile but the `auto-tile` feature is disabled
+	AutoTile,
+}
+
+/// The outcome of loading a [`SerializableTilemap`]
+#[derive(Debug, Default)]
+pub struct TilemapLoadReport {
+	/// The number of tiles that were successfully placed (including downgraded ones)
+	pub placed: usize,
+	/// The tiles that were loaded using their baked texture index, along with the reason why
+	///
+	/// Downgraded tiles that could not be placed are listed in `failed` instead.
+	pub downgraded: Vec<(TileCoord, TileDowngrade)>,
+	/// The tiles that could not be placed, along with the reason why
+	pub failed: Vec<(TileCoord, TilePlacementError)>,
+	/// The number of objects that were successfully placed
+	pub placed_objects: usize,
+	/// The objects that could not be placed, along with the reason why
+	pub failed_objects: Vec<(TileCoord, ObjectPlacementError)>,
+}
+
+/// The outcome of saving tiles with the [`TilemapSerializer`]
+#[derive(Debug, Default)]
+pub struct TilemapSaveReport {
+	/// The number of tiles that were saved
+	pub saved: usize,
+	/// The tiles that could not be saved, along with the reason why
+	pub skipped: Vec<(TileCoord, TileSaveError)>,
+}
+
+impl TilemapSaveReport {
+	/// Add the results of another save to this report
+	pub(crate) fn merge(&mut self, other: TilemapSaveReport) {
+		self.saved += other.saved;
+		self.skipped.extend(other.skipped);
+	}
+}
+
+/// Why a tile could not be saved
+#[derive(Error, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TileSaveError {
+	/// The tile belongs to a tileset that is not loaded
+	#[error("Invalid tileset {0:?}")]
+	InvalidTileset(TilesetId),
+	/// The tile's texture index does not match any tile in its tileset
+	#[error("No tile with index {index} in tileset {tileset_id:?}")]
+	InvalidIndex { tileset_id: TilesetId, index: usize },
+}
+
+/// Contains serializable tilemap data
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SerializableTilemap {
+	/// The version of the save format this tilemap was saved with
+	///
+	/// See [`TilemapMigrations`] for upgrading older saves.
+	#[serde(default)]
+	pub version: u32,
+	pub data: HashMap<u16, HashMap<u16, Vec<SerializableTile>>>,
+	/// The names referenced by the tiles (see [`SerializableTile::name`])
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub names: Vec<NamedTileId>,
+	/// The objects placed in each layer, keyed by map and layer ID like the tiles
+	///
+	/// Objects are not part of the [`TilemapIr`](crate::prelude::TilemapIr), so they are not
+	/// written by the [`TilemapFormats`].
+	#[serde(default, skip_serializing_if = "HashMap::is_empty")]
+	pub objects: HashMap<u16, HashMap<u16, Vec<SerializableObject>>>,
+	/// The tilesets used by this tilemap
+	#[serde(default, skip_serializing_if = "TilesetManifest::is_empty")]
+	pub manifest: TilesetManifest,
+}
+
+/// A system parameter that can be used to handle tilemap serialization and deserialization
+#[derive(SystemParam)]
+pub struct TilemapSerializer<'w, 's> {
+	tiles: TileExtractQuery<'w, 's>,
+	tilesets: Tilesets<'w, 's>,
+	tile_placer: TilePlacer<'w, 's>,
+	tileset_assets: Res<'w, Assets<Tileset>>,
+	asset_server: Res<'w, AssetServer>,
+	registry: Res<'w, TilesetMapRegistry>,
+	combined: Res<'w, CombinedTilesets>,
+	objects: ObjectPlacer<'w, 's>,
+}
+
+macro_rules! save_tiles {
+	($self: ident, $tile: ident, $parent: ident, $pos: ident, $tileset: ident, $elevation: ident, $flags: ident, $owner: ident, $transition: ident, $tiles_map: ident, $report: ident) => {
+		let coord = TileCoord::new(*$pos, $parent.map_id, $parent.layer_id);
+		let tileset = match $self.tilesets.get_by_id(&$tileset.0) {
+			Some(tileset) => tileset,
+			None => {
+				$report
+					.skipped
+					.push((coord, TileSaveError::InvalidTileset($tileset.0)));
+				continue;
+			},
+		};
+		let index = $self.combined.local_index(
+			&$tileset.0,
+			$parent.map_id,
+			$parent.layer_id,
+			$tile.texture_index as usize,
+		);
+		let tile_id = match tileset.get_tile_id(&index) {
+			Some(tile_id) => tile_id,
+			None => {
+				$report.skipped.push((
+					coord,
+					TileSaveError::InvalidIndex {
+						tileset_id: $tileset.0,
+						index,
+					},
+				));
+				continue;
+			},
+		};
+		let map = $tiles_map
+			.entry($parent.map_id)
+			.or_insert_with(HashMap::default);
+		let layer = map.entry($parent.layer_id).or_insert_with(Vec::default);
+		let tile = SerializableTile {
+			id: *tile_id,
+			name: None,
+			pos: *$pos,
+			#[cfg(feature = "variants")]
+			variant: tile_id.variant_index,
+			#[cfg(not(feature = "variants"))]
+			variant: None,
+			#[cfg(feature = "auto-tile")]
+			auto: tile_id.auto_index.is_some(),
+			#[cfg(not(feature = "auto-tile"))]
+			auto: false,
+			index: Some(index),
+			elevation: $elevation.map(|elevation| elevation.0),
+			flags: $flags.copied(),
+			owner: $owner.copied(),
+			transition: $transition.and_then(|transition| transition.remaining),
+		};
+		layer.push(tile);
+		$report.saved += 1;
+	};
+}
+
+impl<'w, 's> TilemapSerializer<'w, 's> {
+	/// Save all current maps
+	///
+	/// Tiles that can't be saved (such as tiles whose tileset is no longer loaded) are skipped
+	/// and listed in the returned report.
+	pub fn save_maps(&self) -> (SerializableTilemap, TilemapSaveReport) {
+		let capacity = self.tiles.iter().count();
+		let mut tiles_map = HashMap::with_capacity(capacity);
+		let mut report = TilemapSaveReport::default();
+		for (tile, parent, pos, tileset, elevation, flags, owner, transition) in self.tiles.iter() {
+			save_tiles!(
+				self, tile, parent, pos, tileset, elevation, flags, owner, transition, tiles_map,
+				report
+			);
+		}
+		let tilemap = self.with_manifest(tiles_map, self.save_objects(|_| true));
+		(tilemap, report)
+	}
+
+	/// Save the given map
+	///
+	/// See [`save_maps`](Self::save_maps) for details.
+	pub fn save_map(&self, map_id: u16) -> (SerializableTilemap, TilemapSaveReport) {
+		let mut tiles_map = HashMap::default();
+		let mut report = TilemapSaveReport::default();
+		for (tile, parent, pos, tileset, elevation, flags, owner, transition) in self.tiles.iter() {
+			if parent.map_id != map_id {
+				continue;
+			}
+
+			save_tiles!(
+				self, tile, parent, pos, tileset, elevation, flags, owner, transition, tiles_map,
+				report
+			);
+		}
+		let objects = self.save_objects(|coord| coord.map_id == map_id);
+		(self.with_manifest(tiles_map, objects), report)
+	}
+
+	/// Save the given layer for the given map
+	///
+	/// See [`save_maps`](Self::save_maps) for details.
+	pub fn save_layer(
+		&self,
+		map_id: u16,
+		layer_id: u16,
+	) -> (SerializableTilemap, TilemapSaveReport) {
+		let mut tiles_map = HashMap::default();
+		let mut report = TilemapSaveReport::default();
+		for (tile, parent, pos, tileset, elevation, flags, owner, transition) in self.tiles.iter() {
+			if parent.map_id != map_id || parent.layer_id != layer_id {
+				continue;
+			}
+
+			save_tiles!(
+				self, tile, parent, pos, tileset, elevation, flags, owner, transition, tiles_map,
+				report
+			);
+		}
+		let objects =
+			self.save_objects(|coord| coord.map_id == map_id && coord.layer_id == layer_id);
+		(self.with_manifest(tiles_map, objects), report)
+	}
+
+	/// Collect the objects matching the given filter
+	fn save_objects<F: Fn(&TileCoord) -> bool>(
+		&self,
+		filter: F,
+	) -> HashMap<u16, HashMap<u16, Vec<SerializableObject>>> {
+		let mut objects_map: HashMap<u16, HashMap<u16, Vec<SerializableObject>>> =
+			HashMap::default();
+		for (coord, _, kind) in self.objects.objects().iter() {
+			if !filter(&coord) {
+				continue;
+			}
+
+			objects_map
+				.entry(coord.map_id)
+				.or_insert_with(HashMap::default)
+				.entry(coord.layer_id)
+				.or_insert_with(Vec::default)
+				.push(SerializableObject {
+					kind: kind.to_string(),
+					pos: coord.pos,
+				});
+		}
+		objects_map
+	}
+
+	/// Store the names of every tile (and its tileset) in the given tilemap
+	///
+	/// This allows the tilemap to be loaded even if the numeric IDs of its tiles have changed,
+	/// such as when tiles are added to a tileset config. Returns the number of named tiles.
+	pub fn name_tiles(&self, tilemap: &mut SerializableTilemap) -> usize {
+		let names = &mut tilemap.names;
+		tilemap
+			.data
+			.values_mut()
+			.flat_map(|layers| layers.values_mut())
+			.map(|tiles| self.name_tile_list(tiles, names))
+			.sum()
+	}
+
+	/// Store the names of every tile in the given list, adding any new name to the given table
+	pub(crate) fn name_tile_list(
+		&self,
+		tiles: &mut [SerializableTile],
+		names: &mut Vec<NamedTileId>,
+	) -> usize {
+		let mut indices: HashMap<NamedTileId, usize> = names
+			.iter()
+			.cloned()
+			.enumerate()
+			.map(|(index, name)| (name, index))
+			.collect();
+
+		let mut count = 0;
+		for tile in tiles {
+			let name = self
+				.tilesets
+				.get_by_id(&tile.id.tileset_id)
+				.and_then(|tileset| {
+					Some(NamedTileId {
+						tileset: tileset.name().to_string(),
+						tile: tileset.get_tile_name(&tile.id.group_id)?.to_string(),
+					})
+				});
+			if let Some(name) = name {
+				let index = *indices.entry(name).or_insert_with_key(|name| {
+					names.push(name.clone());
+					names.len() - 1
+				});
+				tile.name = Some(index);
+				count += 1;
+			}
+		}
+		count
+	}
+
+	/// Get the [`TileId`] that should be placed for the given tile, resolving its name (from the
+	/// given table) if present
+	pub(crate) fn resolve_tile_id(
+		&self,
+		tile: &SerializableTile,
+		names: &[NamedTileId],
+	) -> Result<TileId, TilePlacementError> {
+		let mut id = tile.tile_id();
+		if let Some(index) = tile.name {
+			let name = names
+				.get(index)
+				.ok_or(TilePlacementError::InvalidTileNameIndex(index))?;
+			let tileset = self.tilesets.get_by_name(&name.tileset);
+			let group_id = tileset.and_then(|tileset| tileset.get_tile_group_id(&name.tile));
+			match (tileset, group_id) {
+				(Some(tileset), Some(group_id)) => {
+					id.group_id = *group_id;
+					id.tileset_id = *tileset.id();
+				},
+				_ => {
+					return Err(TilePlacementError::InvalidTileName {
+						tileset: name.tileset.clone(),
+						tile: name.tile.clone(),
+					})
+				},
+			}
+		}
+		Ok(id)
+	}
+
+	/// Wrap the given tiles and objects in a [`SerializableTilemap`] along with the manifest of
+	/// their tilesets
+	fn with_manifest(
+		&self,
+		data: HashMap<u16, HashMap<u16, Vec<SerializableTile>>>,
+		objects: HashMap<u16, HashMap<u16, Vec<SerializableObject>>>,
+	) -> SerializableTilemap {
+		let mut tilemap = SerializableTilemap {
+			version: TILEMAP_FORMAT_VERSION,
+			data,
+			names: Vec::new(),
+			objects,
+			manifest: TilesetManifest::default(),
+		};
+		tilemap.manifest = self.create_manifest(&tilemap.tileset_ids());
+		tilemap
+	}
+
+	/// Load the given map
+	///
+	/// All tilesets used by the map must already be loaded. See
+	/// [`load_maps_with_manifest`](Self::load_maps_with_manifest) for loading them automatically.
+	///
+	/// Tiles saved with data that requires a disabled feature (such as Auto tiles when the
+	/// `auto-tile` feature is disabled) are loaded using their baked texture index and listed
+	/// in the returned report, rather than failing.
+	pub fn load_maps(&mut self, tilemap: &SerializableTilemap) -> TilemapLoadReport {
+		let mut report = TilemapLoadReport::default();
+		for (map_id, layers) in &tilemap.data {
+			for (layer_id, tiles) in layers.iter() {
+				for tile in tiles {
+					let coord = TileCoord {
+						pos: tile.pos,
+						map_id: *map_id,
+						layer_id: *layer_id,
+					};
+					self.load_tile(tile, &tilemap.names, coord, &mut report);
+				}
+			}
+		}
+
+		for (map_id, layers) in &tilemap.objects {
+			for (layer_id, objects) in layers.iter() {
+				for object in objects {
+					let coord = TileCoord::new(object.pos, *map_id, *layer_id);
+					self.load_object(object, coord, &mut report);
+				}
+			}
+		}
+
+		if !report.downgraded.is_empty() {
+			warn!(
+				"Loaded {} tile(s) using their baked texture index since their data requires disabled features",
+				report.downgraded.len()
+			);
+		}
+
+		report
+	}
+	/// Place a single saved object at the given coordinate, replacing any existing object
+	pub(crate) fn load_object(
+		&mut self,
+		object: &SerializableObject,
+		coord: TileCoord,
+		report: &mut TilemapLoadReport,
+	) {
+		self.objects.remove(coord.pos, coord.map_id, coord.layer_id);
+		match self.objects.place(
+			object.kind.as_str(),
+			coord.pos,
+			coord.map_id,
+			coord.layer_id,
+		) {
+			Ok(_) => report.placed_objects += 1,
+			Err(err) => report.failed_objects.push((coord, err)),
+		}
+	}
+
+	/// Place a single saved tile at the given coordinate, recording the result in the report
+	///
+	/// The tile's name (if any) is looked up in the given table.
+	pub(crate) fn load_tile(
+		&mut self,
+		tile: &SerializableTile,
+		names: &[NamedTileId],
+		coord: TileCoord,
+		report: &mut TilemapLoadReport,
+	) {
+		let downgrade = tile.downgrade().filter(|_| tile.index.is_some());
+		let placed = match (self.resolve_tile_id(tile, names), downgrade, tile.index) {
+			(Err(err), ..) => Err(err),
+			(Ok(tile_id), Some(_), Some(index)) => self.tile_placer.place_baked(
+				tile_id,
+				index,
+				coord.pos,
+				coord.map_id,
+				coord.layer_id,
+			),
+			(Ok(tile_id), ..) => {
+				self.tile_placer
+					.place(tile_id, coord.pos, coord.map_id, coord.layer_id)
+			},
+		};
+
+		if let (Ok(_), Some(downgrade)) = (&placed, downgrade) {
+			report.downgraded.push((coord, downgrade));
+		}
+
+		match placed {
+			Ok(placed) => {
+				if let PlacedTile::Added { new_tile, .. } = placed {
+					if let Some(elevation) = tile.elevation {
+						self.tile_placer.set_tile_elevation(new_tile.0, elevation);
+					}
+					if let Some(flags) = tile.flags {
+						self.tile_placer.set_tile_flags(new_tile.0, flags);
+					}
+					if let Some(owner) = tile.owner {
+						self.tile_placer.set_tile_owner(new_tile.0, Some(owner));
+					}
+					if let Some(remaining) = tile.transition {
+						self.tile_placer
+							.restore_transition(new_tile.0, &new_tile.1, remaining);
+					}
+				}
+				report.placed += 1;
+			},
+			Err(err) => report.failed.push((coord, err)),
+		}
+	}
+}
+
+#[cfg(all(test, not(feature = "headless")))]
+mod tests {
+	use bevy::ecs::system::SystemState;
+	use bevy_ecs_tilemap::TilePos;
+	use bevy_tileset::prelude::{TileHandle, TileId, TilesetParent};
+
+	use crate::serialization::{TileSaveError, TilemapSerializer};
+	use crate::testing::{self, TILESET_ID};
+
+	#[test]
+	fn unsaveable_tiles_are_skipped_and_names_are_shared() {
+		let mut app = testing::app();
+		let grass = testing::texture(&mut app, [0, 255, 0, 255]);
+		let dirt = testing::texture(&mut app, [128, 64, 0, 255]);
+		testing::add_tileset(
+			&mut app,
+			vec![
+				TileHandle::new_standard("Grass", grass),
+				TileHandle::new_standard("Dirt", dirt),
+			],
+		);
+		testing::spawn_map(&mut app);
+
+		let grass = TileId::new(0, TILESET_ID);
+		let dirt = TileId::new(1, TILESET_ID);
+		testing::place(&mut app, grass, TilePos(0, 0));
+		testing::place(&mut app, grass, TilePos(1, 0));
+		testing::place(&mut app, dirt, TilePos(2, 0));
+		testing::place(&mut app, dirt, TilePos(3, 0));
+
+		// Pretend the last tile belongs to a tileset that has since been unloaded
+		let orphan = testing::with_placer(&mut app, |placer| {
+			placer.get_tile_entity(TilePos(3, 0), 0u16, 0).unwrap()
+		});
+		app.world.entity_mut(orphan).insert(TilesetParent(99));
+
+		let mut state = SystemState::<TilemapSerializer>::new(&mut app.world);
+		let serializer = state.get_mut(&mut app.world);
+		let (mut tilemap, report) = serializer.save_maps();
+
+		// --- Report --- //
+		assert_eq!(report.saved, 3);
+		assert_eq!(report.skipped.len(), 1);
+		let (coord, err) = report.skipped[0];
+		assert_eq!(coord.pos, TilePos(3, 0));
+		assert_eq!(err, TileSaveError::InvalidTileset(99));
+
+		// --- Names --- //
+		assert_eq!(serializer.name_tiles(&mut tilemap), 3);
+		assert_eq!(tilemap.names.len(), 2);
+		let tiles = &tilemap.data[&0][&0];
+		let name_of = |pos: TilePos| {
+			let tile = tiles.iter().find(|tile| tile.pos == pos).unwrap();
+			&tilemap.names[tile.name.unwrap()].tile
+		};
+		assert_eq!(name_of(TilePos(0, 0)), "Grass");
+		assert_eq!(name_of(TilePos(1, 0)), "Grass");
+		assert_eq!(name_of(TilePos(2, 0)), "Dirt");
+	}
+}
@@ -0,0 +1,131 @@
+//! Serializing rectangular regions of a layer, such as individual chunks of a streaming world
+
+use bevy::prelude::warn;
+use bevy_ecs_tilemap::TilePos;
+use serde::{Deserialize, Serialize};
+
+use crate::coord::TileCoord;
+use crate::serialization::{
+	NamedTileId, SerializableTile, TilemapLoadReport, TilemapSaveReport, TilemapSerializer,
+	TilesetManifest, TILEMAP_FORMAT_VERSION,
+};
+
+/// A rectangular region of a single layer
+///
+/// The positions of all tiles are stored relative to the region's minimum corner, so the region
+/// can be loaded back at any offset (such as when streaming chunks of an infinite world).
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SerializedRegion {
+	/// The version of the save format this region was saved with
+	#[serde(default)]
+	pub version: u32,
+	/// The position of the region's minimum corner when it was saved
+	#[serde(with = "crate::coord::TilePosRef")]
+	pub origin: TilePos,
+	/// The size of the region (in tiles)
+	pub size: (u32, u32),
+	/// All tiles in the region, relative to its minimum corner
+	pub tiles: Vec<SerializableTile>,
+	/// The names referenced by the tiles (see [`SerializableTile::name`])
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub names: Vec<NamedTileId>,
+	/// The tilesets used by the region
+	#[serde(default, skip_serializing_if = "TilesetManifest::is_empty")]
+	pub manifest: TilesetManifest,
+}
+
+impl<'w, 's> TilemapSerializer<'w, 's> {
+	/// Save all tiles within the given rectangle (inclusive) of the given layer
+	///
+	/// Tiles that can't be saved are skipped and listed in the returned report.
+	///
+	/// ```
+	/// # use bevy_ecs_tilemap::TilePos;
+	/// # use bevy_tileset_map::prelude::TilemapSerializer;
+	/// fn save_chunk(serializer: TilemapSerializer) {
+	///   let (region, report) = serializer.serialize_region(TilePos(32, 0), TilePos(63, 31), 0, 0);
+	///   // ...
+	/// }
+	/// ```
+	pub fn serialize_region<Pos: Into<TilePos>>(
+		&self,
+		min: Pos,
+		max: Pos,
+		map_id: u16,
+		layer_id: u16,
+	) -> (SerializedRegion, TilemapSaveReport) {
+		let (min, max) = (min.into(), max.into());
+		let (min, max) = (
+			TilePos(min.0.min(max.0), min.1.min(max.1)),
+			TilePos(min.0.max(max.0), min.1.max(max.1)),
+		);
+
+		let contains =
+			|pos: TilePos| (min.0..=max.0).contains(&pos.0) && (min.1..=max.1).contains(&pos.1);
+
+		let (mut tilemap, mut report) = self.save_layer(map_id, layer_id);
+		let tiles = tilemap
+			.data
+			.remove(&map_id)
+			.and_then(|mut layers| layers.remove(&layer_id))
+			.unwrap_or_default()
+			.into_iter()
+			.filter(|tile| contains(tile.pos))
+			.map(|mut tile| {
+				tile.pos = TilePos(tile.pos.0 - min.0, tile.pos.1 - min.1);
+				tile
+			})
+			.collect::<Vec<_>>();
+
+		// Only report the tiles within the region
+		report.skipped.retain(|(coord, _)| contains(coord.pos));
+		report.saved = tiles.len();
+
+		let ids = tiles.iter().map(|tile| tile.id.tileset_id).collect();
+		let region = SerializedRegion {
+			version: TILEMAP_FORMAT_VERSION,
+			origin: min,
+			size: (max.0 - min.0 + 1, max.1 - min.1 + 1),
+			tiles,
+			names: Vec::new(),
+			manifest: self.create_manifest(&ids),
+		};
+		(region, report)
+	}
+
+	/// Load the given region into the given layer with its minimum corner at the given offset
+	///
+	/// To load the region back where it was saved, use its [`origin`](SerializedRegion::origin)
+	/// as the offset. All affected chunks are notified once the entire region has been loaded.
+	pub fn load_region<Pos: Into<TilePos>>(
+		&mut self,
+		region: &SerializedRegion,
+		offset: Pos,
+		map_id: u16,
+		layer_id: u16,
+	) -> TilemapLoadReport {
+		let offset = offset.into();
+		self.tile_placer.begin_batch();
+
+		let mut report = TilemapLoadReport::default();
+		for tile in &region.tiles {
+			let coord = TileCoord {
+				pos: TilePos(tile.pos.0 + offset.0, tile.pos.1 + offset.1),
+				map_id,
+				layer_id,
+			};
+			self.load_tile(tile, &region.names, coord, &mut report);
+		}
+
+		self.tile_placer.end_batch();
+
+		if !report.downgraded.is_empty() {
+			warn!(
+				"Loaded {} tile(s) using their baked texture index since their data requires disabled features",
+				report.downgraded.len()
+			);
+		}
+
+		report
+	}
+}
@@ -0,0 +1,309 @@
+//! Serializing entire worlds, including the map and layer scaffolding
+
+use bevy::math::Vec2;
+use bevy_ecs_tilemap::prelude::*;
+use bevy_tileset::prelude::TilesetId;
+use serde::{Deserialize, Serialize};
+
+use crate::coord::TileCoord;
+use crate::placement::{PlacedTile, TilePlacementError};
+use crate::serialization::{
+	NamedTileId, SerializableTile, TilemapLoadReport, TilemapSaveReport, TilemapSerializer,
+	TilesetManifest, TILEMAP_FORMAT_VERSION,
+};
+
+/// A document containing every map, layer, and tile in the world
+///
+/// Unlike a [`SerializableTilemap`](crate::prelude::SerializableTilemap), this also contains the
+/// settings of each layer so that the maps themselves can be recreated on load.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct SerializedWorld {
+	/// The version of the save format this world was saved with
+	///
+	/// See [`TilemapMigrations`](crate::prelude::TilemapMigrations) for upgrading older saves.
+	#[serde(default)]
+	pub version: u32,
+	/// All maps, ordered by map ID
+	pub maps: Vec<SerializedMap>,
+	/// The names referenced by the tiles of every layer (see [`SerializableTile::name`])
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub names: Vec<NamedTileId>,
+	/// The tilesets used by the world
+	#[serde(default, skip_serializing_if = "TilesetManifest::is_empty")]
+	pub manifest: TilesetManifest,
+}
+
+/// A single map within a [`SerializedWorld`]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SerializedMap {
+	/// The ID of the map
+	pub map_id: u16,
+	/// All layers of the map, ordered by layer ID
+	pub layers: Vec<SerializedLayer>,
+}
+
+/// A single layer within a [`SerializedMap`]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SerializedLayer {
+	/// The ID of the layer
+	pub layer_id: u16,
+	/// The tileset whose texture is used to render the layer
+	pub tileset: TilesetId,
+	/// The name of the tileset whose texture is used to render the layer
+	///
+	/// When present, this takes precedence over the numeric [`TilesetId`] when loading.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub tileset_name: Option<String>,
+	/// The settings of the layer
+	pub settings: SerializedLayerSettings,
+	/// All tiles in the layer
+	pub tiles: Vec<SerializableTile>,
+}
+
+/// The serializable subset of a layer's [`LayerSettings`]
+#[derive(Debug, Copy, Clone, PartialEq, Deserialize, Serialize)]
+pub struct SerializedLayerSettings {
+	/// The size of the layer (in chunks)
+	pub map_size: (u32, u32),
+	/// The size of each chunk (in tiles)
+	pub chunk_size: (u32, u32),
+	/// The size of each tile (in pixels)
+	pub tile_size: (f32, f32),
+	/// The size of the texture (in pixels)
+	pub texture_size: (f32, f32),
+	/// The size of each grid cell (in pixels)
+	pub grid_size: (f32, f32),
+	/// The type of mesh used to render the layer
+	pub mesh_type: SerializedMeshType,
+}
+
+/// A serializable mirror of [`TilemapMeshType`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub enum SerializedMeshType {
+	Square,
+	HexagonRow,
+	HexagonRowEven,
+	HexagonRowOdd,
+	HexagonColumn,
+	HexagonColumnEven,
+	HexagonColumnOdd,
+	IsometricDiamond,
+	IsometricStaggered,
+	IsometricDiamond3d,
+}
+
+impl From<&LayerSettings> for SerializedLayerSettings {
+	fn from(settings: &LayerSettings) -> Self {
+		Self {
+			map_size: (settings.map_size.0, settings.map_size.1),
+			chunk_size: (settings.chunk_size.0, settings.chunk_size.1),
+			tile_size: (settings.tile_size.0, settings.tile_size.1),
+			texture_size: (settings.texture_size.0, settings.texture_size.1),
+			grid_size: (settings.grid_size.x, settings.grid_size.y),
+			mesh_type: settings.mesh_type.into(),
+		}
+	}
+}
+
+impl From<&SerializedLayerSettings> for LayerSettings {
+	fn from(settings: &SerializedLayerSettings) -> Self {
+		let mut layer_settings = LayerSettings::new(
+			MapSize(settings.map_size.0, settings.map_size.1),
+			ChunkSize(settings.chunk_size.0, settings.chunk_size.1),
+			TileSize(settings.tile_size.0, settings.tile_size.1),
+			TextureSize(settings.texture_size.0, settings.texture_size.1),
+		);
+		layer_settings.grid_size = Vec2::new(settings.grid_size.0, settings.grid_size.1);
+		layer_settings.mesh_type = settings.mesh_type.into();
+		layer_settings
+	}
+}
+
+impl From<TilemapMeshType> for SerializedMeshType {
+	fn from(mesh_type: TilemapMeshType) -> Self {
+		match mesh_type {
+			TilemapMeshType::Square => Self::Square,
+			TilemapMeshType::Hexagon(HexType::Row) => Self::HexagonRow,
+			TilemapMeshType::Hexagon(HexType::RowEven) => Self::HexagonRowEven,
+			TilemapMeshType::Hexagon(HexType::RowOdd) => Self::HexagonRowOdd,
+			TilemapMeshType::Hexagon(HexType::Column) => Self::HexagonColumn,
+			TilemapMeshType::Hexagon(HexType::ColumnEven) => Self::HexagonColumnEven,
+			TilemapMeshType::Hexagon(HexType::ColumnOdd) => Self::HexagonColumnOdd,
+			TilemapMeshType::Isometric(IsoType::Diamond) => Self::IsometricDiamond,
+			TilemapMeshType::Isometric(IsoType::Diamond3d) => Self::IsometricDiamond3d,
+			TilemapMeshType::Isometric(IsoType::Staggered) => Self::IsometricStaggered,
+		}
+	}
+}
+
+impl From<SerializedMeshType> for TilemapMeshType {
+	fn from(mesh_type: SerializedMeshType) -> Self {
+		match mesh_type {
+			SerializedMeshType::Square => Self::Square,
+			SerializedMeshType::HexagonRow => Self::Hexagon(HexType::Row),
+			SerializedMeshType::HexagonRowEven => Self::Hexagon(HexType::RowEven),
+			SerializedMeshType::HexagonRowOdd => Self::Hexagon(HexType::RowOdd),
+			SerializedMeshType::HexagonColumn => Self::Hexagon(HexType::Column),
+			SerializedMeshType::HexagonColumnEven => Self::Hexagon(HexType::ColumnEven),
+			SerializedMeshType::HexagonColumnOdd => Self::Hexagon(HexType::ColumnOdd),
+			SerializedMeshType::IsometricDiamond => Self::Isometric(IsoType::Diamond),
+			SerializedMeshType::IsometricDiamond3d => Self::Isometric(IsoType::Diamond3d),
+			SerializedMeshType::IsometricStaggered => Self::Isometric(IsoType::Staggered),
+		}
+	}
+}
+
+impl<'w, 's> TilemapSerializer<'w, 's> {
+	/// Save every registered map and layer, including their settings
+	///
+	/// Only layers listed in the [`TilesetMapRegistry`](crate::prelude::TilesetMapRegistry) are
+	/// saved. Tiles that can't be saved are skipped and listed in the returned report.
+	pub fn save_world(&self) -> (SerializedWorld, TilemapSaveReport) {
+		let mut world = SerializedWorld {
+			version: TILEMAP_FORMAT_VERSION,
+			..Default::default()
+		};
+		let mut ids = bevy::utils::HashSet::default();
+		let mut report = TilemapSaveReport::default();
+
+		for (map_id, info) in self.registry.maps() {
+			let mut map = SerializedMap {
+				map_id,
+				layers: Vec::new(),
+			};
+
+			for (layer_id, layer) in info.layers() {
+				let tileset = if let Some(tileset) = layer.tilesets.iter().next() {
+					*tileset
+				} else {
+					continue;
+				};
+
+				let (mut tilemap, layer_report) = self.save_layer(map_id, layer_id);
+				report.merge(layer_report);
+				let tiles = tilemap
+					.data
+					.remove(&map_id)
+					.and_then(|mut layers| layers.remove(&layer_id))
+					.unwrap_or_default();
+				ids.extend(tiles.iter().map(|tile| tile.id.tileset_id));
+				ids.insert(tileset);
+
+				map.layers.push(SerializedLayer {
+					layer_id,
+					tileset,
+					tileset_name: None,
+					settings: (&layer.settings).into(),
+					tiles,
+				});
+			}
+
+			world.maps.push(map);
+		}
+
+		world.manifest = self.create_manifest(&ids);
+		(world, report)
+	}
+
+	/// Store the names of every tile and tileset in the given world
+	///
+	/// See [`name_tiles`](Self::name_tiles) for details. Returns the number of named tiles.
+	pub fn name_world_tiles(&self, world: &mut SerializedWorld) -> usize {
+		let mut count = 0;
+		for layer in world.maps.iter_mut().flat_map(|map| map.layers.iter_mut()) {
+			layer.tileset_name = self
+				.tilesets
+				.get_by_id(&layer.tileset)
+				.map(|tileset| tileset.name().to_string());
+			count += self.name_tile_list(&mut layer.tiles, &mut world.names);
+		}
+		count
+	}
+
+	/// Recreate every map and layer in the given world, then load their tiles
+	///
+	/// The maps must not already exist and all tilesets used by the world must already be loaded.
+	/// Layers whose tileset isn't loaded are skipped and their tiles are reported as failed.
+	pub fn load_world(&mut self, world: &SerializedWorld) -> TilemapLoadReport {
+		let mut report = TilemapLoadReport::default();
+		for map in &world.maps {
+			let (map_entity, mut map_component) = self.tile_placer.spawn_map(map.map_id);
+
+			for layer in &map.layers {
+				let tileset = match &layer.tileset_name {
+					Some(name) => self.tilesets.get_by_name(name),
+					None => self.tilesets.get_by_id(&layer.tileset),
+				};
+				let texture = tileset.map(|tileset| tileset.texture().clone());
+				let texture = if let Some(texture) = texture {
+					texture
+				} else {
+					for tile in &layer.tiles {
+						report.failed.push((
+							TileCoord {
+								pos: tile.pos,
+								map_id: map.map_id,
+								layer_id: layer.layer_id,
+							},
+							TilePlacementError::InvalidTileset(layer.tileset),
+						));
+					}
+					continue;
+				};
+
+				let mut layer_builder = self.tile_placer.create_layer_builder(
+					(&layer.settings).into(),
+					map.map_id,
+					layer.layer_id,
+				);
+
+				for tile in &layer.tiles {
+					let coord = TileCoord {
+						pos: tile.pos,
+						map_id: map.map_id,
+						layer_id: layer.layer_id,
+					};
+					let placed = self
+						.resolve_tile_id(tile, &world.names)
+						.and_then(|tile_id| {
+							self.tile_placer
+								.add_to_layer(tile_id, tile.pos, &mut layer_builder)
+						});
+					match placed {
+						Ok(PlacedTile::Added { new_tile, .. }) => {
+							if let Some(elevation) = tile.elevation {
+								self.tile_placer.set_tile_elevation(new_tile.0, elevation);
+							}
+							if let Some(flags) = tile.flags {
+								self.tile_placer.set_tile_flags(new_tile.0, flags);
+							}
+							if let Some(owner) = tile.owner {
+								self.tile_placer.set_tile_owner(new_tile.0, Some(owner));
+							}
+							if let Some(remaining) = tile.transition {
+								self.tile_placer.restore_transition(
+									new_tile.0,
+									&new_tile.1,
+									remaining,
+								);
+							}
+							report.placed += 1;
+						},
+						Ok(..) => report.placed += 1,
+						Err(err) => report.failed.push((coord, err)),
+					}
+				}
+
+				self.tile_placer.finish_layer(
+					&mut map_component,
+					layer.layer_id,
+					layer_builder,
+					texture,
+				);
+			}
+
+			self.tile_placer.finish_map(map_entity, map_component);
+		}
+		report
+	}
+}
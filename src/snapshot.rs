@@ -0,0 +1,211 @@
+//! Immutable, thread-safe views of tilemaps for use outside the ECS
+//!
+//! A [`TilemapReadSnapshot`] can be captured within a system and then sent to a background task
+//! (pathfinding, AI planning, save encoding, etc.). Since tiles may keep changing after the
+//! snapshot was captured, every snapshot records the [`TilemapGeneration`] it was captured at
+//! and can check whether it has gone stale without needing access to the world.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_ecs_tilemap::{Tile, TileParent, TilePos};
+use bevy_tileset::prelude::{TileId, TilesetParent, Tilesets};
+
+use crate::combined::CombinedTilesets;
+use crate::coord::TileCoord;
+use crate::ir::IrScope;
+use crate::metadata::TileMetadata;
+
+/// The query used to capture a [`TilemapReadSnapshot`]
+pub type TileSnapshotQuery<'w, 's> = Query<
+	'w,
+	's,
+	(
+		&'static Tile,
+		&'static TileParent,
+		&'static TilePos,
+		&'static TilesetParent,
+		Option<&'static TileMetadata>,
+	),
+>;
+
+/// A resource containing a counter that is incremented on every frame in which tiles change
+///
+/// This resource is added and kept up to date automatically by the
+/// [`TilesetMapPlugin`](crate::prelude::TilesetMapPlugin).
+#[derive(Debug, Default)]
+pub struct TilemapGeneration(Arc<AtomicU64>);
+
+/// A single tile within a [`TilemapReadSnapshot`]
+#[derive(Debug, Clone)]
+pub struct SnapshotTile {
+	/// The ID of the tile
+	pub id: TileId,
+	/// The metadata of the tile (if any)
+	pub metadata: Option<Arc<TileMetadata>>,
+}
+
+/// An immutable, thread-safe view of the tiles within an [`IrScope`]
+///
+/// Cloning a snapshot is cheap as its contents are shared.
+///
+/// # Examples
+///
+/// ```
+/// # use bevy::prelude::Res;
+/// # use bevy::tasks::AsyncComputeTaskPool;
+/// # use bevy_tileset_map::prelude::*;
+/// fn start_job(
+///   tiles: TileSnapshotQuery,
+///   tilesets: Tilesets,
+///   combined: Res<CombinedTilesets>,
+///   generation: Res<TilemapGeneration>,
+///   pool: Res<AsyncComputeTaskPool>,
+/// ) {
+///   let snapshot = TilemapReadSnapshot::capture(
+///     IrScope::Map(0),
+///     &tiles,
+///     &tilesets,
+///     &combined,
+///     &generation,
+///   );
+///   pool.spawn(async move {
+///     let count = snapshot.len();
+///     if !snapshot.is_stale() {
+///       // ...
+///     }
+///   }).detach();
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct TilemapReadSnapshot {
+	tiles: Arc<HashMap<TileCoord, SnapshotTile>>,
+	scope: IrScope,
+	generation: u64,
+	current: Arc<AtomicU64>,
+}
+
+impl TilemapGeneration {
+	/// The current generation
+	pub fn get(&self) -> u64 {
+		self.0.load(Ordering::Acquire)
+	}
+
+	fn increment(&self) {
+		self.0.fetch_add(1, Ordering::AcqRel);
+	}
+}
+
+impl TilemapReadSnapshot {
+	/// Capture the tiles within the given scope
+	///
+	/// Only tiles that belong to a registered tileset are captured.
+	///
+	/// # Arguments
+	///
+	/// * `scope`: Which tiles to capture
+	/// * `tiles`: A query over all tiles
+	/// * `tilesets`: The registered tilesets
+	/// * `combined`: The combined tilesets of the current layers
+	/// * `generation`: The current tilemap generation
+	///
+	pub fn capture(
+		scope: IrScope,
+		tiles: &TileSnapshotQuery,
+		tilesets: &Tilesets,
+		combined: &CombinedTilesets,
+		generation: &TilemapGeneration,
+	) -> Self {
+		let mut captured = HashMap::default();
+		for (tile, parent, pos, tileset, metadata) in tiles.iter() {
+			if !scope.contains(parent) {
+				continue;
+			}
+
+			let tile_id = tilesets.get_by_id(&tileset.0).and_then(|tileset| {
+				combined.get_tile_id(
+					tileset,
+					parent.map_id,
+					parent.layer_id,
+					tile.texture_index as usize,
+				)
+			});
+			if let Some(tile_id) = tile_id {
+				let coord = TileCoord {
+					pos: *pos,
+					map_id: parent.map_id,
+					layer_id: parent.layer_id,
+				};
+				captured.insert(
+					coord,
+					SnapshotTile {
+						id: tile_id,
+						metadata: metadata.cloned().map(Arc::new),
+					},
+				);
+			}
+		}
+
+		Self {
+			tiles: Arc::new(captured),
+			scope,
+			generation: generation.get(),
+			current: generation.0.clone(),
+		}
+	}
+
+	/// Get the tile at the given coordinate
+	pub fn get(&self, coord: &TileCoord) -> Option<&SnapshotTile> {
+		self.tiles.get(coord)
+	}
+
+	/// Returns true if a tile exists at the given coordinate
+	pub fn contains(&self, coord: &TileCoord) -> bool {
+		self.tiles.contains_key(coord)
+	}
+
+	/// Iterate over all captured tiles (in no particular order)
+	pub fn iter(&self) -> impl Iterator<Item = (&TileCoord, &SnapshotTile)> {
+		self.tiles.iter()
+	}
+
+	/// The number of captured tiles
+	pub fn len(&self) -> usize {
+		self.tiles.len()
+	}
+
+	/// Returns true if no tiles were captured
+	pub fn is_empty(&self) -> bool {
+		self.tiles.is_empty()
+	}
+
+	/// The scope this snapshot was captured with
+	pub fn scope(&self) -> IrScope {
+		self.scope
+	}
+
+	/// The generation this snapshot was captured at
+	pub fn generation(&self) -> u64 {
+		self.generation
+	}
+
+	/// Returns true if tiles have changed since this snapshot was captured
+	///
+	/// This may be called from any thread.
+	pub fn is_stale(&self) -> bool {
+		self.current.load(Ordering::Acquire) != self.generation
+	}
+}
+
+/// __\[SYSTEM\]__ Increments the [`TilemapGeneration`] whenever tiles are added, changed, or removed
+pub(crate) fn update_tilemap_generation(
+	generation: Res<TilemapGeneration>,
+	changed: Query<(), Or<(Changed<Tile>, Changed<TilesetParent>, Changed<TileMetadata>)>>,
+	removed: RemovedComponents<Tile>,
+) {
+	if changed.iter().next().is_some() || removed.iter().next().is_some() {
+		generation.increment();
+	}
+}
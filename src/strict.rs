@@ -0,0 +1,166 @@
+//! Runtime invariant checks for catching integration bugs early
+//!
+//! When the `strict` feature is enabled, the invariants this crate relies on are validated at
+//! the end of every frame. Any violations are logged with precise diagnostics and, depending on
+//! the [`StrictModeConfig`], cause a panic.
+//!
+//! These checks scan every tile each frame and are therefore only intended for development builds.
+
+use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::*;
+use bevy_tileset::prelude::TilesetParent;
+use thiserror::Error;
+
+use crate::coord::TileCoord;
+use crate::registry::TilesetMapRegistry;
+
+/// Configuration for strict mode
+///
+/// This resource is added automatically by the [`TilesetMapPlugin`](crate::prelude::TilesetMapPlugin)
+/// when the `strict` feature is enabled.
+#[derive(Debug, Clone)]
+pub struct StrictModeConfig {
+	/// Whether invariants should be checked at all
+	///
+	/// Defaults to `true`.
+	pub enabled: bool,
+	/// Whether a violation should panic rather than only being logged
+	///
+	/// Defaults to `true` in debug builds.
+	pub panic_on_violation: bool,
+}
+
+impl Default for StrictModeConfig {
+	fn default() -> Self {
+		Self {
+			enabled: true,
+			panic_on_violation: cfg!(debug_assertions),
+		}
+	}
+}
+
+/// A violated invariant
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum StrictViolation {
+	/// A tile exists without a [`TilesetParent`]
+	#[error("Tile {entity:?} at {coord:?} has no TilesetParent")]
+	MissingTilesetParent { entity: Entity, coord: TileCoord },
+	/// The tilemap does not list a tile at the position the tile claims to occupy
+	#[error("Tile {entity:?} claims to be at {coord:?} but the tilemap has {found:?} there")]
+	OccupancyMismatch {
+		entity: Entity,
+		coord: TileCoord,
+		found: Option<Entity>,
+	},
+	/// The [`TilesetMapRegistry`] lists a layer that no longer exists
+	#[error("Registry lists layer {layer_id} of map {map_id} ({entity:?}) but it does not exist")]
+	StaleRegistryLayer {
+		map_id: u16,
+		layer_id: u16,
+		entity: Entity,
+	},
+	/// An auto tile displays a texture that doesn't match its neighborhood
+	#[error("Auto tile {entity:?} ({tile_name:?}) at {coord:?} has texture index {texture_index} which does not match its neighbors")]
+	AutoTileMismatch {
+		entity: Entity,
+		coord: TileCoord,
+		tile_name: String,
+		texture_index: u16,
+	},
+}
+
+/// __\[SYSTEM\]__ Validates the runtime invariants of all tiles and maps
+pub(crate) fn check_invariants(
+	config: Res<StrictModeConfig>,
+	tiles: Query<(Entity, &TilePos, &TileParent, Option<&TilesetParent>), With<Tile>>,
+	registry: Res<TilesetMapRegistry>,
+	mut map_query: MapQuery,
+	#[cfg(feature = "auto-tile")] auto_tiles: crate::auto::AutoTileQuery,
+	#[cfg(feature = "auto-tile")] tilesets: bevy_tileset::prelude::Tilesets,
+	#[cfg(feature = "auto-tile")] active: Res<crate::activity::ActiveChunks>,
+) {
+	if !config.enabled {
+		return;
+	}
+
+	let mut violations = Vec::new();
+
+	// === Tiles === //
+	for (entity, pos, parent, tileset_parent) in tiles.iter() {
+		let coord = TileCoord {
+			pos: *pos,
+			map_id: parent.map_id,
+			layer_id: parent.layer_id,
+		};
+
+		if tileset_parent.is_none() {
+			violations.push(StrictViolation::MissingTilesetParent { entity, coord });
+		}
+
+		let found = map_query
+			.get_tile_entity(*pos, parent.map_id, parent.layer_id)
+			.ok();
+		if found != Some(entity) {
+			violations.push(StrictViolation::OccupancyMismatch {
+				entity,
+				coord,
+				found,
+			});
+		}
+	}
+
+	// === Registry === //
+	for (map_id, layer_id, layer) in registry.layers() {
+		let exists = map_query
+			.get_layer(map_id, layer_id)
+			.map(|(entity, _)| entity == layer.entity)
+			.unwrap_or_default();
+		if !exists {
+			violations.push(StrictViolation::StaleRegistryLayer {
+				map_id,
+				layer_id,
+				entity: layer.entity,
+			});
+		}
+	}
+
+	// === Auto Tiles === //
+	#[cfg(feature = "auto-tile")]
+	check_auto_tiles(&auto_tiles, &tilesets, &active, &mut violations);
+
+	if violations.is_empty() {
+		return;
+	}
+
+	for violation in &violations {
+		error!("[strict] {}", violation);
+	}
+
+	if config.panic_on_violation {
+		panic!(
+			"strict mode found {} invariant violation(s), the first being: {}",
+			violations.len(),
+			violations[0]
+		);
+	}
+}
+
+#[cfg(feature = "auto-tile")]
+fn check_auto_tiles(
+	auto_tiles: &crate::auto::AutoTileQuery,
+	tilesets: &bevy_tileset::prelude::Tilesets,
+	active: &crate::activity::ActiveChunks,
+	violations: &mut Vec<StrictViolation>,
+) {
+	let mismatches = crate::auto::find_auto_tile_mismatches(auto_tiles, tilesets, active);
+	violations.extend(
+		mismatches
+			.into_iter()
+			.map(|mismatch| StrictViolation::AutoTileMismatch {
+				entity: mismatch.entity,
+				coord: mismatch.coord,
+				tile_name: mismatch.tile_name,
+				texture_index: mismatch.texture_index,
+			}),
+	);
+}
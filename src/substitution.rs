@@ -0,0 +1,229 @@
+//! Global tile substitution tables (e.g. for seasonal events)
+//!
+//! A [`SubstitutionTable`] maps tiles to replacement tiles by name (such as `"Grass"` to
+//! `"Snowy Grass"`). Tables are registered in the [`TileSubstitutions`] resource and can then be
+//! applied to (and reverted from) every live tile in a single call using the [`TileSubstituter`]
+//! system param.
+//!
+//! Tiles are updated in place using [`TilePlacer::update`], so their entities, colors, and flags
+//! are kept intact and Auto Tiles are re-evaluated as usual.
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_ecs_tilemap::{Tile, TileParent};
+use bevy_tileset::prelude::{TileId, TilesetParent, Tilesets};
+
+use crate::combined::CombinedTilesets;
+use crate::placement::TilePlacer;
+
+/// A table of tile substitutions, keyed by tileset name and tile name
+///
+/// # Examples
+///
+/// ```
+/// # use bevy_tileset_map::prelude::SubstitutionTable;
+/// let winter = SubstitutionTable::new()
+///   .with("My Tileset", "Grass", "Snowy Grass")
+///   .with("My Tileset", "Water", "Ice");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(
+	feature = "serialization",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(transparent)
+)]
+pub struct SubstitutionTable {
+	tilesets: HashMap<String, HashMap<String, String>>,
+}
+
+/// A resource containing all registered [`SubstitutionTable`]s, along with the currently applied one
+///
+/// This resource is added automatically by the [`TilesetMapPlugin`](crate::prelude::TilesetMapPlugin).
+#[derive(Debug, Default)]
+pub struct TileSubstitutions {
+	tables: HashMap<String, SubstitutionTable>,
+	active: Option<String>,
+	/// The original ID of each substituted tile, along with the ID it was substituted with
+	applied: HashMap<Entity, (TileId, TileId)>,
+}
+
+/// A helper system param used to apply and revert [`SubstitutionTable`]s across all live maps
+#[derive(SystemParam)]
+pub struct TileSubstituter<'w, 's> {
+	placer: TilePlacer<'w, 's>,
+	tilesets: Tilesets<'w, 's>,
+	combined: Res<'w, CombinedTilesets>,
+	substitutions: ResMut<'w, TileSubstitutions>,
+	tiles: Query<
+		'w,
+		's,
+		(
+			Entity,
+			&'static Tile,
+			&'static TileParent,
+			&'static TilesetParent,
+		),
+	>,
+}
+
+impl SubstitutionTable {
+	/// Create an empty table
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Add a substitution to this table (builder-style)
+	pub fn with<S: Into<String>, F: Into<String>, T: Into<String>>(
+		mut self,
+		tileset_name: S,
+		from: F,
+		to: T,
+	) -> Self {
+		self.insert(tileset_name, from, to);
+		self
+	}
+
+	/// Add a substitution to this table, returning the replacement it overwrote (if any)
+	pub fn insert<S: Into<String>, F: Into<String>, T: Into<String>>(
+		&mut self,
+		tileset_name: S,
+		from: F,
+		to: T,
+	) -> Option<String> {
+		self.tilesets
+			.entry(tileset_name.into())
+			.or_insert_with(HashMap::default)
+			.insert(from.into(), to.into())
+	}
+
+	/// Remove a substitution from this table
+	pub fn remove(&mut self, tileset_name: &str, from: &str) -> Option<String> {
+		self.tilesets.get_mut(tileset_name)?.remove(from)
+	}
+
+	/// Get the name of the tile replacing the given tile
+	pub fn get(&self, tileset_name: &str, from: &str) -> Option<&str> {
+		self.tilesets
+			.get(tileset_name)?
+			.get(from)
+			.map(String::as_str)
+	}
+
+	/// Returns true if this table contains no substitutions
+	pub fn is_empty(&self) -> bool {
+		self.tilesets.values().all(HashMap::is_empty)
+	}
+}
+
+impl TileSubstitutions {
+	/// Register a table under the given name, returning the table it replaced (if any)
+	pub fn register<S: Into<String>>(
+		&mut self,
+		name: S,
+		table: SubstitutionTable,
+	) -> Option<SubstitutionTable> {
+		self.tables.insert(name.into(), table)
+	}
+
+	/// Unregister the table with the given name
+	pub fn unregister(&mut self, name: &str) -> Option<SubstitutionTable> {
+		self.tables.remove(name)
+	}
+
+	/// Get the table with the given name
+	pub fn get(&self, name: &str) -> Option<&SubstitutionTable> {
+		self.tables.get(name)
+	}
+
+	/// The name of the currently applied table (if any)
+	pub fn active(&self) -> Option<&str> {
+		self.active.as_deref()
+	}
+}
+
+impl<'w, 's> TileSubstituter<'w, 's> {
+	/// Apply the table with the given name to every live tile
+	///
+	/// Any previously applied table is reverted first. Returns the number of substituted tiles, or
+	/// `None` if no table is registered under the given name.
+	pub fn apply(&mut self, name: &str) -> Option<usize> {
+		let table = self.substitutions.tables.get(name)?.clone();
+		self.revert();
+
+		let mut updates = Vec::new();
+		for (entity, tile, parent, tileset_parent) in self.tiles.iter() {
+			let tileset = if let Some(tileset) = self.tilesets.get_by_id(&tileset_parent.0) {
+				tileset
+			} else {
+				continue;
+			};
+
+			let original = if let Some(id) = self.combined.get_tile_id(
+				tileset,
+				parent.map_id,
+				parent.layer_id,
+				tile.texture_index as usize,
+			) {
+				id
+			} else {
+				continue;
+			};
+
+			let substitute = tileset
+				.get_tile_name(&original.group_id)
+				.and_then(|tile_name| table.get(tileset.name(), tile_name))
+				.and_then(|to| tileset.get_tile_group_id(to))
+				.map(|group_id| TileId::new(*group_id, *tileset.id()));
+			if let Some(substitute) = substitute {
+				updates.push((entity, original, substitute));
+			}
+		}
+
+		let mut count = 0;
+		for (entity, original, substitute) in updates {
+			if self.placer.update(substitute, entity).is_ok() {
+				self.substitutions
+					.applied
+					.insert(entity, (original, substitute));
+				count += 1;
+			}
+		}
+
+		self.substitutions.active = Some(name.to_string());
+		Some(count)
+	}
+
+	/// Revert the currently applied table (if any), restoring every substituted tile
+	///
+	/// Tiles that have been despawned or replaced since the table was applied are left alone.
+	/// Returns the number of restored tiles.
+	pub fn revert(&mut self) -> usize {
+		self.substitutions.active = None;
+		let applied = std::mem::take(&mut self.substitutions.applied);
+
+		let mut count = 0;
+		for (entity, (original, substitute)) in applied {
+			let current =
+				self.tiles
+					.get(entity)
+					.ok()
+					.and_then(|(_, tile, parent, tileset_parent)| {
+						self.combined.get_tile_id(
+							self.tilesets.get_by_id(&tileset_parent.0)?,
+							parent.map_id,
+							parent.layer_id,
+							tile.texture_index as usize,
+						)
+					});
+
+			let is_unchanged = current
+				.map(|current| current.eq_tile_group(&substitute))
+				.unwrap_or_default();
+			if is_unchanged && self.placer.update(original, entity).is_ok() {
+				count += 1;
+			}
+		}
+		count
+	}
+}
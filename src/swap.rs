@@ -0,0 +1,169 @@
+//! Swapping the tileset of an entire layer at runtime
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use bevy_ecs_tilemap::prelude::*;
+use bevy_tileset::prelude::*;
+
+use crate::combined::CombinedTilesets;
+use crate::layers::TileLayerError;
+use crate::placement::TilePlacer;
+
+/// A helper system param used to retarget placed layers from one tileset to another
+///
+/// Tiles are matched by name, so the target tileset should define tiles with the same names
+/// as the original (e.g. a "winter" version of a "summer" tileset). Both tilesets must also share
+/// the same tile size and texture size.
+///
+/// # Examples
+///
+/// ```
+/// # use bevy_tileset_map::prelude::{TilesetId, TilesetSwapper};
+/// struct Themes {
+///   summer: TilesetId,
+///   winter: TilesetId,
+/// }
+///
+/// fn enter_winter(mut swapper: TilesetSwapper, themes: bevy::prelude::Res<Themes>) {
+///   swapper.swap_layer(0u16, 0u16, &themes.summer, &themes.winter).unwrap();
+/// }
+/// ```
+#[derive(SystemParam)]
+pub struct TilesetSwapper<'w, 's> {
+	commands: Commands<'w, 's>,
+	placer: TilePlacer<'w, 's>,
+	tilesets: Tilesets<'w, 's>,
+	combined: Res<'w, CombinedTilesets>,
+	tiles: Query<
+		'w,
+		's,
+		(
+			Entity,
+			&'static Tile,
+			&'static TileParent,
+			&'static TilesetParent,
+		),
+	>,
+}
+
+impl<'w, 's> TilesetSwapper<'w, 's> {
+	/// Retarget every tile of `from` in the given layer to the matching tile in `to`
+	///
+	/// Tiles without a matching name in `to` are left untouched. The texture of every affected
+	/// chunk is replaced with the texture of `to`.
+	///
+	/// Returns the number of swapped tiles.
+	///
+	/// # Arguments
+	///
+	/// * `map_id`: The tile map
+	/// * `layer_id`: The layer within the tile map
+	/// * `from`: The tileset currently used by the layer
+	/// * `to`: The tileset to swap to
+	///
+	pub fn swap_layer<MId: MapId>(
+		&mut self,
+		map_id: MId,
+		layer_id: u16,
+		from: &TilesetId,
+		to: &TilesetId,
+	) -> Result<usize, TileLayerError> {
+		self.swap(map_id.into(), Some(layer_id), from, to)
+	}
+
+	/// Retarget every tile of `from` in all layers of the given map to the matching tile in `to`
+	///
+	/// See [`swap_layer`](Self::swap_layer) for details.
+	pub fn swap_map<MId: MapId>(
+		&mut self,
+		map_id: MId,
+		from: &TilesetId,
+		to: &TilesetId,
+	) -> Result<usize, TileLayerError> {
+		self.swap(map_id.into(), None, from, to)
+	}
+
+	fn swap(
+		&mut self,
+		map_id: u16,
+		layer_id: Option<u16>,
+		from: &TilesetId,
+		to: &TilesetId,
+	) -> Result<usize, TileLayerError> {
+		let from_tileset = self
+			.tilesets
+			.get_by_id(from)
+			.ok_or(TileLayerError::InvalidTileset(*from))?;
+		let to_tileset = self
+			.tilesets
+			.get_by_id(to)
+			.ok_or(TileLayerError::InvalidTileset(*to))?;
+		if from_tileset.tile_size() != to_tileset.tile_size()
+			|| from_tileset.size() != to_tileset.size()
+		{
+			return Err(TileLayerError::IncompatibleTileset {
+				from: *from,
+				to: *to,
+			});
+		}
+
+		let texture = to_tileset.texture().clone();
+		let mut updates = Vec::new();
+		let mut chunks = HashSet::default();
+		for (entity, tile, parent, tileset_parent) in self.tiles.iter() {
+			if &tileset_parent.0 != from
+				|| parent.map_id != map_id
+				|| layer_id
+					.map(|layer_id| layer_id != parent.layer_id)
+					.unwrap_or_default()
+			{
+				continue;
+			}
+
+			let original = if let Some(id) = self.combined.get_tile_id(
+				from_tileset,
+				parent.map_id,
+				parent.layer_id,
+				tile.texture_index as usize,
+			) {
+				id
+			} else {
+				continue;
+			};
+
+			let target = from_tileset
+				.get_tile_name(&original.group_id)
+				.and_then(|tile_name| to_tileset.get_tile_group_id(tile_name))
+				.map(|group_id| {
+					let mut target = original;
+					target.group_id = *group_id;
+					target.tileset_id = *to;
+					target
+				});
+			if let Some(target) = target {
+				updates.push((entity, target));
+				chunks.insert(parent.chunk);
+			}
+		}
+
+		let mut count = 0;
+		for (entity, target) in updates {
+			// Fall back to the plain tile if the exact variant doesn't exist in the new tileset
+			let updated = self.placer.update(target, entity).is_ok()
+				|| self
+					.placer
+					.update(TileId::new(target.group_id, target.tileset_id), entity)
+					.is_ok();
+			if updated {
+				count += 1;
+			}
+		}
+
+		for chunk in chunks {
+			self.commands.entity(chunk).insert(texture.clone());
+		}
+
+		Ok(count)
+	}
+}
@@ -0,0 +1,435 @@
+//! Reusable templates (such as rooms or dungeon pieces) that can be stamped into a map
+//!
+//! Templates can be authored as text files using the `.template` extension, which are loaded
+//! automatically as [`MapTemplate`] assets. A template file consists of an optional `[legend]`
+//! section mapping characters to tiles (given as `Tileset Name/Tile Name`), followed by a `[map]`
+//! section containing the character grid itself. Spaces are left empty.
+//!
+//! ```text
+//! [legend]
+//! # = Dungeon/Wall
+//! . = Dungeon/Floor
+//! [map]
+//! #####
+//! #...#
+//! #...D
+//! #####
+//! ```
+//!
+//! Characters without a legend entry (such as `D` above) must be mapped when stamping the template
+//! using [`TemplateStamp::with_legend`]. This allows a single template to be reused with different
+//! tiles, like doors that lead to different places.
+
+use std::str::FromStr;
+
+use bevy::asset::{AssetLoader, LoadContext, LoadedAsset};
+use bevy::reflect::TypeUuid;
+use bevy::utils::{BoxedFuture, HashMap};
+use bevy_ecs_tilemap::TilePos;
+use bevy_tileset::prelude::TileId;
+use thiserror::Error;
+
+/// Errors related to parsing a [`MapTemplate`]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum MapTemplateError {
+	/// A legend entry is not of the form `c = Tileset Name/Tile Name`
+	///
+	/// Contains the (1-based) line number and the line in question
+	#[error("Invalid legend entry on line {0}: {1:?}")]
+	InvalidLegend(usize, String),
+	/// The template contains a section other than `[legend]` or `[map]`
+	///
+	/// Contains the name of the section in question
+	#[error("Unknown template section {0:?}")]
+	UnknownSection(String),
+	/// The template file is not valid UTF-8
+	#[error("Template is not valid UTF-8: {0}")]
+	InvalidUtf8(#[from] std::str::Utf8Error),
+}
+
+/// A single tile within a [`MapTemplate`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TemplateTile {
+	/// A tile given by its ID
+	Id(TileId),
+	/// A tile given by its name and the name of its tileset
+	Named { tileset: String, tile: String },
+	/// A character that is mapped to a tile using a legend
+	Char(char),
+}
+
+/// A rectangular pattern of tiles that can be stamped into a map using
+/// [`TilePlacer::stamp_template`](crate::prelude::TilePlacer::stamp_template)
+///
+/// This is added as an asset by the `TilesetMapPlugin`, along with a loader for `.template` files.
+#[derive(Debug, Clone, PartialEq, TypeUuid)]
+#[uuid = "6f1b2c3e-9a54-4d07-8e2b-5c1f0a7d3e91"]
+pub struct MapTemplate {
+	width: u32,
+	height: u32,
+	/// The tiles of the template, row by row from the bottom
+	tiles: Vec<Option<TemplateTile>>,
+	/// The tiles the characters of this template map to by default
+	legend: HashMap<char, TemplateTile>,
+}
+
+/// Options for stamping a [`MapTemplate`] into a map
+///
+/// The template is mirrored first, then rotated. The stamp position always marks the bottom-left
+/// corner of the transformed template.
+///
+/// # Examples
+///
+/// ```
+/// # use bevy_tileset_map::prelude::{TemplateStamp, TileId};
+/// let stamp = TemplateStamp::new()
+///   .with_legend('D', TileId::new(3, 0))
+///   .mirrored_x()
+///   .rotated(1);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TemplateStamp {
+	/// The number of 90° clockwise turns applied to the template
+	pub rotation: u8,
+	/// Whether the template is mirrored horizontally
+	pub flip_x: bool,
+	/// Whether the template is mirrored vertically
+	pub flip_y: bool,
+	/// The tiles characters map to, taking precedence over the template's own legend
+	pub legend: HashMap<char, TileId>,
+}
+
+/// The asset loader for `.template` files
+#[derive(Default)]
+pub struct MapTemplateLoader;
+
+impl MapTemplate {
+	/// Create an empty template of the given size
+	pub fn new(width: u32, height: u32) -> Self {
+		Self {
+			width,
+			height,
+			tiles: vec![None; (width * height) as usize],
+			legend: HashMap::default(),
+		}
+	}
+
+	/// Parse a template from the raw contents of a `.template` file
+	///
+	/// See [`MapTemplate::from_str`] for the format.
+	pub fn from_bytes(bytes: &[u8]) -> Result<Self, MapTemplateError> {
+		std::str::from_utf8(bytes)?.parse()
+	}
+
+	/// Create a template from a serialized region
+	///
+	/// Tiles saved with their names are looked up by name when stamped.
+	#[cfg(feature = "serialization")]
+	pub fn from_region(region: &crate::serialization::SerializedRegion) -> Self {
+		let mut template = Self::new(region.size.0, region.size.1);
+		for tile in &region.tiles {
+			let name = tile.name.and_then(|index| region.names.get(index));
+			let template_tile = match name {
+				Some(name) => TemplateTile::Named {
+					tileset: name.tileset.clone(),
+					tile: name.tile.clone(),
+				},
+				None => TemplateTile::Id(tile.tile_id()),
+			};
+			template.set(tile.pos, Some(template_tile));
+		}
+		template
+	}
+
+	/// The width of the template (in tiles)
+	pub fn width(&self) -> u32 {
+		self.width
+	}
+
+	/// The height of the template (in tiles)
+	pub fn height(&self) -> u32 {
+		self.height
+	}
+
+	/// Get the tile at the given position of the (untransformed) template
+	pub fn get(&self, pos: TilePos) -> Option<&TemplateTile> {
+		self.index(pos).and_then(|index| self.tiles[index].as_ref())
+	}
+
+	/// Set the tile at the given position of the (untransformed) template
+	///
+	/// Positions outside the template are ignored.
+	pub fn set(&mut self, pos: TilePos, tile: Option<TemplateTile>) {
+		if let Some(index) = self.index(pos) {
+			self.tiles[index] = tile;
+		}
+	}
+
+	/// Map the given character to the given tile by default
+	pub fn set_legend(&mut self, c: char, tile: TemplateTile) {
+		self.legend.insert(c, tile);
+	}
+
+	/// Get the tile the given character maps to by default
+	pub fn get_legend(&self, c: char) -> Option<&TemplateTile> {
+		self.legend.get(&c)
+	}
+
+	/// The size of the template once transformed by the given stamp
+	pub fn stamped_size(&self, stamp: &TemplateStamp) -> (u32, u32) {
+		if stamp.rotation.is_multiple_of(2) {
+			(self.width, self.height)
+		} else {
+			(self.height, self.width)
+		}
+	}
+
+	/// Iterate over every tile of the template as transformed by the given stamp
+	///
+	/// Positions are relative to the bottom-left corner of the transformed template.
+	pub fn iter_stamped<'a>(
+		&'a self,
+		stamp: &'a TemplateStamp,
+	) -> impl Iterator<Item = (TilePos, &'a TemplateTile)> + 'a {
+		self.tiles
+			.iter()
+			.enumerate()
+			.filter_map(move |(index, tile)| {
+				let tile = tile.as_ref()?;
+				let pos = TilePos(index as u32 % self.width, index as u32 / self.width);
+				Some((self.transform(pos, stamp), tile))
+			})
+	}
+
+	/// Transform the given position of the template using the given stamp
+	fn transform(&self, pos: TilePos, stamp: &TemplateStamp) -> TilePos {
+		let (mut width, mut height) = (self.width, self.height);
+		let mut x = if stamp.flip_x {
+			width - 1 - pos.0
+		} else {
+			pos.0
+		};
+		let mut y = if stamp.flip_y {
+			height - 1 - pos.1
+		} else {
+			pos.1
+		};
+
+		for _ in 0..stamp.rotation % 4 {
+			// Rotate clockwise: the left column becomes the top row
+			let rotated = (y, width - 1 - x);
+			x = rotated.0;
+			y = rotated.1;
+			std::mem::swap(&mut width, &mut height);
+		}
+
+		TilePos(x, y)
+	}
+
+	fn index(&self, pos: TilePos) -> Option<usize> {
+		(pos.0 < self.width && pos.1 < self.height).then(|| (pos.1 * self.width + pos.0) as usize)
+	}
+}
+
+impl FromStr for MapTemplate {
+	type Err = MapTemplateError;
+
+	/// Parse a template from the contents of a `.template` file
+	///
+	/// If the text contains no sections, it is treated as a bare `[map]` section. Blank lines
+	/// surrounding the map are ignored.
+	fn from_str(text: &str) -> Result<Self, Self::Err> {
+		let mut legend = HashMap::default();
+		let mut map = Vec::new();
+		let mut in_legend = false;
+
+		for (number, line) in text.lines().enumerate() {
+			let line = line.trim_end_matches('\r');
+			if let Some(section) = is_section(line) {
+				in_legend = match section {
+					"legend" => true,
+					"map" => false,
+					_ => return Err(MapTemplateError::UnknownSection(section.to_string())),
+				};
+				continue;
+			}
+
+			if !in_legend {
+				if !map.is_empty() || !line.trim().is_empty() {
+					map.push(line);
+				}
+				continue;
+			}
+
+			if line.trim().is_empty() {
+				continue;
+			}
+			let (c, tile) = parse_legend(line)
+				.ok_or_else(|| MapTemplateError::InvalidLegend(number + 1, line.to_string()))?;
+			legend.insert(c, tile);
+		}
+
+		let (width, height, chars) = parse_grid(map);
+		Ok(Self {
+			width,
+			height,
+			tiles: chars
+				.into_iter()
+				.map(|c| c.map(TemplateTile::Char))
+				.collect(),
+			legend,
+		})
+	}
+}
+
+impl TemplateStamp {
+	/// Create a stamp that places the template as-is
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Map the given character to the given tile (builder-style)
+	pub fn with_legend<Id: Into<TileId>>(mut self, c: char, tile_id: Id) -> Self {
+		self.legend.insert(c, tile_id.into());
+		self
+	}
+
+	/// Rotate the template by the given number of 90° clockwise turns (builder-style)
+	pub fn rotated(mut self, turns: u8) -> Self {
+		self.rotation = (self.rotation + turns % 4) % 4;
+		self
+	}
+
+	/// Mirror the template horizontally (builder-style)
+	pub fn mirrored_x(mut self) -> Self {
+		self.flip_x = !self.flip_x;
+		self
+	}
+
+	/// Mirror the template vertically (builder-style)
+	pub fn mirrored_y(mut self) -> Self {
+		self.flip_y = !self.flip_y;
+		self
+	}
+}
+
+impl AssetLoader for MapTemplateLoader {
+	fn load<'a>(
+		&'a self,
+		bytes: &'a [u8],
+		load_context: &'a mut LoadContext,
+	) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+		Box::pin(async move {
+			let template = MapTemplate::from_bytes(bytes)?;
+			load_context.set_default_asset(LoadedAsset::new(template));
+			Ok(())
+		})
+	}
+
+	fn extensions(&self) -> &[&str] {
+		&["template"]
+	}
+}
+
+/// Parse the given rows of characters (top to bottom) into a grid, row by row from the bottom
+///
+/// Returns the width and height of the grid along with its cells, where spaces are left empty.
+/// Rows shorter than the longest row are padded with empty cells.
+pub(crate) fn parse_grid<'a, I: IntoIterator<Item = &'a str>>(
+	rows: I,
+) -> (u32, u32, Vec<Option<char>>) {
+	let mut rows = rows
+		.into_iter()
+		.map(|row| row.trim_end_matches('\r').chars().collect::<Vec<_>>())
+		.collect::<Vec<_>>();
+	while rows.last().is_some_and(|row| row.iter().all(|c| *c == ' ')) {
+		rows.pop();
+	}
+
+	let width = rows.iter().map(Vec::len).max().unwrap_or_default();
+	let height = rows.len();
+	let mut cells = Vec::with_capacity(width * height);
+	for row in rows.iter().rev() {
+		for x in 0..width {
+			cells.push(row.get(x).copied().filter(|c| *c != ' '));
+		}
+	}
+
+	(width as u32, height as u32, cells)
+}
+
+/// Returns the name of the section if the given line is a section header
+fn is_section(line: &str) -> Option<&str> {
+	line.trim()
+		.strip_prefix('[')
+		.and_then(|line| line.strip_suffix(']'))
+		.map(str::trim)
+}
+
+/// Parse a legend entry of the form `c = Tileset Name/Tile Name`
+fn parse_legend(line: &str) -> Option<(char, TemplateTile)> {
+	let line = line.trim_start();
+	let c = line.chars().next()?;
+	let value = line[c.len_utf8()..].trim_start().strip_prefix('=')?.trim();
+	let (tileset, tile) = value.split_once('/')?;
+	let (tileset, tile) = (tileset.trim(), tile.trim());
+	if tileset.is_empty() || tile.is_empty() {
+		return None;
+	}
+
+	Some((
+		c,
+		TemplateTile::Named {
+			tileset: tileset.to_string(),
+			tile: tile.to_string(),
+		},
+	))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Bake the given stamp into a new template
+	fn stamped(template: &MapTemplate, stamp: &TemplateStamp) -> MapTemplate {
+		let (width, height) = template.stamped_size(stamp);
+		let mut result = MapTemplate::new(width, height);
+		for (pos, tile) in template.iter_stamped(stamp) {
+			assert!(
+				pos.0 < width && pos.1 < height,
+				"{:?} is out of bounds",
+				pos
+			);
+			result.set(pos, Some(tile.clone()));
+		}
+		result
+	}
+
+	#[test]
+	fn rotating_four_times_is_the_identity() {
+		let template: MapTemplate = "ab.\ncde".parse().unwrap();
+		let turn = TemplateStamp::new().rotated(1);
+
+		let mut rotated = template.clone();
+		for _ in 0..4 {
+			rotated = stamped(&rotated, &turn);
+		}
+		assert_eq!(rotated, template);
+		assert_eq!(TemplateStamp::new().rotated(3).rotated(1).rotation, 0);
+	}
+
+	#[test]
+	fn rotation_turns_clockwise_after_mirroring() {
+		let template: MapTemplate = "ab.\ncde".parse().unwrap();
+
+		// The left column becomes the top row
+		assert_eq!(
+			stamped(&template, &TemplateStamp::new().rotated(1)),
+			"ca\ndb\ne.".parse().unwrap()
+		);
+		assert_eq!(
+			stamped(&template, &TemplateStamp::new().mirrored_x().rotated(1)),
+			"e.\ndb\nca".parse().unwrap()
+		);
+	}
+}
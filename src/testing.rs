@@ -0,0 +1,132 @@
+//! Helpers for tests that need a real map and tileset
+
+use bevy::asset::AssetPlugin;
+use bevy::ecs::system::SystemState;
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy_ecs_tilemap::prelude::*;
+use bevy_tileset::prelude::{
+	TileHandle, TileId, TilesetBuilder, TilesetId, TilesetPlugin, Tilesets,
+};
+
+use crate::placement::TilePlacer;
+use crate::plugin::TilesetMapPlugin;
+
+/// The ID of the tileset added by [`add_tileset`]
+pub(crate) const TILESET_ID: TilesetId = 1;
+
+/// The size (in tiles) of the layer spawned by [`spawn_map`]
+pub(crate) const MAP_SIZE: u32 = 8;
+
+/// Create an app running the [`TilesetMapPlugin`] without rendering anything
+pub(crate) fn app() -> App {
+	let mut app = App::new();
+	app.add_plugins(MinimalPlugins)
+		.add_plugin(AssetPlugin)
+		.add_asset::<Image>()
+		.add_asset::<TextureAtlas>()
+		.add_asset::<Mesh>()
+		.add_plugin(TilesetPlugin::default())
+		.add_plugin(TilesetMapPlugin::default());
+	app
+}
+
+/// Add a single-pixel texture of the given color
+pub(crate) fn texture(app: &mut App, color: [u8; 4]) -> Handle<Image> {
+	let image = Image::new_fill(
+		Extent3d {
+			width: 1,
+			height: 1,
+			depth_or_array_layers: 1,
+		},
+		TextureDimension::D2,
+		&color,
+		TextureFormat::Rgba8UnormSrgb,
+	);
+	app.world.resource_mut::<Assets<Image>>().add(image)
+}
+
+/// Add a tileset (with the ID [`TILESET_ID`]) made up of the given tiles, each in its own group
+pub(crate) fn add_tileset(app: &mut App, tiles: Vec<TileHandle>) {
+	let mut builder = TilesetBuilder::default();
+	let tileset = app
+		.world
+		.resource_scope(|world, mut images: Mut<Assets<Image>>| {
+			for (group_id, tile) in tiles.into_iter().enumerate() {
+				builder.add_tile(tile, group_id as u32, &*images).unwrap();
+			}
+			builder
+				.build("Test Tileset", TILESET_ID, &mut *images)
+				.unwrap()
+				.into_asset(&mut world.resource_mut::<Assets<TextureAtlas>>())
+		});
+	let handle = app
+		.world
+		.resource_mut::<Assets<bevy_tileset::prelude::Tileset>>()
+		.add(tileset);
+	// Keep the tileset alive
+	app.insert_resource(handle);
+
+	// Let the tileset be registered
+	app.update();
+	app.update();
+}
+
+/// Spawn map 0 containing a single (empty) layer 0 using the texture of the [`add_tileset`] tileset
+pub(crate) fn spawn_map(app: &mut App) {
+	let mut state = SystemState::<Tilesets>::new(&mut app.world);
+	let tilesets = state.get_mut(&mut app.world);
+	let tileset = tilesets.get_by_id(&TILESET_ID).unwrap();
+	let (texture, size, tile_size) = (
+		tileset.texture().clone(),
+		tileset.size(),
+		tileset.tile_size(),
+	);
+	let settings = LayerSettings::new(
+		MapSize(1, 1),
+		ChunkSize(MAP_SIZE, MAP_SIZE),
+		TileSize(tile_size.x, tile_size.y),
+		TextureSize(size.x, size.y),
+	);
+
+	let mut state = SystemState::<(Commands, MapQuery)>::new(&mut app.world);
+	let (mut commands, mut map_query) = state.get_mut(&mut app.world);
+	let map_entity = commands.spawn().id();
+	let mut map = Map::new(0u16, map_entity);
+	let (builder, layer_entity) =
+		LayerBuilder::<TileBundle>::new(&mut commands, settings, 0u16, 0u16);
+	map_query.build_layer(&mut commands, builder, texture);
+	map.add_layer(&mut commands, 0u16, layer_entity);
+	commands
+		.entity(map_entity)
+		.insert(map)
+		.insert(Transform::default())
+		.insert(GlobalTransform::default());
+	state.apply(&mut app.world);
+}
+
+/// Run the given closure with a [`TilePlacer`], applying its commands afterwards
+pub(crate) fn with_placer<R>(app: &mut App, f: impl FnOnce(&mut TilePlacer) -> R) -> R {
+	let mut state = SystemState::<TilePlacer>::new(&mut app.world);
+	let result = f(&mut state.get_mut(&mut app.world));
+	state.apply(&mut app.world);
+	result
+}
+
+/// Place the given tile (in map 0, layer 0) and run a frame
+pub(crate) fn place(app: &mut App, tile_id: TileId, pos: TilePos) {
+	with_placer(app, |placer| {
+		placer.place(tile_id, pos, 0u16, 0u16).unwrap();
+	});
+	app.update();
+}
+
+/// Get the tile at the given position (in map 0, layer 0)
+pub(crate) fn get_tile(app: &mut App, pos: TilePos) -> Option<Tile> {
+	let mut state = SystemState::<MapQuery>::new(&mut app.world);
+	let entity = state
+		.get_mut(&mut app.world)
+		.get_tile_entity(pos, 0u16, 0u16)
+		.ok()?;
+	app.world.get::<Tile>(entity).cloned()
+}
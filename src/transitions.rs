@@ -0,0 +1,193 @@
+//! Tiles that transform into other tiles over time or when triggered
+//!
+//! A tile transitions into the tile named by the [`TRANSITION_METADATA_KEY`] key of its
+//! [`TileMetadata`](crate::prelude::TileMetadata) (within the same tileset). The transition
+//! happens after the number of seconds given by the [`TRANSITION_AFTER_METADATA_KEY`] key, or once
+//! a [`TriggerTileTransitions`] event is sent with the trigger named by the
+//! [`TRANSITION_ON_METADATA_KEY`] key — whichever comes first.
+//!
+//! Transitions are applied with [`TilePlacer::update`], so the tile keeps its entity and picks up
+//! the metadata (and therefore the next transition) of its new tile. This allows for chains such
+//! as crops growing through several stages.
+//!
+//! ```ron
+//! {
+//!   "Farm": {
+//!     "Seedling": { "transition_to": "Sprout", "transition_after": 30.0 },
+//!     "Sprout": { "transition_to": "Wheat", "transition_after": 60.0 },
+//!     "Ice": { "transition_to": "Water", "transition_on": "heat" },
+//!   }
+//! }
+//! ```
+
+use bevy::prelude::*;
+use bevy_ecs_tilemap::{TileParent, TilePos};
+use bevy_tileset::prelude::TileId;
+
+use crate::coord::TileCoord;
+use crate::placement::TilePlacer;
+
+/// The metadata key used to define the name of the tile a tile transitions into
+pub const TRANSITION_METADATA_KEY: &str = "transition_to";
+
+/// The metadata key used to define the number of seconds until a tile transitions
+pub const TRANSITION_AFTER_METADATA_KEY: &str = "transition_after";
+
+/// The metadata key used to define the name of the trigger that makes a tile transition
+pub const TRANSITION_ON_METADATA_KEY: &str = "transition_on";
+
+/// A component describing the pending transition of a tile
+///
+/// This is inserted automatically by the [`TilePlacer`] for tiles whose metadata defines a
+/// transition, but may also be inserted manually.
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct TileTransition {
+	/// The tile to transition into
+	pub target: TileId,
+	/// The number of seconds until the transition happens (if timed)
+	pub remaining: Option<f32>,
+	/// The name of the trigger that makes the transition happen (if any)
+	pub trigger: Option<String>,
+}
+
+/// An event used to fire the transitions of all tiles waiting for the given trigger
+///
+/// # Examples
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_tileset_map::prelude::TriggerTileTransitions;
+/// fn heat_wave(mut triggers: EventWriter<TriggerTileTransitions>) {
+///   // Melt all ice in map 0
+///   triggers.send(TriggerTileTransitions::new("heat").in_map(0));
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct TriggerTileTransitions {
+	/// The name of the trigger
+	pub trigger: String,
+	/// Only fire transitions in this map (if any)
+	pub map_id: Option<u16>,
+	/// Only fire the transition of the tile at this coordinate (if any)
+	pub coord: Option<TileCoord>,
+}
+
+/// An event sent whenever a tile transitions into another tile
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TileTransitionedEvent {
+	/// The tile entity
+	pub entity: Entity,
+	/// The coordinate of the tile
+	pub coord: TileCoord,
+	/// The tile it transitioned into
+	pub target: TileId,
+}
+
+impl TileTransition {
+	/// Create a transition into the given tile after the given number of seconds
+	pub fn after<Id: Into<TileId>>(target: Id, seconds: f32) -> Self {
+		Self {
+			target: target.into(),
+			remaining: Some(seconds),
+			trigger: None,
+		}
+	}
+
+	/// Create a transition into the given tile once the given trigger fires
+	pub fn on<Id: Into<TileId>, S: Into<String>>(target: Id, trigger: S) -> Self {
+		Self {
+			target: target.into(),
+			remaining: None,
+			trigger: Some(trigger.into()),
+		}
+	}
+}
+
+impl TriggerTileTransitions {
+	/// Fire the given trigger for all tiles
+	pub fn new<S: Into<String>>(trigger: S) -> Self {
+		Self {
+			trigger: trigger.into(),
+			map_id: None,
+			coord: None,
+		}
+	}
+
+	/// Only fire the trigger for tiles in the given map (builder-style)
+	pub fn in_map(mut self, map_id: u16) -> Self {
+		self.map_id = Some(map_id);
+		self
+	}
+
+	/// Only fire the trigger for the tile at the given coordinate (builder-style)
+	pub fn at(mut self, coord: TileCoord) -> Self {
+		self.coord = Some(coord);
+		self
+	}
+
+	/// Returns true if this event fires the given transition of the tile at the given coordinate
+	fn fires(&self, transition: &TileTransition, coord: &TileCoord) -> bool {
+		transition.trigger.as_deref() == Some(self.trigger.as_str())
+			&& self.map_id.is_none_or(|map_id| map_id == coord.map_id)
+			&& self.coord.is_none_or(|target| target == *coord)
+	}
+}
+
+/// __\[SYSTEM\]__ Counts down timed transitions and applies all transitions that are due
+pub(crate) fn update_tile_transitions(
+	time: Res<Time>,
+	mut triggers: EventReader<TriggerTileTransitions>,
+	mut tiles: Query<(Entity, &TilePos, &TileParent, &mut TileTransition)>,
+	mut placer: TilePlacer,
+	mut transitioned: EventWriter<TileTransitionedEvent>,
+) {
+	let triggers = triggers.iter().collect::<Vec<_>>();
+	let delta = time.delta_seconds();
+
+	let mut due = Vec::new();
+	for (entity, pos, parent, mut transition) in tiles.iter_mut() {
+		let coord = TileCoord::new(*pos, parent.map_id, parent.layer_id);
+		let mut is_due = triggers
+			.iter()
+			.any(|trigger| trigger.fires(&transition, &coord));
+
+		if let Some(remaining) = transition.remaining.as_mut() {
+			*remaining -= delta;
+			is_due |= *remaining <= 0.0;
+		}
+
+		if is_due {
+			due.push((entity, coord, transition.target));
+		}
+	}
+
+	if due.is_empty() {
+		return;
+	}
+
+	placer.begin_batch();
+	for (entity, coord, target) in due {
+		// Failed transitions are cancelled so they aren't retried every frame
+		if let Err(err) = placer.check_writable(coord.map_id, coord.layer_id) {
+			warn!("Could not transition tile {:?}: {}", coord, err);
+			placer.cancel_tile_transition(entity);
+			continue;
+		}
+
+		match placer.update(target, entity) {
+			Ok(()) => {
+				placer.notify_chunk(coord.pos, coord.map_id, coord.layer_id);
+				transitioned.send(TileTransitionedEvent {
+					entity,
+					coord,
+					target,
+				});
+			},
+			Err(err) => {
+				warn!("Could not transition tile {:?}: {}", coord, err);
+				placer.cancel_tile_transition(entity);
+			},
+		}
+	}
+	placer.end_batch();
+}
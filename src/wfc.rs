@@ -0,0 +1,551 @@
+//! Procedural generation using Wave Function Collapse
+//!
+//! A [`WfcModel`] learns which tiles may be placed next to each other (and how often each tile
+//! appears) from an example map. A [`WfcGenerator`] then uses it to generate new regions in which
+//! every pair of neighboring tiles also appears somewhere in the example. Examples are given as a
+//! [`LayerIr`], which can be loaded from a file or extracted from an existing map using
+//! [`TilemapIr::extract`](crate::prelude::TilemapIr::extract).
+//!
+//! Tiles are learned by their group, ignoring any variant or auto tile index. This means that
+//! Auto Tiles are generated as plain groups and resolved into the correct pieces by the auto tiler
+//! once placed, just like tiles placed by hand.
+//!
+//! # Examples
+//!
+//! ```
+//! # use bevy_ecs_tilemap::TilePos;
+//! # use bevy_tileset_map::prelude::{LayerIr, TileId, WfcGenerator, WfcModel};
+//! let (grass, water) = (TileId::new(0, 0), TileId::new(1, 0));
+//!
+//! // Two rows of grass below two rows of water: water may never be placed below grass
+//! let mut example = LayerIr::builder();
+//! for y in 0..4 {
+//!   for x in 0..2 {
+//!     example.add_tile(TilePos(x, y), if y < 2 { grass } else { water });
+//!   }
+//! }
+//! let model = WfcModel::learn(&example.build());
+//!
+//! let output = WfcGenerator::new(8, 8).with_seed(42).generate(&model).unwrap();
+//! assert_eq!(64, output.iter().count());
+//! ```
+
+use bevy::utils::HashMap;
+use bevy_ecs_tilemap::prelude::{MapId, TilePos};
+use bevy_tileset::prelude::TileId;
+use thiserror::Error;
+
+use crate::coord::TileCoord;
+use crate::ir::{IrSpawnReport, LayerIr, TilemapIr};
+use crate::placement::TilePlacer;
+
+/// The offsets of each direction: up, right, down, then left
+const DIRECTIONS: [(i32, i32); 4] = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+
+/// Errors related to Wave Function Collapse generation
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum WfcError {
+	/// The model has not learned any tiles
+	#[error("The model does not contain any tiles")]
+	EmptyModel,
+	/// A fixed tile is not part of the model
+	#[error("Fixed tile {0:?} is not part of the model")]
+	UnknownTile(TileId),
+	/// Every attempt ran into a position where no tile could be placed
+	#[error("Could not generate a consistent region after {0} attempts")]
+	Contradiction(usize),
+}
+
+/// The adjacency constraints and frequencies of a set of tiles
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WfcModel {
+	tiles: Vec<TileId>,
+	indices: HashMap<TileId, usize>,
+	weights: Vec<f64>,
+	/// Whether the tile at the second index may be placed in the given direction of the first,
+	/// stored as `[direction][first][second]`
+	allowed: [Vec<Vec<bool>>; 4],
+}
+
+/// Generates regions of tiles consistent with a [`WfcModel`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct WfcGenerator {
+	width: u32,
+	height: u32,
+	seed: u64,
+	attempts: usize,
+	fixed: HashMap<TilePos, TileId>,
+}
+
+/// A region generated by a [`WfcGenerator`]
+///
+/// Positions are relative to the bottom-left corner of the region.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WfcOutput {
+	width: u32,
+	height: u32,
+	tiles: Vec<TileId>,
+}
+
+/// The cells of a region being collapsed
+struct Wave<'a> {
+	model: &'a WfcModel,
+	width: u32,
+	height: u32,
+	/// Which tiles are still possible for each cell
+	cells: Vec<Vec<bool>>,
+	/// The number of tiles still possible for each cell
+	counts: Vec<usize>,
+}
+
+/// A small, seedable random number generator (SplitMix64)
+struct Rng(u64);
+
+impl WfcModel {
+	/// Create an empty model
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Learn the tiles of the given example layer
+	///
+	/// Tiles are considered adjacent if they share an edge. Empty positions are ignored.
+	pub fn learn(example: &LayerIr) -> Self {
+		let mut model = Self::new();
+		model.add_example(example);
+		model
+	}
+
+	/// Learn the tiles of another example layer, in addition to those already learned
+	pub fn add_example(&mut self, example: &LayerIr) {
+		let grid = example
+			.iter()
+			.map(|tile| (tile.pos, self.add_tile(tile.resolved_id())))
+			.collect::<HashMap<_, _>>();
+
+		for (pos, tile) in grid.iter() {
+			self.weights[*tile] += 1.0;
+
+			// Only look up and to the right, since `allow` records both directions
+			for (direction, (dx, dy)) in DIRECTIONS.iter().enumerate().take(2) {
+				let neighbor = TilePos((pos.0 as i32 + dx) as u32, (pos.1 as i32 + dy) as u32);
+				if let Some(neighbor) = grid.get(&neighbor) {
+					self.allow_indices(*tile, *neighbor, direction);
+				}
+			}
+		}
+	}
+
+	/// Allow `b` to be placed directly above `a` (and `a` directly below `b`)
+	pub fn allow_vertical<A: Into<TileId>, B: Into<TileId>>(&mut self, a: A, b: B) {
+		let (a, b) = (self.add_tile(a.into()), self.add_tile(b.into()));
+		self.allow_indices(a, b, 0);
+	}
+
+	/// Allow `b` to be placed directly to the right of `a` (and `a` directly to the left of `b`)
+	pub fn allow_horizontal<A: Into<TileId>, B: Into<TileId>>(&mut self, a: A, b: B) {
+		let (a, b) = (self.add_tile(a.into()), self.add_tile(b.into()));
+		self.allow_indices(a, b, 1);
+	}
+
+	/// Set how often the given tile should be chosen relative to the others
+	///
+	/// By default, this is the number of times the tile appeared in the examples.
+	pub fn set_weight<Id: Into<TileId>>(&mut self, tile_id: Id, weight: f64) {
+		let index = self.add_tile(tile_id.into());
+		self.weights[index] = weight.max(0.0);
+	}
+
+	/// The tiles known to this model
+	pub fn tiles(&self) -> &[TileId] {
+		&self.tiles
+	}
+
+	/// Returns true if the given tiles may be placed next to each other, with `b` at the given
+	/// offset from `a`
+	///
+	/// Only the four edge-sharing offsets (such as `(0, 1)` for "above") are supported.
+	pub fn is_allowed(&self, a: &TileId, b: &TileId, offset: (i32, i32)) -> bool {
+		let direction = DIRECTIONS.iter().position(|dir| *dir == offset);
+		match (direction, self.index(a), self.index(b)) {
+			(Some(direction), Some(a), Some(b)) => self.allowed[direction][a][b],
+			_ => false,
+		}
+	}
+
+	/// Returns true if this model has not learned any tiles
+	pub fn is_empty(&self) -> bool {
+		self.tiles.is_empty()
+	}
+
+	/// Get the index of the given tile, adding it to the model if needed
+	fn add_tile(&mut self, tile_id: TileId) -> usize {
+		let tile_id = Self::normalize(&tile_id);
+		if let Some(index) = self.indices.get(&tile_id) {
+			return *index;
+		}
+
+		let index = self.tiles.len();
+		self.tiles.push(tile_id);
+		self.indices.insert(tile_id, index);
+		self.weights.push(0.0);
+		for allowed in self.allowed.iter_mut() {
+			for row in allowed.iter_mut() {
+				row.push(false);
+			}
+			allowed.push(vec![false; index + 1]);
+		}
+		index
+	}
+
+	fn allow_indices(&mut self, a: usize, b: usize, direction: usize) {
+		self.allowed[direction][a][b] = true;
+		self.allowed[(direction + 2) % 4][b][a] = true;
+	}
+
+	fn index(&self, tile_id: &TileId) -> Option<usize> {
+		self.indices.get(&Self::normalize(tile_id)).copied()
+	}
+
+	/// Strip the variant and auto tile index of the given tile
+	fn normalize(tile_id: &TileId) -> TileId {
+		TileId::new(tile_id.group_id, tile_id.tileset_id)
+	}
+}
+
+impl WfcGenerator {
+	/// Create a generator for regions of the given size
+	pub fn new(width: u32, height: u32) -> Self {
+		Self {
+			width,
+			height,
+			seed: 0,
+			attempts: 10,
+			fixed: HashMap::default(),
+		}
+	}
+
+	/// Set the seed used to make random choices (builder-style)
+	pub fn with_seed(mut self, seed: u64) -> Self {
+		self.seed = seed;
+		self
+	}
+
+	/// Set how many times generation is attempted before giving up (builder-style)
+	pub fn with_attempts(mut self, attempts: usize) -> Self {
+		self.attempts = attempts.max(1);
+		self
+	}
+
+	/// Require the given tile at the given position of the region (builder-style)
+	///
+	/// This can be used to make a region fit seamlessly against its existing surroundings.
+	pub fn with_fixed<Id: Into<TileId>>(mut self, pos: TilePos, tile_id: Id) -> Self {
+		self.fixed.insert(pos, tile_id.into());
+		self
+	}
+
+	/// Generate a region consistent with the given model
+	///
+	/// # Errors
+	///
+	/// Returns [`WfcError::Contradiction`] if every attempt ran into a position where no tile
+	/// could be placed, which can happen with very restrictive models or fixed tiles.
+	///
+	pub fn generate(&self, model: &WfcModel) -> Result<WfcOutput, WfcError> {
+		if model.is_empty() {
+			return Err(WfcError::EmptyModel);
+		}
+
+		let mut rng = Rng(self.seed);
+		for _ in 0..self.attempts {
+			let mut wave = Wave::new(model, self.width, self.height);
+			if !self.apply_fixed(&mut wave)? {
+				continue;
+			}
+			if let Some(tiles) = wave.collapse(&mut rng) {
+				return Ok(WfcOutput {
+					width: self.width,
+					height: self.height,
+					tiles,
+				});
+			}
+		}
+
+		Err(WfcError::Contradiction(self.attempts))
+	}
+
+	/// Restrict the fixed positions of the given wave, returning false on a contradiction
+	fn apply_fixed(&self, wave: &mut Wave) -> Result<bool, WfcError> {
+		for (pos, tile_id) in self.fixed.iter() {
+			if pos.0 >= self.width || pos.1 >= self.height {
+				continue;
+			}
+			let tile = wave
+				.model
+				.index(tile_id)
+				.ok_or(WfcError::UnknownTile(*tile_id))?;
+			if !wave.restrict(wave.cell(pos.0, pos.1), tile) {
+				return Ok(false);
+			}
+		}
+		Ok(true)
+	}
+}
+
+impl WfcOutput {
+	/// The width of the region
+	pub fn width(&self) -> u32 {
+		self.width
+	}
+
+	/// The height of the region
+	pub fn height(&self) -> u32 {
+		self.height
+	}
+
+	/// Get the tile generated at the given position
+	pub fn get(&self, pos: TilePos) -> Option<TileId> {
+		if pos.0 >= self.width || pos.1 >= self.height {
+			return None;
+		}
+		self.tiles
+			.get((pos.1 * self.width + pos.0) as usize)
+			.copied()
+	}
+
+	/// Iterate over every generated tile
+	pub fn iter(&self) -> impl Iterator<Item = (TilePos, TileId)> + '_ {
+		let width = self.width;
+		self.tiles
+			.iter()
+			.enumerate()
+			.map(move |(i, tile)| (TilePos(i as u32 % width, i as u32 / width), *tile))
+	}
+
+	/// Convert this region into a [`TilemapIr`], placing its bottom-left corner at the given origin
+	pub fn to_ir(&self, origin: TilePos, map_id: u16, layer_id: u16) -> TilemapIr {
+		let mut builder = TilemapIr::builder();
+		for (pos, tile) in self.iter() {
+			let pos = TilePos(origin.0 + pos.0, origin.1 + pos.1);
+			builder.add_tile(map_id, layer_id, pos, tile);
+		}
+		builder.build()
+	}
+
+	/// Place this region with its bottom-left corner at the given origin
+	///
+	/// All tiles are placed in a single batch, overwriting any existing tiles.
+	pub fn place<MId: MapId>(
+		&self,
+		placer: &mut TilePlacer,
+		origin: TilePos,
+		map_id: MId,
+		layer_id: u16,
+	) -> IrSpawnReport {
+		let map_id = map_id.into();
+		let mut report = IrSpawnReport::default();
+		placer.begin_batch();
+		for (pos, tile) in self.iter() {
+			let pos = TilePos(origin.0 + pos.0, origin.1 + pos.1);
+			match placer.place(tile, pos, map_id, layer_id) {
+				Ok(_) => report.placed += 1,
+				Err(err) => report
+					.failed
+					.push((TileCoord::new(pos, map_id, layer_id), err)),
+			}
+		}
+		placer.end_batch();
+		report
+	}
+}
+
+impl<'a> Wave<'a> {
+	fn new(model: &'a WfcModel, width: u32, height: u32) -> Self {
+		let len = (width * height) as usize;
+		let tile_count = model.tiles.len();
+		Self {
+			model,
+			width,
+			height,
+			cells: vec![vec![true; tile_count]; len],
+			counts: vec![tile_count; len],
+		}
+	}
+
+	fn cell(&self, x: u32, y: u32) -> usize {
+		(y * self.width + x) as usize
+	}
+
+	/// Collapse every cell, returning the chosen tiles (or `None` on a contradiction)
+	fn collapse(&mut self, rng: &mut Rng) -> Option<Vec<TileId>> {
+		while let Some(cell) = self.lowest_entropy(rng) {
+			let tile = self.choose(cell, rng)?;
+			if !self.restrict(cell, tile) {
+				return None;
+			}
+		}
+
+		self.cells
+			.iter()
+			.map(|cell| {
+				let tile = cell.iter().position(|possible| *possible)?;
+				Some(self.model.tiles[tile])
+			})
+			.collect()
+	}
+
+	/// Find the undecided cell with the fewest possible tiles, breaking ties randomly
+	fn lowest_entropy(&self, rng: &mut Rng) -> Option<usize> {
+		self.counts
+			.iter()
+			.enumerate()
+			.filter(|(_, count)| **count > 1)
+			.map(|(cell, count)| (cell, *count as f64 + rng.next() * 0.5))
+			.min_by(|(_, a), (_, b)| a.total_cmp(b))
+			.map(|(cell, _)| cell)
+	}
+
+	/// Randomly choose one of the possible tiles of the given cell, according to their weights
+	fn choose(&self, cell: usize, rng: &mut Rng) -> Option<usize> {
+		let possible = self.cells[cell]
+			.iter()
+			.enumerate()
+			.filter(|(_, possible)| **possible)
+			.map(|(tile, _)| (tile, self.model.weights[tile].max(f64::EPSILON)))
+			.collect::<Vec<_>>();
+
+		let total: f64 = possible.iter().map(|(_, weight)| weight).sum();
+		let mut roll = rng.next() * total;
+		for (tile, weight) in possible.iter() {
+			if roll < *weight {
+				return Some(*tile);
+			}
+			roll -= weight;
+		}
+		possible.last().map(|(tile, _)| *tile)
+	}
+
+	/// Restrict the given cell to the given tile and propagate the constraints, returning false on
+	/// a contradiction
+	fn restrict(&mut self, cell: usize, tile: usize) -> bool {
+		if !self.cells[cell][tile] {
+			return false;
+		}
+		for (other, possible) in self.cells[cell].iter_mut().enumerate() {
+			*possible = other == tile;
+		}
+		self.counts[cell] = 1;
+		self.propagate(cell)
+	}
+
+	/// Remove the tiles no longer allowed by the neighbors of the given cell
+	fn propagate(&mut self, start: usize) -> bool {
+		let mut stack = vec![start];
+		while let Some(cell) = stack.pop() {
+			let (x, y) = (cell as u32 % self.width, cell as u32 / self.width);
+			for (direction, (dx, dy)) in DIRECTIONS.iter().enumerate() {
+				let (nx, ny) = (x as i64 + *dx as i64, y as i64 + *dy as i64);
+				if nx < 0 || ny < 0 || nx >= self.width as i64 || ny >= self.height as i64 {
+					continue;
+				}
+
+				let neighbor = self.cell(nx as u32, ny as u32);
+				let mut changed = false;
+				for other in 0..self.model.tiles.len() {
+					if !self.cells[neighbor][other] {
+						continue;
+					}
+
+					let supported = self.cells[cell].iter().enumerate().any(|(tile, possible)| {
+						*possible && self.model.allowed[direction][tile][other]
+					});
+					if !supported {
+						self.cells[neighbor][other] = false;
+						self.counts[neighbor] -= 1;
+						changed = true;
+					}
+				}
+
+				if self.counts[neighbor] == 0 {
+					return false;
+				}
+				if changed {
+					stack.push(neighbor);
+				}
+			}
+		}
+		true
+	}
+}
+
+impl Rng {
+	/// Get a random number in `[0.0, 1.0)`
+	fn next(&mut self) -> f64 {
+		self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+		let mut z = self.0;
+		z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+		z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+		z ^= z >> 31;
+		(z >> 11) as f64 / (1u64 << 53) as f64
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const GRASS: TileId = TileId::new(0, 0);
+	const WATER: TileId = TileId::new(1, 0);
+
+	/// A model where water may only be placed above grass, and nothing may be placed side by side
+	fn stacked_model() -> WfcModel {
+		let mut model = WfcModel::new();
+		model.allow_vertical(GRASS, WATER);
+		model
+	}
+
+	#[test]
+	fn contradicting_fixed_tiles_fail() {
+		let generator = WfcGenerator::new(1, 2)
+			.with_attempts(3)
+			.with_fixed(TilePos(0, 0), WATER)
+			.with_fixed(TilePos(0, 1), GRASS);
+		assert_eq!(
+			generator.generate(&stacked_model()),
+			Err(WfcError::Contradiction(3))
+		);
+	}
+
+	#[test]
+	fn contradictions_found_while_propagating_fail() {
+		// No tile may be placed next to another, so no row of two tiles can ever be generated
+		let generator = WfcGenerator::new(2, 1).with_seed(7).with_attempts(5);
+		assert_eq!(
+			generator.generate(&stacked_model()),
+			Err(WfcError::Contradiction(5))
+		);
+	}
+
+	#[test]
+	fn consistent_regions_respect_the_model() {
+		let model = stacked_model();
+		let output = WfcGenerator::new(1, 2)
+			.with_fixed(TilePos(0, 1), WATER)
+			.generate(&model)
+			.unwrap();
+		assert_eq!(output.get(TilePos(0, 0)), Some(GRASS));
+		assert_eq!(output.get(TilePos(0, 1)), Some(WATER));
+
+		assert_eq!(
+			WfcGenerator::new(1, 1)
+				.with_fixed(TilePos(0, 0), TileId::new(2, 0))
+				.generate(&model),
+			Err(WfcError::UnknownTile(TileId::new(2, 0)))
+		);
+		assert_eq!(
+			WfcGenerator::new(1, 1).generate(&WfcModel::new()),
+			Err(WfcError::EmptyModel)
+		);
+	}
+}